@@ -0,0 +1,81 @@
+//! Encoding and decoding cost per message
+//!
+//! ```text
+//! cargo bench --bench codec --features joint_api --target x86_64-unknown-linux-gnu
+//! ```
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use irpc::framing::{encode_message, FrameDecoder, MAX_MESSAGE_FRAME_LEN};
+use irpc::{Header, Message, Payload, SetTargetPayload};
+use std::hint::black_box;
+
+/// A small command and the largest routine message
+fn messages() -> [(&'static str, Message); 2] {
+    let header = Header { source_id: 0x0001, target_id: 0x0010, msg_id: 300 };
+    let set_target = Message {
+        header,
+        payload: Payload::SetTarget(SetTargetPayload { target_angle: 45.0, velocity_limit: 30.0 }),
+    };
+    // A zeroed sample: header, then the TelemetryStream tag
+    let mut raw = [0u8; Message::max_size()];
+    raw[..5].copy_from_slice(&[0x10, 0x01, 0xAC, 0x02, 8]);
+    let telemetry = Message::deserialize(&raw).expect("zeroed telemetry decodes");
+    [("SetTarget", set_target), ("TelemetryStream", telemetry)]
+}
+
+fn message_codec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("message");
+    for (name, message) in messages() {
+        let mut buf = [0u8; Message::max_size()];
+        let len = message.serialize_into(&mut buf).unwrap();
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_function(format!("serialize/{}", name), |b| {
+            b.iter(|| black_box(&message).serialize_into(&mut buf).unwrap())
+        });
+        let encoded = buf;
+        group.bench_function(format!("deserialize/{}", name), |b| {
+            b.iter(|| Message::deserialize(black_box(&encoded[..len])).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn stream_framing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("framing");
+    for (name, message) in messages() {
+        let mut frame = [0u8; MAX_MESSAGE_FRAME_LEN];
+        let len = encode_message(&message, &mut frame).unwrap();
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_function(format!("encode/{}", name), |b| {
+            b.iter(|| encode_message(black_box(&message), &mut frame).unwrap())
+        });
+        let encoded = frame;
+        let mut decoder = FrameDecoder::<MAX_MESSAGE_FRAME_LEN>::new();
+        group.bench_function(format!("decode/{}", name), |b| {
+            b.iter(|| encoded[..len].iter().filter_map(|&byte| decoder.push_message(black_box(byte))).count())
+        });
+    }
+    group.finish();
+}
+
+#[cfg(feature = "joint_api")]
+fn canfd_framing(c: &mut Criterion) {
+    use irpc::transport::canfd::{decode_frame, encode_frame};
+
+    let mut group = c.benchmark_group("canfd");
+    let (name, message) = messages().into_iter().next().unwrap();
+    let mut data = [0u8; 64];
+    let (id, len) = encode_frame(&message, &mut data).unwrap();
+    group.bench_function(format!("encode/{}", name), |b| {
+        b.iter(|| encode_frame(black_box(&message), &mut data).unwrap())
+    });
+    let encoded = data;
+    group.bench_function(format!("decode/{}", name), |b| b.iter(|| decode_frame(id, black_box(&encoded[..len])).unwrap()));
+    group.finish();
+}
+
+#[cfg(not(feature = "joint_api"))]
+fn canfd_framing(_: &mut Criterion) {}
+
+criterion_group!(benches, message_codec, stream_framing, canfd_framing);
+criterion_main!(benches);
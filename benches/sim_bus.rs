@@ -0,0 +1,25 @@
+//! Host round trip through the simulated bus
+//!
+//! The floor every real adapter adds its latency to: request correlation,
+//! the adapter task and one encode/decode each way.
+//!
+//! ```text
+//! cargo bench --bench sim_bus --features sim --target x86_64-unknown-linux-gnu
+//! ```
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use irpc::bus::sim::SimBus;
+use irpc::{CommunicationManager, Payload};
+use std::sync::Arc;
+
+fn round_trip(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let comm = runtime.block_on(async { CommunicationManager::with_adapter(Arc::new(SimBus::with_joints([0x0010]))) });
+
+    c.bench_function("sim_bus/RequestStatus", |b| {
+        b.iter(|| runtime.block_on(comm.send_and_wait(0x0010, Payload::RequestStatus)).unwrap())
+    });
+}
+
+criterion_group!(benches, round_trip);
+criterion_main!(benches);
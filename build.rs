@@ -0,0 +1,22 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "grpc")]
+    grpc::generate();
+}
+
+/// Generate the gRPC service from `proto/irpc.proto`
+#[cfg(feature = "grpc")]
+mod grpc {
+    pub fn generate() {
+        println!("cargo:rerun-if-changed=proto/irpc.proto");
+        // A vendored protoc unless the environment points at one
+        if std::env::var_os("PROTOC").is_none() {
+            let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+            std::env::set_var("PROTOC", protoc);
+        }
+        tonic_prost_build::configure()
+            .compile_protos(&["proto/irpc.proto"], &["proto"])
+            .expect("compile proto/irpc.proto");
+    }
+}
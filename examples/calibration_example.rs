@@ -43,9 +43,10 @@ fn main() {
     };
 
     // Serialize
-    let bytes = msg.serialize().expect("Failed to serialize");
-    println!("✅ Message serialized: {} bytes", bytes.len());
-    println!("   CAN frames needed: {}", (bytes.len() + 7) / 8);
+    let mut buf = [0u8; Message::max_size()];
+    let len = msg.serialize_into(&mut buf).expect("Failed to serialize");
+    println!("✅ Message serialized: {} bytes", len);
+    println!("   CAN frames needed: {}", (len + 7) / 8);
     println!();
 
     // Simulate status updates
@@ -95,6 +96,8 @@ fn main() {
             friction_stribeck: 0.0087,
             friction_vstribeck: 0.0953,
             friction_viscous: 0.001034,
+            thermal_resistance: 0.0,  // Thermal phase not requested
+            thermal_time_constant: 0.0,
         },
         confidence: CalibrationConfidence {
             overall: 0.91,
@@ -3,81 +3,25 @@
 //! This example demonstrates how to use the TransportLayer and Joint
 //! on an embedded microcontroller with a CAN bus.
 //!
-//! Note: This is a pseudo-code example for documentation purposes.
-//! In a real embedded project, you would compile this separately with
-//! the proper target and linker script.
+//! The firmware loops are generic over `EmbeddedTransport`; on hardware
+//! you pass your CAN/SPI/UART driver. `main` runs the same joint logic on
+//! the host over an in-memory `channel_pair()`, with the arm side played
+//! by a second `TransportLayer`.
 
 #[cfg(feature = "joint_api")]
-use irpc::{Joint, TransportLayer, EmbeddedTransport, TransportError};
-
-// ============================================================================
-// Mock CAN transport for demonstration
-// ============================================================================
-
-#[cfg(feature = "joint_api")]
-#[derive(Debug)]
-struct CanError;
-
-#[cfg(feature = "joint_api")]
-struct MockCanBus {
-    rx_buffer: [u8; 64],
-    has_data: bool,
-}
-
-#[cfg(feature = "joint_api")]
-impl MockCanBus {
-    fn new() -> Self {
-        Self {
-            rx_buffer: [0u8; 64],
-            has_data: false,
-        }
-    }
-}
-
+use irpc::transport::channel_pair;
 #[cfg(feature = "joint_api")]
-impl EmbeddedTransport for MockCanBus {
-    type Error = CanError;
-
-    fn send_blocking(&mut self, data: &[u8]) -> Result<(), Self::Error> {
-        // In real implementation: send data over CAN-FD
-        // can_fd_transmit(data);
-        Ok(())
-    }
-
-    fn receive_blocking(&mut self) -> Result<Option<&[u8]>, Self::Error> {
-        // In real implementation: check CAN FIFO
-        // if let Some(frame) = can_fd_receive() {
-        //     let len = frame.len();
-        //     self.rx_buffer[..len].copy_from_slice(frame.data());
-        //     Ok(Some(&self.rx_buffer[..len]))
-        // } else {
-        //     Ok(None)
-        // }
-
-        if self.has_data {
-            self.has_data = false;
-            Ok(Some(&self.rx_buffer[..8]))
-        } else {
-            Ok(None)
-        }
-    }
-
-    fn is_ready(&self) -> bool {
-        true
-    }
-}
+use irpc::{EmbeddedTransport, Header, Joint, Message, Payload, TransportLayer};
 
 // ============================================================================
 // Embedded firmware main loop (pseudo-code for documentation)
 // ============================================================================
 
 #[cfg(feature = "joint_api")]
-fn embedded_main_loop() -> ! {
-    // Initialize hardware (CAN, timers, etc.)
-    // ...
-
-    // Create the CAN transport
-    let can_bus = MockCanBus::new();
+#[allow(dead_code)]
+fn embedded_main_loop<T: EmbeddedTransport>(can_bus: T) -> ! {
+    // Initialize hardware (CAN, timers, etc.) and create the CAN transport
+    // before calling this
 
     // Wrap it in TransportLayer for automatic serialization
     let mut transport = TransportLayer::new(can_bus);
@@ -114,8 +58,8 @@ fn embedded_main_loop() -> ! {
 // ============================================================================
 
 #[cfg(feature = "joint_api")]
-fn manual_control_example() {
-    let can_bus = MockCanBus::new();
+#[allow(dead_code)]
+fn manual_control_example<T: EmbeddedTransport>(can_bus: T) -> ! {
     let mut transport = TransportLayer::new(can_bus);
     let mut joint = Joint::new(0x0010);
 
@@ -144,27 +88,46 @@ fn main() {
     println!("iRPC Embedded Joint Example");
     println!("===========================");
     println!();
-    println!("This example demonstrates how to use iRPC on embedded firmware.");
-    println!();
-    println!("In a real embedded application, you would:");
-    println!("1. Initialize your CAN/SPI/UART transport");
-    println!("2. Implement the EmbeddedTransport trait for your hardware");
-    println!("3. Create a TransportLayer wrapping your transport");
-    println!("4. Create a Joint with your device ID");
-    println!("5. Call joint.process_transport() in your main loop");
-    println!();
-    println!("The TransportLayer automatically handles:");
-    println!("- Message serialization (encoding to bytes)");
-    println!("- Message deserialization (decoding from bytes)");
-    println!("- Buffer management");
+
+    // Two connected in-memory ends: one for the arm, one for the joint
+    let (arm_end, joint_end) = channel_pair();
+    let mut arm = TransportLayer::new(arm_end);
+    let mut bus = TransportLayer::new(joint_end);
+    let mut joint = Joint::new(0x0010);
+
+    let commands = [
+        Payload::Configure,
+        Payload::Activate,
+        Payload::SetTarget(irpc::SetTargetPayload {
+            target_angle: 45.0,
+            velocity_limit: 90.0,
+        }),
+        Payload::Deactivate,
+    ];
+
+    for (msg_id, payload) in commands.into_iter().enumerate() {
+        let request = Message {
+            header: Header {
+                source_id: 0x0001,
+                target_id: 0x0010,
+                msg_id: msg_id as u32,
+            },
+            payload,
+        };
+        println!("arm   -> {:?}", request.payload);
+        arm.send_message(&request).expect("send failed");
+
+        // One iteration of the firmware main loop
+        joint.process_transport(&mut bus).expect("joint transport error");
+
+        if let Ok(Some(reply)) = arm.receive_message() {
+            println!("joint <- {:?} (state: {:?})", reply.payload, joint.state());
+        }
+    }
+
     println!();
-    println!("Example usage:");
-    println!("  let mut transport = TransportLayer::new(my_can_bus);");
-    println!("  let mut joint = Joint::new(0x0010);");
-    println!("  ");
-    println!("  loop {{");
-    println!("      joint.process_transport(&mut transport)?;");
-    println!("  }}");
+    println!("On hardware, pass your EmbeddedTransport implementation to");
+    println!("embedded_main_loop() instead of the in-memory channel.");
 }
 
 #[cfg(not(feature = "joint_api"))]
@@ -0,0 +1,68 @@
+//! Gateway exposing a bus of joints to remote workstations over UDP
+//!
+//! The robot side here is a simulated bus; on a real robot it would be the
+//! CAN adapter. Workstations connect with
+//! `NetworkAdapter::udp(any_local_addr, Some(gateway_addr))`, or find the
+//! gateway through its beacon with `discover_peers()`.
+//!
+//! ```text
+//! cargo run --example gateway --features sim -- 0.0.0.0:47101 0x10 0x20
+//! ```
+//!
+//! Arguments: the UDP address to listen on, then the joint IDs to expose
+//! (all joints if none are given).
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+use {
+    irpc::bus::gateway::{DeviceFilter, Gateway},
+    irpc::bus::net::{spawn_beacon, Beacon, NetTransport, NetworkAdapter},
+    irpc::bus::sim::SimBus,
+    irpc::{NET_BEACON_INTERVAL_MS, NET_BEACON_PORT},
+    std::net::SocketAddr,
+    std::sync::Arc,
+    std::time::Duration,
+};
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
+
+    let mut args = std::env::args().skip(1);
+    let listen: SocketAddr = args.next().as_deref().unwrap_or("0.0.0.0:47101").parse()?;
+    let exposed = args
+        .map(|id| u16::from_str_radix(id.trim_start_matches("0x"), 16))
+        .collect::<Result<Vec<_>, _>>()?;
+    let filter = if exposed.is_empty() { DeviceFilter::All } else { DeviceFilter::Only(exposed) };
+
+    // Robot side
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0020, 0x0030]));
+
+    // Workstation side, announced on the local network
+    let uplink = Arc::new(NetworkAdapter::udp(listen, None).await?);
+    let port = uplink.local_addr().map_or(listen.port(), |addr| addr.port());
+    let _beacon = spawn_beacon(
+        ([255, 255, 255, 255], NET_BEACON_PORT).into(),
+        Beacon { transport: NetTransport::Udp, port },
+        Duration::from_millis(NET_BEACON_INTERVAL_MS),
+    );
+
+    let gateway = Gateway::spawn(bus, uplink, filter.clone());
+    println!("Gateway on udp/{} exposing {:?}; Ctrl-C to stop", port, filter);
+
+    let mut report = tokio::time::interval(Duration::from_secs(5));
+    loop {
+        tokio::select! {
+            _ = report.tick() => println!("{:?}", gateway.stats()),
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    println!("Final: {:?}", gateway.stats());
+    Ok(())
+}
+
+#[cfg(not(all(feature = "arm_api", feature = "joint_api")))]
+fn main() {
+    println!("This example requires the 'arm_api' and 'joint_api' features to be enabled.");
+    println!("Run with: cargo run --example gateway --features sim");
+}
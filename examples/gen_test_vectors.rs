@@ -0,0 +1,25 @@
+//! Write the protocol conformance test vectors
+//!
+//! Emits one canonical encoded message per payload variant, for firmware
+//! written in other languages to check its encoder and decoder against (see
+//! `irpc::vectors` for the layout).
+//!
+//! ```text
+//! cargo run --example gen_test_vectors --features arm_api -- tests/vectors
+//! ```
+//!
+//! Argument: the directory to write (`test_vectors` by default).
+
+#[cfg(feature = "arm_api")]
+fn main() -> Result<(), irpc::vectors::VectorError> {
+    let dir = std::env::args().nth(1).unwrap_or_else(|| "test_vectors".to_string());
+    let vectors = irpc::vectors::write_vectors(&dir)?;
+    println!("{} vectors written to {} (schema {:#010x})", vectors.len(), dir, irpc::SCHEMA_HASH);
+    Ok(())
+}
+
+#[cfg(not(feature = "arm_api"))]
+fn main() {
+    println!("This example requires the 'arm_api' feature to be enabled.");
+    println!("Run with: cargo run --example gen_test_vectors --features arm_api -- <directory>");
+}
@@ -0,0 +1,52 @@
+//! Run the iRPC conformance suite against a joint on the bench
+//!
+//! The joint is reached through a TCP gateway in front of its bus.
+//!
+//! ```text
+//! cargo run --example hil_conformance --features hil -- 10.0.0.2:47101 0x10 200
+//! ```
+//!
+//! Arguments: the gateway address, the hex ID of the joint under test, and
+//! optionally the telemetry rate to check in Hz (100 by default). Exits
+//! non-zero unless every check passed.
+
+#[cfg(feature = "hil")]
+use {
+    irpc::bus::net::NetworkAdapter,
+    irpc::hil::{HilConfig, HilSuite},
+    irpc::{CommunicationAdapter, CommunicationManager},
+    std::sync::Arc,
+    std::time::Duration,
+};
+
+#[cfg(feature = "hil")]
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
+
+    let mut args = std::env::args().skip(1);
+    let address = args.next().unwrap_or_else(|| "127.0.0.1:47101".to_string());
+    let joint_id = u16::from_str_radix(args.next().as_deref().unwrap_or("0x10").trim_start_matches("0x"), 16)?;
+    let mut config = HilConfig::new(joint_id);
+    if let Some(rate_hz) = args.next() {
+        config.telemetry_rate_hz = rate_hz.parse()?;
+    }
+
+    let adapter = Arc::new(NetworkAdapter::tcp_connect(address.parse()?));
+    while !adapter.is_connected() {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let comm = CommunicationManager::with_adapter(adapter);
+    let report = HilSuite::new(comm, config).run().await;
+    println!("{}", report);
+    if !report.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "hil"))]
+fn main() {
+    println!("This example requires the 'hil' feature to be enabled.");
+    println!("Run with: cargo run --example hil_conformance --features hil -- <address> <joint id> [telemetry Hz]");
+}
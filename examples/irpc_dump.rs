@@ -0,0 +1,60 @@
+//! Print iRPC traffic, one line per message
+//!
+//! Dumps a live bus reached through a TCP gateway, or the received messages
+//! of a recording.
+//!
+//! ```text
+//! cargo run --example irpc_dump --features arm_api -- 10.0.0.2:47101 0x10 TelemetryStream
+//! cargo run --example irpc_dump --features arm_api -- session.irpclog Nack JointStatus
+//! ```
+//!
+//! Arguments: the gateway address or a `.irpclog` file, then any number of
+//! filters: hex device IDs (`0x10`) and payload kinds (`SetTarget`). With
+//! several of one sort, a message needs to match only one of them.
+
+#[cfg(feature = "arm_api")]
+use {
+    irpc::bus::net::NetworkAdapter,
+    irpc::bus::record::TelemetryPlayer,
+    irpc::bus::sniffer::{Sniffer, SnifferFilter},
+    std::sync::Arc,
+};
+
+#[cfg(feature = "arm_api")]
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let source = args.next().unwrap_or_else(|| "127.0.0.1:47101".to_string());
+    let mut filter = SnifferFilter::new();
+    for arg in args {
+        filter = match arg.strip_prefix("0x") {
+            Some(id) => filter.device(u16::from_str_radix(id, 16)?),
+            None => filter.kind(arg),
+        };
+    }
+
+    if source.ends_with(".irpclog") {
+        let sniffer = Sniffer::new(Arc::new(TelemetryPlayer::open(&source)?.unpaced())).with_filter(filter);
+        while let Some(record) = sniffer.try_next().await? {
+            println!("{}", record);
+        }
+        eprintln!("{} messages", sniffer.seen());
+        return Ok(());
+    }
+
+    let sniffer = Sniffer::new(Arc::new(NetworkAdapter::tcp_connect(source.parse()?))).with_filter(filter);
+    loop {
+        tokio::select! {
+            record = sniffer.next() => println!("{}", record?),
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    eprintln!("{} messages", sniffer.seen());
+    Ok(())
+}
+
+#[cfg(not(feature = "arm_api"))]
+fn main() {
+    println!("This example requires the 'arm_api' feature to be enabled.");
+    println!("Run with: cargo run --example irpc_dump --features arm_api -- <address|file.irpclog> [filters]");
+}
@@ -38,12 +38,16 @@
 
 #[cfg(feature = "stm32g4")]
 use {
+    core::mem::MaybeUninit,
     defmt_rtt as _,
-    panic_probe as _,
     embassy_executor::Spawner,
     embassy_stm32::{self as _, bind_interrupts, can, peripherals, Config},
-    embassy_time::Timer,
-    irpc::{Joint, transport::{CanFdConfig, CanFdTransport}},
+    irpc::{
+        run_embassy, AsyncTransportLayer, CrashKind, CrashRecord, Joint, JointHooks,
+        LifecycleState, Payload, TransportError,
+        crash::{panic_record, CrashSlot},
+        transport::{Addressing, CanFdConfig, CanFdTransport},
+    },
 };
 
 #[cfg(feature = "stm32g4")]
@@ -52,9 +56,73 @@ bind_interrupts!(struct Irqs {
     FDCAN1_IT1 => can::IT1InterruptHandler<peripherals::FDCAN1>;
 });
 
+/// Crash record kept across resets (the `.uninit` section is not zeroed at boot)
+#[cfg(feature = "stm32g4")]
+#[link_section = ".uninit.irpc_crash"]
+static mut CRASH: MaybeUninit<CrashSlot> = MaybeUninit::uninit();
+
+#[cfg(feature = "stm32g4")]
+fn crash_slot() -> &'static mut CrashSlot {
+    // SAFETY: only touched by the fault handlers and once at startup, never concurrently
+    unsafe { CrashSlot::from_noinit(&mut *core::ptr::addr_of_mut!(CRASH)) }
+}
+
+/// Record the panic, then reset so the joint comes back and reports it
+#[cfg(feature = "stm32g4")]
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    crash_slot().store(&panic_record(info, "joint"));
+    cortex_m::peripheral::SCB::sys_reset()
+}
+
+#[cfg(feature = "stm32g4")]
+#[cortex_m_rt::exception]
+unsafe fn HardFault(frame: &cortex_m_rt::ExceptionFrame) -> ! {
+    crash_slot().store(&CrashRecord::new(CrashKind::HardFault, frame.pc(), frame.lr(), "", "HardFault"));
+    cortex_m::peripheral::SCB::sys_reset()
+}
+
+/// Firmware-specific work run by the iRPC joint task
+#[cfg(feature = "stm32g4")]
+struct Firmware;
+
+#[cfg(feature = "stm32g4")]
+impl JointHooks for Firmware {
+    fn update(&mut self, _joint: &mut Joint, _now_us: u64) {
+        // Read encoder, run the current/velocity/position loops, update PWM
+    }
+
+    fn telemetry(&mut self, joint: &Joint) -> Option<Payload> {
+        // Report status at 100 Hz while the joint is enabled
+        (joint.state() == LifecycleState::Active).then_some(Payload::JointStatus {
+            state: joint.state(),
+            error_code: 0,
+        })
+    }
+
+    fn feed_watchdog(&mut self) {
+        // iwdg.pet();
+    }
+
+    fn on_transport_error<E: core::fmt::Debug>(&mut self, error: &TransportError<E>) {
+        defmt::error!("❌ CAN-FD: {:?}", defmt::Debug2Format(error));
+    }
+
+    fn crash_record(&mut self) -> Option<CrashRecord> {
+        // Reported in the boot announcement; the host raises `JointCrashed`
+        crash_slot().take()
+    }
+}
+
+#[cfg(feature = "stm32g4")]
+#[embassy_executor::task]
+async fn joint_task(joint: Joint, transport: CanFdTransport<'static>) -> ! {
+    run_embassy(joint, AsyncTransportLayer::new(transport), Firmware).await
+}
+
 #[cfg(feature = "stm32g4")]
 #[embassy_executor::main]
-async fn main(_spawner: Spawner) {
+async fn main(spawner: Spawner) {
     // 1. Initialize embassy and clocks
     let mut config = Config::default();
     {
@@ -83,11 +151,12 @@ async fn main(_spawner: Spawner) {
         node_id: 0x0010,
         nominal_bitrate: 1_000_000,  // 1 Mbps
         data_bitrate: 5_000_000,     // 5 Mbps
+        addressing: Addressing::Native,
     };
 
     // 3. Create Joint + Transport in one call
     //    iRPC handles ALL hardware configuration internally!
-    let (mut joint, mut transport) = Joint::with_canfd(
+    let (joint, transport) = Joint::with_canfd(
         0x0010,
         p.FDCAN1,
         p.PA11,  // RX
@@ -98,35 +167,9 @@ async fn main(_spawner: Spawner) {
 
     defmt::info!("✅ Joint 0x{:04X} ready with CAN-FD transport", joint.id());
 
-    // 4. Main control loop (EXTREMELY SIMPLE)
-    loop {
-        // Wait for incoming messages
-        match transport.receive_message().await {
-            Ok(msg) => {
-                defmt::debug!("📨 RX: {:?}", msg.payload);
-
-                // Process through state machine
-                if let Some(response) = joint.handle_message(&msg) {
-                    defmt::debug!("📤 TX: {:?}", response.payload);
-
-                    // Send response
-                    if let Err(e) = transport.send_message(&response).await {
-                        defmt::error!("❌ TX failed: {:?}", e);
-                    }
-                }
-            }
-            Err(e) => {
-                defmt::error!("❌ RX failed: {:?}", e);
-                Timer::after_millis(10).await;
-            }
-        }
-
-        // Other firmware tasks can go here:
-        // - Read encoder
-        // - Update motor control
-        // - Send telemetry
-        // - etc.
-    }
+    // 4. Hand everything to the ready-made joint task: commands, control
+    //    ticks, telemetry and watchdog are all driven by run_embassy()
+    spawner.spawn(joint_task(joint, transport)).unwrap();
 }
 
 // ============================================================================
@@ -139,7 +182,7 @@ async fn main(_spawner: Spawner) {
 // - TOTAL: ~250 lines of low-level code
 //
 // NEW approach (iRPC provides transport):
-// - main.rs: ~30 lines (config + loop)
+// - main.rs: ~30 lines (config + hooks; run_embassy() is the loop)
 // - TOTAL: ~30 lines
 //
 // 🎯 Result: 8x less code in firmware!
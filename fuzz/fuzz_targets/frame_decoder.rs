@@ -0,0 +1,21 @@
+//! Arbitrary bytes as received frames
+//!
+//! A CAN-FD frame (the first four bytes as arbitration ID) and a byte
+//! stream through the COBS framing must decode or fail, never panic.
+
+#![no_main]
+
+use irpc::framing::{FrameDecoder, MAX_MESSAGE_FRAME_LEN};
+use irpc::transport::canfd::decode_frame;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Some((id, body)) = data.split_first_chunk::<4>() {
+        let _ = decode_frame(u32::from_le_bytes(*id), body);
+    }
+
+    let mut decoder = FrameDecoder::<MAX_MESSAGE_FRAME_LEN>::new();
+    for &byte in data {
+        let _ = decoder.push_message(byte);
+    }
+});
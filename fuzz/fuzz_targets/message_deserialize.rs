@@ -0,0 +1,35 @@
+//! Arbitrary bytes as a received message body
+//!
+//! Decoding must either fail or yield a message that re-encodes to a
+//! decodable message, and a joint must handle whatever decodes without
+//! panicking.
+
+#![no_main]
+
+use irpc::{Header, Joint, Message, MultiTurnPosition, Payload};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(message) = Message::deserialize(data) else { return };
+
+    let mut buf = [0u8; Message::max_size()];
+    let len = message.serialize_into(&mut buf).expect("decoded message re-encodes");
+    let again = Message::deserialize(&buf[..len]).expect("re-encoded message decodes");
+    let mut again_buf = [0u8; Message::max_size()];
+    let again_len = again.serialize_into(&mut again_buf).expect("decoded message re-encodes");
+    assert_eq!(buf[..len], again_buf[..again_len]);
+
+    // Fresh, and Active with a known position
+    let mut joint = Joint::new(message.header.target_id);
+    joint.handle_message(&message);
+    let mut joint = Joint::new(message.header.target_id);
+    joint.update_encoder(MultiTurnPosition::from_degrees(0.0));
+    for (msg_id, payload) in [(1, Payload::Configure), (2, Payload::Activate)] {
+        let header = Header { source_id: 0x0001, target_id: joint.id(), msg_id };
+        joint.handle_message(&Message { header, payload });
+    }
+    joint.handle_message(&message);
+    for now_us in [0, 1_000, 1_000_000] {
+        joint.poll(now_us);
+    }
+});
@@ -3,22 +3,509 @@
 //! This module provides functionality for standard host environments
 //! with access to std library features, async runtime, and logging.
 
-use crate::protocol::{Message, ProtocolError, DeviceId, MessageId, Payload, Header, LifecycleState, SetTargetPayload};
+use crate::protocol::{Message, ProtocolError, DeviceId, MessageId, Payload, Header, LifecycleState, AssignIdPayload, BootInfoPayload, Capabilities, ControlGains, ControlMode, DigitalIoPayload, GainScheduleEntry, HomingConfig, MotionProfile, MultiTurnPosition, BootMode, SetTargetPayload, SetTargetPayloadV2, TransportStats, JointLimits, CrashRecord, InterlockStatePayload, ConfigureAdaptivePayload, ConfigureTelemetryPayload, CalibrationRequest, CalibrationStatus, CalibrationResult, FlightRecord, ThermalLimits, PowerLimits, PowerStatusPayload, JointStatistics, SelfTestRequest, SelfTestResult, SyncSample, TargetBatch, SCHEMA_HASH};
+use crate::bus::{CommunicationAdapter, DeviceInfo};
+#[cfg(feature = "arm_api")]
+use crate::bus::record::{Direction, LogRecord};
+use crate::clock::{Clock, SystemClock};
+use crate::compat::{self, PayloadGeneration};
+use crate::config::{
+    ADAPTER_POLL_INTERVAL_MS, ARM_DEVICE_ID, BROADCAST_ADDRESS, CANFD_MAX_DATA_LEN, DISCOVERY_WINDOW_MS,
+    ERROR_POSITION_UNKNOWN, ERROR_UNKNOWN_COMMAND, FLIGHT_RECORDS_PER_CHUNK, HOMING_POLL_INTERVAL_MS,
+    JOG_MAX_JOINT_VELOCITY_DPS, JOG_UPDATE_RATE_HZ, OUTBOUND_QUEUE_DEPTH, PENDING_SWEEP_INTERVAL_MS,
+    RECEIVE_BATCH_LEN, SAFETY_HEARTBEATS_PER_TTL, SHUTDOWN_DRAIN_TIMEOUT_MS, STREAM_MSG_ID_FLAG, TELEMETRY_SUBSCRIBER_QUEUE_DEPTH, UNADDRESSED_DEVICE_ID,
+};
+use crate::units::{DegPerSec, Degrees};
+#[cfg(feature = "arm_api")]
+use crate::trajectory::{Trajectory, TrajectoryError};
+#[cfg(feature = "arm_api")]
+use crate::schedule::Schedule;
+#[cfg(feature = "arm_api")]
+use crate::kinematics::{ArmModel, CartesianAxis, KinematicsError};
+#[cfg(feature = "arm_api")]
+use crate::sequence::{MotionSequence, SequenceError, SequenceHandle};
+#[cfg(feature = "arm_api")]
+use crate::group::{GroupError, JointGroup};
+#[cfg(feature = "arm_api")]
+use crate::startup::{StartupError, StartupPlan, StartupStep};
+#[cfg(feature = "arm_api")]
+use crate::arm_config::{ArmConfig, ArmConfigError, JointEntry};
+#[cfg(feature = "arm_api")]
+use crate::rate_limit::{RateLimiter, RateLimits};
+#[cfg(feature = "arm_api")]
+use crate::dispatch::{HandlerId, Handlers, UnsolicitedPayload};
+#[cfg(feature = "arm_api")]
+use crate::latency::LatencyHistogram;
+#[cfg(feature = "arm_api")]
+use crate::safety::{Intervention, SafetyLimits, SafetySupervisor};
+
+#[cfg(feature = "arm_api")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "arm_api")]
+use tokio::sync::{broadcast, mpsc, oneshot, MutexGuard, RwLock};
+
+#[cfg(feature = "arm_api")]
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "arm_api")]
+use tracing::{debug, error, info, info_span, instrument, warn, Instrument, Span};
+
+#[cfg(feature = "arm_api")]
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+#[cfg(feature = "arm_api")]
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+
+#[cfg(feature = "arm_api")]
+use std::sync::{Arc, Mutex, Weak};
+
+/// Collects discovery replies, tolerating duplicates
+///
+/// Joints may answer more than once (retransmission, a second Discover
+/// overlapping the first), so replies are keyed by source ID and the
+/// latest one wins.
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Default)]
+pub struct DiscoveryCollector {
+    devices: HashMap<DeviceId, DeviceInfo>,
+    duplicates: u32,
+}
+
+#[cfg(feature = "arm_api")]
+impl DiscoveryCollector {
+    /// Create an empty collector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Offer an incoming message; returns true if it was a discovery reply
+    pub fn offer(&mut self, message: &Message) -> bool {
+        let Payload::Hello(hello) = &message.payload else {
+            return false;
+        };
+
+        let info = DeviceInfo {
+            id: message.header.source_id,
+            entity_type: hello.entity_type,
+            mode: hello.mode,
+            capabilities: hello.capabilities,
+            schema: hello.schema,
+        };
+        if self.devices.insert(info.id, info).is_some() {
+            self.duplicates += 1;
+            debug!("Duplicate discovery reply from device {}", message.header.source_id);
+        }
+        true
+    }
+
+    /// Number of duplicate replies seen so far
+    pub fn duplicates(&self) -> u32 {
+        self.duplicates
+    }
+
+    /// Finish collection, returning the unique devices sorted by ID
+    pub fn into_devices(self) -> Vec<DeviceInfo> {
+        let mut devices: Vec<DeviceInfo> = self.devices.into_values().collect();
+        devices.sort_by_key(|d| d.id);
+        devices
+    }
+}
+
+/// One step of a deterministic-mode trace
+///
+/// Messages are stored encoded so two traces can be compared byte for byte.
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// A message was queued for transmission
+    Sent(Vec<u8>),
+    /// A message was handed to `process_incoming`
+    Received(Vec<u8>),
+    /// A request timed out waiting for its response
+    Timeout(MessageId),
+}
+
+/// State kept only in deterministic mode
+#[cfg(feature = "arm_api")]
+struct Determinism {
+    outbound_rx: Mutex<mpsc::Receiver<Message>>,
+    trace: Mutex<Vec<TraceEvent>>,
+}
+
+/// The manager's flight recorder: the latest messages through it
+#[cfg(feature = "arm_api")]
+struct FlightLog {
+    capacity: usize,
+    records: VecDeque<LogRecord>,
+}
+
+/// Kind of telemetry a joint publishes
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TelemetryTopic {
+    /// `Payload::TelemetryStream` motion and FOC samples
+    Motion,
+    /// `Payload::AdaptiveStatus` reports
+    Adaptive,
+    /// `Payload::JointStatus` lifecycle and error updates
+    Status,
+}
+
+#[cfg(feature = "arm_api")]
+impl TelemetryTopic {
+    /// Topic a payload is published on, if it is telemetry
+    ///
+    /// v1 `Encoder` telemetry is published as `TelemetryStream`.
+    pub fn of(payload: &Payload) -> Option<Self> {
+        match payload {
+            Payload::TelemetryStream(_) | Payload::Encoder(_) => Some(Self::Motion),
+            Payload::AdaptiveStatus(_) => Some(Self::Adaptive),
+            Payload::JointStatus { .. } => Some(Self::Status),
+            _ => None,
+        }
+    }
+}
+
+/// One telemetry message delivered to a subscriber
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone)]
+pub struct TelemetrySample {
+    /// Joint that sent the message
+    pub joint_id: DeviceId,
+    /// The joint's name when the message arrived, if it has one
+    pub joint_name: Option<Arc<str>>,
+    /// Stream the message belongs to
+    pub topic: TelemetryTopic,
+    /// The message as received
+    pub message: Message,
+    /// When the message arrived, on the communication manager's clock
+    pub received_at: std::time::Duration,
+    /// Replayed from the cache on subscribe rather than received live
+    pub snapshot: bool,
+}
+
+#[cfg(feature = "arm_api")]
+impl TelemetrySample {
+    /// Joint position, for motion samples
+    pub fn position(&self) -> Option<Degrees> {
+        match &self.message.payload {
+            Payload::TelemetryStream(stream) => Some(stream.position()),
+            _ => None,
+        }
+    }
+
+    /// Joint velocity, for motion samples
+    pub fn velocity(&self) -> Option<DegPerSec> {
+        match &self.message.payload {
+            Payload::TelemetryStream(stream) => Some(stream.velocity()),
+            _ => None,
+        }
+    }
+}
+
+/// Which telemetry a subscription receives
+///
+/// The default receives every topic from every joint and starts with a
+/// snapshot of the latest cached value of each.
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TelemetryFilter {
+    /// Joints to receive from (`None` for all)
+    pub joints: Option<Vec<DeviceId>>,
+    /// Topics to receive (`None` for all)
+    pub topics: Option<Vec<TelemetryTopic>>,
+    /// Deliver the latest cached sample of every matching stream first
+    pub snapshot: bool,
+}
+
+#[cfg(feature = "arm_api")]
+impl Default for TelemetryFilter {
+    fn default() -> Self {
+        Self {
+            joints: None,
+            topics: None,
+            snapshot: true,
+        }
+    }
+}
+
+#[cfg(feature = "arm_api")]
+impl TelemetryFilter {
+    fn matches(&self, joint_id: DeviceId, topic: TelemetryTopic) -> bool {
+        self.joints.as_ref().is_none_or(|joints| joints.contains(&joint_id))
+            && self.topics.as_ref().is_none_or(|topics| topics.contains(&topic))
+    }
+}
+
+/// Receiving end of a telemetry subscription
+///
+/// Samples are queued up to `TELEMETRY_SUBSCRIBER_QUEUE_DEPTH`; a subscriber
+/// that falls further behind misses the newest samples until it catches up.
+/// Dropping it unsubscribes.
+#[cfg(feature = "arm_api")]
+pub struct TelemetrySubscriber {
+    rx: mpsc::Receiver<TelemetrySample>,
+}
+
+#[cfg(feature = "arm_api")]
+impl TelemetrySubscriber {
+    /// Wait for the next sample (`None` once the manager is gone)
+    pub async fn recv(&mut self) -> Option<TelemetrySample> {
+        self.rx.recv().await
+    }
+
+    /// Take the next sample if one is queued
+    pub fn try_recv(&mut self) -> Option<TelemetrySample> {
+        self.rx.try_recv().ok()
+    }
+}
+
+/// Latest value per stream plus the live subscribers
+///
+/// Kept under one lock so a new subscriber's snapshot and its first live
+/// sample can neither overlap nor leave a gap.
+#[cfg(feature = "arm_api")]
+#[derive(Default)]
+struct TelemetryHub {
+    latest: BTreeMap<(DeviceId, TelemetryTopic), TelemetrySample>,
+    subscribers: Vec<(TelemetryFilter, mpsc::Sender<TelemetrySample>)>,
+    #[cfg(feature = "metrics")]
+    metrics: crate::metrics::TelemetryMetrics,
+}
+
+/// A joint came back from a reset caused by a firmware crash
+///
+/// Raised when a joint's boot announcement carries a crash record.
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JointCrashed {
+    /// Joint that crashed and restarted
+    pub joint_id: DeviceId,
+    /// What the firmware recorded before resetting
+    pub record: CrashRecord,
+}
+
+/// How the commands streamed to a joint fared (see
+/// [`CommunicationManager::send_streamed`])
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StreamStatus {
+    /// Commands streamed so far
+    pub sent: u64,
+    /// Latest streamed command the joint acknowledged with a `StreamAck`
+    pub last_acked: Option<MessageId>,
+    /// Commands the joint reported missing
+    pub dropped: u64,
+    /// Commands the joint refused
+    pub refused: u64,
+}
+
+/// Calibration progress or outcome reported by a joint
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, Copy)]
+pub enum CalibrationEvent {
+    /// Periodic update while a calibration runs
+    Progress { joint_id: DeviceId, status: CalibrationStatus },
+    /// The calibration ended, successfully or not
+    Finished { joint_id: DeviceId, result: CalibrationResult },
+}
+
+#[cfg(feature = "arm_api")]
+impl CalibrationEvent {
+    /// Joint that sent the report
+    pub fn joint_id(&self) -> DeviceId {
+        match self {
+            Self::Progress { joint_id, .. } | Self::Finished { joint_id, .. } => *joint_id,
+        }
+    }
+}
+
+/// What the orchestrator does while an interlock is tripped
+///
+/// Ordered by severity; each action includes the ones before it.
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InterlockAction {
+    /// Refuse to activate joints
+    BlockActivation,
+    /// Also refuse setpoints and homing, and hold trajectory streaming
+    Pause,
+    /// Also emergency-stop every joint when the interlock trips
+    EmergencyStop,
+}
+
+/// An interlock input and the action it maps to
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterlockConfig {
+    /// Unique name (e.g. "cell door")
+    pub name: String,
+    /// Joint or safety node that reports the input
+    pub source: DeviceId,
+    /// Channel of the input on that node (0-15)
+    pub channel: u8,
+    /// What to do while the input is tripped
+    pub action: InterlockAction,
+}
+
+/// Last known state of an interlock input
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterlockState {
+    /// No report yet, or the channel is not wired; treated as tripped
+    Unknown,
+    /// Input reports safe
+    Clear,
+    /// Input reports unsafe (door open, curtain broken)
+    Tripped,
+}
 
+/// A configured interlock and its state
 #[cfg(feature = "arm_api")]
-use tokio::sync::{mpsc, RwLock};
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterlockStatus {
+    pub config: InterlockConfig,
+    pub state: InterlockState,
+    /// When the source last reported, on the communication manager's clock
+    pub last_report: Option<std::time::Duration>,
+}
+
+#[cfg(feature = "arm_api")]
+impl InterlockStatus {
+    /// Whether the interlock currently applies its action (fail-safe: unknown counts)
+    pub fn is_active(&self) -> bool {
+        self.state != InterlockState::Clear
+    }
+}
+
+/// An interlock changed state
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterlockEvent {
+    /// The interlock after the change
+    pub status: InterlockStatus,
+    /// State before the change
+    pub previous: InterlockState,
+}
+
+/// Configured interlocks, updated from `InterlockState` reports
+#[cfg(feature = "arm_api")]
+struct Interlocks {
+    statuses: Mutex<Vec<InterlockStatus>>,
+    events: broadcast::Sender<InterlockEvent>,
+    changed: tokio::sync::Notify,
+}
+
+#[cfg(feature = "arm_api")]
+impl Default for Interlocks {
+    fn default() -> Self {
+        Self {
+            statuses: Mutex::new(Vec::new()),
+            events: broadcast::channel(32).0,
+            changed: tokio::sync::Notify::new(),
+        }
+    }
+}
 
+/// A request in flight, from [`CommunicationManager::send_request`]
+///
+/// Dropping the handle before the response arrives (e.g. because the
+/// future awaiting it was cancelled) forgets the request; a late response
+/// is then treated as unsolicited.
 #[cfg(feature = "arm_api")]
-use tracing::{info, debug, warn, error};
+pub struct RequestHandle<'a> {
+    manager: &'a CommunicationManager,
+    msg_id: MessageId,
+    target_id: DeviceId,
+    /// Payload kind, for latency tracking
+    kind: &'static str,
+    /// When the request was queued, on the manager's clock
+    sent_at: std::time::Duration,
+    /// `request` span covering the whole exchange
+    span: Span,
+    /// `None` once answered or forgotten
+    rx: Option<oneshot::Receiver<Message>>,
+}
 
 #[cfg(feature = "arm_api")]
-use std::collections::HashMap;
+impl RequestHandle<'_> {
+    pub fn msg_id(&self) -> MessageId {
+        self.msg_id
+    }
+
+    pub fn target_id(&self) -> DeviceId {
+        self.target_id
+    }
+
+    /// Wait up to `timeout` for the response
+    pub async fn response(mut self, timeout: std::time::Duration) -> Result<Message, ProtocolError> {
+        let span = self.span.clone();
+        async move {
+            let clock = self.manager.clock();
+            let rx = self.rx.as_mut().expect("request is pending until answered");
+            match crate::clock::timeout(&*clock, timeout, rx).await {
+                Ok(Ok(msg)) => {
+                    self.rx = None;
+                    let latency = clock.now().saturating_sub(self.sent_at);
+                    self.span.record("latency_us", latency.as_micros() as u64);
+                    debug!("Reply {} after {:?}", msg.payload.name(), latency);
+                    self.manager.record_latency(self.kind, latency);
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::record_reply(self.target_id, latency, &msg.payload);
+                    Ok(msg)
+                }
+                Ok(Err(_)) => {
+                    self.forget().await;
+                    Err(ProtocolError::IoError(self.msg_id))
+                }
+                Err(_) => {
+                    self.forget().await;
+                    warn!("No reply from joint {} within {:?}", self.manager.joint_label(self.target_id), timeout);
+                    self.manager.record(|| TraceEvent::Timeout(self.msg_id));
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::record_timeout(self.target_id);
+                    Err(ProtocolError::Timeout)
+                }
+            }
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// Stop waiting for the response
+    pub async fn cancel(mut self) {
+        self.forget().await;
+    }
+
+    async fn forget(&mut self) {
+        if self.rx.take().is_some() {
+            self.manager.pending_responses.write().await.remove(&self.msg_id);
+        }
+    }
+}
 
 #[cfg(feature = "arm_api")]
-use std::sync::atomic::{AtomicU32, Ordering};
+impl Drop for RequestHandle<'_> {
+    fn drop(&mut self) {
+        if self.rx.take().is_none() {
+            return;
+        }
+        // If the table is busy the sweeper removes the entry instead
+        if let Ok(mut pending) = self.manager.pending_responses.try_write() {
+            pending.remove(&self.msg_id);
+        }
+    }
+}
 
+/// A request waiting for its response
 #[cfg(feature = "arm_api")]
-use std::sync::Arc;
+struct PendingRequest {
+    /// Device that must answer; `None` when any may, as for broadcasts and
+    /// for batches whose members report their own refusals
+    responder: Option<DeviceId>,
+    tx: oneshot::Sender<Message>,
+}
 
 /// Asynchronous communication manager for ARM systems
 ///
@@ -27,17 +514,42 @@ use std::sync::Arc;
 #[cfg(feature = "arm_api")]
 pub struct CommunicationManager {
     message_id_counter: AtomicU32,
-    pending_responses: Arc<RwLock<HashMap<MessageId, tokio::sync::oneshot::Sender<Message>>>>,
-    outbound_tx: mpsc::UnboundedSender<Message>,
+    pending_responses: Arc<RwLock<HashMap<MessageId, PendingRequest>>>,
+    outbound_tx: mpsc::Sender<Message>,
     #[allow(dead_code)]
     inbound_rx: Arc<RwLock<mpsc::UnboundedReceiver<Message>>>,
+    discovery: Arc<RwLock<Option<DiscoveryCollector>>>,
+    capabilities: Mutex<HashMap<DeviceId, Capabilities>>,
+    /// `SCHEMA_HASH` of joints whose firmware speaks another `Payload` layout
+    schema_mismatches: Mutex<HashMap<DeviceId, u32>>,
+    joint_names: Mutex<HashMap<DeviceId, Arc<str>>>,
+    max_message_size: AtomicUsize,
+    clock: Mutex<Arc<dyn Clock>>,
+    telemetry: Mutex<TelemetryHub>,
+    crash_tx: broadcast::Sender<JointCrashed>,
+    calibration_tx: broadcast::Sender<CalibrationEvent>,
+    interlocks: Interlocks,
+    rate_limiter: Mutex<Option<Arc<RateLimiter>>>,
+    handlers: Mutex<Handlers>,
+    /// Round-trip times per payload kind, while tracking is on
+    latencies: Mutex<Option<BTreeMap<&'static str, LatencyHistogram>>>,
+    flight_recorder: Mutex<Option<FlightLog>>,
+    safety: Mutex<SafetySupervisor>,
+    streams: Mutex<HashMap<DeviceId, StreamStatus>>,
+    sync_counter: AtomicU32,
+    /// Latest `SyncSample` of each joint
+    sync_samples: Mutex<HashMap<DeviceId, SyncSample>>,
+    determinism: Option<Determinism>,
+    /// Adapter task of [`with_adapter`](Self::with_adapter) and the signal
+    /// that closes it
+    adapter_task: Mutex<Option<(oneshot::Sender<()>, JoinHandle<()>)>>,
 }
 
 #[cfg(feature = "arm_api")]
 impl CommunicationManager {
     /// Create a new communication manager
     pub fn new() -> Self {
-        let (outbound_tx, _outbound_rx) = mpsc::unbounded_channel();
+        let (outbound_tx, _outbound_rx) = mpsc::channel(OUTBOUND_QUEUE_DEPTH);
         let (_inbound_tx, inbound_rx) = mpsc::unbounded_channel();
         
         Self {
@@ -45,330 +557,3165 @@ impl CommunicationManager {
             pending_responses: Arc::new(RwLock::new(HashMap::new())),
             outbound_tx,
             inbound_rx: Arc::new(RwLock::new(inbound_rx)),
+            discovery: Arc::new(RwLock::new(None)),
+            capabilities: Mutex::new(HashMap::new()),
+            schema_mismatches: Mutex::new(HashMap::new()),
+            joint_names: Mutex::new(HashMap::new()),
+            max_message_size: AtomicUsize::new(CANFD_MAX_DATA_LEN),
+            clock: Mutex::new(Arc::new(SystemClock::new())),
+            telemetry: Mutex::new(TelemetryHub::default()),
+            crash_tx: broadcast::channel(16).0,
+            calibration_tx: broadcast::channel(64).0,
+            interlocks: Interlocks::default(),
+            rate_limiter: Mutex::new(None),
+            handlers: Mutex::new(Handlers::default()),
+            latencies: Mutex::new(None),
+            flight_recorder: Mutex::new(None),
+            safety: Mutex::new(SafetySupervisor::default()),
+            streams: Mutex::new(HashMap::new()),
+            sync_counter: AtomicU32::new(0),
+            sync_samples: Mutex::new(HashMap::new()),
+            determinism: None,
+            adapter_task: Mutex::new(None),
+        }
+    }
+
+    /// Create a communication manager with reproducible scheduling
+    ///
+    /// Intended for debugging and tests of request/response logic:
+    /// - message IDs start from a value derived from `seed`
+    /// - outbound messages are queued in order and drained with
+    ///   [`poll_outbound`](Self::poll_outbound) instead of going to a bus
+    /// - every send, receive and timeout is appended to [`trace`](Self::trace)
+    ///
+    /// Run it on a `current_thread` runtime with paused time (or with a
+    /// [`VirtualClock`](crate::clock::VirtualClock) set through
+    /// [`set_clock`](Self::set_clock)) so task interleaving and timer firing
+    /// order depend only on the inputs; the same seed and the same inputs
+    /// then produce the same trace.
+    pub fn deterministic(seed: u64) -> Self {
+        let (outbound_tx, outbound_rx) = mpsc::channel(OUTBOUND_QUEUE_DEPTH);
+        let (_inbound_tx, inbound_rx) = mpsc::unbounded_channel();
+
+        // splitmix64 spreads nearby seeds over the ID space
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        let first_id = ((z ^ (z >> 31)) as u32 & !STREAM_MSG_ID_FLAG).max(1);
+
+        Self {
+            message_id_counter: AtomicU32::new(first_id),
+            pending_responses: Arc::new(RwLock::new(HashMap::new())),
+            outbound_tx,
+            inbound_rx: Arc::new(RwLock::new(inbound_rx)),
+            discovery: Arc::new(RwLock::new(None)),
+            capabilities: Mutex::new(HashMap::new()),
+            schema_mismatches: Mutex::new(HashMap::new()),
+            joint_names: Mutex::new(HashMap::new()),
+            max_message_size: AtomicUsize::new(CANFD_MAX_DATA_LEN),
+            clock: Mutex::new(Arc::new(SystemClock::new())),
+            telemetry: Mutex::new(TelemetryHub::default()),
+            crash_tx: broadcast::channel(16).0,
+            calibration_tx: broadcast::channel(64).0,
+            interlocks: Interlocks::default(),
+            rate_limiter: Mutex::new(None),
+            handlers: Mutex::new(Handlers::default()),
+            latencies: Mutex::new(None),
+            flight_recorder: Mutex::new(None),
+            safety: Mutex::new(SafetySupervisor::default()),
+            streams: Mutex::new(HashMap::new()),
+            sync_counter: AtomicU32::new(0),
+            sync_samples: Mutex::new(HashMap::new()),
+            determinism: Some(Determinism {
+                outbound_rx: Mutex::new(outbound_rx),
+                trace: Mutex::new(Vec::new()),
+            }),
+            adapter_task: Mutex::new(None),
+        }
+    }
+
+    /// Create a communication manager connected to a bus adapter
+    ///
+    /// Spawns a background task (so this must be called inside a tokio
+    /// runtime) that transmits queued messages through `adapter` and feeds
+    /// everything it receives to [`process_incoming`](Self::process_incoming),
+    /// polling every `ADAPTER_POLL_INTERVAL_MS`. The task ends when the
+    /// returned manager is dropped.
+    ///
+    /// The message size limit starts at the adapter's MTU. Up to
+    /// `OUTBOUND_QUEUE_DEPTH` messages wait for the adapter; see
+    /// [`with_adapter_and_queue_depth`](Self::with_adapter_and_queue_depth).
+    pub fn with_adapter<A>(adapter: Arc<A>) -> Arc<Self>
+    where
+        A: CommunicationAdapter + 'static,
+    {
+        Self::with_adapter_and_queue_depth(adapter, OUTBOUND_QUEUE_DEPTH)
+    }
+
+    /// Like [`with_adapter`](Self::with_adapter), with room for `depth`
+    /// messages waiting for the adapter
+    ///
+    /// While the queue is full, sends fail at once with
+    /// `ProtocolError::QueueFull` instead of piling up behind a stuck
+    /// transport.
+    pub fn with_adapter_and_queue_depth<A>(adapter: Arc<A>, depth: usize) -> Arc<Self>
+    where
+        A: CommunicationAdapter + 'static,
+    {
+        let (outbound_tx, outbound_rx) = mpsc::channel(depth.max(1));
+        let (_inbound_tx, inbound_rx) = mpsc::unbounded_channel();
+
+        let manager = Arc::new(Self {
+            message_id_counter: AtomicU32::new(1),
+            pending_responses: Arc::new(RwLock::new(HashMap::new())),
+            outbound_tx,
+            inbound_rx: Arc::new(RwLock::new(inbound_rx)),
+            discovery: Arc::new(RwLock::new(None)),
+            capabilities: Mutex::new(HashMap::new()),
+            schema_mismatches: Mutex::new(HashMap::new()),
+            joint_names: Mutex::new(HashMap::new()),
+            max_message_size: AtomicUsize::new(adapter.mtu()),
+            clock: Mutex::new(Arc::new(SystemClock::new())),
+            telemetry: Mutex::new(TelemetryHub::default()),
+            crash_tx: broadcast::channel(16).0,
+            calibration_tx: broadcast::channel(64).0,
+            interlocks: Interlocks::default(),
+            rate_limiter: Mutex::new(None),
+            handlers: Mutex::new(Handlers::default()),
+            latencies: Mutex::new(None),
+            flight_recorder: Mutex::new(None),
+            safety: Mutex::new(SafetySupervisor::default()),
+            streams: Mutex::new(HashMap::new()),
+            sync_counter: AtomicU32::new(0),
+            sync_samples: Mutex::new(HashMap::new()),
+            determinism: None,
+            adapter_task: Mutex::new(None),
+        });
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let task = tokio::spawn(Self::run_adapter(Arc::downgrade(&manager), adapter, outbound_rx, stop_rx));
+        *manager.adapter_task.lock().unwrap() = Some((stop_tx, task));
+        manager
+    }
+
+    /// Pump messages between the outbound queue and an adapter
+    ///
+    /// Once `stop` fires or the manager is dropped, the messages still
+    /// queued go out and the adapter is flushed before the task ends.
+    async fn run_adapter<A: CommunicationAdapter>(
+        manager: Weak<Self>,
+        adapter: Arc<A>,
+        mut outbound_rx: mpsc::Receiver<Message>,
+        mut stop: oneshot::Receiver<()>,
+    ) {
+        let poll_interval = std::time::Duration::from_millis(ADAPTER_POLL_INTERVAL_MS);
+        let sweep_interval = std::time::Duration::from_millis(PENDING_SWEEP_INTERVAL_MS);
+        let mut last_sweep = std::time::Duration::ZERO;
+        // Reused by every poll, so receiving does not allocate
+        let mut inbound = Vec::with_capacity(RECEIVE_BATCH_LEN);
+        loop {
+            let Some(clock) = manager.upgrade().map(|manager| manager.clock()) else {
+                return Self::close_adapter(&*adapter, outbound_rx).await;
+            };
+            if clock.now().saturating_sub(last_sweep) >= sweep_interval {
+                let Some(manager) = manager.upgrade() else {
+                    return Self::close_adapter(&*adapter, outbound_rx).await;
+                };
+                manager.sweep_pending().await;
+                last_sweep = clock.now();
+            }
+            tokio::select! {
+                message = outbound_rx.recv() => match message {
+                    Some(message) => {
+                        #[cfg(feature = "metrics")]
+                        crate::metrics::record_outbound_depth(outbound_rx.len());
+                        if let Err(e) = adapter.transmit(&message).await {
+                            warn!("Failed to transmit message {}: {:?}", message.header.msg_id, e);
+                        }
+                    }
+                    // Manager dropped
+                    None => return Self::close_adapter(&*adapter, outbound_rx).await,
+                },
+                // Closed, or the manager dropped
+                _ = &mut stop => return Self::close_adapter(&*adapter, outbound_rx).await,
+                _ = clock.sleep(poll_interval) => {}
+            }
+
+            loop {
+                // A full batch may have left more behind
+                let more = match adapter.receive_batch(&mut inbound).await {
+                    Ok(()) => inbound.len() >= RECEIVE_BATCH_LEN,
+                    Err(e) => {
+                        warn!("Adapter receive failed: {:?}", e);
+                        false
+                    }
+                };
+                if inbound.is_empty() {
+                    break;
+                }
+                let Some(manager) = manager.upgrade() else {
+                    return Self::close_adapter(&*adapter, outbound_rx).await;
+                };
+                for message in inbound.drain(..) {
+                    manager.process_incoming(message).await;
+                }
+                if !more {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Send what is left in the outbound queue, refusing anything newer,
+    /// and flush the adapter
+    async fn close_adapter<A: CommunicationAdapter>(adapter: &A, mut outbound_rx: mpsc::Receiver<Message>) {
+        outbound_rx.close();
+        while let Some(message) = outbound_rx.recv().await {
+            if let Err(e) = adapter.transmit(&message).await {
+                warn!("Failed to transmit message {}: {:?}", message.header.msg_id, e);
+            }
+        }
+        if let Err(e) = adapter.flush().await {
+            warn!("Failed to flush adapter: {:?}", e);
+        }
+        debug!("Adapter closed");
+    }
+
+    /// Stop talking to the adapter of [`with_adapter`](Self::with_adapter)
+    ///
+    /// Messages already queued are still transmitted, then the adapter is
+    /// [flushed](CommunicationAdapter::flush) and its task ends; this
+    /// returns once it has. Later sends fail with `ProtocolError::IoError`
+    /// and requests still waiting for a response time out. Dropping the
+    /// last reference to the manager closes the adapter the same way, in
+    /// the background. Does nothing for managers without an adapter.
+    pub async fn close(&self) {
+        let task = self.adapter_task.lock().unwrap().take();
+        if let Some((stop, task)) = task {
+            let _ = stop.send(());
+            let _ = task.await;
+            info!("Communication adapter closed");
+        }
+    }
+
+    /// Whether this manager was created with [`deterministic`](Self::deterministic)
+    pub fn is_deterministic(&self) -> bool {
+        self.determinism.is_some()
+    }
+
+    /// Take the next queued outbound message (deterministic mode only)
+    pub fn poll_outbound(&self) -> Option<Message> {
+        let determinism = self.determinism.as_ref()?;
+        let message = determinism.outbound_rx.lock().unwrap().try_recv().ok();
+        message
+    }
+
+    /// Events recorded so far (empty unless in deterministic mode)
+    pub fn trace(&self) -> Vec<TraceEvent> {
+        self.determinism
+            .as_ref()
+            .map(|d| d.trace.lock().unwrap().clone())
+            .unwrap_or_default()
+    }
+
+    fn record(&self, event: impl FnOnce() -> TraceEvent) {
+        if let Some(determinism) = &self.determinism {
+            determinism.trace.lock().unwrap().push(event());
+        }
+    }
+
+    fn record_message(&self, message: &Message, event: fn(Vec<u8>) -> TraceEvent) {
+        self.record(|| event(message.serialize().unwrap_or_default()));
+    }
+
+    /// Queue a message at once, skipping the rate limits and safety checks,
+    /// for stops sent where nothing can be awaited
+    fn send_now(&self, target_id: DeviceId, payload: Payload) -> Result<(), ProtocolError> {
+        let header = Header { source_id: ARM_DEVICE_ID, target_id, msg_id: self.next_message_id() };
+        self.enqueue(Message { header, payload })
+    }
+
+    /// Queue a message for transmission, failing with `QueueFull` rather
+    /// than waiting for room
+    fn enqueue(&self, message: Message) -> Result<(), ProtocolError> {
+        let msg_id = message.header.msg_id;
+        // Traced only once queued, so the trace holds what actually goes out
+        let traced = self.determinism.is_some().then(|| message.serialize().unwrap_or_default());
+        let flown = self.is_flight_recording().then(|| message.clone());
+        let sent = self.outbound_tx.try_send(message);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_outbound_depth(self.outbound_depth());
+        match sent {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(message)) => {
+                warn!("Outbound queue full ({} messages), refusing message {} to joint {}",
+                      self.outbound_capacity(), msg_id, self.joint_label(message.header.target_id));
+                return Err(ProtocolError::QueueFull);
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => return Err(ProtocolError::IoError(msg_id)),
+        }
+        if let Some(bytes) = traced {
+            self.record(|| TraceEvent::Sent(bytes));
+        }
+        if let Some(message) = flown {
+            self.record_flight(Direction::Transmitted, message);
+        }
+        Ok(())
+    }
+
+    /// Messages waiting in the outbound queue
+    pub fn outbound_depth(&self) -> usize {
+        self.outbound_tx.max_capacity() - self.outbound_tx.capacity()
+    }
+
+    /// Messages the outbound queue holds before sends fail with `QueueFull`
+    pub fn outbound_capacity(&self) -> usize {
+        self.outbound_tx.max_capacity()
+    }
+    
+    /// Generate a unique message ID, never one of a streamed command
+    fn next_message_id(&self) -> MessageId {
+        self.message_id_counter.fetch_add(1, Ordering::SeqCst) & !STREAM_MSG_ID_FLAG
+    }
+
+    /// Set the largest encoded message the transport can carry
+    ///
+    /// Defaults to one CAN-FD frame (`CANFD_MAX_DATA_LEN`), or the adapter's
+    /// MTU with [`with_adapter`](Self::with_adapter). Messages above the
+    /// limit are rejected with `ProtocolError::PayloadTooLarge` before they
+    /// are queued.
+    pub fn set_max_message_size(&self, limit: usize) {
+        self.max_message_size.store(limit, Ordering::Relaxed);
+    }
+
+    /// Get the largest encoded message the transport can carry
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size.load(Ordering::Relaxed)
+    }
+
+    /// Replace the time source used for timeouts and waits
+    ///
+    /// Defaults to [`SystemClock`]. With a
+    /// [`VirtualClock`](crate::clock::VirtualClock), request timeouts, the
+    /// discovery window, adapter polling and everything the proxies and
+    /// orchestrator time through this manager only advance when the test
+    /// says so. Waits already in progress keep the clock they started with.
+    pub fn set_clock(&self, clock: Arc<dyn Clock>) {
+        *self.clock.lock().unwrap() = clock;
+    }
+
+    /// Get the time source used for timeouts and waits
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.lock().unwrap().clone()
+    }
+
+    /// Pace outgoing messages (see [`rate_limit`](crate::rate_limit)), or
+    /// `None` to send them as they come
+    ///
+    /// While setpoints are spaced, proxies also coalesce setpoints queued
+    /// behind one in flight, as with [`CommandPolicy::Coalesce`].
+    pub fn set_rate_limits(&self, limits: Option<RateLimits>) {
+        *self.rate_limiter.lock().unwrap() = limits.map(|limits| Arc::new(RateLimiter::new(limits)));
+    }
+
+    pub fn rate_limits(&self) -> Option<RateLimits> {
+        self.rate_limiter.lock().unwrap().as_ref().map(|limiter| limiter.limits().clone())
+    }
+
+    fn coalesces_setpoints(&self) -> bool {
+        self.rate_limiter.lock().unwrap().as_ref().is_some_and(|limiter| limiter.limits().coalesces_setpoints())
+    }
+
+    /// Check targets against the safety limits, or stop checking (`None`)
+    ///
+    /// See [`safety`](crate::safety). The audit log is kept across changes.
+    pub fn set_safety_limits(&self, limits: Option<SafetyLimits>) {
+        self.safety.lock().unwrap().set_limits(limits);
+    }
+
+    pub fn safety_limits(&self) -> Option<SafetyLimits> {
+        self.safety.lock().unwrap().limits().cloned()
+    }
+
+    /// Targets the safety limits refused or clamped, oldest first
+    pub fn safety_interventions(&self) -> Vec<Intervention> {
+        self.safety.lock().unwrap().interventions().cloned().collect()
+    }
+
+    /// Refuse or clamp targets that break the safety limits
+    fn supervise(&self, message: &mut Message) -> Result<(), ProtocolError> {
+        let now = self.clock().now();
+        let position = |joint_id| {
+            let sample = self.latest_telemetry(joint_id, TelemetryTopic::Motion)?;
+            sample.position().map(|position| position.0)
+        };
+        self.safety.lock().unwrap().check(message.header.target_id, &mut message.payload, now, position)
+    }
+
+    /// Wait for the message's turn under the rate limits
+    ///
+    /// Fails with `Superseded` when a newer setpoint of the same kind to the
+    /// same joint came in meanwhile.
+    async fn throttle(&self, message: &Message) -> Result<(), ProtocolError> {
+        let Some(limiter) = self.rate_limiter.lock().unwrap().clone() else {
+            return Ok(());
+        };
+        let clock = self.clock();
+        let (target, payload) = (message.header.target_id, &message.payload);
+
+        let slot = limiter.reserve(target, payload, clock.now());
+        clock.sleep_until(slot.ready_at).await;
+        if !limiter.claim(&slot, clock.now()) {
+            debug!("{} to joint {} superseded by a newer one", payload.name(), self.joint_label(target));
+            return Err(ProtocolError::Superseded);
+        }
+        let start = limiter.reserve_bandwidth(payload, message.encoded_size(), clock.now());
+        clock.sleep_until(start).await;
+        Ok(())
+    }
+
+    /// Refuse messages to a joint that discovery found speaking another
+    /// `Payload` layout; it would misread them
+    fn check_schema(&self, message: &Message) -> Result<(), ProtocolError> {
+        match self.schema_mismatches.lock().unwrap().get(&message.header.target_id) {
+            Some(&remote) => Err(ProtocolError::SchemaMismatch { local: SCHEMA_HASH, remote }),
+            None => Ok(()),
+        }
+    }
+
+    /// Reject messages that would not fit the transport
+    fn check_size(&self, message: &Message) -> Result<(), ProtocolError> {
+        let size = message.encoded_size();
+        let limit = self.max_message_size();
+        if size > limit {
+            warn!("Message {} to {} is {} bytes, limit is {}",
+                  message.header.msg_id, message.header.target_id, size, limit);
+            return Err(ProtocolError::PayloadTooLarge { size, limit });
+        }
+        Ok(())
+    }
+    
+    /// Send a message and wait for response
+    pub async fn send_and_wait(&self, target_id: DeviceId, payload: Payload) -> Result<Message, ProtocolError> {
+        let request = self.send_request(target_id, payload).await?;
+        request.response(std::time::Duration::from_secs(5)).await
+    }
+
+    /// Send a message and return a handle for its response
+    ///
+    /// The reply is correlated until the handle is answered, cancelled or
+    /// dropped, so a caller that gives up (e.g. a `select!` branch that
+    /// loses) does not leave its correlation behind.
+    pub async fn send_request(
+        &self,
+        target_id: DeviceId,
+        payload: Payload,
+    ) -> Result<RequestHandle<'_>, ProtocolError> {
+        let msg_id = self.next_message_id();
+        let kind = payload.name();
+        let latency_us = tracing::field::Empty;
+        let span = info_span!("request", msg_id, device_id = target_id, payload = kind, latency_us);
+
+        let mut message = Message {
+            header: Header {
+                source_id: 0x0001, // ARM controller ID
+                target_id,
+                msg_id,
+            },
+            payload,
+        };
+        async move {
+            self.supervise(&mut message)?;
+            self.check_schema(&message)?;
+            self.check_size(&message)?;
+            self.throttle(&message).await?;
+
+            // Register pending response
+            let (tx, rx) = oneshot::channel();
+            let responder = match (message.header.target_id, &message.payload) {
+                (BROADCAST_ADDRESS, _) | (_, Payload::SetTargetBatch(_)) => None,
+                (target_id, _) => Some(target_id),
+            };
+            self.pending_responses.write().await.insert(msg_id, PendingRequest { responder, tx });
+            let sent_at = self.clock().now();
+            let request =
+                RequestHandle { manager: self, msg_id, target_id, kind, sent_at, span: Span::current(), rx: Some(rx) };
+
+            // Send message
+            if let Err(e) = self.enqueue(message) {
+                request.cancel().await;
+                return Err(e);
+            }
+            debug!("Request queued");
+            Ok(request)
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// Record request round-trip times (see [`latency`](crate::latency)),
+    /// or stop and discard them
+    pub fn set_latency_tracking(&self, enabled: bool) {
+        let mut latencies = self.latencies.lock().unwrap();
+        match enabled {
+            true => {
+                latencies.get_or_insert_with(BTreeMap::new);
+            }
+            false => *latencies = None,
+        }
+    }
+
+    /// Round-trip times recorded so far, per payload kind
+    pub fn latency_histograms(&self) -> BTreeMap<&'static str, LatencyHistogram> {
+        self.latencies.lock().unwrap().clone().unwrap_or_default()
+    }
+
+    /// Round-trip times of requests with payload `kind` (see `Payload::name`)
+    pub fn latency_histogram(&self, kind: &str) -> Option<LatencyHistogram> {
+        self.latencies.lock().unwrap().as_ref()?.get(kind).cloned()
+    }
+
+    fn record_latency(&self, kind: &'static str, latency: std::time::Duration) {
+        if let Some(latencies) = self.latencies.lock().unwrap().as_mut() {
+            latencies.entry(kind).or_default().record(latency);
+        }
+    }
+
+    /// Keep the last `capacity` messages sent and received, to read back with
+    /// [`flight_recorder`](Self::flight_recorder) after an incident, or stop
+    /// (`None`) and discard them
+    ///
+    /// Joints keep their own commands, replies and state changes; read those
+    /// with [`JointProxy::flight_recorder`].
+    pub fn set_flight_recorder(&self, capacity: Option<usize>) {
+        let mut flight_recorder = self.flight_recorder.lock().unwrap();
+        match (capacity, flight_recorder.as_mut()) {
+            (Some(capacity), Some(log)) => {
+                log.capacity = capacity;
+                let excess = log.records.len().saturating_sub(capacity);
+                log.records.drain(..excess);
+            }
+            (Some(capacity), None) => *flight_recorder = Some(FlightLog { capacity, records: VecDeque::new() }),
+            (None, _) => *flight_recorder = None,
+        }
+    }
+
+    /// Messages in the flight recorder, oldest first, stamped with the
+    /// manager's [clock](Self::clock)
+    pub fn flight_recorder(&self) -> Vec<LogRecord> {
+        let flight_recorder = self.flight_recorder.lock().unwrap();
+        flight_recorder.as_ref().map(|log| log.records.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    fn is_flight_recording(&self) -> bool {
+        self.flight_recorder.lock().unwrap().is_some()
+    }
+
+    fn record_flight(&self, direction: Direction, message: Message) {
+        let timestamp_us = self.clock().now().as_micros() as u64;
+        if let Some(log) = self.flight_recorder.lock().unwrap().as_mut() {
+            if log.capacity > 0 {
+                if log.records.len() >= log.capacity {
+                    log.records.pop_front();
+                }
+                log.records.push_back(LogRecord { timestamp_us, direction, message });
+            }
+        }
+    }
+
+    /// Requests still waiting for a response
+    pub async fn pending_requests(&self) -> usize {
+        self.pending_responses.read().await.len()
+    }
+
+    /// Drop pending requests nobody waits for anymore; returns how many
+    ///
+    /// Managers created with [`with_adapter`](Self::with_adapter) do this
+    /// every `PENDING_SWEEP_INTERVAL_MS`. Handles clean up after
+    /// themselves, so this only catches those dropped while the table was
+    /// busy.
+    pub async fn sweep_pending(&self) -> usize {
+        let mut pending = self.pending_responses.write().await;
+        let before = pending.len();
+        pending.retain(|_, request| !request.tx.is_closed());
+        let swept = before - pending.len();
+        if swept > 0 {
+            debug!("Dropped {} orphaned pending requests", swept);
+        }
+        swept
+    }
+
+    /// Send a message without waiting for response
+    pub async fn send_fire_and_forget(&self, target_id: DeviceId, payload: Payload) -> Result<(), ProtocolError> {
+        let msg_id = self.next_message_id();
+        
+        let mut message = Message {
+            header: Header {
+                source_id: 0x0001, // ARM controller ID
+                target_id,
+                msg_id,
+            },
+            payload,
+        };
+        self.supervise(&mut message)?;
+        self.check_schema(&message)?;
+        self.check_size(&message)?;
+        self.throttle(&message).await?;
+        
+        self.enqueue(message)
+    }
+    
+    /// Send a command that the joint acknowledges with its next
+    /// `StreamAck` instead of an `Ack` of its own, for setpoints streamed at
+    /// a high rate
+    ///
+    /// The message ID carries `STREAM_MSG_ID_FLAG` and numbers the commands
+    /// streamed to the joint, so it can tell how many went missing; a
+    /// command that could not be queued counts as missing too. See
+    /// [`stream_status`](Self::stream_status), which also counts refusals.
+    pub async fn send_streamed(&self, target_id: DeviceId, payload: Payload) -> Result<(), ProtocolError> {
+        let mut message = Message {
+            header: Header {
+                source_id: 0x0001, // ARM controller ID
+                target_id,
+                msg_id: 0,
+            },
+            payload,
+        };
+        self.supervise(&mut message)?;
+        self.check_schema(&message)?;
+        self.check_size(&message)?;
+        self.throttle(&message).await?;
+
+        let mut streams = self.streams.lock().unwrap();
+        let status = streams.entry(target_id).or_default();
+        status.sent += 1;
+        message.header.msg_id = STREAM_MSG_ID_FLAG | (status.sent as u32 & !STREAM_MSG_ID_FLAG);
+        drop(streams);
+        self.enqueue(message)
+    }
+
+    /// How the commands streamed to a joint fared so far
+    pub fn stream_status(&self, joint_id: DeviceId) -> StreamStatus {
+        self.streams.lock().unwrap().get(&joint_id).copied().unwrap_or_default()
+    }
+
+    /// Broadcast the next `Sync`, returning its cycle counter
+    ///
+    /// Joints in `ControlMode::CyclicPosition` latch the target buffered
+    /// since the previous `Sync` and answer with a `SyncSample` taken at the
+    /// same instant; see [`sync_samples`](Self::sync_samples).
+    pub async fn send_sync(&self) -> Result<u32, ProtocolError> {
+        let cycle_counter = self.sync_counter.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+        self.send_fire_and_forget(BROADCAST_ADDRESS, Payload::Sync { cycle_counter }).await?;
+        Ok(cycle_counter)
+    }
+
+    /// Samples the joints took at the `Sync` of a cycle, by joint
+    ///
+    /// Joints whose latest sample belongs to another cycle are left out.
+    pub fn sync_samples(&self, cycle_counter: u32) -> BTreeMap<DeviceId, SyncSample> {
+        self.sync_samples.lock().unwrap().iter()
+            .filter(|(_, sample)| sample.cycle_counter == cycle_counter)
+            .map(|(&joint_id, &sample)| (joint_id, sample))
+            .collect()
+    }
+
+    /// Account for a `StreamAck`, or the refusal of a streamed command;
+    /// false for any other message
+    fn note_stream(&self, message: &Message) -> bool {
+        let joint_id = message.header.source_id;
+        match message.payload {
+            Payload::StreamAck { last_msg_id, dropped } => {
+                if dropped > 0 {
+                    warn!("Joint {} missed {} streamed commands", self.joint_label(joint_id), dropped);
+                }
+                let mut streams = self.streams.lock().unwrap();
+                let status = streams.entry(joint_id).or_default();
+                status.last_acked = Some(last_msg_id);
+                status.dropped += dropped as u64;
+                true
+            }
+            Payload::Nack { id, error } if id & STREAM_MSG_ID_FLAG != 0 => {
+                warn!("Joint {} refused streamed command {:#010x}: error {}", self.joint_label(joint_id), id, error);
+                self.streams.lock().unwrap().entry(joint_id).or_default().refused += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Broadcast a discovery request and collect replies
+    ///
+    /// Joints answer after a randomized backoff, so replies are gathered for
+    /// `DISCOVERY_WINDOW_MS` (longer than the worst-case backoff) rather than
+    /// until the first response. Duplicate replies are merged.
+    ///
+    /// Devices whose firmware speaks another `Payload` layout (see
+    /// [`DeviceInfo::check_schema`]) are returned too, but every later
+    /// send to them fails with `SchemaMismatch` until a discovery finds
+    /// them matching.
+    pub async fn discover(&self) -> Result<Vec<DeviceInfo>, ProtocolError> {
+        let msg_id = self.next_message_id();
+        *self.discovery.write().await = Some(DiscoveryCollector::new());
+
+        let message = Message {
+            header: Header {
+                source_id: 0x0001, // ARM controller ID
+                target_id: BROADCAST_ADDRESS,
+                msg_id,
+            },
+            payload: Payload::Discover,
+        };
+
+        if let Err(e) = self.enqueue(message) {
+            *self.discovery.write().await = None;
+            return Err(e);
+        }
+
+        self.clock().sleep(std::time::Duration::from_millis(DISCOVERY_WINDOW_MS)).await;
+
+        let collector = self.discovery.write().await.take().unwrap_or_default();
+        if collector.duplicates() > 0 {
+            debug!("Discovery merged {} duplicate replies", collector.duplicates());
+        }
+        let devices = collector.into_devices();
+        info!("Discovery found {} devices", devices.len());
+        let mut capabilities = self.capabilities.lock().unwrap();
+        let mut schema_mismatches = self.schema_mismatches.lock().unwrap();
+        for device in &devices {
+            match device.check_schema() {
+                Ok(()) => {
+                    schema_mismatches.remove(&device.id);
+                    capabilities.insert(device.id, device.capabilities);
+                }
+                Err(e) => {
+                    error!("Device {}: {}; not sending to it", device.id, e);
+                    schema_mismatches.insert(device.id, device.schema);
+                }
+            }
+        }
+        Ok(devices)
+    }
+
+    /// Capabilities a device advertised in its last discovery reply
+    ///
+    /// `None` until the device has been discovered (or its capabilities set
+    /// with [`set_capabilities`](Self::set_capabilities)); proxies then send
+    /// every command and let the joint refuse what it does not handle.
+    pub fn capabilities(&self, device_id: DeviceId) -> Option<Capabilities> {
+        self.capabilities.lock().unwrap().get(&device_id).copied()
+    }
+
+    /// Record a device's capabilities without discovery, e.g. from a
+    /// configuration file
+    pub fn set_capabilities(&self, device_id: DeviceId, capabilities: Capabilities) {
+        self.capabilities.lock().unwrap().insert(device_id, capabilities);
+    }
+
+    /// Name a joint for log messages and telemetry samples, or `None` to
+    /// go back to its bare ID
+    pub fn set_joint_name(&self, device_id: DeviceId, name: Option<String>) {
+        let mut names = self.joint_names.lock().unwrap();
+        match name {
+            Some(name) => names.insert(device_id, name.into()),
+            None => names.remove(&device_id),
+        };
+    }
+
+    /// The joint's name, if it has one
+    pub fn joint_name(&self, device_id: DeviceId) -> Option<Arc<str>> {
+        self.joint_names.lock().unwrap().get(&device_id).cloned()
+    }
+
+    /// A joint's name and ID for messages (`elbow (48)`), or just the ID
+    pub fn joint_label(&self, device_id: DeviceId) -> String {
+        match self.joint_name(device_id) {
+            Some(name) => format!("{} ({})", name, device_id),
+            None => device_id.to_string(),
+        }
+    }
+
+    /// Subscribe to telemetry from the joints
+    ///
+    /// With `filter.snapshot` set, the latest cached sample of every matching
+    /// (joint, topic) stream is queued first, marked `snapshot`, so a late
+    /// subscriber has a complete picture without waiting for the next sample
+    /// of everything; live samples follow.
+    pub fn subscribe_telemetry(&self, filter: TelemetryFilter) -> TelemetrySubscriber {
+        let mut hub = self.telemetry.lock().unwrap();
+        let snapshot: Vec<TelemetrySample> = if filter.snapshot {
+            hub.latest
+                .values()
+                .filter(|sample| filter.matches(sample.joint_id, sample.topic))
+                .map(|sample| TelemetrySample { snapshot: true, ..sample.clone() })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // Room for the whole snapshot on top of the usual queue
+        let (tx, rx) = mpsc::channel(TELEMETRY_SUBSCRIBER_QUEUE_DEPTH + snapshot.len());
+        for sample in snapshot {
+            let _ = tx.try_send(sample);
+        }
+        hub.subscribers.push((filter, tx));
+        TelemetrySubscriber { rx }
+    }
+
+    /// Latest cached telemetry of one stream
+    pub fn latest_telemetry(&self, joint_id: DeviceId, topic: TelemetryTopic) -> Option<TelemetrySample> {
+        self.telemetry.lock().unwrap().latest.get(&(joint_id, topic)).cloned()
+    }
+
+    /// Cache a telemetry message and fan it out to subscribers
+    fn publish_telemetry(&self, message: &Message) {
+        let Some(topic) = TelemetryTopic::of(&message.payload) else {
+            return;
+        };
+        // Subscribers only ever see the current generation of telemetry
+        let message = Message {
+            header: message.header.clone(),
+            payload: compat::translate(message.payload.clone(), PayloadGeneration::V2),
+        };
+        let sample = TelemetrySample {
+            joint_id: message.header.source_id,
+            joint_name: self.joint_name(message.header.source_id),
+            topic,
+            message,
+            received_at: self.clock().now(),
+            snapshot: false,
+        };
+
+        let mut hub = self.telemetry.lock().unwrap();
+        #[cfg(feature = "metrics")]
+        hub.metrics.record(topic, &sample.message);
+        hub.subscribers.retain(|(filter, tx)| {
+            if !filter.matches(sample.joint_id, topic) {
+                return !tx.is_closed();
+            }
+            match tx.try_send(sample.clone()) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    debug!("Telemetry subscriber lagging, dropped sample from joint {}",
+                           self.joint_label(sample.joint_id));
+                    true
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+            }
+        });
+        hub.latest.insert((sample.joint_id, topic), sample);
+    }
+
+    /// Subscribe to crash reports from joints' boot announcements
+    pub fn subscribe_crashes(&self) -> broadcast::Receiver<JointCrashed> {
+        self.crash_tx.subscribe()
+    }
+
+    /// Subscribe to calibration progress and results from all joints
+    pub fn subscribe_calibration(&self) -> broadcast::Receiver<CalibrationEvent> {
+        self.calibration_tx.subscribe()
+    }
+
+    /// Call `handler` with every unsolicited `T` from `joint_id`
+    ///
+    /// See [`dispatch`](crate::dispatch) for the payload types.
+    pub fn on<T: UnsolicitedPayload>(
+        &self,
+        joint_id: DeviceId,
+        handler: impl Fn(DeviceId, T) + Send + Sync + 'static,
+    ) -> HandlerId {
+        self.handlers.lock().unwrap().add(Some(joint_id), handler)
+    }
+
+    /// Call `handler` with every unsolicited `T`, from any joint
+    pub fn on_any<T: UnsolicitedPayload>(
+        &self,
+        handler: impl Fn(DeviceId, T) + Send + Sync + 'static,
+    ) -> HandlerId {
+        self.handlers.lock().unwrap().add(None, handler)
+    }
+
+    /// Unregister a handler; `false` if it was already removed
+    pub fn remove_handler(&self, id: HandlerId) -> bool {
+        self.handlers.lock().unwrap().remove(id)
+    }
+
+    /// Hand an unsolicited message to its handlers; `false` if none took it
+    fn dispatch(&self, message: &Message) -> bool {
+        let calls = self.handlers.lock().unwrap().for_message(message);
+        calls.iter().fold(false, |handled, call| call(message) | handled)
+    }
+
+    /// Add an interlock, replacing any with the same name
+    ///
+    /// It starts in `InterlockState::Unknown`, i.e. active, until its source
+    /// reports.
+    pub fn configure_interlock(&self, config: InterlockConfig) {
+        info!("Interlock '{}' on device {} channel {} -> {:?}",
+              config.name, config.source, config.channel, config.action);
+        let mut statuses = self.interlocks.statuses.lock().unwrap();
+        statuses.retain(|status| status.config.name != config.name);
+        statuses.push(InterlockStatus { config, state: InterlockState::Unknown, last_report: None });
+        drop(statuses);
+        self.interlocks.changed.notify_waiters();
+    }
+
+    /// Remove an interlock by name; returns whether it existed
+    pub fn remove_interlock(&self, name: &str) -> bool {
+        let mut statuses = self.interlocks.statuses.lock().unwrap();
+        let before = statuses.len();
+        statuses.retain(|status| status.config.name != name);
+        let removed = statuses.len() != before;
+        drop(statuses);
+        self.interlocks.changed.notify_waiters();
+        removed
+    }
+
+    /// All configured interlocks and their states
+    pub fn interlocks(&self) -> Vec<InterlockStatus> {
+        self.interlocks.statuses.lock().unwrap().clone()
+    }
+
+    /// Subscribe to interlock state changes
+    pub fn subscribe_interlocks(&self) -> broadcast::Receiver<InterlockEvent> {
+        self.interlocks.events.subscribe()
+    }
+
+    /// Fail with `Interlocked` if an active interlock's action includes `action`
+    pub fn check_interlocks(&self, action: InterlockAction) -> Result<(), ProtocolError> {
+        let statuses = self.interlocks.statuses.lock().unwrap();
+        match statuses.iter().find(|s| s.is_active() && s.config.action >= action) {
+            Some(status) => {
+                warn!("Interlock '{}' is {:?}, refusing {:?}-level command",
+                      status.config.name, status.state, action);
+                Err(ProtocolError::Interlocked)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Wait until no active interlock's action includes `action`
+    pub async fn wait_for_interlocks(&self, action: InterlockAction) {
+        loop {
+            let changed = self.interlocks.changed.notified();
+            tokio::pin!(changed);
+            // Register before checking so a change in between is not missed
+            changed.as_mut().enable();
+            if self.check_interlocks(action).is_ok() {
+                return;
+            }
+            changed.await;
+        }
+    }
+
+    /// Apply an `InterlockState` report to the interlocks it covers
+    fn update_interlocks(&self, source: DeviceId, report: &InterlockStatePayload) {
+        let now = self.clock().now();
+        let mut events = Vec::new();
+        for status in self.interlocks.statuses.lock().unwrap().iter_mut() {
+            if status.config.source != source {
+                continue;
+            }
+            let previous = status.state;
+            status.state = match report.channel(status.config.channel) {
+                Some(true) => InterlockState::Tripped,
+                Some(false) => InterlockState::Clear,
+                None => InterlockState::Unknown,
+            };
+            status.last_report = Some(now);
+            if status.state != previous {
+                events.push(InterlockEvent { status: status.clone(), previous });
+            }
+        }
+
+        if events.is_empty() {
+            return;
+        }
+        for event in events {
+            match event.status.state {
+                InterlockState::Clear => info!("Interlock '{}' cleared", event.status.config.name),
+                state => warn!("Interlock '{}' is {:?}", event.status.config.name, state),
+            }
+            // No subscribers is fine; the state is kept either way
+            let _ = self.interlocks.events.send(event);
+        }
+        self.interlocks.changed.notify_waiters();
+    }
+
+    /// Process incoming message (would typically be called by background task)
+    pub async fn process_incoming(&self, message: Message) {
+        self.record_message(&message, TraceEvent::Received);
+        if self.is_flight_recording() {
+            self.record_flight(Direction::Received, message.clone());
+        }
+        self.publish_telemetry(&message);
+
+        if let Payload::Boot(boot) = &message.payload {
+            let joint_id = message.header.source_id;
+            match boot.crash {
+                Some(record) => {
+                    error!("Joint {} restarted after {:?} in task '{}' (pc {:#010x}, lr {:#010x}): {}",
+                           self.joint_label(joint_id), record.kind, record.task(), record.pc, record.lr,
+                           record.message());
+                    // No subscribers is fine; the crash is logged either way
+                    let _ = self.crash_tx.send(JointCrashed { joint_id, record });
+                }
+                None => info!("Joint {} booted", self.joint_label(joint_id)),
+            }
+            self.dispatch(&message);
+            return;
+        }
+        if let Payload::InterlockState(report) = &message.payload {
+            self.update_interlocks(message.header.source_id, report);
+            self.dispatch(&message);
+            return;
+        }
+        if self.note_stream(&message) {
+            self.dispatch(&message);
+            return;
+        }
+        if let Payload::SyncSample(sample) = message.payload {
+            self.sync_samples.lock().unwrap().insert(message.header.source_id, sample);
+            self.dispatch(&message);
+            return;
+        }
+        if let Payload::FaultReport(report) = &message.payload {
+            warn!("Joint {} reacted to {:?} with {:?} at {} deg, {} A",
+                  self.joint_label(message.header.source_id), report.cause, report.reaction, report.position,
+                  report.current);
+            self.dispatch(&message);
+            return;
+        }
+        let joint_id = message.header.source_id;
+        let calibration = match message.payload {
+            Payload::CalibrationStatus(status) => Some(CalibrationEvent::Progress { joint_id, status }),
+            Payload::CalibrationResult(result) => Some(CalibrationEvent::Finished { joint_id, result }),
+            _ => None,
+        };
+        if let Some(event) = calibration {
+            // No subscribers is fine; nothing waits for the report
+            let _ = self.calibration_tx.send(event);
+            self.dispatch(&message);
+            return;
+        }
+
+        // Discovery replies go to the active collector, if any
+        if let Some(collector) = self.discovery.write().await.as_mut() {
+            if collector.offer(&message) {
+                return;
+            }
+        }
+
+        // Streamed telemetry numbers its own messages, so its IDs can match
+        // those of requests in flight
+        if matches!(TelemetryTopic::of(&message.payload), Some(TelemetryTopic::Motion | TelemetryTopic::Adaptive)) {
+            self.dispatch(&message);
+            return;
+        }
+
+        let msg_id = message.header.msg_id;
+        
+        // Check if this is a response to a pending request, from the device it went to
+        let tx = {
+            let mut pending = self.pending_responses.write().await;
+            match pending.get(&msg_id) {
+                Some(request) if request.responder.is_none_or(|id| id == message.header.source_id) => {
+                    pending.remove(&msg_id)
+                }
+                _ => None,
+            }
+        };
+        let Some(PendingRequest { tx, .. }) = tx else {
+            // Unsolicited: telemetry, status updates, etc.
+            if !self.dispatch(&message) {
+                debug!("Received unsolicited message: {:?}", message);
+            }
+            return;
+        };
+        if tx.send(message).is_err() {
+            warn!("Failed to deliver response for message {}", msg_id);
+        }
+    }
+}
+
+/// Mismatch between a proxy's cached state and the joint's reported state
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateDrift {
+    /// Joint whose cache was out of date
+    pub joint_id: DeviceId,
+    /// State the proxy believed the joint was in
+    pub cached: LifecycleState,
+    /// State reported by the joint (now stored in the cache)
+    pub actual: LifecycleState,
+    /// Error code reported alongside the state
+    pub error_code: u16,
+}
+
+/// How a [`JointProxy`] handles a command issued while another one to the
+/// same joint is still in flight
+///
+/// Applies to state-changing commands; status and bus statistics queries
+/// are never held back.
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CommandPolicy {
+    /// Wait for the earlier commands and send in call order
+    #[default]
+    Queue,
+    /// Like `Queue`, but a waiting setpoint is dropped with
+    /// `ProtocolError::Superseded` when a newer one arrives
+    Coalesce,
+    /// Fail immediately with `ProtocolError::Busy`
+    Reject,
+}
+
+/// Per-joint command serialization, shared by all clones of a proxy
+#[cfg(feature = "arm_api")]
+#[derive(Default)]
+struct CommandGate {
+    /// Held for the whole request/response exchange (FIFO fair)
+    lock: tokio::sync::Mutex<()>,
+    policy: Mutex<CommandPolicy>,
+    /// Bumped by every emergency stop; commands queued before it are dropped
+    estop_epoch: AtomicU64,
+    /// Ticket of the newest setpoint, for coalescing
+    latest_setpoint: AtomicU64,
+}
+
+/// High-level interface for interacting with a single joint
+///
+/// Provides a gRPC-like API for controlling a remote joint device.
+/// All methods are async and handle communication transparently.
+/// Clones share the same cached state, and state-changing commands from
+/// all clones are serialized according to the [`CommandPolicy`].
+#[cfg(feature = "arm_api")]
+#[derive(Clone)]
+pub struct JointProxy {
+    joint_id: DeviceId,
+    comm_manager: Arc<CommunicationManager>,
+    current_state: Arc<RwLock<LifecycleState>>,
+    limits: Arc<RwLock<Option<JointLimits>>>,
+    encoder_offset: Arc<RwLock<Option<MultiTurnPosition>>>,
+    gate: Arc<CommandGate>,
+    generation: Arc<Mutex<Option<PayloadGeneration>>>,
+}
+
+#[cfg(feature = "arm_api")]
+impl JointProxy {
+    /// Create a new joint proxy
+    pub fn new(joint_id: DeviceId, comm_manager: Arc<CommunicationManager>) -> Self {
+        Self {
+            joint_id,
+            comm_manager,
+            current_state: Arc::new(RwLock::new(LifecycleState::Unconfigured)),
+            limits: Arc::new(RwLock::new(None)),
+            encoder_offset: Arc::new(RwLock::new(None)),
+            gate: Arc::new(CommandGate::default()),
+            generation: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Payload generation the joint is known to accept, once a motion
+    /// command has been acknowledged or its capabilities are known
+    pub fn payload_generation(&self) -> Option<PayloadGeneration> {
+        let pinned = *self.generation.lock().unwrap();
+        pinned.or_else(|| {
+            self.capabilities().map(|capabilities| {
+                if capabilities.contains(Capabilities::SET_TARGET_V2) {
+                    PayloadGeneration::V2
+                } else {
+                    PayloadGeneration::V1
+                }
+            })
+        })
+    }
+
+    /// Capabilities the joint advertised during discovery, if it has been
+    /// discovered
+    ///
+    /// Commands needing a capability the joint lacks fail with
+    /// `ProtocolError::Unsupported` without being sent.
+    pub fn capabilities(&self) -> Option<Capabilities> {
+        self.comm_manager.capabilities(self.joint_id)
+    }
+
+    /// Refuse a command the joint is known not to handle
+    fn require(&self, capability: Capabilities) -> Result<(), ProtocolError> {
+        match self.capabilities() {
+            Some(capabilities) if !capabilities.contains(capability) => {
+                warn!("Joint {} lacks capability {:#x}", self.label(), capability.bits());
+                Err(ProtocolError::Unsupported)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Pin the payload generation used for motion commands, or `None` to
+    /// detect it again on the next command
+    pub fn set_payload_generation(&self, generation: Option<PayloadGeneration>) {
+        *self.generation.lock().unwrap() = generation;
+    }
+
+    /// Policy applied to concurrent commands (default `Queue`)
+    pub fn command_policy(&self) -> CommandPolicy {
+        *self.gate.policy.lock().unwrap()
+    }
+
+    /// Change how concurrent commands are handled, for all clones of this proxy
+    pub fn set_command_policy(&self, policy: CommandPolicy) {
+        *self.gate.policy.lock().unwrap() = policy;
+    }
+
+    /// Wait for exclusive use of the joint according to the command policy
+    ///
+    /// `setpoint` marks commands that may be coalesced, under the `Coalesce`
+    /// policy or while the manager's rate limits space setpoints.
+    async fn acquire(&self, setpoint: bool) -> Result<MutexGuard<'_, ()>, ProtocolError> {
+        let gate = &self.gate;
+        let policy = self.command_policy();
+        let epoch = gate.estop_epoch.load(Ordering::SeqCst);
+        let ticket = if setpoint {
+            gate.latest_setpoint.fetch_add(1, Ordering::SeqCst) + 1
+        } else {
+            0
+        };
+
+        let guard = match policy {
+            CommandPolicy::Reject => gate.lock.try_lock().map_err(|_| {
+                warn!("Joint {} busy, rejecting command", self.label());
+                ProtocolError::Busy
+            })?,
+            CommandPolicy::Queue | CommandPolicy::Coalesce => gate.lock.lock().await,
+        };
+
+        if gate.estop_epoch.load(Ordering::SeqCst) != epoch {
+            debug!("Joint {} command dropped after emergency stop", self.label());
+            return Err(ProtocolError::Cancelled);
+        }
+        if (policy == CommandPolicy::Coalesce || self.comm_manager.coalesces_setpoints())
+            && setpoint
+            && gate.latest_setpoint.load(Ordering::SeqCst) != ticket
+        {
+            debug!("Joint {} setpoint superseded", self.label());
+            return Err(ProtocolError::Superseded);
+        }
+        Ok(guard)
+    }
+    
+    /// Get the current state of the joint
+    pub async fn get_state(&self) -> LifecycleState {
+        *self.current_state.read().await
+    }
+
+    async fn cache_state(&self, state: LifecycleState) {
+        *self.current_state.write().await = state;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_state(self.joint_id, state);
+    }
+    
+    /// Configure the joint (transition from Unconfigured to Inactive)
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "configure"))]
+    pub async fn configure(&self) -> Result<(), ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::Configure).await?;
+        
+        match response.payload {
+            Payload::Ack(_) => {
+                self.cache_state(LifecycleState::Inactive).await;
+                info!("Joint {} configured successfully", self.label());
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} configure failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+    
+    /// Activate the joint (transition from Inactive to Active)
+    ///
+    /// Refused with `Interlocked` while any configured interlock is active.
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "activate"))]
+    pub async fn activate(&self) -> Result<(), ProtocolError> {
+        self.comm_manager.check_interlocks(InterlockAction::BlockActivation)?;
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::Activate).await?;
+        
+        match response.payload {
+            Payload::Ack(_) => {
+                self.cache_state(LifecycleState::Active).await;
+                info!("Joint {} activated successfully", self.label());
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} activate failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+    
+    /// Deactivate the joint (transition from Active to Inactive)
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "deactivate"))]
+    pub async fn deactivate(&self) -> Result<(), ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::Deactivate).await?;
+        
+        match response.payload {
+            Payload::Ack(_) => {
+                self.cache_state(LifecycleState::Inactive).await;
+                info!("Joint {} deactivated successfully", self.label());
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} deactivate failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+    
+    /// Reset the joint (transition to Unconfigured from any state)
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "reset"))]
+    pub async fn reset(&self) -> Result<(), ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        self.send_reset().await
+    }
+
+    /// Reset the joint immediately, bypassing the command queue
+    ///
+    /// Does not wait for a command already in flight; commands still
+    /// waiting for their turn fail with `ProtocolError::Cancelled`.
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "emergency_stop"))]
+    pub async fn emergency_stop(&self) -> Result<(), ProtocolError> {
+        self.gate.estop_epoch.fetch_add(1, Ordering::SeqCst);
+        warn!("Joint {} emergency stop", self.label());
+        self.send_reset().await
+    }
+
+    async fn send_reset(&self) -> Result<(), ProtocolError> {
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::Reset).await?;
+        
+        match response.payload {
+            Payload::Ack(_) => {
+                self.cache_state(LifecycleState::Unconfigured).await;
+                info!("Joint {} reset successfully", self.label());
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} reset failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+    
+    /// Set target position and velocity (only works when joint is Active)
+    ///
+    /// Refused with `Interlocked` while a `Pause` or `EmergencyStop`
+    /// interlock is active.
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "set_target"))]
+    pub async fn set_target(&self, target_angle: f32, velocity_limit: f32) -> Result<(), ProtocolError> {
+        self.send_motion(Payload::SetTarget(SetTargetPayload {
+            target_angle,
+            velocity_limit,
+        }))
+        .await?;
+        debug!("Joint {} target set: angle={}, velocity={}",
+               self.label(), target_angle, velocity_limit);
+        Ok(())
+    }
+
+    /// [`set_target`](Self::set_target) with typed units
+    ///
+    /// Radians and radians/second are converted to the protocol's degrees:
+    /// `joint.move_to(Radians(0.5), DegPerSec(30.0))`.
+    pub async fn move_to(
+        &self,
+        target: impl Into<Degrees>,
+        velocity_limit: impl Into<DegPerSec>,
+    ) -> Result<(), ProtocolError> {
+        let target = SetTargetPayload::new(target, velocity_limit);
+        self.set_target(target.target_angle, target.velocity_limit).await
+    }
+
+    /// Set the target with a full motion profile (only works when joint is Active)
+    ///
+    /// Joints running v1 firmware receive the target and velocity limit
+    /// only; see [`compat`](crate::compat).
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "set_target_v2"))]
+    pub async fn set_target_v2(&self, target: SetTargetPayloadV2) -> Result<(), ProtocolError> {
+        self.send_motion(Payload::SetTargetV2(target)).await?;
+        debug!("Joint {} target set: angle={}, profile={:?}",
+               self.label(), target.target_angle, target.profile);
+        Ok(())
+    }
+
+    /// Stream a target without waiting for the joint, for setpoints sent at
+    /// a high rate (see [`CommunicationManager::send_streamed`])
+    ///
+    /// Goes out in the generation the joint is known to accept (v2 while it
+    /// is unknown). Targets the joint refuses or never gets show up in the
+    /// [stream status](Self::stream_status), not as errors.
+    pub async fn stream_target(&self, target: SetTargetPayloadV2) -> Result<(), ProtocolError> {
+        self.comm_manager.check_interlocks(InterlockAction::Pause)?;
+        let payload = match self.payload_generation() {
+            Some(generation) => compat::translate(Payload::SetTargetV2(target), generation),
+            None => Payload::SetTargetV2(target),
+        };
+        self.comm_manager.send_streamed(self.joint_id, payload).await
+    }
+
+    /// How the targets streamed to the joint fared so far
+    pub fn stream_status(&self) -> StreamStatus {
+        self.comm_manager.stream_status(self.joint_id)
+    }
+
+    /// Send targets for several joints in one frame, with this joint as the
+    /// group master that acknowledges them (see `Payload::SetTargetBatch`)
+    ///
+    /// Fails if the master or any listed joint refuses its target; a refusal
+    /// that comes in after the master's `Ack` is only logged.
+    pub async fn send_target_batch(&self, batch: TargetBatch) -> Result<(), ProtocolError> {
+        self.send_batch(batch).await.map_err(|(_, error)| error)
+    }
+
+    /// [`send_target_batch`](Self::send_target_batch), naming the joint
+    /// that failed
+    pub(crate) async fn send_batch(&self, batch: TargetBatch) -> Result<(), (DeviceId, ProtocolError)> {
+        self.comm_manager.check_interlocks(InterlockAction::Pause).map_err(|e| (self.joint_id, e))?;
+        let _guard = self.acquire(true).await.map_err(|e| (self.joint_id, e))?;
+
+        let response = self
+            .comm_manager
+            .send_and_wait(self.joint_id, Payload::SetTargetBatch(batch))
+            .await
+            .map_err(|e| (self.joint_id, e))?;
+        match response.payload {
+            Payload::Ack(_) => Ok(()),
+            Payload::Nack { id, error } => {
+                let joint_id = response.header.source_id;
+                error!("Joint {} refused its batched target: error {}", self.comm_manager.joint_label(joint_id), error);
+                Err((joint_id, ProtocolError::IoError(id)))
+            }
+            _ => Err((self.joint_id, ProtocolError::InvalidMessage)),
+        }
+    }
+
+    /// Send a motion command in the generation the joint accepts
+    ///
+    /// While the generation is unknown, a Nack with `ERROR_UNKNOWN_COMMAND`
+    /// retries once in the other generation and remembers whichever was
+    /// acknowledged.
+    async fn send_motion(&self, payload: Payload) -> Result<(), ProtocolError> {
+        self.comm_manager.check_interlocks(InterlockAction::Pause)?;
+        let _guard = self.acquire(true).await?;
+
+        let known = self.payload_generation();
+        let mut payload = match known {
+            Some(generation) => compat::translate(payload, generation),
+            None => payload,
+        };
+        let mut retried = false;
+        loop {
+            let generation = PayloadGeneration::of(&payload);
+            let response = self.comm_manager.send_and_wait(self.joint_id, payload.clone()).await?;
+            match (response.payload, generation) {
+                (Payload::Ack(_), _) => {
+                    if known.is_none() {
+                        self.set_payload_generation(generation);
+                    }
+                    return Ok(());
+                }
+                (Payload::Nack { error: ERROR_UNKNOWN_COMMAND, .. }, Some(rejected)) if known.is_none() && !retried => {
+                    info!("Joint {} does not accept {:?} payloads, falling back to {:?}",
+                          self.label(), rejected, rejected.other());
+                    retried = true;
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::record_retry(self.joint_id);
+                    payload = compat::translate(payload, rejected.other());
+                }
+                (Payload::Nack { id, error }, _) => {
+                    error!("Joint {} set target failed: error {}", self.label(), error);
+                    return Err(ProtocolError::IoError(id));
+                }
+                _ => return Err(ProtocolError::InvalidMessage),
+            }
+        }
+    }
+
+    /// Run the joint's homing routine (only works when joint is Active)
+    ///
+    /// The joint acknowledges the request immediately; homing has finished
+    /// once its status no longer reports `ERROR_POSITION_UNKNOWN`. Refused
+    /// like [`set_target`](Self::set_target) while interlocked.
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "home"))]
+    pub async fn home(&self) -> Result<(), ProtocolError> {
+        self.require(Capabilities::HOMING)?;
+        self.comm_manager.check_interlocks(InterlockAction::Pause)?;
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::Home).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                info!("Joint {} homing started", self.label());
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} home failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Run a specific homing procedure (only works when joint is Active)
+    ///
+    /// Like [`home`](Self::home), which runs the joint's default procedure
+    /// (`HomingConfig::default()`); the joint reports `Calibrating` until the
+    /// reference is found.
+    pub async fn start_homing(&self, config: HomingConfig) -> Result<(), ProtocolError> {
+        self.require(Capabilities::HOMING)?;
+        self.comm_manager.check_interlocks(InterlockAction::Pause)?;
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::StartHoming(config)).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                info!("Joint {} homing started ({:?})", self.label(), config.method);
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} start homing failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Start the calibration phases selected in `request`
+    ///
+    /// The joint acknowledges the request immediately and then reports
+    /// through [`CommunicationManager::subscribe_calibration`]. Refused like
+    /// [`set_target`](Self::set_target) while interlocked.
+    pub async fn start_calibration(&self, request: CalibrationRequest) -> Result<(), ProtocolError> {
+        self.require(Capabilities::CALIBRATION)?;
+        self.comm_manager.check_interlocks(InterlockAction::Pause)?;
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::StartCalibration(request)).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                info!("Joint {} calibration started", self.label());
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} start calibration failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Abort a running calibration
+    pub async fn stop_calibration(&self) -> Result<(), ProtocolError> {
+        self.require(Capabilities::CALIBRATION)?;
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::StopCalibration).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                info!("Joint {} calibration stopped", self.label());
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} stop calibration failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Run a calibration and wait for its result
+    ///
+    /// Fails with `Timeout` if the joint has not reported a result within
+    /// `timeout`; the calibration is not stopped in that case.
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "calibrate"))]
+    pub async fn calibrate(
+        &self,
+        request: CalibrationRequest,
+        timeout: std::time::Duration,
+    ) -> Result<CalibrationResult, ProtocolError> {
+        let mut events = self.comm_manager.subscribe_calibration();
+        self.start_calibration(request).await?;
+
+        let joint_id = self.joint_id;
+        let result = tokio::time::timeout(timeout, async move {
+            loop {
+                match events.recv().await {
+                    Ok(CalibrationEvent::Finished { joint_id: id, result }) if id == joint_id => return Ok(result),
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return Err(ProtocolError::Cancelled),
+                }
+            }
+        })
+        .await
+        .map_err(|_| ProtocolError::Timeout)??;
+
+        if result.success {
+            info!("Joint {} calibration finished in {:.1}s", self.label(), result.total_time);
+        } else {
+            warn!("Joint {} calibration failed: error {}", self.label(), result.error_code);
+        }
+        Ok(result)
+    }
+
+    /// Apply soft limits and remember them for re-application after homing
+    pub async fn set_limits(&self, limits: JointLimits) -> Result<(), ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::SetLimits(limits)).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                *self.limits.write().await = Some(limits);
+                debug!("Joint {} limits set: {:?}", self.label(), limits);
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} set limits failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Limits last applied through this proxy
+    pub async fn limits(&self) -> Option<JointLimits> {
+        *self.limits.read().await
+    }
+
+    /// Set the joint's telemetry mode, rate and schedule phase
+    pub async fn configure_telemetry(&self, config: ConfigureTelemetryPayload) -> Result<(), ProtocolError> {
+        self.require(Capabilities::TELEMETRY_STREAMING)?;
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::ConfigureTelemetry(config)).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                debug!("Joint {} telemetry configured: {:?}", self.label(), config);
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} telemetry configuration failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Configure coolStep, dcStep, stallGuard and the joint's
+    /// [reaction to collisions](crate::CollisionReaction)
+    pub async fn configure_adaptive(&self, config: ConfigureAdaptivePayload) -> Result<(), ProtocolError> {
+        self.require(Capabilities::ADAPTIVE_CONTROL)?;
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::ConfigureAdaptive(config)).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                debug!("Joint {} adaptive control configured: {:?}", self.label(), config);
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} adaptive control configuration failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Set the temperatures the joint derates and faults at
+    pub async fn configure_thermal(&self, limits: ThermalLimits) -> Result<(), ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::ConfigureThermal(limits)).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                debug!("Joint {} thermal limits set: {:?}", self.label(), limits);
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} thermal limits refused: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Set the supply voltages the joint warns and stops at
+    pub async fn configure_power(&self, limits: PowerLimits) -> Result<(), ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::ConfigurePower(limits)).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                debug!("Joint {} power limits set: {:?}", self.label(), limits);
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} power limits refused: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Query the joint's supply voltage and current draw
+    ///
+    /// Fails with `IoError` if the joint's firmware does not measure them.
+    pub async fn power_status(&self) -> Result<PowerStatusPayload, ProtocolError> {
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::RequestPowerStatus).await?;
+
+        match response.payload {
+            Payload::PowerStatus(status) => Ok(status),
+            Payload::Nack { id, error } => {
+                error!("Joint {} power status request failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Query the joint's lifetime usage counters (energy, motor-on time,
+    /// moves), e.g. for predictive maintenance
+    ///
+    /// With the `metrics` feature they are also published as gauges.
+    pub async fn statistics(&self) -> Result<JointStatistics, ProtocolError> {
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::RequestStatistics).await?;
+
+        match response.payload {
+            Payload::Statistics(statistics) => {
+                #[cfg(feature = "metrics")]
+                crate::metrics::record_statistics(self.joint_id, &statistics);
+                Ok(statistics)
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} statistics request failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Run the joint's self-test: encoder, motor driver and, unless
+    /// `motion_deg` is 0, a move of up to `SELF_TEST_MAX_MOTION_DEG` degrees
+    /// and back
+    ///
+    /// The reply must echo a fresh nonce, which checks the round trip over
+    /// the bus; one that does not fails with `InvalidMessage`. A failed
+    /// check is reported in the result, not as an error. The motion test
+    /// needs the joint Active.
+    pub async fn self_test(&self, motion_deg: f32) -> Result<SelfTestResult, ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        let nonce = self.comm_manager.clock().now().as_micros() as u32 ^ self.joint_id as u32;
+        let request = SelfTestRequest { nonce, motion_deg };
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::RunSelfTest(request)).await?;
+
+        match response.payload {
+            Payload::SelfTestResult(result) if result.nonce == nonce => {
+                if result.passed() {
+                    debug!("Joint {} passed its self-test: {:?}", self.label(), result);
+                } else {
+                    warn!("Joint {} failed its self-test: {:?}", self.label(), result);
+                }
+                Ok(result)
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} self-test refused: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Recover a joint that reports `ERROR_POSITION_UNKNOWN`
+    ///
+    /// Brings the joint to Active (configuring/activating as needed), starts
+    /// homing, polls its status every `HOMING_POLL_INTERVAL_MS` until the
+    /// position is known again, then re-applies the limits last set through
+    /// [`set_limits`](Self::set_limits). Fails with `Timeout` if homing does
+    /// not finish within `timeout` (`HOMING_TIMEOUT_MS` is a sensible default).
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "rehome_and_restore"))]
+    pub async fn rehome_and_restore(&self, timeout: std::time::Duration) -> Result<(), ProtocolError> {
+        let (state, _) = self.query_status().await?;
+        self.cache_state(state).await;
+
+        match state {
+            LifecycleState::Unconfigured => {
+                self.configure().await?;
+                self.activate().await?;
+            }
+            LifecycleState::Inactive => self.activate().await?,
+            LifecycleState::Active => {}
+            _ => {
+                error!("Joint {} cannot be re-homed from {:?}", self.label(), state);
+                return Err(ProtocolError::InvalidStateTransition);
+            }
+        }
+
+        self.home().await?;
+
+        let clock = self.comm_manager.clock();
+        let poll = async {
+            loop {
+                clock.sleep(std::time::Duration::from_millis(HOMING_POLL_INTERVAL_MS)).await;
+                let (_, error_code) = self.query_status().await?;
+                if error_code != ERROR_POSITION_UNKNOWN {
+                    return Ok::<(), ProtocolError>(());
+                }
+            }
+        };
+        match crate::clock::timeout(&*clock, timeout, poll).await {
+            Ok(result) => result?,
+            Err(_) => {
+                error!("Joint {} homing did not finish within {:?}", self.label(), timeout);
+                return Err(ProtocolError::Timeout);
+            }
+        }
+
+        if let Some(limits) = self.limits().await {
+            self.set_limits(limits).await?;
+        }
+
+        info!("Joint {} re-homed and restored", self.label());
+        Ok(())
+    }
+
+    /// Query the joint's authoritative lifecycle state and error code
+    pub async fn query_status(&self) -> Result<(LifecycleState, u16), ProtocolError> {
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::RequestStatus).await?;
+
+        match response.payload {
+            Payload::JointStatus { state, error_code } => Ok((state, error_code)),
+            Payload::Nack { id, error } => {
+                error!("Joint {} status request failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Compare the cached state against the joint and correct the cache
+    ///
+    /// Returns the drift if the cache was wrong (e.g. a status update was
+    /// lost on the bus), or `None` if it was already accurate.
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "reconcile"))]
+    pub async fn reconcile(&self) -> Result<Option<StateDrift>, ProtocolError> {
+        let (actual, error_code) = self.query_status().await?;
+
+        let mut state = self.current_state.write().await;
+        if *state == actual {
+            return Ok(None);
+        }
+
+        let drift = StateDrift {
+            joint_id: self.joint_id,
+            cached: *state,
+            actual,
+            error_code,
+        };
+        *state = actual;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_state(self.joint_id, actual);
+        warn!("Joint {} state drift: cached {:?}, actual {:?} (error {})",
+              self.label(), drift.cached, drift.actual, error_code);
+        Ok(Some(drift))
+    }
+
+    /// Query the joint's transport statistics and bus health counters
+    pub async fn get_bus_stats(&self) -> Result<TransportStats, ProtocolError> {
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::RequestBusStats).await?;
+
+        match response.payload {
+            Payload::BusStats(stats) => {
+                if stats.total_errors() > 0 {
+                    warn!("Joint {} reports {} bus errors (last: {:?})",
+                          self.label(), stats.total_errors(), stats.last_error);
+                }
+                Ok(stats)
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} bus stats request failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Agree on the message size limit with the joint
+    ///
+    /// Reads the MTU the joint's link reports in its bus statistics and
+    /// lowers the communication manager's `max_message_size` to it if it is
+    /// smaller, so oversized requests fail on the host instead of on the
+    /// joint's link. The limit is shared by all joints on the manager, so it
+    /// ends up at the smallest MTU on the bus. Returns the effective limit.
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "negotiate_mtu"))]
+    pub async fn negotiate_mtu(&self) -> Result<usize, ProtocolError> {
+        let stats = self.get_bus_stats().await?;
+        let current = self.comm_manager.max_message_size();
+        let joint_mtu = stats.mtu as usize;
+
+        // 0 means the joint does not know its link's MTU
+        if joint_mtu > 0 && joint_mtu < current {
+            info!("Joint {} link MTU is {} bytes, lowering message limit from {}",
+                  self.label(), joint_mtu, current);
+            self.comm_manager.set_max_message_size(joint_mtu);
+        }
+        Ok(self.comm_manager.max_message_size())
+    }
+
+    /// Persist the joint's applied limits, calibration result and telemetry
+    /// settings in its configuration store
+    pub async fn save_config(&self) -> Result<(), ProtocolError> {
+        self.config_store_command(Payload::SaveConfig).await
+    }
+
+    /// Re-apply the configuration last saved on the joint (refused while Active)
+    ///
+    /// Limits applied since are replaced, so the cached limits are dropped.
+    pub async fn load_config(&self) -> Result<(), ProtocolError> {
+        self.config_store_command(Payload::LoadConfig).await?;
+        *self.limits.write().await = None;
+        Ok(())
+    }
+
+    /// Erase the joint's saved configuration and drop the applied limits,
+    /// encoder offset, calibration result and telemetry settings (refused
+    /// while Active)
+    pub async fn factory_reset(&self) -> Result<(), ProtocolError> {
+        self.config_store_command(Payload::FactoryReset).await?;
+        *self.limits.write().await = None;
+        *self.encoder_offset.write().await = None;
+        Ok(())
+    }
+
+    async fn config_store_command(&self, payload: Payload) -> Result<(), ProtocolError> {
+        self.require(Capabilities::CONFIG_STORE)?;
+        let _guard = self.acquire(false).await?;
+        let name = payload.name();
+        let response = self.comm_manager.send_and_wait(self.joint_id, payload).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                info!("Joint {} {} done", self.label(), name);
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} {} failed: error {}", self.label(), name, error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Query the joint's firmware versions, boot mode and boot count
+    pub async fn boot_info(&self) -> Result<BootInfoPayload, ProtocolError> {
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::RequestBootInfo).await?;
+
+        match response.payload {
+            Payload::BootInfo(info) => Ok(info),
+            Payload::Nack { id, error } => {
+                error!("Joint {} boot info request failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Read the joint's flight recorder, oldest entry first (see
+    /// [`flight_recorder`](crate::flight_recorder))
+    ///
+    /// Entries come `FLIGHT_RECORDS_PER_CHUNK` at a time, so a joint that
+    /// keeps working meanwhile may add some or overwrite the oldest; read it
+    /// once the joint has stopped, e.g. after a fault. Use
+    /// `Payload::tag_name` to name the payloads it lists.
+    #[instrument(name = "joint", skip_all, fields(joint = %self.label(), command = "flight_recorder"))]
+    pub async fn flight_recorder(&self) -> Result<Vec<FlightRecord>, ProtocolError> {
+        self.require(Capabilities::FLIGHT_RECORDER)?;
+        let mut records = Vec::new();
+        let mut from = 0;
+        loop {
+            let request = Payload::RequestFlightRecorder { from };
+            let response = self.comm_manager.send_and_wait(self.joint_id, request).await?;
+            let chunk = match response.payload {
+                Payload::FlightRecorder(chunk) => chunk,
+                Payload::Nack { id, error } => {
+                    error!("Joint {} flight recorder request failed: error {}", self.label(), error);
+                    return Err(ProtocolError::IoError(id));
+                }
+                _ => return Err(ProtocolError::InvalidMessage),
+            };
+            records.extend_from_slice(chunk.records());
+            if chunk.records().len() < FLIGHT_RECORDS_PER_CHUNK {
+                debug!("Read {} flight recorder entries from joint {}", records.len(), self.label());
+                return Ok(records);
+            }
+            from = chunk.first.wrapping_add(FLIGHT_RECORDS_PER_CHUNK as u32);
+        }
+    }
+
+    /// Keep a joint that requires it enabled for `ttl` (up to 65.535 s)
+    ///
+    /// `token` must differ from the previous one. Not serialized with other
+    /// commands, so a long-running one does not hold it up; see
+    /// [`ArmOrchestrator::start_safety_heartbeat`] to refresh every joint
+    /// periodically.
+    pub async fn safety_enable(&self, token: u32, ttl: std::time::Duration) -> Result<(), ProtocolError> {
+        self.require(Capabilities::SAFETY_ENABLE)?;
+        let ttl_ms = ttl.as_millis().min(u16::MAX as u128) as u16;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::SafetyEnable { token, ttl_ms }).await?;
+
+        match response.payload {
+            Payload::Ack(_) => Ok(()),
+            Payload::Nack { id, error } => {
+                error!("Joint {} safety enable refused: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage),
         }
     }
-    
-    /// Generate a unique message ID
-    fn next_message_id(&self) -> MessageId {
-        self.message_id_counter.fetch_add(1, Ordering::SeqCst)
-    }
-    
-    /// Send a message and wait for response
-    pub async fn send_and_wait(&self, target_id: DeviceId, payload: Payload) -> Result<Message, ProtocolError> {
-        let msg_id = self.next_message_id();
-        let (tx, rx) = tokio::sync::oneshot::channel();
-        
-        // Register pending response
-        {
-            let mut pending = self.pending_responses.write().await;
-            pending.insert(msg_id, tx);
+
+    /// Restart the joint into its bootloader for re-flashing
+    ///
+    /// Refused while Active. The joint acknowledges and then resets; it
+    /// answers discovery in [`BootMode::Bootloader`] once the bootloader is
+    /// up, and refuses lifecycle commands until it is flashed and restarted.
+    pub async fn enter_bootloader(&self) -> Result<(), ProtocolError> {
+        self.require(Capabilities::FIRMWARE_UPDATE)?;
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::EnterBootloader).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                warn!("Joint {} restarting into bootloader", self.label());
+                *self.current_state.write().await = LifecycleState::Unconfigured;
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} enter bootloader failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
         }
-        
-        let message = Message {
-            header: Header {
-                source_id: 0x0001, // ARM controller ID
-                target_id,
-                msg_id,
-            },
-            payload,
-        };
-        
-        // Send message
-        if self.outbound_tx.send(message).is_err() {
-            // Remove the pending response entry on send failure
-            let mut pending = self.pending_responses.write().await;
-            pending.remove(&msg_id);
-            return Err(ProtocolError::IoError(msg_id));
+    }
+
+    /// Engage (`true`) or release the joint's holding brake
+    ///
+    /// The joint refuses to release the brake until configured, and refuses
+    /// motion with `ERROR_BRAKE_ENGAGED` while it holds.
+    pub async fn set_brake(&self, engaged: bool) -> Result<(), ProtocolError> {
+        self.require(Capabilities::BRAKE)?;
+        let _guard = self.acquire(false).await?;
+        let command = if engaged { Payload::EngageBrake } else { Payload::ReleaseBrake };
+        let response = self.comm_manager.send_and_wait(self.joint_id, command).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                info!("Joint {} brake {}", self.label(), if engaged { "engaged" } else { "released" });
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} brake command failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
         }
-        
-        // Wait for response with timeout
-        match tokio::time::timeout(std::time::Duration::from_secs(5), rx).await {
-            Ok(Ok(msg)) => Ok(msg),
-            Ok(Err(_)) => {
-                // Remove the pending response entry on oneshot receive error
-                let mut pending = self.pending_responses.write().await;
-                pending.remove(&msg_id);
-                Err(ProtocolError::IoError(msg_id))
+    }
+
+    /// Drive one of the joint's auxiliary digital outputs (brake, fan, ...)
+    pub async fn set_digital_output(&self, channel: u8, value: bool) -> Result<(), ProtocolError> {
+        self.require(Capabilities::AUX_IO)?;
+        let _guard = self.acquire(false).await?;
+        let output = Payload::SetDigitalOutput(DigitalIoPayload { channel, value });
+        let response = self.comm_manager.send_and_wait(self.joint_id, output).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                debug!("Joint {} output {} set {}", self.label(), channel, value);
+                Ok(())
             }
-            Err(_) => {
-                // Remove the pending response entry on timeout
-                let mut pending = self.pending_responses.write().await;
-                pending.remove(&msg_id);
-                Err(ProtocolError::Timeout)
+            Payload::Nack { id, error } => {
+                error!("Joint {} output {} failed: error {}", self.label(), channel, error);
+                Err(ProtocolError::IoError(id))
             }
+            _ => Err(ProtocolError::InvalidMessage)
         }
     }
-    /// Send a message without waiting for response
-    pub async fn send_fire_and_forget(&self, target_id: DeviceId, payload: Payload) -> Result<(), ProtocolError> {
-        let msg_id = self.next_message_id();
-        
-        let message = Message {
-            header: Header {
-                source_id: 0x0001, // ARM controller ID
-                target_id,
-                msg_id,
-            },
-            payload,
-        };
-        
-        self.outbound_tx.send(message)
-            .map_err(|_| ProtocolError::IoError(msg_id))
+
+    /// Read one of the joint's auxiliary digital inputs (`true` = high)
+    pub async fn read_digital_input(&self, channel: u8) -> Result<bool, ProtocolError> {
+        self.require(Capabilities::AUX_IO)?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::ReadDigitalInput(channel)).await?;
+
+        match response.payload {
+            Payload::DigitalInput(input) if input.channel == channel => Ok(input.value),
+            Payload::Nack { id, error } => {
+                error!("Joint {} input {} read failed: error {}", self.label(), channel, error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
     }
-    
-    /// Process incoming message (would typically be called by background task)
-    pub async fn process_incoming(&self, message: Message) {
-        let msg_id = message.header.msg_id;
-        
-        // Check if this is a response to a pending request
-        let mut pending = self.pending_responses.write().await;
-        if let Some(tx) = pending.remove(&msg_id) {
-            if tx.send(message).is_err() {
-                warn!("Failed to deliver response for message {}", msg_id);
+
+    /// Read one of the joint's auxiliary analog inputs, in the channel's unit
+    pub async fn read_analog_input(&self, channel: u8) -> Result<f32, ProtocolError> {
+        self.require(Capabilities::AUX_IO)?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::ReadAnalogInput(channel)).await?;
+
+        match response.payload {
+            Payload::AnalogInput(input) if input.channel == channel => Ok(input.value),
+            Payload::Nack { id, error } => {
+                error!("Joint {} analog input {} read failed: error {}", self.label(), channel, error);
+                Err(ProtocolError::IoError(id))
             }
-        } else {
-            // Handle unsolicited message (telemetry, status updates, etc.)
-            debug!("Received unsolicited message: {:?}", message);
+            _ => Err(ProtocolError::InvalidMessage)
         }
     }
-}
 
-/// High-level interface for interacting with a single joint
-///
-/// Provides a gRPC-like API for controlling a remote joint device.
-/// All methods are async and handle communication transparently.
-#[cfg(feature = "arm_api")]
-pub struct JointProxy {
-    joint_id: DeviceId,
-    comm_manager: Arc<CommunicationManager>,
-    current_state: Arc<RwLock<LifecycleState>>,
-}
+    /// Set the raw encoder position that reads as zero (joint must not be
+    /// Active)
+    ///
+    /// The joint drops its soft limits, which referred to the old zero; save
+    /// the configuration to keep the offset across power cycles.
+    pub async fn set_encoder_offset(&self, offset: MultiTurnPosition) -> Result<(), ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::SetEncoderOffset(offset)).await?;
 
-#[cfg(feature = "arm_api")]
-impl JointProxy {
-    /// Create a new joint proxy
-    pub fn new(joint_id: DeviceId, comm_manager: Arc<CommunicationManager>) -> Self {
-        Self {
-            joint_id,
-            comm_manager,
-            current_state: Arc::new(RwLock::new(LifecycleState::Unconfigured)),
+        match response.payload {
+            Payload::Ack(_) => {
+                *self.encoder_offset.write().await = Some(offset);
+                info!("Joint {} encoder offset set to {} turns {:.3} deg", self.label(), offset.turns, offset.angle);
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {} set encoder offset failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
         }
     }
-    
-    /// Get the current state of the joint
-    pub async fn get_state(&self) -> LifecycleState {
-        *self.current_state.read().await
+
+    /// Encoder offset last set through this proxy
+    pub async fn encoder_offset(&self) -> Option<MultiTurnPosition> {
+        *self.encoder_offset.read().await
     }
-    
-    /// Configure the joint (transition from Unconfigured to Inactive)
-    pub async fn configure(&self) -> Result<(), ProtocolError> {
-        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::Configure).await?;
-        
+
+    /// Read the joint's position with its turn count
+    pub async fn read_multi_turn_position(&self) -> Result<MultiTurnPosition, ProtocolError> {
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::RequestMultiTurnPosition).await?;
+
+        match response.payload {
+            Payload::MultiTurnPosition(position) => Ok(position),
+            Payload::Nack { id, error } => {
+                error!("Joint {} position read failed: error {}", self.label(), error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Set the gains of the joint's position/velocity controller
+    ///
+    /// Accepted in any state; the controller restarts with the new gains.
+    /// Negative or non-finite gains are refused with `ERROR_INVALID_GAINS`.
+    pub async fn configure_gains(&self, gains: ControlGains) -> Result<(), ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::ConfigureGains(gains)).await?;
+
         match response.payload {
             Payload::Ack(_) => {
-                let mut state = self.current_state.write().await;
-                *state = LifecycleState::Inactive;
-                info!("Joint {} configured successfully", self.joint_id);
+                info!("Joint {} controller gains configured", self.label());
                 Ok(())
             }
             Payload::Nack { id, error } => {
-                error!("Joint {} configure failed: error {}", self.joint_id, error);
+                error!("Joint {} configure gains failed: error {}", self.label(), error);
                 Err(ProtocolError::IoError(id))
             }
             _ => Err(ProtocolError::InvalidMessage)
         }
     }
-    
-    /// Activate the joint (transition from Inactive to Active)
-    pub async fn activate(&self) -> Result<(), ProtocolError> {
-        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::Activate).await?;
-        
+
+    /// Set one slot of the joint's gain schedule
+    ///
+    /// Save the configuration to keep the schedule across power cycles.
+    pub async fn set_gain_schedule_entry(&self, entry: GainScheduleEntry) -> Result<(), ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::SetGainScheduleEntry(entry)).await?;
+
         match response.payload {
             Payload::Ack(_) => {
-                let mut state = self.current_state.write().await;
-                *state = LifecycleState::Active;
-                info!("Joint {} activated successfully", self.joint_id);
+                info!("Joint {} gain schedule slot {} set", self.label(), entry.slot);
                 Ok(())
             }
             Payload::Nack { id, error } => {
-                error!("Joint {} activate failed: error {}", self.joint_id, error);
+                error!("Joint {} set gain schedule slot {} failed: error {}", self.label(), entry.slot, error);
                 Err(ProtocolError::IoError(id))
             }
             _ => Err(ProtocolError::InvalidMessage)
         }
     }
-    
-    /// Deactivate the joint (transition from Active to Inactive)
-    pub async fn deactivate(&self) -> Result<(), ProtocolError> {
-        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::Deactivate).await?;
-        
+
+    /// Set the torque the joint's control loop adds to its output, in
+    /// newton-metres (only works when joint is Active)
+    ///
+    /// Meant for gravity compensation, sent alongside targets; see
+    /// [`ArmOrchestrator::send_gravity_feedforward`]. Not held back by
+    /// `Pause` interlocks, since a paused arm still has to carry its weight.
+    pub async fn set_feedforward(&self, torque: f32) -> Result<(), ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::SetFeedforward { torque }).await?;
+
         match response.payload {
             Payload::Ack(_) => {
-                let mut state = self.current_state.write().await;
-                *state = LifecycleState::Inactive;
-                info!("Joint {} deactivated successfully", self.joint_id);
+                debug!("Joint {} feedforward set: torque={:.3}", self.label(), torque);
                 Ok(())
             }
             Payload::Nack { id, error } => {
-                error!("Joint {} deactivate failed: error {}", self.joint_id, error);
+                error!("Joint {} set feedforward failed: error {}", self.label(), error);
                 Err(ProtocolError::IoError(id))
             }
             _ => Err(ProtocolError::InvalidMessage)
         }
     }
-    
-    /// Reset the joint (transition to Unconfigured from any state)
-    pub async fn reset(&self) -> Result<(), ProtocolError> {
-        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::Reset).await?;
-        
+
+    /// Empty the joint's gain schedule, leaving the base gains everywhere
+    pub async fn clear_gain_schedule(&self) -> Result<(), ProtocolError> {
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::ClearGainSchedule).await?;
+
         match response.payload {
             Payload::Ack(_) => {
-                let mut state = self.current_state.write().await;
-                *state = LifecycleState::Unconfigured;
-                info!("Joint {} reset successfully", self.joint_id);
+                info!("Joint {} gain schedule cleared", self.label());
                 Ok(())
             }
             Payload::Nack { id, error } => {
-                error!("Joint {} reset failed: error {}", self.joint_id, error);
+                error!("Joint {} clear gain schedule failed: error {}", self.label(), error);
                 Err(ProtocolError::IoError(id))
             }
             _ => Err(ProtocolError::InvalidMessage)
         }
     }
-    
-    /// Set target position and velocity (only works when joint is Active)
-    pub async fn set_target(&self, target_angle: f32, velocity_limit: f32) -> Result<(), ProtocolError> {
-        let payload = Payload::SetTarget(SetTargetPayload {
-            target_angle,
-            velocity_limit,
-        });
-        
-        let response = self.comm_manager.send_and_wait(self.joint_id, payload).await?;
-        
+
+    /// Choose what the joint's control loop follows (refused while Active)
+    ///
+    /// Switching drops the setpoints of the previous mode.
+    pub async fn set_control_mode(&self, mode: ControlMode) -> Result<(), ProtocolError> {
+        self.require(mode.capability())?;
+        let _guard = self.acquire(false).await?;
+        let response = self.comm_manager.send_and_wait(self.joint_id, Payload::SetControlMode(mode)).await?;
+
         match response.payload {
             Payload::Ack(_) => {
-                debug!("Joint {} target set: angle={}, velocity={}", 
-                       self.joint_id, target_angle, velocity_limit);
+                info!("Joint {} control mode set: {:?}", self.label(), mode);
                 Ok(())
             }
             Payload::Nack { id, error } => {
-                error!("Joint {} set target failed: error {}", self.joint_id, error);
+                error!("Joint {} set control mode failed: error {}", self.label(), error);
                 Err(ProtocolError::IoError(id))
             }
             _ => Err(ProtocolError::InvalidMessage)
         }
     }
-    
+
+    /// Hold a speed in degrees/second (only works when joint is Active in
+    /// `ControlMode::Velocity`)
+    ///
+    /// Refused like [`set_target`](Self::set_target) while interlocked.
+    pub async fn set_velocity(&self, dps: f32) -> Result<(), ProtocolError> {
+        self.send_direct(Payload::SetVelocity { dps }).await?;
+        debug!("Joint {} velocity set: {:.3} deg/s", self.label(), dps);
+        Ok(())
+    }
+
+    /// Apply a torque in newton-metres (only works when joint is Active in
+    /// `ControlMode::Torque`)
+    ///
+    /// Refused like [`set_target`](Self::set_target) while interlocked.
+    pub async fn set_torque(&self, torque_nm: f32) -> Result<(), ProtocolError> {
+        self.send_direct(Payload::SetTorque { torque_nm }).await?;
+        debug!("Joint {} torque set: {:.3} Nm", self.label(), torque_nm);
+        Ok(())
+    }
+
+    /// Send a velocity or torque setpoint
+    async fn send_direct(&self, payload: Payload) -> Result<(), ProtocolError> {
+        self.require(Capabilities::DIRECT_CONTROL)?;
+        self.comm_manager.check_interlocks(InterlockAction::Pause)?;
+        let _guard = self.acquire(true).await?;
+        let command = payload.name();
+        let response = self.comm_manager.send_and_wait(self.joint_id, payload).await?;
+
+        match response.payload {
+            Payload::Ack(_) => Ok(()),
+            Payload::Nack { id, error } => {
+                error!("Joint {} {} failed: error {}", self.label(), command, error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
     /// Get the joint ID
     pub fn id(&self) -> DeviceId {
         self.joint_id
     }
+
+    /// The joint's name and ID for messages, e.g. `elbow (48)`
+    pub fn label(&self) -> String {
+        self.comm_manager.joint_label(self.joint_id)
+    }
+}
+
+/// Angle unit an application works in for a joint
+///
+/// The protocol always carries degrees; the unit is recorded with the joint
+/// so applications can convert at their edge.
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AngleUnit {
+    #[default]
+    Degrees,
+    Radians,
+}
+
+#[cfg(feature = "arm_api")]
+impl AngleUnit {
+    /// Convert a value in this unit to the protocol's degrees
+    pub fn to_degrees(self, value: f32) -> f32 {
+        match self {
+            AngleUnit::Degrees => value,
+            AngleUnit::Radians => value.to_degrees(),
+        }
+    }
+
+    /// Convert a value in the protocol's degrees to this unit
+    pub fn from_degrees(self, degrees: f32) -> f32 {
+        match self {
+            AngleUnit::Degrees => degrees,
+            AngleUnit::Radians => degrees.to_radians(),
+        }
+    }
+
+    fn is_degrees(&self) -> bool {
+        *self == AngleUnit::Degrees
+    }
+}
+
+/// What the arm knows about one of its joints besides its ID
+///
+/// Registered with [`ArmOrchestrator::add_joint_with`]; the name shows up in
+/// log messages and telemetry samples.
+///
+/// ```
+/// use irpc::{AngleUnit, ArmOrchestrator, JointDescriptor};
+///
+/// let mut arm = ArmOrchestrator::new();
+/// arm.add_joint_with(JointDescriptor {
+///     gear_ratio: 50.0,
+///     units: AngleUnit::Radians,
+///     ..JointDescriptor::named(0x0030, "elbow")
+/// });
+/// assert_eq!(arm.joint_name(0x0030), Some("elbow"));
+/// ```
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JointDescriptor {
+    #[serde(rename = "joint_id")]
+    pub id: DeviceId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Entity type the joint is expected to report in discovery
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity_type: Option<u16>,
+    /// Motor turns per output turn
+    #[serde(default = "JointDescriptor::direct_drive", skip_serializing_if = "JointDescriptor::is_direct_drive")]
+    pub gear_ratio: f32,
+    /// Unit the application works in for this joint
+    #[serde(default, skip_serializing_if = "AngleUnit::is_degrees")]
+    pub units: AngleUnit,
+}
+
+#[cfg(feature = "arm_api")]
+impl JointDescriptor {
+    /// An unnamed direct-drive joint in degrees
+    pub fn new(id: DeviceId) -> Self {
+        Self { id, name: None, entity_type: None, gear_ratio: Self::direct_drive(), units: AngleUnit::Degrees }
+    }
+
+    pub fn named(id: DeviceId, name: impl Into<String>) -> Self {
+        Self { name: Some(name.into()), ..Self::new(id) }
+    }
+
+    /// Whether `entity_type` is the expected one, or none is expected
+    pub fn accepts_entity_type(&self, entity_type: u16) -> bool {
+        self.entity_type.is_none_or(|expected| expected == entity_type)
+    }
+
+    fn direct_drive() -> f32 {
+        1.0
+    }
+
+    fn is_direct_drive(ratio: &f32) -> bool {
+        *ratio == 1.0
+    }
+}
+
+/// ARM orchestrator that coordinates multiple joints and manages the system lifecycle
+#[cfg(feature = "arm_api")]
+pub struct ArmOrchestrator {
+    comm_manager: Arc<CommunicationManager>,
+    joints: HashMap<DeviceId, JointProxy>,
+    is_ready: bool,
+    drift_tx: broadcast::Sender<StateDrift>,
+    reconciliation_task: Option<JoinHandle<()>>,
+    interlock_task: Option<JoinHandle<()>>,
+    safety_heartbeat_task: Option<JoinHandle<()>>,
+    sync_task: Option<JoinHandle<()>>,
+    arm_model: Option<ArmModel>,
+    /// Running jog and the signal that stops it
+    jog_task: Option<(oneshot::Sender<()>, JoinHandle<()>)>,
+    groups: BTreeMap<String, JointGroup>,
+    descriptors: HashMap<DeviceId, JointDescriptor>,
+    /// Set by [`shutdown`](Self::shutdown), so dropping does not stop the
+    /// joints again
+    shut_down: bool,
+}
+
+/// Outcome of a command sent to every joint of the arm
+///
+/// Returned by [`ArmOrchestrator::configure_all`] and its siblings, which
+/// carry on past joints that fail, so callers can retry or drop exactly the
+/// joints that did.
+#[cfg(feature = "arm_api")]
+#[must_use]
+#[derive(Debug, Clone, Default)]
+pub struct BulkResult {
+    /// Joints that carried the command out, by joint ID
+    pub succeeded: Vec<DeviceId>,
+    /// Joints that failed and why, by joint ID
+    pub failed: Vec<(DeviceId, ProtocolError)>,
+}
+
+#[cfg(feature = "arm_api")]
+impl BulkResult {
+    /// Whether every joint carried the command out
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// `Err` listing the failed joints, if any
+    pub fn into_result(self) -> Result<(), BulkError> {
+        if self.failed.is_empty() {
+            Ok(())
+        } else {
+            Err(BulkError { failures: self.failed })
+        }
+    }
+}
+
+/// Joints that failed a command sent to the whole arm
+///
+/// See [`BulkResult::into_result`].
+#[cfg(feature = "arm_api")]
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{} joint(s) failed: {}", .failures.len(), describe_failures(.failures))]
+pub struct BulkError {
+    /// Every joint that failed and why, by joint ID
+    pub failures: Vec<(DeviceId, ProtocolError)>,
+}
+
+#[cfg(feature = "arm_api")]
+fn describe_failures(failures: &[(DeviceId, ProtocolError)]) -> String {
+    let described: Vec<String> = failures.iter().map(|(id, e)| format!("joint {} ({})", id, e)).collect();
+    described.join(", ")
+}
+
+/// The failure of the lowest joint ID
+#[cfg(feature = "arm_api")]
+impl From<BulkError> for ProtocolError {
+    fn from(error: BulkError) -> Self {
+        error.failures.into_iter().next().map_or(ProtocolError::InvalidMessage, |(_, e)| e)
+    }
+}
+
+/// Send a command to every joint at once and wait for all of them
+///
+/// One task per joint, so a joint that times out holds up no other. The
+/// outcomes come in the order of `joints`.
+#[cfg(feature = "arm_api")]
+pub(crate) async fn fan_out<'a, F, Fut, T>(
+    joints: impl IntoIterator<Item = &'a JointProxy>,
+    mut command: F,
+) -> Vec<(DeviceId, T)>
+where
+    F: FnMut(JointProxy) -> Fut,
+    Fut: std::future::Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let mut set = tokio::task::JoinSet::new();
+    let mut ids = Vec::new();
+    for (index, joint) in joints.into_iter().enumerate() {
+        let command = command(joint.clone());
+        set.spawn(async move { (index, command.await) });
+        ids.push(joint.id());
+    }
+    let mut outcomes: Vec<Option<T>> = ids.iter().map(|_| None).collect();
+    while let Some(joined) = set.join_next().await {
+        let (index, outcome) = joined.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+        outcomes[index] = Some(outcome);
+    }
+    ids.into_iter().zip(outcomes.into_iter().flatten()).collect()
 }
-/// ARM orchestrator that coordinates multiple joints and manages the system lifecycle
+
+/// Joint states and interlock status of the whole arm
 #[cfg(feature = "arm_api")]
-pub struct ArmOrchestrator {
-    comm_manager: Arc<CommunicationManager>,
-    joints: HashMap<DeviceId, JointProxy>,
-    is_ready: bool,
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemSnapshot {
+    /// Cached lifecycle state of every joint
+    pub joints: HashMap<DeviceId, LifecycleState>,
+    /// Every configured interlock
+    pub interlocks: Vec<InterlockStatus>,
 }
 
 #[cfg(feature = "arm_api")]
 impl ArmOrchestrator {
     /// Create a new ARM orchestrator
     pub fn new() -> Self {
+        Self::with_comm_manager(Arc::new(CommunicationManager::new()))
+    }
+
+    /// Create an orchestrator on an existing communication manager
+    /// (e.g. a [`CommunicationManager::deterministic`] one)
+    pub fn with_comm_manager(comm_manager: Arc<CommunicationManager>) -> Self {
+        let (drift_tx, _) = broadcast::channel(32);
+
         Self {
-            comm_manager: Arc::new(CommunicationManager::new()),
+            comm_manager,
             joints: HashMap::new(),
             is_ready: false,
+            drift_tx,
+            reconciliation_task: None,
+            interlock_task: None,
+            safety_heartbeat_task: None,
+            sync_task: None,
+            arm_model: None,
+            jog_task: None,
+            groups: BTreeMap::new(),
+            descriptors: HashMap::new(),
+            shut_down: false,
         }
     }
     
     /// Add a joint to the orchestrator
     pub fn add_joint(&mut self, joint_id: DeviceId) {
+        self.add_joint_with(JointDescriptor::new(joint_id));
+    }
+
+    /// Add a joint with its name and metadata, replacing any joint with the
+    /// same ID
+    pub fn add_joint_with(&mut self, descriptor: JointDescriptor) {
+        let joint_id = descriptor.id;
         let joint_proxy = JointProxy::new(joint_id, Arc::clone(&self.comm_manager));
         self.joints.insert(joint_id, joint_proxy);
-        info!("Added joint {} to orchestrator", joint_id);
+        self.comm_manager.set_joint_name(joint_id, descriptor.name.clone());
+        self.descriptors.insert(joint_id, descriptor);
+        info!("Added joint {} to orchestrator", self.comm_manager.joint_label(joint_id));
+        if self.interlock_task.is_some() {
+            self.start_interlock_watch();
+        }
+    }
+
+    /// Add an interlock (see [`CommunicationManager::configure_interlock`])
+    ///
+    /// For `InterlockAction::EmergencyStop` interlocks a background task is
+    /// started that emergency-stops every joint as soon as one trips.
+    pub fn configure_interlock(&mut self, config: InterlockConfig) {
+        let estop = config.action == InterlockAction::EmergencyStop;
+        self.comm_manager.configure_interlock(config);
+        if estop && self.interlock_task.is_none() {
+            self.start_interlock_watch();
+        }
+    }
+
+    /// Check every target sent to the arm's joints against `limits` (see
+    /// [`safety`](crate::safety)), or stop checking (`None`)
+    ///
+    /// Give the workspace the [arm model](Self::arm_model) to check it with
+    /// the same kinematics jogging and gravity compensation use.
+    pub fn set_safety_limits(&self, limits: Option<SafetyLimits>) {
+        self.comm_manager.set_safety_limits(limits);
+    }
+
+    /// Targets the safety limits refused or clamped, oldest first
+    pub fn safety_interventions(&self) -> Vec<Intervention> {
+        self.comm_manager.safety_interventions()
+    }
+
+    /// (Re)start the task that reacts to `EmergencyStop` interlocks
+    fn start_interlock_watch(&mut self) {
+        if let Some(task) = self.interlock_task.take() {
+            task.abort();
+        }
+
+        let proxies: Vec<JointProxy> = self.joints.values().cloned().collect();
+        let mut events = self.comm_manager.subscribe_interlocks();
+        self.interlock_task = Some(tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                // Already stopped when it was tripped before
+                if event.status.config.action != InterlockAction::EmergencyStop
+                    || !event.status.is_active()
+                    || event.previous == InterlockState::Tripped
+                {
+                    continue;
+                }
+                warn!("Interlock '{}' tripped - emergency stop", event.status.config.name);
+                for proxy in &proxies {
+                    if let Err(e) = proxy.emergency_stop().await {
+                        error!("Failed to reset joint {} for interlock: {:?}", proxy.label(), e);
+                    }
+                }
+            }
+        }));
     }
     
     /// Get a reference to a joint proxy
     pub fn get_joint(&self, joint_id: DeviceId) -> Option<&JointProxy> {
         self.joints.get(&joint_id)
     }
+
+    /// Name, entity type, gear ratio and units a joint was added with
+    pub fn descriptor(&self, joint_id: DeviceId) -> Option<&JointDescriptor> {
+        self.descriptors.get(&joint_id)
+    }
+
+    /// Give one of the arm's joints a name, used in log messages and
+    /// telemetry and kept in the saved [configuration](Self::save_config)
+    pub fn set_joint_name(&mut self, joint_id: DeviceId, name: impl Into<String>) {
+        if let Some(descriptor) = self.descriptors.get_mut(&joint_id) {
+            descriptor.name = Some(name.into());
+            self.comm_manager.set_joint_name(joint_id, descriptor.name.clone());
+        }
+    }
+
+    pub fn joint_name(&self, joint_id: DeviceId) -> Option<&str> {
+        self.descriptors.get(&joint_id)?.name.as_deref()
+    }
+
+    /// Name a set of the arm's joints to command together (see
+    /// [`group`](crate::group))
+    ///
+    /// Replaces a group of the same name. Joints may belong to several
+    /// groups; the group covers the joints' current proxies, so recreate it
+    /// after re-adding one of them.
+    pub fn create_group(
+        &mut self,
+        name: impl Into<String>,
+        joint_ids: impl IntoIterator<Item = DeviceId>,
+    ) -> Result<&JointGroup, GroupError> {
+        let name = name.into();
+        let mut joints: Vec<JointProxy> = Vec::new();
+        for id in joint_ids {
+            if joints.iter().any(|joint| joint.id() == id) {
+                return Err(GroupError::DuplicateJoint(id));
+            }
+            joints.push(self.joints.get(&id).cloned().ok_or(GroupError::UnknownJoint(id))?);
+        }
+        info!("Created joint group '{}' with {} joints", name, joints.len());
+        self.groups.insert(name.clone(), JointGroup::new(name.clone(), joints));
+        Ok(&self.groups[&name])
+    }
+
+    pub fn group(&self, name: &str) -> Option<&JointGroup> {
+        self.groups.get(name)
+    }
+
+    pub fn remove_group(&mut self, name: &str) -> Option<JointGroup> {
+        self.groups.remove(name)
+    }
+
+    /// Every group, by name
+    pub fn groups(&self) -> impl Iterator<Item = &JointGroup> {
+        self.groups.values()
+    }
     
     /// Configure all joints in the system
-    pub async fn configure_all(&mut self) -> Result<(), ProtocolError> {
+    ///
+    /// Every joint gets its `Configure` at once, so startup takes as long
+    /// as the slowest joint. All joints are waited for, whether or not
+    /// others fail.
+    pub async fn configure_all(&mut self) -> BulkResult {
         info!("Configuring all joints in the system");
-        
-        for (joint_id, joint) in &self.joints {
-            match joint.configure().await {
-                Ok(_) => info!("Joint {} configured successfully", joint_id),
-                Err(e) => {
-                    error!("Failed to configure joint {}: {:?}", joint_id, e);
-                    return Err(e);
-                }
-            }
+        let result = self.command_all("configure", |joint| async move { joint.configure().await }).await;
+        if result.is_success() {
+            info!("All joints configured successfully");
         }
-        
-        info!("All joints configured successfully");
-        Ok(())
+        result
     }
     
-    /// Activate all joints in the system
-    pub async fn activate_all(&mut self) -> Result<(), ProtocolError> {
+    /// Activate all joints in the system, concurrently like
+    /// [`configure_all`](Self::configure_all)
+    ///
+    /// The system is ready once every joint is active.
+    pub async fn activate_all(&mut self) -> BulkResult {
         info!("Activating all joints in the system");
-        
-        for (joint_id, joint) in &self.joints {
-            match joint.activate().await {
-                Ok(_) => info!("Joint {} activated successfully", joint_id),
-                Err(e) => {
-                    error!("Failed to activate joint {}: {:?}", joint_id, e);
-                    return Err(e);
-                }
-            }
+        let result = self.command_all("activate", |joint| async move { joint.activate().await }).await;
+        if result.is_success() {
+            self.is_ready = true;
+            info!("ARM system is now ready - all joints activated");
         }
-        
-        self.is_ready = true;
-        info!("ARM system is now ready - all joints activated");
-        Ok(())
+        result
     }
     
-    /// Deactivate all joints in the system
-    pub async fn deactivate_all(&mut self) -> Result<(), ProtocolError> {
+    /// Deactivate all joints in the system, concurrently like
+    /// [`configure_all`](Self::configure_all)
+    pub async fn deactivate_all(&mut self) -> BulkResult {
         info!("Deactivating all joints in the system");
-        
-        for (joint_id, joint) in &self.joints {
-            match joint.deactivate().await {
-                Ok(_) => info!("Joint {} deactivated successfully", joint_id),
+        let result = self.command_all("deactivate", |joint| async move { joint.deactivate().await }).await;
+        self.is_ready = false;
+        info!("{} joints deactivated", result.succeeded.len());
+        result
+    }
+    
+    /// Emergency stop - reset all joints immediately and at once
+    pub async fn emergency_stop(&mut self) -> BulkResult {
+        warn!("Emergency stop initiated - resetting all joints");
+        let result = self.command_all("reset", |joint| async move { joint.emergency_stop().await }).await;
+        self.is_ready = false;
+        warn!("Emergency stop completed, {} joints failed to reset", result.failed.len());
+        result
+    }
+
+    /// Bring the arm to rest and release the bus
+    ///
+    /// In order:
+    /// - the jog, sync, reconciliation and interlock tasks stop
+    /// - requests in flight get up to `SHUTDOWN_DRAIN_TIMEOUT_MS` to be
+    ///   answered
+    /// - every joint cached as Active is deactivated, all at once; a joint
+    ///   that fails to is reset as by [`emergency_stop`](Self::emergency_stop)
+    /// - the safety heartbeat stops
+    /// - the communication manager is [closed](CommunicationManager::close):
+    ///   queued messages go out and the adapter (e.g. a
+    ///   [`TelemetryRecorder`](crate::bus::record::TelemetryRecorder)) is
+    ///   flushed
+    ///
+    /// Joints that were not Active count as succeeded; the failures are the
+    /// joints that could not be deactivated. Afterwards commands through
+    /// this orchestrator's joints fail, and dropping it sends nothing.
+    pub async fn shutdown(&mut self) -> BulkResult {
+        info!("Shutting down the arm");
+        self.stop_jog().await;
+        self.stop_sync();
+        self.stop_reconciliation();
+        if let Some(task) = self.interlock_task.take() {
+            task.abort();
+        }
+        self.drain_requests().await;
+
+        let result = self.command_all("deactivate", |joint| async move {
+            if joint.get_state().await != LifecycleState::Active {
+                return Ok(());
+            }
+            let deactivated = joint.deactivate().await;
+            if deactivated.is_err() {
+                let _ = joint.emergency_stop().await;
+            }
+            deactivated
+        }).await;
+        self.is_ready = false;
+        self.stop_safety_heartbeat();
+
+        self.comm_manager.close().await;
+        self.shut_down = true;
+        info!("Arm shut down, {} joints failed to deactivate", result.failed.len());
+        result
+    }
+
+    /// Wait for the requests in flight to be answered or time out, up to
+    /// `SHUTDOWN_DRAIN_TIMEOUT_MS`
+    async fn drain_requests(&self) {
+        let clock = self.comm_manager.clock();
+        let deadline = clock.now() + std::time::Duration::from_millis(SHUTDOWN_DRAIN_TIMEOUT_MS);
+        loop {
+            self.comm_manager.sweep_pending().await;
+            let pending = self.comm_manager.pending_requests().await;
+            if pending == 0 {
+                return;
+            }
+            if clock.now() >= deadline {
+                warn!("Shutting down with {} requests unanswered", pending);
+                return;
+            }
+            clock.sleep(std::time::Duration::from_millis(ADAPTER_POLL_INTERVAL_MS)).await;
+        }
+    }
+
+    /// Reset every joint that may be moving, without waiting for it
+    ///
+    /// Joints whose cached state is being changed count as moving. Commands
+    /// queued for a reset joint are dropped, as by an emergency stop.
+    fn safety_stop(&self) {
+        for joint in self.joints.values() {
+            let idle = joint.current_state.try_read().is_ok_and(|state| *state != LifecycleState::Active);
+            if idle {
+                continue;
+            }
+            joint.gate.estop_epoch.fetch_add(1, Ordering::SeqCst);
+            match self.comm_manager.send_now(joint.id(), Payload::Reset) {
+                Ok(()) => warn!("Joint {} reset: orchestrator dropped without shutting down", joint.label()),
+                Err(e) => error!("Failed to reset joint {} on drop: {:?}", joint.label(), e),
+            }
+        }
+    }
+
+    /// Self-test every joint at once before operation (see
+    /// [`JointProxy::self_test`]), by joint ID
+    ///
+    /// Joints that cannot run the motion test (not Active) fail with the
+    /// joint's refusal; pass 0 to check only the encoders, drivers and bus.
+    pub async fn self_test_all(&self, motion_deg: f32) -> BTreeMap<DeviceId, Result<SelfTestResult, ProtocolError>> {
+        let test = move |joint: JointProxy| async move { joint.self_test(motion_deg).await };
+        let results: BTreeMap<_, _> = fan_out(self.joints.values(), test).await.into_iter().collect();
+        let failed = results.values().filter(|result| !result.as_ref().is_ok_and(SelfTestResult::passed)).count();
+        match failed {
+            0 => info!("All {} joints passed their self-test", results.len()),
+            _ => warn!("{} of {} joints failed their self-test", failed, results.len()),
+        }
+        results
+    }
+
+    /// Run `command` on every joint concurrently
+    async fn command_all<F, Fut>(&self, action: &str, command: F) -> BulkResult
+    where
+        F: FnMut(JointProxy) -> Fut,
+        Fut: std::future::Future<Output = Result<(), ProtocolError>> + Send + 'static,
+    {
+        let mut joints: Vec<&JointProxy> = self.joints.values().collect();
+        joints.sort_by_key(|joint| joint.id());
+        let mut result = BulkResult::default();
+        for (joint_id, outcome) in fan_out(joints, command).await {
+            match outcome {
+                Ok(()) => {
+                    debug!("Joint {}: {} done", self.comm_manager.joint_label(joint_id), action);
+                    result.succeeded.push(joint_id);
+                }
                 Err(e) => {
-                    error!("Failed to deactivate joint {}: {:?}", joint_id, e);
-                    // Continue with other joints even if one fails
+                    error!("Failed to {} joint {}: {:?}", action, self.comm_manager.joint_label(joint_id), e);
+                    result.failed.push((joint_id, e));
                 }
             }
         }
-        
-        self.is_ready = false;
-        info!("All joints deactivated");
+        result
+    }
+
+    /// Bring joints up in the order of a startup plan (see
+    /// [`startup`](crate::startup))
+    ///
+    /// Every joint of the plan must belong to the arm. The system is ready
+    /// once a plan covering all of its joints has completed.
+    pub async fn start_up(&mut self, plan: &StartupPlan) -> Result<(), StartupError> {
+        let stages = plan.stages()?;
+        if let Some(id) = plan.joint_ids().into_iter().find(|id| !self.joints.contains_key(id)) {
+            return Err(StartupError::UnknownJoint(id));
+        }
+
+        info!("Starting up {} joints in {} stages", plan.joints.len(), stages.len());
+        for (index, stage) in stages.iter().enumerate() {
+            for (joint_id, outcome) in fan_out(stage.iter().map(|id| &self.joints[id]), Self::bring_up).await {
+                if let Err((step, error)) = outcome {
+                    let joint = self.comm_manager.joint_label(joint_id);
+                    error!("Startup stopped at stage {}: joint {} failed to {}", index + 1, joint, step);
+                    return Err(StartupError::Failed { joint_id, step, error });
+                }
+            }
+            info!("Startup stage {} active: {:?}", index + 1, stage);
+        }
+
+        let braked = plan.braked_joints();
+        let release = |joint: JointProxy| async move { joint.set_brake(false).await };
+        for (joint_id, outcome) in fan_out(braked.iter().map(|id| &self.joints[id]), release).await {
+            outcome.map_err(|error| StartupError::Failed { joint_id, step: StartupStep::ReleaseBrake, error })?;
+        }
+
+        let ids = plan.joint_ids();
+        self.is_ready = self.joints.keys().all(|id| ids.contains(id));
+        info!("Startup complete");
         Ok(())
     }
+
+    /// Step a joint through its lifecycle until it is Active
+    async fn bring_up(joint: JointProxy) -> Result<(), (StartupStep, ProtocolError)> {
+        loop {
+            match joint.get_state().await {
+                LifecycleState::Unconfigured => joint.configure().await.map_err(|e| (StartupStep::Configure, e))?,
+                LifecycleState::Inactive => joint.activate().await.map_err(|e| (StartupStep::Activate, e))?,
+                LifecycleState::Active => return Ok(()),
+                // Calibrating or faulted; not for startup to resolve
+                _ => return Err((StartupStep::Activate, ProtocolError::InvalidStateTransition)),
+            }
+        }
+    }
     
-    /// Emergency stop - reset all joints immediately
-    pub async fn emergency_stop(&mut self) -> Result<(), ProtocolError> {
-        warn!("Emergency stop initiated - resetting all joints");
-        
-        for (joint_id, joint) in &self.joints {
-            match joint.reset().await {
-                Ok(_) => info!("Joint {} reset successfully", joint_id),
-                Err(e) => {
-                    error!("Failed to reset joint {} during emergency stop: {:?}", joint_id, e);
-                    // Continue with other joints even if one fails
+    /// Set the kinematic and mass model of the arm, used for gravity
+    /// compensation and jogging
+    pub fn set_arm_model(&mut self, model: ArmModel) {
+        self.arm_model = Some(model);
+    }
+
+    pub fn arm_model(&self) -> Option<&ArmModel> {
+        self.arm_model.as_ref()
+    }
+
+    /// Send every joint of the arm model the torque that holds the arm
+    /// against gravity at `angles` (degrees, in [`ArmModel::joint_ids`]
+    /// order)
+    ///
+    /// Joints must be Active. Stops at the first joint that refuses.
+    pub async fn send_gravity_feedforward(&self, angles: &[f32]) -> Result<(), KinematicsError> {
+        let model = self.arm_model.as_ref().ok_or(KinematicsError::NoModel)?;
+        let torques = model.gravity_torques(angles)?;
+        for (link, torque) in model.links().iter().zip(torques) {
+            let joint = self.joints.get(&link.joint_id).ok_or(KinematicsError::UnknownJoint(link.joint_id))?;
+            joint.set_feedforward(torque).await?;
+        }
+        Ok(())
+    }
+
+    /// Move the flange along `direction` at `speed` (m/s, or degrees/second
+    /// for rotations; negative reverses) until [`stop_jog`](Self::stop_jog)
+    ///
+    /// Reads where the arm model's joints are, then a background task sends
+    /// them `SetTargetV2` updates at `JOG_UPDATE_RATE_HZ`, each one update
+    /// ahead. Every joint moves at its share of the Cartesian motion; when
+    /// one would exceed its velocity limit (`JOG_MAX_JOINT_VELOCITY_DPS`
+    /// without limits) all slow down together, so the flange keeps its
+    /// direction. Gravity feedforward goes out with every update. The jog
+    /// ends by itself before a joint would leave its position limits, or
+    /// when a joint refuses a command (a `Pause` interlock, say).
+    ///
+    /// Replaces a jog already running.
+    pub async fn jog(&mut self, direction: CartesianAxis, speed: f64) -> Result<(), KinematicsError> {
+        self.stop_jog().await;
+        let model = self.arm_model.clone().ok_or(KinematicsError::NoModel)?;
+        let mut joints = Vec::with_capacity(model.links().len());
+        let mut angles = Vec::with_capacity(model.links().len());
+        for id in model.joint_ids() {
+            let joint = self.joints.get(&id).ok_or(KinematicsError::UnknownJoint(id))?;
+            angles.push(joint.read_multi_turn_position().await?.degrees());
+            joints.push((joint.clone(), joint.limits().await));
+        }
+
+        info!("Jogging {:?} at {}", direction, speed);
+        let clock = self.comm_manager.clock();
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            let period = std::time::Duration::from_secs_f64(1.0 / JOG_UPDATE_RATE_HZ as f64);
+            let start = clock.now();
+            let mut stop = stop_rx;
+            let mut tick = 0;
+            while let Some(next) = Self::jog_step(&model, &joints, &angles, direction, speed, period).await {
+                angles = next;
+                tick += 1;
+                tokio::select! {
+                    _ = clock.sleep_until(start + period * tick) => {}
+                    _ = &mut stop => return,
                 }
             }
+        });
+        self.jog_task = Some((stop_tx, task));
+        Ok(())
+    }
+
+    /// Stop a running jog; the joints come to rest within one more update
+    pub async fn stop_jog(&mut self) {
+        if let Some((stop, task)) = self.jog_task.take() {
+            let _ = stop.send(());
+            let _ = task.await;
+            info!("Jog stopped");
         }
-        
-        self.is_ready = false;
-        warn!("Emergency stop completed");
+    }
+
+    /// Whether a jog is running
+    pub fn is_jogging(&self) -> bool {
+        self.jog_task.as_ref().is_some_and(|(_, task)| !task.is_finished())
+    }
+
+    /// Send the targets one jog period ahead of `angles`; the angles sent,
+    /// or `None` once the jog has to end
+    async fn jog_step(
+        model: &ArmModel,
+        joints: &[(JointProxy, Option<JointLimits>)],
+        angles: &[f32],
+        direction: CartesianAxis,
+        speed: f64,
+        period: std::time::Duration,
+    ) -> Option<Vec<f32>> {
+        // Angles come one per model joint, so the model cannot refuse them
+        let mut rates = model.joint_rates(angles, direction, speed).ok()?;
+        let overshoot = rates
+            .iter()
+            .zip(joints)
+            .map(|(rate, (_, limits))| rate.abs() / limits.map_or(JOG_MAX_JOINT_VELOCITY_DPS, |l| l.max_velocity))
+            .fold(1.0, f32::max);
+        let mut next = Vec::with_capacity(angles.len());
+        for ((rate, angle), (joint, limits)) in rates.iter_mut().zip(angles).zip(joints) {
+            *rate /= overshoot;
+            let target = angle + *rate * period.as_secs_f32();
+            if limits.is_some_and(|l| target < l.min_position || target > l.max_position) {
+                warn!("Jog stopped at the position limits of joint {}", joint.label());
+                return None;
+            }
+            next.push(target);
+        }
+
+        let torques = model.gravity_torques(&next).ok()?;
+        for (((joint, _), target), (rate, torque)) in joints.iter().zip(&next).zip(rates.iter().zip(torques)) {
+            let setpoint = SetTargetPayloadV2 {
+                target_angle: *target,
+                max_velocity: rate.abs(),
+                target_velocity: 0.0,
+                max_acceleration: 0.0,
+                max_deceleration: 0.0,
+                max_jerk: 0.0,
+                profile: MotionProfile::Trapezoidal,
+                max_current: 0.0,
+                max_temperature: 0.0,
+            };
+            let sent = match joint.set_feedforward(torque).await {
+                Ok(()) => joint.set_target_v2(setpoint).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = sent {
+                warn!("Jog stopped: joint {} refused an update: {:?}", joint.label(), e);
+                return None;
+            }
+        }
+        Some(next)
+    }
+
+    /// Start running a motion sequence in the background
+    ///
+    /// Every joint it commands must belong to the arm. Watch and cancel it
+    /// through the returned handle; see [`sequence`](crate::sequence).
+    pub fn start_sequence(&self, sequence: MotionSequence) -> Result<SequenceHandle, SequenceError> {
+        let joints = sequence
+            .joints()
+            .into_iter()
+            .map(|id| self.joints.get(&id).map(|joint| (id, joint.clone())).ok_or(SequenceError::UnknownJoint(id)))
+            .collect::<Result<_, _>>()?;
+        Ok(SequenceHandle::start(sequence, joints, self.comm_manager.clock()))
+    }
+
+    /// Run a motion sequence to the end
+    pub async fn run_sequence(&self, sequence: MotionSequence) -> Result<(), SequenceError> {
+        self.start_sequence(sequence)?.wait().await
+    }
+
+    /// Stream a trajectory to its joints as position setpoints
+    ///
+    /// Checks that every joint belongs to the arm and that the motion stays
+    /// within the limits last applied through each proxy, resamples it to
+    /// `rate_hz` (`TRAJECTORY_STREAM_RATE_HZ` is a sensible default), then
+    /// sends one `SetTarget` per joint per sample on a fixed-rate timer.
+    /// While a `Pause` interlock is active streaming holds at the current
+    /// sample and resumes from there once it clears.
+    ///
+    /// With an [arm model](Self::set_arm_model) whose joints the trajectory
+    /// all moves, each sample's gravity torques go out as `SetFeedforward`
+    /// ahead of its targets.
+    pub async fn stream_trajectory(&self, trajectory: &Trajectory, rate_hz: u32) -> Result<(), TrajectoryError> {
+        let mut joints = Vec::with_capacity(trajectory.joints().len());
+        let mut limits = HashMap::new();
+        for id in trajectory.joints() {
+            let joint = self.joints.get(id).ok_or(TrajectoryError::UnknownJoint(*id))?;
+            if let Some(joint_limits) = joint.limits().await {
+                limits.insert(*id, joint_limits);
+            }
+            joints.push(joint);
+        }
+        trajectory.validate(&limits)?;
+
+        // Trajectory column of each model joint, if it covers them all
+        let model_columns: Option<Vec<usize>> = self.arm_model.as_ref().and_then(|model| {
+            model
+                .joint_ids()
+                .iter()
+                .map(|id| trajectory.joints().iter().position(|j| j == id))
+                .collect()
+        });
+        if self.arm_model.is_some() && model_columns.is_none() {
+            warn!("Trajectory does not move every joint of the arm model, streaming without gravity feedforward");
+        }
+
+        let samples = trajectory.resample(rate_hz)?;
+        let clock = self.comm_manager.clock();
+        let period = std::time::Duration::from_secs_f64(1.0 / rate_hz as f64);
+        let mut start = clock.now();
+        let mut previous = &samples.waypoints()[0].angles;
+        for (index, waypoint) in samples.waypoints().iter().enumerate() {
+            // Deadlines from the start, so slow sends do not accumulate drift
+            clock.sleep_until(start + period * index as u32).await;
+            if self.comm_manager.check_interlocks(InterlockAction::Pause).is_err() {
+                let paused_at = clock.now();
+                self.comm_manager.wait_for_interlocks(InterlockAction::Pause).await;
+                start += clock.now() - paused_at;
+                info!("Interlocks clear, resuming trajectory at sample {}", index);
+            }
+            if let (Some(model), Some(columns)) = (&self.arm_model, &model_columns) {
+                let angles: Vec<f32> = columns.iter().map(|&c| waypoint.angles[c]).collect();
+                // One angle per model joint, so the model cannot refuse them
+                let torques = model.gravity_torques(&angles).unwrap_or_default();
+                for (&column, torque) in columns.iter().zip(torques) {
+                    joints[column].set_feedforward(torque).await?;
+                }
+            }
+            for (j, joint) in joints.iter().enumerate() {
+                // Never below the speed needed to reach this sample in one period
+                let step = (waypoint.angles[j] - previous[j]).abs() * rate_hz as f32;
+                let velocity = samples.velocity(index, j).abs().max(step);
+                joint.set_target(waypoint.angles[j], velocity).await?;
+            }
+            previous = &waypoint.angles;
+        }
+
+        info!("Streamed {} samples to {} joints", samples.waypoints().len(), joints.len());
         Ok(())
     }
-    
+
+    /// Push a bus schedule's telemetry settings to its joints
+    ///
+    /// Joints in the schedule that are not part of the arm are skipped with
+    /// a warning; the first joint that refuses stops the rollout.
+    pub async fn apply_schedule(&self, schedule: &Schedule) -> Result<(), ProtocolError> {
+        for entry in &schedule.joints {
+            let Some(joint) = self.joints.get(&entry.joint_id) else {
+                warn!("Scheduled joint {} is not part of the arm", entry.joint_id);
+                continue;
+            };
+            joint.configure_telemetry(entry.telemetry_config()).await?;
+        }
+        info!("Applied bus schedule to {} joints ({:.0}% peak cycle load)",
+              schedule.joints.len(), schedule.peak_load * 100.0);
+        Ok(())
+    }
+
     /// Check if the ARM system is ready (all joints active)
     pub fn is_ready(&self) -> bool {
         self.is_ready
@@ -390,11 +3737,302 @@ impl ArmOrchestrator {
         
         status
     }
+
+    /// Joints on the bus that are running their bootloader
+    ///
+    /// Runs a discovery round and returns the IDs of devices answering in
+    /// [`BootMode::Bootloader`], whether or not they were added to the
+    /// orchestrator: an interrupted update, or an application image the
+    /// bootloader rejected. They need re-flashing before they can be
+    /// configured.
+    pub async fn find_bootloader_joints(&self) -> Result<Vec<DeviceId>, ProtocolError> {
+        let stuck: Vec<DeviceId> = self
+            .comm_manager
+            .discover()
+            .await?
+            .into_iter()
+            .filter(|device| device.mode == BootMode::Bootloader)
+            .map(|device| device.id)
+            .collect();
+        for joint_id in &stuck {
+            warn!("Joint {} is in its bootloader", joint_id);
+        }
+        Ok(stuck)
+    }
+
+    /// Give the unaddressed joint with `serial_number` the node ID `new_id`
+    /// and add it to the orchestrator
+    ///
+    /// The joint persists the ID in its config store before acknowledging,
+    /// so it keeps it across power cycles. IDs that cannot address a joint
+    /// (broadcast, the arm's own and `UNADDRESSED_DEVICE_ID`) are refused
+    /// with `InvalidMessage`; a serial number no joint has times out.
+    pub async fn commission_joint(&mut self, serial_number: u64, new_id: DeviceId) -> Result<(), ProtocolError> {
+        if matches!(new_id, BROADCAST_ADDRESS | ARM_DEVICE_ID | UNADDRESSED_DEVICE_ID) {
+            return Err(ProtocolError::InvalidMessage);
+        }
+        let assign = Payload::AssignId(AssignIdPayload { serial_number, new_id });
+        let response = self.comm_manager.send_and_wait(BROADCAST_ADDRESS, assign).await?;
+
+        match response.payload {
+            Payload::Ack(_) => {
+                info!("Joint {:#018x} commissioned as {}", serial_number, new_id);
+                self.add_joint(new_id);
+                Ok(())
+            }
+            Payload::Nack { id, error } => {
+                error!("Joint {:#018x} failed to store ID {}: error {}", serial_number, new_id, error);
+                Err(ProtocolError::IoError(id))
+            }
+            _ => Err(ProtocolError::InvalidMessage)
+        }
+    }
+
+    /// Joint states plus interlock status, for dashboards and logs
+    pub async fn snapshot(&self) -> SystemSnapshot {
+        SystemSnapshot {
+            joints: self.get_system_status().await,
+            interlocks: self.comm_manager.interlocks(),
+        }
+    }
+    
+    /// The arm's joints, descriptors, applied limits and encoder offsets and
+    /// its model (see [`arm_config`](crate::arm_config))
+    pub async fn config(&self) -> ArmConfig {
+        let mut joints = Vec::with_capacity(self.joints.len());
+        for (id, joint) in &self.joints {
+            joints.push(JointEntry {
+                descriptor: self.descriptors.get(id).cloned().unwrap_or_else(|| JointDescriptor::new(*id)),
+                limits: joint.limits().await,
+                encoder_offset: joint.encoder_offset().await,
+            });
+        }
+        joints.sort_by_key(|joint| joint.descriptor.id);
+        ArmConfig { joints, arm_model: self.arm_model.clone() }
+    }
+
+    /// Add the configuration's joints and model to the arm and apply their
+    /// encoder offsets and limits
+    ///
+    /// Joints already in the arm keep their proxies and take the
+    /// configuration's descriptors. Offsets and limits are sent to the
+    /// joints, so they must be on the bus and not Active (limits also need a
+    /// known position); the first joint that refuses stops the rest.
+    pub async fn apply_config(&mut self, config: &ArmConfig) -> Result<(), ArmConfigError> {
+        config.validate()?;
+        for entry in &config.joints {
+            let descriptor = entry.descriptor.clone();
+            if self.joints.contains_key(&descriptor.id) {
+                self.comm_manager.set_joint_name(descriptor.id, descriptor.name.clone());
+                self.descriptors.insert(descriptor.id, descriptor);
+            } else {
+                self.add_joint_with(descriptor);
+            }
+        }
+        if let Some(model) = &config.arm_model {
+            self.set_arm_model(model.clone());
+        }
+
+        for entry in &config.joints {
+            let joint = &self.joints[&entry.descriptor.id];
+            let refused = |error| ArmConfigError::Joint { joint_id: entry.descriptor.id, error };
+            // The joint drops its limits when the offset changes
+            if let Some(offset) = entry.encoder_offset {
+                joint.set_encoder_offset(offset).await.map_err(refused)?;
+            }
+            if let Some(limits) = entry.limits {
+                joint.set_limits(limits).await.map_err(refused)?;
+            }
+        }
+        info!("Applied configuration of {} joints", config.joints.len());
+        Ok(())
+    }
+
+    /// Load a configuration file (see [`ArmConfig::load`]) and apply it
+    pub async fn load_config(&mut self, path: impl AsRef<std::path::Path>) -> Result<(), ArmConfigError> {
+        let config = ArmConfig::load(path)?;
+        self.apply_config(&config).await
+    }
+
+    /// Save the arm's [configuration](Self::config) to a file (see
+    /// [`ArmConfig::save`])
+    pub async fn save_config(&self, path: impl AsRef<std::path::Path>) -> Result<(), ArmConfigError> {
+        self.config().await.save(path)
+    }
     
     /// Process incoming message (should be called by background task)
     pub async fn process_incoming_message(&self, message: Message) {
         self.comm_manager.process_incoming(message).await;
     }
+
+    /// Subscribe to joint telemetry (see [`CommunicationManager::subscribe_telemetry`])
+    pub fn subscribe_telemetry(&self, filter: TelemetryFilter) -> TelemetrySubscriber {
+        self.comm_manager.subscribe_telemetry(filter)
+    }
+
+    /// Subscribe to joint crash reports (see [`CommunicationManager::subscribe_crashes`])
+    pub fn subscribe_crashes(&self) -> broadcast::Receiver<JointCrashed> {
+        self.comm_manager.subscribe_crashes()
+    }
+
+    /// Subscribe to calibration reports (see [`CommunicationManager::subscribe_calibration`])
+    pub fn subscribe_calibration(&self) -> broadcast::Receiver<CalibrationEvent> {
+        self.comm_manager.subscribe_calibration()
+    }
+
+    /// Subscribe to state drift events found by reconciliation
+    pub fn subscribe_drift(&self) -> broadcast::Receiver<StateDrift> {
+        self.drift_tx.subscribe()
+    }
+
+    /// Run one reconciliation sweep over all joints
+    ///
+    /// Each joint's authoritative state is queried and compared with its
+    /// proxy cache; caches are corrected and drifts are returned and
+    /// published to `subscribe_drift()` receivers. Joints that fail to
+    /// answer are skipped and retried on the next sweep.
+    pub async fn reconcile_all(&self) -> Vec<StateDrift> {
+        let proxies: Vec<JointProxy> = self.joints.values().cloned().collect();
+        Self::reconcile_proxies(&proxies, &self.drift_tx).await
+    }
+
+    /// Start a background task that reconciles all joints every `interval`
+    ///
+    /// This is meant to run at a low rate (`RECONCILE_INTERVAL_MS` is a
+    /// sensible default) as a safety net for lost status updates.
+    /// The task covers the joints registered at the time of the call;
+    /// restart it after adding joints. Replaces any previously running task.
+    pub fn start_reconciliation(&mut self, interval: std::time::Duration) {
+        self.stop_reconciliation();
+
+        let proxies: Vec<JointProxy> = self.joints.values().cloned().collect();
+        let drift_tx = self.drift_tx.clone();
+        let clock = self.comm_manager.clock();
+
+        info!("Starting state reconciliation every {:?} for {} joints", interval, proxies.len());
+        self.reconciliation_task = Some(tokio::spawn(async move {
+            let mut next = clock.now();
+            loop {
+                clock.sleep_until(next).await;
+                Self::reconcile_proxies(&proxies, &drift_tx).await;
+                // A slow sweep delays the next one instead of bunching them up
+                next = (next + interval).max(clock.now());
+            }
+        }));
+    }
+
+    /// Keep the arm's joints enabled with a `SafetyEnable` heartbeat
+    ///
+    /// A background task sends every joint not known to lack
+    /// `Capabilities::SAFETY_ENABLE` a fresh token with `ttl` (up to
+    /// 65.535 s), `SAFETY_HEARTBEATS_PER_TTL` times per TTL. Joints that
+    /// require it stop and deactivate once it runs out, so if this process
+    /// hangs or loses the bus, the arm stops within `ttl` plus the joints'
+    /// stop time. Start it before activating the joints.
+    pub fn start_safety_heartbeat(&mut self, ttl: std::time::Duration) {
+        self.stop_safety_heartbeat();
+
+        let joints: Vec<DeviceId> = self.joints.keys().copied().collect();
+        let comm_manager = Arc::clone(&self.comm_manager);
+        let clock = comm_manager.clock();
+        let ttl_ms = ttl.as_millis().min(u16::MAX as u128) as u16;
+        let period = ttl / SAFETY_HEARTBEATS_PER_TTL;
+        // Tokens need only differ from the last one a joint saw, which may
+        // have come from an earlier run
+        let mut token = clock.now().as_micros() as u32;
+
+        info!("Starting safety heartbeat every {:?} for {} joints", period, joints.len());
+        self.safety_heartbeat_task = Some(tokio::spawn(async move {
+            let mut next = clock.now();
+            loop {
+                clock.sleep_until(next).await;
+                token = token.wrapping_add(1);
+                for &joint_id in &joints {
+                    if comm_manager.capabilities(joint_id).is_some_and(|c| !c.contains(Capabilities::SAFETY_ENABLE)) {
+                        continue;
+                    }
+                    let heartbeat = Payload::SafetyEnable { token, ttl_ms };
+                    if let Err(e) = comm_manager.send_fire_and_forget(joint_id, heartbeat).await {
+                        warn!("Safety heartbeat to joint {} failed: {:?}", comm_manager.joint_label(joint_id), e);
+                    }
+                }
+                next = (next + period).max(clock.now());
+            }
+        }));
+    }
+
+    /// Stop the safety heartbeat, if running; joints that require it stop
+    /// once their enable runs out
+    pub fn stop_safety_heartbeat(&mut self) {
+        if let Some(task) = self.safety_heartbeat_task.take() {
+            task.abort();
+            info!("Safety heartbeat stopped");
+        }
+    }
+
+    /// Broadcast a `Sync` every `period`, for joints in
+    /// `ControlMode::CyclicPosition`
+    ///
+    /// Such joints buffer the targets sent between two `Sync`s and all move
+    /// to them at the next one, however far apart the targets arrived; each
+    /// answers with a `SyncSample` of its position at that instant (see
+    /// [`CommunicationManager::sync_samples`]).
+    pub fn start_sync(&mut self, period: std::time::Duration) {
+        self.stop_sync();
+
+        let comm_manager = Arc::clone(&self.comm_manager);
+        let clock = comm_manager.clock();
+
+        info!("Starting sync every {:?}", period);
+        self.sync_task = Some(tokio::spawn(async move {
+            let mut next = clock.now();
+            loop {
+                clock.sleep_until(next).await;
+                if let Err(e) = comm_manager.send_sync().await {
+                    warn!("Sync failed: {:?}", e);
+                }
+                next = (next + period).max(clock.now());
+            }
+        }));
+    }
+
+    /// Stop broadcasting `Sync`, if running; cyclic joints then hold the
+    /// target they latched last
+    pub fn stop_sync(&mut self) {
+        if let Some(task) = self.sync_task.take() {
+            task.abort();
+            info!("Sync stopped");
+        }
+    }
+
+    /// Stop the background reconciliation task, if running
+    pub fn stop_reconciliation(&mut self) {
+        if let Some(task) = self.reconciliation_task.take() {
+            task.abort();
+            info!("State reconciliation stopped");
+        }
+    }
+
+    async fn reconcile_proxies(
+        proxies: &[JointProxy],
+        drift_tx: &broadcast::Sender<StateDrift>,
+    ) -> Vec<StateDrift> {
+        let mut drifts = Vec::new();
+
+        for proxy in proxies {
+            match proxy.reconcile().await {
+                Ok(Some(drift)) => {
+                    // No subscribers is fine; the cache is corrected either way
+                    let _ = drift_tx.send(drift);
+                    drifts.push(drift);
+                }
+                Ok(None) => {}
+                Err(e) => debug!("Reconciliation of joint {} skipped: {:?}", proxy.label(), e),
+            }
+        }
+
+        drifts
+    }
 }
 
 /// ARM-specific client for host environments (updated to use orchestrator)
@@ -413,6 +4051,16 @@ impl ArmClient {
         }
     }
     
+    /// Create an ARM client that talks through the given communication manager
+    ///
+    /// Use with [`CommunicationManager::with_adapter`] to drive a real (or
+    /// simulated) bus.
+    pub fn with_comm_manager(comm_manager: Arc<CommunicationManager>) -> Self {
+        Self {
+            orchestrator: ArmOrchestrator::with_comm_manager(comm_manager),
+        }
+    }
+
     /// Add a joint to the system
     pub fn add_joint(&mut self, joint_id: DeviceId) {
         self.orchestrator.add_joint(joint_id);
@@ -421,16 +4069,22 @@ impl ArmClient {
     /// Initialize the ARM system (configure and activate all joints)
     pub async fn initialize(&mut self) -> Result<(), ProtocolError> {
         info!("Initializing ARM system");
-        self.orchestrator.configure_all().await?;
-        self.orchestrator.activate_all().await?;
+        self.orchestrator.configure_all().await.into_result()?;
+        self.orchestrator.activate_all().await.into_result()?;
         info!("ARM system initialization complete");
         Ok(())
     }
+
+    /// Bring the system up in the order of a startup plan (see
+    /// [`ArmOrchestrator::start_up`])
+    pub async fn start_up(&mut self, plan: &StartupPlan) -> Result<(), StartupError> {
+        self.orchestrator.start_up(plan).await
+    }
     
-    /// Shutdown the ARM system
+    /// Shutdown the ARM system (see [`ArmOrchestrator::shutdown`])
     pub async fn shutdown(&mut self) -> Result<(), ProtocolError> {
         info!("Shutting down ARM system");
-        self.orchestrator.deactivate_all().await?;
+        self.orchestrator.shutdown().await.into_result()?;
         info!("ARM system shutdown complete");
         Ok(())
     }
@@ -441,8 +4095,10 @@ impl ArmClient {
     }
     
     /// Emergency stop the system
+    ///
+    /// Every joint is reset even if some fail; the error is the first failure.
     pub async fn emergency_stop(&mut self) -> Result<(), ProtocolError> {
-        self.orchestrator.emergency_stop().await
+        Ok(self.orchestrator.emergency_stop().await.into_result()?)
     }
     
     /// Check if the system is ready
@@ -454,6 +4110,16 @@ impl ArmClient {
     pub async fn get_system_status(&self) -> HashMap<DeviceId, LifecycleState> {
         self.orchestrator.get_system_status().await
     }
+
+    /// Get joint states and interlock status
+    pub async fn snapshot(&self) -> SystemSnapshot {
+        self.orchestrator.snapshot().await
+    }
+
+    /// Add an interlock (see [`ArmOrchestrator::configure_interlock`])
+    pub fn configure_interlock(&mut self, config: InterlockConfig) {
+        self.orchestrator.configure_interlock(config);
+    }
     
     /// Send a message asynchronously (legacy method for compatibility)
     pub async fn send_async(&self, message: Message) -> Result<(), ProtocolError> {
@@ -478,6 +4144,32 @@ impl Default for ArmClient {
     }
 }
 
+/// Stops the background tasks and, unless the orchestrator was
+/// [shut down](ArmOrchestrator::shutdown), engages a safety stop: every
+/// joint that may be Active is reset, as by an emergency stop.
+///
+/// The resets are only queued, since nothing can be awaited here. A
+/// communication manager sends what is queued before its adapter task ends,
+/// so they go out as long as the runtime keeps running; on the way out of
+/// `main`, call `shutdown` instead to be sure.
+#[cfg(feature = "arm_api")]
+impl Drop for ArmOrchestrator {
+    fn drop(&mut self) {
+        self.stop_reconciliation();
+        if let Some(task) = self.interlock_task.take() {
+            task.abort();
+        }
+        self.stop_safety_heartbeat();
+        self.stop_sync();
+        if let Some((_, task)) = self.jog_task.take() {
+            task.abort();
+        }
+        if !self.shut_down {
+            self.safety_stop();
+        }
+    }
+}
+
 #[cfg(feature = "arm_api")]
 impl Default for ArmOrchestrator {
     fn default() -> Self {
@@ -0,0 +1,130 @@
+//! Saved arm topology
+//!
+//! An [`ArmConfig`] describes a whole arm: its joints with their
+//! [descriptors](crate::JointDescriptor), soft limits and calibrated encoder
+//! offsets, plus the arm's kinematic and mass model. Applications load it instead of wiring joints up in code:
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), irpc::arm_config::ArmConfigError> {
+//! let mut arm = irpc::ArmOrchestrator::new();
+//! arm.load_config("arm.json").await?;
+//! // ... commission, home, calibrate ...
+//! arm.save_config("arm.json").await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The file format is chosen by extension; `.json` needs the `json`
+//! feature. Joints are listed by ID, every field but `joint_id` optional:
+//!
+//! ```json
+//! {
+//!   "joints": [
+//!     { "joint_id": 16, "name": "shoulder",
+//!       "limits": { "min_position": -90.0, "max_position": 90.0, "max_velocity": 60.0 },
+//!       "encoder_offset": { "turns": 0, "angle": 12.5 } },
+//!     { "joint_id": 17, "name": "elbow", "gear_ratio": 50.0, "units": "radians" }
+//!   ]
+//! }
+//! ```
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::arm::JointDescriptor;
+use crate::kinematics::ArmModel;
+use crate::protocol::{DeviceId, JointLimits, MultiTurnPosition, ProtocolError};
+
+/// Arm configuration errors
+#[derive(Error, Debug)]
+pub enum ArmConfigError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid configuration: {0}")]
+    Parse(String),
+    #[error("Unsupported configuration format: {0}")]
+    UnsupportedFormat(String),
+    #[error("Joint {0} is listed twice")]
+    DuplicateJoint(DeviceId),
+    #[error("Joint {joint_id} refused its configuration: {error}")]
+    Joint { joint_id: DeviceId, error: ProtocolError },
+}
+
+/// One joint of an [`ArmConfig`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JointEntry {
+    /// ID, name and metadata, inline in the entry
+    #[serde(flatten)]
+    pub descriptor: JointDescriptor,
+    /// Soft limits, applied with the configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<JointLimits>,
+    /// Calibrated encoder zero, applied ahead of the limits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoder_offset: Option<MultiTurnPosition>,
+}
+
+impl JointEntry {
+    pub fn new(joint_id: DeviceId) -> Self {
+        Self { descriptor: JointDescriptor::new(joint_id), limits: None, encoder_offset: None }
+    }
+}
+
+/// Joints and model of a whole arm
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ArmConfig {
+    /// The joints, by ID
+    pub joints: Vec<JointEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arm_model: Option<ArmModel>,
+}
+
+impl ArmConfig {
+    /// Read a configuration file, the format chosen by extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ArmConfigError> {
+        let path = path.as_ref();
+        match path.extension().and_then(|e| e.to_str()) {
+            #[cfg(feature = "json")]
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json(&std::fs::read_to_string(path)?),
+            _ => Err(ArmConfigError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+
+    /// Write the configuration to a file, the format chosen by extension
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ArmConfigError> {
+        let path = path.as_ref();
+        match path.extension().and_then(|e| e.to_str()) {
+            #[cfg(feature = "json")]
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Ok(std::fs::write(path, self.to_json()?)?),
+            _ => Err(ArmConfigError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> Result<Self, ArmConfigError> {
+        let config: Self = serde_json::from_str(text).map_err(|e| ArmConfigError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, ArmConfigError> {
+        serde_json::to_string_pretty(self).map_err(|e| ArmConfigError::Parse(e.to_string()))
+    }
+
+    /// Check that no joint is listed twice
+    pub fn validate(&self) -> Result<(), ArmConfigError> {
+        for (index, joint) in self.joints.iter().enumerate() {
+            if self.joints[..index].iter().any(|other| other.descriptor.id == joint.descriptor.id) {
+                return Err(ArmConfigError::DuplicateJoint(joint.descriptor.id));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn joint(&self, joint_id: DeviceId) -> Option<&JointEntry> {
+        self.joints.iter().find(|joint| joint.descriptor.id == joint_id)
+    }
+}
@@ -0,0 +1,75 @@
+//! Auxiliary I/O on spare joint pins
+//!
+//! Joints often have GPIO and ADC inputs left over after the motor and
+//! encoder: a holding brake, a fan, a limit switch, a supply voltage sense.
+//! Firmware implements [`AuxIoHandler`] over its HAL and passes it to
+//! [`Joint::handle_aux_io`](crate::Joint::handle_aux_io), or returns it from
+//! `JointHooks::aux_io` with `run_embassy`; the joint then answers
+//! `SetDigitalOutput`, `ReadDigitalInput` and `ReadAnalogInput` without the
+//! protocol knowing what is wired where. Channel numbers are board-specific.
+//!
+//! ```ignore
+//! struct BoardIo { fan: Output<'static>, limit: Input<'static> }
+//!
+//! impl AuxIoHandler for BoardIo {
+//!     fn set_digital_output(&mut self, channel: u8, value: bool) -> Result<(), AuxIoError> {
+//!         match channel {
+//!             0 => Ok(self.fan.set_level(value.into())),
+//!             _ => Err(AuxIoError::NoSuchChannel),
+//!         }
+//!     }
+//!
+//!     fn read_digital_input(&mut self, channel: u8) -> Result<bool, AuxIoError> {
+//!         match channel {
+//!             0 => Ok(self.limit.is_high()),
+//!             _ => Err(AuxIoError::NoSuchChannel),
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::config::{ERROR_AUX_IO, ERROR_NO_SUCH_CHANNEL};
+
+/// Auxiliary I/O errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum AuxIoError {
+    /// The board has no such channel (or not of that kind)
+    #[error("No such I/O channel")]
+    NoSuchChannel,
+
+    /// The channel exists but could not be driven or read
+    #[error("I/O channel failed")]
+    Failed,
+}
+
+impl AuxIoError {
+    /// Error code reported in the `Nack`
+    pub const fn code(&self) -> u16 {
+        match self {
+            AuxIoError::NoSuchChannel => ERROR_NO_SUCH_CHANNEL,
+            AuxIoError::Failed => ERROR_AUX_IO,
+        }
+    }
+}
+
+/// Board-specific auxiliary inputs and outputs
+///
+/// Every method defaults to `NoSuchChannel`, so a board implements only the
+/// kinds of channel it has.
+pub trait AuxIoHandler {
+    /// Drive digital output `channel` high (`true`) or low
+    fn set_digital_output(&mut self, _channel: u8, _value: bool) -> Result<(), AuxIoError> {
+        Err(AuxIoError::NoSuchChannel)
+    }
+
+    /// Level of digital input `channel`
+    fn read_digital_input(&mut self, _channel: u8) -> Result<bool, AuxIoError> {
+        Err(AuxIoError::NoSuchChannel)
+    }
+
+    /// Value of analog input `channel`, in the channel's unit (volts unless
+    /// the board documents otherwise)
+    fn read_analog_input(&mut self, _channel: u8) -> Result<f32, AuxIoError> {
+        Err(AuxIoError::NoSuchChannel)
+    }
+}
@@ -0,0 +1,346 @@
+//! Latency and throughput of a live bus
+//!
+//! A [`BusBenchmark`] measures what a system built on iRPC can expect from
+//! its actual topology (adapter, gateway, bus load and joint firmware),
+//! through any adapter a [`CommunicationManager`] was built with:
+//!
+//! ```no_run
+//! use irpc::benchmark::{BenchmarkConfig, BusBenchmark};
+//! # async fn example(comm: std::sync::Arc<irpc::CommunicationManager>) -> Result<(), irpc::ProtocolError> {
+//! // Joints Active and streaming telemetry
+//! let bench = BusBenchmark::new(comm, [0x0010, 0x0020], BenchmarkConfig::default());
+//! let report = bench.run().await?;
+//! println!("{}", report);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! - **round-trip latency**: `RequestStatus` sent to the joints in turn, one
+//!   at a time
+//! - **setpoint rate**: `SetTarget` sent to the joints in turn at a fixed
+//!   rate for [`step_window`](BenchmarkConfig::step_window), starting at
+//!   [`start_rate_hz`](BenchmarkConfig::start_rate_hz) and doubling up to
+//!   [`max_rate_hz`](BenchmarkConfig::max_rate_hz) until a step loses
+//!   setpoints (refused, or not answered in time). The last step without
+//!   losses is the sustainable rate. Each joint is sent its own current
+//!   position, so it holds still, and must be Active.
+//! - **telemetry throughput**: the samples and bytes the joints stream during
+//!   [`telemetry_window`](BenchmarkConfig::telemetry_window), and the jitter
+//!   of their arrival
+//!
+//! Percentiles are exact, from every sample taken. Time is measured on the
+//! manager's [clock](CommunicationManager::clock). For code-level costs
+//! (encoding, framing) see the criterion benches in `benches/`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinSet;
+
+use crate::arm::{CommunicationManager, TelemetryFilter, TelemetryTopic};
+use crate::clock::timeout;
+use crate::protocol::{DeviceId, Message, Payload, ProtocolError, SetTargetPayload};
+
+/// How long and how hard to measure
+#[derive(Debug, Clone)]
+pub struct BenchmarkConfig {
+    /// Requests timed for the round-trip latency
+    pub round_trips: usize,
+    /// Setpoint rate of the first step, all joints together
+    pub start_rate_hz: f64,
+    /// Setpoint rate not to go beyond
+    pub max_rate_hz: f64,
+    /// How long each setpoint rate is held
+    pub step_window: Duration,
+    /// How long to count telemetry for
+    pub telemetry_window: Duration,
+    /// Longest a reply may take before the request counts as lost
+    pub response_timeout: Duration,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        Self {
+            round_trips: 1000,
+            start_rate_hz: 100.0,
+            max_rate_hz: 10_000.0,
+            step_window: Duration::from_millis(500),
+            telemetry_window: Duration::from_secs(2),
+            response_timeout: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Distribution of measured durations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Percentiles {
+    pub count: usize,
+    pub min: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+}
+
+impl Percentiles {
+    /// Percentiles of `samples`, `None` if there are none
+    pub fn of(samples: &mut [Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+        // Nearest rank
+        let rank = |q: f64| samples[((q * samples.len() as f64).ceil() as usize).clamp(1, samples.len()) - 1];
+        let total: Duration = samples.iter().sum();
+        Some(Self {
+            count: samples.len(),
+            min: samples[0],
+            p50: rank(0.50),
+            p90: rank(0.90),
+            p99: rank(0.99),
+            max: samples[samples.len() - 1],
+            mean: total / samples.len() as u32,
+        })
+    }
+}
+
+impl core::fmt::Display for Percentiles {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let ms = |d: Duration| d.as_secs_f64() * 1e3;
+        write!(
+            f,
+            "min {:.3} / p50 {:.3} / p90 {:.3} / p99 {:.3} / max {:.3} ms (mean {:.3} ms, n = {})",
+            ms(self.min),
+            ms(self.p50),
+            ms(self.p90),
+            ms(self.p99),
+            ms(self.max),
+            ms(self.mean),
+            self.count
+        )
+    }
+}
+
+/// One setpoint rate tried
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateStep {
+    /// Setpoints sent per second, all joints together
+    pub rate_hz: f64,
+    pub sent: u64,
+    pub acked: u64,
+    /// Setpoints refused, or not answered in time
+    pub lost: u64,
+    /// Round-trip times of the acknowledged setpoints, under that load
+    pub latency: Option<Percentiles>,
+}
+
+/// The setpoint rates tried, in order; all but the last one without losses
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SetpointRate {
+    pub steps: Vec<RateStep>,
+}
+
+impl SetpointRate {
+    /// The fastest step without losses
+    pub fn sustained(&self) -> Option<&RateStep> {
+        self.steps.iter().rev().find(|step| step.lost == 0)
+    }
+}
+
+/// What the joints streamed unasked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TelemetryThroughput {
+    pub samples: u64,
+    pub samples_per_sec: f64,
+    /// Encoded message bytes per second
+    pub bytes_per_sec: f64,
+    /// Time between consecutive samples of each joint
+    pub interval: Option<Percentiles>,
+}
+
+/// Everything a [`BusBenchmark`] measured
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkReport {
+    pub joints: Vec<DeviceId>,
+    pub round_trip: Option<Percentiles>,
+    pub setpoint_rate: SetpointRate,
+    pub telemetry: TelemetryThroughput,
+}
+
+impl core::fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let joints: Vec<String> = self.joints.iter().map(|id| format!("{:#06x}", id)).collect();
+        writeln!(f, "iRPC bus benchmark, joints {}", joints.join(", "))?;
+        match &self.round_trip {
+            Some(latency) => writeln!(f, "  round trip:  {}", latency)?,
+            None => writeln!(f, "  round trip:  no replies")?,
+        }
+        match self.setpoint_rate.sustained() {
+            Some(step) => {
+                writeln!(f, "  setpoints:   {:.0}/s sustained ({} acked)", step.rate_hz, step.acked)?;
+                if let Some(latency) = &step.latency {
+                    writeln!(f, "    under load {}", latency)?;
+                }
+            }
+            None => writeln!(f, "  setpoints:   lost at every rate")?,
+        }
+        let telemetry = &self.telemetry;
+        write!(
+            f,
+            "  telemetry:   {:.1} samples/s, {:.0} B/s ({} samples)",
+            telemetry.samples_per_sec, telemetry.bytes_per_sec, telemetry.samples
+        )?;
+        if let Some(interval) = &telemetry.interval {
+            write!(f, "\n    interval   {}", interval)?;
+        }
+        Ok(())
+    }
+}
+
+/// Measures latency and throughput to a set of joints
+pub struct BusBenchmark {
+    comm: Arc<CommunicationManager>,
+    joints: Vec<DeviceId>,
+    config: BenchmarkConfig,
+}
+
+impl BusBenchmark {
+    pub fn new(
+        comm: Arc<CommunicationManager>,
+        joints: impl IntoIterator<Item = DeviceId>,
+        config: BenchmarkConfig,
+    ) -> Self {
+        Self { comm, joints: joints.into_iter().collect(), config }
+    }
+
+    /// Run every measurement
+    pub async fn run(&self) -> Result<BenchmarkReport, ProtocolError> {
+        Ok(BenchmarkReport {
+            joints: self.joints.clone(),
+            round_trip: self.round_trip_latency().await?,
+            setpoint_rate: self.setpoint_rate().await?,
+            telemetry: self.telemetry_throughput().await,
+        })
+    }
+
+    /// Round-trip time of `RequestStatus`, one request at a time
+    ///
+    /// Fails on the first request not answered in time.
+    pub async fn round_trip_latency(&self) -> Result<Option<Percentiles>, ProtocolError> {
+        let clock = self.comm.clock();
+        let mut samples = Vec::with_capacity(self.config.round_trips);
+        for &joint_id in self.joints.iter().cycle().take(self.config.round_trips) {
+            let sent_at = clock.now();
+            let request = self.comm.send_request(joint_id, Payload::RequestStatus).await?;
+            request.response(self.config.response_timeout).await?;
+            samples.push(clock.now().saturating_sub(sent_at));
+        }
+        Ok(Percentiles::of(&mut samples))
+    }
+
+    /// Step the `SetTarget` rate up until setpoints get lost
+    ///
+    /// Fails if a joint's position cannot be read.
+    pub async fn setpoint_rate(&self) -> Result<SetpointRate, ProtocolError> {
+        let mut setpoints = Vec::with_capacity(self.joints.len());
+        for &joint_id in &self.joints {
+            let reply = self.comm.send_and_wait(joint_id, Payload::RequestMultiTurnPosition).await?;
+            let position = match reply.payload {
+                Payload::MultiTurnPosition(position) => position,
+                Payload::Nack { id, .. } => return Err(ProtocolError::IoError(id)),
+                _ => return Err(ProtocolError::InvalidMessage),
+            };
+            let target = SetTargetPayload { target_angle: position.degrees(), velocity_limit: 1.0 };
+            setpoints.push((joint_id, target));
+        }
+
+        let mut rate = SetpointRate::default();
+        let mut rate_hz = self.config.start_rate_hz.min(self.config.max_rate_hz);
+        while !setpoints.is_empty() && rate_hz > 0.0 {
+            let step = self.rate_step(&setpoints, rate_hz).await;
+            rate.steps.push(step);
+            if step.lost > 0 || rate_hz >= self.config.max_rate_hz {
+                break;
+            }
+            rate_hz = (rate_hz * 2.0).min(self.config.max_rate_hz);
+        }
+        Ok(rate)
+    }
+
+    /// Send `setpoints` in turn at `rate_hz` for one step window
+    async fn rate_step(&self, setpoints: &[(DeviceId, SetTargetPayload)], rate_hz: f64) -> RateStep {
+        let clock = self.comm.clock();
+        let period = Duration::from_secs_f64(1.0 / rate_hz);
+        let started = clock.now();
+        let mut requests = JoinSet::new();
+        for (&(joint_id, target), sent) in setpoints.iter().cycle().zip(0u32..) {
+            let due = started + period * sent;
+            if due >= started + self.config.step_window {
+                break;
+            }
+            clock.sleep_until(due).await;
+            let (comm, clock) = (self.comm.clone(), clock.clone());
+            let response_timeout = self.config.response_timeout;
+            requests.spawn(async move {
+                let sent_at = clock.now();
+                let reply = match comm.send_request(joint_id, Payload::SetTarget(target)).await {
+                    Ok(request) => request.response(response_timeout).await,
+                    Err(e) => Err(e),
+                };
+                matches!(reply, Ok(Message { payload: Payload::Ack(_), .. })).then(|| clock.now().saturating_sub(sent_at))
+            });
+        }
+
+        let (mut samples, mut sent) = (Vec::new(), 0);
+        while let Some(result) = requests.join_next().await {
+            sent += 1;
+            samples.extend(result.expect("setpoint request panicked"));
+        }
+        RateStep {
+            rate_hz,
+            sent,
+            acked: samples.len() as u64,
+            lost: sent - samples.len() as u64,
+            latency: Percentiles::of(&mut samples),
+        }
+    }
+
+    /// Telemetry the joints stream during `telemetry_window`
+    pub async fn telemetry_throughput(&self) -> TelemetryThroughput {
+        let mut subscriber = self.comm.subscribe_telemetry(TelemetryFilter {
+            joints: Some(self.joints.clone()),
+            topics: Some(vec![TelemetryTopic::Motion]),
+            snapshot: false,
+        });
+        let clock = self.comm.clock();
+        let window = self.config.telemetry_window;
+        let deadline = clock.now() + window;
+
+        let (mut samples, mut bytes) = (0u64, 0u64);
+        let mut last_seen = std::collections::HashMap::new();
+        let mut intervals = Vec::new();
+        loop {
+            let remaining = deadline.saturating_sub(clock.now());
+            if remaining.is_zero() {
+                break;
+            }
+            let Ok(Some(sample)) = timeout(clock.as_ref(), remaining, subscriber.recv()).await else {
+                break;
+            };
+            samples += 1;
+            bytes += sample.message.encoded_size() as u64;
+            if let Some(previous) = last_seen.insert(sample.joint_id, sample.received_at) {
+                intervals.push(sample.received_at.saturating_sub(previous));
+            }
+        }
+
+        let seconds = window.as_secs_f64().max(f64::EPSILON);
+        TelemetryThroughput {
+            samples,
+            samples_per_sec: samples as f64 / seconds,
+            bytes_per_sec: bytes as f64 / seconds,
+            interval: Percentiles::of(&mut intervals),
+        }
+    }
+}
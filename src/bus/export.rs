@@ -0,0 +1,291 @@
+//! Exporting recorded traffic for plotting tools
+//!
+//! Turns the [`LogRecord`]s of a [recording](super::record) into formats
+//! that Foxglove Studio, PlotJuggler and spreadsheets open directly:
+//!
+//! - [`export_mcap`] writes an MCAP file with one channel per joint, direction
+//!   and payload kind (`/joint/16/TelemetryStream`,
+//!   `/joint/16/command/SetTarget`). Messages are JSON, described by a JSON
+//!   schema derived from the protocol struct they came from.
+//! - [`export_csv`] writes one payload kind as a CSV table, one column per
+//!   struct field.
+//!
+//! Field names and types come from the payload types' serde
+//! implementations, so new protocol fields show up without changes here.
+//!
+//! ```no_run
+//! # fn example() -> Result<(), irpc::bus::export::ExportError> {
+//! use irpc::bus::export::{export_csv, export_mcap};
+//! use irpc::bus::record::open_log;
+//! use std::fs::File;
+//!
+//! let records = open_log("session.irpclog").unwrap();
+//! export_mcap(&records, File::create("session.mcap")?)?;
+//! export_csv(&records, "TelemetryStream", File::create("telemetry.csv")?)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::io::{self, BufWriter, Write};
+
+use serde_json::{json, Map, Value};
+
+use super::record::{Direction, LogRecord};
+use crate::protocol::{DeviceId, Payload};
+
+/// MCAP file magic, format version 0
+const MCAP_MAGIC: &[u8; 8] = b"\x89MCAP0\r\n";
+
+/// MCAP record opcodes
+mod op {
+    pub const HEADER: u8 = 0x01;
+    pub const FOOTER: u8 = 0x02;
+    pub const SCHEMA: u8 = 0x03;
+    pub const CHANNEL: u8 = 0x04;
+    pub const MESSAGE: u8 = 0x05;
+    pub const DATA_END: u8 = 0x0F;
+}
+
+/// Export errors
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    /// Writing the output failed
+    #[error("Export I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// A payload could not be expressed as JSON
+    #[error("Export encoding error: {0}")]
+    Encoding(#[from] serde_json::Error),
+}
+
+/// Payload kind (its variant name) and its fields as a JSON object
+fn payload_fields(payload: &Payload) -> Result<(String, Value), ExportError> {
+    Ok(match serde_json::to_value(payload)? {
+        // Unit variants
+        Value::String(kind) => (kind, Value::Object(Map::new())),
+        Value::Object(map) if map.len() == 1 => {
+            let (kind, body) = map.into_iter().next().unwrap();
+            let body = match body {
+                Value::Object(_) => body,
+                // Newtype variants over a plain value, e.g. `Ack(id)`
+                value => json!({ "value": value }),
+            };
+            (kind, body)
+        }
+        other => ("Unknown".into(), json!({ "value": other })),
+    })
+}
+
+/// The joint a record belongs to
+fn joint_of(record: &LogRecord) -> DeviceId {
+    match record.direction {
+        Direction::Received => record.message.header.source_id,
+        Direction::Transmitted => record.message.header.target_id,
+    }
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Transmitted => "transmitted",
+        Direction::Received => "received",
+    }
+}
+
+/// JSON schema describing `value`
+fn json_schema(value: &Value) -> Value {
+    match value {
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(n) if n.is_f64() => json!({ "type": "number" }),
+        Value::Number(_) => json!({ "type": "integer" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(items) => json!({
+            "type": "array",
+            "items": items.first().map(json_schema).unwrap_or_else(|| json!({})),
+        }),
+        Value::Object(fields) => json!({
+            "type": "object",
+            "properties": fields
+                .iter()
+                .map(|(name, value)| (name.clone(), json_schema(value)))
+                .collect::<Map<_, _>>(),
+        }),
+        // `None` options: the type is unknown from this sample
+        Value::Null => json!({}),
+    }
+}
+
+/// Minimal unchunked MCAP writer
+struct McapWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> McapWriter<W> {
+    fn record(&mut self, opcode: u8, body: &[u8]) -> io::Result<()> {
+        self.out.write_all(&[opcode])?;
+        self.out.write_all(&(body.len() as u64).to_le_bytes())?;
+        self.out.write_all(body)
+    }
+}
+
+fn put_str(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    buf.extend_from_slice(s.as_bytes());
+}
+
+/// Write `records` as an MCAP file
+///
+/// Log times are the recording timestamps, in nanoseconds.
+pub fn export_mcap(records: &[LogRecord], writer: impl Write) -> Result<(), ExportError> {
+    let mut mcap = McapWriter { out: BufWriter::new(writer) };
+    mcap.out.write_all(MCAP_MAGIC)?;
+
+    let mut header = Vec::new();
+    put_str(&mut header, "");
+    put_str(&mut header, concat!("irpc ", env!("CARGO_PKG_VERSION")));
+    mcap.record(op::HEADER, &header)?;
+
+    // Ids start at 1; schema id 0 means "no schema"
+    let mut schemas: HashMap<String, u16> = HashMap::new();
+    let mut channels: HashMap<(DeviceId, Direction, String), (u16, u32)> = HashMap::new();
+
+    for record in records {
+        let (kind, body) = payload_fields(&record.message.payload)?;
+        let joint = joint_of(record);
+
+        let next_schema = schemas.len() as u16 + 1;
+        let schema_id = match schemas.get(&kind) {
+            Some(&id) => id,
+            None => {
+                let mut schema = json_schema(&body);
+                schema["title"] = json!(format!("irpc.{}", kind));
+                let mut buf = Vec::new();
+                buf.extend_from_slice(&next_schema.to_le_bytes());
+                put_str(&mut buf, &format!("irpc.{}", kind));
+                put_str(&mut buf, "jsonschema");
+                let data = serde_json::to_vec(&schema)?;
+                buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                buf.extend_from_slice(&data);
+                mcap.record(op::SCHEMA, &buf)?;
+                schemas.insert(kind.clone(), next_schema);
+                next_schema
+            }
+        };
+
+        let next_channel = channels.len() as u16 + 1;
+        let (channel_id, sequence) = channels
+            .entry((joint, record.direction, kind.clone()))
+            .or_insert_with(|| (next_channel, 0));
+        if *sequence == 0 {
+            let topic = match record.direction {
+                Direction::Received => format!("/joint/{}/{}", joint, kind),
+                Direction::Transmitted => format!("/joint/{}/command/{}", joint, kind),
+            };
+            let mut metadata = Vec::new();
+            for (key, value) in [("joint_id", joint.to_string()), ("direction", direction_name(record.direction).into())] {
+                put_str(&mut metadata, key);
+                put_str(&mut metadata, &value);
+            }
+            let mut buf = Vec::new();
+            buf.extend_from_slice(&channel_id.to_le_bytes());
+            buf.extend_from_slice(&schema_id.to_le_bytes());
+            put_str(&mut buf, &topic);
+            put_str(&mut buf, "json");
+            buf.extend_from_slice(&(metadata.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&metadata);
+            mcap.record(op::CHANNEL, &buf)?;
+        }
+        *sequence += 1;
+
+        let log_time = record.timestamp_us.saturating_mul(1_000);
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&channel_id.to_le_bytes());
+        buf.extend_from_slice(&sequence.to_le_bytes());
+        buf.extend_from_slice(&log_time.to_le_bytes());
+        buf.extend_from_slice(&log_time.to_le_bytes());
+        buf.extend_from_slice(&serde_json::to_vec(&body)?);
+        mcap.record(op::MESSAGE, &buf)?;
+    }
+
+    // No CRC and no summary section
+    mcap.record(op::DATA_END, &0u32.to_le_bytes())?;
+    let mut footer = Vec::new();
+    footer.extend_from_slice(&0u64.to_le_bytes());
+    footer.extend_from_slice(&0u64.to_le_bytes());
+    footer.extend_from_slice(&0u32.to_le_bytes());
+    mcap.record(op::FOOTER, &footer)?;
+    mcap.out.write_all(MCAP_MAGIC)?;
+    mcap.out.flush()?;
+    Ok(())
+}
+
+/// Flatten nested objects and arrays into `a.b` / `a.0` columns
+fn flatten(prefix: &str, value: &Value, out: &mut Vec<(String, Value)>) {
+    let name = |key: &str| if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+    match value {
+        Value::Object(fields) => fields.iter().for_each(|(key, value)| flatten(&name(key), value, out)),
+        Value::Array(items) => items.iter().enumerate().for_each(|(i, value)| flatten(&name(&i.to_string()), value, out)),
+        value => out.push((prefix.to_string(), value.clone())),
+    }
+}
+
+fn csv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    };
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// Write the records of one payload kind (e.g. `"TelemetryStream"`) as CSV
+///
+/// Columns are `timestamp_us`, `direction`, `joint_id`, `msg_id`, then the
+/// payload's fields in declaration order. Returns the number of rows.
+pub fn export_csv(records: &[LogRecord], kind: &str, writer: impl Write) -> Result<usize, ExportError> {
+    let mut out = BufWriter::new(writer);
+    let mut columns: Option<Vec<String>> = None;
+    let mut rows = 0;
+
+    for record in records {
+        let (record_kind, body) = payload_fields(&record.message.payload)?;
+        if record_kind != kind {
+            continue;
+        }
+        let mut fields = Vec::new();
+        flatten("", &body, &mut fields);
+
+        let columns = columns.get_or_insert_with(|| fields.iter().map(|(name, _)| name.clone()).collect());
+        if rows == 0 {
+            let header: Vec<&str> = ["timestamp_us", "direction", "joint_id", "msg_id"]
+                .into_iter()
+                .chain(columns.iter().map(String::as_str))
+                .collect();
+            writeln!(out, "{}", header.join(","))?;
+        }
+
+        let mut row = vec![
+            record.timestamp_us.to_string(),
+            direction_name(record.direction).to_string(),
+            joint_of(record).to_string(),
+            record.message.header.msg_id.to_string(),
+        ];
+        // Optional fields may be absent in some rows; keep the columns aligned
+        row.extend(columns.iter().map(|column| {
+            fields
+                .iter()
+                .find(|(name, _)| name == column)
+                .map(|(_, value)| csv_field(value))
+                .unwrap_or_default()
+        }));
+        writeln!(out, "{}", row.join(","))?;
+        rows += 1;
+    }
+
+    out.flush()?;
+    Ok(rows)
+}
@@ -0,0 +1,163 @@
+//! Bridge between two adapters
+//!
+//! A [`Gateway`] forwards iRPC messages in both directions between two
+//! [`CommunicationAdapter`]s, e.g. the CAN bus of a live robot on one side
+//! and a [`NetworkAdapter`](super::net::NetworkAdapter) on the other, so a
+//! developer workstation can drive and debug the robot remotely. A
+//! [`DeviceFilter`] limits which joints are reachable through it.
+//!
+//! ```no_run
+//! # async fn example() -> std::io::Result<()> {
+//! use irpc::bus::gateway::{DeviceFilter, Gateway};
+//! use irpc::bus::net::NetworkAdapter;
+//! use std::sync::Arc;
+//!
+//! let robot = Arc::new(NetworkAdapter::tcp_connect("10.0.0.2:47101".parse().unwrap()));
+//! let workstation = Arc::new(NetworkAdapter::udp("0.0.0.0:47101".parse().unwrap(), None).await?);
+//! let gateway = Gateway::spawn(robot, workstation, DeviceFilter::only([0x0010, 0x0020]));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::CommunicationAdapter;
+use crate::config::{ADAPTER_POLL_INTERVAL_MS, BROADCAST_ADDRESS};
+use crate::protocol::{DeviceId, Message};
+
+/// Which messages a gateway forwards
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DeviceFilter {
+    /// Everything
+    #[default]
+    All,
+    /// Broadcasts, and messages to or from one of these devices
+    Only(Vec<DeviceId>),
+}
+
+impl DeviceFilter {
+    /// Forward only traffic of these devices (plus broadcasts)
+    pub fn only(devices: impl IntoIterator<Item = DeviceId>) -> Self {
+        Self::Only(devices.into_iter().collect())
+    }
+
+    /// Whether `message` passes the filter
+    pub fn allows(&self, message: &Message) -> bool {
+        match self {
+            Self::All => true,
+            Self::Only(devices) => {
+                let header = &message.header;
+                header.target_id == BROADCAST_ADDRESS
+                    || devices.contains(&header.target_id)
+                    || devices.contains(&header.source_id)
+            }
+        }
+    }
+}
+
+/// Message counters of a running gateway
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GatewayStats {
+    /// Messages forwarded from the first adapter to the second
+    pub forwarded_a_to_b: u64,
+    /// Messages forwarded from the second adapter to the first
+    pub forwarded_b_to_a: u64,
+    /// Messages dropped by the filter
+    pub filtered: u64,
+    /// Receive or transmit failures (the message is lost, the gateway carries on)
+    pub errors: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    forwarded_a_to_b: AtomicU64,
+    forwarded_b_to_a: AtomicU64,
+    filtered: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// Running bridge between two adapters; stops when dropped
+pub struct Gateway {
+    counters: Arc<Counters>,
+    task: JoinHandle<()>,
+}
+
+impl Gateway {
+    /// Start forwarding between `a` and `b` in both directions
+    ///
+    /// Both adapters are polled every `ADAPTER_POLL_INTERVAL_MS`, and
+    /// everything they have queued is forwarded on each poll.
+    pub fn spawn<A, B>(a: Arc<A>, b: Arc<B>, filter: DeviceFilter) -> Self
+    where
+        A: CommunicationAdapter + 'static,
+        B: CommunicationAdapter + 'static,
+    {
+        let counters = Arc::new(Counters::default());
+        info!("Gateway started ({:?})", filter);
+        let task = tokio::spawn({
+            let counters = counters.clone();
+            async move {
+                let poll_interval = Duration::from_millis(ADAPTER_POLL_INTERVAL_MS);
+                loop {
+                    forward(&*a, &*b, &filter, &counters, &counters.forwarded_a_to_b).await;
+                    forward(&*b, &*a, &filter, &counters, &counters.forwarded_b_to_a).await;
+                    tokio::time::sleep(poll_interval).await;
+                }
+            }
+        });
+        Self { counters, task }
+    }
+
+    /// Counters so far
+    pub fn stats(&self) -> GatewayStats {
+        GatewayStats {
+            forwarded_a_to_b: self.counters.forwarded_a_to_b.load(Ordering::Relaxed),
+            forwarded_b_to_a: self.counters.forwarded_b_to_a.load(Ordering::Relaxed),
+            filtered: self.counters.filtered.load(Ordering::Relaxed),
+            errors: self.counters.errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for Gateway {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Move everything queued on `from` to `to`
+async fn forward<F, T>(from: &F, to: &T, filter: &DeviceFilter, counters: &Counters, forwarded: &AtomicU64)
+where
+    F: CommunicationAdapter,
+    T: CommunicationAdapter,
+{
+    loop {
+        let message = match from.receive().await {
+            Ok(Some(message)) => message,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Gateway receive failed: {:?}", e);
+                counters.errors.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        };
+        if !filter.allows(&message) {
+            debug!("Gateway filtered message {} from {} to {}",
+                   message.header.msg_id, message.header.source_id, message.header.target_id);
+            counters.filtered.fetch_add(1, Ordering::Relaxed);
+            continue;
+        }
+        match to.transmit(&message).await {
+            Ok(()) => forwarded.fetch_add(1, Ordering::Relaxed),
+            Err(e) => {
+                warn!("Gateway transmit of message {} failed: {:?}", message.header.msg_id, e);
+                counters.errors.fetch_add(1, Ordering::Relaxed)
+            }
+        };
+    }
+}
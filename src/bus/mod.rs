@@ -0,0 +1,567 @@
+use crate::protocol::{BootMode, Capabilities, DeviceId, ProtocolError, SCHEMA_HASH};
+
+#[cfg(any(feature = "arm_api", feature = "joint_api"))]
+use crate::protocol::Message;
+
+#[cfg(feature = "joint_api")]
+use crate::protocol::MessageRef;
+
+#[cfg(feature = "joint_api")]
+use crate::protocol::{TransportErrorKind, TransportStats};
+
+/// UDP/TCP adapter and peer discovery for remote gateways and simulators
+#[cfg(feature = "arm_api")]
+pub mod net;
+
+/// Bidirectional bridge between two adapters, with device filtering
+#[cfg(feature = "arm_api")]
+pub mod gateway;
+
+/// Recording traffic to disk and replaying it
+#[cfg(feature = "arm_api")]
+pub mod record;
+
+/// Passive monitoring with filtering, for dump and sniffer tools
+#[cfg(feature = "arm_api")]
+pub mod sniffer;
+
+/// MCAP and CSV export of recordings
+#[cfg(feature = "json")]
+pub mod export;
+
+/// In-process joint simulation for host tests (needs both APIs)
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+pub mod sim;
+
+/// WebSocket adapter for browser diagnostics tools (wasm32)
+#[cfg(feature = "wasm")]
+pub mod ws;
+
+/// Device information for discovery
+#[derive(Debug, Clone)]
+pub struct DeviceInfo {
+    pub id: DeviceId,
+    pub entity_type: u16,
+    /// Firmware image the device is running
+    pub mode: BootMode,
+    /// Optional commands the device handles
+    pub capabilities: Capabilities,
+    /// `SCHEMA_HASH` of the device's firmware
+    pub schema: u32,
+}
+
+impl DeviceInfo {
+    /// `SchemaMismatch` unless the device speaks this build's `Payload` layout
+    pub fn check_schema(&self) -> Result<(), ProtocolError> {
+        match self.schema {
+            SCHEMA_HASH => Ok(()),
+            remote => Err(ProtocolError::SchemaMismatch { local: SCHEMA_HASH, remote }),
+        }
+    }
+}
+
+// ============================================================================
+// ARM API: Async communication adapter (for host std environment)
+// ============================================================================
+
+#[cfg(feature = "arm_api")]
+use async_trait::async_trait;
+
+#[cfg(feature = "arm_api")]
+use crate::config::{CANFD_MAX_DATA_LEN, RECEIVE_BATCH_LEN};
+
+#[cfg(feature = "arm_api")]
+#[async_trait]
+pub trait CommunicationAdapter: Send + Sync {
+    type Error: core::fmt::Debug;
+
+    async fn transmit(&self, message: &Message) -> Result<(), Self::Error>;
+    async fn receive(&self) -> Result<Option<Message>, Self::Error>;
+
+    /// Move received messages into `batch`, up to `RECEIVE_BATCH_LEN` of them
+    ///
+    /// `CommunicationManager` drains adapters through this, with one buffer
+    /// it keeps for its lifetime, so a poll costs one call however many
+    /// messages arrived. The default calls [`receive`](Self::receive) until
+    /// it runs dry; adapters that queue messages should hand over their
+    /// queue directly instead.
+    async fn receive_batch(&self, batch: &mut Vec<Message>) -> Result<(), Self::Error> {
+        while batch.len() < RECEIVE_BATCH_LEN {
+            match self.receive().await? {
+                Some(message) => batch.push(message),
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    async fn discover_devices(&self) -> Result<Vec<DeviceInfo>, Self::Error>;
+    fn is_connected(&self) -> bool;
+
+    /// Largest encoded message the bus carries, in bytes
+    ///
+    /// Defaults to one CAN-FD frame. `CommunicationManager::with_adapter`
+    /// uses it as the initial message size limit.
+    fn mtu(&self) -> usize {
+        CANFD_MAX_DATA_LEN
+    }
+
+    /// Push out anything the adapter buffers, e.g. records not yet written
+    ///
+    /// `CommunicationManager` calls this once, after transmitting its last
+    /// message, when it closes the adapter. Defaults to doing nothing.
+    async fn flush(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+// ============================================================================
+// JOINT API: Embedded transport trait (for no_std firmware)
+// ============================================================================
+
+/// Low-level embedded transport for no_std environments
+///
+/// This trait provides blocking send/receive operations for raw byte buffers.
+/// It's designed for use with CAN, SPI, UART, or other embedded communication buses.
+#[cfg(feature = "joint_api")]
+pub trait EmbeddedTransport {
+    /// Transport-specific error type
+    type Error: core::fmt::Debug;
+
+    /// Send raw bytes over the transport (blocking)
+    fn send_blocking(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Receive raw bytes from the transport (blocking, non-blocking returns None)
+    ///
+    /// Returns Ok(Some(data)) if data is available, Ok(None) if no data ready.
+    fn receive_blocking(&mut self) -> Result<Option<&[u8]>, Self::Error>;
+
+    /// Check if transport is ready for communication
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    /// Largest encoded message one `send_blocking` call can carry, in bytes
+    ///
+    /// Defaults to [`Message::max_size()`], i.e. any message. Links with
+    /// smaller frames (UART packets, SPI transfers, UDP datagrams) should
+    /// return their limit; `TransportLayer` then refuses larger messages
+    /// instead of handing them to the driver.
+    fn mtu(&self) -> usize {
+        Message::max_size()
+    }
+}
+
+// ============================================================================
+// Transport Layer: High-level wrapper with automatic serialization
+// ============================================================================
+
+/// Default size of a transport layer's receive and transmit buffers, in
+/// bytes: [`Message::max_size()`], so any message fits
+#[cfg(feature = "joint_api")]
+pub const DEFAULT_TRANSPORT_BUFFER_LEN: usize = Message::max_size();
+
+/// High-level transport layer that handles message serialization/deserialization
+///
+/// This wrapper provides a simple API for sending and receiving Messages,
+/// automatically handling the encoding/decoding internally.
+///
+/// It holds a receive and a transmit buffer of `N` bytes each. The default
+/// fits any message; memory-constrained joints whose bus carries smaller
+/// frames can shrink them (e.g. to `CANFD_MAX_DATA_LEN`), and transports that
+/// deliver reassembled transfers with trailing bytes can grow them (see
+/// [`with_buffers`](Self::with_buffers)). Messages that do not fit are
+/// refused with `FrameTooLarge` either way.
+///
+/// # Example
+/// ```no_run
+/// use irpc::{TransportLayer, Message};
+///
+/// // Assuming you have a CAN or UART transport implementing EmbeddedTransport
+/// let mut transport = TransportLayer::new(my_can_bus);
+///
+/// // Send a message - serialization is automatic
+/// transport.send_message(&message)?;
+///
+/// // Receive a message - deserialization is automatic
+/// if let Some(msg) = transport.receive_message()? {
+///     // Process message
+/// }
+/// ```
+#[cfg(feature = "joint_api")]
+pub struct TransportLayer<T: EmbeddedTransport, const N: usize = DEFAULT_TRANSPORT_BUFFER_LEN> {
+    transport: T,
+    rx_buffer: [u8; N],
+    tx_buffer: [u8; N],
+    stats: TransportStats,
+}
+
+#[cfg(feature = "joint_api")]
+impl<T: EmbeddedTransport> TransportLayer<T> {
+    /// Create a new transport layer wrapping an embedded transport
+    pub fn new(transport: T) -> Self {
+        Self::with_buffers(transport)
+    }
+}
+
+#[cfg(feature = "joint_api")]
+impl<T: EmbeddedTransport, const N: usize> TransportLayer<T, N> {
+    /// Create a transport layer with `N`-byte buffers, e.g.
+    /// `TransportLayer::<_, CANFD_MAX_DATA_LEN>::with_buffers(transport)`
+    pub fn with_buffers(transport: T) -> Self {
+        let stats = TransportStats::with_mtu(transport.mtu().min(N));
+        Self {
+            transport,
+            rx_buffer: [0u8; N],
+            tx_buffer: [0u8; N],
+            stats,
+        }
+    }
+
+    /// Send a message (automatically serializes)
+    ///
+    /// This method serializes into an internal fixed-size buffer (no heap
+    /// allocation) and sends the encoded bytes over the underlying transport.
+    pub fn send_message(&mut self, message: &Message) -> Result<(), TransportError<T::Error>> {
+        let len = encode_message(message, &mut self.tx_buffer, self.transport.mtu(), &mut self.stats)?;
+
+        match self.transport.send_blocking(&self.tx_buffer[..len]) {
+            Ok(()) => {
+                self.stats.tx_frames = self.stats.tx_frames.wrapping_add(1);
+                Ok(())
+            }
+            Err(e) => {
+                self.stats.record_error(TransportErrorKind::Transport);
+                Err(TransportError::TransportError(e))
+            }
+        }
+    }
+
+    /// Receive a message (automatically deserializes)
+    ///
+    /// Returns Ok(Some(message)) if a message was received and successfully decoded,
+    /// Ok(None) if no data is available, or Err if there was a transport or deserialization error.
+    pub fn receive_message(&mut self) -> Result<Option<Message>, TransportError<T::Error>> {
+        match self.transport.receive_blocking() {
+            Ok(Some(data)) => decode_message(data, &mut self.rx_buffer, &mut self.stats).map(Some),
+            Ok(None) => Ok(None),
+            Err(e) => {
+                self.stats.record_error(TransportErrorKind::Transport);
+                Err(TransportError::TransportError(e))
+            }
+        }
+    }
+
+    /// Receive a message, leaving its payload encoded
+    ///
+    /// Like [`receive_message`](Self::receive_message), but only the header
+    /// is decoded; the [`MessageRef`] borrows the payload from the layer's
+    /// receive buffer. The payload's fields are not checked until decoded,
+    /// so a frame with a valid header and tag always counts as received.
+    pub fn receive_message_ref(&mut self) -> Result<Option<MessageRef<'_>>, TransportError<T::Error>> {
+        match self.transport.receive_blocking() {
+            Ok(Some(data)) => {
+                let len = copy_frame(data, &mut self.rx_buffer, &mut self.stats)?;
+                MessageRef::deserialize(&self.rx_buffer[..len]).map(Some).map_err(|_| {
+                    self.stats.record_error(TransportErrorKind::Deserialization);
+                    TransportError::DeserializationFailed
+                })
+            }
+            Ok(None) => Ok(None),
+            Err(e) => {
+                self.stats.record_error(TransportErrorKind::Transport);
+                Err(TransportError::TransportError(e))
+            }
+        }
+    }
+
+    /// Get the transport statistics collected so far
+    pub fn stats(&self) -> &TransportStats {
+        &self.stats
+    }
+
+    /// Get mutable statistics, e.g. to record CRC or bus-off events the
+    /// driver observes below this layer
+    pub fn stats_mut(&mut self) -> &mut TransportStats {
+        &mut self.stats
+    }
+
+    /// Reset all statistics to zero
+    pub fn reset_stats(&mut self) {
+        self.stats = TransportStats::with_mtu(self.mtu());
+    }
+
+    /// Check if the transport is ready
+    pub fn is_ready(&self) -> bool {
+        self.transport.is_ready()
+    }
+
+    /// Largest encoded message the layer sends: the transport's MTU, or
+    /// the buffer size if that is smaller
+    pub fn mtu(&self) -> usize {
+        self.transport.mtu().min(N)
+    }
+
+    /// Get a mutable reference to the underlying transport
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Get a reference to the underlying transport
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Consume the layer and return the underlying transport
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+}
+
+/// Serialize a message into a layer's TX buffer, counting failures
+///
+/// Messages that encode to more than `mtu` bytes, or do not fit the
+/// buffer, are refused with `FrameTooLarge` before they reach the transport.
+#[cfg(feature = "joint_api")]
+fn encode_message<E: core::fmt::Debug>(
+    message: &Message,
+    buffer: &mut [u8],
+    mtu: usize,
+    stats: &mut TransportStats,
+) -> Result<usize, TransportError<E>> {
+    let len = message.serialize_into(buffer).map_err(|_| {
+        stats.record_error(TransportErrorKind::Serialization);
+        if message.encoded_size() > buffer.len() {
+            TransportError::FrameTooLarge
+        } else {
+            TransportError::SerializationFailed
+        }
+    })?;
+    if len > mtu {
+        stats.record_error(TransportErrorKind::Serialization);
+        return Err(TransportError::FrameTooLarge);
+    }
+    Ok(len)
+}
+
+/// Decode a received frame through a layer's RX buffer, counting it
+#[cfg(feature = "joint_api")]
+fn decode_message<E: core::fmt::Debug>(
+    data: &[u8],
+    buffer: &mut [u8],
+    stats: &mut TransportStats,
+) -> Result<Message, TransportError<E>> {
+    let len = copy_frame(data, buffer, stats)?;
+
+    // Deserialize
+    Message::deserialize(&buffer[..len]).map_err(|_| {
+        stats.record_error(TransportErrorKind::Deserialization);
+        TransportError::DeserializationFailed
+    })
+}
+
+/// Count a received frame and copy it into a layer's RX buffer
+#[cfg(feature = "joint_api")]
+fn copy_frame<E: core::fmt::Debug>(
+    data: &[u8],
+    buffer: &mut [u8],
+    stats: &mut TransportStats,
+) -> Result<usize, TransportError<E>> {
+    stats.rx_frames = stats.rx_frames.wrapping_add(1);
+
+    // Reject rather than truncate frames longer than the buffer
+    if data.len() > buffer.len() {
+        stats.record_error(TransportErrorKind::FrameTooLarge);
+        return Err(TransportError::FrameTooLarge);
+    }
+
+    // Copy data to our buffer (needed because transport may reuse its buffer)
+    let len = data.len();
+    buffer[..len].copy_from_slice(data);
+    Ok(len)
+}
+
+// ============================================================================
+// Async variants for executor-based firmware (embassy etc.)
+// ============================================================================
+
+/// Async counterpart of [`EmbeddedTransport`]
+///
+/// Drivers built on an async HAL (such as the embassy-based CAN transports)
+/// implement this instead, so they can be wrapped in an
+/// [`AsyncTransportLayer`] and driven by [`Joint::process_transport_async`].
+///
+/// [`Joint::process_transport_async`]: crate::Joint::process_transport_async
+#[cfg(feature = "joint_api")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncEmbeddedTransport {
+    /// Transport-specific error type
+    type Error: core::fmt::Debug;
+
+    /// Send raw bytes over the transport, waiting until they are queued
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Wait for the next frame and return its raw bytes
+    async fn receive(&mut self) -> Result<&[u8], Self::Error>;
+
+    /// Check if transport is ready for communication
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    /// Largest encoded message one `send` call can carry, in bytes
+    ///
+    /// Same meaning and default as [`EmbeddedTransport::mtu`].
+    fn mtu(&self) -> usize {
+        Message::max_size()
+    }
+}
+
+/// Async transport layer that handles message serialization/deserialization
+///
+/// Same buffers (sized by `N` the same way) and statistics as
+/// [`TransportLayer`], but sending and receiving await the underlying
+/// [`AsyncEmbeddedTransport`].
+///
+/// # Example
+/// ```no_run
+/// use irpc::{AsyncTransportLayer, Joint};
+///
+/// # async fn run(can_transport: impl irpc::AsyncEmbeddedTransport) {
+/// let mut transport = AsyncTransportLayer::new(can_transport);
+/// let mut joint = Joint::new(0x0010);
+///
+/// loop {
+///     // Waits for a message, handles it and sends the response
+///     if let Err(e) = joint.process_transport_async(&mut transport).await {
+///         // Handle transport error
+///     }
+/// }
+/// # }
+/// ```
+#[cfg(feature = "joint_api")]
+pub struct AsyncTransportLayer<T: AsyncEmbeddedTransport, const N: usize = DEFAULT_TRANSPORT_BUFFER_LEN> {
+    transport: T,
+    rx_buffer: [u8; N],
+    tx_buffer: [u8; N],
+    stats: TransportStats,
+}
+
+#[cfg(feature = "joint_api")]
+impl<T: AsyncEmbeddedTransport> AsyncTransportLayer<T> {
+    /// Create a new transport layer wrapping an async embedded transport
+    pub fn new(transport: T) -> Self {
+        Self::with_buffers(transport)
+    }
+}
+
+#[cfg(feature = "joint_api")]
+impl<T: AsyncEmbeddedTransport, const N: usize> AsyncTransportLayer<T, N> {
+    /// Create a transport layer with `N`-byte buffers, e.g.
+    /// `AsyncTransportLayer::<_, CANFD_MAX_DATA_LEN>::with_buffers(transport)`
+    pub fn with_buffers(transport: T) -> Self {
+        let stats = TransportStats::with_mtu(transport.mtu().min(N));
+        Self {
+            transport,
+            rx_buffer: [0u8; N],
+            tx_buffer: [0u8; N],
+            stats,
+        }
+    }
+
+    /// Send a message (automatically serializes)
+    pub async fn send_message(&mut self, message: &Message) -> Result<(), TransportError<T::Error>> {
+        let len = encode_message(message, &mut self.tx_buffer, self.transport.mtu(), &mut self.stats)?;
+
+        match self.transport.send(&self.tx_buffer[..len]).await {
+            Ok(()) => {
+                self.stats.tx_frames = self.stats.tx_frames.wrapping_add(1);
+                Ok(())
+            }
+            Err(e) => {
+                self.stats.record_error(TransportErrorKind::Transport);
+                Err(TransportError::TransportError(e))
+            }
+        }
+    }
+
+    /// Wait for the next message (automatically deserializes)
+    pub async fn receive_message(&mut self) -> Result<Message, TransportError<T::Error>> {
+        match self.transport.receive().await {
+            Ok(data) => decode_message(data, &mut self.rx_buffer, &mut self.stats),
+            Err(e) => {
+                self.stats.record_error(TransportErrorKind::Transport);
+                Err(TransportError::TransportError(e))
+            }
+        }
+    }
+
+    /// Get the transport statistics collected so far
+    pub fn stats(&self) -> &TransportStats {
+        &self.stats
+    }
+
+    /// Get mutable statistics, e.g. to record CRC or bus-off events the
+    /// driver observes below this layer
+    pub fn stats_mut(&mut self) -> &mut TransportStats {
+        &mut self.stats
+    }
+
+    /// Reset all statistics to zero
+    pub fn reset_stats(&mut self) {
+        self.stats = TransportStats::with_mtu(self.mtu());
+    }
+
+    /// Check if the transport is ready
+    pub fn is_ready(&self) -> bool {
+        self.transport.is_ready()
+    }
+
+    /// Largest encoded message the layer sends: the transport's MTU, or
+    /// the buffer size if that is smaller
+    pub fn mtu(&self) -> usize {
+        self.transport.mtu().min(N)
+    }
+
+    /// Get a mutable reference to the underlying transport
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Get a reference to the underlying transport
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Consume the layer and return the underlying transport
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+}
+
+/// Transport layer errors
+#[cfg(feature = "joint_api")]
+#[derive(Debug)]
+pub enum TransportError<E: core::fmt::Debug> {
+    /// Failed to serialize message
+    SerializationFailed,
+    /// Failed to deserialize message
+    DeserializationFailed,
+    /// Frame is larger than the layer's buffer (received), or than the
+    /// buffer or the MTU (sent)
+    FrameTooLarge,
+    /// Underlying transport error
+    TransportError(E),
+}
+
+#[cfg(feature = "joint_api")]
+impl<E: core::fmt::Debug> From<TransportError<E>> for ProtocolError {
+    fn from(e: TransportError<E>) -> Self {
+        match e {
+            TransportError::SerializationFailed => ProtocolError::SerializationError(crate::protocol::error_text("Transport serialization failed")),
+            TransportError::DeserializationFailed => ProtocolError::DeserializationError(crate::protocol::error_text("Transport deserialization failed")),
+            TransportError::FrameTooLarge => ProtocolError::InvalidMessage,
+            TransportError::TransportError(_) => ProtocolError::IoError(0),
+        }
+    }
+}
\ No newline at end of file
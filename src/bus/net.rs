@@ -0,0 +1,439 @@
+//! iRPC over IP networks
+//!
+//! [`NetworkAdapter`] implements [`CommunicationAdapter`] over UDP or TCP, so
+//! an arm controller on one machine can talk to a CAN gateway or simulator
+//! on another:
+//!
+//! - UDP carries one encoded message per datagram. Without a fixed peer,
+//!   replies go to whoever sent the last datagram.
+//! - TCP carries messages as [`framing`](crate::framing) frames (COBS with
+//!   a length and CRC, delimited by zero bytes). A client reconnects with exponential backoff
+//!   (`NET_RECONNECT_MIN_MS` to `NET_RECONNECT_MAX_MS`) whenever the
+//!   connection drops; a listener serves the most recent connection.
+//!
+//! Messages that arrive while nothing is polling are queued, and transmits
+//! while disconnected fail with [`NetworkError::NotConnected`].
+//!
+//! Gateways and simulators can announce themselves with
+//! [`spawn_beacon`]; hosts find them with [`discover_peers`].
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::mpsc;
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::{debug, info, warn};
+
+use super::{CommunicationAdapter, DeviceInfo};
+use crate::config::{NET_RECONNECT_MAX_MS, NET_RECONNECT_MIN_MS, RECEIVE_BATCH_LEN};
+use crate::framing::{encode_message, FrameDecoder, FramingError, MAX_MESSAGE_FRAME_LEN};
+use crate::protocol::{Message, ProtocolError};
+
+/// Largest message either link accepts; any message fits
+const MAX_FRAME_LEN: usize = Message::max_size();
+
+/// Network adapter errors
+#[derive(Debug, thiserror::Error)]
+pub enum NetworkError {
+    /// No TCP connection, or no UDP peer known yet
+    #[error("Not connected")]
+    NotConnected,
+
+    /// Socket error
+    #[error("Network I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// A message could not be encoded
+    #[error("Protocol error: {0}")]
+    Protocol(#[from] ProtocolError),
+
+    /// A message could not be framed
+    #[error("Framing error: {0}")]
+    Framing(#[from] FramingError),
+}
+
+/// The current TCP connection's write half, tagged with its generation
+type TcpWriter = Arc<tokio::sync::Mutex<Option<(u64, OwnedWriteHalf)>>>;
+
+enum Link {
+    Udp {
+        socket: Arc<UdpSocket>,
+        peer: Arc<Mutex<Option<SocketAddr>>>,
+    },
+    Tcp {
+        writer: TcpWriter,
+        connected: Arc<AtomicBool>,
+    },
+}
+
+/// [`CommunicationAdapter`] over UDP or TCP
+pub struct NetworkAdapter {
+    link: Link,
+    local_addr: Option<SocketAddr>,
+    inbound_rx: Mutex<mpsc::UnboundedReceiver<Message>>,
+    task: JoinHandle<()>,
+}
+
+impl NetworkAdapter {
+    /// Exchange datagrams on `bind`, with `peer` or with whoever sends first
+    pub async fn udp(bind: SocketAddr, peer: Option<SocketAddr>) -> io::Result<Self> {
+        let socket = Arc::new(UdpSocket::bind(bind).await?);
+        let local_addr = socket.local_addr()?;
+        let fixed_peer = peer.is_some();
+        let peer = Arc::new(Mutex::new(peer));
+        let (inbound_tx, inbound_rx) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn({
+            let socket = socket.clone();
+            let peer = peer.clone();
+            async move {
+                let mut buf = [0u8; MAX_FRAME_LEN];
+                loop {
+                    let (len, from) = match socket.recv_from(&mut buf).await {
+                        Ok(received) => received,
+                        Err(e) => {
+                            // e.g. ICMP port unreachable from an earlier send
+                            debug!("UDP receive failed: {}", e);
+                            continue;
+                        }
+                    };
+                    match Message::deserialize(&buf[..len]) {
+                        Ok(message) => {
+                            if !fixed_peer {
+                                *peer.lock().unwrap() = Some(from);
+                            }
+                            if inbound_tx.send(message).is_err() {
+                                return;
+                            }
+                        }
+                        Err(e) => warn!("Dropping malformed datagram from {}: {:?}", from, e),
+                    }
+                }
+            }
+        });
+
+        info!("UDP adapter listening on {}", local_addr);
+        Ok(Self {
+            link: Link::Udp { socket, peer },
+            local_addr: Some(local_addr),
+            inbound_rx: Mutex::new(inbound_rx),
+            task,
+        })
+    }
+
+    /// Connect to a TCP server, reconnecting whenever the connection drops
+    ///
+    /// Returns immediately; [`is_connected`](CommunicationAdapter::is_connected)
+    /// turns true once the first connection is up.
+    pub fn tcp_connect(addr: SocketAddr) -> Self {
+        let writer = TcpWriter::default();
+        let connected = Arc::new(AtomicBool::new(false));
+        let (inbound_tx, inbound_rx) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn({
+            let writer = writer.clone();
+            let connected = connected.clone();
+            async move {
+                let min = Duration::from_millis(NET_RECONNECT_MIN_MS);
+                let mut backoff = min;
+                for generation in 0.. {
+                    match TcpStream::connect(addr).await {
+                        Ok(stream) => {
+                            info!("Connected to {}", addr);
+                            backoff = min;
+                            let result = serve_tcp(stream, generation, &writer, &connected, &inbound_tx).await;
+                            warn!("Connection to {} lost: {:?}", addr, result);
+                        }
+                        Err(e) => debug!("Connecting to {} failed: {}", addr, e),
+                    }
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(Duration::from_millis(NET_RECONNECT_MAX_MS));
+                }
+            }
+        });
+
+        Self {
+            link: Link::Tcp { writer, connected },
+            local_addr: None,
+            inbound_rx: Mutex::new(inbound_rx),
+            task,
+        }
+    }
+
+    /// Accept TCP connections on `bind`
+    ///
+    /// A new connection replaces the current one, so a client that lost its
+    /// connection without the listener noticing can come straight back.
+    pub async fn tcp_listen(bind: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(bind).await?;
+        let local_addr = listener.local_addr()?;
+        let writer = TcpWriter::default();
+        let connected = Arc::new(AtomicBool::new(false));
+        let (inbound_tx, inbound_rx) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn({
+            let writer = writer.clone();
+            let connected = connected.clone();
+            async move {
+                // Dropped (aborting the connection task) when the adapter is
+                let mut connection = JoinSet::new();
+                for generation in 0.. {
+                    let (stream, peer) = match listener.accept().await {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            warn!("Accept on {} failed: {}", local_addr, e);
+                            continue;
+                        }
+                    };
+                    info!("Accepted connection from {}", peer);
+                    connection.abort_all();
+                    while connection.try_join_next().is_some() {}
+                    let (writer, connected, inbound_tx) = (writer.clone(), connected.clone(), inbound_tx.clone());
+                    connection.spawn(async move {
+                        let result = serve_tcp(stream, generation, &writer, &connected, &inbound_tx).await;
+                        debug!("Connection from {} closed: {:?}", peer, result);
+                    });
+                }
+            }
+        });
+
+        info!("TCP adapter listening on {}", local_addr);
+        Ok(Self {
+            link: Link::Tcp { writer, connected },
+            local_addr: Some(local_addr),
+            inbound_rx: Mutex::new(inbound_rx),
+            task,
+        })
+    }
+
+    /// Address the adapter is bound to (`None` for TCP clients)
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+}
+
+impl Drop for NetworkAdapter {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Read frames from one connection until it fails
+async fn serve_tcp(
+    stream: TcpStream,
+    generation: u64,
+    writer: &TcpWriter,
+    connected: &AtomicBool,
+    inbound_tx: &mpsc::UnboundedSender<Message>,
+) -> Result<(), NetworkError> {
+    stream.set_nodelay(true)?;
+    let (mut read, write) = stream.into_split();
+    *writer.lock().await = Some((generation, write));
+    connected.store(true, Ordering::Relaxed);
+
+    let result = read_frames(&mut read, inbound_tx).await;
+
+    // A newer connection may already have replaced this one
+    let mut writer = writer.lock().await;
+    if writer.as_ref().is_some_and(|(g, _)| *g == generation) {
+        *writer = None;
+        connected.store(false, Ordering::Relaxed);
+    }
+    result
+}
+
+async fn read_frames(
+    read: &mut OwnedReadHalf,
+    inbound_tx: &mpsc::UnboundedSender<Message>,
+) -> Result<(), NetworkError> {
+    let mut decoder = FrameDecoder::<MAX_MESSAGE_FRAME_LEN>::new();
+    let mut buf = [0u8; 512];
+    loop {
+        let len = read.read(&mut buf).await?;
+        if len == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        for &byte in &buf[..len] {
+            match decoder.push_message(byte) {
+                Some(Ok(message)) => {
+                    // Nothing left to deliver to once the adapter is gone
+                    let Ok(()) = inbound_tx.send(message) else { return Ok(()) };
+                }
+                Some(Err(e)) => warn!("Dropping malformed frame: {:?}", e),
+                None => {}
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl CommunicationAdapter for NetworkAdapter {
+    type Error = NetworkError;
+
+    async fn transmit(&self, message: &Message) -> Result<(), Self::Error> {
+        match &self.link {
+            Link::Udp { socket, peer } => {
+                let mut datagram = [0u8; MAX_FRAME_LEN];
+                let len = message.serialize_into(&mut datagram)?;
+                let peer = (*peer.lock().unwrap()).ok_or(NetworkError::NotConnected)?;
+                socket.send_to(&datagram[..len], peer).await?;
+            }
+            Link::Tcp { writer, connected } => {
+                let mut frame = [0u8; MAX_MESSAGE_FRAME_LEN];
+                let len = encode_message(message, &mut frame)?;
+
+                let mut writer = writer.lock().await;
+                let (_, write) = writer.as_mut().ok_or(NetworkError::NotConnected)?;
+                if let Err(e) = write.write_all(&frame[..len]).await {
+                    // The reader sees the failure too and the connection is re-established
+                    *writer = None;
+                    connected.store(false, Ordering::Relaxed);
+                    return Err(e.into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<Option<Message>, Self::Error> {
+        Ok(self.inbound_rx.lock().unwrap().try_recv().ok())
+    }
+
+    async fn receive_batch(&self, batch: &mut Vec<Message>) -> Result<(), Self::Error> {
+        let mut inbound_rx = self.inbound_rx.lock().unwrap();
+        while batch.len() < RECEIVE_BATCH_LEN {
+            let Ok(message) = inbound_rx.try_recv() else { break };
+            batch.push(message);
+        }
+        Ok(())
+    }
+
+    /// The network hides the bus behind it; use `CommunicationManager::discover`
+    async fn discover_devices(&self) -> Result<Vec<DeviceInfo>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    fn is_connected(&self) -> bool {
+        match &self.link {
+            Link::Udp { peer, .. } => peer.lock().unwrap().is_some(),
+            Link::Tcp { connected, .. } => connected.load(Ordering::Relaxed),
+        }
+    }
+
+    fn mtu(&self) -> usize {
+        MAX_FRAME_LEN
+    }
+}
+
+// ============================================================================
+// Peer discovery
+// ============================================================================
+
+/// Link a beacon's sender accepts connections on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum NetTransport {
+    /// [`NetworkAdapter::udp`]
+    Udp = 0,
+    /// [`NetworkAdapter::tcp_listen`], reached with [`NetworkAdapter::tcp_connect`]
+    Tcp = 1,
+}
+
+/// Periodic announcement of an iRPC endpoint
+///
+/// Eight bytes on the wire: `iRPC`, format version, transport, port (big-endian).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Beacon {
+    /// How to talk to the endpoint
+    pub transport: NetTransport,
+    /// Port the endpoint listens on (at the beacon's source address)
+    pub port: u16,
+}
+
+impl Beacon {
+    const MAGIC: [u8; 4] = *b"iRPC";
+    const VERSION: u8 = 1;
+
+    /// Wire encoding
+    pub fn encode(&self) -> [u8; 8] {
+        let port = self.port.to_be_bytes();
+        let [m0, m1, m2, m3] = Self::MAGIC;
+        [m0, m1, m2, m3, Self::VERSION, self.transport as u8, port[0], port[1]]
+    }
+
+    /// Parse a received datagram (`None` if it is not a beacon)
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let [m0, m1, m2, m3, version, transport, p0, p1] = *bytes else {
+            return None;
+        };
+        if [m0, m1, m2, m3] != Self::MAGIC || version != Self::VERSION {
+            return None;
+        }
+        let transport = match transport {
+            0 => NetTransport::Udp,
+            1 => NetTransport::Tcp,
+            _ => return None,
+        };
+        Some(Self { transport, port: u16::from_be_bytes([p0, p1]) })
+    }
+}
+
+/// An endpoint found by [`discover_peers`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Peer {
+    /// Address to connect to (beacon source IP, announced port)
+    pub addr: SocketAddr,
+    /// How to talk to it
+    pub transport: NetTransport,
+}
+
+/// Send `beacon` to `target` every `period` until the task is aborted
+///
+/// `target` is typically the subnet broadcast address on `NET_BEACON_PORT`.
+pub fn spawn_beacon(target: SocketAddr, beacon: Beacon, period: Duration) -> JoinHandle<io::Result<()>> {
+    tokio::spawn(async move {
+        let bind: SocketAddr = if target.is_ipv4() {
+            (std::net::Ipv4Addr::UNSPECIFIED, 0).into()
+        } else {
+            (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+        let socket = UdpSocket::bind(bind).await?;
+        socket.set_broadcast(true)?;
+        let bytes = beacon.encode();
+        loop {
+            if let Err(e) = socket.send_to(&bytes, target).await {
+                debug!("Beacon to {} failed: {}", target, e);
+            }
+            tokio::time::sleep(period).await;
+        }
+    })
+}
+
+/// Listen on `listen` for `window` and return the endpoints that announced themselves
+pub async fn discover_peers(listen: SocketAddr, window: Duration) -> io::Result<Vec<Peer>> {
+    let socket = UdpSocket::bind(listen).await?;
+    let deadline = tokio::time::Instant::now() + window;
+    let mut peers = Vec::new();
+    let mut buf = [0u8; 16];
+    while let Ok(received) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await {
+        let (len, from) = received?;
+        if let Some(beacon) = Beacon::decode(&buf[..len]) {
+            let peer = Peer {
+                addr: SocketAddr::new(from.ip(), beacon.port),
+                transport: beacon.transport,
+            };
+            if !peers.contains(&peer) {
+                peers.push(peer);
+            }
+        }
+    }
+    peers.sort();
+    Ok(peers)
+}
@@ -0,0 +1,436 @@
+//! Recording bus traffic to disk and replaying it
+//!
+//! [`TelemetryRecorder`] wraps any [`CommunicationAdapter`] and logs the
+//! messages passing through it, all of them or only `TelemetryStream`
+//! samples, to a compact binary file. [`TelemetryPlayer`] is an adapter that
+//! feeds a recorded log back to a [`CommunicationManager`](crate::CommunicationManager)
+//! at its original pace, so control and analysis code can be run offline and
+//! regression-tested against real data; [`replay_into`](TelemetryPlayer::replay_into)
+//! sends it onto another adapter instead.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), irpc::bus::record::RecordingError> {
+//! use irpc::bus::record::{RecordScope, TelemetryPlayer, TelemetryRecorder};
+//! use irpc::bus::net::NetworkAdapter;
+//! use irpc::CommunicationManager;
+//! use std::sync::Arc;
+//!
+//! // Record a session with the robot
+//! let robot = Arc::new(NetworkAdapter::tcp_connect("10.0.0.2:47101".parse().unwrap()));
+//! let recorder = Arc::new(TelemetryRecorder::create("session.irpclog", robot, RecordScope::All)?);
+//! let comm = CommunicationManager::with_adapter(recorder);
+//!
+//! // ... later, replay it without the robot
+//! let player = Arc::new(TelemetryPlayer::open("session.irpclog")?);
+//! let comm = CommunicationManager::with_adapter(player);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Log format
+//!
+//! An 8-byte header (`iRPClog` and a format version byte) followed by one
+//! record per message: a 2-byte big-endian length, then the postcard
+//! encoding of a [`LogRecord`]. A record cut short by a crash ends the log.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::{CommunicationAdapter, DeviceInfo};
+use crate::clock::{Clock, SystemClock};
+use crate::protocol::{Message, Payload};
+
+/// File header: format name and version
+const LOG_MAGIC: [u8; 8] = *b"iRPClog\x01";
+
+/// Largest encoded record: a varint timestamp, the direction and a message
+const MAX_RECORD_LEN: usize = 10 + 1 + Message::max_size();
+
+/// Recording and replay errors
+#[derive(Debug, thiserror::Error)]
+pub enum RecordingError {
+    /// Reading or writing the log failed
+    #[error("Recording I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// The file does not start with the log header
+    #[error("Not an iRPC log (bad header)")]
+    NotALog,
+
+    /// A complete record could not be decoded
+    #[error("Corrupt log record {index}: {reason}")]
+    Corrupt { index: usize, reason: String },
+}
+
+/// Which way a recorded message travelled, seen from the host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    /// Sent by the host
+    Transmitted,
+    /// Received from the bus
+    Received,
+}
+
+/// Which messages a recorder logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordScope {
+    /// Everything transmitted and received
+    #[default]
+    All,
+    /// Received `TelemetryStream` samples only
+    TelemetryStream,
+}
+
+impl RecordScope {
+    fn includes(self, direction: Direction, message: &Message) -> bool {
+        match self {
+            Self::All => true,
+            Self::TelemetryStream => {
+                direction == Direction::Received && matches!(message.payload, Payload::TelemetryStream(_))
+            }
+        }
+    }
+}
+
+/// One logged message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogRecord {
+    /// Time since the recording started, in microseconds
+    pub timestamp_us: u64,
+    pub direction: Direction,
+    pub message: Message,
+}
+
+/// One line per record: seconds, `TX`/`RX`, then the message
+impl std::fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let direction = match self.direction {
+            Direction::Transmitted => "TX",
+            Direction::Received => "RX",
+        };
+        write!(
+            f,
+            "{:>6}.{:06} {} {}",
+            self.timestamp_us / 1_000_000,
+            self.timestamp_us % 1_000_000,
+            direction,
+            self.message
+        )
+    }
+}
+
+/// Read every record of a log
+pub fn read_log(reader: impl Read) -> Result<Vec<LogRecord>, RecordingError> {
+    let mut reader = BufReader::new(reader);
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic).map_err(|_| RecordingError::NotALog)?;
+    if magic != LOG_MAGIC {
+        return Err(RecordingError::NotALog);
+    }
+
+    let mut records = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        let mut len = [0u8; 2];
+        match reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        buf.resize(u16::from_be_bytes(len) as usize, 0);
+        match reader.read_exact(&mut buf) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                warn!("Log ends in a truncated record after {} records", records.len());
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        let record = postcard::from_bytes(&buf).map_err(|e| RecordingError::Corrupt {
+            index: records.len(),
+            reason: e.to_string(),
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Read every record of the log at `path`
+pub fn open_log(path: impl AsRef<Path>) -> Result<Vec<LogRecord>, RecordingError> {
+    read_log(File::open(path)?)
+}
+
+/// Adapter wrapper that logs the traffic passing through it
+///
+/// A failed write is logged and does not disturb the bus; the recorder
+/// keeps trying with later messages.
+pub struct TelemetryRecorder<A> {
+    inner: Arc<A>,
+    scope: RecordScope,
+    writer: Mutex<Box<dyn Write + Send>>,
+    clock: Arc<dyn Clock>,
+    records: AtomicU64,
+}
+
+impl<A> TelemetryRecorder<A> {
+    /// Record `inner`'s traffic to a new file at `path`
+    pub fn create(path: impl AsRef<Path>, inner: Arc<A>, scope: RecordScope) -> Result<Self, RecordingError> {
+        Self::new(BufWriter::new(File::create(path)?), inner, scope)
+    }
+
+    /// Record `inner`'s traffic to `writer`
+    pub fn new(
+        mut writer: impl Write + Send + 'static,
+        inner: Arc<A>,
+        scope: RecordScope,
+    ) -> Result<Self, RecordingError> {
+        writer.write_all(&LOG_MAGIC)?;
+        Ok(Self {
+            inner,
+            scope,
+            writer: Mutex::new(Box::new(writer)),
+            clock: Arc::new(SystemClock::new()),
+            records: AtomicU64::new(0),
+        })
+    }
+
+    /// Timestamp records with `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The wrapped adapter
+    pub fn inner(&self) -> &Arc<A> {
+        &self.inner
+    }
+
+    /// Records written so far
+    pub fn records(&self) -> u64 {
+        self.records.load(Ordering::Relaxed)
+    }
+
+    /// Push buffered records to the file
+    pub fn flush(&self) -> Result<(), RecordingError> {
+        Ok(self.writer.lock().unwrap().flush()?)
+    }
+
+    fn log(&self, direction: Direction, message: &Message) {
+        if !self.scope.includes(direction, message) {
+            return;
+        }
+        let record = LogRecord {
+            timestamp_us: self.clock.now().as_micros() as u64,
+            direction,
+            message: message.clone(),
+        };
+        let mut buf = [0u8; MAX_RECORD_LEN];
+        let result = postcard::to_slice(&record, &mut buf)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            .and_then(|bytes| {
+                let mut writer = self.writer.lock().unwrap();
+                writer.write_all(&(bytes.len() as u16).to_be_bytes())?;
+                writer.write_all(bytes)
+            });
+        match result {
+            Ok(()) => {
+                self.records.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => warn!("Failed to record message {}: {}", message.header.msg_id, e),
+        }
+    }
+}
+
+#[async_trait]
+impl<A: CommunicationAdapter> CommunicationAdapter for TelemetryRecorder<A> {
+    type Error = A::Error;
+
+    async fn transmit(&self, message: &Message) -> Result<(), Self::Error> {
+        self.inner.transmit(message).await?;
+        self.log(Direction::Transmitted, message);
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<Option<Message>, Self::Error> {
+        let message = self.inner.receive().await?;
+        if let Some(message) = &message {
+            self.log(Direction::Received, message);
+        }
+        Ok(message)
+    }
+
+    async fn receive_batch(&self, batch: &mut Vec<Message>) -> Result<(), Self::Error> {
+        let start = batch.len();
+        self.inner.receive_batch(batch).await?;
+        for message in &batch[start..] {
+            self.log(Direction::Received, message);
+        }
+        Ok(())
+    }
+
+    async fn discover_devices(&self) -> Result<Vec<DeviceInfo>, Self::Error> {
+        self.inner.discover_devices().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    fn mtu(&self) -> usize {
+        self.inner.mtu()
+    }
+
+    async fn flush(&self) -> Result<(), Self::Error> {
+        if let Err(e) = TelemetryRecorder::flush(self) {
+            warn!("Failed to flush recording: {}", e);
+        }
+        self.inner.flush().await
+    }
+}
+
+/// Adapter that replays the received messages of a recording
+///
+/// Messages are delivered at their recorded spacing on the player's clock,
+/// starting from the first poll, or all at once when [`unpaced`](Self::unpaced).
+/// Transmitted messages go nowhere; they are kept for inspection with
+/// [`transmitted`](Self::transmitted).
+pub struct TelemetryPlayer {
+    queue: Mutex<VecDeque<LogRecord>>,
+    devices: Vec<DeviceInfo>,
+    clock: Arc<dyn Clock>,
+    paced: bool,
+    /// Clock time of the first poll and the timestamp it maps to
+    start: Mutex<Option<(Duration, u64)>>,
+    transmitted: Mutex<Vec<Message>>,
+}
+
+impl TelemetryPlayer {
+    /// Replay the received messages among `records`
+    pub fn new(records: impl IntoIterator<Item = LogRecord>) -> Self {
+        let queue: VecDeque<_> = records
+            .into_iter()
+            .filter(|record| record.direction == Direction::Received)
+            .collect();
+        let devices = queue
+            .iter()
+            .filter_map(|record| match &record.message.payload {
+                Payload::Hello(hello) => Some(DeviceInfo {
+                    id: record.message.header.source_id,
+                    entity_type: hello.entity_type,
+                    mode: hello.mode,
+                    capabilities: hello.capabilities,
+                    schema: hello.schema,
+                }),
+                _ => None,
+            })
+            .collect();
+        Self {
+            queue: Mutex::new(queue),
+            devices,
+            clock: Arc::new(SystemClock::new()),
+            paced: true,
+            start: Mutex::new(None),
+            transmitted: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Replay the log at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        Ok(Self::new(open_log(path)?))
+    }
+
+    /// Pace replay by `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Deliver every message as soon as it is polled
+    pub fn unpaced(mut self) -> Self {
+        self.paced = false;
+        self
+    }
+
+    /// Messages not yet delivered
+    pub fn remaining(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    /// Whether every message has been delivered
+    pub fn is_finished(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Messages the host transmitted during the replay
+    pub fn transmitted(&self) -> Vec<Message> {
+        self.transmitted.lock().unwrap().clone()
+    }
+
+    /// Clock time at which `record` is due
+    fn due(&self, record: &LogRecord) -> Duration {
+        let (started, first_us) =
+            *self.start.lock().unwrap().get_or_insert((self.clock.now(), record.timestamp_us));
+        started + Duration::from_micros(record.timestamp_us.saturating_sub(first_us))
+    }
+
+    /// Next message, if it is due
+    fn next_due(&self) -> Option<Message> {
+        let mut queue = self.queue.lock().unwrap();
+        let record = queue.front()?;
+        if self.paced && self.due(record) > self.clock.now() {
+            return None;
+        }
+        queue.pop_front().map(|record| record.message)
+    }
+
+    /// Transmit the remaining messages on `adapter`, keeping their spacing
+    ///
+    /// Returns how many were sent.
+    pub async fn replay_into<A: CommunicationAdapter + ?Sized>(&self, adapter: &A) -> Result<usize, A::Error> {
+        let mut sent = 0;
+        loop {
+            let Some(record) = self.queue.lock().unwrap().front().cloned() else {
+                break;
+            };
+            if self.paced {
+                self.clock.sleep_until(self.due(&record)).await;
+            }
+            adapter.transmit(&record.message).await?;
+            self.queue.lock().unwrap().pop_front();
+            sent += 1;
+        }
+        debug!("Replayed {} recorded messages", sent);
+        Ok(sent)
+    }
+}
+
+#[async_trait]
+impl CommunicationAdapter for TelemetryPlayer {
+    type Error = std::convert::Infallible;
+
+    async fn transmit(&self, message: &Message) -> Result<(), Self::Error> {
+        self.transmitted.lock().unwrap().push(message.clone());
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<Option<Message>, Self::Error> {
+        Ok(self.next_due())
+    }
+
+    async fn discover_devices(&self) -> Result<Vec<DeviceInfo>, Self::Error> {
+        Ok(self.devices.clone())
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,595 @@
+//! In-process simulation of a bus of joints
+//!
+//! [`SimBus`] implements [`CommunicationAdapter`] for the arm side and runs
+//! real [`Joint`] state machines on the other, so host code can be exercised
+//! end-to-end without hardware:
+//!
+//! ```
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> Result<(), irpc::ProtocolError> {
+//! use irpc::bus::sim::SimBus;
+//! use irpc::{ArmClient, CommunicationManager, LifecycleState};
+//! use std::sync::Arc;
+//!
+//! let bus = Arc::new(SimBus::with_joints([0x0010, 0x0020]));
+//! let mut client = ArmClient::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+//! client.add_joint(0x0010);
+//! client.add_joint(0x0020);
+//!
+//! client.initialize().await?;
+//! assert_eq!(bus.joint_state(0x0020), Some(LifecycleState::Active));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Every message is encoded and decoded on its way through, so size limits
+//! (the bus [MTU](SimBus::with_mtu)) and wire-format regressions show up as
+//! they would on a real bus. Messages to IDs without a joint are dropped, and
+//! the request times out. Joints answer bus statistics requests with zeroed
+//! counters and the bus MTU. A joint told to `EnterBootloader` restarts as
+//! its bootloader, which only answers discovery, status and boot info.
+//! Each joint has an in-memory [config store](crate::config_store), so
+//! `SaveConfig`, `LoadConfig` and `AssignId` work as on hardware; attached
+//! joints advertise `CONFIG_STORE` and `FIRMWARE_UPDATE` on top of their
+//! own capabilities. [Auxiliary I/O](SimBus::set_aux_io) can be wired to a
+//! joint with any [`AuxIoHandler`]. Homing finds the reference at once: the
+//! joint is back in `Active`, at the homing offset, by the time it replies.
+//! The true position is the joint's raw encoder position, so an encoder
+//! offset shifts what the joint reports but not where it physically is.
+//!
+//! Joints see time from the bus [clock](SimBus::with_clock): `tokio::time`
+//! by default, so discovery backoff and timeouts behave deterministically on
+//! a runtime with paused time, or a [`VirtualClock`](crate::clock::VirtualClock)
+//! shared with the communication manager to step both sides together.
+//!
+//! With [telemetry](SimBus::with_telemetry) enabled, active joints follow
+//! their `SetTarget`/`SetTargetV2` setpoints at the commanded velocity and stream
+//! `TelemetryStream` samples. Each joint's [`SensorModel`] makes those
+//! samples non-ideal (encoder quantization, Gaussian noise, transport
+//! latency and temperature drift) so host-side filters and settle detection
+//! can be tested against realistic data; [`true_position`](SimBus::true_position)
+//! gives the ground truth to compare with.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::vec::Vec;
+
+use async_trait::async_trait;
+
+use super::{CommunicationAdapter, DeviceInfo};
+use crate::clock::{Clock, SystemClock};
+use crate::config::{
+    ARM_DEVICE_ID, BROADCAST_ADDRESS, CANFD_MAX_DATA_LEN, RECEIVE_BATCH_LEN, SIM_AMBIENT_TEMPERATURE_C,
+    SIM_SUPPLY_VOLTAGE_V,
+};
+use crate::aux_io::AuxIoHandler;
+use crate::config_store::MemoryConfigStore;
+use crate::joint::Joint;
+use crate::protocol::{
+    BootMode, Capabilities, DeviceId, Header, LifecycleState, Message, MessageId, MultiTurnPosition, Payload,
+    ProtocolError, TelemetryStream, TransportStats, SCHEMA_HASH,
+};
+
+/// Non-ideal sensor behaviour of a simulated joint
+///
+/// The default is an ideal sensor: exact values, no delay, constant
+/// temperature.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SensorModel {
+    /// Encoder step in degrees; reported positions are multiples of it (0 = exact)
+    pub resolution_deg: f32,
+    /// Standard deviation of position noise in degrees, applied before quantization
+    pub position_noise_deg: f32,
+    /// Standard deviation of velocity noise in degrees/second
+    pub velocity_noise_dps: f32,
+    /// Time from sampling until the sample reaches the arm
+    pub latency: Duration,
+    /// Temperature reported at time zero, in Celsius
+    pub ambient_c: f32,
+    /// Change of the reported temperature per second of bus time, in Celsius
+    pub temperature_drift_c_per_s: f32,
+    /// Standard deviation of temperature noise in Celsius
+    pub temperature_noise_c: f32,
+    /// Supply voltage in volts, fed to the joint on every poll
+    pub supply_v: f32,
+    /// Noise seed; mixed with the joint ID so joints do not share noise
+    pub seed: u64,
+}
+
+impl SensorModel {
+    /// Quantize positions like an encoder with `bits` of resolution per turn
+    pub fn with_encoder_bits(mut self, bits: u32) -> Self {
+        self.resolution_deg = 360.0 / (1u64 << bits) as f32;
+        self
+    }
+}
+
+impl Default for SensorModel {
+    fn default() -> Self {
+        Self {
+            resolution_deg: 0.0,
+            position_noise_deg: 0.0,
+            velocity_noise_dps: 0.0,
+            latency: Duration::ZERO,
+            ambient_c: SIM_AMBIENT_TEMPERATURE_C,
+            temperature_drift_c_per_s: 0.0,
+            temperature_noise_c: 0.0,
+            supply_v: SIM_SUPPLY_VOLTAGE_V,
+            seed: 0,
+        }
+    }
+}
+
+/// Ideal motion of a joint towards its last setpoint
+#[derive(Default)]
+struct Motion {
+    position: f32,
+    velocity: f32,
+    target: f32,
+    velocity_limit: f32,
+    updated_us: u64,
+}
+
+impl Motion {
+    /// Move towards the target at the velocity limit until `now_us`
+    fn advance(&mut self, now_us: u64, active: bool) {
+        let dt = now_us.saturating_sub(self.updated_us) as f32 * 1e-6;
+        self.updated_us = self.updated_us.max(now_us);
+        let remaining = self.target - self.position;
+        let step = self.velocity_limit.abs() * dt;
+        if !active || remaining == 0.0 {
+            self.velocity = 0.0;
+        } else if remaining.abs() <= step {
+            self.position = self.target;
+            self.velocity = 0.0;
+        } else {
+            self.position += step.copysign(remaining);
+            self.velocity = self.velocity_limit.abs().copysign(remaining);
+        }
+    }
+}
+
+/// A joint plus what the simulator keeps around it
+struct SimJoint {
+    joint: Joint,
+    store: MemoryConfigStore,
+    aux_io: Option<Box<dyn AuxIoHandler + Send>>,
+    sensors: SensorModel,
+    motion: Motion,
+    rng: u64,
+    next_sample_us: u64,
+    sample_id: MessageId,
+}
+
+impl SimJoint {
+    fn new(mut joint: Joint, sensors: SensorModel) -> Self {
+        // The bus provides a config store and a bootloader to every joint
+        joint.set_capabilities(joint.capabilities() | Capabilities::CONFIG_STORE | Capabilities::FIRMWARE_UPDATE);
+        let mut sim = Self {
+            joint,
+            store: MemoryConfigStore::new(),
+            aux_io: None,
+            sensors,
+            motion: Motion::default(),
+            rng: 0,
+            next_sample_us: 0,
+            sample_id: 0,
+        };
+        sim.set_sensors(sensors);
+        sim
+    }
+
+    fn set_sensors(&mut self, sensors: SensorModel) {
+        self.sensors = sensors;
+        // xorshift state must not be zero
+        self.rng = (sensors.seed ^ ((self.joint.id() as u64) << 32)) | 1;
+    }
+
+    /// Move towards the joint's setpoint
+    fn follow_target(&mut self) {
+        if let Some(setpoint) = self.joint.target() {
+            self.motion.target = setpoint.target_angle + self.joint.encoder_offset().degrees();
+            self.motion.velocity_limit = setpoint.max_velocity;
+        }
+    }
+
+    /// Standard normal sample (xorshift64* and Box-Muller)
+    fn gaussian(&mut self) -> f32 {
+        let mut uniform = || {
+            self.rng ^= self.rng >> 12;
+            self.rng ^= self.rng << 25;
+            self.rng ^= self.rng >> 27;
+            let bits = self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11;
+            // In (0, 1], so the logarithm stays finite
+            (bits + 1) as f64 / (1u64 << 53) as f64
+        };
+        let (u1, u2) = (uniform(), uniform());
+        ((-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()) as f32
+    }
+
+    /// What the sensors report at `now_us`; the joint gets the temperature
+    /// too, so it derates and faults on a drifting one
+    fn sample(&mut self, now_us: u64) -> Message {
+        let sensors = self.sensors;
+        let mut position = self.motion.position - self.joint.encoder_offset().degrees()
+            + sensors.position_noise_deg * self.gaussian();
+        if sensors.resolution_deg > 0.0 {
+            position = (position / sensors.resolution_deg).round() * sensors.resolution_deg;
+        }
+        let velocity = self.motion.velocity + sensors.velocity_noise_dps * self.gaussian();
+        let temperature_c = sensors.ambient_c
+            + sensors.temperature_drift_c_per_s * now_us as f32 * 1e-6
+            + sensors.temperature_noise_c * self.gaussian();
+        self.joint.update_temperature(temperature_c);
+
+        self.sample_id = self.sample_id.wrapping_add(1);
+        Message {
+            header: Header {
+                source_id: self.joint.id(),
+                target_id: ARM_DEVICE_ID,
+                msg_id: self.sample_id,
+            },
+            payload: Payload::TelemetryStream(TelemetryStream {
+                timestamp_us: now_us,
+                position,
+                velocity,
+                acceleration: 0.0,
+                current_d: 0.0,
+                current_q: 0.0,
+                voltage_d: 0.0,
+                voltage_q: 0.0,
+                torque_estimate: 0.0,
+                power: 0.0,
+                load_percent: 0.0,
+                foc_loop_time_us: 0,
+                temperature_c,
+                warnings: self.joint.warnings(),
+                trajectory_active: self.motion.velocity != 0.0,
+                brake_engaged: self.joint.brake_engaged(),
+            }),
+        }
+    }
+}
+
+struct SimState {
+    joints: Vec<SimJoint>,
+    /// Replies from joints waiting to be received by the arm
+    inbound: VecDeque<Message>,
+    /// Telemetry in flight, with the time it reaches the arm
+    delayed: Vec<(u64, Message)>,
+    transmitted: u64,
+}
+
+/// Simulated bus connecting the arm side to in-process joints
+pub struct SimBus {
+    state: Mutex<SimState>,
+    clock: Arc<dyn Clock>,
+    mtu: usize,
+    sensors: SensorModel,
+    telemetry_period_us: Option<u64>,
+}
+
+impl SimBus {
+    /// Create a bus with no joints attached
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(SimState {
+                joints: Vec::new(),
+                inbound: VecDeque::new(),
+                delayed: Vec::new(),
+                transmitted: 0,
+            }),
+            clock: Arc::new(SystemClock::new()),
+            mtu: CANFD_MAX_DATA_LEN,
+            sensors: SensorModel::default(),
+            telemetry_period_us: None,
+        }
+    }
+
+    /// Set the bus MTU (one CAN-FD frame by default)
+    ///
+    /// Larger messages are refused in either direction, and joints report
+    /// the MTU in their bus statistics.
+    pub fn with_mtu(mut self, mtu: usize) -> Self {
+        self.mtu = mtu;
+        self
+    }
+
+    /// Set the time source the joints see
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Stream telemetry from every active joint once per `period`
+    ///
+    /// Off by default. Samples are `TelemetryStream` messages, delivered
+    /// whole as if the bus fragmented them; missed periods are skipped
+    /// rather than sent in a burst.
+    pub fn with_telemetry(mut self, period: Duration) -> Self {
+        self.telemetry_period_us = Some(period.as_micros().max(1) as u64);
+        self
+    }
+
+    /// Set the sensor model of every joint, present and future
+    pub fn with_sensor_model(mut self, sensors: SensorModel) -> Self {
+        self.sensors = sensors;
+        for joint in self.state.get_mut().unwrap().joints.iter_mut() {
+            joint.set_sensors(sensors);
+        }
+        self
+    }
+
+    /// Set the sensor model of one joint; returns whether it is attached
+    pub fn set_sensor_model(&self, id: DeviceId, sensors: SensorModel) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match state.joints.iter_mut().find(|j| j.joint.id() == id) {
+            Some(joint) => {
+                joint.set_sensors(sensors);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Actual position of a joint in degrees, without sensor effects
+    pub fn true_position(&self, id: DeviceId) -> Option<f32> {
+        let now_us = self.now_us();
+        let mut state = self.state.lock().unwrap();
+        let joint = state.joints.iter_mut().find(|j| j.joint.id() == id)?;
+        let active = joint.joint.state() == LifecycleState::Active;
+        joint.motion.advance(now_us, active);
+        Some(joint.motion.position)
+    }
+
+    /// Create a bus with a fresh joint for each ID
+    pub fn with_joints(ids: impl IntoIterator<Item = DeviceId>) -> Self {
+        let bus = Self::new();
+        for id in ids {
+            bus.add_joint(Joint::new(id));
+        }
+        bus
+    }
+
+    /// Attach a joint, replacing any joint with the same ID
+    ///
+    /// Unaddressed joints all share one ID and are never replaced.
+    pub fn add_joint(&self, joint: Joint) {
+        let mut state = self.state.lock().unwrap();
+        if !joint.is_unaddressed() {
+            state.joints.retain(|j| j.joint.id() != joint.id());
+        }
+        state.joints.push(SimJoint::new(joint, self.sensors));
+    }
+
+    /// Detach a joint, as if it was unplugged
+    pub fn remove_joint(&self, id: DeviceId) -> Option<Joint> {
+        let mut state = self.state.lock().unwrap();
+        let index = state.joints.iter().position(|j| j.joint.id() == id)?;
+        Some(state.joints.remove(index).joint)
+    }
+
+    /// IDs of the attached joints
+    pub fn joint_ids(&self) -> Vec<DeviceId> {
+        self.state.lock().unwrap().joints.iter().map(|j| j.joint.id()).collect()
+    }
+
+    /// Lifecycle state of an attached joint
+    pub fn joint_state(&self, id: DeviceId) -> Option<LifecycleState> {
+        self.with_joint(id, |joint| joint.state())
+    }
+
+    /// Inspect or modify an attached joint (e.g. to inject a fault)
+    pub fn with_joint<R>(&self, id: DeviceId, f: impl FnOnce(&mut Joint) -> R) -> Option<R> {
+        let mut state = self.state.lock().unwrap();
+        state.joints.iter_mut().find(|j| j.joint.id() == id).map(|j| f(&mut j.joint))
+    }
+
+    /// Wire auxiliary I/O to an attached joint, which then answers the
+    /// auxiliary I/O commands with it and advertises `AUX_IO`
+    ///
+    /// Returns false if no joint has that ID.
+    pub fn set_aux_io(&self, id: DeviceId, io: impl AuxIoHandler + Send + 'static) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(sim) = state.joints.iter_mut().find(|j| j.joint.id() == id) else {
+            return false;
+        };
+        sim.joint.set_capabilities(sim.joint.capabilities() | Capabilities::AUX_IO);
+        sim.aux_io = Some(Box::new(io));
+        true
+    }
+
+    /// Number of messages the arm side has transmitted
+    pub fn transmitted(&self) -> u64 {
+        self.state.lock().unwrap().transmitted
+    }
+
+    /// Bring the joints up to the bus clock, queueing what they sent meanwhile
+    fn advance(&self, state: &mut SimState) -> Result<(), ProtocolError> {
+        let now_us = self.now_us();
+        let SimState { joints, inbound, delayed, .. } = state;
+
+        for sim in joints.iter_mut() {
+            sim.joint.update_power(sim.sensors.supply_v, 0.0);
+            if sim.joint.self_test_running() {
+                sim.motion.advance(now_us, sim.joint.state() == LifecycleState::Active);
+                sim.joint.update_encoder(MultiTurnPosition::from_degrees(sim.motion.position));
+            }
+            // Release time-dependent replies (delayed discovery answers,
+            // self-test results)
+            if let Some(message) = sim.joint.poll(now_us) {
+                // The motion test heads back to where it started
+                if matches!(message.payload, Payload::SelfTestResult(_)) {
+                    sim.follow_target();
+                }
+                inbound.push_back(over_the_wire(&message, self.mtu)?);
+            }
+
+            let active = sim.joint.state() == LifecycleState::Active;
+            if let (Some(period), true) = (self.telemetry_period_us, active) {
+                if sim.next_sample_us <= now_us {
+                    // Latest due period only; earlier missed ones are skipped
+                    let at = now_us - (now_us - sim.next_sample_us) % period;
+                    sim.motion.advance(at, active);
+                    let sample = sim.sample(at);
+                    // A real bus fragments telemetry; deliver it whole here
+                    let sample = over_the_wire(&sample, Message::max_size())?;
+                    delayed.push((at + sim.sensors.latency.as_micros() as u64, sample));
+                    sim.next_sample_us = at + period;
+                }
+            } else {
+                sim.next_sample_us = now_us;
+            }
+            sim.motion.advance(now_us, active);
+        }
+
+        // Deliver telemetry whose latency has passed, oldest first
+        delayed.sort_by_key(|(due_us, _)| *due_us);
+        let arrived = delayed.partition_point(|(due_us, _)| *due_us <= now_us);
+        inbound.extend(delayed.drain(..arrived).map(|(_, message)| message));
+        Ok(())
+    }
+
+    /// Microseconds on the bus clock, as seen by the joints
+    fn now_us(&self) -> u64 {
+        self.clock.now().as_micros() as u64
+    }
+}
+
+impl Default for SimBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Encode and decode a message, as a real bus would
+fn over_the_wire(message: &Message, mtu: usize) -> Result<Message, ProtocolError> {
+    let mut bytes = [0u8; Message::max_size()];
+    let len = message.serialize_into(&mut bytes)?;
+    if len > mtu {
+        return Err(ProtocolError::PayloadTooLarge { size: len, limit: mtu });
+    }
+    Message::deserialize(&bytes[..len])
+}
+
+/// Answer a bus statistics request the way `Joint::process_transport` does
+fn bus_stats_reply(joint: &Joint, message: &Message, mtu: usize) -> Option<Message> {
+    if message.header.target_id != joint.id() || !matches!(message.payload, Payload::RequestBusStats) {
+        return None;
+    }
+    Some(Message {
+        header: Header {
+            source_id: joint.id(),
+            target_id: message.header.source_id,
+            msg_id: message.header.msg_id,
+        },
+        payload: Payload::BusStats(TransportStats::with_mtu(mtu)),
+    })
+}
+
+/// The joint as its bootloader comes up after an `EnterBootloader`
+fn restart_in_bootloader(joint: &Joint) -> Joint {
+    let mut info = joint.boot_info();
+    info.mode = BootMode::Bootloader;
+    info.boot_count = info.boot_count.wrapping_add(1);
+    let mut bootloader = Joint::new(joint.id());
+    bootloader.set_entity_type(joint.entity_type());
+    bootloader.set_serial_number(joint.serial_number());
+    bootloader.set_capabilities(joint.capabilities());
+    bootloader.set_boot_info(info);
+    bootloader
+}
+
+#[async_trait]
+impl CommunicationAdapter for SimBus {
+    type Error = ProtocolError;
+
+    async fn transmit(&self, message: &Message) -> Result<(), Self::Error> {
+        let message = over_the_wire(message, self.mtu)?;
+        let now_us = self.now_us();
+        let mut state = self.state.lock().unwrap();
+        let SimState { joints, inbound, transmitted, .. } = &mut *state;
+        *transmitted += 1;
+
+        let target = message.header.target_id;
+        // Every joint listed in a batch takes its target from it
+        let listed = |id| matches!(&message.payload, Payload::SetTargetBatch(batch) if batch.target_for(id).is_some());
+        for sim in joints
+            .iter_mut()
+            .filter(|j| target == BROADCAST_ADDRESS || j.joint.id() == target || listed(j.joint.id()))
+        {
+            let active = sim.joint.state() == LifecycleState::Active;
+            sim.motion.advance(now_us, active);
+            sim.joint.update_encoder(MultiTurnPosition::from_degrees(sim.motion.position));
+            let reply = bus_stats_reply(&sim.joint, &message, self.mtu)
+                .or_else(|| sim.aux_io.as_mut().and_then(|io| sim.joint.handle_aux_io(&message, io.as_mut())))
+                .or_else(|| sim.joint.handle_with_store(&message, &mut sim.store));
+            // The joint moves towards setpoints it accepted (streamed ones,
+            // and batched ones sent to another joint, are not acknowledged
+            // one by one) or took at a sync, and on its own for a self-test
+            if matches!(
+                (&message.payload, reply.as_ref().map(|r| &r.payload)),
+                (
+                    Payload::SetTarget(_) | Payload::SetTargetV2(_) | Payload::SetTargetBatch(_),
+                    Some(Payload::Ack(_) | Payload::StreamAck { .. }) | None
+                ) | (Payload::RunSelfTest(_), None)
+                    | (Payload::Sync { .. }, Some(Payload::SyncSample(_)))
+            ) {
+                sim.follow_target();
+            }
+            // Homing finds the reference straight away
+            if let Some(homing) = sim.joint.homing() {
+                sim.motion.position = homing.offset + sim.joint.encoder_offset().degrees();
+                sim.motion.target = sim.motion.position;
+                sim.joint.complete_homing();
+            }
+            if let Some(reply) = reply {
+                inbound.push_back(over_the_wire(&reply, self.mtu)?);
+            }
+            if sim.joint.bootloader_requested() {
+                sim.joint = restart_in_bootloader(&sim.joint);
+            }
+        }
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<Option<Message>, Self::Error> {
+        let mut state = self.state.lock().unwrap();
+        self.advance(&mut state)?;
+        Ok(state.inbound.pop_front())
+    }
+
+    async fn receive_batch(&self, batch: &mut Vec<Message>) -> Result<(), Self::Error> {
+        let mut state = self.state.lock().unwrap();
+        self.advance(&mut state)?;
+        let take = state.inbound.len().min(RECEIVE_BATCH_LEN.saturating_sub(batch.len()));
+        batch.extend(state.inbound.drain(..take));
+        Ok(())
+    }
+
+    async fn discover_devices(&self) -> Result<Vec<DeviceInfo>, Self::Error> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .joints
+            .iter()
+            .map(|sim| DeviceInfo {
+                id: sim.joint.id(),
+                entity_type: sim.joint.entity_type(),
+                mode: sim.joint.boot_info().mode,
+                capabilities: sim.joint.capabilities(),
+                schema: SCHEMA_HASH,
+            })
+            .collect())
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    fn mtu(&self) -> usize {
+        self.mtu
+    }
+}
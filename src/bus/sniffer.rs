@@ -0,0 +1,167 @@
+//! Passive bus monitoring
+//!
+//! A [`Sniffer`] attaches to any [`CommunicationAdapter`] that sees the whole
+//! bus (a listen-only CAN interface, a gateway uplink, a recording) and turns
+//! the traffic into timestamped [`LogRecord`]s, keeping only those that pass
+//! a [`SnifferFilter`]. It never transmits. Records print as one line each,
+//! which is all a dump tool needs:
+//!
+//! ```no_run
+//! # async fn example() {
+//! use irpc::bus::net::NetworkAdapter;
+//! use irpc::bus::sniffer::{Sniffer, SnifferFilter};
+//! use std::sync::Arc;
+//!
+//! let bus = Arc::new(NetworkAdapter::tcp_connect("10.0.0.2:47101".parse().unwrap()));
+//! let sniffer = Sniffer::new(bus).with_filter(SnifferFilter::new().device(0x0010).kind("TelemetryStream"));
+//! while let Ok(record) = sniffer.next().await {
+//!     println!("{}", record);
+//! }
+//! # }
+//! ```
+//!
+//! See `examples/irpc_dump.rs` for a command-line front end.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::record::{Direction, LogRecord};
+use super::CommunicationAdapter;
+use crate::clock::{Clock, SystemClock};
+use crate::config::{ADAPTER_POLL_INTERVAL_MS, ARM_DEVICE_ID};
+use crate::protocol::{DeviceId, Message};
+
+/// Which messages a sniffer reports
+///
+/// Empty criteria match everything; a message must pass every criterion
+/// that is set.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SnifferFilter {
+    devices: Vec<DeviceId>,
+    kinds: Vec<String>,
+    direction: Option<Direction>,
+}
+
+impl SnifferFilter {
+    /// Filter that matches everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also match messages from or to `device`
+    pub fn device(mut self, device: DeviceId) -> Self {
+        self.devices.push(device);
+        self
+    }
+
+    /// Also match payloads named `kind` (see [`Payload::name`](crate::Payload::name))
+    pub fn kind(mut self, kind: impl Into<String>) -> Self {
+        self.kinds.push(kind.into());
+        self
+    }
+
+    /// Only match messages travelling in `direction`
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// Whether a message seen travelling in `direction` passes the filter
+    pub fn matches(&self, direction: Direction, message: &Message) -> bool {
+        let header = &message.header;
+        (self.devices.is_empty()
+            || self.devices.contains(&header.source_id)
+            || self.devices.contains(&header.target_id))
+            && (self.kinds.is_empty() || self.kinds.iter().any(|kind| kind == message.payload.name()))
+            && self.direction.is_none_or(|d| d == direction)
+    }
+}
+
+/// Read-only tap on an adapter
+///
+/// Everything the adapter receives is reported; messages sent from the host
+/// ID (`ARM_DEVICE_ID` unless changed with [`with_host`](Self::with_host)) are
+/// marked [`Direction::Transmitted`], all others [`Direction::Received`].
+/// Timestamps count from the sniffer's creation on its clock.
+pub struct Sniffer<A> {
+    inner: Arc<A>,
+    filter: SnifferFilter,
+    host_id: DeviceId,
+    clock: Arc<dyn Clock>,
+    seen: AtomicU64,
+}
+
+impl<A: CommunicationAdapter> Sniffer<A> {
+    /// Monitor `inner`, reporting everything
+    pub fn new(inner: Arc<A>) -> Self {
+        Self {
+            inner,
+            filter: SnifferFilter::default(),
+            host_id: ARM_DEVICE_ID,
+            clock: Arc::new(SystemClock::new()),
+            seen: AtomicU64::new(0),
+        }
+    }
+
+    /// Report only messages passing `filter`
+    pub fn with_filter(mut self, filter: SnifferFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Treat messages from `host_id` as transmitted by the host
+    pub fn with_host(mut self, host_id: DeviceId) -> Self {
+        self.host_id = host_id;
+        self
+    }
+
+    /// Timestamp records and pace polling with `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The monitored adapter
+    pub fn inner(&self) -> &Arc<A> {
+        &self.inner
+    }
+
+    /// Messages received so far, including those the filter dropped
+    pub fn seen(&self) -> u64 {
+        self.seen.load(Ordering::Relaxed)
+    }
+
+    /// Next matching message already queued on the adapter, if any
+    pub async fn try_next(&self) -> Result<Option<LogRecord>, A::Error> {
+        while let Some(message) = self.inner.receive().await? {
+            self.seen.fetch_add(1, Ordering::Relaxed);
+            let direction = if message.header.source_id == self.host_id {
+                Direction::Transmitted
+            } else {
+                Direction::Received
+            };
+            if self.filter.matches(direction, &message) {
+                return Ok(Some(LogRecord {
+                    timestamp_us: self.clock.now().as_micros() as u64,
+                    direction,
+                    message,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Wait for the next matching message
+    ///
+    /// The adapter is polled every `ADAPTER_POLL_INTERVAL_MS`.
+    pub async fn next(&self) -> Result<LogRecord, A::Error> {
+        let poll_interval = Duration::from_millis(ADAPTER_POLL_INTERVAL_MS);
+        loop {
+            if let Some(record) = self.try_next().await? {
+                return Ok(record);
+            }
+            self.clock.sleep(poll_interval).await;
+        }
+    }
+}
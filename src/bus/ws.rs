@@ -0,0 +1,221 @@
+//! iRPC over WebSocket for browser tools
+//!
+//! [`WebSocketAdapter`] lets a diagnostics UI compiled to `wasm32` talk to
+//! the arm directly from the browser. It speaks the TCP framing of the
+//! network transport (see [`framing`](crate::framing)), so a plain
+//! WebSocket-to-TCP bridge such as `websockify` in front
+//! of a `NetworkAdapter::tcp_listen` gateway is all the server side needs.
+//! Frames may be split across or share WebSocket messages; [`FrameDecoder`]
+//! reassembles them.
+//!
+//! Build the JavaScript package with
+//!
+//! ```text
+//! cargo rustc --release --lib --crate-type cdylib --target wasm32-unknown-unknown --features wasm
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/irpc.wasm
+//! ```
+//!
+//! From JavaScript, messages are plain objects mirroring [`Message`]
+//! (`{header: {source_id, target_id, msg_id}, payload: {TelemetryStream:
+//! {...}}}`):
+//!
+//! ```text
+//! import init, { WebSocketAdapter } from "./irpc.js";
+//! await init();
+//! const bus = new WebSocketAdapter("ws://gateway.local:9001");
+//! setInterval(() => {
+//!   for (let m = bus.receive(); m !== undefined; m = bus.receive()) {
+//!     if (m.payload.TelemetryStream) plot(m.header.source_id, m.payload.TelemetryStream);
+//!   }
+//! }, 20);
+//! ```
+
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use web_sys::{BinaryType, MessageEvent, WebSocket};
+
+use crate::framing::{self, FramingError, MAX_MESSAGE_FRAME_LEN};
+use crate::protocol::{Message, ProtocolError};
+
+/// Encode `message` as one frame, as on the TCP link
+pub fn encode_frame(message: &Message) -> Result<Vec<u8>, ProtocolError> {
+    let mut frame = alloc::vec![0u8; MAX_MESSAGE_FRAME_LEN];
+    let len = framing::encode_message(message, &mut frame).map_err(protocol_error)?;
+    frame.truncate(len);
+    Ok(frame)
+}
+
+fn protocol_error(error: FramingError) -> ProtocolError {
+    match error {
+        FramingError::Protocol(e) => e,
+        _ => ProtocolError::InvalidMessage,
+    }
+}
+
+/// Reassembles frames from a byte stream
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    frames: framing::FrameDecoder<MAX_MESSAGE_FRAME_LEN>,
+    decoded: VecDeque<Result<Message, ProtocolError>>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append received bytes
+    pub fn push(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if let Some(result) = self.frames.push_message(byte) {
+                self.decoded.push_back(result.map_err(protocol_error));
+            }
+        }
+    }
+
+    /// Next complete frame, decoded
+    ///
+    /// `None` until a whole frame has arrived. A frame that does not decode
+    /// is returned as an error, so decoding can carry on with the next one;
+    /// a frame too long for any message is reported as
+    /// `ProtocolError::InvalidMessage` and skipped up to its delimiter.
+    pub fn next_message(&mut self) -> Option<Result<Message, ProtocolError>> {
+        self.decoded.pop_front()
+    }
+
+    /// Bytes waiting for the rest of their frame
+    pub fn pending(&self) -> usize {
+        self.frames.pending()
+    }
+}
+
+/// Messages received but not yet polled, plus the decode error count
+#[derive(Default)]
+struct Inbound {
+    decoder: FrameDecoder,
+    messages: VecDeque<Message>,
+    decode_errors: u32,
+}
+
+/// Browser WebSocket carrying iRPC frames
+///
+/// Received messages are queued until polled with
+/// [`receive`](Self::receive) (JavaScript) or
+/// [`receive_message`](Self::receive_message) (Rust).
+#[wasm_bindgen]
+pub struct WebSocketAdapter {
+    socket: WebSocket,
+    inbound: Rc<RefCell<Inbound>>,
+    // Kept alive for as long as the socket may call it
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+}
+
+fn js_error(error: impl core::fmt::Display) -> JsValue {
+    JsValue::from_str(&alloc::format!("{}", error))
+}
+
+/// `message` as a plain JavaScript object (maps as objects, integers as numbers)
+fn to_js(message: &Message) -> Result<JsValue, JsValue> {
+    Serialize::serialize(message, &serde_wasm_bindgen::Serializer::json_compatible()).map_err(js_error)
+}
+
+#[wasm_bindgen]
+impl WebSocketAdapter {
+    /// Open a WebSocket to `url` (`ws://` or `wss://`)
+    ///
+    /// Returns immediately; the socket is usable once
+    /// [`is_connected`](Self::is_connected) turns true.
+    #[wasm_bindgen(constructor)]
+    pub fn connect(url: &str) -> Result<WebSocketAdapter, JsValue> {
+        let socket = WebSocket::new(url)?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let inbound = Rc::new(RefCell::new(Inbound::default()));
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new({
+            let inbound = inbound.clone();
+            move |event: MessageEvent| {
+                // Text messages are not part of the protocol
+                let Ok(data) = event.data().dyn_into::<js_sys::ArrayBuffer>() else {
+                    return;
+                };
+                let mut inbound = inbound.borrow_mut();
+                let Inbound { decoder, messages, decode_errors } = &mut *inbound;
+                decoder.push(&js_sys::Uint8Array::new(&data).to_vec());
+                while let Some(result) = decoder.next_message() {
+                    match result {
+                        Ok(message) => messages.push_back(message),
+                        Err(_) => *decode_errors += 1,
+                    }
+                }
+            }
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        Ok(Self { socket, inbound, _on_message: on_message })
+    }
+
+    /// Whether the socket is open
+    #[wasm_bindgen(js_name = isConnected)]
+    pub fn is_connected(&self) -> bool {
+        self.socket.ready_state() == WebSocket::OPEN
+    }
+
+    /// Next received message as a JavaScript object, or `undefined`
+    pub fn receive(&self) -> Result<JsValue, JsValue> {
+        match self.receive_message() {
+            Some(message) => to_js(&message),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    /// Send a message given as a JavaScript object
+    pub fn transmit(&self, message: JsValue) -> Result<(), JsValue> {
+        let message: Message = serde_wasm_bindgen::from_value(message)?;
+        self.transmit_message(&message)
+    }
+
+    /// Frames received that did not decode
+    #[wasm_bindgen(js_name = decodeErrors)]
+    pub fn decode_errors(&self) -> u32 {
+        self.inbound.borrow().decode_errors
+    }
+
+    /// Close the socket
+    pub fn close(&self) -> Result<(), JsValue> {
+        self.socket.close()
+    }
+}
+
+impl WebSocketAdapter {
+    /// Next received message
+    pub fn receive_message(&self) -> Option<Message> {
+        self.inbound.borrow_mut().messages.pop_front()
+    }
+
+    /// Send `message` as one frame
+    pub fn transmit_message(&self, message: &Message) -> Result<(), JsValue> {
+        let frame = encode_frame(message).map_err(js_error)?;
+        self.socket.send_with_u8_array(&frame)
+    }
+}
+
+impl Drop for WebSocketAdapter {
+    fn drop(&mut self) {
+        self.socket.set_onmessage(None);
+        let _ = self.socket.close();
+    }
+}
+
+/// Decode one encoded message (without length prefix) into a JavaScript object
+///
+/// For tools that receive frames by other means, e.g. from a recording.
+#[wasm_bindgen(js_name = decodeMessage)]
+pub fn decode_message(bytes: &[u8]) -> Result<JsValue, JsValue> {
+    let message = Message::deserialize(bytes).map_err(js_error)?;
+    to_js(&message)
+}
@@ -0,0 +1,186 @@
+//! Injectable time source for host code
+//!
+//! Everything time-dependent on the host side (request timeouts, discovery
+//! windows, homing polls, trajectory streaming, reconciliation, the
+//! simulated bus) reads time and sleeps through a [`Clock`].
+//!
+//! - [`SystemClock`] (the default) uses `tokio::time`, so it also follows
+//!   tokio's paused test clock.
+//! - [`VirtualClock`] only moves when told to, so tests can fast-forward
+//!   through long timeouts and trajectories and get the same interleaving
+//!   on every run.
+//!
+//! # Example
+//!
+//! ```
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! use irpc::clock::{Clock, VirtualClock};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! let clock = Arc::new(VirtualClock::new());
+//! let sleeper = tokio::spawn({
+//!     let clock = clock.clone();
+//!     async move { clock.sleep(Duration::from_secs(3600)).await }
+//! });
+//!
+//! tokio::task::yield_now().await;
+//! clock.advance(Duration::from_secs(3600));
+//! sleeper.await.unwrap();
+//! assert_eq!(clock.now(), Duration::from_secs(3600));
+//! # }
+//! ```
+
+use std::future::Future;
+use std::sync::Mutex;
+use std::task::{Poll, Waker};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+/// Source of monotonic time and sleeps
+#[async_trait]
+pub trait Clock: Send + Sync {
+    /// Time elapsed since the clock's epoch
+    fn now(&self) -> Duration;
+
+    /// Wait until `duration` has passed on this clock
+    async fn sleep(&self, duration: Duration);
+
+    /// Wait until the clock reaches `deadline` (measured like [`now`](Self::now))
+    async fn sleep_until(&self, deadline: Duration) {
+        let now = self.now();
+        if deadline > now {
+            self.sleep(deadline - now).await;
+        }
+    }
+}
+
+/// The requested time passed before the future completed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+/// Run `future` for at most `duration` of `clock` time
+pub async fn timeout<F: Future>(clock: &dyn Clock, duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+    tokio::select! {
+        output = future => Ok(output),
+        _ = clock.sleep(duration) => Err(Elapsed),
+    }
+}
+
+/// Wall-clock time from `tokio::time`
+///
+/// The epoch is the moment the clock was created.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    epoch: tokio::time::Instant,
+}
+
+impl SystemClock {
+    /// Create a clock starting at zero now
+    pub fn new() -> Self {
+        Self {
+            epoch: tokio::time::Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.epoch.elapsed()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+#[derive(Default)]
+struct VirtualState {
+    now: Duration,
+    sleepers: Vec<(Duration, Waker)>,
+}
+
+/// Manually driven clock for simulation and tests
+///
+/// Starts at zero and only moves on [`advance`](Self::advance) or
+/// [`advance_to_next`](Self::advance_to_next); sleepers whose deadline is
+/// reached are woken then. Tasks must get a chance to run (e.g.
+/// `tokio::task::yield_now()`) between steps so they can register their
+/// next sleep.
+#[derive(Default)]
+pub struct VirtualClock {
+    state: Mutex<VirtualState>,
+}
+
+impl VirtualClock {
+    /// Create a clock at time zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move time forward, waking every sleeper that is now due
+    pub fn advance(&self, duration: Duration) {
+        let now = self.state.lock().unwrap().now + duration;
+        self.set(now);
+    }
+
+    /// Jump to the earliest pending deadline, if any sleeper is waiting
+    ///
+    /// Returns the new time, or `None` (time unchanged) if nothing sleeps.
+    pub fn advance_to_next(&self) -> Option<Duration> {
+        let next = self.state.lock().unwrap().sleepers.iter().map(|(deadline, _)| *deadline).min()?;
+        self.set(next);
+        Some(next)
+    }
+
+    /// Number of sleeps waiting for time to pass
+    pub fn pending_sleepers(&self) -> usize {
+        self.state.lock().unwrap().sleepers.len()
+    }
+
+    fn set(&self, now: Duration) {
+        let due: Vec<Waker> = {
+            let mut state = self.state.lock().unwrap();
+            state.now = state.now.max(now);
+            let now = state.now;
+            let (due, waiting) = state.sleepers.drain(..).partition(|(deadline, _)| *deadline <= now);
+            state.sleepers = waiting;
+            due.into_iter().map(|(_, waker)| waker).collect::<Vec<_>>()
+        };
+        // Wake outside the lock; woken tasks may sleep again right away
+        due.into_iter().for_each(Waker::wake);
+    }
+}
+
+#[async_trait]
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        self.state.lock().unwrap().now
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        let deadline = self.now() + duration;
+        std::future::poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap();
+            if state.now >= deadline {
+                return Poll::Ready(());
+            }
+            // Keep one entry per sleeping task across repeated polls
+            match state.sleepers.iter_mut().find(|(d, w)| *d == deadline && w.will_wake(cx.waker())) {
+                Some((_, waker)) => waker.clone_from(cx.waker()),
+                None => state.sleepers.push((deadline, cx.waker().clone())),
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
@@ -0,0 +1,128 @@
+//! Translation between payload generations
+//!
+//! Several v1 payloads have v2 successors carrying a superset of their
+//! data:
+//!
+//! | v1 | v2 |
+//! |----|----|
+//! | `SetTarget` | `SetTargetV2` |
+//! | `Encoder` | `TelemetryStream` |
+//!
+//! During a fleet migration old and new firmware and hosts share a bus. The
+//! functions here map one generation onto the other so either side can
+//! talk to the other: [`JointProxy`](crate::JointProxy) falls back to the
+//! generation a joint accepts, joints translate incoming v1 commands into
+//! their v2 representation, and the host publishes v1 telemetry as v2.
+//!
+//! Upgrading fills fields the old payload lacks with conservative
+//! defaults; downgrading drops them.
+
+use crate::config::V1_TARGET_ACCELERATION_DPS2;
+use crate::protocol::{
+    EncoderTelemetry, MotionProfile, Payload, SetTargetPayload, SetTargetPayloadV2, TelemetryStream, Warnings,
+};
+
+/// Generation of a payload that has a counterpart in the other generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PayloadGeneration {
+    /// `SetTarget`, `Encoder`
+    V1,
+    /// `SetTargetV2`, `TelemetryStream`
+    V2,
+}
+
+impl PayloadGeneration {
+    /// Generation of `payload`, or `None` if it exists in both
+    pub fn of(payload: &Payload) -> Option<Self> {
+        match payload {
+            Payload::SetTarget(_) | Payload::Encoder(_) => Some(Self::V1),
+            Payload::SetTargetV2(_) | Payload::TelemetryStream(_) => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    /// The other generation
+    pub fn other(self) -> Self {
+        match self {
+            Self::V1 => Self::V2,
+            Self::V2 => Self::V1,
+        }
+    }
+}
+
+impl From<SetTargetPayload> for SetTargetPayloadV2 {
+    /// Trapezoidal move at the v1 velocity limit, accelerating with
+    /// `V1_TARGET_ACCELERATION_DPS2`, coming to rest at the target, with
+    /// jerk, current and temperature limits disabled
+    fn from(target: SetTargetPayload) -> Self {
+        Self {
+            target_angle: target.target_angle,
+            max_velocity: target.velocity_limit,
+            target_velocity: 0.0,
+            max_acceleration: V1_TARGET_ACCELERATION_DPS2,
+            max_deceleration: V1_TARGET_ACCELERATION_DPS2,
+            max_jerk: 0.0,
+            profile: MotionProfile::Trapezoidal,
+            max_current: 0.0,
+            max_temperature: 0.0,
+        }
+    }
+}
+
+impl From<SetTargetPayloadV2> for SetTargetPayload {
+    /// Keeps the target and velocity limit; the profile is up to the joint
+    fn from(target: SetTargetPayloadV2) -> Self {
+        Self {
+            target_angle: target.target_angle,
+            velocity_limit: target.max_velocity,
+        }
+    }
+}
+
+impl From<TelemetryStream> for EncoderTelemetry {
+    fn from(stream: TelemetryStream) -> Self {
+        Self {
+            position: stream.position,
+            velocity: stream.velocity,
+        }
+    }
+}
+
+impl From<EncoderTelemetry> for TelemetryStream {
+    /// Position and velocity only; everything else reads zero, including
+    /// the timestamp, which v1 telemetry does not carry
+    fn from(encoder: EncoderTelemetry) -> Self {
+        Self {
+            timestamp_us: 0,
+            position: encoder.position,
+            velocity: encoder.velocity,
+            acceleration: 0.0,
+            current_d: 0.0,
+            current_q: 0.0,
+            voltage_d: 0.0,
+            voltage_q: 0.0,
+            torque_estimate: 0.0,
+            power: 0.0,
+            load_percent: 0.0,
+            foc_loop_time_us: 0,
+            temperature_c: 0.0,
+            warnings: Warnings::NONE,
+            trajectory_active: false,
+            brake_engaged: false,
+        }
+    }
+}
+
+/// Express `payload` in generation `to`
+///
+/// Payloads that exist in both generations, or are already in `to`, are
+/// returned unchanged.
+pub fn translate(payload: Payload, to: PayloadGeneration) -> Payload {
+    match (payload, to) {
+        (Payload::SetTarget(target), PayloadGeneration::V2) => Payload::SetTargetV2(target.into()),
+        (Payload::SetTargetV2(target), PayloadGeneration::V1) => Payload::SetTarget(target.into()),
+        (Payload::Encoder(encoder), PayloadGeneration::V2) => Payload::TelemetryStream(encoder.into()),
+        (Payload::TelemetryStream(stream), PayloadGeneration::V1) => Payload::Encoder(stream.into()),
+        (payload, _) => payload,
+    }
+}
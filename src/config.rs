@@ -4,10 +4,198 @@
 pub const BROADCAST_ADDRESS: u16 = 0x0000;
 pub const ARM_DEVICE_ID: u16 = 0x0001;
 pub const JOINT_ID_OFFSET: u16 = 0x0010;
+// Node ID of a joint waiting to be commissioned with `AssignId` (the highest
+// ID every transport can address)
+pub const UNADDRESSED_DEVICE_ID: u16 = 0x007F;
 
 // --- Communication Parameters ---
 pub const REQUEST_TIMEOUT_MS: u64 = 100;
 pub const MAX_RETRIES: u32 = 3;
+// How often the host drains a `CommunicationAdapter` for incoming messages
+pub const ADAPTER_POLL_INTERVAL_MS: u64 = 1;
+// Messages the host takes from an adapter per `receive_batch` call (a
+// millisecond of 1 kHz telemetry from a few dozen joints)
+pub const RECEIVE_BATCH_LEN: usize = 64;
+// Default capacity of the host's outbound message queue; sends fail with
+// `ProtocolError::QueueFull` while it is full
+pub const OUTBOUND_QUEUE_DEPTH: usize = 256;
+// How often the host drops pending requests whose caller stopped waiting
+pub const PENDING_SWEEP_INTERVAL_MS: u64 = 1_000;
+// Longest `ArmOrchestrator::shutdown` waits for requests in flight to be answered
+pub const SHUTDOWN_DRAIN_TIMEOUT_MS: u64 = 500;
+// Maximum data length of a single CAN-FD frame
+pub const CANFD_MAX_DATA_LEN: usize = 64;
+// Maximum data length of a classic CAN 2.0 frame
+pub const CLASSIC_CAN_MAX_DATA_LEN: usize = 8;
+// Concurrent fragmented transfers a classic CAN node can reassemble
+pub const FRAGMENT_REASSEMBLY_SLOTS: usize = 4;
+// Default depth of the interrupt-side CAN-FD receive queue, in frames
+// (covers a 1 kHz telemetry burst from several joints between main-loop polls)
+pub const CANFD_RX_QUEUE_DEPTH: usize = 32;
+// Default depth, in frames, of the in-memory loopback and channel-pair transports
+pub const LOOPBACK_QUEUE_DEPTH: usize = 16;
+// Default Cyphal/CAN port IDs of iRPC transfers (unregulated ranges): broadcasts
+// use the subject, addressed messages the service
+pub const CYPHAL_SUBJECT_ID: u16 = 4_000;
+pub const CYPHAL_SERVICE_ID: u16 = 200;
+
+// --- Command Streaming ---
+// Commands whose message ID has this bit set are streamed: the joint answers
+// them with a `StreamAck` now and then instead of an `Ack` each. The other
+// bits number the commands streamed to the joint, so it can count gaps
+pub const STREAM_MSG_ID_FLAG: u32 = 1 << 31;
+// A joint sends its `StreamAck` every this many streamed commands, and once
+// none came for the idle time
+pub const STREAM_ACK_INTERVAL: u32 = 50;
+pub const STREAM_ACK_IDLE_US: u64 = 20_000;
+
+// --- Target Batches ---
+// Joints one `SetTargetBatch` can carry; sized to fit one CAN-FD frame
+pub const TARGET_BATCH_MAX_JOINTS: usize = 4;
+
+// --- Network Adapter ---
+// TCP reconnect backoff, doubling from the minimum up to the maximum
+pub const NET_RECONNECT_MIN_MS: u64 = 100;
+pub const NET_RECONNECT_MAX_MS: u64 = 5_000;
+// UDP port for endpoint discovery beacons, and how often they are sent
+pub const NET_BEACON_PORT: u16 = 47_100;
+pub const NET_BEACON_INTERVAL_MS: u64 = 1_000;
 
 // --- Entity Type Identifiers ---
-pub const ENTITY_TYPE_JOINT_CLN17: u16 = 0x1001;
\ No newline at end of file
+pub const ENTITY_TYPE_JOINT_CLN17: u16 = 0x1001;
+
+// --- Discovery ---
+// Joints answer a broadcast Discover after an ID-derived slot plus random jitter,
+// so a fully populated bus does not answer in one burst.
+pub const DISCOVERY_SLOTS: u32 = 16;
+pub const DISCOVERY_SLOT_US: u32 = 2_000;
+pub const DISCOVERY_JITTER_US: u32 = 1_500;
+// Host collection window; must exceed the worst-case joint backoff
+// (DISCOVERY_SLOTS * DISCOVERY_SLOT_US + DISCOVERY_JITTER_US)
+pub const DISCOVERY_WINDOW_MS: u64 = 100;
+
+// --- State Reconciliation ---
+// Default period of the orchestrator's background state sweep
+pub const RECONCILE_INTERVAL_MS: u64 = 1_000;
+
+// --- Joint Error Codes ---
+// Reported in `Nack::error` and `JointStatus::error_code`
+// Command not accepted in the joint's current lifecycle state or mode
+pub const ERROR_INVALID_STATE: u16 = 4;
+// Encoder lost validity; motion is refused until the joint is re-homed
+pub const ERROR_POSITION_UNKNOWN: u16 = 5;
+// Target outside the applied `JointLimits` (or not a finite number)
+pub const ERROR_LIMIT_VIOLATION: u16 = 6;
+// Command needs the application, but the node is running its bootloader
+pub const ERROR_IN_BOOTLOADER: u16 = 7;
+// The configuration store failed or holds no valid configuration
+pub const ERROR_CONFIG_STORE: u16 = 8;
+// Auxiliary I/O channel the board does not have
+pub const ERROR_NO_SUCH_CHANNEL: u16 = 9;
+// Auxiliary I/O channel could not be driven or read
+pub const ERROR_AUX_IO: u16 = 10;
+// Motion refused while the holding brake is engaged
+pub const ERROR_BRAKE_ENGAGED: u16 = 11;
+// The motor driver refused to enable or reported a fault
+pub const ERROR_MOTOR_FAULT: u16 = 12;
+// Controller gains negative or not finite
+pub const ERROR_INVALID_GAINS: u16 = 13;
+// The joint requires `SafetyEnable` and holds no valid one
+pub const ERROR_SAFETY_ENABLE: u16 = 14;
+// The winding is at or above the critical temperature of its `ThermalLimits`
+pub const ERROR_OVERTEMPERATURE: u16 = 15;
+// The supply is below the brown-out voltage of its `PowerLimits`
+pub const ERROR_UNDERVOLTAGE: u16 = 16;
+// Payload the joint does not handle (e.g. a v1 command on v2-only firmware)
+pub const ERROR_UNKNOWN_COMMAND: u16 = 255;
+
+// --- Position Recovery ---
+// How often the host polls joint status while waiting for homing to finish
+pub const HOMING_POLL_INTERVAL_MS: u64 = 50;
+// Default time allowed for a joint to finish homing
+pub const HOMING_TIMEOUT_MS: u64 = 30_000;
+
+// --- Telemetry ---
+// Samples queued per host telemetry subscriber before new ones are dropped
+// (a quarter second of 1 kHz telemetry)
+pub const TELEMETRY_SUBSCRIBER_QUEUE_DEPTH: usize = 256;
+
+// --- Trajectory Streaming ---
+// Default rate at which loaded trajectories are resampled and streamed
+pub const TRAJECTORY_STREAM_RATE_HZ: u32 = 100;
+
+// --- Jogging ---
+// Rate at which a Cartesian jog sends joint targets
+pub const JOG_UPDATE_RATE_HZ: u32 = 50;
+// Joint speed a jog stays under when the joint has no limits applied
+pub const JOG_MAX_JOINT_VELOCITY_DPS: f32 = 30.0;
+
+// --- Safety Supervisor ---
+// Interventions kept in the host's safety audit log
+pub const SAFETY_AUDIT_LEN: usize = 256;
+
+// --- Motion Sequences ---
+// A sequence's move is done once every joint reads this close to its target
+pub const SEQUENCE_POSITION_TOLERANCE_DEG: f32 = 0.5;
+pub const SEQUENCE_POLL_INTERVAL_MS: u64 = 20;
+pub const SEQUENCE_MOVE_TIMEOUT_MS: u64 = 30_000;
+
+// --- Bus Scheduling ---
+// Share of each bus cycle a planned schedule may fill; the rest is headroom
+// for commands, retransmissions and error frames
+pub const SCHEDULE_MAX_UTILIZATION: f32 = 0.7;
+
+// --- Payload Compatibility ---
+// Acceleration and deceleration assumed when a v1 `SetTarget` is translated
+// to `SetTargetV2`
+pub const V1_TARGET_ACCELERATION_DPS2: f32 = 1_000.0;
+
+// --- Crash Reporting ---
+// Text kept in a `CrashRecord`; sized so a `Boot` announcement with a crash
+// record still fits one CAN-FD frame
+pub const CRASH_TASK_NAME_LEN: usize = 8;
+pub const CRASH_MESSAGE_LEN: usize = 24;
+
+// --- Flight Recorder ---
+// Entries a joint's flight recorder keeps (16 bytes each)
+pub const FLIGHT_RECORDER_LEN: usize = 48;
+// Entries per `FlightRecorder` reply; sized to fit one CAN-FD frame
+pub const FLIGHT_RECORDS_PER_CHUNK: usize = 3;
+
+// --- Safety Enable ---
+// A joint that stops on its own (its `SafetyEnable` ran out, a collision)
+// ramps the velocity down at this rate; after a lapsed enable it deactivates
+// once slower than the stop velocity, or after the stop time at the latest
+pub const SAFETY_STOP_DECELERATION_DPS2: f32 = 360.0;
+pub const SAFETY_STOP_VELOCITY_DPS: f32 = 1.0;
+pub const SAFETY_STOP_TIME_US: u64 = 500_000;
+// The host's heartbeat refreshes the enable this many times per TTL
+pub const SAFETY_HEARTBEATS_PER_TTL: u32 = 4;
+
+// --- Collision Reaction ---
+// Speed a joint backs off at after a collision
+pub const COLLISION_BACKOFF_VELOCITY_DPS: f32 = 10.0;
+
+// --- Self-Test ---
+// The motion test moves at most this far, at this speed, and fails unless
+// the joint covered half the distance within the test time
+pub const SELF_TEST_MAX_MOTION_DEG: f32 = 5.0;
+pub const SELF_TEST_VELOCITY_DPS: f32 = 10.0;
+pub const SELF_TEST_TIME_US: u64 = 1_000_000;
+
+// --- Joint Runner ---
+// Default periods of the embassy joint runner's hooks (`JointHooks`)
+pub const JOINT_UPDATE_PERIOD_US: u64 = 1_000;
+pub const JOINT_TELEMETRY_PERIOD_US: u64 = 10_000;
+// Well inside typical independent watchdog timeouts (100 ms and up)
+pub const JOINT_WATCHDOG_PERIOD_US: u64 = 20_000;
+
+// --- Control Loop ---
+// Gain sets a joint's gain schedule holds besides its base gains
+pub const GAIN_SCHEDULE_SLOTS: usize = 4;
+
+// --- Simulation ---
+// Temperature simulated joints report before any drift
+pub const SIM_AMBIENT_TEMPERATURE_C: f32 = 25.0;
+// Supply voltage of simulated joints
+pub const SIM_SUPPLY_VOLTAGE_V: f32 = 24.0;
@@ -0,0 +1,195 @@
+//! Joint configuration kept across power cycles
+//!
+//! Firmware implements [`ConfigStore`] over whatever non-volatile memory the
+//! board has (a flash page, an EEPROM, a file on a simulator) and passes it
+//! to [`Joint::handle_with_store`](crate::Joint::handle_with_store), or
+//! returns it from `JointHooks::config_store` with `run_embassy`. The joint
+//! then answers `SaveConfig`, `LoadConfig` and `FactoryReset`, and
+//! [`Joint::restore_config`](crate::Joint::restore_config) brings the saved
+//! node ID, soft limits, calibration result, telemetry settings, encoder
+//! offset, controller gains and usage counters back at boot.
+//!
+//! ```ignore
+//! let mut store = FlashPage::new(flash, CONFIG_PAGE);
+//! let mut joint = Joint::new(0x0010);
+//! // An empty store is fine: the joint keeps its defaults
+//! let _ = joint.restore_config(&mut store);
+//! loop {
+//!     if let Some(msg) = transport.receive_message()? {
+//!         if let Some(reply) = joint.handle_with_store(&msg, &mut store) {
+//!             transport.send_message(&reply)?;
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! # Record format
+//!
+//! The store holds one record of [`JointConfig::RECORD_SIZE`] bytes: a
+//! 4-byte magic, a format version byte, a 2-byte little-endian length, the
+//! postcard encoding of the [`JointConfig`] padded with zeros, and a 4-byte
+//! FNV-1a checksum over everything before it. Erased memory or a record
+//! torn by a power loss fails the check and reads as no configuration.
+
+use postcard::experimental::max_size::MaxSize;
+use serde::{Deserialize, Serialize};
+
+use crate::control::GainSchedule;
+use crate::crash::checksum;
+use crate::protocol::{
+    ConfigureTelemetryPayload, ControlGains, ControlMode, DeviceId, JointLimits, JointStatistics, MotorParameters,
+    MultiTurnPosition,
+};
+
+/// Marks a configuration record ("iRCF" in ASCII)
+const CONFIG_MAGIC: u32 = 0x6952_4346;
+
+/// Record layout version, bumped when `JointConfig` changes
+const CONFIG_FORMAT_VERSION: u8 = 5;
+
+/// Magic, version and length
+const HEADER_LEN: usize = 7;
+
+/// Configuration store errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ConfigStoreError {
+    /// The memory could not be read, written or erased
+    #[error("Config store I/O failed")]
+    Io,
+
+    /// The store holds no valid record (erased, torn or an older format)
+    #[error("No valid configuration stored")]
+    NoConfig,
+}
+
+/// Non-volatile memory holding one configuration record
+///
+/// Records are at most [`JointConfig::RECORD_SIZE`] bytes; implementations
+/// reserve that much (a flash page is plenty).
+pub trait ConfigStore {
+    /// Fill `buf` from the start of the record area
+    ///
+    /// Erased or never written memory is returned as it is; the record
+    /// check rejects it.
+    fn read(&mut self, buf: &mut [u8]) -> Result<(), ConfigStoreError>;
+
+    /// Replace the stored record with `record`
+    fn write(&mut self, record: &[u8]) -> Result<(), ConfigStoreError>;
+
+    /// Erase the stored record
+    fn erase(&mut self) -> Result<(), ConfigStoreError>;
+}
+
+/// Everything a joint keeps across power cycles
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, Default)]
+pub struct JointConfig {
+    /// Node ID to use instead of the firmware default
+    pub node_id: Option<DeviceId>,
+    pub limits: Option<JointLimits>,
+    /// Parameters identified by the last successful calibration
+    pub motor_parameters: Option<MotorParameters>,
+    pub telemetry: Option<ConfigureTelemetryPayload>,
+    /// Raw encoder position that reads as zero
+    pub encoder_offset: Option<MultiTurnPosition>,
+    /// Base gains of the position/velocity controller
+    pub gains: Option<ControlGains>,
+    pub gain_schedule: GainSchedule,
+    /// What the control loop follows after boot
+    pub control_mode: ControlMode,
+    /// Usage counters, saved along with the settings
+    pub statistics: JointStatistics,
+}
+
+/// cbindgen:ignore
+impl JointConfig {
+    /// Size of a stored record in bytes
+    pub const RECORD_SIZE: usize = HEADER_LEN + Self::POSTCARD_MAX_SIZE + 4;
+
+    /// Encode as a store record
+    pub fn to_record(&self) -> [u8; Self::RECORD_SIZE] {
+        let mut record = [0u8; Self::RECORD_SIZE];
+        let body = &mut record[HEADER_LEN..HEADER_LEN + Self::POSTCARD_MAX_SIZE];
+        // Cannot fail: the buffer holds the largest possible configuration
+        let len = postcard::to_slice(self, body).map_or(0, |used| used.len());
+        record[..4].copy_from_slice(&CONFIG_MAGIC.to_le_bytes());
+        record[4] = CONFIG_FORMAT_VERSION;
+        record[5..HEADER_LEN].copy_from_slice(&(len as u16).to_le_bytes());
+        let sum = checksum(&record[..Self::RECORD_SIZE - 4]);
+        record[Self::RECORD_SIZE - 4..].copy_from_slice(&sum.to_le_bytes());
+        record
+    }
+
+    /// Decode a store record
+    pub fn from_record(record: &[u8]) -> Result<Self, ConfigStoreError> {
+        let record = record.get(..Self::RECORD_SIZE).ok_or(ConfigStoreError::NoConfig)?;
+        let (data, sum) = record.split_at(Self::RECORD_SIZE - 4);
+        if data[..4] != CONFIG_MAGIC.to_le_bytes()
+            || data[4] != CONFIG_FORMAT_VERSION
+            || sum != checksum(data).to_le_bytes()
+        {
+            return Err(ConfigStoreError::NoConfig);
+        }
+        let len = u16::from_le_bytes([data[5], data[6]]) as usize;
+        let body = data[HEADER_LEN..].get(..len).ok_or(ConfigStoreError::NoConfig)?;
+        postcard::from_bytes(body).map_err(|_| ConfigStoreError::NoConfig)
+    }
+
+    /// Read the stored configuration
+    pub fn load(store: &mut dyn ConfigStore) -> Result<Self, ConfigStoreError> {
+        let mut record = [0u8; Self::RECORD_SIZE];
+        store.read(&mut record)?;
+        Self::from_record(&record)
+    }
+
+    /// Replace the stored configuration
+    pub fn save(&self, store: &mut dyn ConfigStore) -> Result<(), ConfigStoreError> {
+        store.write(&self.to_record())
+    }
+}
+
+/// Store in RAM, for simulation and tests
+///
+/// Starts erased (all `0xFF`, like flash).
+#[derive(Debug, Clone)]
+pub struct MemoryConfigStore {
+    bytes: [u8; JointConfig::RECORD_SIZE],
+}
+
+impl MemoryConfigStore {
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0xFF; JointConfig::RECORD_SIZE],
+        }
+    }
+
+    /// The raw record area, e.g. to corrupt it in a test
+    pub fn bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+}
+
+impl Default for MemoryConfigStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigStore for MemoryConfigStore {
+    fn read(&mut self, buf: &mut [u8]) -> Result<(), ConfigStoreError> {
+        let len = buf.len().min(self.bytes.len());
+        buf[..len].copy_from_slice(&self.bytes[..len]);
+        buf[len..].fill(0xFF);
+        Ok(())
+    }
+
+    fn write(&mut self, record: &[u8]) -> Result<(), ConfigStoreError> {
+        let target = self.bytes.get_mut(..record.len()).ok_or(ConfigStoreError::Io)?;
+        target.copy_from_slice(record);
+        Ok(())
+    }
+
+    fn erase(&mut self) -> Result<(), ConfigStoreError> {
+        self.bytes.fill(0xFF);
+        Ok(())
+    }
+}
@@ -0,0 +1,323 @@
+//! Cascaded position/velocity control loop
+//!
+//! Closes the gap between an accepted `SetTarget` and a moving motor.
+//! [`Joint::control_step`](crate::Joint::control_step) runs one cycle: it
+//! samples the encoder, runs the position loop (degrees → velocity setpoint
+//! in degrees/second), the velocity loop (→ current setpoint in amperes) and
+//! commands the current through the [`MotorDriver`](crate::motor::MotorDriver).
+//! The arm sets the gains with `ConfigureGains`; until then the joint does
+//! not drive the motor.
+//!
+//! ```ignore
+//! let mut ticker = Ticker::every(Duration::from_hz(1000));
+//! loop {
+//!     if let Err(e) = joint.control_step(0.001, &mut encoder, &mut driver) {
+//!         defmt::warn!("control step failed: {}", e);
+//!     }
+//!     ticker.next().await;
+//! }
+//! ```
+//!
+//! Without a target the controller holds the position it started from. The
+//! target's velocity, acceleration and current limits (when set) bound the
+//! setpoints; the target itself is approached directly, without a planned
+//! trajectory.
+//!
+//! # Gain scheduling
+//!
+//! Gains tuned unloaded rarely suit a loaded arm. A [`GainSchedule`] holds up
+//! to `GAIN_SCHEDULE_SLOTS` further gain sets, each for a [`GainRegion`]: the
+//! target's motion profile, a minimum speed and a minimum load (the current
+//! setpoint). Every cycle the highest slot whose region the controller is in
+//! supplies the gains, otherwise the base gains do. The arm fills slots with
+//! `SetGainScheduleEntry`, and `SaveConfig` keeps the schedule with the base
+//! gains. Switching keeps the integral terms, so the output does not jump.
+//!
+//! # Control modes
+//!
+//! `SetControlMode` picks what the loop follows while the joint is not
+//! Active. In [`ControlMode::Velocity`](crate::protocol::ControlMode) the
+//! `SetVelocity` speed feeds the velocity loop directly
+//! ([`CascadedController::update_velocity`]); in `ControlMode::Torque` the
+//! `SetTorque` torque is commanded as current through the motor's torque
+//! constant, bypassing both loops. The current limit and feedforward apply
+//! in every mode.
+//!
+//! # Thermal derating
+//!
+//! A joint above the warning temperature of its `ThermalLimits` scales the
+//! current limit, the target's velocity limit and the `SetVelocity` speed
+//! down with [`CascadedController::set_derating`].
+
+use postcard::experimental::max_size::MaxSize;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GAIN_SCHEDULE_SLOTS;
+use crate::encoder::EncoderError;
+use crate::motor::MotorError;
+use crate::protocol::{ControlGains, GainRegion, GainScheduleEntry, MotionProfile, PidGains, SetTargetPayloadV2};
+
+/// Control cycle errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ControlError {
+    /// The encoder could not be read; the position is now unknown
+    #[error("Encoder error: {0}")]
+    Encoder(#[from] EncoderError),
+
+    /// The motor driver refused the command
+    #[error("Motor error: {0}")]
+    Motor(#[from] MotorError),
+}
+
+/// A single PID loop
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pid {
+    gains: PidGains,
+    /// Integral term, already scaled by `ki`
+    integral: f32,
+    last_error: Option<f32>,
+}
+
+impl Pid {
+    pub const fn new(gains: PidGains) -> Self {
+        Self { gains, integral: 0.0, last_error: None }
+    }
+
+    pub fn gains(&self) -> PidGains {
+        self.gains
+    }
+
+    /// Switch gains without a bump: the integral term carries over, bounded
+    /// by the new limit
+    pub fn set_gains(&mut self, gains: PidGains) {
+        self.gains = gains;
+        self.integral = self.integral.max(-gains.integral_limit).min(gains.integral_limit);
+    }
+
+    /// Forget the integral and the previous error
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.last_error = None;
+    }
+
+    /// Output for `error` after `dt` seconds
+    ///
+    /// The derivative term starts on the second update after a reset.
+    pub fn update(&mut self, error: f32, dt: f32) -> f32 {
+        let derivative = match self.last_error {
+            Some(last) if dt > 0.0 => (error - last) / dt,
+            _ => 0.0,
+        };
+        self.last_error = Some(error);
+        let limit = self.gains.integral_limit;
+        self.integral = (self.integral + self.gains.ki * error * dt).max(-limit).min(limit);
+        self.gains.kp * error + self.integral + self.gains.kd * derivative
+    }
+}
+
+/// Gain sets for operating regions
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct GainSchedule {
+    slots: [Option<(GainRegion, ControlGains)>; GAIN_SCHEDULE_SLOTS],
+}
+
+impl GainSchedule {
+    /// Fill the entry's slot; `false` if there is no such slot
+    pub fn set(&mut self, entry: &GainScheduleEntry) -> bool {
+        match self.slots.get_mut(entry.slot as usize) {
+            Some(slot) => {
+                *slot = Some((entry.region, entry.gains));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Region and gains in `slot`, if filled
+    pub fn get(&self, slot: u8) -> Option<(GainRegion, ControlGains)> {
+        self.slots.get(slot as usize).copied().flatten()
+    }
+
+    pub fn clear(&mut self) {
+        self.slots = Default::default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+
+    /// Gains of the highest slot whose region covers the operating point
+    pub fn select(&self, profile: Option<MotionProfile>, speed: f32, load: f32) -> Option<ControlGains> {
+        self.slots
+            .iter()
+            .rev()
+            .flatten()
+            .find(|(region, _)| region.contains(profile, speed, load))
+            .map(|(_, gains)| *gains)
+    }
+}
+
+/// Position loop feeding a velocity loop, producing a current setpoint
+#[derive(Debug, Clone, Copy)]
+pub struct CascadedController {
+    gains: ControlGains,
+    schedule: GainSchedule,
+    /// Gains used by the last update
+    active: ControlGains,
+    position: Pid,
+    velocity: Pid,
+    /// Position held when there is no target
+    hold: Option<f32>,
+    /// Current added to the velocity loop's output
+    feedforward: f32,
+    velocity_setpoint: f32,
+    current_setpoint: f32,
+    /// Share of the current and velocity limits in use (thermal derating)
+    derating: f32,
+}
+
+impl CascadedController {
+    pub const fn new(gains: ControlGains) -> Self {
+        Self {
+            gains,
+            schedule: GainSchedule { slots: [None; GAIN_SCHEDULE_SLOTS] },
+            active: gains,
+            position: Pid::new(gains.position),
+            velocity: Pid::new(gains.velocity),
+            hold: None,
+            feedforward: 0.0,
+            velocity_setpoint: 0.0,
+            current_setpoint: 0.0,
+            derating: 1.0,
+        }
+    }
+
+    /// Base gains, used outside every scheduled region
+    pub fn gains(&self) -> ControlGains {
+        self.gains
+    }
+
+    pub fn schedule(&self) -> &GainSchedule {
+        &self.schedule
+    }
+
+    pub fn set_schedule(&mut self, schedule: GainSchedule) {
+        self.schedule = schedule;
+    }
+
+    /// Gains the last update ran with
+    pub fn active_gains(&self) -> ControlGains {
+        self.active
+    }
+
+    /// Start over: integrals cleared, the next update picks the position to
+    /// hold
+    pub fn reset(&mut self) {
+        self.position.reset();
+        self.velocity.reset();
+        self.hold = None;
+        self.feedforward = 0.0;
+        self.velocity_setpoint = 0.0;
+        self.current_setpoint = 0.0;
+    }
+
+    /// Current in amperes added to the output from the next update on
+    /// (gravity compensation); still bounded by the current limit
+    pub fn set_feedforward(&mut self, current: f32) {
+        self.feedforward = current;
+    }
+
+    /// Scale the current limit and velocity limits by `derating` (0.0-1.0)
+    /// from the next update on; kept across resets
+    pub fn set_derating(&mut self, derating: f32) {
+        self.derating = derating.clamp(0.0, 1.0);
+    }
+
+    pub fn derating(&self) -> f32 {
+        self.derating
+    }
+
+    /// Velocity setpoint of the last update, in degrees/second
+    pub fn velocity_setpoint(&self) -> f32 {
+        self.velocity_setpoint
+    }
+
+    /// Current setpoint of the last update, in amperes
+    pub fn current_setpoint(&self) -> f32 {
+        self.current_setpoint
+    }
+
+    /// One control cycle of `dt` seconds; returns the current setpoint in
+    /// amperes
+    ///
+    /// `position` is in degrees, `velocity` in degrees/second. `None` holds
+    /// the position of the first update after a reset.
+    pub fn update(&mut self, target: Option<&SetTargetPayloadV2>, position: f32, velocity: f32, dt: f32) -> f32 {
+        self.schedule_gains(target.map(|t| t.profile), velocity);
+
+        let hold = *self.hold.get_or_insert(position);
+        let setpoint = target.map_or(hold, |t| t.target_angle);
+
+        let mut velocity_setpoint = self.position.update(setpoint - position, dt);
+        if let Some(limit) = target.map(|t| t.max_velocity * self.derating).filter(|v| *v > 0.0) {
+            velocity_setpoint = velocity_setpoint.max(-limit).min(limit);
+        }
+        if let Some(limit) = target.map(|t| t.max_acceleration).filter(|a| *a > 0.0) {
+            let step = limit * dt;
+            velocity_setpoint = velocity_setpoint
+                .max(self.velocity_setpoint - step)
+                .min(self.velocity_setpoint + step);
+        }
+        self.velocity_setpoint = velocity_setpoint;
+
+        let mut max_current = self.active.max_current * self.derating;
+        if let Some(limit) = target.map(|t| t.max_current).filter(|c| *c > 0.0) {
+            max_current = max_current.min(limit);
+        }
+        let current = self.velocity.update(velocity_setpoint - velocity, dt) + self.feedforward;
+        self.current_setpoint = current.max(-max_current).min(max_current);
+        self.current_setpoint
+    }
+
+    /// One cycle of the velocity loop alone, towards `velocity_setpoint` in
+    /// degrees/second; returns the current setpoint in amperes
+    ///
+    /// The position loop is left idle and forgets the position it held.
+    pub fn update_velocity(&mut self, velocity_setpoint: f32, velocity: f32, dt: f32) -> f32 {
+        self.schedule_gains(None, velocity);
+        self.position.reset();
+        self.hold = None;
+        self.velocity_setpoint = velocity_setpoint * self.derating;
+
+        let max_current = self.active.max_current * self.derating;
+        let current = self.velocity.update(self.velocity_setpoint - velocity, dt) + self.feedforward;
+        self.current_setpoint = current.max(-max_current).min(max_current);
+        self.current_setpoint
+    }
+
+    /// Command `current` in amperes directly, plus the feedforward and within
+    /// the current limit; both loops stay idle
+    pub fn update_current(&mut self, current: f32) -> f32 {
+        self.position.reset();
+        self.velocity.reset();
+        self.hold = None;
+        self.velocity_setpoint = 0.0;
+
+        let max_current = self.active.max_current * self.derating;
+        self.current_setpoint = (current + self.feedforward).max(-max_current).min(max_current);
+        self.current_setpoint
+    }
+
+    /// Switch to the gains the schedule picks for the operating point
+    fn schedule_gains(&mut self, profile: Option<MotionProfile>, velocity: f32) {
+        let gains = self
+            .schedule
+            .select(profile, velocity.abs(), self.current_setpoint.abs())
+            .unwrap_or(self.gains);
+        if gains != self.active {
+            self.active = gains;
+            self.position.set_gains(gains.position);
+            self.velocity.set_gains(gains.velocity);
+        }
+    }
+}
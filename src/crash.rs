@@ -0,0 +1,159 @@
+//! Crash records kept across resets
+//!
+//! A panic or HardFault handler stores a [`CrashRecord`] in a [`CrashSlot`]
+//! placed in RAM that the startup code does not zero, then resets the MCU.
+//! After the restart the record is taken out of the slot and sent in the
+//! joint's boot announcement ([`Joint::boot_announcement`](crate::Joint::boot_announcement),
+//! or [`JointHooks::crash_record`](crate::JointHooks::crash_record) with
+//! `run_embassy`), and the host reports it as a `JointCrashed` event.
+//!
+//! ```ignore
+//! use core::mem::MaybeUninit;
+//! use irpc::crash::{panic_record, CrashSlot};
+//!
+//! #[link_section = ".uninit.irpc_crash"]
+//! static mut CRASH: MaybeUninit<CrashSlot> = MaybeUninit::uninit();
+//!
+//! #[panic_handler]
+//! fn panic(info: &core::panic::PanicInfo) -> ! {
+//!     let slot = unsafe { CrashSlot::from_noinit(&mut *core::ptr::addr_of_mut!(CRASH)) };
+//!     slot.store(&panic_record(info, "joint"));
+//!     cortex_m::peripheral::SCB::sys_reset()
+//! }
+//! ```
+
+use core::fmt::Write;
+use core::mem::MaybeUninit;
+use core::panic::PanicInfo;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use postcard::experimental::max_size::MaxSize;
+
+use crate::config::CRASH_MESSAGE_LEN;
+use crate::protocol::{CrashKind, CrashRecord};
+
+/// Marks a slot holding a record ("iRPC" in ASCII)
+const CRASH_SLOT_MAGIC: u32 = 0x6952_5043;
+
+/// Storage for one crash record that survives a reset
+///
+/// Only plain integers, so whatever the RAM holds after power-up is a valid
+/// (empty or corrupted) slot; a magic number and checksum tell a stored
+/// record apart from leftover contents.
+#[repr(C)]
+pub struct CrashSlot {
+    magic: u32,
+    checksum: u32,
+    bytes: [u8; CrashRecord::POSTCARD_MAX_SIZE],
+}
+
+impl CrashSlot {
+    /// An empty slot
+    pub const fn new() -> Self {
+        Self {
+            magic: 0,
+            checksum: 0,
+            bytes: [0; CrashRecord::POSTCARD_MAX_SIZE],
+        }
+    }
+
+    /// View a slot in non-initialized RAM (e.g. a `.uninit` section)
+    ///
+    /// # Safety
+    /// `slot` must be ordinary RAM that nothing else accesses while the
+    /// returned reference is alive.
+    pub unsafe fn from_noinit(slot: &mut MaybeUninit<CrashSlot>) -> &mut CrashSlot {
+        // SAFETY: every bit pattern is a valid `CrashSlot`; exclusivity is
+        // the caller's promise
+        unsafe { slot.assume_init_mut() }
+    }
+
+    /// Store a record, replacing any previous one
+    ///
+    /// Safe to call from a panic or fault handler: it does not allocate,
+    /// lock or panic.
+    pub fn store(&mut self, record: &CrashRecord) {
+        self.bytes = [0; CrashRecord::POSTCARD_MAX_SIZE];
+        // Cannot fail: the buffer holds the largest possible record
+        let _ = postcard::to_slice(record, &mut self.bytes);
+        self.checksum = checksum(&self.bytes);
+        self.magic = CRASH_SLOT_MAGIC;
+        // Make sure the writes happen before the handler resets the MCU
+        compiler_fence(Ordering::SeqCst);
+    }
+
+    /// The stored record, if the slot holds a valid one
+    pub fn peek(&self) -> Option<CrashRecord> {
+        if self.magic != CRASH_SLOT_MAGIC || self.checksum != checksum(&self.bytes) {
+            return None;
+        }
+        postcard::from_bytes(&self.bytes).ok()
+    }
+
+    /// Take the stored record, leaving the slot empty
+    pub fn take(&mut self) -> Option<CrashRecord> {
+        let record = self.peek();
+        self.magic = 0;
+        record
+    }
+}
+
+impl Default for CrashSlot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// FNV-1a over the slot contents
+pub(crate) fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811C_9DC5, |hash, &b| (hash ^ b as u32).wrapping_mul(0x0100_0193))
+}
+
+/// Build a record for a panic, for use in a `#[panic_handler]`
+///
+/// The message is `file:line: message` with the directory stripped from the
+/// file name, truncated to `CRASH_MESSAGE_LEN` bytes.
+pub fn panic_record(info: &PanicInfo, task: &str) -> CrashRecord {
+    let mut message = TruncatingWriter::default();
+    if let Some(location) = info.location() {
+        let file = location.file().rsplit(['/', '\\']).next().unwrap_or_default();
+        let _ = write!(message, "{}:{}: ", file, location.line());
+    }
+    let _ = write!(message, "{}", info.message());
+    CrashRecord::new(CrashKind::Panic, 0, 0, task, message.as_str())
+}
+
+/// Build a record for a watchdog reset
+///
+/// Nothing runs a handler when the watchdog fires, so firmware that finds
+/// the MCU's watchdog reset flag set at startup reports this instead of the
+/// (empty) slot's contents.
+pub fn watchdog_record() -> CrashRecord {
+    CrashRecord::new(CrashKind::Watchdog, 0, 0, "", "watchdog reset")
+}
+
+/// `fmt::Write` into a fixed buffer that silently drops what does not fit
+#[derive(Default)]
+struct TruncatingWriter {
+    buf: [u8; CRASH_MESSAGE_LEN],
+    len: usize,
+}
+
+impl TruncatingWriter {
+    fn as_str(&self) -> &str {
+        // Only whole characters are ever copied in
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+}
+
+impl Write for TruncatingWriter {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let mut take = s.len().min(self.buf.len() - self.len);
+        while !s.is_char_boundary(take) {
+            take -= 1;
+        }
+        self.buf[self.len..self.len + take].copy_from_slice(&s.as_bytes()[..take]);
+        self.len += take;
+        Ok(())
+    }
+}
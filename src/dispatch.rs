@@ -0,0 +1,142 @@
+//! Handlers for unsolicited messages
+//!
+//! Joints send telemetry, status changes, boot reports, calibration
+//! progress and fault reports on their own. Handlers registered with
+//! [`CommunicationManager::on`](crate::CommunicationManager::on) receive
+//! them by payload type, from one joint or from all of them:
+//!
+//! ```
+//! use irpc::{CommunicationManager, TelemetryStream};
+//! use irpc::dispatch::StatusReport;
+//!
+//! let comm = CommunicationManager::new();
+//! comm.on::<TelemetryStream>(0x0010, |_joint, stream| println!("shoulder at {}", stream.position));
+//! let faults = comm.on_any::<StatusReport>(|joint, status| {
+//!     if status.error_code != 0 {
+//!         eprintln!("joint {} reports error {:#06x}", joint, status.error_code);
+//!     }
+//! });
+//! comm.remove_handler(faults);
+//! ```
+//!
+//! Handlers run on the task that processes incoming messages, so they
+//! should return quickly; spawn a task for anything slow. Replies to
+//! requests go to the request instead and are never dispatched.
+
+use std::sync::Arc;
+
+use crate::protocol::{
+    AdaptiveStatusPayload, BootPayload, CalibrationResult, CalibrationStatus, DeviceId, EncoderTelemetry,
+    FaultReportPayload, InterlockStatePayload, LifecycleState, Message, Payload, SyncSample, TelemetryStream,
+};
+
+/// A payload type handlers can be registered for
+pub trait UnsolicitedPayload: Sized + 'static {
+    /// The value carried by `payload`, if it is of this type
+    fn from_payload(payload: &Payload) -> Option<Self>;
+}
+
+/// Every payload, whatever its kind
+impl UnsolicitedPayload for Payload {
+    fn from_payload(payload: &Payload) -> Option<Self> {
+        Some(payload.clone())
+    }
+}
+
+macro_rules! unsolicited {
+    ($($variant:ident => $ty:ty),* $(,)?) => {
+        $(
+            impl UnsolicitedPayload for $ty {
+                fn from_payload(payload: &Payload) -> Option<Self> {
+                    match payload {
+                        Payload::$variant(value) => Some(*value),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+unsolicited! {
+    TelemetryStream => TelemetryStream,
+    Encoder => EncoderTelemetry,
+    AdaptiveStatus => AdaptiveStatusPayload,
+    CalibrationStatus => CalibrationStatus,
+    CalibrationResult => CalibrationResult,
+    Boot => BootPayload,
+    InterlockState => InterlockStatePayload,
+    FaultReport => FaultReportPayload,
+    SyncSample => SyncSample,
+}
+
+/// A `Payload::JointStatus` report: lifecycle state and fault code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusReport {
+    pub state: LifecycleState,
+    /// `0` when the joint reports no fault
+    pub error_code: u16,
+}
+
+impl UnsolicitedPayload for StatusReport {
+    fn from_payload(payload: &Payload) -> Option<Self> {
+        match *payload {
+            Payload::JointStatus { state, error_code } => Some(Self { state, error_code }),
+            _ => None,
+        }
+    }
+}
+
+/// Registration of a handler, for [`remove_handler`](crate::CommunicationManager::remove_handler)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandlerId(u64);
+
+/// Calls a handler if the message is of its type; `true` if it did
+type Call = Arc<dyn Fn(&Message) -> bool + Send + Sync>;
+
+/// Handlers registered on a communication manager
+#[derive(Default)]
+pub(crate) struct Handlers {
+    next_id: u64,
+    /// In registration order, with the joint each one listens to
+    entries: Vec<(HandlerId, Option<DeviceId>, Call)>,
+}
+
+impl Handlers {
+    pub fn add<T, F>(&mut self, joint_id: Option<DeviceId>, handler: F) -> HandlerId
+    where
+        T: UnsolicitedPayload,
+        F: Fn(DeviceId, T) + Send + Sync + 'static,
+    {
+        self.next_id += 1;
+        let id = HandlerId(self.next_id);
+        let call: Call = Arc::new(move |message: &Message| match T::from_payload(&message.payload) {
+            Some(value) => {
+                handler(message.header.source_id, value);
+                true
+            }
+            None => false,
+        });
+        self.entries.push((id, joint_id, call));
+        id
+    }
+
+    pub fn remove(&mut self, id: HandlerId) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|(entry, _, _)| *entry != id);
+        self.entries.len() != before
+    }
+
+    /// Handlers listening to the sender of `message`
+    ///
+    /// Returned rather than called, so handlers run without the registry
+    /// locked and may register or remove handlers themselves.
+    pub fn for_message(&self, message: &Message) -> Vec<Call> {
+        let source = message.header.source_id;
+        self.entries
+            .iter()
+            .filter(|(_, joint_id, _)| joint_id.is_none_or(|joint_id| joint_id == source))
+            .map(|(_, _, call)| call.clone())
+            .collect()
+    }
+}
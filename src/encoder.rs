@@ -0,0 +1,318 @@
+//! Position sensors behind a common interface
+//!
+//! Firmware wraps its encoder in [`Encoder`] and hands it to
+//! [`Joint::sample_encoder`](crate::Joint::sample_encoder) every control
+//! cycle, or returns it from `JointHooks::encoder` with `run_embassy`, which
+//! does that before each `update`. The joint then knows its position and
+//! velocity, answers `RequestMultiTurnPosition`, drops into the
+//! position-unknown state when the encoder fails, and builds telemetry with
+//! [`Joint::encoder_telemetry`](crate::Joint::encoder_telemetry):
+//!
+//! ```ignore
+//! impl JointHooks for Board {
+//!     fn encoder(&mut self) -> Option<&mut dyn Encoder> {
+//!         Some(&mut self.encoder)
+//!     }
+//!
+//!     fn telemetry(&mut self, joint: &Joint) -> Option<Payload> {
+//!         joint.encoder_telemetry().map(Payload::Encoder)
+//!     }
+//! }
+//! ```
+//!
+//! Two reference implementations are included:
+//!
+//! - [`QuadratureEncoder`] extends a 16-bit hardware counter (a timer in
+//!   encoder mode; `embassy_stm32::timer::qei::Qei` with the stm32 features)
+//!   to a multi-turn position
+//! - [`As5047`] reads an AS5047P-style 14-bit magnetic absolute encoder over
+//!   an `embedded-hal` [`SpiDevice`](embedded_hal::spi::SpiDevice) (with the
+//!   stm32 or `mcp2518fd` features)
+//!
+//! Both count turns from power-up; the turn count of a single-turn encoder
+//! is not kept across power cycles.
+
+use core::ops::{BitOr, BitOrAssign};
+
+use crate::protocol::MultiTurnPosition;
+
+/// Encoder read errors
+///
+/// Any of them leaves the joint's position unknown until re-homed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum EncoderError {
+    /// The encoder did not answer, or its answer failed the check
+    #[error("Encoder communication failed")]
+    Communication,
+
+    /// The sensor reports it cannot measure (magnet missing, signal lost)
+    #[error("Encoder signal lost")]
+    SignalLost,
+}
+
+/// Encoder warning flags
+///
+/// Conditions that do not (yet) invalidate the reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct EncoderFlags(pub u8);
+
+impl EncoderFlags {
+    pub const NONE: Self = Self(0);
+    /// Magnetic field weaker than the sensor's range
+    pub const FIELD_LOW: Self = Self(1 << 0);
+    /// Magnetic field stronger than the sensor's range
+    pub const FIELD_HIGH: Self = Self(1 << 1);
+    /// The sensor flagged an error on its last frame
+    pub const SENSOR_ERROR: Self = Self(1 << 2);
+    /// Moved more than half a revolution between reads; the turn count may
+    /// be off
+    pub const OVERSPEED: Self = Self(1 << 3);
+
+    /// Raw flag bits
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Whether every flag in `other` is set
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether no flag is set
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for EncoderFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for EncoderFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// A joint position sensor
+pub trait Encoder {
+    /// Raw position, turns included (before the joint's encoder offset)
+    fn read_position(&mut self) -> Result<MultiTurnPosition, EncoderError>;
+
+    /// Velocity in degrees/second, for sensors that measure it
+    ///
+    /// `None` (the default) makes the joint differentiate positions.
+    fn read_velocity(&mut self) -> Option<f32> {
+        None
+    }
+
+    /// Counts per revolution
+    fn resolution(&self) -> u32;
+
+    /// Warnings raised since the last read
+    fn error_flags(&mut self) -> EncoderFlags {
+        EncoderFlags::NONE
+    }
+}
+
+/// Extends a wrapping count to a multi-turn position
+///
+/// Successive counts must be less than half the modulus apart.
+#[derive(Debug, Clone, Copy)]
+struct TurnCounter {
+    counts_per_rev: u32,
+    /// Range of the raw count (it wraps from `modulus - 1` to 0)
+    modulus: u32,
+    last: Option<u32>,
+    total: i64,
+}
+
+impl TurnCounter {
+    const fn new(counts_per_rev: u32, modulus: u32) -> Self {
+        Self { counts_per_rev, modulus, last: None, total: 0 }
+    }
+
+    /// Account for a new raw count; `false` if it moved half the modulus or
+    /// more, so the direction is ambiguous
+    fn update(&mut self, raw: u32) -> bool {
+        let raw = raw % self.modulus;
+        let Some(last) = self.last.replace(raw) else {
+            self.total = raw as i64;
+            return true;
+        };
+        let half = (self.modulus / 2) as i64;
+        let mut delta = raw as i64 - last as i64;
+        if delta >= half {
+            delta -= self.modulus as i64;
+        } else if delta < -half {
+            delta += self.modulus as i64;
+        }
+        self.total += delta;
+        delta.abs() < half
+    }
+
+    fn position(&self) -> MultiTurnPosition {
+        let cpr = self.counts_per_rev as i64;
+        MultiTurnPosition {
+            turns: self.total.div_euclid(cpr) as i32,
+            angle: self.total.rem_euclid(cpr) as f32 * 360.0 / cpr as f32,
+        }
+    }
+}
+
+/// A 16-bit hardware quadrature counter
+pub trait QuadratureCounter {
+    /// Current count, wrapping at 2¹⁶
+    fn count(&mut self) -> u16;
+}
+
+/// Incremental encoder on a 16-bit quadrature counter
+///
+/// The counter must run over its full 16-bit range (auto-reload `0xFFFF`)
+/// and be read at least once per half range of travel. Position 0 is where
+/// the counter read 0; home the joint to give it meaning.
+pub struct QuadratureEncoder<C> {
+    counter: C,
+    turns: TurnCounter,
+    flags: EncoderFlags,
+}
+
+impl<C: QuadratureCounter> QuadratureEncoder<C> {
+    /// `counts_per_rev` counts the edges of both channels (4 × lines)
+    pub const fn new(counter: C, counts_per_rev: u32) -> Self {
+        Self {
+            counter,
+            turns: TurnCounter::new(counts_per_rev, 1 << 16),
+            flags: EncoderFlags::NONE,
+        }
+    }
+
+    /// Give back the counter
+    pub fn release(self) -> C {
+        self.counter
+    }
+}
+
+impl<C: QuadratureCounter> Encoder for QuadratureEncoder<C> {
+    fn read_position(&mut self) -> Result<MultiTurnPosition, EncoderError> {
+        if !self.turns.update(self.counter.count() as u32) {
+            self.flags |= EncoderFlags::OVERSPEED;
+        }
+        Ok(self.turns.position())
+    }
+
+    fn resolution(&self) -> u32 {
+        self.turns.counts_per_rev
+    }
+
+    fn error_flags(&mut self) -> EncoderFlags {
+        core::mem::take(&mut self.flags)
+    }
+}
+
+#[cfg(any(feature = "stm32g4", feature = "stm32f4"))]
+impl<T: embassy_stm32::timer::GeneralInstance4Channel> QuadratureCounter for embassy_stm32::timer::qei::Qei<'_, T> {
+    fn count(&mut self) -> u16 {
+        Self::count(self)
+    }
+}
+
+/// AS5047P register addresses
+#[cfg(feature = "embedded-hal")]
+mod as5047_reg {
+    pub const NOP: u16 = 0x0000;
+    pub const ERRFL: u16 = 0x0001;
+    pub const DIAAGC: u16 = 0x3FFC;
+    pub const ANGLECOM: u16 = 0x3FFF;
+}
+
+/// AS5047P/AS5048A magnetic absolute encoder (14 bits) on SPI
+///
+/// The SPI device must use mode 1 with 16-bit words sent MSB first; every
+/// frame is a separate transaction (the sensor needs CS to rise between
+/// frames). Turns are counted from the first read.
+#[cfg(feature = "embedded-hal")]
+pub struct As5047<S> {
+    spi: S,
+    turns: TurnCounter,
+    flags: EncoderFlags,
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<S: embedded_hal::spi::SpiDevice> As5047<S> {
+    /// Counts per revolution
+    pub const RESOLUTION: u32 = 1 << 14;
+
+    pub const fn new(spi: S) -> Self {
+        Self {
+            spi,
+            turns: TurnCounter::new(Self::RESOLUTION, Self::RESOLUTION),
+            flags: EncoderFlags::NONE,
+        }
+    }
+
+    /// Give back the SPI device
+    pub fn release(self) -> S {
+        self.spi
+    }
+
+    /// Read a register; the answer comes with the following frame
+    fn read_register(&mut self, address: u16) -> Result<u16, EncoderError> {
+        self.transfer(address | 0x4000)?;
+        let word = self.transfer(as5047_reg::NOP | 0x4000)?;
+        if word & 0x4000 != 0 {
+            self.flags |= EncoderFlags::SENSOR_ERROR;
+            // Reading ERRFL clears the error flag
+            self.transfer(as5047_reg::ERRFL | 0x4000)?;
+            self.transfer(as5047_reg::NOP | 0x4000)?;
+        }
+        Ok(word & 0x3FFF)
+    }
+
+    /// One 16-bit frame with even parity in bit 15
+    fn transfer(&mut self, command: u16) -> Result<u16, EncoderError> {
+        let command = command | (((command & 0x7FFF).count_ones() as u16 & 1) << 15);
+        let mut frame = command.to_be_bytes();
+        self.spi.transfer_in_place(&mut frame).map_err(|_| EncoderError::Communication)?;
+        let word = u16::from_be_bytes(frame);
+        if !word.count_ones().is_multiple_of(2) {
+            return Err(EncoderError::Communication);
+        }
+        Ok(word)
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<S: embedded_hal::spi::SpiDevice> Encoder for As5047<S> {
+    fn read_position(&mut self) -> Result<MultiTurnPosition, EncoderError> {
+        let diagnostics = self.read_register(as5047_reg::DIAAGC)?;
+        // MAGL and MAGH: field out of range; COF: CORDIC overflow
+        if diagnostics & (1 << 11) != 0 {
+            self.flags |= EncoderFlags::FIELD_LOW;
+        }
+        if diagnostics & (1 << 10) != 0 {
+            self.flags |= EncoderFlags::FIELD_HIGH;
+        }
+        if diagnostics & (1 << 9) != 0 {
+            return Err(EncoderError::SignalLost);
+        }
+        let angle = self.read_register(as5047_reg::ANGLECOM)?;
+        if !self.turns.update(angle as u32) {
+            self.flags |= EncoderFlags::OVERSPEED;
+        }
+        Ok(self.turns.position())
+    }
+
+    fn resolution(&self) -> u32 {
+        Self::RESOLUTION
+    }
+
+    fn error_flags(&mut self) -> EncoderFlags {
+        core::mem::take(&mut self.flags)
+    }
+}
@@ -0,0 +1,517 @@
+//! C API for existing C firmware
+//!
+//! Lets C code speak iRPC and run the joint state machine without being
+//! rewritten in Rust. The functions and types here are laid out for
+//! [cbindgen](https://github.com/mozilla/cbindgen); `cbindgen.toml` at the
+//! crate root generates `irpc.h`:
+//!
+//! ```text
+//! cbindgen --config cbindgen.toml --output include/irpc.h
+//! ```
+//!
+//! On a host, link the crate as a static library
+//! (`cargo rustc --release --features ffi,arm_api --crate-type staticlib`).
+//! Firmware builds are `no_std`, so wrap the crate in a small `staticlib`
+//! crate that provides the `#[panic_handler]` and `#[global_allocator]`.
+//!
+//! [`IrpcMessage`] is the C view of a [`Message`]: the header plus an
+//! [`IrpcPayload`] tagged union whose variants carry the protocol's
+//! `#[repr(C)]` payload structs. Messages from C must hold valid tags and
+//! enum values, as produced by [`irpc_message_decode`] or written with the
+//! generated constants.
+//!
+//! Fallible functions report an [`IrpcStatus`].
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::joint::Joint;
+use crate::protocol::{
+    AdaptiveStatusPayload, AnalogInputPayload, AssignIdPayload, BootInfoPayload, BootPayload, CalibrationRequest,
+    CalibrationResult, CalibrationStatus, ConfigureAdaptivePayload, ConfigureTelemetryPayload, ControlGains,
+    ControlMode, CrashKind, CrashRecord, DeviceId, DigitalIoPayload, EncoderTelemetry, FaultReportPayload,
+    FlightRecorderChunk, GainScheduleEntry, Header, HelloPayload, HomingConfig, InterlockStatePayload, JointLimits,
+    JointStatistics, LifecycleState, Message, MessageId, MultiTurnPosition, Payload, PowerLimits, PowerStatusPayload,
+    SelfTestRequest, SelfTestResult, SetTargetPayload, SetTargetPayloadV2, SyncSample, TargetBatch,
+    TelemetryStream, ThermalLimits, TransportStats,
+};
+
+/// Result of a C API call
+///
+/// Functions that return a count use the negative values for errors.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrpcStatus {
+    /// Success
+    Ok = 0,
+    /// A required pointer was null
+    NullPointer = -1,
+    /// The output buffer cannot hold the encoded message
+    BufferTooSmall = -2,
+    /// The input bytes are not a valid message
+    Decode = -3,
+}
+
+/// `Boot` payload with the optional crash record flattened for C
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct IrpcBootPayload {
+    /// Entity type identifier (see `ENTITY_TYPE_*` constants)
+    pub entity_type: u16,
+    /// Whether `crash` holds a record
+    pub has_crash: bool,
+    /// Why the previous run ended; zeroed unless `has_crash`
+    pub crash: CrashRecord,
+}
+
+/// `JointStatus` payload
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct IrpcJointStatus {
+    pub state: LifecycleState,
+    pub error_code: u16,
+}
+
+/// `Nack` payload
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct IrpcNack {
+    pub id: MessageId,
+    pub error: u16,
+}
+
+/// `SafetyEnable` payload
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct IrpcSafetyEnable {
+    pub token: u32,
+    pub ttl_ms: u16,
+}
+
+/// `StreamAck` payload
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct IrpcStreamAck {
+    pub last_msg_id: MessageId,
+    pub dropped: u32,
+}
+
+/// C view of [`Payload`], variant for variant
+///
+/// New payload variants must be added here as well (the conversions below
+/// are exhaustive, so the build fails until they are).
+#[repr(C, u8)]
+#[derive(Debug, Clone, Copy)]
+pub enum IrpcPayload {
+    SetTarget(SetTargetPayload),
+    Configure,
+    Activate,
+    Deactivate,
+    Reset,
+    SetTargetV2(SetTargetPayloadV2),
+    Encoder(EncoderTelemetry),
+    JointStatus(IrpcJointStatus),
+    TelemetryStream(TelemetryStream),
+    ConfigureTelemetry(ConfigureTelemetryPayload),
+    RequestTelemetry,
+    ConfigureAdaptive(ConfigureAdaptivePayload),
+    RequestAdaptiveStatus,
+    AdaptiveStatus(AdaptiveStatusPayload),
+    StartCalibration(CalibrationRequest),
+    StopCalibration,
+    CalibrationStatus(CalibrationStatus),
+    CalibrationResult(CalibrationResult),
+    Ack(MessageId),
+    Nack(IrpcNack),
+    ArmReady,
+    Discover,
+    Hello(HelloPayload),
+    RequestBusStats,
+    BusStats(TransportStats),
+    RequestStatus,
+    Home,
+    SetLimits(JointLimits),
+    Boot(IrpcBootPayload),
+    InterlockState(InterlockStatePayload),
+    EnterBootloader,
+    RequestBootInfo,
+    BootInfo(BootInfoPayload),
+    SaveConfig,
+    LoadConfig,
+    FactoryReset,
+    AssignId(AssignIdPayload),
+    SetDigitalOutput(DigitalIoPayload),
+    ReadDigitalInput(u8),
+    DigitalInput(DigitalIoPayload),
+    ReadAnalogInput(u8),
+    AnalogInput(AnalogInputPayload),
+    EngageBrake,
+    ReleaseBrake,
+    StartHoming(HomingConfig),
+    SetEncoderOffset(MultiTurnPosition),
+    RequestMultiTurnPosition,
+    MultiTurnPosition(MultiTurnPosition),
+    ConfigureGains(ControlGains),
+    SetGainScheduleEntry(GainScheduleEntry),
+    ClearGainSchedule,
+    SetFeedforward(f32),
+    SetControlMode(ControlMode),
+    SetVelocity(f32),
+    SetTorque(f32),
+    RequestFlightRecorder(u32),
+    FlightRecorder(FlightRecorderChunk),
+    SafetyEnable(IrpcSafetyEnable),
+    FaultReport(FaultReportPayload),
+    ConfigureThermal(ThermalLimits),
+    ConfigurePower(PowerLimits),
+    RequestPowerStatus,
+    PowerStatus(PowerStatusPayload),
+    RequestStatistics,
+    Statistics(JointStatistics),
+    RunSelfTest(SelfTestRequest),
+    SelfTestResult(SelfTestResult),
+    StreamAck(IrpcStreamAck),
+    SetTargetBatch(TargetBatch),
+    Sync(u32),
+    SyncSample(SyncSample),
+}
+
+/// C view of [`Message`]
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct IrpcMessage {
+    pub header: Header,
+    pub payload: IrpcPayload,
+}
+
+impl From<Payload> for IrpcPayload {
+    fn from(payload: Payload) -> Self {
+        match payload {
+            Payload::SetTarget(p) => Self::SetTarget(p),
+            Payload::Configure => Self::Configure,
+            Payload::Activate => Self::Activate,
+            Payload::Deactivate => Self::Deactivate,
+            Payload::Reset => Self::Reset,
+            Payload::SetTargetV2(p) => Self::SetTargetV2(p),
+            Payload::Encoder(p) => Self::Encoder(p),
+            Payload::JointStatus { state, error_code } => Self::JointStatus(IrpcJointStatus { state, error_code }),
+            Payload::TelemetryStream(p) => Self::TelemetryStream(p),
+            Payload::ConfigureTelemetry(p) => Self::ConfigureTelemetry(p),
+            Payload::RequestTelemetry => Self::RequestTelemetry,
+            Payload::ConfigureAdaptive(p) => Self::ConfigureAdaptive(p),
+            Payload::RequestAdaptiveStatus => Self::RequestAdaptiveStatus,
+            Payload::AdaptiveStatus(p) => Self::AdaptiveStatus(p),
+            Payload::StartCalibration(p) => Self::StartCalibration(p),
+            Payload::StopCalibration => Self::StopCalibration,
+            Payload::CalibrationStatus(p) => Self::CalibrationStatus(p),
+            Payload::CalibrationResult(p) => Self::CalibrationResult(p),
+            Payload::Ack(id) => Self::Ack(id),
+            Payload::Nack { id, error } => Self::Nack(IrpcNack { id, error }),
+            Payload::ArmReady => Self::ArmReady,
+            Payload::Discover => Self::Discover,
+            Payload::Hello(p) => Self::Hello(p),
+            Payload::RequestBusStats => Self::RequestBusStats,
+            Payload::BusStats(p) => Self::BusStats(p),
+            Payload::RequestStatus => Self::RequestStatus,
+            Payload::Home => Self::Home,
+            Payload::SetLimits(p) => Self::SetLimits(p),
+            Payload::Boot(p) => Self::Boot(IrpcBootPayload {
+                entity_type: p.entity_type,
+                has_crash: p.crash.is_some(),
+                crash: p.crash.unwrap_or_else(|| CrashRecord::new(CrashKind::Panic, 0, 0, "", "")),
+            }),
+            Payload::InterlockState(p) => Self::InterlockState(p),
+            Payload::EnterBootloader => Self::EnterBootloader,
+            Payload::RequestBootInfo => Self::RequestBootInfo,
+            Payload::BootInfo(p) => Self::BootInfo(p),
+            Payload::SaveConfig => Self::SaveConfig,
+            Payload::LoadConfig => Self::LoadConfig,
+            Payload::FactoryReset => Self::FactoryReset,
+            Payload::AssignId(p) => Self::AssignId(p),
+            Payload::SetDigitalOutput(p) => Self::SetDigitalOutput(p),
+            Payload::ReadDigitalInput(p) => Self::ReadDigitalInput(p),
+            Payload::DigitalInput(p) => Self::DigitalInput(p),
+            Payload::ReadAnalogInput(p) => Self::ReadAnalogInput(p),
+            Payload::AnalogInput(p) => Self::AnalogInput(p),
+            Payload::EngageBrake => Self::EngageBrake,
+            Payload::ReleaseBrake => Self::ReleaseBrake,
+            Payload::StartHoming(p) => Self::StartHoming(p),
+            Payload::SetEncoderOffset(p) => Self::SetEncoderOffset(p),
+            Payload::RequestMultiTurnPosition => Self::RequestMultiTurnPosition,
+            Payload::MultiTurnPosition(p) => Self::MultiTurnPosition(p),
+            Payload::ConfigureGains(p) => Self::ConfigureGains(p),
+            Payload::SetGainScheduleEntry(p) => Self::SetGainScheduleEntry(p),
+            Payload::ClearGainSchedule => Self::ClearGainSchedule,
+            Payload::SetFeedforward { torque } => Self::SetFeedforward(torque),
+            Payload::SetControlMode(mode) => Self::SetControlMode(mode),
+            Payload::SetVelocity { dps } => Self::SetVelocity(dps),
+            Payload::SetTorque { torque_nm } => Self::SetTorque(torque_nm),
+            Payload::RequestFlightRecorder { from } => Self::RequestFlightRecorder(from),
+            Payload::FlightRecorder(p) => Self::FlightRecorder(p),
+            Payload::SafetyEnable { token, ttl_ms } => Self::SafetyEnable(IrpcSafetyEnable { token, ttl_ms }),
+            Payload::FaultReport(p) => Self::FaultReport(p),
+            Payload::ConfigureThermal(p) => Self::ConfigureThermal(p),
+            Payload::ConfigurePower(p) => Self::ConfigurePower(p),
+            Payload::RequestPowerStatus => Self::RequestPowerStatus,
+            Payload::PowerStatus(p) => Self::PowerStatus(p),
+            Payload::RequestStatistics => Self::RequestStatistics,
+            Payload::Statistics(p) => Self::Statistics(p),
+            Payload::RunSelfTest(p) => Self::RunSelfTest(p),
+            Payload::SelfTestResult(p) => Self::SelfTestResult(p),
+            Payload::StreamAck { last_msg_id, dropped } => Self::StreamAck(IrpcStreamAck { last_msg_id, dropped }),
+            Payload::SetTargetBatch(p) => Self::SetTargetBatch(p),
+            Payload::Sync { cycle_counter } => Self::Sync(cycle_counter),
+            Payload::SyncSample(p) => Self::SyncSample(p),
+        }
+    }
+}
+
+impl From<IrpcPayload> for Payload {
+    fn from(payload: IrpcPayload) -> Self {
+        match payload {
+            IrpcPayload::SetTarget(p) => Self::SetTarget(p),
+            IrpcPayload::Configure => Self::Configure,
+            IrpcPayload::Activate => Self::Activate,
+            IrpcPayload::Deactivate => Self::Deactivate,
+            IrpcPayload::Reset => Self::Reset,
+            IrpcPayload::SetTargetV2(p) => Self::SetTargetV2(p),
+            IrpcPayload::Encoder(p) => Self::Encoder(p),
+            IrpcPayload::JointStatus(IrpcJointStatus { state, error_code }) => Self::JointStatus { state, error_code },
+            IrpcPayload::TelemetryStream(p) => Self::TelemetryStream(p),
+            IrpcPayload::ConfigureTelemetry(p) => Self::ConfigureTelemetry(p),
+            IrpcPayload::RequestTelemetry => Self::RequestTelemetry,
+            IrpcPayload::ConfigureAdaptive(p) => Self::ConfigureAdaptive(p),
+            IrpcPayload::RequestAdaptiveStatus => Self::RequestAdaptiveStatus,
+            IrpcPayload::AdaptiveStatus(p) => Self::AdaptiveStatus(p),
+            IrpcPayload::StartCalibration(p) => Self::StartCalibration(p),
+            IrpcPayload::StopCalibration => Self::StopCalibration,
+            IrpcPayload::CalibrationStatus(p) => Self::CalibrationStatus(p),
+            IrpcPayload::CalibrationResult(p) => Self::CalibrationResult(p),
+            IrpcPayload::Ack(id) => Self::Ack(id),
+            IrpcPayload::Nack(IrpcNack { id, error }) => Self::Nack { id, error },
+            IrpcPayload::ArmReady => Self::ArmReady,
+            IrpcPayload::Discover => Self::Discover,
+            IrpcPayload::Hello(p) => Self::Hello(p),
+            IrpcPayload::RequestBusStats => Self::RequestBusStats,
+            IrpcPayload::BusStats(p) => Self::BusStats(p),
+            IrpcPayload::RequestStatus => Self::RequestStatus,
+            IrpcPayload::Home => Self::Home,
+            IrpcPayload::SetLimits(p) => Self::SetLimits(p),
+            IrpcPayload::Boot(p) => Self::Boot(BootPayload {
+                entity_type: p.entity_type,
+                crash: p.has_crash.then_some(p.crash),
+            }),
+            IrpcPayload::InterlockState(p) => Self::InterlockState(p),
+            IrpcPayload::EnterBootloader => Self::EnterBootloader,
+            IrpcPayload::RequestBootInfo => Self::RequestBootInfo,
+            IrpcPayload::BootInfo(p) => Self::BootInfo(p),
+            IrpcPayload::SaveConfig => Self::SaveConfig,
+            IrpcPayload::LoadConfig => Self::LoadConfig,
+            IrpcPayload::FactoryReset => Self::FactoryReset,
+            IrpcPayload::AssignId(p) => Self::AssignId(p),
+            IrpcPayload::SetDigitalOutput(p) => Self::SetDigitalOutput(p),
+            IrpcPayload::ReadDigitalInput(p) => Self::ReadDigitalInput(p),
+            IrpcPayload::DigitalInput(p) => Self::DigitalInput(p),
+            IrpcPayload::ReadAnalogInput(p) => Self::ReadAnalogInput(p),
+            IrpcPayload::AnalogInput(p) => Self::AnalogInput(p),
+            IrpcPayload::EngageBrake => Self::EngageBrake,
+            IrpcPayload::ReleaseBrake => Self::ReleaseBrake,
+            IrpcPayload::StartHoming(p) => Self::StartHoming(p),
+            IrpcPayload::SetEncoderOffset(p) => Self::SetEncoderOffset(p),
+            IrpcPayload::RequestMultiTurnPosition => Self::RequestMultiTurnPosition,
+            IrpcPayload::MultiTurnPosition(p) => Self::MultiTurnPosition(p),
+            IrpcPayload::ConfigureGains(p) => Self::ConfigureGains(p),
+            IrpcPayload::SetGainScheduleEntry(p) => Self::SetGainScheduleEntry(p),
+            IrpcPayload::ClearGainSchedule => Self::ClearGainSchedule,
+            IrpcPayload::SetFeedforward(torque) => Self::SetFeedforward { torque },
+            IrpcPayload::SetControlMode(mode) => Self::SetControlMode(mode),
+            IrpcPayload::SetVelocity(dps) => Self::SetVelocity { dps },
+            IrpcPayload::SetTorque(torque_nm) => Self::SetTorque { torque_nm },
+            IrpcPayload::RequestFlightRecorder(from) => Self::RequestFlightRecorder { from },
+            IrpcPayload::FlightRecorder(p) => Self::FlightRecorder(p),
+            IrpcPayload::SafetyEnable(IrpcSafetyEnable { token, ttl_ms }) => Self::SafetyEnable { token, ttl_ms },
+            IrpcPayload::FaultReport(p) => Self::FaultReport(p),
+            IrpcPayload::ConfigureThermal(p) => Self::ConfigureThermal(p),
+            IrpcPayload::ConfigurePower(p) => Self::ConfigurePower(p),
+            IrpcPayload::RequestPowerStatus => Self::RequestPowerStatus,
+            IrpcPayload::PowerStatus(p) => Self::PowerStatus(p),
+            IrpcPayload::RequestStatistics => Self::RequestStatistics,
+            IrpcPayload::Statistics(p) => Self::Statistics(p),
+            IrpcPayload::RunSelfTest(p) => Self::RunSelfTest(p),
+            IrpcPayload::SelfTestResult(p) => Self::SelfTestResult(p),
+            IrpcPayload::StreamAck(IrpcStreamAck { last_msg_id, dropped }) => Self::StreamAck { last_msg_id, dropped },
+            IrpcPayload::SetTargetBatch(p) => Self::SetTargetBatch(p),
+            IrpcPayload::Sync(cycle_counter) => Self::Sync { cycle_counter },
+            IrpcPayload::SyncSample(p) => Self::SyncSample(p),
+        }
+    }
+}
+
+impl From<Message> for IrpcMessage {
+    fn from(message: Message) -> Self {
+        Self {
+            header: message.header,
+            payload: message.payload.into(),
+        }
+    }
+}
+
+impl From<IrpcMessage> for Message {
+    fn from(message: IrpcMessage) -> Self {
+        Self {
+            header: message.header,
+            payload: message.payload.into(),
+        }
+    }
+}
+
+/// Largest encoded message, for sizing buffers
+#[no_mangle]
+pub extern "C" fn irpc_max_message_size() -> usize {
+    Message::max_size()
+}
+
+/// Encode `message` into `buf`, storing the encoded length in `written`
+///
+/// # Safety
+/// `message` must point to a valid [`IrpcMessage`], `buf` to `len` writable
+/// bytes and `written` to a writable `usize`.
+#[no_mangle]
+pub unsafe extern "C" fn irpc_message_encode(
+    message: *const IrpcMessage,
+    buf: *mut u8,
+    len: usize,
+    written: *mut usize,
+) -> IrpcStatus {
+    if message.is_null() || buf.is_null() || written.is_null() {
+        return IrpcStatus::NullPointer;
+    }
+    // SAFETY: non-null and valid per the caller's contract
+    let (message, buf) = unsafe { (Message::from((*message).clone()), core::slice::from_raw_parts_mut(buf, len)) };
+    if message.encoded_size() > len {
+        return IrpcStatus::BufferTooSmall;
+    }
+    match message.serialize_into(buf) {
+        Ok(n) => {
+            // SAFETY: non-null and writable per the caller's contract
+            unsafe { *written = n };
+            IrpcStatus::Ok
+        }
+        Err(_) => IrpcStatus::BufferTooSmall,
+    }
+}
+
+/// Decode `len` bytes at `buf` into `out`
+///
+/// # Safety
+/// `buf` must point to `len` readable bytes and `out` to writable storage
+/// for an [`IrpcMessage`].
+#[no_mangle]
+pub unsafe extern "C" fn irpc_message_decode(buf: *const u8, len: usize, out: *mut IrpcMessage) -> IrpcStatus {
+    if buf.is_null() || out.is_null() {
+        return IrpcStatus::NullPointer;
+    }
+    // SAFETY: non-null and readable per the caller's contract
+    let bytes = unsafe { core::slice::from_raw_parts(buf, len) };
+    match Message::deserialize(bytes) {
+        Ok(message) => {
+            // SAFETY: non-null and writable per the caller's contract
+            unsafe { out.write(message.into()) };
+            IrpcStatus::Ok
+        }
+        Err(_) => IrpcStatus::Decode,
+    }
+}
+
+/// A joint state machine owned by C code
+pub struct IrpcJoint {
+    joint: Joint,
+}
+
+/// Create a joint with device ID `id`; free it with [`irpc_joint_free`]
+#[no_mangle]
+pub extern "C" fn irpc_joint_new(id: DeviceId) -> *mut IrpcJoint {
+    Box::into_raw(Box::new(IrpcJoint { joint: Joint::new(id) }))
+}
+
+/// Free a joint created by [`irpc_joint_new`]; null is ignored
+///
+/// # Safety
+/// `joint` must come from [`irpc_joint_new`] and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn irpc_joint_free(joint: *mut IrpcJoint) {
+    if !joint.is_null() {
+        // SAFETY: allocated by `irpc_joint_new`, ownership returns here
+        drop(unsafe { Box::from_raw(joint) });
+    }
+}
+
+/// Current lifecycle state of `joint`
+///
+/// # Safety
+/// `joint` must be a live joint from [`irpc_joint_new`].
+#[no_mangle]
+pub unsafe extern "C" fn irpc_joint_state(joint: *const IrpcJoint) -> LifecycleState {
+    // SAFETY: live joint per the caller's contract
+    unsafe { &*joint }.joint.state()
+}
+
+/// Feed `request` to `joint`; returns 1 and fills `reply` if the joint
+/// answers, 0 if it stays silent (e.g. the message is for another device),
+/// or a negative [`IrpcStatus`]
+///
+/// # Safety
+/// `joint` must be a live joint from [`irpc_joint_new`], `request` must
+/// point to a valid [`IrpcMessage`] and `reply` to writable storage for one.
+#[no_mangle]
+pub unsafe extern "C" fn irpc_joint_handle(
+    joint: *mut IrpcJoint,
+    request: *const IrpcMessage,
+    reply: *mut IrpcMessage,
+) -> i32 {
+    if joint.is_null() || request.is_null() || reply.is_null() {
+        return IrpcStatus::NullPointer as i32;
+    }
+    // SAFETY: valid pointers per the caller's contract
+    let (joint, request) = unsafe { (&mut *joint, Message::from((*request).clone())) };
+    match joint.joint.handle_message(&request) {
+        Some(message) => {
+            // SAFETY: non-null and writable per the caller's contract
+            unsafe { reply.write(message.into()) };
+            1
+        }
+        None => 0,
+    }
+}
+
+/// Decode a received frame, feed it to `joint` and encode any reply into
+/// `tx`; returns the reply length, 0 if there is none, or a negative
+/// [`IrpcStatus`]
+///
+/// # Safety
+/// `joint` must be a live joint from [`irpc_joint_new`], `rx` must point to
+/// `rx_len` readable bytes and `tx` to `tx_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn irpc_joint_handle_frame(
+    joint: *mut IrpcJoint,
+    rx: *const u8,
+    rx_len: usize,
+    tx: *mut u8,
+    tx_len: usize,
+) -> isize {
+    if joint.is_null() || rx.is_null() || tx.is_null() {
+        return IrpcStatus::NullPointer as isize;
+    }
+    // SAFETY: valid pointers per the caller's contract
+    let (joint, rx, tx) = unsafe {
+        (&mut *joint, core::slice::from_raw_parts(rx, rx_len), core::slice::from_raw_parts_mut(tx, tx_len))
+    };
+    let Ok(request) = Message::deserialize(rx) else {
+        return IrpcStatus::Decode as isize;
+    };
+    match joint.joint.handle_message(&request) {
+        Some(reply) if reply.encoded_size() > tx.len() => IrpcStatus::BufferTooSmall as isize,
+        Some(reply) => reply.serialize_into(tx).map_or(IrpcStatus::BufferTooSmall as isize, |n| n as isize),
+        None => 0,
+    }
+}
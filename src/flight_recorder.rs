@@ -0,0 +1,90 @@
+//! Joint flight recorder
+//!
+//! With [`Joint::set_flight_recorder`](crate::Joint::set_flight_recorder)
+//! on, a joint keeps its last `FLIGHT_RECORDER_LEN` commands, replies and
+//! state changes in a ring buffer, so there is something to look at after an
+//! incident. The host reads it back with `RequestFlightRecorder`
+//! (`JointProxy::flight_recorder()` does the chunking), typically once the
+//! joint reported a fault. Entries are stamped with the time of the latest
+//! [`Joint::poll`](crate::Joint::poll) and the buffer lives in RAM, so a
+//! reset clears it.
+//!
+//! Reading the recorder does not record anything, so a dump does not push
+//! out the entries it is reading.
+
+use crate::config::FLIGHT_RECORDER_LEN;
+use crate::protocol::{FlightEvent, FlightRecord, FlightRecorderChunk};
+
+/// Ring buffer of the latest [`FlightRecord`]s
+///
+/// Entries are numbered in the order they were recorded, from 0 and
+/// wrapping at `u32::MAX`; the buffer holds the last `FLIGHT_RECORDER_LEN`.
+#[derive(Debug, Clone)]
+pub struct FlightRecorder {
+    records: [FlightRecord; FLIGHT_RECORDER_LEN],
+    /// Slot the next entry goes to
+    next: usize,
+    /// Entries held
+    len: usize,
+    /// Number of the next entry
+    recorded: u32,
+}
+
+impl Default for FlightRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FlightRecorder {
+    pub const fn new() -> Self {
+        let unused = FlightRecord { timestamp_us: 0, event: FlightEvent::Received { msg_id: 0, payload: 0 } };
+        Self { records: [unused; FLIGHT_RECORDER_LEN], next: 0, len: 0, recorded: 0 }
+    }
+
+    pub fn record(&mut self, timestamp_us: u32, event: FlightEvent) {
+        self.records[self.next] = FlightRecord { timestamp_us, event };
+        self.next = (self.next + 1) % FLIGHT_RECORDER_LEN;
+        self.len = (self.len + 1).min(FLIGHT_RECORDER_LEN);
+        self.recorded = self.recorded.wrapping_add(1);
+    }
+
+    /// Number the next entry will get
+    pub fn recorded(&self) -> u32 {
+        self.recorded
+    }
+
+    /// Number of the oldest entry held
+    pub fn oldest(&self) -> u32 {
+        self.recorded.wrapping_sub(self.len as u32)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Entries held, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = FlightRecord> + '_ {
+        (0..self.len).map(|age| self.records[self.slot(age)])
+    }
+
+    /// Reply to `RequestFlightRecorder { from }`: the entries from number
+    /// `from` on, or from the oldest held if `from` is not between that and
+    /// the next one (it was overwritten)
+    pub fn chunk(&self, from: u32) -> FlightRecorderChunk {
+        let skip = match from.wrapping_sub(self.oldest()) as usize {
+            offset if offset <= self.len => offset,
+            _ => 0,
+        };
+        FlightRecorderChunk::new(self.oldest().wrapping_add(skip as u32), self.iter().skip(skip))
+    }
+
+    /// Slot of the entry recorded `age` entries after the oldest
+    fn slot(&self, age: usize) -> usize {
+        (self.next + FLIGHT_RECORDER_LEN - self.len + age) % FLIGHT_RECORDER_LEN
+    }
+}
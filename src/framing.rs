@@ -0,0 +1,279 @@
+//! Byte-stream framing for UART, SPI and network links
+//!
+//! Links that carry a stream of bytes rather than frames wrap each encoded
+//! message with [`encode_frame`] and pick them out of the stream again with
+//! a [`FrameDecoder`]:
+//!
+//! ```text
+//! COBS( length (u16 BE) | payload | CRC-16/CCITT-FALSE (BE) ) 0x00
+//! ```
+//!
+//! COBS leaves no zero byte in the frame, so the `0x00` delimiter always
+//! marks a frame end: a receiver that starts mid-stream, drops bytes or
+//! overflows resynchronises at the next delimiter. The length and the CRC
+//! (over length and payload) reject frames that were cut short, run
+//! together or corrupted on the way.
+//!
+//! # Example
+//!
+//! ```
+//! use irpc::framing::{encode_message, FrameDecoder, MAX_MESSAGE_FRAME_LEN};
+//! use irpc::{Header, Message, Payload};
+//!
+//! let message = Message {
+//!     header: Header { source_id: 0x0001, target_id: 0x0010, msg_id: 1 },
+//!     payload: Payload::Configure,
+//! };
+//! let mut wire = [0u8; MAX_MESSAGE_FRAME_LEN];
+//! let len = encode_message(&message, &mut wire).unwrap();
+//!
+//! let mut decoder = FrameDecoder::<MAX_MESSAGE_FRAME_LEN>::new();
+//! let (last, rest) = wire[..len].split_last().unwrap();
+//! assert!(rest.iter().all(|&b| decoder.push_message(b).is_none()));
+//! assert_eq!(decoder.push_message(*last).unwrap().unwrap().header.msg_id, 1);
+//! ```
+
+use crate::protocol::{Message, ProtocolError};
+
+/// Ends every frame; never appears inside one
+pub const FRAME_DELIMITER: u8 = 0x00;
+
+/// Length field ahead of the payload
+const LEN_LEN: usize = 2;
+/// CRC behind the payload
+const CRC_LEN: usize = 2;
+/// Longest run COBS encodes behind one code byte
+const MAX_RUN: usize = 254;
+
+/// Bytes on the wire for a payload of `payload_len` bytes, delimiter included
+pub const fn max_encoded_len(payload_len: usize) -> usize {
+    let body = LEN_LEN + payload_len + CRC_LEN;
+    body + body / MAX_RUN + 1 + 1
+}
+
+/// Bytes on the wire for the largest message
+pub const MAX_MESSAGE_FRAME_LEN: usize = max_encoded_len(Message::max_size());
+
+/// Framing errors
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum FramingError {
+    /// The output buffer cannot hold the encoded frame
+    #[error("Buffer too small for the frame")]
+    BufferTooSmall,
+
+    /// A payload longer than the length field can express
+    #[error("Payload too long to frame")]
+    PayloadTooLong,
+
+    /// More bytes arrived without a delimiter than the decoder holds; the
+    /// rest of the frame is skipped
+    #[error("Frame overflows the decoder buffer")]
+    Overflow,
+
+    /// A code byte points past the end of the frame
+    #[error("Invalid COBS encoding")]
+    Encoding,
+
+    /// The length field does not match the frame
+    #[error("Frame length mismatch")]
+    Length,
+
+    /// The CRC does not match the frame
+    #[error("Frame CRC mismatch")]
+    Crc,
+
+    /// The payload is not a valid message
+    #[error("Protocol error: {0}")]
+    Protocol(#[from] ProtocolError),
+}
+
+/// CRC-16/CCITT-FALSE, continued from `crc`
+///
+/// Start from `0xFFFF`. Running it over a payload followed by its CRC
+/// (big-endian) yields zero.
+pub const fn crc16(mut crc: u16, data: &[u8]) -> u16 {
+    let mut i = 0;
+    while i < data.len() {
+        crc ^= (data[i] as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+            bit += 1;
+        }
+        i += 1;
+    }
+    crc
+}
+
+/// COBS encoder writing one frame into a buffer known to be large enough
+struct CobsWriter<'a> {
+    out: &'a mut [u8],
+    /// Where the code byte of the current run goes
+    code_at: usize,
+    pos: usize,
+}
+
+impl<'a> CobsWriter<'a> {
+    fn new(out: &'a mut [u8]) -> Self {
+        Self { out, code_at: 0, pos: 1 }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if byte == 0 {
+                self.end_run();
+                continue;
+            }
+            self.out[self.pos] = byte;
+            self.pos += 1;
+            if self.pos - self.code_at == MAX_RUN + 1 {
+                self.end_run();
+            }
+        }
+    }
+
+    fn end_run(&mut self) {
+        self.out[self.code_at] = (self.pos - self.code_at) as u8;
+        self.code_at = self.pos;
+        self.pos += 1;
+    }
+
+    /// Close the last run and append the delimiter; the frame length
+    fn finish(self) -> usize {
+        self.out[self.code_at] = (self.pos - self.code_at) as u8;
+        self.out[self.pos] = FRAME_DELIMITER;
+        self.pos + 1
+    }
+}
+
+/// Frame `payload` into `out`, delimiter included; the bytes written
+///
+/// `out` must hold [`max_encoded_len`] of the payload length.
+pub fn encode_frame(payload: &[u8], out: &mut [u8]) -> Result<usize, FramingError> {
+    let len = u16::try_from(payload.len()).map_err(|_| FramingError::PayloadTooLong)?;
+    if out.len() < max_encoded_len(payload.len()) {
+        return Err(FramingError::BufferTooSmall);
+    }
+    let len = len.to_be_bytes();
+    let crc = crc16(crc16(0xFFFF, &len), payload).to_be_bytes();
+
+    let mut writer = CobsWriter::new(out);
+    writer.write(&len);
+    writer.write(payload);
+    writer.write(&crc);
+    Ok(writer.finish())
+}
+
+/// Serialize `message` and frame it into `out`; the bytes written
+///
+/// An `out` of [`MAX_MESSAGE_FRAME_LEN`] bytes holds any message.
+pub fn encode_message(message: &Message, out: &mut [u8]) -> Result<usize, FramingError> {
+    let mut payload = [0u8; Message::max_size()];
+    let len = message.serialize_into(&mut payload)?;
+    encode_frame(&payload[..len], out)
+}
+
+/// Decode a frame received without its delimiter, in place; the payload
+///
+/// For links that deliver whole frames (e.g. an SPI transaction); stream
+/// links use a [`FrameDecoder`].
+pub fn decode_frame(frame: &mut [u8]) -> Result<&[u8], FramingError> {
+    // Decoded bytes never overtake the encoded ones still to be read
+    let (mut read, mut write) = (0, 0);
+    while read < frame.len() {
+        let code = frame[read] as usize;
+        if code == 0 || read + code > frame.len() {
+            return Err(FramingError::Encoding);
+        }
+        frame.copy_within(read + 1..read + code, write);
+        write += code - 1;
+        read += code;
+        // Every run but a full one and the last ended in a zero
+        if code != MAX_RUN + 1 && read < frame.len() {
+            frame[write] = 0;
+            write += 1;
+        }
+    }
+
+    let body = &frame[..write];
+    if body.len() < LEN_LEN + CRC_LEN {
+        return Err(FramingError::Length);
+    }
+    let len = u16::from_be_bytes([body[0], body[1]]) as usize;
+    if len != body.len() - LEN_LEN - CRC_LEN {
+        return Err(FramingError::Length);
+    }
+    if crc16(0xFFFF, body) != 0 {
+        return Err(FramingError::Crc);
+    }
+    Ok(&body[LEN_LEN..LEN_LEN + len])
+}
+
+/// Picks frames out of a byte stream
+///
+/// `N` bounds the encoded frame, without its delimiter;
+/// [`MAX_MESSAGE_FRAME_LEN`] holds any message. Bytes before the first
+/// delimiter belong to a frame whose start was missed and are rejected
+/// with it.
+#[derive(Debug)]
+pub struct FrameDecoder<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    /// The current frame overflowed; skip to the next delimiter
+    skipping: bool,
+}
+
+impl<const N: usize> FrameDecoder<N> {
+    pub const fn new() -> Self {
+        Self { buf: [0; N], len: 0, skipping: false }
+    }
+
+    /// Take one received byte; the payload of the frame it completes
+    ///
+    /// Empty frames (back-to-back delimiters, e.g. line idle fill) are
+    /// skipped. A frame that overflows the buffer is reported once, when
+    /// it overflows.
+    pub fn push(&mut self, byte: u8) -> Option<Result<&[u8], FramingError>> {
+        if byte != FRAME_DELIMITER {
+            if self.skipping {
+                return None;
+            }
+            if self.len == N {
+                self.len = 0;
+                self.skipping = true;
+                return Some(Err(FramingError::Overflow));
+            }
+            self.buf[self.len] = byte;
+            self.len += 1;
+            return None;
+        }
+
+        let len = core::mem::take(&mut self.len);
+        if core::mem::take(&mut self.skipping) || len == 0 {
+            return None;
+        }
+        Some(decode_frame(&mut self.buf[..len]))
+    }
+
+    /// Take one received byte; the message of the frame it completes
+    pub fn push_message(&mut self, byte: u8) -> Option<Result<Message, FramingError>> {
+        self.push(byte).map(|frame| Ok(Message::deserialize(frame?)?))
+    }
+
+    /// Bytes of a frame still waiting for its delimiter
+    pub fn pending(&self) -> usize {
+        self.len
+    }
+
+    /// Drop a partly received frame, e.g. after the link was reset
+    pub fn reset(&mut self) {
+        self.len = 0;
+        self.skipping = false;
+    }
+}
+
+impl<const N: usize> Default for FrameDecoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,156 @@
+//! Named groups of joints
+//!
+//! Systems with more than one mechanism (two arms, an arm and a gripper)
+//! rarely want every joint handled at once. A [`JointGroup`] names a set of
+//! the orchestrator's joints and runs lifecycle commands, targets and status
+//! queries on all of them concurrently:
+//!
+//! ```no_run
+//! # async fn example(mut arm: irpc::ArmOrchestrator) -> Result<(), irpc::group::GroupError> {
+//! let wrist = arm.create_group("wrist", [0x0030, 0x0040])?.clone();
+//! wrist.configure().await?;
+//! wrist.activate().await?;
+//! wrist.set_targets(&[15.0, -30.0], 45.0).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Every joint of a group gets its command before any reply is awaited, so a
+//! group takes as long as its slowest joint. The first failure in group order
+//! is reported once all joints have answered.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::arm::{fan_out, JointProxy};
+use crate::config::TARGET_BATCH_MAX_JOINTS;
+use crate::protocol::{BatchTarget, DeviceId, LifecycleState, ProtocolError, SetTargetPayload, TargetBatch};
+
+/// Joint group errors
+#[derive(Error, Debug)]
+pub enum GroupError {
+    #[error("Joint {0} is not part of the arm")]
+    UnknownJoint(DeviceId),
+    #[error("Joint {0} is listed twice")]
+    DuplicateJoint(DeviceId),
+    #[error("{actual} targets for a group of {expected} joints")]
+    TargetCount { expected: usize, actual: usize },
+    #[error("Joint {joint_id} failed: {error}")]
+    Joint { joint_id: DeviceId, error: ProtocolError },
+}
+
+/// A named set of joints commanded together
+///
+/// Created with [`ArmOrchestrator::create_group`](crate::ArmOrchestrator::create_group);
+/// covers the joint proxies at the time it was created.
+#[derive(Clone)]
+pub struct JointGroup {
+    name: String,
+    joints: Vec<JointProxy>,
+}
+
+impl JointGroup {
+    pub(crate) fn new(name: String, joints: Vec<JointProxy>) -> Self {
+        Self { name, joints }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Joint IDs in group order
+    pub fn joint_ids(&self) -> Vec<DeviceId> {
+        self.joints.iter().map(JointProxy::id).collect()
+    }
+
+    pub fn contains(&self, joint_id: DeviceId) -> bool {
+        self.joints.iter().any(|joint| joint.id() == joint_id)
+    }
+
+    /// Configure every joint of the group
+    pub async fn configure(&self) -> Result<(), GroupError> {
+        self.each(|joint| async move { joint.configure().await }).await
+    }
+
+    /// Activate every joint of the group
+    pub async fn activate(&self) -> Result<(), GroupError> {
+        self.each(|joint| async move { joint.activate().await }).await
+    }
+
+    /// Deactivate every joint of the group
+    pub async fn deactivate(&self) -> Result<(), GroupError> {
+        self.each(|joint| async move { joint.deactivate().await }).await
+    }
+
+    /// Send every joint its target angle in degrees, in group order, at most
+    /// `velocity_limit` degrees/second
+    pub async fn set_targets(&self, angles: &[f32], velocity_limit: f32) -> Result<(), GroupError> {
+        if angles.len() != self.joints.len() {
+            return Err(GroupError::TargetCount { expected: self.joints.len(), actual: angles.len() });
+        }
+        let mut targets = angles.iter().copied();
+        self.each(|joint| {
+            let angle = targets.next().unwrap_or_default();
+            async move { joint.set_target(angle, velocity_limit).await }
+        })
+        .await
+    }
+
+    /// [`set_targets`](Self::set_targets) in `SetTargetBatch` frames
+    ///
+    /// Each frame carries the targets of up to `TARGET_BATCH_MAX_JOINTS`
+    /// joints and goes to the first of them, which acknowledges it, so the
+    /// joints of a frame take their targets at the same time.
+    pub async fn set_targets_batched(&self, angles: &[f32], velocity_limit: f32) -> Result<(), GroupError> {
+        if angles.len() != self.joints.len() {
+            return Err(GroupError::TargetCount { expected: self.joints.len(), actual: angles.len() });
+        }
+        let targets: Vec<BatchTarget> = self
+            .joints
+            .iter()
+            .zip(angles)
+            .map(|(joint, &target_angle)| BatchTarget {
+                joint_id: joint.id(),
+                target: SetTargetPayload { target_angle, velocity_limit },
+            })
+            .collect();
+        let mut batches = targets.chunks(TARGET_BATCH_MAX_JOINTS).filter_map(TargetBatch::new);
+        let masters = self.joints.iter().step_by(TARGET_BATCH_MAX_JOINTS);
+        let outcomes = fan_out(masters, |master| {
+            let batch = batches.next();
+            async move {
+                match batch {
+                    Some(batch) => master.send_batch(batch).await,
+                    None => Ok(()),
+                }
+            }
+        })
+        .await;
+        for (_, result) in outcomes {
+            result.map_err(|(joint_id, error)| GroupError::Joint { joint_id, error })?;
+        }
+        Ok(())
+    }
+
+    /// Cached lifecycle state of every joint of the group
+    pub async fn status(&self) -> HashMap<DeviceId, LifecycleState> {
+        let mut status = HashMap::with_capacity(self.joints.len());
+        for joint in &self.joints {
+            status.insert(joint.id(), joint.get_state().await);
+        }
+        status
+    }
+
+    /// Run `command` on all joints at once; the first failure in group order
+    async fn each<F, Fut>(&self, command: F) -> Result<(), GroupError>
+    where
+        F: FnMut(JointProxy) -> Fut,
+        Fut: std::future::Future<Output = Result<(), ProtocolError>> + Send + 'static,
+    {
+        for (joint_id, result) in fan_out(&self.joints, command).await {
+            result.map_err(|error| GroupError::Joint { joint_id, error })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,278 @@
+//! gRPC bridge for the arm API
+//!
+//! [`ArmService`] exposes an [`ArmOrchestrator`] as the `irpc.v1.Arm` gRPC
+//! service defined in `proto/irpc.proto` (status, configure, activate,
+//! set_target and telemetry streaming), so dashboards and test scripts in
+//! any language can drive the arm. The protobuf messages mirror the payload
+//! types in [`protocol`](crate::protocol); the generated code lives in
+//! [`proto`].
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), tonic::transport::Error> {
+//! use irpc::grpc::ArmService;
+//! use irpc::ArmOrchestrator;
+//!
+//! let mut arm = ArmOrchestrator::new();
+//! arm.add_joint(0x0010);
+//! ArmService::new(arm).serve("0.0.0.0:50051".parse().unwrap()).await
+//! # }
+//! ```
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, RwLock};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status};
+use tracing::{debug, info};
+
+use crate::arm::{ArmOrchestrator, JointProxy, TelemetryFilter, TelemetrySample, TelemetryTopic};
+use crate::protocol::{AdaptiveStatusPayload, DeviceId, LifecycleState, Payload, ProtocolError, StallStatus, TelemetryStream};
+
+/// Code generated from `proto/irpc.proto`
+#[allow(missing_docs, clippy::all)]
+pub mod proto {
+    tonic::include_proto!("irpc.v1");
+}
+
+use proto::arm_server::{Arm, ArmServer};
+
+/// Samples buffered per gRPC telemetry stream before the forwarder waits
+const STREAM_BUFFER: usize = 64;
+
+/// `irpc.v1.Arm` service backed by an orchestrator
+#[derive(Clone)]
+pub struct ArmService {
+    orchestrator: Arc<RwLock<ArmOrchestrator>>,
+}
+
+impl ArmService {
+    /// Serve `orchestrator`, which should already know its joints
+    pub fn new(orchestrator: ArmOrchestrator) -> Self {
+        Self::shared(Arc::new(RwLock::new(orchestrator)))
+    }
+
+    /// Serve an orchestrator that the application keeps using as well
+    pub fn shared(orchestrator: Arc<RwLock<ArmOrchestrator>>) -> Self {
+        Self { orchestrator }
+    }
+
+    /// The service, ready to add to a `tonic::transport::Server`
+    pub fn into_server(self) -> ArmServer<Self> {
+        ArmServer::new(self)
+    }
+
+    /// Serve on `addr` until the server fails
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+        info!("gRPC arm service listening on {}", addr);
+        tonic::transport::Server::builder()
+            .add_service(self.into_server())
+            .serve(addr)
+            .await
+    }
+}
+
+/// Joint addressed by a request, or `NOT_FOUND`
+fn joint(orchestrator: &ArmOrchestrator, joint_id: u32) -> Result<&JointProxy, Status> {
+    DeviceId::try_from(joint_id)
+        .ok()
+        .and_then(|id| orchestrator.get_joint(id))
+        .ok_or_else(|| Status::not_found(format!("no joint {}", joint_id)))
+}
+
+/// Map a protocol failure to the closest gRPC status
+fn status(error: ProtocolError) -> Status {
+    let message = error.to_string();
+    match error {
+        ProtocolError::Timeout => Status::deadline_exceeded(message),
+        ProtocolError::Interlocked
+        | ProtocolError::SafetyViolation
+        | ProtocolError::InvalidStateTransition
+        | ProtocolError::SchemaMismatch { .. } => Status::failed_precondition(message),
+        ProtocolError::Busy | ProtocolError::QueueFull => Status::unavailable(message),
+        ProtocolError::Superseded | ProtocolError::Cancelled => Status::aborted(message),
+        ProtocolError::PayloadTooLarge { .. } => Status::invalid_argument(message),
+        ProtocolError::Unsupported => Status::unimplemented(message),
+        _ => Status::internal(message),
+    }
+}
+
+#[tonic::async_trait]
+impl Arm for ArmService {
+    async fn get_status(&self, _request: Request<proto::StatusRequest>) -> Result<Response<proto::SystemStatus>, Status> {
+        let snapshot = self.orchestrator.read().await.snapshot().await;
+        let mut joints: Vec<_> = snapshot
+            .joints
+            .into_iter()
+            .map(|(joint_id, state)| proto::JointState {
+                joint_id: joint_id.into(),
+                state: proto::LifecycleState::from(state).into(),
+            })
+            .collect();
+        joints.sort_by_key(|joint| joint.joint_id);
+        let interlocks = snapshot
+            .interlocks
+            .into_iter()
+            .map(|status| proto::Interlock {
+                active: status.is_active(),
+                name: status.config.name,
+                source: status.config.source.into(),
+                channel: status.config.channel.into(),
+            })
+            .collect();
+        Ok(Response::new(proto::SystemStatus { joints, interlocks }))
+    }
+
+    async fn configure(&self, request: Request<proto::JointRequest>) -> Result<Response<proto::CommandReply>, Status> {
+        match request.into_inner().joint_id {
+            Some(joint_id) => joint(&*self.orchestrator.read().await, joint_id)?.configure().await,
+            None => self.orchestrator.write().await.configure_all().await.into_result().map_err(ProtocolError::from),
+        }
+        .map_err(status)?;
+        Ok(Response::new(proto::CommandReply {}))
+    }
+
+    async fn activate(&self, request: Request<proto::JointRequest>) -> Result<Response<proto::CommandReply>, Status> {
+        match request.into_inner().joint_id {
+            Some(joint_id) => joint(&*self.orchestrator.read().await, joint_id)?.activate().await,
+            None => self.orchestrator.write().await.activate_all().await.into_result().map_err(ProtocolError::from),
+        }
+        .map_err(status)?;
+        Ok(Response::new(proto::CommandReply {}))
+    }
+
+    async fn set_target(&self, request: Request<proto::SetTargetRequest>) -> Result<Response<proto::CommandReply>, Status> {
+        let request = request.into_inner();
+        let target = request.target.ok_or_else(|| Status::invalid_argument("missing target"))?;
+        let orchestrator = self.orchestrator.read().await;
+        joint(&orchestrator, request.joint_id)?
+            .set_target(target.target_angle, target.velocity_limit)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(proto::CommandReply {}))
+    }
+
+    type StreamTelemetryStream = Pin<Box<dyn Stream<Item = Result<proto::TelemetrySample, Status>> + Send>>;
+
+    async fn stream_telemetry(
+        &self,
+        request: Request<proto::TelemetryRequest>,
+    ) -> Result<Response<Self::StreamTelemetryStream>, Status> {
+        let request = request.into_inner();
+        let joints = request
+            .joint_ids
+            .iter()
+            .map(|&id| DeviceId::try_from(id).map_err(|_| Status::invalid_argument(format!("no joint {}", id))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let topics = request
+            .topics()
+            .map(|topic| match topic {
+                proto::TelemetryTopic::Motion => Ok(TelemetryTopic::Motion),
+                proto::TelemetryTopic::Adaptive => Ok(TelemetryTopic::Adaptive),
+                proto::TelemetryTopic::Status => Ok(TelemetryTopic::Status),
+                proto::TelemetryTopic::Unspecified => Err(Status::invalid_argument("unspecified topic")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let filter = TelemetryFilter {
+            joints: (!joints.is_empty()).then_some(joints),
+            topics: (!topics.is_empty()).then_some(topics),
+            snapshot: request.snapshot,
+        };
+
+        let mut subscriber = self.orchestrator.read().await.subscribe_telemetry(filter);
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            while let Some(sample) = subscriber.recv().await {
+                if tx.send(Ok(proto::TelemetrySample::from(sample))).await.is_err() {
+                    break;
+                }
+            }
+            debug!("gRPC telemetry stream closed");
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+}
+
+impl From<LifecycleState> for proto::LifecycleState {
+    fn from(state: LifecycleState) -> Self {
+        match state {
+            LifecycleState::Unconfigured => Self::Unconfigured,
+            LifecycleState::Inactive => Self::Inactive,
+            LifecycleState::Active => Self::Active,
+            LifecycleState::Calibrating => Self::Calibrating,
+            LifecycleState::Error => Self::Error,
+        }
+    }
+}
+
+impl From<TelemetryStream> for proto::TelemetryStream {
+    fn from(stream: TelemetryStream) -> Self {
+        Self {
+            timestamp_us: stream.timestamp_us,
+            position: stream.position,
+            velocity: stream.velocity,
+            acceleration: stream.acceleration,
+            current_d: stream.current_d,
+            current_q: stream.current_q,
+            voltage_d: stream.voltage_d,
+            voltage_q: stream.voltage_q,
+            torque_estimate: stream.torque_estimate,
+            power: stream.power,
+            load_percent: stream.load_percent,
+            foc_loop_time_us: stream.foc_loop_time_us.into(),
+            temperature_c: stream.temperature_c,
+            warnings: stream.warnings.bits().into(),
+            trajectory_active: stream.trajectory_active,
+            brake_engaged: stream.brake_engaged,
+        }
+    }
+}
+
+impl From<AdaptiveStatusPayload> for proto::AdaptiveStatusPayload {
+    fn from(status: AdaptiveStatusPayload) -> Self {
+        let stall_status = match status.stall_status {
+            StallStatus::Normal => proto::StallStatus::Normal,
+            StallStatus::Warning => proto::StallStatus::Warning,
+            StallStatus::Stalled => proto::StallStatus::Stalled,
+        };
+        Self {
+            load_percent: status.load_percent,
+            current_scale: status.current_scale,
+            coolstep_enabled: status.coolstep_enabled,
+            power_savings_percent: status.power_savings_percent,
+            energy_saved_wh: status.energy_saved_wh,
+            velocity_scale: status.velocity_scale,
+            dcstep_enabled: status.dcstep_enabled,
+            dcstep_derating: status.dcstep_derating,
+            stall_status: stall_status.into(),
+            stallguard_enabled: status.stallguard_enabled,
+            stall_confidence: status.stall_confidence,
+        }
+    }
+}
+
+impl From<TelemetrySample> for proto::TelemetrySample {
+    fn from(sample: TelemetrySample) -> Self {
+        use proto::telemetry_sample::Payload as Sample;
+
+        let payload = match sample.message.payload {
+            Payload::TelemetryStream(stream) => Some(Sample::Motion(stream.into())),
+            Payload::AdaptiveStatus(status) => Some(Sample::Adaptive(status.into())),
+            Payload::JointStatus { state, error_code } => Some(Sample::Status(proto::JointStatus {
+                state: proto::LifecycleState::from(state).into(),
+                error_code: error_code.into(),
+            })),
+            // Only telemetry topics reach subscribers
+            _ => None,
+        };
+        Self {
+            joint_id: sample.joint_id.into(),
+            joint_name: sample.joint_name.as_deref().unwrap_or_default().to_owned(),
+            received_at_us: sample.received_at.as_micros() as u64,
+            snapshot: sample.snapshot,
+            payload,
+        }
+    }
+}
@@ -0,0 +1,419 @@
+//! Hardware-in-the-loop conformance suite
+//!
+//! Vendors building joints against iRPC check their firmware with a
+//! [`HilSuite`]: it drives one real joint, over whatever adapter the
+//! [`CommunicationManager`] was built with, through a scripted set of checks
+//! and collects a pass/fail [`HilReport`]:
+//!
+//! ```no_run
+//! use irpc::bus::net::NetworkAdapter;
+//! use irpc::hil::{HilConfig, HilSuite};
+//! use irpc::CommunicationManager;
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let adapter = Arc::new(NetworkAdapter::tcp_connect("10.0.0.2:47101".parse()?));
+//! let comm = CommunicationManager::with_adapter(adapter);
+//! let report = HilSuite::new(comm, HilConfig::new(0x0010)).run().await;
+//! println!("{}", report);
+//! assert!(report.passed());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The checks run in order, each starting from a `Reset` joint:
+//!
+//! - **discovery**: the joint answers `Discover` with the local `SCHEMA_HASH`
+//! - **lifecycle**: `Configure`, `Activate`, `Deactivate` and `Reset` are
+//!   acknowledged and `RequestStatus` reports the state each one leads to
+//! - **invalid transitions**: commands the current state does not allow, and
+//!   commands the joint does not know, are refused with a `Nack` and leave
+//!   the state alone
+//! - **telemetry rate**: once asked for periodic telemetry at
+//!   [`telemetry_rate_hz`](HilConfig::telemetry_rate_hz), the joint streams
+//!   within [`rate_tolerance`](HilConfig::rate_tolerance) of that rate
+//! - **timeouts**: every reply came within
+//!   [`response_timeout`](HilConfig::response_timeout), the joint stays quiet
+//!   for commands addressed to another node, and a request nobody answers
+//!   times out on the host without leaving its correlation behind
+//!
+//! The joint is left `Unconfigured`. It is expected to be free to move: the
+//! telemetry check activates it, although without a target.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::info;
+
+use crate::arm::{CommunicationManager, TelemetryFilter, TelemetryTopic};
+use crate::clock::timeout;
+use crate::config::ERROR_UNKNOWN_COMMAND;
+use crate::protocol::{
+    ConfigureTelemetryPayload, DeviceId, LifecycleState, Payload, ProtocolError, TelemetryMode, SCHEMA_HASH,
+};
+
+/// How the suite talks to the joint under test
+#[derive(Debug, Clone)]
+pub struct HilConfig {
+    /// Joint under test
+    pub joint_id: DeviceId,
+    /// A node ID nothing on the bus answers to, for the timeout check
+    pub absent_id: DeviceId,
+    /// Longest a reply may take
+    pub response_timeout: Duration,
+    /// Periodic telemetry rate to ask for
+    pub telemetry_rate_hz: u16,
+    /// How long to count telemetry samples for
+    pub telemetry_window: Duration,
+    /// Largest accepted deviation of the measured telemetry rate, as a
+    /// fraction of the requested one
+    pub rate_tolerance: f32,
+}
+
+impl HilConfig {
+    /// Defaults for `joint_id`: replies within 100 ms, and 100 Hz telemetry
+    /// counted for 2 s to within 5%
+    pub fn new(joint_id: DeviceId) -> Self {
+        Self {
+            joint_id,
+            absent_id: 0x07FE,
+            response_timeout: Duration::from_millis(100),
+            telemetry_rate_hz: 100,
+            telemetry_window: Duration::from_secs(2),
+            rate_tolerance: 0.05,
+        }
+    }
+}
+
+/// How one check went
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckOutcome {
+    Passed,
+    /// The joint did not conform; what it did instead
+    Failed(String),
+}
+
+/// One check of a [`HilReport`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub outcome: CheckOutcome,
+    /// Time the check took, on the communication manager's clock
+    pub elapsed: Duration,
+}
+
+impl CheckResult {
+    pub fn passed(&self) -> bool {
+        self.outcome == CheckOutcome::Passed
+    }
+}
+
+/// Outcome of a [`HilSuite`] run
+#[derive(Debug, Clone, PartialEq)]
+pub struct HilReport {
+    pub joint_id: DeviceId,
+    pub checks: Vec<CheckResult>,
+    /// Slowest reply seen during the run
+    pub max_response_time: Duration,
+    /// Telemetry rate measured by the telemetry check, in Hz
+    pub telemetry_rate_hz: Option<f32>,
+}
+
+impl HilReport {
+    /// Whether every check passed
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(CheckResult::passed)
+    }
+
+    /// The checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.checks.iter().filter(|check| !check.passed())
+    }
+
+    /// The check called `name`
+    pub fn check(&self, name: &str) -> Option<&CheckResult> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+}
+
+impl core::fmt::Display for HilReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "iRPC conformance report for joint {:#06x}", self.joint_id)?;
+        for check in &self.checks {
+            let elapsed_ms = check.elapsed.as_secs_f64() * 1e3;
+            match &check.outcome {
+                CheckOutcome::Passed => writeln!(f, "  PASS  {:<20} {:>9.1} ms", check.name, elapsed_ms)?,
+                CheckOutcome::Failed(reason) => {
+                    writeln!(f, "  FAIL  {:<20} {:>9.1} ms  {}", check.name, elapsed_ms, reason)?
+                }
+            }
+        }
+        writeln!(f, "  slowest reply: {:.1} ms", self.max_response_time.as_secs_f64() * 1e3)?;
+        if let Some(rate) = self.telemetry_rate_hz {
+            writeln!(f, "  telemetry rate: {:.1} Hz", rate)?;
+        }
+        let failed = self.failures().count();
+        match failed {
+            0 => write!(f, "PASSED: {} checks", self.checks.len()),
+            _ => write!(f, "FAILED: {} of {} checks", failed, self.checks.len()),
+        }
+    }
+}
+
+/// Why a check failed
+type CheckError = String;
+
+/// The checks, in the order they run
+#[derive(Debug, Clone, Copy)]
+enum Check {
+    Discovery,
+    Lifecycle,
+    InvalidTransitions,
+    TelemetryRate,
+    Timeouts,
+}
+
+impl Check {
+    const ALL: [Check; 5] =
+        [Check::Discovery, Check::Lifecycle, Check::InvalidTransitions, Check::TelemetryRate, Check::Timeouts];
+
+    fn name(self) -> &'static str {
+        match self {
+            Check::Discovery => "discovery",
+            Check::Lifecycle => "lifecycle",
+            Check::InvalidTransitions => "invalid transitions",
+            Check::TelemetryRate => "telemetry rate",
+            Check::Timeouts => "timeouts",
+        }
+    }
+}
+
+/// Drives a joint through the conformance checks
+pub struct HilSuite {
+    comm: Arc<CommunicationManager>,
+    config: HilConfig,
+    max_response_time: Duration,
+    telemetry_rate_hz: Option<f32>,
+}
+
+impl HilSuite {
+    pub fn new(comm: Arc<CommunicationManager>, config: HilConfig) -> Self {
+        Self { comm, config, max_response_time: Duration::ZERO, telemetry_rate_hz: None }
+    }
+
+    /// Run every check and report on them
+    pub async fn run(mut self) -> HilReport {
+        let mut checks = Vec::new();
+        for check in Check::ALL {
+            let started = self.comm.clock().now();
+            let outcome = match self.run_check(check).await {
+                Ok(()) => CheckOutcome::Passed,
+                Err(reason) => CheckOutcome::Failed(reason),
+            };
+            let elapsed = self.comm.clock().now().saturating_sub(started);
+            info!("HIL check {}: {:?}", check.name(), outcome);
+            checks.push(CheckResult { name: check.name(), outcome, elapsed });
+        }
+        // Leave the joint as it was found on the bench
+        let _ = self.command(Payload::Reset).await;
+
+        HilReport {
+            joint_id: self.config.joint_id,
+            checks,
+            max_response_time: self.max_response_time,
+            telemetry_rate_hz: self.telemetry_rate_hz,
+        }
+    }
+
+    async fn run_check(&mut self, check: Check) -> Result<(), CheckError> {
+        match check {
+            Check::Discovery => self.discovery().await,
+            Check::Lifecycle => self.lifecycle().await,
+            Check::InvalidTransitions => self.invalid_transitions().await,
+            Check::TelemetryRate => self.telemetry_rate().await,
+            Check::Timeouts => self.timeouts().await,
+        }
+    }
+
+    /// Send `payload` to the joint under test; its reply
+    async fn request(&mut self, payload: Payload) -> Result<Payload, CheckError> {
+        let name = payload.name();
+        let clock = self.comm.clock();
+        let sent_at = clock.now();
+        let request = self.comm.send_request(self.config.joint_id, payload).await.map_err(|e| {
+            format!("{} not sent: {}", name, e)
+        })?;
+        let reply = match request.response(self.config.response_timeout).await {
+            Ok(reply) => reply,
+            Err(ProtocolError::Timeout) => {
+                return Err(format!("no reply to {} within {:?}", name, self.config.response_timeout))
+            }
+            Err(e) => return Err(format!("{} failed: {}", name, e)),
+        };
+        self.max_response_time = self.max_response_time.max(clock.now().saturating_sub(sent_at));
+        Ok(reply.payload)
+    }
+
+    /// Send a command the joint must acknowledge
+    async fn command(&mut self, payload: Payload) -> Result<(), CheckError> {
+        let name = payload.name();
+        match self.request(payload).await? {
+            Payload::Ack(_) => Ok(()),
+            Payload::Nack { error, .. } => Err(format!("{} refused with error {}", name, error)),
+            other => Err(format!("{} answered with {}", name, other.name())),
+        }
+    }
+
+    /// Send a command the joint must refuse
+    async fn refused(&mut self, payload: Payload, expected_error: Option<u16>) -> Result<(), CheckError> {
+        let name = payload.name();
+        match self.request(payload).await? {
+            Payload::Nack { error, .. } if expected_error.is_none_or(|expected| expected == error) => Ok(()),
+            Payload::Nack { error, .. } => {
+                Err(format!("{} refused with error {}, expected {}", name, error, expected_error.unwrap_or_default()))
+            }
+            other => Err(format!("{} answered with {} instead of a Nack", name, other.name())),
+        }
+    }
+
+    /// Check the state the joint reports
+    async fn expect_state(&mut self, expected: LifecycleState) -> Result<(), CheckError> {
+        match self.request(Payload::RequestStatus).await? {
+            Payload::JointStatus { state, .. } if state == expected => Ok(()),
+            Payload::JointStatus { state, .. } => Err(format!("joint is {:?}, expected {:?}", state, expected)),
+            other => Err(format!("RequestStatus answered with {}", other.name())),
+        }
+    }
+
+    async fn reset(&mut self) -> Result<(), CheckError> {
+        self.command(Payload::Reset).await?;
+        self.expect_state(LifecycleState::Unconfigured).await
+    }
+
+    async fn discovery(&mut self) -> Result<(), CheckError> {
+        let devices = self.comm.discover().await.map_err(|e| format!("discovery failed: {}", e))?;
+        let Some(device) = devices.iter().find(|device| device.id == self.config.joint_id) else {
+            return Err("joint did not answer Discover".to_string());
+        };
+        if device.schema != SCHEMA_HASH {
+            return Err(format!("joint schema {:#010x}, host schema {:#010x}", device.schema, SCHEMA_HASH));
+        }
+        Ok(())
+    }
+
+    async fn lifecycle(&mut self) -> Result<(), CheckError> {
+        self.reset().await?;
+        let steps = [
+            (Payload::Configure, LifecycleState::Inactive),
+            (Payload::Activate, LifecycleState::Active),
+            (Payload::Deactivate, LifecycleState::Inactive),
+            (Payload::Activate, LifecycleState::Active),
+            (Payload::Reset, LifecycleState::Unconfigured),
+        ];
+        for (payload, state) in steps {
+            self.command(payload).await?;
+            self.expect_state(state).await?;
+        }
+        Ok(())
+    }
+
+    async fn invalid_transitions(&mut self) -> Result<(), CheckError> {
+        self.reset().await?;
+        for payload in [Payload::Activate, Payload::Deactivate] {
+            self.refused(payload, None).await?;
+            self.expect_state(LifecycleState::Unconfigured).await?;
+        }
+        self.command(Payload::Configure).await?;
+        for payload in [Payload::Configure, Payload::Deactivate] {
+            self.refused(payload, None).await?;
+            self.expect_state(LifecycleState::Inactive).await?;
+        }
+        // Joint-to-arm traffic is nothing a joint takes as a command
+        self.refused(Payload::ArmReady, Some(ERROR_UNKNOWN_COMMAND)).await?;
+        self.expect_state(LifecycleState::Inactive).await?;
+        self.reset().await
+    }
+
+    async fn telemetry_rate(&mut self) -> Result<(), CheckError> {
+        let rate_hz = self.config.telemetry_rate_hz;
+        self.reset().await?;
+        self.command(Payload::Configure).await?;
+        self.command(Payload::ConfigureTelemetry(ConfigureTelemetryPayload {
+            mode: TelemetryMode::Periodic,
+            rate_hz,
+            change_threshold: 0.0,
+            phase_offset_us: 0,
+        }))
+        .await?;
+        self.command(Payload::Activate).await?;
+
+        let mut subscriber = self.comm.subscribe_telemetry(TelemetryFilter {
+            joints: Some(vec![self.config.joint_id]),
+            topics: Some(vec![TelemetryTopic::Motion]),
+            snapshot: false,
+        });
+        // Count the intervals between samples, so the window needs no
+        // alignment with the joint's period
+        let clock = self.comm.clock();
+        let deadline = clock.now() + self.config.telemetry_window;
+        let (mut first, mut last, mut samples) = (None, None, 0u32);
+        loop {
+            let remaining = deadline.saturating_sub(clock.now());
+            if remaining.is_zero() {
+                break;
+            }
+            match timeout(clock.as_ref(), remaining, subscriber.recv()).await {
+                Ok(Some(sample)) => {
+                    first.get_or_insert(sample.received_at);
+                    last = Some(sample.received_at);
+                    samples += 1;
+                }
+                Ok(None) | Err(_) => break,
+            }
+        }
+        self.reset().await?;
+
+        let (Some(first), Some(last)) = (first, last) else {
+            return Err(format!("no telemetry within {:?}", self.config.telemetry_window));
+        };
+        if samples < 2 || last <= first {
+            return Err(format!("{} telemetry sample(s) within {:?}", samples, self.config.telemetry_window));
+        }
+        let measured = (samples - 1) as f32 / (last - first).as_secs_f32();
+        self.telemetry_rate_hz = Some(measured);
+        let deviation = (measured - rate_hz as f32).abs() / rate_hz as f32;
+        if deviation > self.config.rate_tolerance {
+            return Err(format!("telemetry at {:.1} Hz, {} Hz requested", measured, rate_hz));
+        }
+        Ok(())
+    }
+
+    async fn timeouts(&mut self) -> Result<(), CheckError> {
+        if self.max_response_time > self.config.response_timeout {
+            return Err(format!(
+                "slowest reply took {:?}, limit {:?}",
+                self.max_response_time, self.config.response_timeout
+            ));
+        }
+
+        // Nobody, the joint under test included, answers for an absent node
+        let absent_id = self.config.absent_id;
+        let request = self
+            .comm
+            .send_request(absent_id, Payload::RequestStatus)
+            .await
+            .map_err(|e| format!("RequestStatus not sent: {}", e))?;
+        match request.response(self.config.response_timeout).await {
+            Err(ProtocolError::Timeout) => {}
+            Ok(reply) => {
+                return Err(format!("node {:#06x} answered for {:#06x}", reply.header.source_id, absent_id));
+            }
+            Err(e) => return Err(format!("request to {:#06x} failed: {}", absent_id, e)),
+        }
+        if self.comm.pending_requests().await != 0 {
+            return Err("a timed out request is still pending".to_string());
+        }
+        // And the joint still answers afterwards
+        self.expect_state(LifecycleState::Unconfigured).await
+    }
+}
@@ -1,4 +1,76 @@
-use crate::protocol::{DeviceId, LifecycleState, Message, Payload, Header};
+use crate::config::{
+    ARM_DEVICE_ID, BROADCAST_ADDRESS, COLLISION_BACKOFF_VELOCITY_DPS, DISCOVERY_JITTER_US, DISCOVERY_SLOTS,
+    DISCOVERY_SLOT_US, ENTITY_TYPE_JOINT_CLN17, ERROR_BRAKE_ENGAGED, ERROR_CONFIG_STORE, ERROR_IN_BOOTLOADER,
+    ERROR_INVALID_GAINS, ERROR_INVALID_STATE, ERROR_LIMIT_VIOLATION, ERROR_MOTOR_FAULT, ERROR_OVERTEMPERATURE,
+    ERROR_POSITION_UNKNOWN, ERROR_SAFETY_ENABLE, ERROR_UNDERVOLTAGE, ERROR_UNKNOWN_COMMAND,
+    SAFETY_STOP_DECELERATION_DPS2, SAFETY_STOP_TIME_US, SAFETY_STOP_VELOCITY_DPS, SELF_TEST_MAX_MOTION_DEG,
+    SELF_TEST_TIME_US, SELF_TEST_VELOCITY_DPS, STREAM_ACK_IDLE_US, STREAM_ACK_INTERVAL, STREAM_MSG_ID_FLAG,
+    UNADDRESSED_DEVICE_ID,
+};
+use crate::protocol::{
+    AnalogInputPayload, BootInfoPayload, BootMode, BootPayload, CalibrationResult, Capabilities, CollisionReaction,
+    ConfigureAdaptivePayload, ControlGains, ControlMode, CrashRecord, DigitalIoPayload, DeviceId, EncoderTelemetry,
+    FaultCause, FaultReportPayload, FlightEvent, GainScheduleEntry, HomingConfig, LifecycleState, Message, MessageId,
+    JointStatistics, MotionProfile, PowerLimits, PowerStatusPayload, SelfTestOutcome, SelfTestRequest, SelfTestResult,
+    SetTargetPayload, StallStatus, SCHEMA_HASH, SyncSample, TargetBatch, ThermalLimits, Warnings,
+    MotorParameters, MultiTurnPosition, Payload, Header, HelloPayload, JointLimits, ConfigureTelemetryPayload, SetTargetPayloadV2,
+};
+use crate::config_store::{ConfigStore, ConfigStoreError, JointConfig};
+use crate::aux_io::AuxIoHandler;
+use crate::control::{CascadedController, ControlError, GainSchedule};
+use crate::encoder::{Encoder, EncoderError, EncoderFlags};
+use crate::motor::{DriverFaults, MotorCommand, MotorDriver, MotorError};
+use crate::thermal::ThermalModel;
+use crate::joint_log::{JointEvent, JointLogger};
+use crate::flight_recorder::FlightRecorder;
+
+/// A discovery reply waiting for its backoff delay to elapse
+#[derive(Debug, Clone, Copy)]
+struct PendingHello {
+    reply_to: DeviceId,
+    msg_id: MessageId,
+    delay_us: u32,
+    /// Absolute send time, fixed on the first `poll()` after scheduling
+    due_us: Option<u64>,
+}
+
+/// A self-test waiting for its motion test to finish
+#[derive(Debug, Clone, Copy)]
+struct PendingSelfTest {
+    reply_to: DeviceId,
+    msg_id: MessageId,
+    /// Outcome of the checks done so far
+    result: SelfTestResult,
+    /// Position the motion test started from, degrees
+    start_deg: f32,
+    motion_deg: f32,
+    started_us: u64,
+}
+
+/// Commands streamed without an `Ack` each (see `STREAM_MSG_ID_FLAG`)
+#[derive(Debug, Clone, Copy, Default)]
+struct StreamAcks {
+    /// Sender and message ID of the latest streamed command
+    reply_to: DeviceId,
+    last_msg_id: Option<MessageId>,
+    /// Commands missing from the sequence since the last `StreamAck`
+    dropped: u32,
+    /// Commands streamed since the last `StreamAck`
+    pending: u32,
+    /// `now_us` when the latest one arrived
+    received_us: u64,
+}
+
+/// `SafetyEnable` bookkeeping of a joint that requires it
+#[derive(Debug, Clone, Copy, Default)]
+struct SafetyEnable {
+    /// Token of the latest accepted `SafetyEnable`
+    token: Option<u32>,
+    /// When the enable runs out; `None` before the first one
+    expires_us: Option<u64>,
+    /// When the joint started stopping after it ran out
+    stopping_since_us: Option<u64>,
+}
 
 /// Represents a single joint on the embedded device, driven by a state machine.
 ///
@@ -7,6 +79,85 @@ use crate::protocol::{DeviceId, LifecycleState, Message, Payload, Header};
 pub struct Joint {
     id: DeviceId,
     state: LifecycleState,
+    entity_type: u16,
+    rng_state: u32,
+    pending_hello: Option<PendingHello>,
+    position_valid: bool,
+    /// Homing procedure in progress (the joint reports `Calibrating`)
+    homing: Option<HomingConfig>,
+    limits: Option<JointLimits>,
+    motor_parameters: Option<MotorParameters>,
+    thermal_model: Option<ThermalModel>,
+    telemetry_config: Option<ConfigureTelemetryPayload>,
+    target: Option<SetTargetPayloadV2>,
+    /// Target held for the next `Sync` in `ControlMode::CyclicPosition`
+    buffered_target: Option<SetTargetPayloadV2>,
+    accept_v1: bool,
+    boot_info: BootInfoPayload,
+    bootloader_requested: bool,
+    /// Node ID restored from the config store or assigned with `AssignId`,
+    /// persisted again on save
+    stored_id: Option<DeviceId>,
+    serial_number: u64,
+    capabilities: Capabilities,
+    /// Holding brake command; only meaningful with `Capabilities::BRAKE`
+    brake_engaged: bool,
+    /// Latest reading passed to `update_encoder`
+    raw_position: Option<MultiTurnPosition>,
+    encoder_offset: MultiTurnPosition,
+    /// Velocity from the last `sample_encoder`, degrees/second
+    velocity: f32,
+    sampled_us: u64,
+    encoder_flags: EncoderFlags,
+    /// Whether the joint has the motor driver enabled
+    driver_enabled: bool,
+    driver_faults: DriverFaults,
+    /// Position/velocity controller, once gains are configured
+    controller: Option<CascadedController>,
+    /// Kept apart from the controller so slots set before the base gains survive
+    gain_schedule: GainSchedule,
+    /// Feedforward torque in Nm, from `SetFeedforward`
+    feedforward: f32,
+    control_mode: ControlMode,
+    /// Speed in degrees/second from `SetVelocity`
+    commanded_velocity: f32,
+    /// Torque in Nm from `SetTorque`
+    commanded_torque: f32,
+    logger: Option<JointLogger>,
+    flight_recorder: Option<FlightRecorder>,
+    /// Latest time passed to `poll()`, stamped on flight recorder entries
+    now_us: u64,
+    /// Set while the joint only stays Active with a fresh `SafetyEnable`
+    safety_enable: Option<SafetyEnable>,
+    /// From `ConfigureAdaptive`, with `Capabilities::ADAPTIVE_CONTROL`
+    adaptive: Option<ConfigureAdaptivePayload>,
+    /// stallGuard's verdict from the last `update_load`
+    stall: StallStatus,
+    /// Since when the current has been above the collision threshold
+    overcurrent_since_us: Option<u64>,
+    /// Set once the joint reacted to a collision, until the load is normal
+    collision_latched: bool,
+    /// Fault report waiting for `poll()`
+    pending_fault: Option<FaultReportPayload>,
+    /// From `ConfigureThermal`
+    thermal_limits: Option<ThermalLimits>,
+    /// Latest winding temperature passed to `update_temperature`, Celsius
+    temperature_c: Option<f32>,
+    /// From `ConfigurePower`
+    power_limits: Option<PowerLimits>,
+    /// Latest readings passed to `update_power`
+    power: Option<PowerStatusPayload>,
+    /// When the joint started stopping on a brown-out
+    brownout_since_us: Option<u64>,
+    /// `now_us` of the last `update_power`, the start of the energy step
+    power_sampled_us: u64,
+    /// Usage counters behind `statistics()`
+    energy_j: f64,
+    motor_on_us: u64,
+    move_count: u32,
+    /// `RunSelfTest` whose motion test is in progress
+    self_test: Option<PendingSelfTest>,
+    stream: StreamAcks,
 }
 
 impl Joint {
@@ -15,9 +166,1198 @@ impl Joint {
         Self {
             id,
             state: LifecycleState::Unconfigured,
+            entity_type: ENTITY_TYPE_JOINT_CLN17,
+            rng_state: Self::seed_rng(id, 0),
+            pending_hello: None,
+            position_valid: true,
+            homing: None,
+            limits: None,
+            motor_parameters: None,
+            thermal_model: None,
+            telemetry_config: None,
+            target: None,
+            buffered_target: None,
+            accept_v1: true,
+            boot_info: BootInfoPayload::default(),
+            bootloader_requested: false,
+            stored_id: None,
+            serial_number: 0,
+            capabilities: Capabilities::SET_TARGET_V2 | Capabilities::HOMING | Capabilities::TELEMETRY_STREAMING,
+            brake_engaged: true,
+            raw_position: None,
+            encoder_offset: MultiTurnPosition::default(),
+            velocity: 0.0,
+            sampled_us: 0,
+            encoder_flags: EncoderFlags::NONE,
+            driver_enabled: false,
+            driver_faults: DriverFaults::NONE,
+            controller: None,
+            gain_schedule: GainSchedule::default(),
+            feedforward: 0.0,
+            control_mode: ControlMode::Position,
+            commanded_velocity: 0.0,
+            commanded_torque: 0.0,
+            logger: None,
+            flight_recorder: None,
+            now_us: 0,
+            safety_enable: None,
+            adaptive: None,
+            stall: StallStatus::Normal,
+            overcurrent_since_us: None,
+            collision_latched: false,
+            pending_fault: None,
+            thermal_limits: None,
+            temperature_c: None,
+            power_limits: None,
+            power: None,
+            brownout_since_us: None,
+            power_sampled_us: 0,
+            energy_j: 0.0,
+            motor_on_us: 0,
+            move_count: 0,
+            self_test: None,
+            stream: StreamAcks::default(),
+        }
+    }
+
+    /// Creates a joint waiting to be commissioned
+    ///
+    /// The joint listens on `UNADDRESSED_DEVICE_ID` and ignores everything
+    /// but an `AssignId` broadcast carrying its serial number. Firmware
+    /// starts this way when the config store holds no node ID:
+    ///
+    /// ```ignore
+    /// let mut joint = Joint::unaddressed(uid_serial());
+    /// let _ = joint.restore_config(&mut store);
+    /// ```
+    pub fn unaddressed(serial_number: u64) -> Self {
+        let mut joint = Self::new(UNADDRESSED_DEVICE_ID);
+        joint.serial_number = serial_number;
+        joint
+    }
+
+    /// Set the factory serial number `AssignId` is matched against
+    pub fn set_serial_number(&mut self, serial_number: u64) {
+        self.serial_number = serial_number;
+    }
+
+    /// Factory serial number of this unit
+    pub fn serial_number(&self) -> u64 {
+        self.serial_number
+    }
+
+    /// Whether the joint still waits for a node ID
+    pub fn is_unaddressed(&self) -> bool {
+        self.id == UNADDRESSED_DEVICE_ID
+    }
+
+    /// Report state changes, refused commands, restarts and loop stalls to
+    /// `logger` (see [`joint_log`](crate::joint_log)), or stop reporting
+    pub fn set_logger(&mut self, logger: Option<JointLogger>) {
+        self.logger = logger;
+    }
+
+    fn log(&self, event: JointEvent) {
+        if let Some(logger) = self.logger {
+            logger(self.id, &event);
+        }
+    }
+
+    /// Keep the last `FLIGHT_RECORDER_LEN` commands, replies and state
+    /// changes for the host to read back (see
+    /// [`flight_recorder`](crate::flight_recorder)), or drop them
+    ///
+    /// Also sets or clears `Capabilities::FLIGHT_RECORDER`.
+    pub fn set_flight_recorder(&mut self, enabled: bool) {
+        if enabled != self.flight_recorder.is_some() {
+            self.flight_recorder = enabled.then(FlightRecorder::new);
+        }
+        self.capabilities = if enabled {
+            self.capabilities | Capabilities::FLIGHT_RECORDER
+        } else {
+            self.capabilities.difference(Capabilities::FLIGHT_RECORDER)
+        };
+    }
+
+    /// The flight recorder, if enabled
+    pub fn flight_recorder(&self) -> Option<&FlightRecorder> {
+        self.flight_recorder.as_ref()
+    }
+
+    /// Require a fresh `SafetyEnable` to activate and to stay Active, or
+    /// stop requiring one
+    ///
+    /// While required, `Activate` and motion commands are refused with
+    /// `ERROR_SAFETY_ENABLE` unless the latest `SafetyEnable` is still
+    /// valid, and each one must carry a token different from the previous.
+    /// Once it runs out, [`poll`](Self::poll) stops the joint: motion
+    /// setpoints are dropped, the position controller holds where the joint
+    /// is, ramping the speed down at `SAFETY_STOP_DECELERATION_DPS2`, and
+    /// the joint deactivates once slower than `SAFETY_STOP_VELOCITY_DPS`,
+    /// or `SAFETY_STOP_TIME_US` after it ran out at the latest. A host that
+    /// hangs thus stops the arm within the TTL plus the stop time.
+    ///
+    /// Also sets or clears `Capabilities::SAFETY_ENABLE`.
+    pub fn require_safety_enable(&mut self, required: bool) {
+        if required != self.safety_enable.is_some() {
+            self.safety_enable = required.then(SafetyEnable::default);
+        }
+        self.capabilities = if required {
+            self.capabilities | Capabilities::SAFETY_ENABLE
+        } else {
+            self.capabilities.difference(Capabilities::SAFETY_ENABLE)
+        };
+    }
+
+    /// Whether the joint may be Active: it does not require `SafetyEnable`,
+    /// or the latest one has not run out at the last [`poll`](Self::poll)
+    pub fn safety_enabled(&self) -> bool {
+        self.safety_enable.is_none_or(|enable| enable.expires_us.is_some_and(|expires| self.now_us < expires))
+    }
+
+    fn refresh_safety_enable(&mut self, msg_id: MessageId, token: u32, ttl_ms: u16) -> Payload {
+        let now_us = self.now_us;
+        let Some(enable) = self.safety_enable.as_mut() else {
+            return Payload::Nack { id: msg_id, error: ERROR_UNKNOWN_COMMAND };
+        };
+        // A repeated token may be a stuck retransmission, not a live host
+        if enable.token == Some(token) {
+            return Payload::Nack { id: msg_id, error: ERROR_SAFETY_ENABLE };
+        }
+        *enable = SafetyEnable {
+            token: Some(token),
+            expires_us: Some(now_us + ttl_ms as u64 * 1_000),
+            stopping_since_us: None,
+        };
+        Payload::Ack(msg_id)
+    }
+
+    /// Stop and deactivate once the `SafetyEnable` ran out
+    fn check_safety_enable(&mut self) {
+        if self.safety_enabled() || !self.may_drive() {
+            return;
+        }
+        let Some(enable) = self.safety_enable.as_mut() else {
+            return;
+        };
+        let since_us = match enable.stopping_since_us {
+            Some(since_us) => since_us,
+            None => {
+                enable.stopping_since_us = Some(self.now_us);
+                self.log(JointEvent::SafetyEnableExpired);
+                self.abort_homing();
+                self.stop_motion();
+                self.now_us
+            }
+        };
+        let stopped = self.velocity.abs() < SAFETY_STOP_VELOCITY_DPS;
+        if stopped || self.now_us.saturating_sub(since_us) >= SAFETY_STOP_TIME_US {
+            self.set_state(LifecycleState::Inactive);
+        }
+    }
+
+    /// Drop motion commands and hold where the joint is, decelerating
+    fn stop_motion(&mut self) {
+        self.buffered_target = None;
+        self.commanded_velocity = 0.0;
+        self.commanded_torque = 0.0;
+        self.feedforward = 0.0;
+        self.target = self.position().map(|position| SetTargetPayloadV2 {
+            target_angle: position.degrees(),
+            max_velocity: 0.0,
+            target_velocity: 0.0,
+            max_acceleration: SAFETY_STOP_DECELERATION_DPS2,
+            max_deceleration: SAFETY_STOP_DECELERATION_DPS2,
+            max_jerk: 0.0,
+            profile: MotionProfile::Trapezoidal,
+            max_current: 0.0,
+            max_temperature: 0.0,
+        });
+    }
+
+    fn record(&mut self, event: FlightEvent) {
+        if let Some(recorder) = self.flight_recorder.as_mut() {
+            recorder.record(self.now_us as u32, event);
+        }
+    }
+
+    fn set_state(&mut self, to: LifecycleState) {
+        let from = core::mem::replace(&mut self.state, to);
+        if from != to {
+            self.log(JointEvent::StateChanged { from, to });
+            self.record(FlightEvent::StateChanged { from, to });
+        }
+    }
+
+    /// Record a command addressed to this joint
+    fn note_command(&mut self, msg: &Message) {
+        let addressed = msg.header.target_id == self.id
+            || msg.header.target_id == BROADCAST_ADDRESS
+            || matches!(&msg.payload, Payload::SetTargetBatch(batch) if batch.target_for(self.id).is_some());
+        // Reading the recorder would push out what is being read, and
+        // heartbeats and sync frames everything else
+        let periodic = matches!(msg.payload, Payload::SafetyEnable { .. } | Payload::Sync { .. });
+        if addressed && !periodic && !matches!(msg.payload, Payload::RequestFlightRecorder { .. }) {
+            self.record(FlightEvent::Received { msg_id: msg.header.msg_id, payload: msg.payload.tag() });
+        }
+    }
+
+    /// Record `reply` to `msg`, and log it if it refuses the command
+    fn note_reply(&mut self, msg: &Message, reply: &Message) {
+        let msg_id = msg.header.msg_id;
+        match reply.payload {
+            Payload::Nack { id, error } => {
+                self.log(JointEvent::Nacked { command: msg.payload.name(), msg_id: id, error });
+                self.record(FlightEvent::Nacked { msg_id, error });
+            }
+            Payload::FlightRecorder(_) => {}
+            _ if matches!(msg.payload, Payload::SafetyEnable { .. } | Payload::Sync { .. }) => {}
+            _ => self.record(FlightEvent::Replied { msg_id, payload: reply.payload.tag() }),
+        }
+    }
+
+    /// Report that the encoder lost validity (e.g. a power glitch on an
+    /// absolute encoder)
+    ///
+    /// The joint then reports `ERROR_POSITION_UNKNOWN` in its status and
+    /// refuses motion until re-homed. Applied limits are dropped because they
+    /// refer to the lost position frame.
+    pub fn report_encoder_fault(&mut self) {
+        self.abort_homing();
+        self.position_valid = false;
+        self.limits = None;
+    }
+
+    /// Report a fault the joint cannot recover from on its own (overcurrent,
+    /// driver fault, ...)
+    ///
+    /// The joint enters `Error`, engages its brake and refuses motion until
+    /// the arm resets it.
+    pub fn report_fault(&mut self) {
+        self.homing = None;
+        self.set_state(LifecycleState::Error);
+        self.brake_engaged = true;
+    }
+
+    /// Whether the holding brake should hold
+    ///
+    /// Firmware drives the brake from this. The brake starts engaged, is
+    /// engaged again on `Reset` (emergency stop) and [`report_fault`](Self::report_fault),
+    /// and may only be released once configured. Always false without
+    /// `Capabilities::BRAKE`.
+    pub fn brake_engaged(&self) -> bool {
+        self.capabilities.contains(Capabilities::BRAKE) && self.brake_engaged
+    }
+
+    /// Whether the joint currently knows its position
+    pub fn position_known(&self) -> bool {
+        self.position_valid
+    }
+
+    /// Feed the raw encoder position, turns included; call every control
+    /// cycle
+    ///
+    /// Single-turn encoders keep the turn count in firmware and pass it here.
+    pub fn update_encoder(&mut self, raw: MultiTurnPosition) {
+        self.raw_position = Some(raw);
+    }
+
+    /// Offset-corrected position, answered to `RequestMultiTurnPosition`
+    ///
+    /// `None` before the first [`update_encoder`](Self::update_encoder) and
+    /// while the position is unknown.
+    pub fn position(&self) -> Option<MultiTurnPosition> {
+        self.raw_position
+            .filter(|_| self.position_valid)
+            .map(|raw| raw.relative_to(self.encoder_offset))
+    }
+
+    /// Raw encoder position that reads as zero
+    pub fn encoder_offset(&self) -> MultiTurnPosition {
+        self.encoder_offset
+    }
+
+    /// Read `encoder` and feed the joint; call every control cycle
+    ///
+    /// Updates the position like [`update_encoder`](Self::update_encoder)
+    /// and the velocity (differentiated between samples unless the encoder
+    /// measures it). A failed read is reported like
+    /// [`report_encoder_fault`](Self::report_encoder_fault).
+    pub fn sample_encoder(
+        &mut self,
+        encoder: &mut dyn Encoder,
+        now_us: u64,
+    ) -> Result<MultiTurnPosition, EncoderError> {
+        let read = encoder.read_position();
+        self.encoder_flags = encoder.error_flags();
+        let raw = match read {
+            Ok(raw) => raw,
+            Err(e) => {
+                if self.position_valid {
+                    self.report_encoder_fault();
+                }
+                self.raw_position = None;
+                self.velocity = 0.0;
+                return Err(e);
+            }
+        };
+        self.velocity = match (encoder.read_velocity(), self.raw_position) {
+            (Some(velocity), _) => velocity,
+            (None, Some(last)) if now_us > self.sampled_us => {
+                raw.relative_to(last).degrees() / ((now_us - self.sampled_us) as f32 * 1e-6)
+            }
+            (None, _) => 0.0,
+        };
+        self.sampled_us = now_us;
+        self.update_encoder(raw);
+        Ok(raw)
+    }
+
+    /// Velocity from the last [`sample_encoder`](Self::sample_encoder), in
+    /// degrees/second
+    pub fn velocity(&self) -> f32 {
+        self.velocity
+    }
+
+    /// Encoder warnings from the last [`sample_encoder`](Self::sample_encoder)
+    pub fn encoder_flags(&self) -> EncoderFlags {
+        self.encoder_flags
+    }
+
+    /// Adaptive control settings from `ConfigureAdaptive`, for firmware to
+    /// program its driver with
+    pub fn adaptive_config(&self) -> Option<ConfigureAdaptivePayload> {
+        self.adaptive
+    }
+
+    /// Feed stallGuard's verdict and the measured motor current, in
+    /// amperes; call every control cycle
+    ///
+    /// With a [`CollisionReaction`] set through `ConfigureAdaptive`, a stall
+    /// (with stallGuard enabled) or a current above the collision threshold
+    /// for `collision_time_ms` makes an Active joint react on its own:
+    ///
+    /// - `Stop` drops the setpoint and holds where the joint is, ramping the
+    ///   speed down at `SAFETY_STOP_DECELERATION_DPS2`
+    /// - `BackOff` does the same, then moves `collision_backoff_deg` back
+    ///   from the direction of travel at `COLLISION_BACKOFF_VELOCITY_DPS`
+    ///   (in position control; in the other modes it only stops)
+    /// - `GoLimp` deactivates, so the driver is disabled
+    ///
+    /// The next [`poll`](Self::poll) then returns a `FaultReport` for the
+    /// arm. The joint reacts once per collision, and again only after the
+    /// load was back to normal. Homing against an end stop (`Calibrating`)
+    /// is not a collision. A high load or stall, and a current above the
+    /// threshold, also raise [`warnings`](Self::warnings).
+    pub fn update_load(&mut self, stall: StallStatus, current: f32, now_us: u64) {
+        self.stall = stall;
+        let Some(config) = self.adaptive else {
+            return;
+        };
+        let threshold = config.collision_current_threshold;
+        let overloaded = threshold > 0.0 && current.abs() > threshold;
+        let since_us = match overloaded {
+            true => *self.overcurrent_since_us.get_or_insert(now_us),
+            false => {
+                self.overcurrent_since_us = None;
+                now_us
+            }
+        };
+        if config.collision_reaction == CollisionReaction::None {
+            return;
+        }
+        let stalled = config.stallguard_enable && stall == StallStatus::Stalled;
+        if !stalled && !overloaded {
+            self.collision_latched = false;
+            return;
+        }
+        if self.collision_latched || self.state != LifecycleState::Active {
+            return;
+        }
+        let cause = match stalled {
+            true => FaultCause::Stall,
+            false if now_us - since_us >= config.collision_time_ms as u64 * 1_000 => FaultCause::Overcurrent,
+            false => return,
+        };
+        self.react_to_collision(cause, &config, current);
+    }
+
+    fn react_to_collision(&mut self, cause: FaultCause, config: &ConfigureAdaptivePayload, current: f32) {
+        let reaction = config.collision_reaction;
+        self.collision_latched = true;
+        self.log(JointEvent::Collision { cause, reaction });
+        let position = self.position().map(|position| position.degrees());
+        // Where the joint was heading: its velocity, or its target if it
+        // barely moves (it may already be blocked)
+        let heading = match (self.target, position) {
+            (Some(target), Some(position)) if self.velocity.abs() < SAFETY_STOP_VELOCITY_DPS => {
+                target.target_angle - position
+            }
+            _ => self.velocity,
+        };
+        match reaction {
+            CollisionReaction::Stop | CollisionReaction::BackOff => self.stop_motion(),
+            CollisionReaction::GoLimp => self.set_state(LifecycleState::Inactive),
+            CollisionReaction::None => {}
+        }
+        let backs_off = reaction == CollisionReaction::BackOff
+            && matches!(self.control_mode, ControlMode::Position | ControlMode::CyclicPosition);
+        if let (true, Some(target)) = (backs_off && heading != 0.0, self.target.as_mut()) {
+            let mut angle = target.target_angle - config.collision_backoff_deg.copysign(heading);
+            if let Some(limits) = self.limits {
+                angle = angle.clamp(limits.min_position, limits.max_position);
+            }
+            target.target_angle = angle;
+            target.max_velocity = COLLISION_BACKOFF_VELOCITY_DPS;
+        }
+        self.pending_fault = Some(FaultReportPayload {
+            cause,
+            reaction,
+            position: position.unwrap_or(f32::NAN),
+            current,
+        });
+    }
+
+    /// Feed the winding temperature, in Celsius, e.g. the one reported in
+    /// `TelemetryStream`; call at least every telemetry period
+    ///
+    /// With `ThermalLimits` set through `ConfigureThermal`, the control loop
+    /// derates above the warning temperature (see [`derating`](Self::derating))
+    /// and `Warnings::OVERTEMPERATURE` is raised in [`warnings`](Self::warnings).
+    /// At the critical temperature the joint faults like
+    /// [`report_fault`](Self::report_fault), reports `ERROR_OVERTEMPERATURE`
+    /// in its status and refuses `Activate` until it has cooled down.
+    pub fn update_temperature(&mut self, temperature_c: f32) {
+        self.temperature_c = Some(temperature_c);
+        if self.overheated() && self.state != LifecycleState::Error {
+            self.report_fault();
+        }
+    }
+
+    /// Thresholds from `ConfigureThermal`, if set
+    pub fn thermal_limits(&self) -> Option<ThermalLimits> {
+        self.thermal_limits
+    }
+
+    /// Share of the current and velocity limits the control loop uses: 1
+    /// until the temperature passes the warning threshold
+    pub fn derating(&self) -> f32 {
+        match (self.thermal_limits, self.temperature_c) {
+            (Some(limits), Some(temperature_c)) => limits.derating(temperature_c),
+            _ => 1.0,
+        }
+    }
+
+    /// At or above the critical temperature
+    fn overheated(&self) -> bool {
+        matches!((self.thermal_limits, self.temperature_c), (Some(limits), Some(t)) if t >= limits.critical_c)
+    }
+
+    /// Feed the supply voltage, in volts, and the current drawn from it, in
+    /// amperes; call at least every telemetry period
+    ///
+    /// The readings answer `RequestPowerStatus`. With `PowerLimits` set
+    /// through `ConfigurePower`, `Warnings::UNDERVOLTAGE` is raised below
+    /// the undervoltage threshold. Below the brown-out voltage the joint
+    /// stops like on a lapsed `SafetyEnable`, then deactivates with its brake
+    /// engaged; it reports `ERROR_UNDERVOLTAGE` in its status and refuses
+    /// `Activate` until the supply has recovered. The stop is timed by
+    /// [`poll`](Self::poll).
+    pub fn update_power(&mut self, bus_voltage: f32, bus_current: f32) {
+        if let Some(power) = self.power {
+            // Energy drawn since the last readings; regeneration is not counted
+            let elapsed_s = self.now_us.saturating_sub(self.power_sampled_us) as f64 * 1e-6;
+            self.energy_j += (power.power() as f64 * elapsed_s).max(0.0);
+        }
+        self.power_sampled_us = self.now_us;
+        self.power = Some(PowerStatusPayload { bus_voltage, bus_current });
+        self.check_power();
+    }
+
+    /// Thresholds from `ConfigurePower`, if set
+    pub fn power_limits(&self) -> Option<PowerLimits> {
+        self.power_limits
+    }
+
+    /// Readings from the last [`update_power`](Self::update_power)
+    pub fn power_status(&self) -> Option<PowerStatusPayload> {
+        self.power
+    }
+
+    /// Supply below `threshold` of the power limits
+    fn supply_below(&self, threshold: fn(&PowerLimits) -> f32) -> bool {
+        matches!((self.power_limits, self.power), (Some(limits), Some(power)) if power.bus_voltage < threshold(&limits))
+    }
+
+    fn browned_out(&self) -> bool {
+        self.supply_below(|limits| limits.brownout_v)
+    }
+
+    /// Stop, then deactivate with the brake engaged, on a brown-out
+    fn check_power(&mut self) {
+        if !self.browned_out() {
+            self.brownout_since_us = None;
+            return;
+        }
+        if !self.may_drive() {
+            return;
+        }
+        let since_us = match self.brownout_since_us {
+            Some(since_us) => since_us,
+            None => {
+                self.brownout_since_us = Some(self.now_us);
+                self.log(JointEvent::Brownout);
+                self.abort_homing();
+                self.stop_motion();
+                self.now_us
+            }
+        };
+        let stopped = self.velocity.abs() < SAFETY_STOP_VELOCITY_DPS;
+        if stopped || self.now_us.saturating_sub(since_us) >= SAFETY_STOP_TIME_US {
+            self.set_state(LifecycleState::Inactive);
+            self.brake_engaged = true;
         }
     }
 
+    /// Warning flags for `TelemetryStream::warnings`, from the temperature,
+    /// load, encoder, supply and driver readings fed to the joint and its
+    /// `SafetyEnable` heartbeat
+    pub fn warnings(&self) -> Warnings {
+        let driver = |fault| self.driver_faults.contains(fault);
+        let heartbeat_lost = matches!(
+            self.safety_enable,
+            Some(SafetyEnable { expires_us: Some(expires_us), .. }) if self.now_us >= expires_us
+        );
+        [
+            (self.derating() < 1.0 || driver(DriverFaults::OVERTEMPERATURE), Warnings::OVERTEMPERATURE),
+            (
+                self.supply_below(|limits| limits.undervoltage_v) || driver(DriverFaults::UNDERVOLTAGE),
+                Warnings::UNDERVOLTAGE,
+            ),
+            (self.overcurrent_since_us.is_some() || driver(DriverFaults::OVERCURRENT), Warnings::OVERCURRENT),
+            (!self.encoder_flags.is_empty(), Warnings::ENCODER_ERROR),
+            (heartbeat_lost, Warnings::COMM_DEGRADED),
+            (self.stall != StallStatus::Normal, Warnings::STALL_WARNING),
+        ]
+        .into_iter()
+        .filter(|(raised, _)| *raised)
+        .fold(Warnings::NONE, |warnings, (_, flag)| warnings | flag)
+    }
+
+    fn configure_adaptive(&mut self, msg_id: MessageId, config: ConfigureAdaptivePayload) -> Payload {
+        let valid = [config.collision_current_threshold, config.collision_backoff_deg]
+            .iter()
+            .all(|value| value.is_finite() && *value >= 0.0);
+        if !valid {
+            return Payload::Nack { id: msg_id, error: ERROR_LIMIT_VIOLATION };
+        }
+        self.adaptive = Some(config);
+        self.overcurrent_since_us = None;
+        self.collision_latched = false;
+        Payload::Ack(msg_id)
+    }
+
+    /// Whether the motor may be energised in the current state
+    fn may_drive(&self) -> bool {
+        matches!(self.state, LifecycleState::Active | LifecycleState::Calibrating)
+    }
+
+    /// Bring `driver` in line with the joint; call after every message and
+    /// control tick
+    ///
+    /// Disables the driver once the joint leaves `Active`/`Calibrating`
+    /// (deactivated, reset, faulted). A fault latched by the driver disables
+    /// it and puts the joint in `Error` like [`report_fault`](Self::report_fault).
+    pub fn sync_driver(&mut self, driver: &mut dyn MotorDriver) {
+        self.driver_faults = driver.faults();
+        if !self.driver_faults.is_empty() && self.state != LifecycleState::Error {
+            self.report_fault();
+        }
+        if self.driver_enabled && !self.may_drive() {
+            driver.disable();
+            self.driver_enabled = false;
+        }
+    }
+
+    /// Pass a control loop output to `driver`
+    ///
+    /// Refused with `MotorError::Disabled` unless the joint is `Active` or
+    /// `Calibrating` with the driver enabled. A driver fault is reported
+    /// like [`report_fault`](Self::report_fault).
+    pub fn command_motor(&mut self, driver: &mut dyn MotorDriver, command: MotorCommand) -> Result<(), MotorError> {
+        if !self.driver_enabled || !self.may_drive() {
+            return Err(MotorError::Disabled);
+        }
+        let result = match command {
+            MotorCommand::Voltage(volts) => driver.set_voltage(volts),
+            MotorCommand::Current(amps) => driver.set_current(amps),
+        };
+        if result == Err(MotorError::Fault) {
+            self.sync_driver(driver);
+        }
+        result
+    }
+
+    /// Faults the motor driver reported at the last [`sync_driver`](Self::sync_driver)
+    pub fn driver_faults(&self) -> DriverFaults {
+        self.driver_faults
+    }
+
+    /// Run one cycle of the position/velocity controller, `dt` seconds after
+    /// the previous one
+    ///
+    /// Samples `encoder` like [`sample_encoder`](Self::sample_encoder), then,
+    /// while `Active` with a known position and the brake released, drives
+    /// the motor through [`command_motor`](Self::command_motor) as the
+    /// [`control_mode`](Self::control_mode) says: towards the target (or
+    /// holding position without one), at the `SetVelocity` speed, or with
+    /// the `SetTorque` torque. Torques, the `SetFeedforward` one included,
+    /// become current once calibration has identified the torque constant;
+    /// until then torque mode commands nothing. The current and velocity
+    /// limits are [derated](Self::derating) when hot. In any other state, or
+    /// before `ConfigureGains`, nothing is commanded and the controller
+    /// starts over.
+    pub fn control_step(
+        &mut self,
+        dt: f32,
+        encoder: &mut dyn Encoder,
+        driver: &mut dyn MotorDriver,
+    ) -> Result<(), ControlError> {
+        let now_us = self.sampled_us + (dt * 1e6) as u64;
+        self.sample_encoder(encoder, now_us)?;
+        let position = self.position();
+        let (target, velocity) = (self.target, self.velocity);
+        let brake_engaged = self.brake_engaged();
+        let amps_per_nm = match self.motor_parameters {
+            Some(p) if p.torque_constant_kt > 0.0 => Some(1.0 / p.torque_constant_kt),
+            _ => None,
+        };
+        let velocity_setpoint = self.limited_velocity(position);
+        let derating = self.derating();
+        let Some(controller) = self.controller.as_mut() else {
+            return Ok(());
+        };
+        controller.set_derating(derating);
+        let position = match position {
+            Some(position) if self.state == LifecycleState::Active && !brake_engaged => position,
+            _ => {
+                controller.reset();
+                return Ok(());
+            }
+        };
+        controller.set_feedforward(self.feedforward * amps_per_nm.unwrap_or(0.0));
+        let current = match self.control_mode {
+            ControlMode::Position | ControlMode::CyclicPosition => {
+                controller.update(target.as_ref(), position.degrees(), velocity, dt)
+            }
+            ControlMode::Velocity => controller.update_velocity(velocity_setpoint, velocity, dt),
+            ControlMode::Torque => match amps_per_nm {
+                Some(amps_per_nm) => controller.update_current(self.commanded_torque * amps_per_nm),
+                None => {
+                    controller.reset();
+                    return Ok(());
+                }
+            },
+        };
+        self.command_motor(driver, MotorCommand::Current(current))?;
+        Ok(())
+    }
+
+    /// The position/velocity controller, once `ConfigureGains` set its gains
+    pub fn controller(&self) -> Option<&CascadedController> {
+        self.controller.as_ref()
+    }
+
+    /// What the control loop follows, from `SetControlMode` or the stored
+    /// configuration
+    pub fn control_mode(&self) -> ControlMode {
+        self.control_mode
+    }
+
+    /// Speed from the last `SetVelocity`, in degrees/second
+    pub fn commanded_velocity(&self) -> f32 {
+        self.commanded_velocity
+    }
+
+    /// Torque from the last `SetTorque`, in newton-metres
+    pub fn commanded_torque(&self) -> f32 {
+        self.commanded_torque
+    }
+
+    /// The `SetVelocity` speed, stopped at a position limit it would cross
+    fn limited_velocity(&self, position: Option<MultiTurnPosition>) -> f32 {
+        let speed = self.commanded_velocity;
+        match (self.limits, position.map(|p| p.degrees())) {
+            (Some(limits), Some(angle)) if speed > 0.0 && angle >= limits.max_position => 0.0,
+            (Some(limits), Some(angle)) if speed < 0.0 && angle <= limits.min_position => 0.0,
+            _ => speed,
+        }
+    }
+
+    /// Feedforward torque from the last `SetFeedforward`, in newton-metres
+    ///
+    /// Zero again after every `Activate`.
+    pub fn feedforward(&self) -> f32 {
+        self.feedforward
+    }
+
+    /// Gain sets scheduled by operating region
+    pub fn gain_schedule(&self) -> &GainSchedule {
+        &self.gain_schedule
+    }
+
+    /// Basic telemetry from the latest encoder sample, while the position is
+    /// known
+    pub fn encoder_telemetry(&self) -> Option<EncoderTelemetry> {
+        self.position().map(|position| EncoderTelemetry {
+            position: position.degrees(),
+            velocity: self.velocity,
+        })
+    }
+
+    /// Whether the arm has requested homing that firmware has not finished yet
+    pub fn homing_requested(&self) -> bool {
+        self.homing.is_some()
+    }
+
+    /// The homing procedure firmware should run, while one is in progress
+    ///
+    /// Set by `StartHoming`, or by `Home` with [`HomingConfig::default`].
+    /// The joint reports `Calibrating` until firmware calls
+    /// [`complete_homing`](Self::complete_homing) or
+    /// [`abort_homing`](Self::abort_homing).
+    pub fn homing(&self) -> Option<HomingConfig> {
+        self.homing
+    }
+
+    /// Called by firmware once its homing routine has re-established position
+    ///
+    /// Firmware sets its position to the config's `offset` at the reference
+    /// point; the joint returns to `Active`.
+    pub fn complete_homing(&mut self) {
+        self.position_valid = true;
+        if self.homing.take().is_some() && self.state == LifecycleState::Calibrating {
+            self.set_state(LifecycleState::Active);
+        }
+    }
+
+    /// Called by firmware when its homing routine gave up (no index pulse,
+    /// travel exceeded, ...)
+    ///
+    /// The joint returns to `Active` with its position still unknown.
+    pub fn abort_homing(&mut self) {
+        if self.homing.take().is_some() && self.state == LifecycleState::Calibrating {
+            self.set_state(LifecycleState::Active);
+        }
+    }
+
+    /// Enter the homing sub-state; the old position reference is discarded
+    fn start_homing(&mut self, msg_id: MessageId, config: HomingConfig) -> Payload {
+        match self.state {
+            LifecycleState::Active if self.brake_engaged() => Payload::Nack {
+                id: msg_id,
+                error: ERROR_BRAKE_ENGAGED,
+            },
+            LifecycleState::Active => {
+                // Firmware runs the homing routine and calls complete_homing()
+                self.set_state(LifecycleState::Calibrating);
+                self.homing = Some(config);
+                self.position_valid = false;
+                self.target = None;
+                Payload::Ack(msg_id)
+            }
+            _ => Payload::Nack {
+                id: msg_id,
+                error: ERROR_INVALID_STATE,
+            },
+        }
+    }
+
+    /// Soft limits currently applied, if any
+    pub fn limits(&self) -> Option<JointLimits> {
+        self.limits
+    }
+
+    /// Last accepted motion target, in its v2 form (in
+    /// `ControlMode::CyclicPosition`, the one taken at the latest `Sync`)
+    ///
+    /// v1 `SetTarget` commands are translated on arrival (see
+    /// [`compat`](crate::compat)), so firmware only deals with one
+    /// representation.
+    pub fn target(&self) -> Option<SetTargetPayloadV2> {
+        self.target
+    }
+
+    /// Whether v1 motion commands are accepted (default) or refused with
+    /// `ERROR_UNKNOWN_COMMAND`, as firmware that dropped v1 support would
+    pub fn set_accept_v1(&mut self, accept: bool) {
+        self.accept_v1 = accept;
+    }
+
+    /// Telemetry settings last received from the arm (rate and schedule
+    /// phase), for the firmware's telemetry hook to follow
+    pub fn telemetry_config(&self) -> Option<ConfigureTelemetryPayload> {
+        self.telemetry_config
+    }
+
+    /// Take over the parameters identified by a calibration run
+    ///
+    /// Firmware calls this with the result it reports to the arm. A thermal
+    /// model is kept only if the thermal phase ran; otherwise the previous
+    /// one (if any) stays in use.
+    pub fn apply_calibration(&mut self, result: &CalibrationResult) {
+        if !result.success {
+            return;
+        }
+        self.motor_parameters = Some(result.parameters);
+        if let Some(model) = ThermalModel::from_parameters(&result.parameters) {
+            self.thermal_model = Some(model);
+        }
+    }
+
+    /// Parameters of the last successful calibration, applied or restored
+    pub fn motor_parameters(&self) -> Option<MotorParameters> {
+        self.motor_parameters
+    }
+
+    /// Usage counters since the joint was put into service, answering
+    /// `RequestStatistics`
+    ///
+    /// Energy is integrated from the readings passed to
+    /// [`update_power`](Self::update_power), between [`poll`](Self::poll)
+    /// times; so is the time spent Active or Calibrating. A move starts with
+    /// every target accepted while the joint stands still. The counters are
+    /// saved with the configuration and restored by
+    /// [`restore_config`](Self::restore_config); `LoadConfig` and
+    /// `FactoryReset` leave them alone.
+    pub fn statistics(&self) -> JointStatistics {
+        JointStatistics {
+            energy_wh: (self.energy_j / 3600.0) as f32,
+            motor_on_s: (self.motor_on_us / 1_000_000) as u32,
+            move_count: self.move_count,
+        }
+    }
+
+    /// Whether a `RunSelfTest` is moving the joint
+    ///
+    /// The self-test checks that the encoder has been read without errors
+    /// and that the motor driver reports no faults. With a motion distance
+    /// it then moves the joint that far (at most `SELF_TEST_MAX_MOTION_DEG`)
+    /// at `SELF_TEST_VELOCITY_DPS`, refused like a `SetTarget` unless the
+    /// joint may move. The test passes once the joint covered half the
+    /// distance, and fails if it did not within `SELF_TEST_TIME_US` or left
+    /// `Active`; the joint then heads back to where it started and
+    /// [`poll`](Self::poll) returns the `SelfTestResult`. Without a motion
+    /// test the result is the reply.
+    pub fn self_test_running(&self) -> bool {
+        self.self_test.is_some()
+    }
+
+    fn run_self_test(&mut self, header: &Header, request: SelfTestRequest) -> Option<Payload> {
+        let msg_id = header.msg_id;
+        if !request.motion_deg.is_finite() || request.motion_deg.abs() > SELF_TEST_MAX_MOTION_DEG {
+            return Some(Payload::Nack { id: msg_id, error: ERROR_LIMIT_VIOLATION });
+        }
+        if self.self_test.is_some() {
+            return Some(Payload::Nack { id: msg_id, error: ERROR_INVALID_STATE }); // already testing
+        }
+        let outcome = |passed: bool| if passed { SelfTestOutcome::Passed } else { SelfTestOutcome::Failed };
+        let result = SelfTestResult {
+            nonce: request.nonce,
+            encoder: outcome(self.raw_position.is_some() && self.encoder_flags.is_empty()),
+            driver: outcome(self.driver_faults.is_empty()),
+            ..Default::default()
+        };
+        if request.motion_deg == 0.0 {
+            return Some(Payload::SelfTestResult(result));
+        }
+
+        let Some(start_deg) = self.position().map(|position| position.degrees()) else {
+            let error = if self.state == LifecycleState::Active { ERROR_POSITION_UNKNOWN } else { ERROR_INVALID_STATE };
+            return Some(Payload::Nack { id: msg_id, error });
+        };
+        let target = SetTargetPayload {
+            target_angle: start_deg + request.motion_deg,
+            velocity_limit: SELF_TEST_VELOCITY_DPS,
+        };
+        let reply = self.set_target(msg_id, target.into());
+        if !matches!(reply, Payload::Ack(_)) {
+            return Some(reply);
+        }
+        self.self_test = Some(PendingSelfTest {
+            reply_to: header.source_id,
+            msg_id,
+            result,
+            start_deg,
+            motion_deg: request.motion_deg,
+            started_us: self.now_us,
+        });
+        None
+    }
+
+    /// Finish the motion test once it passed or failed
+    fn check_self_test(&mut self) -> Option<Message> {
+        let test = self.self_test?;
+        let moved_deg = self
+            .position()
+            .map_or(0.0, |position| (position.degrees() - test.start_deg) * test.motion_deg.signum());
+        let covered = moved_deg >= test.motion_deg.abs() / 2.0;
+        let active = self.state == LifecycleState::Active;
+        if !covered && active && self.now_us < test.started_us + SELF_TEST_TIME_US {
+            return None;
+        }
+
+        self.self_test = None;
+        // Back to the start, unless the target changed since
+        let test_target = test.start_deg + test.motion_deg;
+        if let Some(target) = self.target.as_mut().filter(|target| active && target.target_angle == test_target) {
+            target.target_angle = test.start_deg;
+        }
+        let motion = if covered { SelfTestOutcome::Passed } else { SelfTestOutcome::Failed };
+        Some(Message {
+            header: Header {
+                source_id: self.id,
+                target_id: test.reply_to,
+                msg_id: test.msg_id,
+            },
+            payload: Payload::SelfTestResult(SelfTestResult { motion, moved_deg, ..test.result }),
+        })
+    }
+
+    /// Save the usage counters into the stored configuration, leaving the
+    /// rest of it as stored; call now and then (on deactivation, every few
+    /// minutes), minding the memory's write endurance
+    ///
+    /// A blank store gets a record holding the defaults and the counters.
+    pub fn save_statistics(&self, store: &mut dyn ConfigStore) -> Result<(), ConfigStoreError> {
+        let mut config = match JointConfig::load(store) {
+            Err(ConfigStoreError::NoConfig) => JointConfig::default(),
+            loaded => loaded?,
+        };
+        config.statistics = self.statistics();
+        config.save(store)
+    }
+
+    /// The configuration `SaveConfig` persists: applied limits, calibration
+    /// result, telemetry settings, encoder offset, controller gains and
+    /// control mode, the node ID if it came from the store, and the usage
+    /// counters
+    pub fn config(&self) -> JointConfig {
+        JointConfig {
+            node_id: self.stored_id,
+            limits: self.limits,
+            motor_parameters: self.motor_parameters,
+            telemetry: self.telemetry_config,
+            encoder_offset: (self.encoder_offset != MultiTurnPosition::default()).then_some(self.encoder_offset),
+            gains: self.controller.as_ref().map(CascadedController::gains),
+            gain_schedule: self.gain_schedule,
+            control_mode: self.control_mode,
+            statistics: self.statistics(),
+        }
+    }
+
+    /// Apply the stored configuration, node ID included; call once at boot
+    ///
+    /// A blank or corrupted store returns `ConfigStoreError::NoConfig` and
+    /// leaves the defaults in place.
+    pub fn restore_config(&mut self, store: &mut dyn ConfigStore) -> Result<(), ConfigStoreError> {
+        let config = JointConfig::load(store)?;
+        if let Some(id) = config.node_id {
+            self.id = id;
+        }
+        self.stored_id = config.node_id;
+        self.apply_config(&config);
+        self.energy_j = config.statistics.energy_wh as f64 * 3600.0;
+        self.motor_on_us = config.statistics.motor_on_s as u64 * 1_000_000;
+        self.move_count = config.statistics.move_count;
+        Ok(())
+    }
+
+    fn apply_config(&mut self, config: &JointConfig) {
+        self.limits = config.limits;
+        self.motor_parameters = config.motor_parameters;
+        self.thermal_model = config.motor_parameters.as_ref().and_then(ThermalModel::from_parameters);
+        self.telemetry_config = config.telemetry;
+        self.encoder_offset = config.encoder_offset.unwrap_or_default();
+        self.gain_schedule = config.gain_schedule;
+        self.control_mode = config.control_mode;
+        self.controller = config.gains.map(|gains| {
+            let mut controller = CascadedController::new(gains);
+            controller.set_schedule(config.gain_schedule);
+            controller
+        });
+    }
+
+    /// Winding thermal model used for derating, once identified
+    pub fn thermal_model(&self) -> Option<ThermalModel> {
+        self.thermal_model
+    }
+
+    /// Set the entity type reported in discovery replies
+    pub fn set_entity_type(&mut self, entity_type: u16) {
+        self.entity_type = entity_type;
+    }
+
+    /// Entity type reported in discovery replies
+    pub fn entity_type(&self) -> u16 {
+        self.entity_type
+    }
+
+    /// Set the capabilities reported in discovery replies
+    ///
+    /// The default covers what the state machine handles on its own
+    /// (`SET_TARGET_V2`, `HOMING`, `TELEMETRY_STREAMING`). Firmware adds the
+    /// flags for commands it handles itself: `CALIBRATION`,
+    /// `ADAPTIVE_CONTROL`, `CONFIG_STORE` when it passes a store to
+    /// [`handle_with_store`](Self::handle_with_store), `AUX_IO` when it
+    /// passes I/O to [`handle_aux_io`](Self::handle_aux_io), `FIRMWARE_UPDATE`
+    /// when it has a bootloader. `BRAKE` makes the state machine handle the
+    /// brake commands (see [`brake_engaged`](Self::brake_engaged)).
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    /// Capabilities reported in discovery replies
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Set the firmware versions, boot mode and boot count the joint reports
+    ///
+    /// A bootloader built on this state machine sets `mode` to
+    /// [`BootMode::Bootloader`]; the joint then answers discovery and
+    /// `RequestBootInfo` but refuses every other command with
+    /// `ERROR_IN_BOOTLOADER`.
+    pub fn set_boot_info(&mut self, info: BootInfoPayload) {
+        self.boot_info = info;
+    }
+
+    /// Firmware versions, boot mode and boot count reported to the arm
+    pub fn boot_info(&self) -> BootInfoPayload {
+        self.boot_info
+    }
+
+    /// Whether the arm has asked for a restart into the bootloader
+    ///
+    /// Firmware checks this after sending the reply to a message, and resets
+    /// into its bootloader once the acknowledgement is out.
+    pub fn bootloader_requested(&self) -> bool {
+        self.bootloader_requested
+    }
+
+    /// Announcement to send once after reset
+    ///
+    /// `crash` is the record left by the previous run, if it crashed (see
+    /// [`crash`](crate::crash)); the host reports it as a `JointCrashed` event.
+    pub fn boot_announcement(&self, crash: Option<CrashRecord>) -> Message {
+        if let Some(record) = crash {
+            self.log(JointEvent::Restarted(record));
+        }
+        Message {
+            header: Header {
+                source_id: self.id,
+                target_id: ARM_DEVICE_ID,
+                msg_id: 0,
+            },
+            payload: Payload::Boot(BootPayload {
+                entity_type: self.entity_type,
+                crash,
+            }),
+        }
+    }
+
+    /// Seed the discovery jitter generator
+    ///
+    /// Without a seed, jitter is derived from the joint ID only, so two boards
+    /// flashed with the same ID would pick the same delay. Firmware should pass
+    /// something unique per unit (MCU UID, ADC noise, ...).
+    pub fn set_discovery_seed(&mut self, seed: u32) {
+        self.rng_state = Self::seed_rng(self.id, seed);
+    }
+
+    /// Backoff before answering a discovery request, in microseconds
+    ///
+    /// The delay is an ID-derived slot (`DISCOVERY_SLOT_US` wide) plus random
+    /// jitter below `DISCOVERY_JITTER_US`, so joints on a fully populated bus
+    /// spread their replies instead of arbitrating in one burst.
+    pub fn discovery_backoff_us(&mut self) -> u32 {
+        let id = self.id as u32;
+        let slot = (id ^ (id >> 4) ^ (id >> 8)) % DISCOVERY_SLOTS;
+        slot * DISCOVERY_SLOT_US + self.next_random() % DISCOVERY_JITTER_US
+    }
+
+    /// Drive time-dependent work and return a message to send, if any
+    ///
+    /// Currently this releases [fault reports](Self::update_load), the
+    /// results of [self-tests](Self::self_test_running), the `StreamAck` of
+    /// a [stream](Self::handle_message) that went idle and delayed discovery
+    /// replies, and stops the joint when its
+    /// [`SafetyEnable`](Self::require_safety_enable) ran out or the supply
+    /// [browned out](Self::update_power); the time also stamps flight
+    /// recorder entries and advances the [usage counters](Self::statistics).
+    /// Call it regularly from the firmware main loop with a monotonic
+    /// microsecond timestamp.
+    pub fn poll(&mut self, now_us: u64) -> Option<Message> {
+        if self.may_drive() {
+            self.motor_on_us += now_us.saturating_sub(self.now_us);
+        }
+        self.now_us = now_us;
+        self.check_safety_enable();
+        self.check_power();
+        if let Some(report) = self.pending_fault.take() {
+            return Some(Message {
+                header: Header {
+                    source_id: self.id,
+                    target_id: ARM_DEVICE_ID,
+                    msg_id: 0,
+                },
+                payload: Payload::FaultReport(report),
+            });
+        }
+        if let Some(message) = self.check_self_test() {
+            return Some(message);
+        }
+        if let Some(message) = self.stream_ack() {
+            return Some(message);
+        }
+        let pending = self.pending_hello.as_mut()?;
+        let due = *pending.due_us.get_or_insert(now_us + pending.delay_us as u64);
+        if now_us < due {
+            return None;
+        }
+
+        let pending = self.pending_hello.take()?;
+        Some(Message {
+            header: Header {
+                source_id: self.id,
+                target_id: pending.reply_to,
+                msg_id: pending.msg_id,
+            },
+            payload: Payload::Hello(HelloPayload {
+                entity_type: self.entity_type,
+                mode: self.boot_info.mode,
+                capabilities: self.capabilities,
+                schema: SCHEMA_HASH,
+            }),
+        })
+    }
+
+    fn seed_rng(id: DeviceId, seed: u32) -> u32 {
+        // xorshift state must never be zero
+        let state = seed ^ ((id as u32) << 16 | id as u32) ^ 0x9E37_79B9;
+        if state == 0 { 0x9E37_79B9 } else { state }
+    }
+
+    fn next_random(&mut self) -> u32 {
+        // xorshift32
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng_state = x;
+        x
+    }
+
     /// Returns the current lifecycle state of the Joint.
     pub fn state(&self) -> LifecycleState {
         self.state
@@ -84,22 +1424,290 @@ impl Joint {
         let joint = Self::new(device_id);
         let transport = crate::transport::CanFdTransport::new(fdcan, rx_pin, tx_pin, irqs, config)?;
 
-        Ok((joint, transport))
+        Ok((joint, transport))
+    }
+
+    /// Accept a motion target if the joint is active, homed and within limits
+    fn set_target(&mut self, msg_id: MessageId, target: SetTargetPayloadV2) -> Payload {
+        match self.state {
+            LifecycleState::Active if !self.position_valid => Payload::Nack {
+                id: msg_id,
+                error: ERROR_POSITION_UNKNOWN,
+            },
+            LifecycleState::Active if self.brake_engaged() => Payload::Nack {
+                id: msg_id,
+                error: ERROR_BRAKE_ENGAGED,
+            },
+            LifecycleState::Active
+                if !matches!(self.control_mode, ControlMode::Position | ControlMode::CyclicPosition) =>
+            {
+                Payload::Nack {
+                    id: msg_id,
+                    error: ERROR_INVALID_STATE, // not in position mode
+                }
+            }
+            LifecycleState::Active => {
+                if self.limits.is_some_and(|l| !l.allows(target.target_angle, target.max_velocity)) {
+                    Payload::Nack {
+                        id: msg_id,
+                        error: ERROR_LIMIT_VIOLATION,
+                    }
+                } else {
+                    if self.velocity.abs() < SAFETY_STOP_VELOCITY_DPS {
+                        self.move_count = self.move_count.wrapping_add(1);
+                    }
+                    // Firmware picks the target up through `target()`
+                    match self.control_mode {
+                        ControlMode::CyclicPosition => self.buffered_target = Some(target),
+                        _ => self.target = Some(target),
+                    }
+                    Payload::Ack(msg_id)
+                }
+            }
+            _ => Payload::Nack { 
+                id: msg_id, 
+                error: ERROR_INVALID_STATE
+            }
+        }
+    }
+
+    fn set_control_mode(&mut self, msg_id: MessageId, mode: ControlMode) -> Payload {
+        match self.state {
+            LifecycleState::Active | LifecycleState::Calibrating => Payload::Nack {
+                id: msg_id,
+                error: ERROR_INVALID_STATE, // deactivate first
+            },
+            _ => {
+                // Setpoints of the old mode must not carry over
+                self.control_mode = mode;
+                self.target = None;
+                self.buffered_target = None;
+                self.commanded_velocity = 0.0;
+                self.commanded_torque = 0.0;
+                if let Some(controller) = self.controller.as_mut() {
+                    controller.reset();
+                }
+                Payload::Ack(msg_id)
+            }
+        }
+    }
+
+    /// Why a `SetVelocity`/`SetTorque` for `mode` is refused, if it is;
+    /// `allowed` is whether the value passed its checks
+    fn direct_command_error(&self, mode: ControlMode, allowed: bool) -> Option<u16> {
+        match self.state {
+            LifecycleState::Active if !self.position_valid => Some(ERROR_POSITION_UNKNOWN),
+            LifecycleState::Active if self.brake_engaged() => Some(ERROR_BRAKE_ENGAGED),
+            LifecycleState::Active if self.control_mode != mode => Some(ERROR_INVALID_STATE),
+            LifecycleState::Active if !allowed => Some(ERROR_LIMIT_VIOLATION),
+            LifecycleState::Active => None,
+            _ => Some(ERROR_INVALID_STATE),
+        }
+    }
+
+    fn configure_gains(&mut self, msg_id: MessageId, gains: ControlGains) -> Payload {
+        if !gains.is_valid() {
+            return Payload::Nack {
+                id: msg_id,
+                error: ERROR_INVALID_GAINS,
+            };
+        }
+        // Integrals built up under the old gains do not carry over
+        let mut controller = CascadedController::new(gains);
+        controller.set_schedule(self.gain_schedule);
+        self.controller = Some(controller);
+        Payload::Ack(msg_id)
+    }
+
+    fn set_gain_schedule_entry(&mut self, msg_id: MessageId, entry: &GainScheduleEntry) -> Payload {
+        if !entry.gains.is_valid() || !entry.region.is_valid() || !self.gain_schedule.set(entry) {
+            return Payload::Nack {
+                id: msg_id,
+                error: ERROR_INVALID_GAINS,
+            };
+        }
+        if let Some(controller) = self.controller.as_mut() {
+            controller.set_schedule(self.gain_schedule);
+        }
+        Payload::Ack(msg_id)
     }
 
     /// The core state machine logic. Processes an incoming message and returns a response.
     /// This function is the heart of the firmware's command processing.
+    ///
+    /// State changes and refusals are reported to the [logger](Self::set_logger),
+    /// and the command, its reply and state changes are kept in the
+    /// [flight recorder](Self::set_flight_recorder).
+    ///
+    /// Commands streamed with `STREAM_MSG_ID_FLAG` in their message ID are
+    /// not acknowledged one by one: every `STREAM_ACK_INTERVAL`th reply is a
+    /// `StreamAck` for all of them, counting the ones missing from the
+    /// sequence, and [`poll`](Self::poll) sends one once the stream has been
+    /// idle for `STREAM_ACK_IDLE_US`. Refusals are still sent right away.
     pub fn handle_message(&mut self, msg: &Message) -> Option<Message> {
-        // Check if the message is targeted to this joint
-        if msg.header.target_id != self.id {
+        self.note_command(msg);
+        let reply = self.dispatch(msg);
+        if let Some(reply) = &reply {
+            self.note_reply(msg, reply);
+        }
+        if msg.header.msg_id & STREAM_MSG_ID_FLAG == 0 || msg.header.target_id != self.id {
+            return reply;
+        }
+        self.note_streamed(msg);
+        match reply {
+            Some(Message { payload: Payload::Ack(_), .. }) => self.stream_ack(),
+            reply => reply,
+        }
+    }
+
+    /// Count a streamed command, and the ones missing before it
+    fn note_streamed(&mut self, msg: &Message) {
+        let stream = &mut self.stream;
+        if let Some(last_msg_id) = stream.last_msg_id {
+            let gap = msg.header.msg_id.wrapping_sub(last_msg_id).wrapping_sub(1) & !STREAM_MSG_ID_FLAG;
+            // A command from behind means the host started over, not a loss
+            if gap < STREAM_MSG_ID_FLAG / 2 {
+                stream.dropped = stream.dropped.saturating_add(gap);
+            }
+        }
+        stream.reply_to = msg.header.source_id;
+        stream.last_msg_id = Some(msg.header.msg_id);
+        stream.pending += 1;
+        stream.received_us = self.now_us;
+    }
+
+    /// The `StreamAck` for the commands streamed so far, once one is due
+    fn stream_ack(&mut self) -> Option<Message> {
+        let stream = &mut self.stream;
+        let idle = self.now_us >= stream.received_us + STREAM_ACK_IDLE_US;
+        if stream.pending == 0 || (stream.pending < STREAM_ACK_INTERVAL && !idle) {
+            return None;
+        }
+        stream.pending = 0;
+        let dropped = core::mem::take(&mut stream.dropped);
+        let payload = Payload::StreamAck { last_msg_id: stream.last_msg_id?, dropped };
+        Some(Message {
+            header: Header {
+                source_id: self.id,
+                target_id: stream.reply_to,
+                msg_id: 0,
+            },
+            payload,
+        })
+    }
+
+    fn dispatch(&mut self, msg: &Message) -> Option<Message> {
+        if let Payload::SetTargetBatch(batch) = &msg.payload {
+            return self.take_batch_target(msg, batch);
+        }
+
+        // Broadcasts: discovery, whose reply is delayed and released later by
+        // `poll()`, cycle sync, and node ID assignment while unaddressed
+        if msg.header.target_id == BROADCAST_ADDRESS {
+            match msg.payload {
+                Payload::Discover if !self.is_unaddressed() => {
+                    let delay_us = self.discovery_backoff_us();
+                    self.pending_hello = Some(PendingHello {
+                        reply_to: msg.header.source_id,
+                        msg_id: msg.header.msg_id,
+                        delay_us,
+                        due_us: None,
+                    });
+                }
+                Payload::Sync { cycle_counter } if !self.is_unaddressed() => {
+                    return self.sync(msg, cycle_counter);
+                }
+                Payload::AssignId(assign)
+                    if self.is_unaddressed()
+                        && assign.serial_number == self.serial_number
+                        && !matches!(assign.new_id, BROADCAST_ADDRESS | ARM_DEVICE_ID | UNADDRESSED_DEVICE_ID) =>
+                {
+                    self.id = assign.new_id;
+                    self.stored_id = Some(assign.new_id);
+                    return Some(Message {
+                        header: Header {
+                            source_id: self.id,
+                            target_id: msg.header.source_id,
+                            msg_id: msg.header.msg_id,
+                        },
+                        payload: Payload::Ack(msg.header.msg_id),
+                    });
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        // Check if the message is targeted to this joint; unaddressed joints
+        // share their ID, so they never answer directly
+        if msg.header.target_id != self.id || self.is_unaddressed() {
             return None;
         }
 
         let response_payload = match &msg.payload {
+            Payload::RequestStatus => {
+                let error_code = if self.boot_info.mode == BootMode::Bootloader {
+                    ERROR_IN_BOOTLOADER
+                } else if self.overheated() {
+                    ERROR_OVERTEMPERATURE
+                } else if self.browned_out() {
+                    ERROR_UNDERVOLTAGE
+                } else if !self.position_valid {
+                    ERROR_POSITION_UNKNOWN
+                } else {
+                    0
+                };
+                Some(Payload::JointStatus { state: self.state, error_code })
+            }
+            Payload::RequestBootInfo => Some(Payload::BootInfo(self.boot_info)),
+            Payload::RequestFlightRecorder { from } if self.flight_recorder.is_some() => {
+                self.flight_recorder.as_ref().map(|recorder| Payload::FlightRecorder(recorder.chunk(*from)))
+            }
+            Payload::EnterBootloader if self.boot_info.mode == BootMode::Bootloader => {
+                Some(Payload::Ack(msg.header.msg_id))
+            }
+            Payload::EnterBootloader => {
+                match self.state {
+                    LifecycleState::Active => Some(Payload::Nack {
+                        id: msg.header.msg_id,
+                        error: ERROR_INVALID_STATE // deactivate first
+                    }),
+                    _ => {
+                        // Firmware resets once the Ack is sent
+                        self.bootloader_requested = true;
+                        Some(Payload::Ack(msg.header.msg_id))
+                    }
+                }
+            }
+            _ if self.boot_info.mode == BootMode::Bootloader => Some(Payload::Nack {
+                id: msg.header.msg_id,
+                error: ERROR_IN_BOOTLOADER,
+            }),
+            Payload::SafetyEnable { token, ttl_ms } => {
+                Some(self.refresh_safety_enable(msg.header.msg_id, *token, *ttl_ms))
+            }
+            Payload::Activate
+            | Payload::SetTarget(_)
+            | Payload::SetTargetV2(_)
+            | Payload::SetVelocity { .. }
+            | Payload::SetTorque { .. }
+            | Payload::SetFeedforward { .. }
+            | Payload::Home
+            | Payload::StartHoming(_)
+                if !self.safety_enabled() =>
+            {
+                Some(Payload::Nack { id: msg.header.msg_id, error: ERROR_SAFETY_ENABLE })
+            }
+            Payload::Activate if self.overheated() => {
+                Some(Payload::Nack { id: msg.header.msg_id, error: ERROR_OVERTEMPERATURE })
+            }
+            Payload::Activate if self.browned_out() => {
+                Some(Payload::Nack { id: msg.header.msg_id, error: ERROR_UNDERVOLTAGE })
+            }
             Payload::Configure => {
                 match self.state {
                     LifecycleState::Unconfigured => {
-                        self.state = LifecycleState::Inactive;
+                        self.set_state(LifecycleState::Inactive);
                         Some(Payload::Ack(msg.header.msg_id))
                     }
                     _ => Some(Payload::Nack { 
@@ -111,7 +1719,14 @@ impl Joint {
             Payload::Activate => {
                 match self.state {
                     LifecycleState::Inactive => {
-                        self.state = LifecycleState::Active;
+                        self.set_state(LifecycleState::Active);
+                        // Computed for wherever the arm was; start without
+                        self.feedforward = 0.0;
+                        // Start still in every mode
+                        self.target = None;
+                        self.buffered_target = None;
+                        self.commanded_velocity = 0.0;
+                        self.commanded_torque = 0.0;
                         Some(Payload::Ack(msg.header.msg_id))
                     }
                     _ => Some(Payload::Nack { 
@@ -123,7 +1738,9 @@ impl Joint {
             Payload::Deactivate => {
                 match self.state {
                     LifecycleState::Active => {
-                        self.state = LifecycleState::Inactive;
+                        self.set_state(LifecycleState::Inactive);
+                        self.target = None;
+                        self.buffered_target = None;
                         Some(Payload::Ack(msg.header.msg_id))
                     }
                     _ => Some(Payload::Nack { 
@@ -133,26 +1750,151 @@ impl Joint {
                 }
             }
             Payload::Reset => {
-                self.state = LifecycleState::Unconfigured;
+                // Also the emergency stop: hold the joint
+                self.set_state(LifecycleState::Unconfigured);
+                self.homing = None;
+                self.target = None;
+                self.buffered_target = None;
+                self.brake_engaged = true;
                 Some(Payload::Ack(msg.header.msg_id))
             }
-            Payload::SetTarget(_target) => {
+            Payload::EngageBrake if self.capabilities.contains(Capabilities::BRAKE) => {
+                self.brake_engaged = true;
+                Some(Payload::Ack(msg.header.msg_id))
+            }
+            Payload::ReleaseBrake if self.capabilities.contains(Capabilities::BRAKE) => {
                 match self.state {
-                    LifecycleState::Active => {
-                        // In a real implementation, this would set the target angle and velocity
+                    LifecycleState::Unconfigured | LifecycleState::Error => Some(Payload::Nack {
+                        id: msg.header.msg_id,
+                        error: ERROR_INVALID_STATE, // configure first
+                    }),
+                    _ => {
+                        self.brake_engaged = false;
                         Some(Payload::Ack(msg.header.msg_id))
                     }
-                    _ => Some(Payload::Nack { 
-                        id: msg.header.msg_id, 
-                        error: 4 // Invalid state for set target
+                }
+            }
+            Payload::SetTarget(target) if self.accept_v1 => {
+                Some(self.set_target(msg.header.msg_id, (*target).into()))
+            }
+            Payload::SetTargetV2(target) => Some(self.set_target(msg.header.msg_id, *target)),
+            Payload::Home => Some(self.start_homing(msg.header.msg_id, HomingConfig::default())),
+            Payload::StartHoming(config) => Some(self.start_homing(msg.header.msg_id, *config)),
+            Payload::StopCalibration if self.homing.is_some() => {
+                self.abort_homing();
+                Some(Payload::Ack(msg.header.msg_id))
+            }
+            Payload::SetLimits(limits) => {
+                if self.position_valid {
+                    self.limits = Some(*limits);
+                    Some(Payload::Ack(msg.header.msg_id))
+                } else {
+                    // Limits are meaningless until position is re-established
+                    Some(Payload::Nack {
+                        id: msg.header.msg_id,
+                        error: ERROR_POSITION_UNKNOWN,
                     })
                 }
             }
+            Payload::SetEncoderOffset(offset) => {
+                match self.state {
+                    LifecycleState::Active | LifecycleState::Calibrating => Some(Payload::Nack {
+                        id: msg.header.msg_id,
+                        error: ERROR_INVALID_STATE // deactivate first
+                    }),
+                    _ => {
+                        // Limits and setpoints refer to the old zero
+                        self.encoder_offset = *offset;
+                        self.limits = None;
+                        self.target = None;
+                        Some(Payload::Ack(msg.header.msg_id))
+                    }
+                }
+            }
+            Payload::RequestMultiTurnPosition => Some(match self.position() {
+                Some(position) => Payload::MultiTurnPosition(position),
+                None => Payload::Nack {
+                    id: msg.header.msg_id,
+                    error: ERROR_POSITION_UNKNOWN,
+                },
+            }),
+            Payload::ConfigureTelemetry(config) => {
+                self.telemetry_config = Some(*config);
+                Some(Payload::Ack(msg.header.msg_id))
+            }
+            Payload::ConfigureThermal(limits) => Some(match limits.is_valid() {
+                true => {
+                    self.thermal_limits = Some(*limits);
+                    Payload::Ack(msg.header.msg_id)
+                }
+                false => Payload::Nack { id: msg.header.msg_id, error: ERROR_LIMIT_VIOLATION },
+            }),
+            Payload::ConfigurePower(limits) => Some(match limits.is_valid() {
+                true => {
+                    self.power_limits = Some(*limits);
+                    Payload::Ack(msg.header.msg_id)
+                }
+                false => Payload::Nack { id: msg.header.msg_id, error: ERROR_LIMIT_VIOLATION },
+            }),
+            Payload::RequestStatistics => Some(Payload::Statistics(self.statistics())),
+            Payload::RunSelfTest(request) => self.run_self_test(&msg.header, *request),
+            Payload::RequestPowerStatus => Some(match self.power {
+                Some(power) => Payload::PowerStatus(power),
+                None => Payload::Nack { id: msg.header.msg_id, error: ERROR_UNKNOWN_COMMAND },
+            }),
+            Payload::ConfigureAdaptive(config) if self.capabilities.contains(Capabilities::ADAPTIVE_CONTROL) => {
+                Some(self.configure_adaptive(msg.header.msg_id, *config))
+            }
+            Payload::ConfigureGains(gains) => Some(self.configure_gains(msg.header.msg_id, *gains)),
+            Payload::SetGainScheduleEntry(entry) => Some(self.set_gain_schedule_entry(msg.header.msg_id, entry)),
+            Payload::SetFeedforward { torque } => Some(match self.state {
+                LifecycleState::Active if torque.is_finite() => {
+                    self.feedforward = *torque;
+                    Payload::Ack(msg.header.msg_id)
+                }
+                LifecycleState::Active => Payload::Nack {
+                    id: msg.header.msg_id,
+                    error: ERROR_LIMIT_VIOLATION,
+                },
+                _ => Payload::Nack {
+                    id: msg.header.msg_id,
+                    error: ERROR_INVALID_STATE,
+                },
+            }),
+            Payload::SetControlMode(mode) if self.capabilities.contains(mode.capability()) => {
+                Some(self.set_control_mode(msg.header.msg_id, *mode))
+            }
+            Payload::SetVelocity { dps } if self.capabilities.contains(Capabilities::DIRECT_CONTROL) => {
+                let allowed = dps.is_finite() && self.limits.is_none_or(|l| dps.abs() <= l.max_velocity);
+                Some(match self.direct_command_error(ControlMode::Velocity, allowed) {
+                    Some(error) => Payload::Nack { id: msg.header.msg_id, error },
+                    None => {
+                        self.commanded_velocity = *dps;
+                        Payload::Ack(msg.header.msg_id)
+                    }
+                })
+            }
+            Payload::SetTorque { torque_nm } if self.capabilities.contains(Capabilities::DIRECT_CONTROL) => {
+                Some(match self.direct_command_error(ControlMode::Torque, torque_nm.is_finite()) {
+                    Some(error) => Payload::Nack { id: msg.header.msg_id, error },
+                    None => {
+                        self.commanded_torque = *torque_nm;
+                        Payload::Ack(msg.header.msg_id)
+                    }
+                })
+            }
+            Payload::ClearGainSchedule => {
+                self.gain_schedule.clear();
+                if let Some(controller) = self.controller.as_mut() {
+                    controller.set_schedule(self.gain_schedule);
+                }
+                Some(Payload::Ack(msg.header.msg_id))
+            }
             _ => {
                 // Unknown or unhandled command
                 Some(Payload::Nack { 
                     id: msg.header.msg_id, 
-                    error: 255 // Unknown command
+                    error: ERROR_UNKNOWN_COMMAND,
                 })
             }
         };
@@ -167,6 +1909,200 @@ impl Joint {
             payload,
         })
     }
+
+    /// Take the held target and sample the position at a `Sync`, in
+    /// `ControlMode::CyclicPosition` while Active
+    fn sync(&mut self, msg: &Message, cycle_counter: u32) -> Option<Message> {
+        if self.control_mode != ControlMode::CyclicPosition || self.state != LifecycleState::Active {
+            return None;
+        }
+        if let Some(target) = self.buffered_target.take() {
+            self.target = Some(target);
+        }
+        let position = self.position()?;
+        Some(Message {
+            header: Header { source_id: self.id, target_id: msg.header.source_id, msg_id: msg.header.msg_id },
+            payload: Payload::SyncSample(SyncSample { cycle_counter, position: position.degrees(), velocity: self.velocity }),
+        })
+    }
+
+    /// This joint's target from a `SetTargetBatch`, taken like a
+    /// `SetTargetV2`
+    ///
+    /// Joints see the batch whoever it is addressed to. The master it is
+    /// addressed to answers for the batch (with an `Ack` if it lists no
+    /// target for the master); the other joints only answer to refuse.
+    fn take_batch_target(&mut self, msg: &Message, batch: &TargetBatch) -> Option<Message> {
+        if self.is_unaddressed() {
+            return None;
+        }
+        let master = msg.header.target_id == self.id;
+        let Some(target) = batch.target_for(self.id) else {
+            return master.then_some(Message {
+                header: Header { source_id: self.id, target_id: msg.header.source_id, msg_id: msg.header.msg_id },
+                payload: Payload::Ack(msg.header.msg_id),
+            });
+        };
+        let own = Message {
+            header: Header { target_id: self.id, ..msg.header },
+            payload: Payload::SetTargetV2(target.into()),
+        };
+        match self.dispatch(&own)? {
+            Message { payload: Payload::Ack(_), .. } if !master => None,
+            reply => Some(reply),
+        }
+    }
+
+    /// [`handle_message`](Self::handle_message) plus the configuration
+    /// storage commands, which need the store
+    ///
+    /// Without a store those commands are refused with `ERROR_UNKNOWN_COMMAND`.
+    /// A node ID taken from `AssignId` is saved before it is acknowledged; if
+    /// that fails the joint keeps the ID until reset and answers
+    /// `ERROR_CONFIG_STORE`.
+    pub fn handle_with_store(&mut self, msg: &Message, store: &mut dyn ConfigStore) -> Option<Message> {
+        if let Payload::AssignId(_) = msg.payload {
+            let reply = self.handle_message(msg)?;
+            if self.config().save(store).is_err() {
+                let refusal = Message {
+                    header: reply.header,
+                    payload: Payload::Nack {
+                        id: msg.header.msg_id,
+                        error: ERROR_CONFIG_STORE,
+                    },
+                };
+                self.note_reply(msg, &refusal);
+                return Some(refusal);
+            }
+            return Some(reply);
+        }
+
+        if msg.header.target_id != self.id || self.boot_info.mode == BootMode::Bootloader {
+            return self.handle_message(msg);
+        }
+
+        let id = msg.header.msg_id;
+        let stored = |result: Result<(), ConfigStoreError>| match result {
+            Ok(()) => Payload::Ack(id),
+            Err(_) => Payload::Nack { id, error: ERROR_CONFIG_STORE },
+        };
+        let payload = match msg.payload {
+            Payload::SaveConfig => stored(self.config().save(store)),
+            Payload::LoadConfig | Payload::FactoryReset if self.state == LifecycleState::Active => {
+                Payload::Nack {
+                    id,
+                    error: ERROR_INVALID_STATE // deactivate first
+                }
+            }
+            Payload::LoadConfig => stored(JointConfig::load(store).map(|config| self.apply_config(&config))),
+            Payload::FactoryReset => stored(store.erase().map(|()| {
+                // The node ID in use stays until the next boot
+                self.stored_id = None;
+                self.apply_config(&JointConfig::default());
+            })),
+            _ => return self.handle_message(msg),
+        };
+        let reply = Message {
+            header: Header {
+                source_id: self.id,
+                target_id: msg.header.source_id,
+                msg_id: id,
+            },
+            payload,
+        };
+        self.note_command(msg);
+        self.note_reply(msg, &reply);
+        Some(reply)
+    }
+
+    /// Enable the motor `driver` for an `Activate` the joint would accept
+    ///
+    /// Returns `None` when the driver enabled (and for every other message),
+    /// so firmware tries it first and falls back to the regular handling,
+    /// then calls [`sync_driver`](Self::sync_driver):
+    ///
+    /// ```ignore
+    /// let reply = joint.handle_motor(&msg, &mut driver).or_else(|| joint.handle_message(&msg));
+    /// joint.sync_driver(&mut driver);
+    /// ```
+    ///
+    /// A driver that will not enable refuses the command with
+    /// `ERROR_MOTOR_FAULT` and the joint stays `Inactive`.
+    pub fn handle_motor(&mut self, msg: &Message, driver: &mut dyn MotorDriver) -> Option<Message> {
+        if msg.header.target_id != self.id
+            || self.is_unaddressed()
+            || self.boot_info.mode == BootMode::Bootloader
+            || !matches!(msg.payload, Payload::Activate)
+            || self.state != LifecycleState::Inactive
+        {
+            return None;
+        }
+
+        match driver.enable() {
+            Ok(()) => {
+                self.driver_enabled = true;
+                None
+            }
+            Err(_) => {
+                self.driver_faults = driver.faults();
+                let refusal = Message {
+                    header: Header {
+                        source_id: self.id,
+                        target_id: msg.header.source_id,
+                        msg_id: msg.header.msg_id,
+                    },
+                    payload: Payload::Nack {
+                        id: msg.header.msg_id,
+                        error: ERROR_MOTOR_FAULT,
+                    },
+                };
+                self.note_command(msg);
+                self.note_reply(msg, &refusal);
+                Some(refusal)
+            }
+        }
+    }
+
+    /// Answer the auxiliary I/O commands with the board's `io`
+    ///
+    /// Returns `None` for every other message (and in bootloader mode), so
+    /// firmware tries it first and falls back to the regular handling:
+    ///
+    /// ```ignore
+    /// let reply = joint.handle_aux_io(&msg, &mut io).or_else(|| joint.handle_message(&msg));
+    /// ```
+    ///
+    /// Without a handler the commands are refused with `ERROR_UNKNOWN_COMMAND`.
+    pub fn handle_aux_io(&mut self, msg: &Message, io: &mut dyn AuxIoHandler) -> Option<Message> {
+        if msg.header.target_id != self.id || self.is_unaddressed() || self.boot_info.mode == BootMode::Bootloader {
+            return None;
+        }
+
+        let id = msg.header.msg_id;
+        let result = match msg.payload {
+            Payload::SetDigitalOutput(output) => {
+                io.set_digital_output(output.channel, output.value).map(|()| Payload::Ack(id))
+            }
+            Payload::ReadDigitalInput(channel) => io
+                .read_digital_input(channel)
+                .map(|value| Payload::DigitalInput(DigitalIoPayload { channel, value })),
+            Payload::ReadAnalogInput(channel) => io
+                .read_analog_input(channel)
+                .map(|value| Payload::AnalogInput(AnalogInputPayload { channel, value })),
+            _ => return None,
+        };
+        let reply = Message {
+            header: Header {
+                source_id: self.id,
+                target_id: msg.header.source_id,
+                msg_id: id,
+            },
+            payload: result.unwrap_or_else(|e| Payload::Nack { id, error: e.code() }),
+        };
+        self.note_command(msg);
+        self.note_reply(msg, &reply);
+        Some(reply)
+    }
 }
 
 // ============================================================================
@@ -174,7 +2110,11 @@ impl Joint {
 // ============================================================================
 
 #[cfg(feature = "joint_api")]
-use crate::bus::{TransportLayer, EmbeddedTransport, TransportError};
+use crate::bus::{
+    AsyncEmbeddedTransport, AsyncTransportLayer, EmbeddedTransport, TransportError, TransportLayer,
+};
+#[cfg(feature = "joint_api")]
+use crate::protocol::TransportStats;
 
 #[cfg(feature = "joint_api")]
 impl Joint {
@@ -198,12 +2138,18 @@ impl Joint {
     ///     }
     /// }
     /// ```
-    pub fn process_transport<T: EmbeddedTransport>(
+    pub fn process_transport<T: EmbeddedTransport, const N: usize>(
         &mut self,
-        transport: &mut TransportLayer<T>,
+        transport: &mut TransportLayer<T, N>,
     ) -> Result<bool, TransportError<T::Error>> {
         // Try to receive a message
         if let Some(msg) = transport.receive_message()? {
+            // Bus statistics live in the transport, so answer them here
+            if let Some(response) = self.bus_stats_reply(&msg, transport.stats()) {
+                transport.send_message(&response)?;
+                return Ok(true);
+            }
+
             // Process it through the state machine
             if let Some(response) = self.handle_message(&msg) {
                 // Send the response
@@ -214,12 +2160,65 @@ impl Joint {
         Ok(false) // No message or not for us
     }
 
+    /// Async variant of [`process_transport`](Self::process_transport)
+    ///
+    /// Waits for the next message, processes it through the state machine
+    /// and sends the response if one is generated.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use irpc::{AsyncTransportLayer, Joint};
+    ///
+    /// # async fn run(can_transport: impl irpc::AsyncEmbeddedTransport) {
+    /// let mut joint = Joint::new(0x0010);
+    /// let mut transport = AsyncTransportLayer::new(can_transport);
+    ///
+    /// loop {
+    ///     if let Err(e) = joint.process_transport_async(&mut transport).await {
+    ///         // Handle transport error
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn process_transport_async<T: AsyncEmbeddedTransport, const N: usize>(
+        &mut self,
+        transport: &mut AsyncTransportLayer<T, N>,
+    ) -> Result<bool, TransportError<T::Error>> {
+        let msg = transport.receive_message().await?;
+
+        let response = match self.bus_stats_reply(&msg, transport.stats()) {
+            Some(response) => Some(response),
+            None => self.handle_message(&msg),
+        };
+
+        if let Some(response) = response {
+            transport.send_message(&response).await?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Build the reply to a `RequestBusStats` addressed to this joint
+    fn bus_stats_reply(&self, msg: &Message, stats: &TransportStats) -> Option<Message> {
+        if msg.header.target_id != self.id || !matches!(msg.payload, Payload::RequestBusStats) {
+            return None;
+        }
+        Some(Message {
+            header: Header {
+                source_id: self.id,
+                target_id: msg.header.source_id,
+                msg_id: msg.header.msg_id,
+            },
+            payload: Payload::BusStats(*stats),
+        })
+    }
+
     /// Convenience method: receive and handle message (without auto-response)
     ///
     /// This allows you to control when/how responses are sent.
-    pub fn receive_and_handle<T: EmbeddedTransport>(
+    pub fn receive_and_handle<T: EmbeddedTransport, const N: usize>(
         &mut self,
-        transport: &mut TransportLayer<T>,
+        transport: &mut TransportLayer<T, N>,
     ) -> Result<Option<Message>, TransportError<T::Error>> {
         if let Some(msg) = transport.receive_message()? {
             Ok(self.handle_message(&msg))
@@ -227,4 +2226,255 @@ impl Joint {
             Ok(None)
         }
     }
-}
\ No newline at end of file
+}
+// ============================================================================
+// Ready-made embassy task (embassy feature)
+// ============================================================================
+
+#[cfg(feature = "embassy")]
+use crate::config::{
+    JOINT_TELEMETRY_PERIOD_US, JOINT_UPDATE_PERIOD_US, JOINT_WATCHDOG_PERIOD_US,
+};
+#[cfg(feature = "embassy")]
+use embassy_futures::select::{select, Either};
+#[cfg(feature = "embassy")]
+use embassy_time::{Duration, Instant, Timer};
+
+/// Firmware callbacks driven by [`run_embassy`]
+///
+/// Every method has a no-op default, and `()` implements the trait, so a
+/// joint that only answers commands needs no hooks at all. Periods are
+/// associated constants so the loop needs no runtime configuration.
+#[cfg(feature = "embassy")]
+pub trait JointHooks {
+    /// Period of [`update`](Self::update), in microseconds
+    const UPDATE_PERIOD_US: u64 = JOINT_UPDATE_PERIOD_US;
+    /// Period of [`telemetry`](Self::telemetry), in microseconds
+    const TELEMETRY_PERIOD_US: u64 = JOINT_TELEMETRY_PERIOD_US;
+    /// Period of [`feed_watchdog`](Self::feed_watchdog), in microseconds
+    const WATCHDOG_PERIOD_US: u64 = JOINT_WATCHDOG_PERIOD_US;
+
+    /// Control tick: read sensors, run the control loop, drive the motor
+    ///
+    /// `now_us` counts from the start of the runner.
+    fn update(&mut self, _joint: &mut Joint, _now_us: u64) {}
+
+    /// Telemetry to send to the arm this period, if any
+    fn telemetry(&mut self, _joint: &Joint) -> Option<Payload> {
+        None
+    }
+
+    /// Feed the hardware watchdog
+    ///
+    /// Called from the runner loop itself, so the watchdog fires if the loop
+    /// stalls.
+    fn feed_watchdog(&mut self) {}
+
+    /// A receive or send failed; the runner carries on
+    fn on_transport_error<E: core::fmt::Debug>(&mut self, _error: &TransportError<E>) {}
+
+    /// Crash record left by the previous run, sent in the boot announcement
+    ///
+    /// Called once at startup; typically `CrashSlot::take()` on the slot
+    /// the panic and fault handlers write to.
+    fn crash_record(&mut self) -> Option<CrashRecord> {
+        None
+    }
+
+    /// Non-volatile store for the joint's configuration, if the board has one
+    ///
+    /// The runner restores the configuration from it before the boot
+    /// announcement and answers `SaveConfig`, `LoadConfig` and `FactoryReset`
+    /// with it.
+    fn config_store(&mut self) -> Option<&mut dyn ConfigStore> {
+        None
+    }
+
+    /// Auxiliary inputs and outputs, if the board has any
+    ///
+    /// The runner answers `SetDigitalOutput`, `ReadDigitalInput` and
+    /// `ReadAnalogInput` with it.
+    fn aux_io(&mut self) -> Option<&mut dyn AuxIoHandler> {
+        None
+    }
+
+    /// The motor power stage, if the runner should manage it
+    ///
+    /// The runner enables it on `Activate` with [`Joint::handle_motor`] and
+    /// keeps it in line with [`Joint::sync_driver`] after every message and
+    /// before each [`update`](Self::update). Control output goes through
+    /// [`Joint::command_motor`].
+    fn motor_driver(&mut self) -> Option<&mut dyn MotorDriver> {
+        None
+    }
+
+    /// The joint's position sensor, if the runner should read it
+    ///
+    /// Sampled with [`Joint::sample_encoder`] right before each
+    /// [`update`](Self::update).
+    fn encoder(&mut self) -> Option<&mut dyn Encoder> {
+        None
+    }
+}
+
+#[cfg(feature = "embassy")]
+impl JointHooks for () {}
+
+/// Run a joint forever: commands, control ticks, telemetry and watchdog
+///
+/// Takes everything by value, so it can be the whole body of an
+/// `#[embassy_executor::task]` and the state lives in the task's static
+/// storage (the transport can come from a `StaticCell`). It first sends the
+/// [boot announcement](Joint::boot_announcement) with the hooks' crash
+/// record (after restoring the configuration from the hooks' store, if
+/// any), then waits for the next message or the next tick, whichever comes
+/// first:
+///
+/// - messages are answered like [`process_transport_async`](Joint::process_transport_async)
+/// - delayed discovery replies are released by [`poll`](Joint::poll)
+/// - `hooks` are called at their configured periods; missed ticks are
+///   skipped rather than replayed in a burst
+/// - the temperature in the hooks' `TelemetryStream` is passed to
+///   [`update_temperature`](Joint::update_temperature), and the joint's
+///   [`warnings`](Joint::warnings) are added to it
+///
+/// The transport's `receive` must be cancel-safe: it is dropped when a tick
+/// is due before a frame arrives.
+///
+/// # Example
+/// ```no_run
+/// use irpc::{AsyncTransportLayer, Joint};
+/// use irpc::transport::CanFdTransport;
+///
+/// #[embassy_executor::task]
+/// async fn joint_task(transport: CanFdTransport<'static>) -> ! {
+///     irpc::run_embassy(Joint::new(0x0010), AsyncTransportLayer::new(transport), ()).await
+/// }
+/// ```
+#[cfg(feature = "embassy")]
+pub async fn run_embassy<T, H, const N: usize>(mut joint: Joint, mut transport: AsyncTransportLayer<T, N>, mut hooks: H) -> !
+where
+    T: AsyncEmbeddedTransport,
+    H: JointHooks,
+{
+    let start = Instant::now();
+    let mut ticks = [
+        Tick::new(start, H::UPDATE_PERIOD_US),
+        Tick::new(start, H::TELEMETRY_PERIOD_US),
+        Tick::new(start, H::WATCHDOG_PERIOD_US),
+    ];
+    let mut telemetry_id: MessageId = 0;
+
+    if let Some(store) = hooks.config_store() {
+        // A blank store keeps the defaults
+        let _ = joint.restore_config(store);
+    }
+    let boot = joint.boot_announcement(hooks.crash_record());
+    if let Err(e) = transport.send_message(&boot).await {
+        hooks.on_transport_error(&e);
+    }
+
+    loop {
+        let deadline = ticks.iter().map(|t| t.next).min().unwrap_or(start);
+        let outcome = match select(transport.receive_message(), Timer::at(deadline)).await {
+            Either::First(received) => Some(received),
+            Either::Second(()) => None,
+        };
+
+        match outcome {
+            Some(Ok(msg)) => {
+                let response = joint
+                    .bus_stats_reply(&msg, transport.stats())
+                    .or_else(|| hooks.motor_driver().and_then(|driver| joint.handle_motor(&msg, driver)))
+                    .or_else(|| hooks.aux_io().and_then(|io| joint.handle_aux_io(&msg, io)))
+                    .or_else(|| match hooks.config_store() {
+                        Some(store) => joint.handle_with_store(&msg, store),
+                        None => joint.handle_message(&msg),
+                    });
+                if let Some(response) = response {
+                    if let Err(e) = transport.send_message(&response).await {
+                        hooks.on_transport_error(&e);
+                    }
+                }
+            }
+            Some(Err(e)) => hooks.on_transport_error(&e),
+            None => {}
+        }
+
+        let now = Instant::now();
+        let now_us = now.duration_since(start).as_micros();
+
+        if let Some(hello) = joint.poll(now_us) {
+            if let Err(e) = transport.send_message(&hello).await {
+                hooks.on_transport_error(&e);
+            }
+        }
+        if let Some(driver) = hooks.motor_driver() {
+            joint.sync_driver(driver);
+        }
+        if ticks[0].due(now) {
+            if let Some(encoder) = hooks.encoder() {
+                // A failure is recorded in the joint's state
+                let _ = joint.sample_encoder(encoder, now_us);
+            }
+            hooks.update(&mut joint, now_us);
+        }
+        if ticks[1].due(now) {
+            if let Some(mut payload) = hooks.telemetry(&joint) {
+                if let Payload::TelemetryStream(stream) = &mut payload {
+                    joint.update_temperature(stream.temperature_c);
+                    stream.warnings |= joint.warnings();
+                }
+                telemetry_id = telemetry_id.wrapping_add(1);
+                let message = Message {
+                    header: Header {
+                        source_id: joint.id(),
+                        target_id: ARM_DEVICE_ID,
+                        msg_id: telemetry_id,
+                    },
+                    payload,
+                };
+                if let Err(e) = transport.send_message(&message).await {
+                    hooks.on_transport_error(&e);
+                }
+            }
+        }
+        let watchdog_due = ticks[2].next;
+        if ticks[2].due(now) {
+            let late_us = (now - watchdog_due).as_micros();
+            if late_us > H::WATCHDOG_PERIOD_US {
+                joint.log(JointEvent::WatchdogLate { late_us });
+            }
+            hooks.feed_watchdog();
+        }
+    }
+}
+
+/// A periodic deadline of the runner
+#[cfg(feature = "embassy")]
+struct Tick {
+    next: Instant,
+    period: Duration,
+}
+
+#[cfg(feature = "embassy")]
+impl Tick {
+    fn new(start: Instant, period_us: u64) -> Self {
+        Self {
+            next: start,
+            period: Duration::from_micros(period_us.max(1)),
+        }
+    }
+
+    /// Whether the tick fired; advances to the next deadline after `now`
+    fn due(&mut self, now: Instant) -> bool {
+        if now < self.next {
+            return false;
+        }
+        self.next += self.period;
+        if self.next <= now {
+            self.next = now + self.period;
+        }
+        true
+    }
+}
@@ -0,0 +1,66 @@
+//! Joint-side diagnostics
+//!
+//! A [`Joint`](crate::Joint) reports what firmware authors usually end up
+//! instrumenting by hand: lifecycle transitions, refused commands with their
+//! reason, crashes and watchdog resets reported at boot, a stalled runner
+//! loop, a lapsed safety enable, collisions and brown-outs. Install a
+//! [`JointLogger`] with [`Joint::set_logger`](crate::Joint::set_logger); it
+//! is a plain function, so it works without an allocator:
+//!
+//! ```ignore
+//! fn log(joint: DeviceId, event: &JointEvent) {
+//!     rprintln!("joint {}: {:?}", joint, event);
+//! }
+//! joint.set_logger(Some(log));
+//! ```
+//!
+//! With the `defmt` feature, [`defmt_logger`] logs every event through
+//! defmt at a fitting level.
+
+use crate::protocol::{CollisionReaction, CrashRecord, DeviceId, FaultCause, LifecycleState, MessageId};
+
+/// Something worth logging that happened in a joint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum JointEvent {
+    /// The lifecycle state changed, on a command or a fault
+    StateChanged { from: LifecycleState, to: LifecycleState },
+    /// A command was refused with `error` (one of the `ERROR_*` codes or a
+    /// state error)
+    Nacked { command: &'static str, msg_id: MessageId, error: u16 },
+    /// The previous run ended in a crash or watchdog reset; reported when
+    /// the boot announcement is built
+    Restarted(CrashRecord),
+    /// The runner fed the watchdog `late_us` microseconds after it was due;
+    /// the loop stalled and the watchdog may be about to reset the MCU
+    WatchdogLate { late_us: u64 },
+    /// The `SafetyEnable` ran out while Active; the joint stops and
+    /// deactivates
+    SafetyEnableExpired,
+    /// A stall or overcurrent was detected while Active and the joint
+    /// reacted
+    Collision { cause: FaultCause, reaction: CollisionReaction },
+    /// The supply dropped below the brown-out voltage while Active; the
+    /// joint stops and deactivates
+    Brownout,
+}
+
+/// Receives a joint's [`JointEvent`]s along with its node ID
+pub type JointLogger = fn(DeviceId, &JointEvent);
+
+/// Log events through defmt: faults, restarts and safety stops as errors,
+/// refusals and stalls as warnings, transitions as info
+#[cfg(feature = "defmt")]
+pub fn defmt_logger(joint: DeviceId, event: &JointEvent) {
+    match event {
+        JointEvent::StateChanged { to: LifecycleState::Error, .. }
+        | JointEvent::Restarted(_)
+        | JointEvent::SafetyEnableExpired
+        | JointEvent::Collision { .. }
+        | JointEvent::Brownout => {
+            defmt::error!("joint {=u16}: {}", joint, event)
+        }
+        JointEvent::Nacked { .. } | JointEvent::WatchdogLate { .. } => defmt::warn!("joint {=u16}: {}", joint, event),
+        JointEvent::StateChanged { .. } => defmt::info!("joint {=u16}: {}", joint, event),
+    }
+}
@@ -0,0 +1,287 @@
+//! Serial-chain arm model for host-side dynamics
+//!
+//! An [`ArmModel`] describes the arm as a chain of revolute joints with
+//! standard Denavit–Hartenberg parameters and the mass of each link. From
+//! joint angles it computes the torque each joint needs to hold the links
+//! against gravity, which the orchestrator streams to the joints as
+//! `SetFeedforward` so their control loops need not integrate it up:
+//!
+//! ```no_run
+//! use irpc::kinematics::{ArmModel, DhParameters, Link};
+//! # async fn example(mut arm: irpc::ArmOrchestrator) -> Result<(), irpc::kinematics::KinematicsError> {
+//! let upper_arm = Link {
+//!     joint_id: 0x0010,
+//!     dh: DhParameters { a: 0.3, alpha: 0.0, d: 0.0, theta_offset: 0.0 },
+//!     mass: 2.0,
+//!     center_of_mass: [-0.15, 0.0, 0.0],
+//! };
+//! // Shoulder axis horizontal: gravity acts in the link plane
+//! arm.set_arm_model(ArmModel::new(vec![upper_arm]).with_gravity([0.0, -9.806_65, 0.0]));
+//! arm.send_gravity_feedforward(&[45.0]).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The same model drives Cartesian jogging: [`ArmModel::joint_rates`] turns
+//! a speed along a [`CartesianAxis`] into joint velocities, which
+//! `ArmOrchestrator::jog` streams as targets.
+//!
+//! Lengths are in metres, masses in kilograms and angles in degrees, like
+//! the rest of the protocol. Link `i` rotates about the z axis of frame
+//! `i - 1` (frame 0 is the base); its centre of mass is given in frame `i`.
+//! The flange is the origin of the last frame.
+
+use crate::protocol::{DeviceId, ProtocolError};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Standard gravity along the base frame's -z axis, in m/s²
+pub const STANDARD_GRAVITY: [f64; 3] = [0.0, 0.0, -9.806_65];
+
+/// Damping of the least-squares joint rate solution, in metres: keeps the
+/// rates bounded near singular poses at the cost of some accuracy there
+const RATE_DAMPING: f64 = 0.01;
+
+/// Direction the flange moves in, in the base frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CartesianAxis {
+    /// Translation along x
+    X,
+    /// Translation along y
+    Y,
+    /// Translation along z
+    Z,
+    /// Rotation about x
+    Rx,
+    /// Rotation about y
+    Ry,
+    /// Rotation about z
+    Rz,
+}
+
+impl CartesianAxis {
+    fn unit(self) -> [f64; 3] {
+        match self {
+            Self::X | Self::Rx => [1.0, 0.0, 0.0],
+            Self::Y | Self::Ry => [0.0, 1.0, 0.0],
+            Self::Z | Self::Rz => [0.0, 0.0, 1.0],
+        }
+    }
+
+    /// Whether the axis is a rotation
+    pub fn is_rotation(self) -> bool {
+        matches!(self, Self::Rx | Self::Ry | Self::Rz)
+    }
+}
+
+/// Arm model and feedforward errors
+#[derive(Error, Debug)]
+pub enum KinematicsError {
+    #[error("Expected {expected} joint angles, got {got}")]
+    AngleCount { expected: usize, got: usize },
+    #[error("No arm model set")]
+    NoModel,
+    #[error("Joint {0} is not part of the arm")]
+    UnknownJoint(DeviceId),
+    #[error("Protocol error: {0}")]
+    Protocol(#[from] ProtocolError),
+}
+
+/// Standard Denavit–Hartenberg parameters of one link
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct DhParameters {
+    /// Link length along x, in metres
+    pub a: f64,
+    /// Link twist about x, in degrees
+    pub alpha: f64,
+    /// Link offset along z, in metres
+    pub d: f64,
+    /// Angle added to the joint position, in degrees
+    pub theta_offset: f64,
+}
+
+/// One joint of the chain and the link it moves
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Link {
+    pub joint_id: DeviceId,
+    pub dh: DhParameters,
+    /// Link mass in kilograms, payload included
+    pub mass: f64,
+    /// Centre of mass in the link's own frame, in metres
+    pub center_of_mass: [f64; 3],
+}
+
+/// Rotation and origin of a link frame in the base frame
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    rotation: [[f64; 3]; 3],
+    origin: [f64; 3],
+}
+
+impl Frame {
+    const BASE: Self = Self {
+        rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        origin: [0.0; 3],
+    };
+
+    /// The next frame, `theta` degrees into the joint
+    fn then(&self, dh: &DhParameters, theta: f64) -> Self {
+        let (st, ct) = (theta + dh.theta_offset).to_radians().sin_cos();
+        let (sa, ca) = dh.alpha.to_radians().sin_cos();
+        let local = [[ct, -st * ca, st * sa], [st, ct * ca, -ct * sa], [0.0, sa, ca]];
+        let offset = [dh.a * ct, dh.a * st, dh.d];
+
+        let mut rotation = [[0.0; 3]; 3];
+        for (row, out) in self.rotation.iter().zip(rotation.iter_mut()) {
+            for (col, value) in out.iter_mut().enumerate() {
+                *value = (0..3).map(|k| row[k] * local[k][col]).sum();
+            }
+        }
+        Self { rotation, origin: add(self.origin, self.rotate(offset)) }
+    }
+
+    fn rotate(&self, v: [f64; 3]) -> [f64; 3] {
+        self.rotation.map(|row| dot(row, v))
+    }
+
+    /// Joint axis of the following link
+    fn z_axis(&self) -> [f64; 3] {
+        self.rotation.map(|row| row[2])
+    }
+}
+
+fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn scale(a: [f64; 3], k: f64) -> [f64; 3] {
+    a.map(|v| v * k)
+}
+
+/// `x` with `m x = b`, for symmetric positive definite `m`
+fn solve3(m: [[f64; 3]; 3], b: [f64; 3]) -> [f64; 3] {
+    let det = dot(m[0], cross(m[1], m[2]));
+    // Columns of the inverse times det are the cross products of the rows
+    let inverse = [cross(m[1], m[2]), cross(m[2], m[0]), cross(m[0], m[1])];
+    scale(add(add(scale(inverse[0], b[0]), scale(inverse[1], b[1])), scale(inverse[2], b[2])), 1.0 / det)
+}
+
+/// Kinematic chain with link masses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArmModel {
+    links: Vec<Link>,
+    /// Gravity in the base frame, in m/s²
+    gravity: [f64; 3],
+}
+
+impl ArmModel {
+    /// Chain from the base outwards, under [`STANDARD_GRAVITY`]
+    pub fn new(links: Vec<Link>) -> Self {
+        Self { links, gravity: STANDARD_GRAVITY }
+    }
+
+    /// Gravity for an arm mounted other than upright (wall, ceiling)
+    pub fn with_gravity(mut self, gravity: [f64; 3]) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn links(&self) -> &[Link] {
+        &self.links
+    }
+
+    /// Joint IDs from the base outwards; angles are passed in this order
+    pub fn joint_ids(&self) -> Vec<DeviceId> {
+        self.links.iter().map(|link| link.joint_id).collect()
+    }
+
+    /// Frames 0 (base) to n for the given joint angles
+    fn frames(&self, angles: &[f32]) -> Result<Vec<Frame>, KinematicsError> {
+        if angles.len() != self.links.len() {
+            return Err(KinematicsError::AngleCount { expected: self.links.len(), got: angles.len() });
+        }
+        let mut frames = Vec::with_capacity(self.links.len() + 1);
+        frames.push(Frame::BASE);
+        for (link, angle) in self.links.iter().zip(angles) {
+            let next = frames[frames.len() - 1].then(&link.dh, *angle as f64);
+            frames.push(next);
+        }
+        Ok(frames)
+    }
+
+    /// Position of the flange in the base frame at `angles`, in metres
+    pub fn flange_position(&self, angles: &[f32]) -> Result<[f64; 3], KinematicsError> {
+        let frames = self.frames(angles)?;
+        Ok(frames[frames.len() - 1].origin)
+    }
+
+    /// Joint velocities in degrees/second that move the flange along `axis`
+    /// at `speed` (m/s, or degrees/second for rotations) from `angles`
+    ///
+    /// A damped least-squares solution: motion the chain cannot make is
+    /// approximated as closely as it allows, and near singular poses the
+    /// rates stay bounded while the flange falls behind the request.
+    pub fn joint_rates(&self, angles: &[f32], axis: CartesianAxis, speed: f64) -> Result<Vec<f32>, KinematicsError> {
+        let frames = self.frames(angles)?;
+        let flange = frames[frames.len() - 1].origin;
+        // Row of the Jacobian along each base axis, one column per joint
+        let columns: Vec<[f64; 3]> = frames[..self.links.len()]
+            .iter()
+            .map(|frame| {
+                if axis.is_rotation() {
+                    frame.z_axis()
+                } else {
+                    cross(frame.z_axis(), sub(flange, frame.origin))
+                }
+            })
+            .collect();
+        let velocity = scale(axis.unit(), if axis.is_rotation() { speed.to_radians() } else { speed });
+
+        let mut jjt = [[0.0; 3]; 3];
+        for (r, row) in jjt.iter_mut().enumerate() {
+            for (c, value) in row.iter_mut().enumerate() {
+                *value = columns.iter().map(|column| column[r] * column[c]).sum::<f64>();
+            }
+            row[r] += RATE_DAMPING * RATE_DAMPING;
+        }
+        let y = solve3(jjt, velocity);
+        Ok(columns.iter().map(|column| dot(*column, y).to_degrees() as f32).collect())
+    }
+
+    /// Torque each joint must apply to hold the arm still at `angles`, in
+    /// newton-metres (positive along the joint's z axis)
+    pub fn gravity_torques(&self, angles: &[f32]) -> Result<Vec<f32>, KinematicsError> {
+        let frames = self.frames(angles)?;
+        let centers: Vec<[f64; 3]> = self
+            .links
+            .iter()
+            .zip(&frames[1..])
+            .map(|(link, frame)| add(frame.origin, frame.rotate(link.center_of_mass)))
+            .collect();
+
+        let torques = (0..self.links.len())
+            .map(|i| {
+                let (axis, origin) = (frames[i].z_axis(), frames[i].origin);
+                let moment = self.links[i..]
+                    .iter()
+                    .zip(&centers[i..])
+                    .map(|(link, center)| cross(sub(*center, origin), self.gravity.map(|g| g * link.mass)))
+                    .fold([0.0; 3], add);
+                -dot(axis, moment) as f32
+            })
+            .collect();
+        Ok(torques)
+    }
+}
@@ -0,0 +1,101 @@
+//! Request latency histograms
+//!
+//! With latency tracking on
+//! ([`CommunicationManager::set_latency_tracking`](crate::CommunicationManager::set_latency_tracking)),
+//! the manager records the round-trip time of every answered request, per
+//! payload kind, into a [`LatencyHistogram`]:
+//!
+//! ```
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let comm = irpc::CommunicationManager::new();
+//! comm.set_latency_tracking(true);
+//! // ... run the arm ...
+//! for (kind, histogram) in comm.latency_histograms() {
+//!     println!("{}: {} requests, p99 {:?}", kind, histogram.count(), histogram.quantile(0.99));
+//! }
+//! # }
+//! ```
+//!
+//! Buckets double in width from 1 µs, so quantiles are upper bounds within
+//! a factor of two; `min`, `max` and `mean` are exact.
+
+use std::time::Duration;
+
+/// Buckets up to 2^26 µs (about 67 s); slower replies land in the last one
+const BUCKETS: usize = 27;
+
+/// Distribution of request round-trip times
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// Bucket `i` counts latencies up to 2^i µs
+    buckets: [u64; BUCKETS],
+    count: u64,
+    total: Duration,
+    min: Duration,
+    max: Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self { buckets: [0; BUCKETS], count: 0, total: Duration::ZERO, min: Duration::MAX, max: Duration::ZERO }
+    }
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        let micros = latency.as_micros().max(1);
+        let bucket = (u128::BITS - (micros - 1).leading_zeros()) as usize;
+        self.buckets[bucket.min(BUCKETS - 1)] += 1;
+        self.count += 1;
+        self.total += latency;
+        self.min = self.min.min(latency);
+        self.max = self.max.max(latency);
+    }
+
+    /// Requests recorded
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn min(&self) -> Option<Duration> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_nanos((self.total.as_nanos() / self.count as u128) as u64))
+    }
+
+    /// Upper bound of the latency below which a fraction `q` of requests fall
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (upper, count) in self.buckets() {
+            seen += count;
+            if seen >= rank {
+                return Some(upper.min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// Non-empty buckets as (upper bound, count), fastest first
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        self.buckets.iter().enumerate().filter(|(_, count)| **count > 0).map(|(i, count)| {
+            let upper = Duration::from_micros(1 << i);
+            // The last bucket is open-ended
+            (if i == BUCKETS - 1 { upper.max(self.max) } else { upper }, *count)
+        })
+    }
+}
@@ -3,25 +3,130 @@
 //! This library provides a feature-gated implementation of the iRPC protocol
 //! for robotic systems, supporting both std host environments and no_std
 //! embedded environments.
+//!
+//! Without features the crate is `no_std` and holds the protocol types
+//! ([`Message`], [`Payload`], framing and units) only. `std` builds them on
+//! std without pulling in the host runtime; `arm_api` adds the host side on
+//! top of `std`, and `joint_api` the firmware side. `sim` enables both, so one
+//! host binary runs an `ArmOrchestrator` against simulated joints
+//! (`bus::sim::SimBus`); this is the configuration the integration tests use.
 
-#![cfg_attr(not(feature = "arm_api"), no_std)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
-// When using no_std, we need alloc for Vec and String
-#[cfg(not(feature = "arm_api"))]
+// When using no_std, we need alloc for Vec and String (unless no_alloc is set).
+// Declared under std too, so code shared with wasm32 builds can name `alloc::`.
+#[cfg(not(feature = "no_alloc"))]
 extern crate alloc;
 
+#[cfg(all(feature = "std", feature = "no_alloc"))]
+compile_error!("the `no_alloc` feature cannot be combined with `std` (or `arm_api`)");
+
+#[cfg(all(feature = "ffi", feature = "no_alloc"))]
+compile_error!("the `ffi` feature cannot be combined with `no_alloc`");
+
+#[cfg(all(feature = "wasm", feature = "no_alloc"))]
+compile_error!("the `wasm` feature cannot be combined with `no_alloc`");
+
 // Core modules available in all configurations
 pub mod config;
 pub mod protocol;
 pub mod bus;
+pub mod framing;
+pub mod thermal;
+pub mod compat;
+pub mod units;
 
 // Feature-gated modules
 #[cfg(feature = "arm_api")]
 pub mod arm;
 
+#[cfg(feature = "arm_api")]
+pub mod clock;
+
+#[cfg(feature = "arm_api")]
+pub mod storage;
+
+#[cfg(feature = "arm_api")]
+pub mod trajectory;
+
+#[cfg(feature = "arm_api")]
+pub mod kinematics;
+
+#[cfg(feature = "arm_api")]
+pub mod schedule;
+
+#[cfg(feature = "arm_api")]
+pub mod sequence;
+
+#[cfg(feature = "arm_api")]
+pub mod group;
+
+#[cfg(feature = "arm_api")]
+pub mod startup;
+
+#[cfg(feature = "arm_api")]
+pub mod arm_config;
+
+#[cfg(feature = "arm_api")]
+pub mod rate_limit;
+
+#[cfg(feature = "arm_api")]
+pub mod dispatch;
+
+#[cfg(feature = "arm_api")]
+pub mod latency;
+
+#[cfg(feature = "arm_api")]
+pub mod safety;
+
+#[cfg(feature = "arm_api")]
+pub mod vectors;
+
+#[cfg(feature = "arm_api")]
+pub mod benchmark;
+
+#[cfg(feature = "hil")]
+pub mod hil;
+
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+#[cfg(feature = "python")]
+pub mod python;
+
 #[cfg(feature = "joint_api")]
 pub mod joint;
 
+#[cfg(feature = "joint_api")]
+pub mod crash;
+
+#[cfg(feature = "joint_api")]
+pub mod joint_log;
+
+#[cfg(feature = "joint_api")]
+pub mod flight_recorder;
+
+#[cfg(feature = "joint_api")]
+pub mod config_store;
+
+#[cfg(feature = "joint_api")]
+pub mod aux_io;
+
+#[cfg(feature = "joint_api")]
+pub mod encoder;
+
+#[cfg(feature = "joint_api")]
+pub mod motor;
+
+#[cfg(feature = "joint_api")]
+pub mod control;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
 // Concrete transport implementations (joint_api only)
 #[cfg(feature = "joint_api")]
 pub mod transport;
@@ -29,13 +134,19 @@ pub mod transport;
 // Re-export commonly used types
 pub use config::*;
 pub use protocol::*;
+pub use units::{DegPerSec, Degrees, RadPerSec, Radians};
 
 // Re-export bus types based on features
+pub use bus::DeviceInfo;
+
 #[cfg(feature = "arm_api")]
-pub use bus::{CommunicationAdapter, DeviceInfo};
+pub use bus::CommunicationAdapter;
 
 #[cfg(feature = "joint_api")]
-pub use bus::{EmbeddedTransport, TransportLayer, TransportError, DeviceInfo};
+pub use bus::{
+    EmbeddedTransport, TransportLayer, TransportError,
+    AsyncEmbeddedTransport, AsyncTransportLayer, DEFAULT_TRANSPORT_BUFFER_LEN,
+};
 
 #[cfg(feature = "arm_api")]
 pub use arm::*;
@@ -0,0 +1,160 @@
+//! Per-joint host metrics
+//!
+//! With the `metrics` feature the communication manager and joint proxies
+//! report through the [`metrics`](::metrics) crate facade. Nothing is
+//! collected until the application installs a recorder; to serve them to
+//! Prometheus on `/metrics`, install `metrics-exporter-prometheus`:
+//!
+//! ```text
+//! metrics_exporter_prometheus::PrometheusBuilder::new()
+//!     .with_http_listener(([0, 0, 0, 0], 9000))
+//!     .install()?;
+//! irpc::metrics::describe();
+//! ```
+//!
+//! Every per-joint series carries a `joint` label with the joint's device
+//! ID; telemetry counts are also labelled with their `topic`.
+//!
+//! A joint's telemetry series are registered with the recorder on its first
+//! sample and reused for every sample after it, so install the recorder
+//! before the communication manager starts receiving telemetry.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use ::metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram, Counter, Gauge, Unit};
+
+use crate::arm::TelemetryTopic;
+use crate::protocol::{DeviceId, JointStatistics, LifecycleState, Message, Payload};
+
+/// Histogram of request round-trip times, in seconds
+pub const COMMAND_LATENCY: &str = "irpc_command_latency_seconds";
+/// Counter of requests that got no reply in time
+pub const COMMAND_TIMEOUTS: &str = "irpc_command_timeouts_total";
+/// Counter of requests the joint refused
+pub const COMMAND_NACKS: &str = "irpc_command_nacks_total";
+/// Counter of requests sent again after a failed attempt
+pub const COMMAND_RETRIES: &str = "irpc_command_retries_total";
+/// Counter of telemetry samples received; its rate is the telemetry rate
+pub const TELEMETRY_SAMPLES: &str = "irpc_telemetry_samples_total";
+/// Gauge of the last reported motor temperature, in °C
+pub const JOINT_TEMPERATURE: &str = "irpc_joint_temperature_celsius";
+/// Gauge of the last reported load, in percent
+pub const JOINT_LOAD: &str = "irpc_joint_load_percent";
+/// Gauge of the last known lifecycle state (`LifecycleState` discriminant)
+pub const JOINT_LIFECYCLE_STATE: &str = "irpc_joint_lifecycle_state";
+/// Gauge of messages waiting in the outbound queue, across all joints
+pub const OUTBOUND_QUEUE_DEPTH: &str = "irpc_outbound_queue_depth";
+/// Gauge of the energy a joint drew over its lifetime, in watt-hours
+pub const JOINT_ENERGY: &str = "irpc_joint_energy_watt_hours";
+/// Gauge of the time a joint's motor was driven, in seconds
+pub const JOINT_MOTOR_ON: &str = "irpc_joint_motor_on_seconds";
+/// Gauge of the moves a joint started
+pub const JOINT_MOVES: &str = "irpc_joint_moves";
+
+/// Register units and help texts with the installed recorder
+///
+/// Optional; call once after installing the recorder.
+pub fn describe() {
+    describe_histogram!(COMMAND_LATENCY, Unit::Seconds, "Request round-trip time");
+    describe_counter!(COMMAND_TIMEOUTS, Unit::Count, "Requests that got no reply in time");
+    describe_counter!(COMMAND_NACKS, Unit::Count, "Requests the joint refused");
+    describe_counter!(COMMAND_RETRIES, Unit::Count, "Requests sent again after a failed attempt");
+    describe_counter!(TELEMETRY_SAMPLES, Unit::Count, "Telemetry samples received");
+    describe_gauge!(JOINT_TEMPERATURE, "Last reported motor temperature in degrees Celsius");
+    describe_gauge!(JOINT_LOAD, Unit::Percent, "Last reported load");
+    describe_gauge!(OUTBOUND_QUEUE_DEPTH, Unit::Count, "Messages waiting for the transport");
+    describe_gauge!(JOINT_ENERGY, "Energy drawn over the joint's lifetime in watt-hours");
+    describe_gauge!(JOINT_MOTOR_ON, Unit::Seconds, "Time the joint's motor was driven");
+    describe_gauge!(JOINT_MOVES, Unit::Count, "Moves the joint started");
+    describe_gauge!(
+        JOINT_LIFECYCLE_STATE,
+        "Last known lifecycle state (0 unconfigured, 1 inactive, 2 active, 3 calibrating, 4 error)"
+    );
+}
+
+/// A reply arrived `latency` after the request was sent
+pub(crate) fn record_reply(joint: DeviceId, latency: Duration, payload: &Payload) {
+    histogram!(COMMAND_LATENCY, "joint" => joint.to_string()).record(latency.as_secs_f64());
+    if matches!(payload, Payload::Nack { .. }) {
+        counter!(COMMAND_NACKS, "joint" => joint.to_string()).increment(1);
+    }
+}
+
+pub(crate) fn record_timeout(joint: DeviceId) {
+    counter!(COMMAND_TIMEOUTS, "joint" => joint.to_string()).increment(1);
+}
+
+pub(crate) fn record_retry(joint: DeviceId) {
+    counter!(COMMAND_RETRIES, "joint" => joint.to_string()).increment(1);
+}
+
+pub(crate) fn record_state(joint: DeviceId, state: LifecycleState) {
+    gauge!(JOINT_LIFECYCLE_STATE, "joint" => joint.to_string()).set(state as u8);
+}
+
+pub(crate) fn record_outbound_depth(depth: usize) {
+    gauge!(OUTBOUND_QUEUE_DEPTH).set(depth as f64);
+}
+
+/// The joint's usage counters were read
+pub(crate) fn record_statistics(joint: DeviceId, statistics: &JointStatistics) {
+    gauge!(JOINT_ENERGY, "joint" => joint.to_string()).set(statistics.energy_wh);
+    gauge!(JOINT_MOTOR_ON, "joint" => joint.to_string()).set(statistics.motor_on_s);
+    gauge!(JOINT_MOVES, "joint" => joint.to_string()).set(statistics.move_count);
+}
+
+/// Telemetry topics in the order of [`JointTelemetry::samples`], with their labels
+const TOPICS: [(TelemetryTopic, &str); 3] = [
+    (TelemetryTopic::Motion, "motion"),
+    (TelemetryTopic::Adaptive, "adaptive"),
+    (TelemetryTopic::Status, "status"),
+];
+
+/// Telemetry series of every joint heard from, so recording a sample
+/// neither builds labels nor allocates
+#[derive(Default)]
+pub(crate) struct TelemetryMetrics {
+    joints: HashMap<DeviceId, JointTelemetry>,
+}
+
+/// One joint's telemetry series
+struct JointTelemetry {
+    samples: [Counter; 3],
+    temperature: Gauge,
+    load: Gauge,
+    state: Gauge,
+}
+
+impl JointTelemetry {
+    fn register(joint: DeviceId) -> Self {
+        let label = joint.to_string();
+        Self {
+            samples: TOPICS.map(|(_, topic)| counter!(TELEMETRY_SAMPLES, "joint" => label.clone(), "topic" => topic)),
+            temperature: gauge!(JOINT_TEMPERATURE, "joint" => label.clone()),
+            load: gauge!(JOINT_LOAD, "joint" => label.clone()),
+            state: gauge!(JOINT_LIFECYCLE_STATE, "joint" => label),
+        }
+    }
+}
+
+impl TelemetryMetrics {
+    /// A telemetry sample was published on `topic`
+    pub(crate) fn record(&mut self, topic: TelemetryTopic, message: &Message) {
+        let joint = message.header.source_id;
+        let series = self.joints.entry(joint).or_insert_with(|| JointTelemetry::register(joint));
+        if let Some(index) = TOPICS.iter().position(|(known, _)| *known == topic) {
+            series.samples[index].increment(1);
+        }
+
+        match &message.payload {
+            Payload::TelemetryStream(stream) => {
+                series.temperature.set(stream.temperature_c);
+                series.load.set(stream.load_percent);
+            }
+            Payload::AdaptiveStatus(status) => series.load.set(status.load_percent),
+            Payload::JointStatus { state, .. } => series.state.set(*state as u8),
+            _ => {}
+        }
+    }
+}
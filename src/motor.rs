@@ -0,0 +1,110 @@
+//! Motor power stages behind a common interface
+//!
+//! Firmware wraps its gate driver / FOC stage in [`MotorDriver`]. The joint
+//! owns when the stage may be energised:
+//!
+//! - [`Joint::handle_motor`](crate::Joint::handle_motor) enables the driver
+//!   when an `Activate` arrives and refuses the command with
+//!   `ERROR_MOTOR_FAULT` if the driver will not enable
+//! - [`Joint::sync_driver`](crate::Joint::sync_driver), called after every
+//!   message and control tick, disables the driver whenever the joint leaves
+//!   `Active`/`Calibrating`, and puts the joint in `Error` when the driver
+//!   reports a fault
+//! - [`Joint::command_motor`](crate::Joint::command_motor) passes the control
+//!   loop's output to the driver only while the joint may move
+//!
+//! `run_embassy` does all three when `JointHooks::motor_driver` returns the
+//! driver.
+
+use core::ops::{BitOr, BitOrAssign};
+
+/// Motor driver errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum MotorError {
+    /// The power stage is in fault (see [`MotorDriver::faults`])
+    #[error("Motor driver fault")]
+    Fault,
+
+    /// The driver has no such command mode
+    #[error("Command mode not supported by the driver")]
+    Unsupported,
+
+    /// The joint is not in a state that may drive the motor
+    #[error("Motor is disabled")]
+    Disabled,
+}
+
+/// Power stage fault flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct DriverFaults(pub u8);
+
+impl DriverFaults {
+    pub const NONE: Self = Self(0);
+    pub const OVERCURRENT: Self = Self(1 << 0);
+    pub const UNDERVOLTAGE: Self = Self(1 << 1);
+    pub const OVERVOLTAGE: Self = Self(1 << 2);
+    pub const OVERTEMPERATURE: Self = Self(1 << 3);
+    /// Gate driver or bridge fault (shorted FET, bootstrap failure, ...)
+    pub const GATE_DRIVER: Self = Self(1 << 4);
+
+    /// Raw flag bits
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Whether every flag in `other` is set
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether no flag is set
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for DriverFaults {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for DriverFaults {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// What the control loop asks of the power stage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MotorCommand {
+    /// Quadrature-axis voltage in volts
+    Voltage(f32),
+    /// Quadrature-axis current in amperes
+    Current(f32),
+}
+
+/// A motor power stage
+pub trait MotorDriver {
+    /// Energise the power stage
+    fn enable(&mut self) -> Result<(), MotorError>;
+
+    /// De-energise the power stage; must not fail
+    fn disable(&mut self);
+
+    /// Command a voltage, in volts (signed: direction)
+    fn set_voltage(&mut self, volts: f32) -> Result<(), MotorError>;
+
+    /// Command a current, in amperes, for stages with a current loop
+    fn set_current(&mut self, _amps: f32) -> Result<(), MotorError> {
+        Err(MotorError::Unsupported)
+    }
+
+    /// Faults currently latched by the power stage
+    fn faults(&mut self) -> DriverFaults {
+        DriverFaults::NONE
+    }
+}
@@ -1,12 +1,18 @@
 use serde::{Serialize, Deserialize};
+use postcard::experimental::max_size::MaxSize;
 
-#[cfg(not(feature = "arm_api"))]
+use crate::config::{
+    CANFD_MAX_DATA_LEN, CRASH_MESSAGE_LEN, CRASH_TASK_NAME_LEN, FLIGHT_RECORDS_PER_CHUNK, TARGET_BATCH_MAX_JOINTS,
+};
+use crate::units::{DegPerSec, Degrees, RadPerSec, Radians};
+
+#[cfg(all(not(feature = "std"), not(feature = "no_alloc")))]
 extern crate alloc;
 
-#[cfg(not(feature = "arm_api"))]
+#[cfg(all(not(feature = "std"), not(feature = "no_alloc")))]
 use alloc::{vec::Vec, string::String};
 
-#[cfg(feature = "arm_api")]
+#[cfg(feature = "std")]
 use std::{vec::Vec, string::String};
 
 /// Device identifier type
@@ -15,16 +21,33 @@ pub type DeviceId = u16;
 /// Message identifier type for request/response correlation
 pub type MessageId = u32;
 
+/// Owned error text carried by [`ProtocolError`]
+///
+/// A regular `String` normally; a fixed-capacity `heapless::String` when
+/// built with the `no_alloc` feature.
+#[cfg(not(feature = "no_alloc"))]
+pub type ErrorString = String;
+
+/// Owned error text carried by [`ProtocolError`]
+///
+/// A regular `String` normally; a fixed-capacity `heapless::String` when
+/// built with the `no_alloc` feature.
+///
+/// cbindgen:ignore
+#[cfg(feature = "no_alloc")]
+pub type ErrorString = heapless::String<32>;
+
 /// Lifecycle state of a joint in the robotic system
 ///
 /// State transitions follow a strict lifecycle:
 /// - Unconfigured → Inactive (via Configure)
 /// - Inactive → Active (via Activate)
 /// - Active → Inactive (via Deactivate)
-/// - Active → Calibrating (via StartCalibration)
-/// - Calibrating → Active (via calibration completion)
+/// - Active → Calibrating (via StartCalibration, Home or StartHoming)
+/// - Calibrating → Active (via calibration or homing completion)
 /// - Any → Unconfigured (via Reset)
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum LifecycleState {
     /// Joint is not configured and cannot accept commands
@@ -33,14 +56,16 @@ pub enum LifecycleState {
     Inactive = 1,
     /// Joint is active and can execute motion commands
     Active = 2,
-    /// Joint is performing automatic calibration
+    /// Joint is performing automatic calibration or homing
     Calibrating = 3,
     /// Joint is in error state
     Error = 4,
 }
 
 /// Target position and velocity for joint motion (v1.0)
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct SetTargetPayload {
     /// Target angle in degrees
     pub target_angle: f32,
@@ -48,8 +73,25 @@ pub struct SetTargetPayload {
     pub velocity_limit: f32,
 }
 
+impl SetTargetPayload {
+    /// Target from typed values; radians are converted to degrees
+    pub fn new(target: impl Into<Degrees>, velocity_limit: impl Into<DegPerSec>) -> Self {
+        Self { target_angle: target.into().0, velocity_limit: velocity_limit.into().0 }
+    }
+
+    pub fn target(&self) -> Degrees {
+        Degrees(self.target_angle)
+    }
+
+    pub fn velocity_limit(&self) -> DegPerSec {
+        DegPerSec(self.velocity_limit)
+    }
+}
+
 /// Enhanced target with motion profiling (v2.0)
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct SetTargetPayloadV2 {
     /// Target angle in degrees
     pub target_angle: f32,
@@ -72,8 +114,19 @@ pub struct SetTargetPayloadV2 {
     pub max_temperature: f32,
 }
 
+impl SetTargetPayloadV2 {
+    pub fn target(&self) -> Degrees {
+        Degrees(self.target_angle)
+    }
+
+    pub fn max_velocity(&self) -> DegPerSec {
+        DegPerSec(self.max_velocity)
+    }
+}
+
 /// Motion profile type for trajectory generation
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum MotionProfile {
     /// Trapezoidal velocity profile - constant acceleration/deceleration
@@ -85,7 +138,9 @@ pub enum MotionProfile {
 }
 
 /// Encoder telemetry data from a joint (v1.0 - basic)
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct EncoderTelemetry {
     /// Current position in degrees
     pub position: f32,
@@ -93,15 +148,28 @@ pub struct EncoderTelemetry {
     pub velocity: f32,
 }
 
+impl EncoderTelemetry {
+    pub fn position(&self) -> Degrees {
+        Degrees(self.position)
+    }
+
+    pub fn velocity(&self) -> DegPerSec {
+        DegPerSec(self.velocity)
+    }
+}
+
 /// Comprehensive telemetry stream (v2.0)
 ///
-/// Size: 64 bytes (struct) + ~10 bytes (postcard) = ~74 bytes
-/// Fits in CAN-FD frame (64 bytes data payload)
+/// Size: up to 61 bytes (postcard) + 12 bytes header/tag = up to 73 bytes.
+/// This exceeds a single CAN-FD frame (64 bytes data payload), so it is
+/// listed in [`Payload::requires_fragmentation`].
 ///
 /// At 1 kHz streaming:
-/// - Bandwidth: 74 bytes * 8 * 1000 = 592 kbps
-/// - CAN-FD usage: 592 / 5000 = 11.8% (plenty of headroom)
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+/// - Bandwidth: 73 bytes * 8 * 1000 = 584 kbps
+/// - CAN-FD usage: 584 / 5000 = 11.7% (plenty of headroom)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct TelemetryStream {
     /// Timestamp in microseconds since boot
     pub timestamp_us: u64,
@@ -139,14 +207,155 @@ pub struct TelemetryStream {
     pub temperature_c: f32,
     
     // Status flags
-    /// Warning flags bitmap
-    pub warnings: u16,
+    /// Warning flags
+    pub warnings: Warnings,
     /// Is trajectory currently active?
     pub trajectory_active: bool,
+    /// Is the holding brake engaged? (v2.2; false for joints without one)
+    pub brake_engaged: bool,
+}
+
+impl TelemetryStream {
+    pub fn position(&self) -> Degrees {
+        Degrees(self.position)
+    }
+
+    pub fn velocity(&self) -> DegPerSec {
+        DegPerSec(self.velocity)
+    }
+}
+
+/// Conditions a joint flags in `TelemetryStream::warnings` (v2.2)
+///
+/// A set of flags like [`Capabilities`]; none of them stops the joint by
+/// itself. Bits not defined here are kept, and displayed as raw bits.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(transparent)]
+pub struct Warnings(pub u16);
+
+impl Warnings {
+    pub const NONE: Self = Self(0);
+    /// Above the thermal warning temperature, derating; or a driver
+    /// overtemperature
+    pub const OVERTEMPERATURE: Self = Self(1 << 0);
+    /// Supply below the undervoltage threshold of the joint's
+    /// `PowerLimits`, or a driver undervoltage
+    pub const UNDERVOLTAGE: Self = Self(1 << 1);
+    /// Current above the collision threshold, or a driver overcurrent
+    pub const OVERCURRENT: Self = Self(1 << 2);
+    /// The encoder flagged its reading (`EncoderFlags`)
+    pub const ENCODER_ERROR: Self = Self(1 << 3);
+    /// The `SafetyEnable` heartbeat ran out
+    pub const COMM_DEGRADED: Self = Self(1 << 4);
+    /// stallGuard reports a high load or a stall
+    pub const STALL_WARNING: Self = Self(1 << 5);
+
+    const NAMES: [(Self, &'static str); 6] = [
+        (Self::OVERTEMPERATURE, "OVERTEMPERATURE"),
+        (Self::UNDERVOLTAGE, "UNDERVOLTAGE"),
+        (Self::OVERCURRENT, "OVERCURRENT"),
+        (Self::ENCODER_ERROR, "ENCODER_ERROR"),
+        (Self::COMM_DEGRADED, "COMM_DEGRADED"),
+        (Self::STALL_WARNING, "STALL_WARNING"),
+    ];
+
+    /// Raw flag bits
+    pub const fn bits(&self) -> u16 {
+        self.0
+    }
+
+    /// Whether every flag in `other` is set
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether any flag in `other` is set
+    pub const fn intersects(&self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Flags set in either
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// `self` without the flags in `other`
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn is_overtemperature(&self) -> bool {
+        self.contains(Self::OVERTEMPERATURE)
+    }
+
+    pub const fn is_undervoltage(&self) -> bool {
+        self.contains(Self::UNDERVOLTAGE)
+    }
+
+    pub const fn is_overcurrent(&self) -> bool {
+        self.contains(Self::OVERCURRENT)
+    }
+
+    pub const fn is_encoder_error(&self) -> bool {
+        self.contains(Self::ENCODER_ERROR)
+    }
+
+    pub const fn is_comm_degraded(&self) -> bool {
+        self.contains(Self::COMM_DEGRADED)
+    }
+
+    pub const fn is_stall_warning(&self) -> bool {
+        self.contains(Self::STALL_WARNING)
+    }
+
+    /// Names of the flags set, in bit order, e.g. for a dashboard
+    pub fn names(self) -> impl Iterator<Item = &'static str> {
+        Self::NAMES.into_iter().filter(move |(flag, _)| self.contains(*flag)).map(|(_, name)| name)
+    }
+}
+
+impl core::ops::BitOr for Warnings {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl core::ops::BitOrAssign for Warnings {
+    fn bitor_assign(&mut self, other: Self) {
+        *self = self.union(other);
+    }
+}
+
+/// Flag names joined with `|` (`OVERTEMPERATURE|STALL_WARNING`), undefined
+/// bits in hex, `NONE` when empty
+impl core::fmt::Display for Warnings {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("NONE");
+        }
+        let mut separator = "";
+        for name in self.names() {
+            write!(f, "{}{}", separator, name)?;
+            separator = "|";
+        }
+        let unknown = Self::NAMES.iter().fold(*self, |rest, (flag, _)| rest.difference(*flag));
+        if !unknown.is_empty() {
+            write!(f, "{}{:#06x}", separator, unknown.bits())?;
+        }
+        Ok(())
+    }
 }
 
 /// Telemetry streaming mode
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum TelemetryMode {
     /// Send telemetry only on explicit request
@@ -162,7 +371,9 @@ pub enum TelemetryMode {
 }
 
 /// Configure telemetry streaming
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct ConfigureTelemetryPayload {
     /// Streaming mode
     pub mode: TelemetryMode,
@@ -170,10 +381,14 @@ pub struct ConfigureTelemetryPayload {
     pub rate_hz: u16,
     /// Change threshold (for OnChange mode, 0.0 = use default)
     pub change_threshold: f32,
+    /// Start of the first sample within each period, in microseconds, as
+    /// assigned by a bus schedule (v2.2; 0 = unscheduled)
+    pub phase_offset_us: u32,
 }
 
 /// Stall detection status
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum StallStatus {
     /// Normal operation
@@ -184,8 +399,52 @@ pub enum StallStatus {
     Stalled = 2,
 }
 
+/// What a joint does on its own when it detects a collision or overload
+/// (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum CollisionReaction {
+    /// Nothing; stallGuard only reports in `AdaptiveStatus`
+    #[default]
+    None = 0,
+    /// Drop the setpoint and hold where the joint is
+    Stop = 1,
+    /// Move `collision_backoff_deg` back from the direction of travel
+    BackOff = 2,
+    /// Deactivate, leaving the motor unpowered so the joint can be pushed
+    /// away
+    GoLimp = 3,
+}
+
+/// What made a joint react on its own
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum FaultCause {
+    /// stallGuard reported `StallStatus::Stalled`
+    Stall = 0,
+    /// The motor current stayed above the collision threshold
+    Overcurrent = 1,
+}
+
+/// A joint detected a collision or overload and reacted (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct FaultReportPayload {
+    pub cause: FaultCause,
+    pub reaction: CollisionReaction,
+    /// Where it happened (degrees; NaN if the position is unknown)
+    pub position: f32,
+    /// Motor current at the time (A)
+    pub current: f32,
+}
+
 /// Configure adaptive control features (v2.0 - Phase 3)
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct ConfigureAdaptivePayload {
     /// Enable coolStep (adaptive current reduction)
     pub coolstep_enable: bool,
@@ -207,10 +466,21 @@ pub struct ConfigureAdaptivePayload {
     pub stallguard_current_threshold: f32,
     /// Velocity threshold for stall detection (deg/s)
     pub stallguard_velocity_threshold: f32,
+
+    /// What the joint does on a stall or overcurrent (v2.2)
+    pub collision_reaction: CollisionReaction,
+    /// Current that counts as an overload once held for
+    /// `collision_time_ms` (A, 0 = stallGuard only)
+    pub collision_current_threshold: f32,
+    pub collision_time_ms: u16,
+    /// How far `CollisionReaction::BackOff` moves back (degrees)
+    pub collision_backoff_deg: f32,
 }
 
 /// Adaptive control status telemetry (v2.0 - Phase 3)
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct AdaptiveStatusPayload {
     /// Estimated load percentage (0-100%)
     pub load_percent: f32,
@@ -240,9 +510,12 @@ pub struct AdaptiveStatusPayload {
 }
 
 /// Calibration request configuration
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct CalibrationRequest {
-    /// Phases to run (bitmask: bit 0 = Inertia, bit 1 = Friction, bit 2 = TorqueConstant, bit 3 = Damping, bit 4 = Validation)
+    /// Phases to run (bitmask: bit 0 = Inertia, bit 1 = Friction, bit 2 = TorqueConstant, bit 3 = Damping, bit 4 = Validation,
+    /// bit 5 = Thermal; see the `PHASE_*` constants)
     pub phases: u8,
     /// Maximum test current (A)
     pub max_current: f32,
@@ -256,6 +529,40 @@ pub struct CalibrationRequest {
     pub return_home: bool,
 }
 
+impl CalibrationRequest {
+    pub const PHASE_INERTIA: u8 = 1 << 0;
+    pub const PHASE_FRICTION: u8 = 1 << 1;
+    pub const PHASE_TORQUE_CONSTANT: u8 = 1 << 2;
+    pub const PHASE_DAMPING: u8 = 1 << 3;
+    pub const PHASE_VALIDATION: u8 = 1 << 4;
+    /// Optional winding thermal identification (v2.2): current pulses of
+    /// `max_current` for up to `phase_timeout`, then cooling. Not in the
+    /// default set because it takes minutes.
+    pub const PHASE_THERMAL: u8 = 1 << 5;
+
+    /// Whether the given `PHASE_*` bit is requested
+    pub const fn runs(&self, phase: u8) -> bool {
+        self.phases & phase != 0
+    }
+
+    pub fn max_velocity(&self) -> RadPerSec {
+        RadPerSec(self.max_velocity)
+    }
+
+    pub fn max_position_range(&self) -> Radians {
+        Radians(self.max_position_range)
+    }
+
+    /// Limit the test motion; degrees are converted to radians
+    pub fn with_motion_limits(
+        self,
+        max_velocity: impl Into<RadPerSec>,
+        max_position_range: impl Into<Radians>,
+    ) -> Self {
+        Self { max_velocity: max_velocity.into().0, max_position_range: max_position_range.into().0, ..self }
+    }
+}
+
 impl Default for CalibrationRequest {
     fn default() -> Self {
         Self {
@@ -270,7 +577,8 @@ impl Default for CalibrationRequest {
 }
 
 /// Calibration phase identifiers
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum CalibrationPhase {
     Idle = 0,
@@ -281,10 +589,14 @@ pub enum CalibrationPhase {
     Validation = 5,
     Complete = 6,
     Failed = 7,
+    /// Winding thermal identification (v2.2)
+    ThermalTest = 8,
 }
 
 /// Calibration status update (sent periodically during calibration)
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct CalibrationStatus {
     /// Current calibration phase
     pub phase: CalibrationPhase,
@@ -300,8 +612,20 @@ pub struct CalibrationStatus {
     pub current_iq: f32,
 }
 
+impl CalibrationStatus {
+    pub fn position(&self) -> Radians {
+        Radians(self.current_position)
+    }
+
+    pub fn velocity(&self) -> RadPerSec {
+        RadPerSec(self.current_velocity)
+    }
+}
+
 /// Identified motor parameters
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct MotorParameters {
     /// Rotor inertia (kg·m²)
     pub inertia_J: f32,
@@ -317,10 +641,16 @@ pub struct MotorParameters {
     pub friction_vstribeck: f32,
     /// Viscous friction coefficient (Nm·s/rad)
     pub friction_viscous: f32,
+    /// Winding-to-ambient thermal resistance (K/W), 0 if the thermal phase did not run (v2.2)
+    pub thermal_resistance: f32,
+    /// Winding thermal time constant (s), 0 if the thermal phase did not run (v2.2)
+    pub thermal_time_constant: f32,
 }
 
 /// Calibration confidence metrics
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct CalibrationConfidence {
     /// Overall confidence (0.0 - 1.0)
     pub overall: f32,
@@ -335,7 +665,9 @@ pub struct CalibrationConfidence {
 }
 
 /// Calibration result
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct CalibrationResult {
     /// Calibration success flag
     pub success: bool,
@@ -349,70 +681,1580 @@ pub struct CalibrationResult {
     pub error_code: u16,
 }
 
+/// Firmware image a node is running (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum BootMode {
+    /// The application; the node takes commands
+    #[default]
+    Application = 0,
+    /// The bootloader, waiting to be re-flashed
+    Bootloader = 1,
+}
+
+/// Optional commands a joint's firmware handles, advertised in `Hello` (v2.2)
+///
+/// A set of flags; combine them with `|`. Bits not defined here are kept,
+/// so a newer joint's flags survive a round trip through older hosts.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(transparent)]
+pub struct Capabilities(pub u32);
+
+impl Capabilities {
+    pub const NONE: Self = Self(0);
+    /// `SetTargetV2` motion profiles (`SetTarget` only otherwise)
+    pub const SET_TARGET_V2: Self = Self(1 << 0);
+    /// `StartCalibration` motor parameter identification
+    pub const CALIBRATION: Self = Self(1 << 1);
+    /// `ConfigureAdaptive` (coolStep, dcStep, stallGuard)
+    pub const ADAPTIVE_CONTROL: Self = Self(1 << 2);
+    /// `EnterBootloader` and re-flashing
+    pub const FIRMWARE_UPDATE: Self = Self(1 << 3);
+    /// `ConfigureTelemetry` and `TelemetryStream`
+    pub const TELEMETRY_STREAMING: Self = Self(1 << 4);
+    /// `Home` and `StartHoming`
+    pub const HOMING: Self = Self(1 << 5);
+    /// `SaveConfig`, `LoadConfig` and `FactoryReset`
+    pub const CONFIG_STORE: Self = Self(1 << 6);
+    /// `SetDigitalOutput`, `ReadDigitalInput` and `ReadAnalogInput`
+    pub const AUX_IO: Self = Self(1 << 7);
+    /// A holding brake, driven with `EngageBrake` and `ReleaseBrake`
+    pub const BRAKE: Self = Self(1 << 8);
+    /// Velocity and torque control modes: `SetControlMode`, `SetVelocity`
+    /// and `SetTorque`
+    pub const DIRECT_CONTROL: Self = Self(1 << 9);
+    /// A flight recorder, read with `RequestFlightRecorder`
+    pub const FLIGHT_RECORDER: Self = Self(1 << 10);
+    /// Stays Active only while `SafetyEnable` is refreshed
+    pub const SAFETY_ENABLE: Self = Self(1 << 11);
+    /// `ControlMode::CyclicPosition`, following `Sync`
+    pub const CYCLIC_SYNC: Self = Self(1 << 12);
+
+    /// Raw flag bits
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Whether every flag in `other` is set
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Flags set in either
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// `self` without the flags in `other`
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl core::ops::BitOr for Capabilities {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl core::ops::BitOrAssign for Capabilities {
+    fn bitor_assign(&mut self, other: Self) {
+        *self = self.union(other);
+    }
+}
+
+/// Discovery response sent by a joint after its backoff delay (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct HelloPayload {
+    /// Entity type identifier (see `ENTITY_TYPE_*` constants)
+    pub entity_type: u16,
+    /// Firmware image answering
+    pub mode: BootMode,
+    /// Optional commands the firmware handles
+    pub capabilities: Capabilities,
+    /// `SCHEMA_HASH` of the firmware build
+    pub schema: u32,
+}
+
+/// Firmware version, `major.minor.patch` (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct FirmwareVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+impl FirmwareVersion {
+    /// `0.0.0`: no valid image
+    pub const NONE: Self = Self::new(0, 0, 0);
+
+    pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Whether this is [`NONE`](Self::NONE)
+    pub const fn is_none(&self) -> bool {
+        self.major == 0 && self.minor == 0 && self.patch == 0
+    }
+}
+
+impl core::fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Firmware and boot information of a node, answering `RequestBootInfo` (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct BootInfoPayload {
+    /// Firmware image answering
+    pub mode: BootMode,
+    /// Installed application; [`FirmwareVersion::NONE`] if the bootloader
+    /// found no valid image
+    pub app_version: FirmwareVersion,
+    pub bootloader_version: FirmwareVersion,
+    /// Resets since the counter was last cleared (e.g. at production)
+    pub boot_count: u32,
+}
+
+/// Node ID assignment for a commissioned joint, sent as a broadcast (v2.2)
+///
+/// Only an unaddressed joint whose serial number matches takes the ID; it
+/// acknowledges from `new_id`.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct AssignIdPayload {
+    /// Factory serial number of the joint to address
+    pub serial_number: u64,
+    pub new_id: DeviceId,
+}
+
+/// Level of a digital auxiliary I/O channel (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct DigitalIoPayload {
+    pub channel: u8,
+    /// `true` = high
+    pub value: bool,
+}
+
+/// Reading of an analog auxiliary input channel (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct AnalogInputPayload {
+    pub channel: u8,
+    /// In the channel's unit (volts unless the board documents otherwise)
+    pub value: f32,
+}
+
+/// What brought the firmware down (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum CrashKind {
+    /// Rust panic (including failed `assert!`s)
+    Panic = 0,
+    /// Cortex-M HardFault (bus, memory or usage fault escalated)
+    HardFault = 1,
+    /// Hardware watchdog reset: the firmware stopped feeding it
+    Watchdog = 2,
+}
+
+/// Minimal description of a firmware crash, kept across the reset (v2.2)
+///
+/// Text fields are fixed-size, NUL-padded UTF-8 and truncated to fit, so the
+/// record has a fixed size and the boot announcement fits one CAN-FD frame.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct CrashRecord {
+    /// What happened
+    pub kind: CrashKind,
+    /// Program counter at the fault (0 if unknown, e.g. for panics)
+    pub pc: u32,
+    /// Link register at the fault (0 if unknown)
+    pub lr: u32,
+    task: [u8; CRASH_TASK_NAME_LEN],
+    message: [u8; CRASH_MESSAGE_LEN],
+}
+
+impl CrashRecord {
+    /// Create a record, truncating `task` and `message` to fit
+    pub fn new(kind: CrashKind, pc: u32, lr: u32, task: &str, message: &str) -> Self {
+        let mut record = Self {
+            kind,
+            pc,
+            lr,
+            task: [0; CRASH_TASK_NAME_LEN],
+            message: [0; CRASH_MESSAGE_LEN],
+        };
+        copy_truncated(&mut record.task, task);
+        copy_truncated(&mut record.message, message);
+        record
+    }
+
+    /// Name of the task that crashed (empty if unknown)
+    pub fn task(&self) -> &str {
+        padded_str(&self.task)
+    }
+
+    /// Panic message or fault description, possibly truncated
+    pub fn message(&self) -> &str {
+        padded_str(&self.message)
+    }
+}
+
+/// Copy as much of `text` as fits, cutting at a character boundary
+fn copy_truncated(buf: &mut [u8], text: &str) {
+    let mut len = text.len().min(buf.len());
+    while !text.is_char_boundary(len) {
+        len -= 1;
+    }
+    buf[..len].copy_from_slice(&text.as_bytes()[..len]);
+    buf[len..].fill(0);
+}
+
+/// Text up to the first NUL; invalid UTF-8 (a corrupted record) is cut off
+fn padded_str(buf: &[u8]) -> &str {
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    match core::str::from_utf8(&buf[..end]) {
+        Ok(text) => text,
+        Err(e) => core::str::from_utf8(&buf[..e.valid_up_to()]).unwrap_or_default(),
+    }
+}
+
+/// Announcement a joint sends once after every reset (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BootPayload {
+    /// Entity type identifier (see `ENTITY_TYPE_*` constants)
+    pub entity_type: u16,
+    /// Why the previous run ended, if it crashed
+    pub crash: Option<CrashRecord>,
+}
+
+/// Kind of the most recent transport error, for [`TransportStats`] (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum TransportErrorKind {
+    /// No error recorded yet
+    #[default]
+    None = 0,
+    /// Outgoing message failed to serialize (e.g. larger than the buffer)
+    Serialization = 1,
+    /// Incoming frame failed to deserialize
+    Deserialization = 2,
+    /// Incoming frame larger than any valid message
+    FrameTooLarge = 3,
+    /// Error reported by the underlying driver
+    Transport = 4,
+    /// CRC error detected by the bus controller
+    Crc = 5,
+    /// Bus controller entered bus-off
+    BusOff = 6,
+    /// Received frame dropped because the RX queue was full
+    RxOverflow = 7,
+}
+
+/// Transport statistics and bus health counters (v2.2)
+///
+/// Maintained by `TransportLayer`, `AsyncTransportLayer` and the CAN
+/// transports on the joint and reportable to the arm via
+/// `Payload::BusStats`. Counters wrap on overflow.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct TransportStats {
+    /// Frames transmitted successfully
+    pub tx_frames: u32,
+    /// Frames received (including ones that failed to decode)
+    pub rx_frames: u32,
+    /// Outgoing messages that failed to serialize or exceeded the MTU
+    pub serialization_failures: u32,
+    /// Incoming frames that failed to deserialize or were oversized
+    pub deserialization_failures: u32,
+    /// Errors reported by the underlying driver
+    pub transport_errors: u32,
+    /// CRC errors seen by the bus controller
+    pub crc_errors: u32,
+    /// Bus-off events
+    pub bus_off_events: u32,
+    /// Frames retransmitted by the controller or driver
+    pub retransmissions: u32,
+    /// Received frames dropped because the RX queue was full
+    pub rx_overflows: u32,
+    /// Most recent error kind
+    pub last_error: TransportErrorKind,
+    /// Largest encoded message the link carries in one send, in bytes
+    /// (0 if unknown)
+    pub mtu: u16,
+}
+
+impl TransportStats {
+    /// Create zeroed statistics
+    pub const fn new() -> Self {
+        Self {
+            tx_frames: 0,
+            rx_frames: 0,
+            serialization_failures: 0,
+            deserialization_failures: 0,
+            transport_errors: 0,
+            crc_errors: 0,
+            bus_off_events: 0,
+            retransmissions: 0,
+            rx_overflows: 0,
+            last_error: TransportErrorKind::None,
+            mtu: 0,
+        }
+    }
+
+    /// Create zeroed statistics for a link with the given MTU
+    pub const fn with_mtu(mtu: usize) -> Self {
+        let mut stats = Self::new();
+        stats.mtu = if mtu > u16::MAX as usize { u16::MAX } else { mtu as u16 };
+        stats
+    }
+
+    /// Count an error of the given kind and remember it as the last error
+    pub fn record_error(&mut self, kind: TransportErrorKind) {
+        let counter = match kind {
+            TransportErrorKind::None => return,
+            TransportErrorKind::Serialization => &mut self.serialization_failures,
+            TransportErrorKind::Deserialization | TransportErrorKind::FrameTooLarge => {
+                &mut self.deserialization_failures
+            }
+            TransportErrorKind::Transport => &mut self.transport_errors,
+            TransportErrorKind::Crc => &mut self.crc_errors,
+            TransportErrorKind::BusOff => &mut self.bus_off_events,
+            TransportErrorKind::RxOverflow => &mut self.rx_overflows,
+        };
+        *counter = counter.wrapping_add(1);
+        self.last_error = kind;
+    }
+
+    /// Total number of errors of any kind
+    pub fn total_errors(&self) -> u32 {
+        self.serialization_failures
+            .wrapping_add(self.deserialization_failures)
+            .wrapping_add(self.transport_errors)
+            .wrapping_add(self.crc_errors)
+            .wrapping_add(self.bus_off_events)
+            .wrapping_add(self.rx_overflows)
+    }
+
+    /// Add counters kept elsewhere (e.g. by an interrupt-side RX queue)
+    ///
+    /// `last_error` is taken from `other` if it recorded any error, `mtu`
+    /// only if this side does not know it.
+    pub fn merge(&mut self, other: &TransportStats) {
+        self.tx_frames = self.tx_frames.wrapping_add(other.tx_frames);
+        self.rx_frames = self.rx_frames.wrapping_add(other.rx_frames);
+        self.serialization_failures = self.serialization_failures.wrapping_add(other.serialization_failures);
+        self.deserialization_failures = self.deserialization_failures.wrapping_add(other.deserialization_failures);
+        self.transport_errors = self.transport_errors.wrapping_add(other.transport_errors);
+        self.crc_errors = self.crc_errors.wrapping_add(other.crc_errors);
+        self.bus_off_events = self.bus_off_events.wrapping_add(other.bus_off_events);
+        self.retransmissions = self.retransmissions.wrapping_add(other.retransmissions);
+        self.rx_overflows = self.rx_overflows.wrapping_add(other.rx_overflows);
+        if other.last_error != TransportErrorKind::None {
+            self.last_error = other.last_error;
+        }
+        if self.mtu == 0 {
+            self.mtu = other.mtu;
+        }
+    }
+}
+
+/// Soft position and velocity limits enforced by the joint (v2.2)
+///
+/// Limits are expressed in the joint's position frame, so they are dropped
+/// when the joint loses its position and must be re-applied after homing.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct JointLimits {
+    /// Minimum position in degrees
+    pub min_position: f32,
+    /// Maximum position in degrees
+    pub max_position: f32,
+    /// Maximum velocity in degrees/second
+    pub max_velocity: f32,
+}
+
+impl JointLimits {
+    /// Whether a target lies within the limits
+    pub fn allows(&self, target_angle: f32, velocity_limit: f32) -> bool {
+        target_angle >= self.min_position
+            && target_angle <= self.max_position
+            && velocity_limit <= self.max_velocity
+    }
+}
+
+/// Winding temperature thresholds of a joint (v2.2)
+///
+/// Above `warning_c` the joint derates: its current and velocity limits
+/// shrink linearly down to `min_scale` of their value at `critical_c`. At
+/// `critical_c` the joint faults.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct ThermalLimits {
+    /// Temperature derating starts at (Celsius)
+    pub warning_c: f32,
+    /// Temperature the joint faults at (Celsius)
+    pub critical_c: f32,
+    /// Share of the limits left just below `critical_c` (0.0-1.0)
+    pub min_scale: f32,
+}
+
+impl ThermalLimits {
+    /// Whether the thresholds are finite and in order, and the scale a share
+    pub fn is_valid(&self) -> bool {
+        self.warning_c.is_finite()
+            && self.critical_c.is_finite()
+            && self.warning_c < self.critical_c
+            && (0.0..=1.0).contains(&self.min_scale)
+    }
+
+    /// Share of the current and velocity limits left at `temperature_c`;
+    /// 0 at or above the critical temperature
+    pub fn derating(&self, temperature_c: f32) -> f32 {
+        if temperature_c >= self.critical_c {
+            0.0
+        } else if temperature_c <= self.warning_c {
+            1.0
+        } else {
+            let heat = (temperature_c - self.warning_c) / (self.critical_c - self.warning_c);
+            1.0 - heat * (1.0 - self.min_scale)
+        }
+    }
+}
+
+/// Supply voltage thresholds of a joint (v2.2)
+///
+/// Below `undervoltage_v` the joint raises `Warnings::UNDERVOLTAGE`. Below
+/// `brownout_v` it stops in a controlled way, then deactivates with its
+/// brake engaged, before the sagging supply lets it collapse.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct PowerLimits {
+    /// Voltage the warning is raised below (volts)
+    pub undervoltage_v: f32,
+    /// Voltage the joint stops below (volts)
+    pub brownout_v: f32,
+}
+
+impl PowerLimits {
+    /// Whether the thresholds are finite, positive and in order
+    pub fn is_valid(&self) -> bool {
+        self.undervoltage_v.is_finite() && self.brownout_v > 0.0 && self.brownout_v < self.undervoltage_v
+    }
+}
+
+/// Supply readings of a joint, answering `RequestPowerStatus` (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct PowerStatusPayload {
+    /// Supply (DC bus) voltage in volts
+    pub bus_voltage: f32,
+    /// Current drawn from the supply in amperes
+    pub bus_current: f32,
+}
+
+impl PowerStatusPayload {
+    /// Power drawn from the supply in watts
+    pub fn power(&self) -> f32 {
+        self.bus_voltage * self.bus_current
+    }
+}
+
+/// Lifetime usage counters of a joint, answering `RequestStatistics` (v2.2)
+///
+/// Kept across power cycles in the joint's config store, for predictive
+/// maintenance.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct JointStatistics {
+    /// Energy drawn from the supply, in watt-hours
+    pub energy_wh: f32,
+    /// Time spent with the motor driven (Active or Calibrating), in seconds
+    pub motor_on_s: u32,
+    /// Moves started: targets accepted while the joint stood still
+    pub move_count: u32,
+}
+
+/// What a joint checks for `RunSelfTest` (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct SelfTestRequest {
+    /// Echoed in the result, so the host can tell the round trip worked
+    pub nonce: u32,
+    /// Distance to move in the motion test, degrees from the current
+    /// position; 0 skips the test
+    pub motion_deg: f32,
+}
+
+/// Outcome of one self-test check
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum SelfTestOutcome {
+    /// Not run (e.g. the motion test without a distance)
+    #[default]
+    Skipped = 0,
+    Passed = 1,
+    Failed = 2,
+}
+
+/// Results of a joint's self-test, answering `RunSelfTest` (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct SelfTestResult {
+    /// The request's nonce
+    pub nonce: u32,
+    /// The encoder has been read and reports no errors
+    pub encoder: SelfTestOutcome,
+    /// The motor driver reports no faults
+    pub driver: SelfTestOutcome,
+    /// The joint moved at least half the requested distance in time
+    pub motion: SelfTestOutcome,
+    /// Distance covered in the motion test, degrees in the requested
+    /// direction
+    pub moved_deg: f32,
+}
+
+impl SelfTestResult {
+    /// Whether no check failed
+    pub fn passed(&self) -> bool {
+        [self.encoder, self.driver, self.motion].iter().all(|outcome| *outcome != SelfTestOutcome::Failed)
+    }
+}
+
+/// Where a joint was at a `Sync`, answering it in
+/// `ControlMode::CyclicPosition` (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct SyncSample {
+    /// The `Sync`'s cycle counter
+    pub cycle_counter: u32,
+    /// Position in degrees
+    pub position: f32,
+    /// Velocity in degrees/second
+    pub velocity: f32,
+}
+
+impl SyncSample {
+    pub fn position(&self) -> Degrees {
+        Degrees(self.position)
+    }
+
+    pub fn velocity(&self) -> DegPerSec {
+        DegPerSec(self.velocity)
+    }
+}
+
+/// How a joint finds its reference position (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum HomingMethod {
+    /// Move until the incremental encoder's index pulse
+    IndexPulse = 0,
+    /// Move until the joint stalls against its mechanical end stop
+    HardStop = 1,
+    /// Read the absolute encoder; no motion
+    AbsoluteEncoder = 2,
+}
+
+/// Direction of the homing search (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum HomingDirection {
+    Positive = 0,
+    Negative = 1,
+}
+
+/// Homing procedure parameters (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct HomingConfig {
+    pub method: HomingMethod,
+    pub direction: HomingDirection,
+    /// Search speed in degrees/second
+    pub speed: f32,
+    /// Position in degrees assigned to the reference point
+    pub offset: f32,
+}
+
+impl Default for HomingConfig {
+    /// What `Payload::Home` runs: a slow search for the index pulse
+    fn default() -> Self {
+        Self {
+            method: HomingMethod::IndexPulse,
+            direction: HomingDirection::Positive,
+            speed: 10.0,
+            offset: 0.0,
+        }
+    }
+}
+
+/// Joint position across revolutions (v2.2)
+///
+/// The position in degrees is `turns * 360 + angle`, with `angle` in
+/// `[0, 360)`. Unlike a plain `f32` of degrees it keeps full resolution far
+/// from zero and says which revolution a multi-turn joint is on.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct MultiTurnPosition {
+    /// Whole revolutions, negative below zero
+    pub turns: i32,
+    /// Angle within the revolution in degrees
+    pub angle: f32,
+}
+
+impl MultiTurnPosition {
+    /// Split a position in degrees into turns and angle
+    pub fn from_degrees(degrees: f32) -> Self {
+        Self { turns: 0, angle: degrees }.normalized()
+    }
+
+    /// Position in degrees
+    pub fn degrees(&self) -> f32 {
+        self.turns as f32 * 360.0 + self.angle
+    }
+
+    /// This position measured from `zero` (a raw encoder position minus the
+    /// encoder offset)
+    pub fn relative_to(&self, zero: MultiTurnPosition) -> Self {
+        Self {
+            turns: self.turns.wrapping_sub(zero.turns),
+            angle: self.angle - zero.angle,
+        }
+        .normalized()
+    }
+
+    /// Bring `angle` into `[0, 360)`, carrying into `turns`
+    fn normalized(self) -> Self {
+        // No floor() in core
+        let carry = (self.angle / 360.0) as i32;
+        let mut turns = self.turns.wrapping_add(carry);
+        let mut angle = self.angle - carry as f32 * 360.0;
+        if angle < 0.0 {
+            angle += 360.0;
+            turns = turns.wrapping_sub(1);
+        }
+        if angle >= 360.0 {
+            angle -= 360.0;
+            turns = turns.wrapping_add(1);
+        }
+        Self { turns, angle }
+    }
+}
+
+/// Gains of one PID loop in the joint controller (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct PidGains {
+    /// Proportional gain
+    pub kp: f32,
+    /// Integral gain, per second
+    pub ki: f32,
+    /// Derivative gain, in seconds
+    pub kd: f32,
+    /// Bound on the integral term, in the loop's output unit (anti-windup)
+    pub integral_limit: f32,
+}
+
+impl PidGains {
+    /// Whether every gain is finite and non-negative
+    pub fn is_valid(&self) -> bool {
+        [self.kp, self.ki, self.kd, self.integral_limit]
+            .iter()
+            .all(|g| g.is_finite() && *g >= 0.0)
+    }
+}
+
+/// Gains of the joint's cascaded position/velocity controller (v2.2)
+///
+/// The position loop turns a position error in degrees into a velocity
+/// setpoint in degrees/second; the velocity loop turns the velocity error
+/// into a motor current setpoint in amperes.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct ControlGains {
+    pub position: PidGains,
+    pub velocity: PidGains,
+    /// Current setpoint limit in amperes
+    pub max_current: f32,
+}
+
+impl ControlGains {
+    /// Whether every gain and the current limit are finite and non-negative
+    pub fn is_valid(&self) -> bool {
+        self.position.is_valid() && self.velocity.is_valid() && self.max_current.is_finite() && self.max_current >= 0.0
+    }
+}
+
+/// What a joint's control loop follows (v2.2)
+///
+/// Chosen with `SetControlMode` while the joint is not Active.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum ControlMode {
+    /// Targets from `SetTarget`/`SetTargetV2` through the cascaded loop
+    #[default]
+    Position = 0,
+    /// Speeds from `SetVelocity` through the velocity loop alone
+    Velocity = 1,
+    /// Torques from `SetTorque`, commanded as motor current
+    Torque = 2,
+    /// Targets from `SetTarget`/`SetTargetV2` held until the next `Sync`,
+    /// when every joint takes its own at once
+    CyclicPosition = 3,
+}
+
+impl ControlMode {
+    /// Capability a joint needs for the mode
+    pub const fn capability(self) -> Capabilities {
+        match self {
+            ControlMode::Position => Capabilities::NONE,
+            ControlMode::Velocity | ControlMode::Torque => Capabilities::DIRECT_CONTROL,
+            ControlMode::CyclicPosition => Capabilities::CYCLIC_SYNC,
+        }
+    }
+}
+
+/// Operating region a scheduled gain set applies to (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct GainRegion {
+    /// Motion profiles of the current target the region covers, as
+    /// [`profile_mask`](Self::profile_mask) bits; 0 for any, including
+    /// holding without a target
+    pub profiles: u8,
+    /// Lowest measured speed (absolute) the region covers, in degrees/second
+    pub min_velocity: f32,
+    /// Lowest load (absolute current setpoint) the region covers, in amperes
+    pub min_load: f32,
+}
+
+impl GainRegion {
+    /// Bit of `profile` in `profiles`
+    pub const fn profile_mask(profile: MotionProfile) -> u8 {
+        1 << profile as u8
+    }
+
+    /// Whether both thresholds are finite and non-negative
+    pub fn is_valid(&self) -> bool {
+        [self.min_velocity, self.min_load].iter().all(|t| t.is_finite() && *t >= 0.0)
+    }
+
+    /// Whether the controller is in this region
+    pub fn contains(&self, profile: Option<MotionProfile>, speed: f32, load: f32) -> bool {
+        let profile_matches = match profile {
+            _ if self.profiles == 0 => true,
+            Some(profile) => self.profiles & Self::profile_mask(profile) != 0,
+            None => false,
+        };
+        profile_matches && speed >= self.min_velocity && load >= self.min_load
+    }
+}
+
+/// One slot of a joint's gain schedule (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct GainScheduleEntry {
+    /// Slot index, below `GAIN_SCHEDULE_SLOTS`; higher slots take precedence
+    pub slot: u8,
+    pub region: GainRegion,
+    pub gains: ControlGains,
+}
+
+/// Interlock channel states reported by a joint or safety node (v2.2)
+///
+/// Bit `n` of each mask is channel `n`. Channels not in `wired` have no
+/// input connected and carry no state.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct InterlockStatePayload {
+    /// Channels with an input connected
+    pub wired: u16,
+    /// Wired channels whose input is tripped (door open, curtain broken, ...)
+    pub tripped: u16,
+}
+
+impl InterlockStatePayload {
+    /// State of one channel: `None` if not wired, `Some(true)` if tripped
+    pub fn channel(&self, channel: u8) -> Option<bool> {
+        let bit = 1u16.checked_shl(channel as u32)?;
+        (self.wired & bit != 0).then_some(self.tripped & bit != 0)
+    }
+}
+
+/// What a joint's flight recorder entry describes (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C, u8)]
+pub enum FlightEvent {
+    /// A command arrived; `payload` is its [`Payload::tag`]
+    Received { msg_id: MessageId, payload: u8 },
+    /// The joint answered command `msg_id` with a `payload` reply
+    Replied { msg_id: MessageId, payload: u8 },
+    /// The joint refused command `msg_id` with `error`
+    Nacked { msg_id: MessageId, error: u16 },
+    /// The lifecycle state changed, on a command or a fault
+    StateChanged { from: LifecycleState, to: LifecycleState },
+}
+
+/// One entry of a joint's flight recorder (v2.2)
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct FlightRecord {
+    /// Joint clock when it was recorded, in microseconds (the low 32 bits,
+    /// so it wraps about every 71 minutes)
+    pub timestamp_us: u32,
+    pub event: FlightEvent,
+}
+
+/// Consecutive flight recorder entries, oldest first (v2.2)
+///
+/// Entries are numbered from 0 at boot; `first` is the number of the first
+/// one here. A chunk holds at most `FLIGHT_RECORDS_PER_CHUNK` entries so it
+/// fits one CAN-FD frame.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct FlightRecorderChunk {
+    pub first: u32,
+    len: u8,
+    records: [FlightRecord; FLIGHT_RECORDS_PER_CHUNK],
+}
+
+impl FlightRecorderChunk {
+    /// Chunk of `records` numbered from `first`, keeping as many as fit
+    pub fn new(first: u32, records: impl IntoIterator<Item = FlightRecord>) -> Self {
+        let unused = FlightRecord { timestamp_us: 0, event: FlightEvent::Received { msg_id: 0, payload: 0 } };
+        let mut chunk = Self { first, len: 0, records: [unused; FLIGHT_RECORDS_PER_CHUNK] };
+        for (slot, record) in chunk.records.iter_mut().zip(records) {
+            *slot = record;
+            chunk.len += 1;
+        }
+        chunk
+    }
+
+    pub fn records(&self) -> &[FlightRecord] {
+        &self.records[..(self.len as usize).min(FLIGHT_RECORDS_PER_CHUNK)]
+    }
+}
+
+/// One joint's target in a [`TargetBatch`]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct BatchTarget {
+    pub joint_id: DeviceId,
+    pub target: SetTargetPayload,
+}
+
+/// Targets for several joints sent in one frame (v2.2)
+///
+/// Holds at most `TARGET_BATCH_MAX_JOINTS` targets so it fits one CAN-FD
+/// frame; a joint listed twice takes its first target.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct TargetBatch {
+    len: u8,
+    targets: [BatchTarget; TARGET_BATCH_MAX_JOINTS],
+}
+
+impl TargetBatch {
+    /// Batch of `targets`, or `None` if there are more than fit
+    pub fn new(targets: &[BatchTarget]) -> Option<Self> {
+        if targets.len() > TARGET_BATCH_MAX_JOINTS {
+            return None;
+        }
+        let unused = BatchTarget { joint_id: 0, target: SetTargetPayload { target_angle: 0.0, velocity_limit: 0.0 } };
+        let mut batch = Self { len: targets.len() as u8, targets: [unused; TARGET_BATCH_MAX_JOINTS] };
+        batch.targets[..targets.len()].copy_from_slice(targets);
+        Some(batch)
+    }
+
+    pub fn targets(&self) -> &[BatchTarget] {
+        &self.targets[..(self.len as usize).min(TARGET_BATCH_MAX_JOINTS)]
+    }
+
+    /// Target of `joint_id`, if the batch has one
+    pub fn target_for(&self, joint_id: DeviceId) -> Option<SetTargetPayload> {
+        self.targets().iter().find(|entry| entry.joint_id == joint_id).map(|entry| entry.target)
+    }
+}
+
 /// Message payload variants for the iRPC protocol
-#[derive(Serialize, Deserialize, Debug, Clone)]
+///
+/// Every variant carries its wire tag as an explicit discriminant, and the
+/// tag is the variant's position, which is what the encoding uses. Tags
+/// never change once released: new variants go at the end with the next
+/// tag, and a retired variant stays (renamed `Reserved<tag>`, carrying
+/// nothing) so the variants after it keep theirs. Mixed-version fleets rely
+/// on this; `tests/wire_compat_tests.rs` checks that each variant encodes
+/// with its discriminant and decodes the v1.0 fixtures.
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
 pub enum Payload {
     // Arm → Joint Commands (v1.0)
     /// Set target position and velocity (only valid in Active state)
-    SetTarget(SetTargetPayload),
+    SetTarget(SetTargetPayload) = 0,
     /// Configure the joint (Unconfigured → Inactive)
-    Configure,
+    Configure = 1,
     /// Activate the joint (Inactive → Active)
-    Activate,
+    Activate = 2,
     /// Deactivate the joint (Active → Inactive)
-    Deactivate,
+    Deactivate = 3,
     /// Reset the joint to Unconfigured state
-    Reset,
+    Reset = 4,
 
     // Arm → Joint Commands (v2.0)
     /// Set target with motion profiling (enhanced version)
-    SetTargetV2(SetTargetPayloadV2),
+    SetTargetV2(SetTargetPayloadV2) = 5,
 
     // Joint → Arm Telemetry & Status (v1.0)
     /// Encoder position and velocity data (basic)
-    Encoder(EncoderTelemetry),
+    Encoder(EncoderTelemetry) = 6,
     /// Joint status update with state and error code
-    JointStatus { state: LifecycleState, error_code: u16 },
+    JointStatus { state: LifecycleState, error_code: u16 } = 7,
     
     // Joint → Arm Telemetry & Status (v2.0)
     /// Comprehensive telemetry stream
-    TelemetryStream(TelemetryStream),
+    TelemetryStream(TelemetryStream) = 8,
     
     // Telemetry Configuration (v2.0)
     /// Configure telemetry streaming mode
-    ConfigureTelemetry(ConfigureTelemetryPayload),
+    ConfigureTelemetry(ConfigureTelemetryPayload) = 9,
     /// Request immediate telemetry (for OnDemand mode)
-    RequestTelemetry,
+    RequestTelemetry = 10,
 
     // Adaptive Control Configuration & Status (v2.0 - Phase 3)
     /// Configure adaptive control features (coolStep, dcStep, stallGuard)
-    ConfigureAdaptive(ConfigureAdaptivePayload),
+    ConfigureAdaptive(ConfigureAdaptivePayload) = 11,
     /// Request immediate adaptive status
-    RequestAdaptiveStatus,
+    RequestAdaptiveStatus = 12,
     /// Adaptive control status telemetry
-    AdaptiveStatus(AdaptiveStatusPayload),
+    AdaptiveStatus(AdaptiveStatusPayload) = 13,
 
     // Motor Calibration (v2.1) - Phase 6
     /// Start automatic motor parameter calibration
-    StartCalibration(CalibrationRequest),
+    StartCalibration(CalibrationRequest) = 14,
     /// Stop/abort ongoing calibration
-    StopCalibration,
+    StopCalibration = 15,
     /// Calibration status update (Joint → Arm, sent every 100ms during calibration)
-    CalibrationStatus(CalibrationStatus),
+    CalibrationStatus(CalibrationStatus) = 16,
     /// Calibration final result (Joint → Arm, sent once at end)
-    CalibrationResult(CalibrationResult),
+    CalibrationResult(CalibrationResult) = 17,
 
     // Bidirectional Management
     /// Acknowledgment of successful command
-    Ack(MessageId),
+    Ack(MessageId) = 18,
     /// Negative acknowledgment with error code
-    Nack { id: MessageId, error: u16 },
+    Nack { id: MessageId, error: u16 } = 19,
     /// Arm ready broadcast signal
-    ArmReady,
+    ArmReady = 20,
+
+    // Discovery (v2.2)
+    /// Discovery request (Arm → broadcast), answered by every joint with `Hello`
+    Discover = 21,
+    /// Discovery response (Joint → Arm, sent after a randomized backoff)
+    Hello(HelloPayload) = 22,
+
+    // Bus Diagnostics (v2.2)
+    /// Request transport statistics from a joint
+    RequestBusStats = 23,
+    /// Transport statistics and bus health counters (Joint → Arm)
+    BusStats(TransportStats) = 24,
+
+    // State Reconciliation (v2.2)
+    /// Request the joint's authoritative state, answered with `JointStatus`
+    RequestStatus = 25,
+
+    // Position Recovery (v2.2)
+    /// Run the joint's homing routine (only works when joint is Active)
+    Home = 26,
+    /// Apply soft position/velocity limits
+    SetLimits(JointLimits) = 27,
+
+    // Crash Reporting (v2.2)
+    /// Sent once after reset (Joint → Arm), with the crash record if the
+    /// previous run crashed
+    Boot(BootPayload) = 28,
+
+    // Interlocks (v2.2)
+    /// Interlock inputs (Joint or safety node → Arm), sent on every change
+    /// and periodically
+    InterlockState(InterlockStatePayload) = 29,
+
+    // Bootloader (v2.2)
+    /// Restart into the bootloader for re-flashing (refused while Active)
+    EnterBootloader = 30,
+    /// Request firmware versions and boot mode, answered with `BootInfo`
+    RequestBootInfo = 31,
+    /// Firmware versions, boot mode and boot count (Joint → Arm)
+    BootInfo(BootInfoPayload) = 32,
+
+    // Configuration Storage (v2.2)
+    /// Persist limits, calibration result, node ID and telemetry settings
+    SaveConfig = 33,
+    /// Re-apply the persisted configuration (not while Active; the node ID
+    /// changes only at the next boot)
+    LoadConfig = 34,
+    /// Erase the persisted configuration and drop the applied one (not while Active)
+    FactoryReset = 35,
+
+    // Commissioning (v2.2)
+    /// Give an unaddressed joint its node ID, which it persists (Arm → broadcast)
+    AssignId(AssignIdPayload) = 36,
+
+    // Auxiliary I/O (v2.2)
+    /// Drive a digital output (brake, fan, ...), answered with `Ack`
+    SetDigitalOutput(DigitalIoPayload) = 37,
+    /// Read a digital input channel, answered with `DigitalInput`
+    ReadDigitalInput(u8) = 38,
+    /// Digital input level (Joint → Arm)
+    DigitalInput(DigitalIoPayload) = 39,
+    /// Read an analog input channel, answered with `AnalogInput`
+    ReadAnalogInput(u8) = 40,
+    /// Analog input reading (Joint → Arm)
+    AnalogInput(AnalogInputPayload) = 41,
+
+    // Holding Brake (v2.2)
+    /// Engage the holding brake (always accepted)
+    EngageBrake = 42,
+    /// Release the holding brake (refused while Unconfigured or in Error)
+    ReleaseBrake = 43,
+
+    // Homing (v2.2)
+    /// Run the homing procedure described by the config (only works when
+    /// joint is Active); the joint reports `Calibrating` until it finishes
+    StartHoming(HomingConfig) = 44,
+
+    // Encoder Offset (v2.2)
+    /// Set the raw encoder position that reads as zero (refused while Active)
+    SetEncoderOffset(MultiTurnPosition) = 45,
+    /// Request the joint's position with its turn count
+    RequestMultiTurnPosition = 46,
+    /// Offset-corrected position (Joint → Arm)
+    MultiTurnPosition(MultiTurnPosition) = 47,
+
+    // Control Loop (v2.2)
+    /// Set the gains of the joint's position/velocity controller
+    ConfigureGains(ControlGains) = 48,
+    /// Set one slot of the joint's gain schedule
+    SetGainScheduleEntry(GainScheduleEntry) = 49,
+    /// Empty the gain schedule; the base gains apply everywhere
+    ClearGainSchedule = 50,
+
+    // Feedforward (v2.2)
+    /// Torque in newton-metres the control loop adds to its output (gravity
+    /// compensation), streamed alongside targets (only valid in Active state)
+    SetFeedforward { torque: f32 } = 51,
+
+    // Direct Control (v2.2)
+    /// Switch what the control loop follows (refused while Active)
+    SetControlMode(ControlMode) = 52,
+    /// Speed in degrees/second to hold in `ControlMode::Velocity` (only
+    /// valid in Active state)
+    SetVelocity { dps: f32 } = 53,
+    /// Torque in newton-metres to apply in `ControlMode::Torque` (only
+    /// valid in Active state)
+    SetTorque { torque_nm: f32 } = 54,
+    // Flight Recorder (v2.2)
+    /// Request flight recorder entries numbered `from` and up, answered with
+    /// `FlightRecorder` (from the oldest entry held if `from` was overwritten)
+    RequestFlightRecorder { from: u32 } = 55,
+    /// Flight recorder entries (Joint → Arm); a chunk that is not full ends
+    /// the recording
+    FlightRecorder(FlightRecorderChunk) = 56,
+    // Safety Enable (v2.2)
+    /// Keep a joint that requires it enabled for `ttl_ms` milliseconds;
+    /// `token` must differ from the previous one. When it runs out the
+    /// joint stops and deactivates
+    SafetyEnable { token: u32, ttl_ms: u16 } = 57,
+    // Collision Reaction (v2.2)
+    /// The joint detected a collision or overload and reacted on its own
+    /// (Joint → Arm)
+    FaultReport(FaultReportPayload) = 58,
+    // Thermal Protection (v2.2)
+    /// Set the temperatures a joint derates and faults at
+    ConfigureThermal(ThermalLimits) = 59,
+    // Power Monitoring (v2.2)
+    /// Set the supply voltages a joint warns and stops at
+    ConfigurePower(PowerLimits) = 60,
+    /// Request the joint's supply readings, answered with `PowerStatus`
+    RequestPowerStatus = 61,
+    /// Supply voltage and current (Joint → Arm)
+    PowerStatus(PowerStatusPayload) = 62,
+    // Usage Statistics (v2.2)
+    /// Request the joint's usage counters, answered with `Statistics`
+    RequestStatistics = 63,
+    /// Energy, motor-on time and move count (Joint → Arm)
+    Statistics(JointStatistics) = 64,
+    // Self-Test (v2.2)
+    /// Check the encoder, driver and, optionally, motion; answered with
+    /// `SelfTestResult` once the motion test is over
+    RunSelfTest(SelfTestRequest) = 65,
+    /// Outcome of every check (Joint → Arm)
+    SelfTestResult(SelfTestResult) = 66,
+    // Streaming (v2.2)
+    /// Acknowledges the commands streamed since the previous one, up to
+    /// `last_msg_id`, in place of an `Ack` each; `dropped` of them never
+    /// arrived (Joint → Arm, see `STREAM_MSG_ID_FLAG`)
+    StreamAck { last_msg_id: MessageId, dropped: u32 } = 67,
+    // Target Batches (v2.2)
+    /// A target for each listed joint, taken like a `SetTargetV2`. Sent to
+    /// one of the joints, the group master, which acknowledges the batch;
+    /// the others answer only to refuse their target
+    SetTargetBatch(TargetBatch) = 68,
+    // Cyclic Synchronous Mode (v2.2)
+    /// Broadcast once per cycle: joints in `ControlMode::CyclicPosition`
+    /// take the target they hold and answer with a `SyncSample`
+    Sync { cycle_counter: u32 } = 69,
+    /// Position and velocity at the `Sync` (Joint → Arm)
+    SyncSample(SyncSample) = 70,
+}
+
+impl Payload {
+    /// Exact encoded size of this payload in bytes (excluding the header)
+    pub fn encoded_size(&self) -> usize {
+        // The size-counting serializer cannot fail for protocol types
+        postcard::experimental::serialized_size(self).unwrap_or(usize::MAX)
+    }
+
+    /// Whether this variant's worst-case encoding exceeds a single CAN-FD frame
+    ///
+    /// Such payloads can only be sent over transports that fragment or have
+    /// a larger MTU. The list is checked against the computed type sizes at
+    /// compile time, so it cannot go stale.
+    pub const fn requires_fragmentation(&self) -> bool {
+        matches!(self, Payload::TelemetryStream(_) | Payload::CalibrationResult(_))
+    }
+
+    /// Variant tag, its discriminant and the first byte of the encoding
+    ///
+    /// Compact enough to keep in a joint's flight recorder;
+    /// [`tag_name`](Self::tag_name) gives the variant name back.
+    pub const fn tag(&self) -> u8 {
+        // SAFETY: a `repr(u8)` enum starts with its discriminant as a `u8`
+        unsafe { *(self as *const Self).cast::<u8>() }
+    }
+
+    /// Name of the variant with wire tag `tag`, if there is one
+    pub fn tag_name(tag: u8) -> Option<&'static str> {
+        PAYLOAD_NAMES.get(tag as usize).copied()
+    }
+
+    /// View an encoded payload without decoding its fields
+    ///
+    /// Only the tag is read; `bytes` must start with it. See [`PayloadRef`].
+    pub fn deserialize_borrowed(bytes: &[u8]) -> Result<PayloadRef<'_>, ProtocolError> {
+        match bytes.first() {
+            Some(&tag) if Self::tag_name(tag).is_some() => Ok(PayloadRef { bytes }),
+            Some(_) => Err(ProtocolError::DeserializationError(error_text("Unknown payload tag"))),
+            None => Err(ProtocolError::DeserializationError(error_text("Missing payload tag"))),
+        }
+    }
+
+    /// Variant name, e.g. `"SetTarget"` (the key used by the JSON encoding)
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Payload::Configure => "Configure",
+            Payload::Activate => "Activate",
+            Payload::Deactivate => "Deactivate",
+            Payload::Reset => "Reset",
+            Payload::SetTarget(_) => "SetTarget",
+            Payload::SetTargetV2(_) => "SetTargetV2",
+            Payload::Encoder(_) => "Encoder",
+            Payload::JointStatus { .. } => "JointStatus",
+            Payload::TelemetryStream(_) => "TelemetryStream",
+            Payload::ConfigureTelemetry(_) => "ConfigureTelemetry",
+            Payload::RequestTelemetry => "RequestTelemetry",
+            Payload::ConfigureAdaptive(_) => "ConfigureAdaptive",
+            Payload::RequestAdaptiveStatus => "RequestAdaptiveStatus",
+            Payload::AdaptiveStatus(_) => "AdaptiveStatus",
+            Payload::StartCalibration(_) => "StartCalibration",
+            Payload::StopCalibration => "StopCalibration",
+            Payload::CalibrationStatus(_) => "CalibrationStatus",
+            Payload::CalibrationResult(_) => "CalibrationResult",
+            Payload::Ack(_) => "Ack",
+            Payload::Nack { .. } => "Nack",
+            Payload::ArmReady => "ArmReady",
+            Payload::Discover => "Discover",
+            Payload::Hello(_) => "Hello",
+            Payload::RequestBusStats => "RequestBusStats",
+            Payload::BusStats(_) => "BusStats",
+            Payload::RequestStatus => "RequestStatus",
+            Payload::Home => "Home",
+            Payload::SetLimits(_) => "SetLimits",
+            Payload::Boot(_) => "Boot",
+            Payload::InterlockState(_) => "InterlockState",
+            Payload::EnterBootloader => "EnterBootloader",
+            Payload::RequestBootInfo => "RequestBootInfo",
+            Payload::BootInfo(_) => "BootInfo",
+            Payload::SaveConfig => "SaveConfig",
+            Payload::LoadConfig => "LoadConfig",
+            Payload::FactoryReset => "FactoryReset",
+            Payload::AssignId(_) => "AssignId",
+            Payload::SetDigitalOutput(_) => "SetDigitalOutput",
+            Payload::ReadDigitalInput(_) => "ReadDigitalInput",
+            Payload::DigitalInput(_) => "DigitalInput",
+            Payload::ReadAnalogInput(_) => "ReadAnalogInput",
+            Payload::AnalogInput(_) => "AnalogInput",
+            Payload::EngageBrake => "EngageBrake",
+            Payload::ReleaseBrake => "ReleaseBrake",
+            Payload::StartHoming(_) => "StartHoming",
+            Payload::SetEncoderOffset(_) => "SetEncoderOffset",
+            Payload::RequestMultiTurnPosition => "RequestMultiTurnPosition",
+            Payload::MultiTurnPosition(_) => "MultiTurnPosition",
+            Payload::ConfigureGains(_) => "ConfigureGains",
+            Payload::SetGainScheduleEntry(_) => "SetGainScheduleEntry",
+            Payload::ClearGainSchedule => "ClearGainSchedule",
+            Payload::SetFeedforward { .. } => "SetFeedforward",
+            Payload::SetControlMode(_) => "SetControlMode",
+            Payload::SetVelocity { .. } => "SetVelocity",
+            Payload::SetTorque { .. } => "SetTorque",
+            Payload::RequestFlightRecorder { .. } => "RequestFlightRecorder",
+            Payload::FlightRecorder(_) => "FlightRecorder",
+            Payload::SafetyEnable { .. } => "SafetyEnable",
+            Payload::FaultReport(_) => "FaultReport",
+            Payload::ConfigureThermal(_) => "ConfigureThermal",
+            Payload::ConfigurePower(_) => "ConfigurePower",
+            Payload::RequestPowerStatus => "RequestPowerStatus",
+            Payload::PowerStatus(_) => "PowerStatus",
+            Payload::RequestStatistics => "RequestStatistics",
+            Payload::Statistics(_) => "Statistics",
+            Payload::RunSelfTest(_) => "RunSelfTest",
+            Payload::SelfTestResult(_) => "SelfTestResult",
+            Payload::StreamAck { .. } => "StreamAck",
+            Payload::SetTargetBatch(_) => "SetTargetBatch",
+            Payload::Sync { .. } => "Sync",
+            Payload::SyncSample(_) => "SyncSample",
+        }
+    }
 }
 
+/// Variant names in wire order, for [`Payload::tag_name`]
+const PAYLOAD_NAMES: [&str; 71] = [
+    "SetTarget", "Configure", "Activate", "Deactivate", "Reset", "SetTargetV2", "Encoder", "JointStatus",
+    "TelemetryStream", "ConfigureTelemetry", "RequestTelemetry", "ConfigureAdaptive", "RequestAdaptiveStatus",
+    "AdaptiveStatus", "StartCalibration", "StopCalibration", "CalibrationStatus", "CalibrationResult", "Ack", "Nack",
+    "ArmReady", "Discover", "Hello", "RequestBusStats", "BusStats", "RequestStatus", "Home", "SetLimits", "Boot",
+    "InterlockState", "EnterBootloader", "RequestBootInfo", "BootInfo", "SaveConfig", "LoadConfig", "FactoryReset",
+    "AssignId", "SetDigitalOutput", "ReadDigitalInput", "DigitalInput", "ReadAnalogInput", "AnalogInput",
+    "EngageBrake", "ReleaseBrake", "StartHoming", "SetEncoderOffset", "RequestMultiTurnPosition", "MultiTurnPosition",
+    "ConfigureGains", "SetGainScheduleEntry", "ClearGainSchedule", "SetFeedforward", "SetControlMode", "SetVelocity",
+    "SetTorque", "RequestFlightRecorder", "FlightRecorder", "SafetyEnable", "FaultReport",
+    "ConfigureThermal", "ConfigurePower", "RequestPowerStatus", "PowerStatus", "RequestStatistics", "Statistics",
+    "RunSelfTest", "SelfTestResult", "StreamAck", "SetTargetBatch", "Sync", "SyncSample",
+];
+
+/// Fingerprint of the `Payload` layout this build speaks (v2.2)
+///
+/// FNV-1a over the variant names in wire order and the largest encoded
+/// payload. Joints send theirs in `Hello`, so a host and firmware built
+/// from diverging definitions (a variant inserted, removed or reordered)
+/// are caught at discovery instead of misreading each other's messages.
+pub const SCHEMA_HASH: u32 = schema_hash(&PAYLOAD_NAMES, <Payload as MaxSize>::POSTCARD_MAX_SIZE);
+
+const fn schema_hash(names: &[&str], max_size: usize) -> u32 {
+    const FNV_PRIME: u32 = 0x0100_0193;
+    let mut hash: u32 = 0x811C_9DC5;
+    let mut i = 0;
+    while i < names.len() {
+        let name = names[i].as_bytes();
+        let mut j = 0;
+        while j < name.len() {
+            hash = (hash ^ name[j] as u32).wrapping_mul(FNV_PRIME);
+            j += 1;
+        }
+        // Keeps "AB", "C" apart from "A", "BC"
+        hash = (hash ^ b';' as u32).wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+    let size = (max_size as u32).to_le_bytes();
+    let mut k = 0;
+    while k < size.len() {
+        hash = (hash ^ size[k] as u32).wrapping_mul(FNV_PRIME);
+        k += 1;
+    }
+    hash
+}
+
+/// One-line summary: the variant name and its key fields
+///
+/// Meant for logs and bus sniffers; use `Debug` for every field.
+impl core::fmt::Display for Payload {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())?;
+        match self {
+            Payload::SetTarget(t) => {
+                write!(f, " angle={:.3} vel_limit={:.3}", t.target_angle, t.velocity_limit)
+            }
+            Payload::SetTargetV2(t) => write!(
+                f,
+                " angle={:.3} max_vel={:.3} max_acc={:.3}",
+                t.target_angle, t.max_velocity, t.max_acceleration
+            ),
+            Payload::Encoder(e) => write!(f, " pos={:.3} vel={:.3}", e.position, e.velocity),
+            Payload::JointStatus { state, error_code } => {
+                write!(f, " state={:?} error={}", state, error_code)
+            }
+            Payload::TelemetryStream(t) => write!(
+                f,
+                " t={}us pos={:.3} vel={:.3} iq={:.2} temp={:.1}",
+                t.timestamp_us, t.position, t.velocity, t.current_q, t.temperature_c
+            ),
+            Payload::ConfigureTelemetry(c) => write!(f, " mode={:?} rate={}Hz", c.mode, c.rate_hz),
+            Payload::AdaptiveStatus(a) => {
+                write!(f, " load={:.1}% current_scale={:.2}", a.load_percent, a.current_scale)
+            }
+            Payload::StartCalibration(c) => write!(f, " phases={:#07b}", c.phases),
+            Payload::CalibrationStatus(c) => {
+                write!(f, " phase={:?} progress={:.0}%", c.phase, c.progress * 100.0)
+            }
+            Payload::CalibrationResult(r) => {
+                write!(f, " success={} error={} time={:.1}s", r.success, r.error_code, r.total_time)
+            }
+            Payload::Ack(id) => write!(f, " #{}", id),
+            Payload::Nack { id, error } => write!(f, " #{} error={}", id, error),
+            Payload::StreamAck { last_msg_id, dropped } => write!(f, " #{} dropped={}", last_msg_id, dropped),
+            Payload::Sync { cycle_counter } => write!(f, " cycle={}", cycle_counter),
+            Payload::SyncSample(s) => {
+                write!(f, " cycle={} pos={:.3} vel={:.3}", s.cycle_counter, s.position, s.velocity)
+            }
+            Payload::SetTargetBatch(b) => {
+                for BatchTarget { joint_id, target } in b.targets() {
+                    write!(f, " {:#06x}:{:.3}@{:.3}", joint_id, target.target_angle, target.velocity_limit)?;
+                }
+                Ok(())
+            }
+            Payload::Hello(h) => write!(
+                f,
+                " entity={:#06x} mode={:?} caps={:#x} schema={:#010x}",
+                h.entity_type,
+                h.mode,
+                h.capabilities.bits(),
+                h.schema
+            ),
+            Payload::BusStats(s) => write!(
+                f,
+                " tx={} rx={} errors={}",
+                s.tx_frames,
+                s.rx_frames,
+                s.serialization_failures
+                    .saturating_add(s.deserialization_failures)
+                    .saturating_add(s.transport_errors)
+            ),
+            Payload::SetLimits(l) => write!(
+                f,
+                " pos=[{:.3}, {:.3}] max_vel={:.3}",
+                l.min_position, l.max_position, l.max_velocity
+            ),
+            Payload::Boot(b) => match &b.crash {
+                Some(crash) => write!(
+                    f,
+                    " entity={:#06x} crash={:?} task={:?} {:?}",
+                    b.entity_type,
+                    crash.kind,
+                    crash.task(),
+                    crash.message()
+                ),
+                None => write!(f, " entity={:#06x}", b.entity_type),
+            },
+            Payload::InterlockState(i) => write!(f, " wired={:#06x} tripped={:#06x}", i.wired, i.tripped),
+            Payload::BootInfo(b) => write!(
+                f,
+                " mode={:?} app={} bootloader={} boots={}",
+                b.mode, b.app_version, b.bootloader_version, b.boot_count
+            ),
+            Payload::AssignId(a) => write!(f, " serial={:#018x} id={:#06x}", a.serial_number, a.new_id),
+            Payload::SetDigitalOutput(io) | Payload::DigitalInput(io) => {
+                write!(f, " ch={} value={}", io.channel, io.value)
+            }
+            Payload::ReadDigitalInput(channel) | Payload::ReadAnalogInput(channel) => write!(f, " ch={}", channel),
+            Payload::AnalogInput(a) => write!(f, " ch={} value={:.3}", a.channel, a.value),
+            Payload::StartHoming(h) => write!(
+                f,
+                " method={:?} dir={:?} speed={:.1} offset={:.3}",
+                h.method, h.direction, h.speed, h.offset
+            ),
+            Payload::SetEncoderOffset(p) | Payload::MultiTurnPosition(p) => {
+                write!(f, " turns={} angle={:.3}", p.turns, p.angle)
+            }
+            Payload::ConfigureGains(g) => write!(
+                f,
+                " pos=[{}, {}, {}] vel=[{}, {}, {}] max_current={:.3}",
+                g.position.kp, g.position.ki, g.position.kd, g.velocity.kp, g.velocity.ki, g.velocity.kd, g.max_current
+            ),
+            Payload::SetFeedforward { torque } => write!(f, " torque={:.3}", torque),
+            Payload::SetControlMode(mode) => write!(f, " mode={:?}", mode),
+            Payload::SetVelocity { dps } => write!(f, " dps={:.3}", dps),
+            Payload::SetTorque { torque_nm } => write!(f, " torque={:.3}", torque_nm),
+            Payload::RequestFlightRecorder { from } => write!(f, " from={}", from),
+            Payload::FlightRecorder(chunk) => write!(f, " first={} len={}", chunk.first, chunk.records().len()),
+            Payload::SafetyEnable { token, ttl_ms } => write!(f, " token={} ttl={}ms", token, ttl_ms),
+            Payload::FaultReport(r) => write!(
+                f,
+                " cause={:?} reaction={:?} pos={:.3} current={:.3}",
+                r.cause, r.reaction, r.position, r.current
+            ),
+            Payload::ConfigureThermal(t) => write!(
+                f,
+                " warning={:.1}C critical={:.1}C min_scale={:.2}",
+                t.warning_c, t.critical_c, t.min_scale
+            ),
+            Payload::ConfigurePower(p) => {
+                write!(f, " undervoltage={:.1}V brownout={:.1}V", p.undervoltage_v, p.brownout_v)
+            }
+            Payload::PowerStatus(p) => write!(f, " bus={:.2}V {:.3}A", p.bus_voltage, p.bus_current),
+            Payload::Statistics(s) => {
+                write!(f, " energy={:.3}Wh on={}s moves={}", s.energy_wh, s.motor_on_s, s.move_count)
+            }
+            Payload::RunSelfTest(r) => write!(f, " nonce={:#010x} motion={:.3}", r.nonce, r.motion_deg),
+            Payload::SelfTestResult(r) => write!(
+                f,
+                " nonce={:#010x} encoder={:?} driver={:?} motion={:?} moved={:.3}",
+                r.nonce, r.encoder, r.driver, r.motion, r.moved_deg
+            ),
+            Payload::SetGainScheduleEntry(e) => write!(
+                f,
+                " slot={} profiles={:#04x} min_vel={:.3} min_load={:.3}",
+                e.slot, e.region.profiles, e.region.min_velocity, e.region.min_load
+            ),
+            Payload::Configure
+            | Payload::Activate
+            | Payload::Deactivate
+            | Payload::Reset
+            | Payload::RequestTelemetry
+            | Payload::ConfigureAdaptive(_)
+            | Payload::RequestAdaptiveStatus
+            | Payload::StopCalibration
+            | Payload::ArmReady
+            | Payload::Discover
+            | Payload::RequestBusStats
+            | Payload::RequestStatus
+            | Payload::Home
+            | Payload::EnterBootloader
+            | Payload::RequestBootInfo
+            | Payload::SaveConfig
+            | Payload::LoadConfig
+            | Payload::FactoryReset
+            | Payload::EngageBrake
+            | Payload::ReleaseBrake
+            | Payload::RequestMultiTurnPosition
+            | Payload::ClearGainSchedule
+            | Payload::RequestPowerStatus
+            | Payload::RequestStatistics => Ok(()),
+        }
+    }
+}
+
+/// Encoded size of the `Payload` variant tag (a varint; one byte while the
+/// enum has fewer than 128 variants)
+const PAYLOAD_TAG_SIZE: usize = 1;
+
+/// Whether a message carrying a `T` payload always fits one CAN-FD frame
+const fn fits_canfd_frame<T: MaxSize>() -> bool {
+    Header::POSTCARD_MAX_SIZE + PAYLOAD_TAG_SIZE + T::POSTCARD_MAX_SIZE <= CANFD_MAX_DATA_LEN
+}
+
+// Compile-time frame budget: every payload must fit one CAN-FD frame unless it
+// is listed in `Payload::requires_fragmentation`, and listed ones must not fit
+// (otherwise the list is stale). New payload variants must be added here.
+const _: () = {
+    assert!(fits_canfd_frame::<SetTargetPayload>());
+    assert!(fits_canfd_frame::<SetTargetPayloadV2>());
+    assert!(fits_canfd_frame::<EncoderTelemetry>());
+    assert!(fits_canfd_frame::<(LifecycleState, u16)>()); // JointStatus
+    assert!(fits_canfd_frame::<ConfigureTelemetryPayload>());
+    assert!(fits_canfd_frame::<ConfigureAdaptivePayload>());
+    assert!(fits_canfd_frame::<AdaptiveStatusPayload>());
+    assert!(fits_canfd_frame::<CalibrationRequest>());
+    assert!(fits_canfd_frame::<CalibrationStatus>());
+    assert!(fits_canfd_frame::<MessageId>()); // Ack
+    assert!(fits_canfd_frame::<(MessageId, u16)>()); // Nack
+    assert!(fits_canfd_frame::<HelloPayload>());
+    assert!(fits_canfd_frame::<TransportStats>());
+    assert!(fits_canfd_frame::<JointLimits>());
+    assert!(fits_canfd_frame::<BootPayload>());
+    assert!(fits_canfd_frame::<InterlockStatePayload>());
+    assert!(fits_canfd_frame::<BootInfoPayload>());
+    assert!(fits_canfd_frame::<AssignIdPayload>());
+    assert!(fits_canfd_frame::<DigitalIoPayload>());
+    assert!(fits_canfd_frame::<AnalogInputPayload>());
+    assert!(fits_canfd_frame::<HomingConfig>());
+    assert!(fits_canfd_frame::<MultiTurnPosition>());
+    assert!(fits_canfd_frame::<ControlGains>());
+    assert!(fits_canfd_frame::<GainScheduleEntry>());
+    assert!(fits_canfd_frame::<f32>()); // SetFeedforward
+    assert!(fits_canfd_frame::<ControlMode>());
+    assert!(fits_canfd_frame::<f32>()); // SetVelocity, SetTorque
+    assert!(fits_canfd_frame::<u32>()); // RequestFlightRecorder
+    assert!(fits_canfd_frame::<FlightRecorderChunk>());
+    assert!(fits_canfd_frame::<(u32, u16)>()); // SafetyEnable
+    assert!(fits_canfd_frame::<(MessageId, u32)>()); // StreamAck
+    assert!(fits_canfd_frame::<TargetBatch>());
+    assert!(fits_canfd_frame::<u32>()); // Sync
+    assert!(fits_canfd_frame::<SyncSample>());
+    assert!(fits_canfd_frame::<FaultReportPayload>());
+    assert!(fits_canfd_frame::<ThermalLimits>());
+    assert!(fits_canfd_frame::<PowerLimits>());
+    assert!(fits_canfd_frame::<PowerStatusPayload>());
+    assert!(fits_canfd_frame::<JointStatistics>());
+    assert!(fits_canfd_frame::<SelfTestRequest>());
+    assert!(fits_canfd_frame::<SelfTestResult>());
+
+    // Marked as requiring fragmentation
+    assert!(!fits_canfd_frame::<TelemetryStream>());
+    assert!(!fits_canfd_frame::<CalibrationResult>());
+};
+
 /// Message header containing routing and correlation information
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
 pub struct Header {
     /// Source device ID
     pub source_id: DeviceId,
@@ -423,87 +2265,261 @@ pub struct Header {
 }
 
 /// Complete iRPC message with header and payload
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, MaxSize, Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Message {
   pub header: Header,
   pub payload: Payload,
 }
 
 /// Protocol error types
-#[derive(Debug, Clone)]
-#[cfg_attr(feature = "arm_api", derive(thiserror::Error))]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum ProtocolError {
     /// Invalid message format
-    #[cfg_attr(feature = "arm_api", error("Invalid message format"))]
+    #[error("Invalid message format")]
     InvalidMessage,
 
     /// Unsupported protocol version
-    #[cfg_attr(feature = "arm_api", error("Unsupported protocol version"))]
+    #[error("Unsupported protocol version")]
     UnsupportedVersion,
 
     /// Communication timeout
-    #[cfg_attr(feature = "arm_api", error("Communication timeout"))]
+    #[error("Communication timeout")]
     Timeout,
 
     /// General IO error
-    #[cfg_attr(feature = "arm_api", error("IO error for message {0}"))]
+    #[error("IO error for message {0}")]
     IoError(MessageId),
 
     /// Serialization error
-    #[cfg_attr(feature = "arm_api", error("Serialization failed: {0}"))]
-    SerializationError(String),
+    #[error("Serialization failed: {0}")]
+    SerializationError(ErrorString),
 
     /// Deserialization error
-    #[cfg_attr(feature = "arm_api", error("Deserialization failed: {0}"))]
-    DeserializationError(String),
+    #[error("Deserialization failed: {0}")]
+    DeserializationError(ErrorString),
 
     /// Invalid lifecycle state transition
-    #[cfg_attr(feature = "arm_api", error("Invalid state transition"))]
+    #[error("Invalid state transition")]
     InvalidStateTransition,
 
     /// Hardware error
-    #[cfg_attr(feature = "arm_api", error("Hardware error: {0}"))]
+    #[error("Hardware error: {0}")]
     HardwareError(u16),
+
+    /// Encoded message exceeds what the transport can carry
+    #[error("Payload too large: {size} bytes exceeds limit of {limit} bytes")]
+    PayloadTooLarge { size: usize, limit: usize },
+
+    /// Another command to the same joint is in flight (`CommandPolicy::Reject`)
+    #[error("Joint busy with another command")]
+    Busy,
+
+    /// A newer setpoint replaced this one before it was sent (`CommandPolicy::Coalesce`
+    /// or rate limiting)
+    #[error("Command superseded by a newer setpoint")]
+    Superseded,
+
+    /// An emergency stop was issued while this command was waiting
+    #[error("Command cancelled by emergency stop")]
+    Cancelled,
+
+    /// A configured interlock is tripped (or has not reported yet)
+    #[error("Refused by an active interlock")]
+    Interlocked,
+
+    /// The host's safety limits refused the command (see `safety`)
+    #[error("Refused by the safety limits")]
+    SafetyViolation,
+
+    /// The joint does not advertise the capability the command needs
+    #[error("Command not supported by the joint")]
+    Unsupported,
+
+    /// The joint's firmware was built from a different `Payload` layout
+    /// (its `SCHEMA_HASH` differs); nothing is sent to it
+    #[error("Payload schema mismatch: host {local:#010x}, joint {remote:#010x}")]
+    SchemaMismatch { local: u32, remote: u32 },
+
+    /// The host's outbound queue is full, e.g. because the transport is stuck
+    #[error("Outbound queue full")]
+    QueueFull,
+}
+
+/// Error text for [`ProtocolError`], built without `std`
+///
+/// With `no_alloc` the text is cut off at the `ErrorString` capacity.
+pub(crate) fn error_text(error: impl core::fmt::Display) -> ErrorString {
+    struct Truncating<'a>(&'a mut ErrorString);
+
+    impl core::fmt::Write for Truncating<'_> {
+        #[cfg(not(feature = "no_alloc"))]
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            self.0.push_str(s);
+            Ok(())
+        }
+
+        #[cfg(feature = "no_alloc")]
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            s.chars().try_for_each(|c| self.0.push(c).map_err(|_| core::fmt::Error))
+        }
+    }
+
+    let mut text = ErrorString::new();
+    // A full string ends the write early; the text so far is kept
+    let _ = core::fmt::write(&mut Truncating(&mut text), format_args!("{}", error));
+    text
 }
 
 impl Message {
     /// Serialize message to bytes using postcard
+    ///
+    /// Not available with the `no_alloc` feature; use [`Message::serialize_into`].
+    #[cfg(not(feature = "no_alloc"))]
     pub fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
-        #[cfg(feature = "arm_api")]
-        {
-            postcard::to_stdvec(self).map_err(|e| {
-                ProtocolError::SerializationError(e.to_string())
-            })
-        }
+        postcard::to_extend(self, Vec::new()).map_err(|e| ProtocolError::SerializationError(error_text(e)))
+    }
 
-        #[cfg(not(feature = "arm_api"))]
-        {
-            postcard::to_allocvec(self).map_err(|_| {
-                ProtocolError::SerializationError(String::new())
-            })
-        }
+    /// Exact encoded size of this message in bytes
+    pub fn encoded_size(&self) -> usize {
+        postcard::experimental::serialized_size(self).unwrap_or(usize::MAX)
+    }
+
+    /// Serialize message into a caller-provided buffer using postcard
+    ///
+    /// Returns the number of bytes written. Unlike [`Message::serialize`], this
+    /// never touches the heap, which makes it suitable for firmware hot paths.
+    /// Fails with `SerializationError` if `buf` is too small for the message.
+    pub fn serialize_into(&self, buf: &mut [u8]) -> Result<usize, ProtocolError> {
+        postcard::to_slice(self, buf)
+            .map(|used| used.len())
+            .map_err(|e| ProtocolError::SerializationError(error_text(e)))
     }
 
     /// Deserialize message from bytes using postcard
     pub fn deserialize(bytes: &[u8]) -> Result<Self, ProtocolError> {
-        #[cfg(feature = "arm_api")]
-        {
-            postcard::from_bytes(bytes).map_err(|e| {
-                ProtocolError::DeserializationError(e.to_string())
-            })
-        }
-
-        #[cfg(not(feature = "arm_api"))]
-        {
-            postcard::from_bytes(bytes).map_err(|_| {
-                ProtocolError::DeserializationError(String::new())
-            })
-        }
+        postcard::from_bytes(bytes).map_err(|e| ProtocolError::DeserializationError(error_text(e)))
     }
 
-    /// Get the maximum serialized size estimate (for buffer allocation)
+    /// Get the maximum serialized size of any message (for buffer allocation)
+    ///
+    /// Computed at compile time from the protocol type definitions, so a
+    /// buffer of this size can never truncate an encoded message.
     pub const fn max_size() -> usize {
-        // Header (2 + 2 + 4 = 8 bytes) + Payload (worst case ~20 bytes) + overhead
-        128
+        <Message as MaxSize>::POSTCARD_MAX_SIZE
+    }
+}
+
+/// A payload still encoded in the buffer it arrived in
+///
+/// Firmware can route on the variant and decode just the fields it acts on,
+/// without a full [`Payload`] on the stack. Made by
+/// [`Payload::deserialize_borrowed`]; the tag is known to be valid, the
+/// fields are only checked when decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadRef<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> PayloadRef<'a> {
+    /// Variant tag, as [`Payload::tag`] would return once decoded
+    pub fn tag(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    /// Variant name, e.g. `"SetTarget"`
+    pub fn name(&self) -> &'static str {
+        Payload::tag_name(self.tag()).unwrap_or("Unknown")
+    }
+
+    /// The encoded payload, tag included
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
     }
-}
\ No newline at end of file
+
+    /// The encoded fields, after the tag
+    pub fn body(&self) -> &'a [u8] {
+        &self.bytes[1..]
+    }
+
+    /// Decode the variant's fields as `T`
+    ///
+    /// `T` is the variant's field type (`SetTargetPayload` for `SetTarget`),
+    /// a tuple of the fields of a struct variant in declaration order
+    /// (`(LifecycleState, u16)` for `JointStatus`), or `()` for a unit
+    /// variant. Types that borrow, such as `&[u8]`, borrow from the buffer.
+    /// The tag is not checked against `T`; match on [`tag`](Self::tag) first.
+    pub fn fields<T: Deserialize<'a>>(&self) -> Result<T, ProtocolError> {
+        postcard::from_bytes(self.body()).map_err(|e| ProtocolError::DeserializationError(error_text(e)))
+    }
+
+    /// Decode the whole payload
+    pub fn decode(&self) -> Result<Payload, ProtocolError> {
+        postcard::from_bytes(self.bytes).map_err(|e| ProtocolError::DeserializationError(error_text(e)))
+    }
+}
+
+/// A message whose payload is still encoded in the receive buffer
+///
+/// The borrowed counterpart of [`Message`]: only the header is decoded.
+///
+/// ```
+/// use irpc::{MessageRef, SetTargetPayload};
+///
+/// // 0x0001 -> 0x0010 #7 SetTarget 45° at 30°/s
+/// let frame = [0x01, 0x10, 0x07, 0x00, 0x00, 0x00, 0x34, 0x42, 0x00, 0x00, 0xF0, 0x41];
+/// let message = MessageRef::deserialize(&frame)?;
+/// assert_eq!(message.header.target_id, 0x0010);
+/// if message.payload.name() == "SetTarget" {
+///     let target: SetTargetPayload = message.payload.fields()?;
+///     assert_eq!(target.target_angle, 45.0);
+/// }
+/// # Ok::<(), irpc::ProtocolError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct MessageRef<'a> {
+    pub header: Header,
+    pub payload: PayloadRef<'a>,
+}
+
+impl<'a> MessageRef<'a> {
+    /// Decode the header of an encoded message, borrowing the payload
+    pub fn deserialize(bytes: &'a [u8]) -> Result<Self, ProtocolError> {
+        let (header, payload) =
+            postcard::take_from_bytes(bytes).map_err(|e| ProtocolError::DeserializationError(error_text(e)))?;
+        Ok(Self { header, payload: Payload::deserialize_borrowed(payload)? })
+    }
+
+    /// Decode the payload too, into an owned [`Message`]
+    pub fn decode(&self) -> Result<Message, ProtocolError> {
+        Ok(Message { header: self.header.clone(), payload: self.payload.decode()? })
+    }
+}
+
+#[cfg(feature = "json")]
+impl Message {
+    /// Encode as JSON, for logs and debugging tools
+    ///
+    /// Payloads are externally tagged with their variant name, e.g.
+    /// `{"header":{...},"payload":{"Ack":42}}`; unit variants are plain
+    /// strings (`"payload":"Activate"`).
+    pub fn to_json(&self) -> Result<String, ProtocolError> {
+        serde_json::to_string(self).map_err(|e| ProtocolError::SerializationError(error_text(e)))
+    }
+
+    /// Decode a message produced by [`Message::to_json`]
+    pub fn from_json(text: &str) -> Result<Self, ProtocolError> {
+        serde_json::from_str(text).map_err(|e| ProtocolError::DeserializationError(error_text(e)))
+    }
+}
+
+/// `0x0001 -> 0x0010 #42 SetTarget angle=1.571 vel_limit=2.000`
+impl core::fmt::Display for Message {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:#06x} -> {:#06x} #{} {}",
+            self.header.source_id, self.header.target_id, self.header.msg_id, self.payload
+        )
+    }
+}
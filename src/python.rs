@@ -0,0 +1,490 @@
+//! Python bindings for the arm API
+//!
+//! Exposes [`ArmClient`], [`JointProxy`], telemetry subscriptions and
+//! calibration as the `irpc` Python extension module, with every bus
+//! operation an `async` method for use under `asyncio`. Build and install it
+//! into the active virtualenv with [maturin](https://www.maturin.rs) (the
+//! features are set in `pyproject.toml`):
+//!
+//! ```text
+//! maturin develop --release
+//! ```
+//!
+//! ```python
+//! import asyncio
+//! import irpc
+//!
+//! async def main():
+//!     arm = irpc.ArmClient.connect_tcp("192.168.1.50:9000", [0x10, 0x11])
+//!     await arm.initialize()
+//!     await arm.joint(0x10).set_target(45.0, 30.0)
+//!     async for sample in arm.subscribe_telemetry(joints=[0x10], topics=["motion"]):
+//!         print(sample["data"]["position"])
+//!
+//! asyncio.run(main())
+//! ```
+//!
+//! Lifecycle states are returned by name (`"Active"`), telemetry samples and
+//! calibration reports as dictionaries whose `data` entry holds the payload's
+//! fields. Failures raise `TimeoutError` for timeouts and `irpc.IrpcError`
+//! otherwise. The bus runs on a tokio runtime owned by the module.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{
+    PyException, PyKeyError, PyNotImplementedError, PyStopAsyncIteration, PyTimeoutError, PyValueError,
+};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use pyo3_async_runtimes::tokio::{future_into_py, get_runtime};
+use serde_json::Value;
+use tokio::sync::{broadcast, Mutex, RwLock};
+
+use crate::arm::{
+    ArmClient, CalibrationEvent, CommunicationManager, JointProxy, TelemetryFilter, TelemetrySample,
+    TelemetrySubscriber, TelemetryTopic,
+};
+use crate::bus::net::NetworkAdapter;
+use crate::protocol::{CalibrationRequest, DeviceId, LifecycleState, Payload, ProtocolError};
+
+create_exception!(irpc, IrpcError, PyException, "Error reported by the iRPC arm API");
+
+fn py_err(error: ProtocolError) -> PyErr {
+    match error {
+        ProtocolError::Timeout => PyTimeoutError::new_err(error.to_string()),
+        ProtocolError::Unsupported => PyNotImplementedError::new_err(error.to_string()),
+        _ => IrpcError::new_err(error.to_string()),
+    }
+}
+
+fn state_name(state: LifecycleState) -> String {
+    format!("{:?}", state)
+}
+
+fn parse_addr(addr: &str) -> PyResult<SocketAddr> {
+    addr.parse().map_err(|_| PyValueError::new_err(format!("invalid socket address: {}", addr)))
+}
+
+fn topic_name(topic: TelemetryTopic) -> &'static str {
+    match topic {
+        TelemetryTopic::Motion => "motion",
+        TelemetryTopic::Adaptive => "adaptive",
+        TelemetryTopic::Status => "status",
+    }
+}
+
+fn parse_topic(name: &str) -> PyResult<TelemetryTopic> {
+    match name {
+        "motion" => Ok(TelemetryTopic::Motion),
+        "adaptive" => Ok(TelemetryTopic::Adaptive),
+        "status" => Ok(TelemetryTopic::Status),
+        _ => Err(PyValueError::new_err(format!("unknown telemetry topic: {}", name))),
+    }
+}
+
+/// A JSON value as the equivalent Python object
+fn json_to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any(),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_pyobject(py)?.into_any(),
+            (_, Some(u)) => u.into_pyobject(py)?.into_any(),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_pyobject(py)?.into_any(),
+        },
+        Value::String(s) => s.into_pyobject(py)?.into_any(),
+        Value::Array(items) => {
+            let items = items.iter().map(|item| json_to_py(py, item)).collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items)?.into_any()
+        }
+        Value::Object(fields) => {
+            let dict = PyDict::new(py);
+            for (key, value) in fields {
+                dict.set_item(key, json_to_py(py, value)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+/// Fields of a payload struct (or a unit variant's empty dict) as a dict
+fn payload_data<'py>(py: Python<'py>, value: impl serde::Serialize) -> PyResult<Bound<'py, PyAny>> {
+    let value = serde_json::to_value(value).map_err(|e| IrpcError::new_err(e.to_string()))?;
+    json_to_py(py, &value)
+}
+
+fn sample_to_py(py: Python<'_>, sample: TelemetrySample) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    dict.set_item("joint_id", sample.joint_id)?;
+    dict.set_item("joint_name", sample.joint_name.as_deref())?;
+    dict.set_item("topic", topic_name(sample.topic))?;
+    dict.set_item("received_at", sample.received_at.as_secs_f64())?;
+    dict.set_item("snapshot", sample.snapshot)?;
+    match sample.message.payload {
+        Payload::TelemetryStream(stream) => dict.set_item("data", payload_data(py, stream)?)?,
+        Payload::Encoder(encoder) => dict.set_item("data", payload_data(py, encoder)?)?,
+        Payload::AdaptiveStatus(status) => dict.set_item("data", payload_data(py, status)?)?,
+        Payload::JointStatus { state, error_code } => {
+            let data = PyDict::new(py);
+            data.set_item("state", state_name(state))?;
+            data.set_item("error_code", error_code)?;
+            dict.set_item("data", data)?;
+        }
+        other => dict.set_item("data", payload_data(py, other)?)?,
+    }
+    Ok(dict.into_any().unbind())
+}
+
+fn calibration_to_py(py: Python<'_>, event: CalibrationEvent) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    dict.set_item("joint_id", event.joint_id())?;
+    match event {
+        CalibrationEvent::Progress { status, .. } => {
+            dict.set_item("kind", "progress")?;
+            dict.set_item("data", payload_data(py, status)?)?;
+        }
+        CalibrationEvent::Finished { result, .. } => {
+            dict.set_item("kind", "finished")?;
+            dict.set_item("data", payload_data(py, result)?)?;
+        }
+    }
+    Ok(dict.into_any().unbind())
+}
+
+/// Python `ArmClient`: an arm on a network bus or in simulation
+#[pyclass(name = "ArmClient", module = "irpc")]
+pub struct PyArmClient {
+    client: Arc<RwLock<ArmClient>>,
+    comm: Arc<CommunicationManager>,
+    /// Proxies share state with the client's, without taking its lock
+    joints: HashMap<DeviceId, JointProxy>,
+}
+
+impl PyArmClient {
+    fn from_comm(comm: Arc<CommunicationManager>, joints: Vec<DeviceId>) -> Self {
+        let mut client = ArmClient::with_comm_manager(comm.clone());
+        for &id in &joints {
+            client.add_joint(id);
+        }
+        let joints = joints
+            .into_iter()
+            .filter_map(|id| client.get_joint(id).map(|proxy| (id, proxy.clone())))
+            .collect();
+        Self { client: Arc::new(RwLock::new(client)), comm, joints }
+    }
+}
+
+#[pymethods]
+impl PyArmClient {
+    /// Connect to a gateway or simulator over TCP (reconnects automatically)
+    #[staticmethod]
+    fn connect_tcp(addr: &str, joints: Vec<DeviceId>) -> PyResult<Self> {
+        let addr = parse_addr(addr)?;
+        let _runtime = get_runtime().enter();
+        let adapter = Arc::new(NetworkAdapter::tcp_connect(addr));
+        Ok(Self::from_comm(CommunicationManager::with_adapter(adapter), joints))
+    }
+
+    /// Exchange datagrams on `bind` with `peer` (awaitable)
+    #[staticmethod]
+    #[pyo3(signature = (bind, joints, peer = None))]
+    fn connect_udp<'py>(py: Python<'py>, bind: &str, joints: Vec<DeviceId>, peer: Option<&str>) -> PyResult<Bound<'py, PyAny>> {
+        let bind = parse_addr(bind)?;
+        let peer = peer.map(parse_addr).transpose()?;
+        future_into_py(py, async move {
+            let adapter = NetworkAdapter::udp(bind, peer)
+                .await
+                .map_err(|e| IrpcError::new_err(e.to_string()))?;
+            Ok(Self::from_comm(CommunicationManager::with_adapter(Arc::new(adapter)), joints))
+        })
+    }
+
+    /// Simulated joints in this process, e.g. for testing planners offline
+    #[cfg(feature = "joint_api")]
+    #[staticmethod]
+    #[pyo3(signature = (joints, telemetry_period_ms = None))]
+    fn simulated(joints: Vec<DeviceId>, telemetry_period_ms: Option<u64>) -> Self {
+        use crate::bus::sim::SimBus;
+
+        let _runtime = get_runtime().enter();
+        let mut bus = SimBus::with_joints(joints.iter().copied());
+        if let Some(period) = telemetry_period_ms {
+            bus = bus.with_telemetry(Duration::from_millis(period));
+        }
+        Self::from_comm(CommunicationManager::with_adapter(Arc::new(bus)), joints)
+    }
+
+    /// Configure and activate all joints
+    fn initialize<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move { client.write().await.initialize().await.map_err(py_err) })
+    }
+
+    /// Deactivate the joints and close the bus (see `ArmOrchestrator::shutdown`)
+    fn shutdown<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move { client.write().await.shutdown().await.map_err(py_err) })
+    }
+
+    /// Emergency-stop all joints
+    fn emergency_stop<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move { client.write().await.emergency_stop().await.map_err(py_err) })
+    }
+
+    /// Cached lifecycle state of every joint, by joint ID
+    fn status<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let status = client.read().await.get_system_status().await;
+            Ok(status.into_iter().map(|(id, state)| (id, state_name(state))).collect::<HashMap<_, _>>())
+        })
+    }
+
+    /// Proxy for one joint
+    fn joint(&self, joint_id: DeviceId) -> PyResult<PyJointProxy> {
+        self.joints
+            .get(&joint_id)
+            .map(|proxy| PyJointProxy { proxy: proxy.clone() })
+            .ok_or_else(|| PyKeyError::new_err(joint_id))
+    }
+
+    /// Subscribe to telemetry; iterate the result with `async for`
+    ///
+    /// `topics` takes `"motion"`, `"adaptive"` and `"status"`; `None` means all.
+    #[pyo3(signature = (joints = None, topics = None, snapshot = true))]
+    fn subscribe_telemetry(
+        &self,
+        joints: Option<Vec<DeviceId>>,
+        topics: Option<Vec<String>>,
+        snapshot: bool,
+    ) -> PyResult<PyTelemetrySubscription> {
+        let topics = topics
+            .map(|topics| topics.iter().map(|name| parse_topic(name)).collect::<PyResult<Vec<_>>>())
+            .transpose()?;
+        let subscriber = self.comm.subscribe_telemetry(TelemetryFilter { joints, topics, snapshot });
+        Ok(PyTelemetrySubscription { subscriber: Arc::new(Mutex::new(subscriber)) })
+    }
+
+    /// Subscribe to calibration progress and results from all joints
+    fn subscribe_calibration(&self) -> PyCalibrationSubscription {
+        PyCalibrationSubscription { events: Arc::new(Mutex::new(self.comm.subscribe_calibration())) }
+    }
+}
+
+/// Python `JointProxy`: commands to one joint
+#[pyclass(name = "JointProxy", module = "irpc")]
+pub struct PyJointProxy {
+    proxy: JointProxy,
+}
+
+/// Run a unit proxy command as a Python awaitable
+macro_rules! command {
+    ($self:ident, $py:ident, |$proxy:ident| $call:expr) => {{
+        let $proxy = $self.proxy.clone();
+        future_into_py($py, async move { $call.await.map_err(py_err) })
+    }};
+}
+
+#[pymethods]
+impl PyJointProxy {
+    /// Device ID of the joint
+    #[getter]
+    fn id(&self) -> DeviceId {
+        self.proxy.id()
+    }
+
+    /// Cached lifecycle state
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let proxy = self.proxy.clone();
+        future_into_py(py, async move { Ok(state_name(proxy.get_state().await)) })
+    }
+
+    /// Ask the joint for its state; returns `(state, error_code)`
+    fn query_status<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let proxy = self.proxy.clone();
+        future_into_py(py, async move {
+            let (state, error_code) = proxy.query_status().await.map_err(py_err)?;
+            Ok((state_name(state), error_code))
+        })
+    }
+
+    fn configure<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        command!(self, py, |proxy| proxy.configure())
+    }
+
+    fn activate<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        command!(self, py, |proxy| proxy.activate())
+    }
+
+    fn deactivate<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        command!(self, py, |proxy| proxy.deactivate())
+    }
+
+    fn reset<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        command!(self, py, |proxy| proxy.reset())
+    }
+
+    fn emergency_stop<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        command!(self, py, |proxy| proxy.emergency_stop())
+    }
+
+    fn home<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        command!(self, py, |proxy| proxy.home())
+    }
+
+    /// Move to `target_angle` (degrees) at up to `velocity_limit` (deg/s)
+    fn set_target<'py>(&self, py: Python<'py>, target_angle: f32, velocity_limit: f32) -> PyResult<Bound<'py, PyAny>> {
+        command!(self, py, |proxy| proxy.set_target(target_angle, velocity_limit))
+    }
+
+    /// Start a calibration; unset arguments keep the protocol defaults
+    #[pyo3(signature = (phases = None, max_current = None, max_velocity = None, max_position_range = None, phase_timeout = None, return_home = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn start_calibration<'py>(
+        &self,
+        py: Python<'py>,
+        phases: Option<u8>,
+        max_current: Option<f32>,
+        max_velocity: Option<f32>,
+        max_position_range: Option<f32>,
+        phase_timeout: Option<f32>,
+        return_home: Option<bool>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let request = calibration_request(phases, max_current, max_velocity, max_position_range, phase_timeout, return_home);
+        command!(self, py, |proxy| proxy.start_calibration(request))
+    }
+
+    fn stop_calibration<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        command!(self, py, |proxy| proxy.stop_calibration())
+    }
+
+    /// Run a calibration and return its result as a dict
+    #[pyo3(signature = (phases = None, max_current = None, max_velocity = None, max_position_range = None, phase_timeout = None, return_home = None, timeout = 600.0))]
+    #[allow(clippy::too_many_arguments)]
+    fn calibrate<'py>(
+        &self,
+        py: Python<'py>,
+        phases: Option<u8>,
+        max_current: Option<f32>,
+        max_velocity: Option<f32>,
+        max_position_range: Option<f32>,
+        phase_timeout: Option<f32>,
+        return_home: Option<bool>,
+        timeout: f64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let request = calibration_request(phases, max_current, max_velocity, max_position_range, phase_timeout, return_home);
+        let timeout = Duration::try_from_secs_f64(timeout).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let proxy = self.proxy.clone();
+        future_into_py(py, async move {
+            let result = proxy.calibrate(request, timeout).await.map_err(py_err)?;
+            Python::with_gil(|py| payload_data(py, result).map(Bound::unbind))
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("JointProxy(0x{:04x})", self.proxy.id())
+    }
+}
+
+fn calibration_request(
+    phases: Option<u8>,
+    max_current: Option<f32>,
+    max_velocity: Option<f32>,
+    max_position_range: Option<f32>,
+    phase_timeout: Option<f32>,
+    return_home: Option<bool>,
+) -> CalibrationRequest {
+    let defaults = CalibrationRequest::default();
+    CalibrationRequest {
+        phases: phases.unwrap_or(defaults.phases),
+        max_current: max_current.unwrap_or(defaults.max_current),
+        max_velocity: max_velocity.unwrap_or(defaults.max_velocity),
+        max_position_range: max_position_range.unwrap_or(defaults.max_position_range),
+        phase_timeout: phase_timeout.unwrap_or(defaults.phase_timeout),
+        return_home: return_home.unwrap_or(defaults.return_home),
+    }
+}
+
+/// Telemetry samples as an async iterator of dicts
+#[pyclass(name = "TelemetrySubscription", module = "irpc")]
+pub struct PyTelemetrySubscription {
+    subscriber: Arc<Mutex<TelemetrySubscriber>>,
+}
+
+#[pymethods]
+impl PyTelemetrySubscription {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let subscriber = self.subscriber.clone();
+        future_into_py(py, async move {
+            let sample = subscriber.lock().await.recv().await;
+            match sample {
+                Some(sample) => Python::with_gil(|py| sample_to_py(py, sample)),
+                None => Err(PyStopAsyncIteration::new_err(())),
+            }
+        })
+    }
+
+    /// The next queued sample, or `None` without waiting
+    fn try_recv(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        self.subscriber
+            .blocking_lock()
+            .try_recv()
+            .map(|sample| sample_to_py(py, sample))
+            .transpose()
+    }
+}
+
+/// Calibration reports as an async iterator of dicts
+#[pyclass(name = "CalibrationSubscription", module = "irpc")]
+pub struct PyCalibrationSubscription {
+    events: Arc<Mutex<broadcast::Receiver<CalibrationEvent>>>,
+}
+
+#[pymethods]
+impl PyCalibrationSubscription {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let events = self.events.clone();
+        future_into_py(py, async move {
+            let mut events = events.lock().await;
+            loop {
+                match events.recv().await {
+                    Ok(event) => return Python::with_gil(|py| calibration_to_py(py, event)),
+                    // Progress reports are best effort; skip what was missed
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return Err(PyStopAsyncIteration::new_err(())),
+                }
+            }
+        })
+    }
+}
+
+/// The `irpc` Python module
+#[pymodule]
+pub fn irpc(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyArmClient>()?;
+    m.add_class::<PyJointProxy>()?;
+    m.add_class::<PyTelemetrySubscription>()?;
+    m.add_class::<PyCalibrationSubscription>()?;
+    m.add("IrpcError", m.py().get_type::<IrpcError>())?;
+    m.add("PHASE_INERTIA", CalibrationRequest::PHASE_INERTIA)?;
+    m.add("PHASE_FRICTION", CalibrationRequest::PHASE_FRICTION)?;
+    m.add("PHASE_TORQUE_CONSTANT", CalibrationRequest::PHASE_TORQUE_CONSTANT)?;
+    m.add("PHASE_DAMPING", CalibrationRequest::PHASE_DAMPING)?;
+    m.add("PHASE_VALIDATION", CalibrationRequest::PHASE_VALIDATION)?;
+    m.add("PHASE_THERMAL", CalibrationRequest::PHASE_THERMAL)?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
@@ -0,0 +1,188 @@
+//! Host-side command rate limiting
+//!
+//! A GUI slider can issue hundreds of targets a second, each one a full
+//! request/response exchange on the bus. [`RateLimits`] set on a
+//! [`CommunicationManager`](crate::CommunicationManager) pace outgoing
+//! messages by [`PayloadClass`]:
+//!
+//! - `min_interval` spaces messages of one kind to the same joint. For
+//!   [setpoints](PayloadClass::Setpoint) only the freshest waiting message is
+//!   sent; older ones fail with `ProtocolError::Superseded`. Other classes
+//!   are queued and sent one interval apart.
+//! - `max_bytes_per_sec` caps the bus bandwidth the class takes across all
+//!   joints; messages wait for their share.
+//!
+//! ```
+//! use std::time::Duration;
+//! use irpc::rate_limit::{ClassLimit, PayloadClass, RateLimits};
+//!
+//! let limits = RateLimits::new()
+//!     .class(PayloadClass::Setpoint, ClassLimit { min_interval: Duration::from_millis(20), ..Default::default() })
+//!     .class(PayloadClass::Query, ClassLimit { max_bytes_per_sec: Some(2_000), ..Default::default() });
+//! irpc::CommunicationManager::new().set_rate_limits(Some(limits));
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::protocol::{DeviceId, Payload};
+
+/// Kind of traffic a rate limit applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PayloadClass {
+    /// Motion setpoints (`SetTarget`, `SetTargetV2`, `SetVelocity`,
+    /// `SetTorque`, `SetFeedforward`), which a newer one makes obsolete
+    Setpoint,
+    /// Status, telemetry, position, input and flight recorder requests and
+    /// discovery
+    Query,
+    /// Everything else: lifecycle, configuration, calibration, I/O
+    Command,
+}
+
+impl PayloadClass {
+    pub fn of(payload: &Payload) -> Self {
+        match payload {
+            Payload::SetTarget(_)
+            | Payload::SetTargetV2(_)
+            | Payload::SetVelocity { .. }
+            | Payload::SetTorque { .. }
+            | Payload::SetFeedforward { .. } => Self::Setpoint,
+            Payload::RequestStatus
+            | Payload::RequestTelemetry
+            | Payload::RequestAdaptiveStatus
+            | Payload::RequestBusStats
+            | Payload::RequestBootInfo
+            | Payload::RequestMultiTurnPosition
+            | Payload::RequestFlightRecorder { .. }
+            | Payload::ReadDigitalInput(_)
+            | Payload::ReadAnalogInput(_)
+            | Payload::Discover => Self::Query,
+            _ => Self::Command,
+        }
+    }
+}
+
+/// Limits for one [`PayloadClass`]; the default limits nothing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ClassLimit {
+    /// Shortest spacing between messages of one kind to the same joint
+    pub min_interval: Duration,
+    /// Bandwidth the class may use across all joints, in encoded bytes
+    pub max_bytes_per_sec: Option<u32>,
+}
+
+/// Per-class rate limits; classes without an entry are not limited
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RateLimits {
+    pub classes: BTreeMap<PayloadClass, ClassLimit>,
+}
+
+impl RateLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the limit of one class
+    pub fn class(mut self, class: PayloadClass, limit: ClassLimit) -> Self {
+        self.classes.insert(class, limit);
+        self
+    }
+
+    pub fn get(&self, class: PayloadClass) -> ClassLimit {
+        self.classes.get(&class).copied().unwrap_or_default()
+    }
+
+    /// Whether setpoints are spaced, and so coalesced
+    pub fn coalesces_setpoints(&self) -> bool {
+        !self.get(PayloadClass::Setpoint).min_interval.is_zero()
+    }
+}
+
+/// Spacing of one kind of message to one joint
+#[derive(Default)]
+struct Lane {
+    /// Earliest time the next message may go
+    next_free: Duration,
+    /// Ticket of the newest waiting message
+    latest: u64,
+}
+
+/// Reservation of a message's turn, from [`RateLimiter::reserve`]
+pub(crate) struct Slot {
+    /// When the message may be sent, on the manager's clock
+    pub ready_at: Duration,
+    ticket: Option<(DeviceId, &'static str, u64)>,
+}
+
+/// Pacing state behind [`RateLimits`], kept by the communication manager
+///
+/// Times are on the manager's clock; the manager sleeps until a slot is
+/// ready and then asks whether the message should still go.
+pub(crate) struct RateLimiter {
+    limits: RateLimits,
+    lanes: Mutex<HashMap<(DeviceId, &'static str), Lane>>,
+    /// Per class, when its bandwidth is next free
+    bandwidth: Mutex<BTreeMap<PayloadClass, Duration>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self { limits, lanes: Mutex::new(HashMap::new()), bandwidth: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn limits(&self) -> &RateLimits {
+        &self.limits
+    }
+
+    /// Take a turn for a message to `target`
+    ///
+    /// Setpoints wait for the lane to free up and then compete on their
+    /// ticket; other messages book the lane for their interval.
+    pub fn reserve(&self, target: DeviceId, payload: &Payload, now: Duration) -> Slot {
+        let class = PayloadClass::of(payload);
+        let interval = self.limits.get(class).min_interval;
+        if interval.is_zero() {
+            return Slot { ready_at: now, ticket: None };
+        }
+        let mut lanes = self.lanes.lock().unwrap();
+        let lane = lanes.entry((target, payload.name())).or_default();
+        let ready_at = lane.next_free.max(now);
+        if class == PayloadClass::Setpoint {
+            lane.latest += 1;
+            Slot { ready_at, ticket: Some((target, payload.name(), lane.latest)) }
+        } else {
+            lane.next_free = ready_at + interval;
+            Slot { ready_at, ticket: None }
+        }
+    }
+
+    /// Claim a ready slot: `false` if a newer setpoint of the same kind has
+    /// taken it over
+    pub fn claim(&self, slot: &Slot, now: Duration) -> bool {
+        let Some((target, kind, ticket)) = slot.ticket else {
+            return true;
+        };
+        let mut lanes = self.lanes.lock().unwrap();
+        let lane = lanes.entry((target, kind)).or_default();
+        if lane.latest != ticket {
+            return false;
+        }
+        lane.next_free = now + self.limits.get(PayloadClass::Setpoint).min_interval;
+        true
+    }
+
+    /// Book bandwidth for `size` encoded bytes; returns when they may go
+    pub fn reserve_bandwidth(&self, payload: &Payload, size: usize, now: Duration) -> Duration {
+        let class = PayloadClass::of(payload);
+        let Some(rate) = self.limits.get(class).max_bytes_per_sec.filter(|rate| *rate > 0) else {
+            return now;
+        };
+        let mut bandwidth = self.bandwidth.lock().unwrap();
+        let next_free = bandwidth.entry(class).or_default();
+        let start = (*next_free).max(now);
+        *next_free = start + Duration::from_secs_f64(size as f64 / rate as f64);
+        start
+    }
+}
@@ -0,0 +1,307 @@
+//! Host-side safety limits
+//!
+//! Joints enforce the limits they were sent with `SetLimits`, but only once a
+//! target reaches them, and only for themselves. [`SafetyLimits`] set on a
+//! [`CommunicationManager`](crate::CommunicationManager) (or through
+//! [`ArmOrchestrator::set_safety_limits`](crate::ArmOrchestrator::set_safety_limits))
+//! check every outgoing `SetTarget` and `SetTargetV2`, and each target of a
+//! `SetTargetBatch`, before it is queued:
+//!
+//! - against per-joint [`JointLimits`] and an arm-wide maximum speed
+//! - with an arm model, against a box the flange must stay in; the other
+//!   joints are taken at their last target let through, or else at their
+//!   latest reported position, and the check is skipped while any of them
+//!   is unknown
+//!
+//! A violating target is refused with `ProtocolError::SafetyViolation`, or
+//! with [`SafetyAction::Clamp`] brought back within the limits and sent.
+//! Either way the intervention is logged and kept in an audit log of the
+//! last `SAFETY_AUDIT_LEN`, read with
+//! [`CommunicationManager::safety_interventions`](crate::CommunicationManager::safety_interventions).
+//!
+//! ```
+//! use irpc::safety::{SafetyAction, SafetyLimits};
+//! use irpc::JointLimits;
+//!
+//! let limits = SafetyLimits::new()
+//!     .joint(0x0010, JointLimits { min_position: -90.0, max_position: 90.0, max_velocity: 60.0 })
+//!     .max_velocity(45.0)
+//!     .action(SafetyAction::Clamp);
+//! irpc::CommunicationManager::new().set_safety_limits(Some(limits));
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use tracing::{debug, warn};
+
+use crate::config::SAFETY_AUDIT_LEN;
+use crate::kinematics::ArmModel;
+use crate::protocol::{BatchTarget, DeviceId, JointLimits, Payload, ProtocolError, TargetBatch};
+
+/// Bisection steps when clamping a target to the workspace
+const WORKSPACE_CLAMP_STEPS: usize = 24;
+
+/// What happens to a target that violates the limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SafetyAction {
+    /// Refuse it with `ProtocolError::SafetyViolation`
+    #[default]
+    Reject,
+    /// Send the closest target within the limits instead
+    ///
+    /// Targets that cannot be clamped (not a number, or a workspace
+    /// violation from an unknown or already violating position) are still
+    /// refused.
+    Clamp,
+}
+
+/// Box the flange must stay in, in the arm model's base frame
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub model: ArmModel,
+    /// Lower corner, in metres
+    pub min: [f64; 3],
+    /// Upper corner, in metres
+    pub max: [f64; 3],
+}
+
+impl Workspace {
+    pub fn contains(&self, point: [f64; 3]) -> bool {
+        (0..3).all(|axis| point[axis] >= self.min[axis] && point[axis] <= self.max[axis])
+    }
+}
+
+/// Limits outgoing targets are checked against; the default checks nothing
+#[derive(Debug, Clone, Default)]
+pub struct SafetyLimits {
+    pub joints: HashMap<DeviceId, JointLimits>,
+    pub workspace: Option<Workspace>,
+    /// Highest velocity limit any target may carry, in degrees/second
+    pub max_velocity: Option<f32>,
+    pub action: SafetyAction,
+}
+
+impl SafetyLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the limits of one joint
+    pub fn joint(mut self, joint_id: DeviceId, limits: JointLimits) -> Self {
+        self.joints.insert(joint_id, limits);
+        self
+    }
+
+    /// Keep the flange of `model` between `min` and `max` (metres)
+    pub fn workspace(mut self, model: ArmModel, min: [f64; 3], max: [f64; 3]) -> Self {
+        self.workspace = Some(Workspace { model, min, max });
+        self
+    }
+
+    /// Cap the velocity limit of every target, e.g. for a reduced speed mode
+    pub fn max_velocity(mut self, max_velocity: f32) -> Self {
+        self.max_velocity = Some(max_velocity);
+        self
+    }
+
+    pub fn action(mut self, action: SafetyAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Velocity limit that applies to `joint_id`, if any
+    fn velocity_limit(&self, joint_id: DeviceId) -> Option<f32> {
+        let joint = self.joints.get(&joint_id).map(|limits| limits.max_velocity);
+        match (joint, self.max_velocity) {
+            (Some(joint), Some(arm)) => Some(joint.min(arm)),
+            (joint, arm) => joint.or(arm),
+        }
+    }
+}
+
+/// A way a target broke the limits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Violation {
+    /// The target angle or velocity is not a finite number
+    NotFinite,
+    /// The angle is outside the joint's position limits (degrees)
+    Position { min: f32, max: f32 },
+    /// The velocity limit is above the joint's or the arm's maximum
+    /// (degrees/second)
+    Velocity { max: f32 },
+    /// The flange would end up at `flange` (metres), outside the workspace
+    Workspace { flange: [f64; 3] },
+}
+
+/// A target the supervisor refused or changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Intervention {
+    /// When, on the communication manager's clock
+    pub at: Duration,
+    pub joint_id: DeviceId,
+    /// Kind of payload (see `Payload::name`)
+    pub payload: &'static str,
+    pub violations: Vec<Violation>,
+    /// Target angle and velocity limit as requested, in degrees and
+    /// degrees/second
+    pub requested: (f32, f32),
+    /// What was sent instead; `None` if the target was refused
+    pub sent: Option<(f32, f32)>,
+}
+
+/// State behind [`SafetyLimits`], kept by the communication manager
+#[derive(Default)]
+pub(crate) struct SafetySupervisor {
+    limits: Option<SafetyLimits>,
+    /// Last target let through to each joint
+    accepted: HashMap<DeviceId, f32>,
+    interventions: VecDeque<Intervention>,
+}
+
+impl SafetySupervisor {
+    pub fn limits(&self) -> Option<&SafetyLimits> {
+        self.limits.as_ref()
+    }
+
+    /// Replace the limits; the audit log is kept
+    pub fn set_limits(&mut self, limits: Option<SafetyLimits>) {
+        self.limits = limits;
+        self.accepted.clear();
+    }
+
+    pub fn interventions(&self) -> impl Iterator<Item = &Intervention> {
+        self.interventions.iter()
+    }
+
+    /// Check a payload about to go to `joint_id`, clamping it in place if
+    /// the limits say so
+    ///
+    /// `position` gives a joint's latest known position in degrees, for
+    /// joints no target went to yet.
+    pub fn check(
+        &mut self,
+        joint_id: DeviceId,
+        payload: &mut Payload,
+        now: Duration,
+        position: impl Fn(DeviceId) -> Option<f32>,
+    ) -> Result<(), ProtocolError> {
+        if self.limits.is_none() {
+            return Ok(());
+        }
+        let kind = payload.name();
+        match payload {
+            Payload::SetTarget(target) => {
+                self.check_target(joint_id, kind, &mut target.target_angle, &mut target.velocity_limit, now, &position)
+            }
+            Payload::SetTargetV2(target) => {
+                self.check_target(joint_id, kind, &mut target.target_angle, &mut target.max_velocity, now, &position)
+            }
+            // Every joint's target on its own; the batch goes only if all do
+            Payload::SetTargetBatch(batch) => {
+                let accepted = self.accepted.clone();
+                let mut targets = batch.targets().to_vec();
+                for BatchTarget { joint_id, target } in &mut targets {
+                    let (angle, velocity) = (&mut target.target_angle, &mut target.velocity_limit);
+                    if let Err(error) = self.check_target(*joint_id, kind, angle, velocity, now, &position) {
+                        self.accepted = accepted;
+                        return Err(error);
+                    }
+                }
+                *batch = TargetBatch::new(&targets).unwrap_or(*batch);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check one target angle and velocity limit, clamping them in place
+    fn check_target(
+        &mut self,
+        joint_id: DeviceId,
+        kind: &'static str,
+        angle: &mut f32,
+        velocity: &mut f32,
+        now: Duration,
+        position: &impl Fn(DeviceId) -> Option<f32>,
+    ) -> Result<(), ProtocolError> {
+        let Some(limits) = &self.limits else {
+            return Ok(());
+        };
+        let requested = (*angle, *velocity);
+        let mut violations = Vec::new();
+        // Whether the target can still be sent after clamping
+        let mut clamped = limits.action == SafetyAction::Clamp;
+
+        if !angle.is_finite() || !velocity.is_finite() {
+            violations.push(Violation::NotFinite);
+            clamped = false;
+        } else {
+            if let Some(max) = limits.velocity_limit(joint_id).filter(|max| *velocity > *max) {
+                violations.push(Violation::Velocity { max });
+                *velocity = max;
+            }
+            if let Some(joint) = limits.joints.get(&joint_id) {
+                if *angle < joint.min_position || *angle > joint.max_position {
+                    violations.push(Violation::Position { min: joint.min_position, max: joint.max_position });
+                    *angle = angle.clamp(joint.min_position, joint.max_position);
+                }
+            }
+            if let Some(workspace) = &limits.workspace {
+                let pose = |angle: f32| -> Option<[f64; 3]> {
+                    let angles = workspace.model.links().iter().map(|link| match link.joint_id == joint_id {
+                        true => Some(angle),
+                        false => self.accepted.get(&link.joint_id).copied().or_else(|| position(link.joint_id)),
+                    });
+                    workspace.model.flange_position(&angles.collect::<Option<Vec<f32>>>()?).ok()
+                };
+                let moves_flange = workspace.model.joint_ids().contains(&joint_id);
+                match pose(*angle) {
+                    Some(flange) if !workspace.contains(flange) => {
+                        violations.push(Violation::Workspace { flange });
+                        let from = self.accepted.get(&joint_id).copied().or_else(|| position(joint_id));
+                        match from.filter(|from| pose(*from).is_some_and(|flange| workspace.contains(flange))) {
+                            // Move only as far towards the target as stays inside
+                            Some(mut inside) => {
+                                let mut outside = *angle;
+                                for _ in 0..WORKSPACE_CLAMP_STEPS {
+                                    let middle = (inside + outside) / 2.0;
+                                    match pose(middle).is_some_and(|flange| workspace.contains(flange)) {
+                                        true => inside = middle,
+                                        false => outside = middle,
+                                    }
+                                }
+                                *angle = inside;
+                            }
+                            None => clamped = false,
+                        }
+                    }
+                    None if moves_flange => debug!("Pose unknown, workspace not checked for joint {}", joint_id),
+                    _ => {}
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            self.accepted.insert(joint_id, *angle);
+            return Ok(());
+        }
+        let sent = clamped.then_some((*angle, *velocity));
+        match sent {
+            Some((angle, velocity)) => {
+                warn!("{} to joint {} clamped to {} deg at {} deg/s: {:?}",
+                      kind, joint_id, angle, velocity, violations);
+                self.accepted.insert(joint_id, angle);
+            }
+            None => warn!("{} to joint {} refused: {:?}", kind, joint_id, violations),
+        }
+        if self.interventions.len() >= SAFETY_AUDIT_LEN {
+            self.interventions.pop_front();
+        }
+        self.interventions.push_back(Intervention { at: now, joint_id, payload: kind, violations, requested, sent });
+        match sent {
+            Some(_) => Ok(()),
+            None => Err(ProtocolError::SafetyViolation),
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! Bus time-division scheduling
+//!
+//! Plans when each joint puts its periodic traffic on the bus. Given the bus
+//! bitrates, a base cycle and per-joint telemetry rates, frame sizes and
+//! cyclic slot lengths, [`plan`] assigns every stream a phase offset so that
+//! no cycle carries more than its share of frames, or reports which cycle is
+//! over-subscribed. The result is pushed to the joints with
+//! [`ArmOrchestrator::apply_schedule`](crate::ArmOrchestrator::apply_schedule)
+//! as `ConfigureTelemetry` messages.
+//!
+//! ```
+//! use irpc::schedule::{plan, BusTiming, JointTraffic, Topology};
+//! use std::time::Duration;
+//!
+//! let mut topology = Topology::new(BusTiming::default(), Duration::from_millis(1));
+//! for joint_id in 0x0010..0x0016 {
+//!     topology = topology.with_joint(JointTraffic::telemetry(joint_id, 500, 64));
+//! }
+//! let schedule = plan(&topology)?;
+//! assert!(schedule.peak_load < 0.7);
+//! # Ok::<(), irpc::schedule::ScheduleError>(())
+//! ```
+//!
+//! Frame times are worst-case estimates for CAN-FD frames with extended
+//! IDs and bit rate switching; messages longer than one frame count as
+//! that many full frames.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::config::{CANFD_MAX_DATA_LEN, SCHEDULE_MAX_UTILIZATION};
+use crate::protocol::{ConfigureTelemetryPayload, DeviceId, TelemetryMode};
+
+/// CAN-FD data lengths a frame can carry
+const CANFD_DATA_LENGTHS: [usize; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64];
+
+/// Scheduling failures
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ScheduleError {
+    #[error("Cycle and bitrates must be non-zero")]
+    InvalidTiming,
+    #[error("Joint {0} appears more than once")]
+    DuplicateJoint(DeviceId),
+    #[error("Joint {joint}: {rate_hz} Hz is not a whole number of cycles")]
+    RateNotAligned { joint: DeviceId, rate_hz: u32 },
+    #[error("Bus over-subscribed: cycle {cycle} is {load:.2} busy (limit {limit:.2})")]
+    OverSubscribed { cycle: usize, load: f32, limit: f32 },
+}
+
+/// Bitrates of a CAN-FD bus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusTiming {
+    /// Arbitration phase bitrate (bit/s)
+    pub nominal_bitrate: u32,
+    /// Data phase bitrate (bit/s); equal to `nominal_bitrate` without bit rate switching
+    pub data_bitrate: u32,
+}
+
+impl BusTiming {
+    /// Worst-case time one frame carrying `data_len` bytes occupies the bus
+    ///
+    /// Counts dynamic stuff bits at one per four bits, plus the fixed stuff
+    /// bits of the CRC field and the interframe space.
+    pub fn frame_time(&self, data_len: usize) -> Duration {
+        let data_len = CANFD_DATA_LENGTHS
+            .into_iter()
+            .find(|&len| len >= data_len)
+            .unwrap_or(CANFD_MAX_DATA_LEN);
+        // SOF, 29-bit ID, SRR, IDE, RRS, FDF, res, BRS
+        let arbitration = 36u64;
+        // CRC delimiter, ACK slot and delimiter, EOF, interframe space
+        let trailer = 13u64;
+        // ESI, DLC, data
+        let data = 5 + 8 * data_len as u64;
+        let crc: u64 = if data_len <= 16 { 17 } else { 21 };
+        // Stuff count, CRC and their fixed stuff bits
+        let checked = 4 + crc + (4 + crc).div_ceil(4);
+
+        let nominal_bits = arbitration + arbitration.div_ceil(4) + trailer;
+        let data_bits = data + data.div_ceil(4) + checked;
+        Duration::from_nanos(
+            nominal_bits * 1_000_000_000 / self.nominal_bitrate as u64
+                + data_bits * 1_000_000_000 / self.data_bitrate as u64,
+        )
+    }
+
+    /// Bus time of a message of `len` bytes, split into full frames if needed
+    pub fn message_time(&self, len: usize) -> Duration {
+        let frames = len.div_ceil(CANFD_MAX_DATA_LEN).max(1);
+        let last = len - (frames - 1) * CANFD_MAX_DATA_LEN;
+        self.frame_time(CANFD_MAX_DATA_LEN) * (frames - 1) as u32 + self.frame_time(last)
+    }
+}
+
+impl Default for BusTiming {
+    /// 1 Mbit/s arbitration, 5 Mbit/s data
+    fn default() -> Self {
+        Self {
+            nominal_bitrate: 1_000_000,
+            data_bitrate: 5_000_000,
+        }
+    }
+}
+
+/// Periodic traffic of one joint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JointTraffic {
+    pub joint_id: DeviceId,
+    /// Telemetry rate in Hz (0 = no telemetry)
+    pub telemetry_rate_hz: u32,
+    /// Encoded size of one telemetry message in bytes (see `Message::encoded_size()`)
+    pub telemetry_len: usize,
+    /// Bus time reserved for the joint in every cycle, for cyclic exchange
+    pub cyclic_slot: Option<Duration>,
+}
+
+impl JointTraffic {
+    /// A joint that only streams telemetry
+    pub fn telemetry(joint_id: DeviceId, rate_hz: u32, len: usize) -> Self {
+        Self {
+            joint_id,
+            telemetry_rate_hz: rate_hz,
+            telemetry_len: len,
+            cyclic_slot: None,
+        }
+    }
+
+    /// Also reserve `slot` in every cycle
+    pub fn with_cyclic_slot(mut self, slot: Duration) -> Self {
+        self.cyclic_slot = Some(slot);
+        self
+    }
+}
+
+/// Bus and joints to schedule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topology {
+    pub timing: BusTiming,
+    /// Base cycle; every telemetry period must be a whole number of cycles
+    pub cycle: Duration,
+    /// Largest fraction of any cycle the schedule may fill
+    pub max_utilization: f32,
+    pub joints: Vec<JointTraffic>,
+}
+
+impl Topology {
+    /// An empty topology, allowed to fill `SCHEDULE_MAX_UTILIZATION` of each cycle
+    pub fn new(timing: BusTiming, cycle: Duration) -> Self {
+        Self {
+            timing,
+            cycle,
+            max_utilization: SCHEDULE_MAX_UTILIZATION,
+            joints: Vec::new(),
+        }
+    }
+
+    /// Add a joint
+    pub fn with_joint(mut self, traffic: JointTraffic) -> Self {
+        self.joints.push(traffic);
+        self
+    }
+}
+
+/// When one joint transmits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JointSchedule {
+    pub joint_id: DeviceId,
+    pub telemetry_rate_hz: u32,
+    /// Start of the first telemetry message within its period
+    pub telemetry_offset: Duration,
+    /// Bus time of one telemetry message
+    pub telemetry_time: Duration,
+    /// Start of the joint's cyclic slot within every cycle
+    pub slot_offset: Option<Duration>,
+}
+
+impl JointSchedule {
+    /// Telemetry configuration to push to the joint
+    pub fn telemetry_config(&self) -> ConfigureTelemetryPayload {
+        ConfigureTelemetryPayload {
+            mode: if self.telemetry_rate_hz == 0 {
+                TelemetryMode::OnDemand
+            } else {
+                TelemetryMode::Periodic
+            },
+            rate_hz: self.telemetry_rate_hz.try_into().unwrap_or(u16::MAX),
+            change_threshold: 0.0,
+            phase_offset_us: self.telemetry_offset.as_micros() as u32,
+        }
+    }
+}
+
+/// A feasible schedule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    pub cycle: Duration,
+    /// Length after which the pattern repeats
+    pub hyperperiod: Duration,
+    /// Average fraction of bus time in use
+    pub utilization: f32,
+    /// Fraction of the busiest cycle in use
+    pub peak_load: f32,
+    /// One entry per joint, in topology order
+    pub joints: Vec<JointSchedule>,
+}
+
+impl Schedule {
+    /// Schedule of one joint
+    pub fn joint(&self, joint_id: DeviceId) -> Option<&JointSchedule> {
+        self.joints.iter().find(|joint| joint.joint_id == joint_id)
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Compute a schedule for `topology`
+///
+/// Cyclic slots come first in every cycle, in topology order. Telemetry
+/// streams are then placed fastest first: each takes the phase (cycle within
+/// its period) whose cycles are least loaded and starts after everything
+/// already placed there, so frames never overlap and load spreads across
+/// cycles.
+pub fn plan(topology: &Topology) -> Result<Schedule, ScheduleError> {
+    let timing = &topology.timing;
+    let cycle_ns = topology.cycle.as_nanos() as u64;
+    if cycle_ns == 0 || timing.nominal_bitrate == 0 || timing.data_bitrate == 0 {
+        return Err(ScheduleError::InvalidTiming);
+    }
+    let mut seen = HashSet::new();
+    if let Some(joint) = topology.joints.iter().find(|joint| !seen.insert(joint.joint_id)) {
+        return Err(ScheduleError::DuplicateJoint(joint.joint_id));
+    }
+
+    // Period of each stream in cycles
+    let mut periods = Vec::with_capacity(topology.joints.len());
+    for joint in &topology.joints {
+        let rate = joint.telemetry_rate_hz as u64;
+        let period = match rate {
+            0 => None,
+            _ if !1_000_000_000u64.is_multiple_of(rate) || !(1_000_000_000 / rate).is_multiple_of(cycle_ns) => {
+                return Err(ScheduleError::RateNotAligned { joint: joint.joint_id, rate_hz: joint.telemetry_rate_hz });
+            }
+            _ => Some(1_000_000_000 / rate / cycle_ns),
+        };
+        periods.push(period);
+    }
+    let cycles = periods.iter().flatten().fold(1, |hyper, &period| hyper / gcd(hyper, period) * period);
+
+    // Busy time of each cycle so far, in nanoseconds from its start
+    let mut load = vec![0u64; cycles as usize];
+    let mut schedules: Vec<JointSchedule> = topology
+        .joints
+        .iter()
+        .map(|joint| JointSchedule {
+            joint_id: joint.joint_id,
+            telemetry_rate_hz: joint.telemetry_rate_hz,
+            telemetry_offset: Duration::ZERO,
+            telemetry_time: timing.message_time(joint.telemetry_len),
+            slot_offset: None,
+        })
+        .collect();
+
+    for (joint, schedule) in topology.joints.iter().zip(&mut schedules) {
+        if let Some(slot) = joint.cyclic_slot {
+            schedule.slot_offset = Some(Duration::from_nanos(load[0]));
+            load.iter_mut().for_each(|busy| *busy += slot.as_nanos() as u64);
+        }
+    }
+
+    let mut order: Vec<usize> = (0..schedules.len()).filter(|&i| periods[i].is_some()).collect();
+    order.sort_by_key(|&i| (periods[i], schedules[i].joint_id));
+    for i in order {
+        let period = periods[i].unwrap_or(1) as usize;
+        let busiest = |phase: usize| load.iter().skip(phase).step_by(period).copied().max().unwrap_or(0);
+        let phase = (0..period).min_by_key(|&phase| busiest(phase)).unwrap_or(0);
+        let start = busiest(phase);
+        let end = start + schedules[i].telemetry_time.as_nanos() as u64;
+        load.iter_mut().skip(phase).step_by(period).for_each(|busy| *busy = end);
+        schedules[i].telemetry_offset = Duration::from_nanos(phase as u64 * cycle_ns + start);
+    }
+
+    let (peak_cycle, peak) = load.iter().enumerate().max_by_key(|(_, busy)| **busy).map_or((0, 0), |(c, b)| (c, *b));
+    let peak_load = peak as f32 / cycle_ns as f32;
+    if peak_load > topology.max_utilization {
+        return Err(ScheduleError::OverSubscribed {
+            cycle: peak_cycle,
+            load: peak_load,
+            limit: topology.max_utilization,
+        });
+    }
+
+    // Average over the hyperperiod, gaps between frames excluded
+    let mut busy_ns: u64 = topology.joints.iter().filter_map(|joint| joint.cyclic_slot).map(|slot| slot.as_nanos() as u64 * cycles).sum();
+    for (schedule, period) in schedules.iter().zip(&periods) {
+        if let Some(period) = period {
+            busy_ns += schedule.telemetry_time.as_nanos() as u64 * (cycles / period);
+        }
+    }
+
+    Ok(Schedule {
+        cycle: topology.cycle,
+        hyperperiod: topology.cycle * cycles as u32,
+        utilization: busy_ns as f32 / (cycles * cycle_ns) as f32,
+        peak_load,
+        joints: schedules,
+    })
+}
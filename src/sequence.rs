@@ -0,0 +1,352 @@
+//! Scripted motion sequences
+//!
+//! A [`MotionSequence`] describes a job (moves, pauses, gripper actions,
+//! branches running side by side, repetitions) as data, built step by step:
+//!
+//! ```no_run
+//! use irpc::sequence::MotionSequence;
+//! use std::time::Duration;
+//!
+//! # async fn example(arm: irpc::ArmOrchestrator) -> Result<(), irpc::sequence::SequenceError> {
+//! let pick = MotionSequence::new()
+//!     .move_to([(0x0010, 30.0), (0x0011, -45.0)], 60.0)
+//!     .set_gripper(0x0011, 0, true)
+//!     .wait(Duration::from_millis(300))
+//!     .parallel([
+//!         MotionSequence::new().move_to([(0x0010, 0.0)], 60.0),
+//!         MotionSequence::new().move_to([(0x0011, 0.0)], 30.0),
+//!     ]);
+//! let job = MotionSequence::new().repeat(3, pick);
+//!
+//! let mut run = arm.start_sequence(job)?;
+//! while let Some(event) = run.next_event().await {
+//!     println!("{:?}", event);
+//! }
+//! run.wait().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ArmOrchestrator::start_sequence`](crate::ArmOrchestrator::start_sequence)
+//! runs it in a background task and returns a [`SequenceHandle`] for its
+//! progress events and cancellation. A move is done once every joint reads
+//! within `SEQUENCE_POSITION_TOLERANCE_DEG` of its target; the first step
+//! that fails ends the sequence, parallel branches included.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::sync::{mpsc, watch};
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::{info, warn};
+
+use crate::arm::JointProxy;
+use crate::clock::Clock;
+use crate::config::{SEQUENCE_MOVE_TIMEOUT_MS, SEQUENCE_POLL_INTERVAL_MS, SEQUENCE_POSITION_TOLERANCE_DEG};
+use crate::protocol::{DeviceId, ProtocolError};
+
+/// Motion sequence errors
+#[derive(Error, Debug)]
+pub enum SequenceError {
+    #[error("Joint {0} is not part of the arm")]
+    UnknownJoint(DeviceId),
+    #[error("Joint {joint} did not reach {target} within the move timeout")]
+    NotReached { joint: DeviceId, target: f32 },
+    #[error("Sequence cancelled")]
+    Cancelled,
+    #[error("Protocol error: {0}")]
+    Protocol(#[from] ProtocolError),
+}
+
+/// One step of a [`MotionSequence`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MotionStep {
+    /// Send joints to angles in degrees, at most `velocity` degrees/second,
+    /// and wait until they get there
+    MoveTo { targets: Vec<(DeviceId, f32)>, velocity: f32 },
+    /// Do nothing for a while
+    Wait(Duration),
+    /// Close (`true`) or open a gripper wired to a joint's digital output
+    SetGripper { joint_id: DeviceId, channel: u8, closed: bool },
+    /// Run the sequences side by side; done when all of them are
+    Parallel(Vec<MotionSequence>),
+    /// Run a sequence `count` times
+    Repeat { count: u32, body: MotionSequence },
+}
+
+/// Steps run one after the other
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MotionSequence {
+    steps: Vec<MotionStep>,
+}
+
+impl MotionSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move joints to angles in degrees at most `velocity` degrees/second
+    pub fn move_to(mut self, targets: impl IntoIterator<Item = (DeviceId, f32)>, velocity: f32) -> Self {
+        let targets = targets.into_iter().collect();
+        self.steps.push(MotionStep::MoveTo { targets, velocity });
+        self
+    }
+
+    pub fn wait(mut self, duration: Duration) -> Self {
+        self.steps.push(MotionStep::Wait(duration));
+        self
+    }
+
+    /// Close (`true`) or open the gripper on `channel` of a joint's digital
+    /// outputs
+    pub fn set_gripper(mut self, joint_id: DeviceId, channel: u8, closed: bool) -> Self {
+        self.steps.push(MotionStep::SetGripper { joint_id, channel, closed });
+        self
+    }
+
+    /// Run `branches` side by side
+    pub fn parallel(mut self, branches: impl IntoIterator<Item = MotionSequence>) -> Self {
+        self.steps.push(MotionStep::Parallel(branches.into_iter().collect()));
+        self
+    }
+
+    /// Run `body` `count` times
+    pub fn repeat(mut self, count: u32, body: MotionSequence) -> Self {
+        self.steps.push(MotionStep::Repeat { count, body });
+        self
+    }
+
+    pub fn steps(&self) -> &[MotionStep] {
+        &self.steps
+    }
+
+    /// Moves, waits and gripper actions a run goes through, repetitions
+    /// unrolled and every parallel branch counted
+    pub fn step_count(&self) -> usize {
+        self.steps
+            .iter()
+            .map(|step| match step {
+                MotionStep::Parallel(branches) => branches.iter().map(Self::step_count).sum(),
+                MotionStep::Repeat { count, body } => *count as usize * body.step_count(),
+                _ => 1,
+            })
+            .sum()
+    }
+
+    /// Every joint the sequence commands
+    pub fn joints(&self) -> Vec<DeviceId> {
+        let mut joints = Vec::new();
+        for step in &self.steps {
+            match step {
+                MotionStep::MoveTo { targets, .. } => joints.extend(targets.iter().map(|(id, _)| *id)),
+                MotionStep::SetGripper { joint_id, .. } => joints.push(*joint_id),
+                MotionStep::Parallel(branches) => joints.extend(branches.iter().flat_map(Self::joints)),
+                MotionStep::Repeat { body, .. } => joints.extend(body.joints()),
+                MotionStep::Wait(_) => {}
+            }
+        }
+        joints.sort_unstable();
+        joints.dedup();
+        joints
+    }
+}
+
+/// Progress of a running sequence
+///
+/// Only moves, waits and gripper actions are reported; `completed` counts
+/// those finished so far out of the run's [`MotionSequence::step_count`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SequenceEvent {
+    StepStarted { step: MotionStep, completed: usize, total: usize },
+    StepFinished { step: MotionStep, completed: usize, total: usize },
+}
+
+/// A sequence running in the background
+pub struct SequenceHandle {
+    events: mpsc::UnboundedReceiver<SequenceEvent>,
+    cancel: watch::Sender<bool>,
+    task: JoinHandle<Result<(), SequenceError>>,
+}
+
+impl SequenceHandle {
+    /// Start `sequence` on `joints`, which must include every joint it
+    /// commands
+    pub(crate) fn start(
+        sequence: MotionSequence,
+        joints: HashMap<DeviceId, JointProxy>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let (cancel, cancel_rx) = watch::channel(false);
+        let runner = Arc::new(Runner {
+            joints,
+            clock,
+            events: events_tx,
+            cancel: cancel_rx,
+            completed: AtomicUsize::new(0),
+            total: sequence.step_count(),
+        });
+        info!("Starting motion sequence of {} steps", runner.total);
+        let task = tokio::spawn(async move {
+            let result = runner.run(sequence).await;
+            match &result {
+                Ok(()) => info!("Motion sequence finished"),
+                Err(e) => warn!("Motion sequence ended: {}", e),
+            }
+            result
+        });
+        Self { events, cancel, task }
+    }
+
+    /// The next progress event; `None` once the sequence has ended and every
+    /// event was received
+    pub async fn next_event(&mut self) -> Option<SequenceEvent> {
+        self.events.recv().await
+    }
+
+    /// Stop the sequence; joints in the middle of a move hold where they are
+    pub fn cancel(&self) {
+        let _ = self.cancel.send(true);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Wait for the sequence to end
+    pub async fn wait(self) -> Result<(), SequenceError> {
+        self.task.await.unwrap_or(Err(SequenceError::Cancelled))
+    }
+}
+
+type StepFuture = Pin<Box<dyn Future<Output = Result<(), SequenceError>> + Send>>;
+
+/// What a running sequence shares between its branches
+struct Runner {
+    joints: HashMap<DeviceId, JointProxy>,
+    clock: Arc<dyn Clock>,
+    events: mpsc::UnboundedSender<SequenceEvent>,
+    cancel: watch::Receiver<bool>,
+    completed: AtomicUsize,
+    total: usize,
+}
+
+impl Runner {
+    fn run(self: &Arc<Self>, sequence: MotionSequence) -> StepFuture {
+        let runner = Arc::clone(self);
+        Box::pin(async move {
+            for step in sequence.steps {
+                match step {
+                    MotionStep::Parallel(branches) => {
+                        let mut set = JoinSet::new();
+                        for branch in branches {
+                            set.spawn(runner.run(branch));
+                        }
+                        // Dropping the set on the first error stops the others
+                        while let Some(result) = set.join_next().await {
+                            result.unwrap_or(Err(SequenceError::Cancelled))?;
+                        }
+                    }
+                    MotionStep::Repeat { count, body } => {
+                        for _ in 0..count {
+                            runner.run(body.clone()).await?;
+                        }
+                    }
+                    step => runner.run_step(step).await?,
+                }
+            }
+            Ok(())
+        })
+    }
+
+    async fn run_step(&self, step: MotionStep) -> Result<(), SequenceError> {
+        if *self.cancel.borrow() {
+            return Err(SequenceError::Cancelled);
+        }
+        let completed = self.completed.load(Ordering::Relaxed);
+        let _ = self.events.send(SequenceEvent::StepStarted { step: step.clone(), completed, total: self.total });
+
+        match &step {
+            MotionStep::MoveTo { targets, velocity } => self.move_to(targets, *velocity).await?,
+            MotionStep::Wait(duration) => {
+                tokio::select! {
+                    _ = self.clock.sleep(*duration) => {}
+                    _ = self.cancelled() => return Err(SequenceError::Cancelled),
+                }
+            }
+            MotionStep::SetGripper { joint_id, channel, closed } => {
+                self.joint(*joint_id)?.set_digital_output(*channel, *closed).await?;
+            }
+            MotionStep::Parallel(_) | MotionStep::Repeat { .. } => unreachable!("run() expands these"),
+        }
+
+        let completed = self.completed.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = self.events.send(SequenceEvent::StepFinished { step, completed, total: self.total });
+        Ok(())
+    }
+
+    /// Send the targets, then poll positions until every joint is there
+    async fn move_to(&self, targets: &[(DeviceId, f32)], velocity: f32) -> Result<(), SequenceError> {
+        for (id, angle) in targets {
+            self.joint(*id)?.set_target(*angle, velocity).await?;
+        }
+
+        let deadline = self.clock.now() + Duration::from_millis(SEQUENCE_MOVE_TIMEOUT_MS);
+        let mut moving: Vec<(DeviceId, f32)> = targets.to_vec();
+        loop {
+            let mut still_moving = Vec::with_capacity(moving.len());
+            for (id, angle) in moving {
+                let position = self.joint(id)?.read_multi_turn_position().await?.degrees();
+                if (position - angle).abs() > SEQUENCE_POSITION_TOLERANCE_DEG {
+                    still_moving.push((id, angle));
+                }
+            }
+            moving = still_moving;
+            let Some(&(joint, target)) = moving.first() else {
+                return Ok(());
+            };
+            if self.clock.now() >= deadline {
+                return Err(SequenceError::NotReached { joint, target });
+            }
+            tokio::select! {
+                _ = self.clock.sleep(Duration::from_millis(SEQUENCE_POLL_INTERVAL_MS)) => {}
+                _ = self.cancelled() => {
+                    self.hold(&moving, velocity).await;
+                    return Err(SequenceError::Cancelled);
+                }
+            }
+        }
+    }
+
+    /// Stop joints where they are, as far as they answer
+    async fn hold(&self, joints: &[(DeviceId, f32)], velocity: f32) {
+        for (id, _) in joints {
+            let Ok(joint) = self.joint(*id) else { continue };
+            let held = match joint.read_multi_turn_position().await {
+                Ok(position) => joint.set_target(position.degrees(), velocity).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = held {
+                warn!("Joint {} could not be held after cancelling: {:?}", id, e);
+            }
+        }
+    }
+
+    /// Resolves once the sequence is cancelled
+    async fn cancelled(&self) {
+        let mut cancel = self.cancel.clone();
+        // The sender lives as long as the handle; a dropped handle never cancels
+        if cancel.wait_for(|cancelled| *cancelled).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    fn joint(&self, id: DeviceId) -> Result<&JointProxy, SequenceError> {
+        self.joints.get(&id).ok_or(SequenceError::UnknownJoint(id))
+    }
+}
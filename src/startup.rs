@@ -0,0 +1,150 @@
+//! Dependency-ordered arm startup
+//!
+//! [`ArmOrchestrator::configure_all`](crate::ArmOrchestrator::configure_all)
+//! and `activate_all` bring every joint up at once. Real arms often need an
+//! order: the base holding before the wrist powers up, brakes released only
+//! once everything holds position. A [`StartupPlan`] declares it:
+//!
+//! ```no_run
+//! use irpc::startup::StartupPlan;
+//!
+//! # async fn example(mut arm: irpc::ArmOrchestrator) -> Result<(), irpc::startup::StartupError> {
+//! let plan = StartupPlan::new()
+//!     .joint(0x0010, [])
+//!     .joint(0x0020, [0x0010])
+//!     .braked_joint(0x0030, [0x0020])
+//!     .braked_joint(0x0040, [0x0020]);
+//! arm.start_up(&plan).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ArmOrchestrator::start_up`](crate::ArmOrchestrator::start_up) brings the
+//! joints up in [stages](StartupPlan::stages): a joint starts once every
+//! joint it comes after is Active, and the joints of one stage start
+//! together. Each joint is stepped through its lifecycle from the state its
+//! proxy last saw (`Unconfigured` → configure → `Inactive` → activate →
+//! `Active`), so a joint that is already up is left alone. Once every stage
+//! is Active the braked joints release their brakes. The first stage with a
+//! failure ends the startup.
+
+use thiserror::Error;
+
+use crate::protocol::{DeviceId, ProtocolError};
+
+/// Startup plan errors
+#[derive(Error, Debug)]
+pub enum StartupError {
+    #[error("Joint {0} is not part of the arm")]
+    UnknownJoint(DeviceId),
+    #[error("Joint {0} is listed twice")]
+    DuplicateJoint(DeviceId),
+    #[error("Joint {joint_id} comes after joint {after}, which is not in the plan")]
+    UnknownDependency { joint_id: DeviceId, after: DeviceId },
+    #[error("Joints {0:?} depend on each other")]
+    Cycle(Vec<DeviceId>),
+    #[error("Joint {joint_id} failed to {step}: {error}")]
+    Failed { joint_id: DeviceId, step: StartupStep, error: ProtocolError },
+}
+
+/// What a joint was doing when startup failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupStep {
+    Configure,
+    Activate,
+    ReleaseBrake,
+}
+
+impl core::fmt::Display for StartupStep {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            StartupStep::Configure => "configure",
+            StartupStep::Activate => "activate",
+            StartupStep::ReleaseBrake => "release its brake",
+        })
+    }
+}
+
+/// One joint of a [`StartupPlan`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartupJoint {
+    pub joint_id: DeviceId,
+    /// Joints that must be Active before this one starts
+    pub after: Vec<DeviceId>,
+    /// Release the joint's brake once the whole plan is Active
+    pub release_brake: bool,
+}
+
+/// The order in which to bring the arm's joints up
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StartupPlan {
+    pub joints: Vec<StartupJoint>,
+}
+
+impl StartupPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every joint at once, as `configure_all` / `activate_all` do
+    pub fn flat(joint_ids: impl IntoIterator<Item = DeviceId>) -> Self {
+        joint_ids.into_iter().fold(Self::new(), |plan, id| plan.joint(id, []))
+    }
+
+    /// Bring `joint_id` up once every joint in `after` is Active
+    pub fn joint(mut self, joint_id: DeviceId, after: impl IntoIterator<Item = DeviceId>) -> Self {
+        self.joints.push(StartupJoint { joint_id, after: after.into_iter().collect(), release_brake: false });
+        self
+    }
+
+    /// Like [`joint`](Self::joint), releasing the joint's brake at the end
+    pub fn braked_joint(mut self, joint_id: DeviceId, after: impl IntoIterator<Item = DeviceId>) -> Self {
+        self.joints.push(StartupJoint { joint_id, after: after.into_iter().collect(), release_brake: true });
+        self
+    }
+
+    /// Joint IDs in plan order
+    pub fn joint_ids(&self) -> Vec<DeviceId> {
+        self.joints.iter().map(|joint| joint.joint_id).collect()
+    }
+
+    /// Joints whose brakes are released at the end, in plan order
+    pub fn braked_joints(&self) -> Vec<DeviceId> {
+        self.joints.iter().filter(|joint| joint.release_brake).map(|joint| joint.joint_id).collect()
+    }
+
+    /// The joints grouped into stages that start one after another
+    ///
+    /// Each joint is in the first stage after all the joints it comes
+    /// after; within a stage joints keep their plan order.
+    pub fn stages(&self) -> Result<Vec<Vec<DeviceId>>, StartupError> {
+        let ids = self.joint_ids();
+        for (index, joint) in self.joints.iter().enumerate() {
+            if ids[..index].contains(&joint.joint_id) {
+                return Err(StartupError::DuplicateJoint(joint.joint_id));
+            }
+            if let Some(&after) = joint.after.iter().find(|id| !ids.contains(id)) {
+                return Err(StartupError::UnknownDependency { joint_id: joint.joint_id, after });
+            }
+        }
+
+        let mut started: Vec<DeviceId> = Vec::with_capacity(ids.len());
+        let mut stages = Vec::new();
+        while started.len() < ids.len() {
+            let stage: Vec<DeviceId> = self
+                .joints
+                .iter()
+                .filter(|joint| !started.contains(&joint.joint_id))
+                .filter(|joint| joint.after.iter().all(|id| started.contains(id)))
+                .map(|joint| joint.joint_id)
+                .collect();
+            if stage.is_empty() {
+                let stuck = ids.into_iter().filter(|id| !started.contains(id)).collect();
+                return Err(StartupError::Cycle(stuck));
+            }
+            started.extend(&stage);
+            stages.push(stage);
+        }
+        Ok(stages)
+    }
+}
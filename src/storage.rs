@@ -0,0 +1,415 @@
+//! Host-side persistence backends
+//!
+//! Everything the host keeps across runs (calibration bundles, audit logs,
+//! lifetime counters, incident files) goes through the [`Storage`] trait.
+//! Records are opaque byte blobs addressed by a namespace and a key; the
+//! [`StorageExt`] helpers store serde types with postcard.
+//!
+//! # Backends
+//!
+//! - [`MemoryStorage`] - in-process map, for tests and dry runs
+//! - [`FileStorage`] - one file per record under a root directory
+//! - `SqliteStorage` - single database file (requires the `sqlite` feature)
+//!
+//! # Example
+//!
+//! ```no_run
+//! use irpc::storage::{FileStorage, Storage, StorageExt, namespace};
+//!
+//! let store = FileStorage::open("/var/lib/irpc")?;
+//! store.save(namespace::COUNTERS, "joint-0010", &1234u64)?;
+//! let cycles: Option<u64> = store.load(namespace::COUNTERS, "joint-0010")?;
+//! # Ok::<(), irpc::storage::StorageError>(())
+//! ```
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// Well-known namespaces used by the host
+pub mod namespace {
+    /// Motor calibration bundles, keyed by joint
+    pub const CALIBRATION: &str = "calibration";
+    /// Append-only operator and command audit log
+    pub const AUDIT: &str = "audit";
+    /// Lifetime counters (cycles, hours, faults)
+    pub const COUNTERS: &str = "counters";
+    /// Incident reports and crash dumps
+    pub const INCIDENTS: &str = "incidents";
+}
+
+/// Storage backend errors
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid namespace or key: {0:?}")]
+    InvalidKey(String),
+    #[error("Encoding error: {0}")]
+    Encoding(String),
+    #[error("Backend error: {0}")]
+    Backend(String),
+}
+
+/// A namespaced key-value store for host-side state
+///
+/// Implementations must be safe to share between tasks; writes of a single
+/// record are atomic (a reader sees either the old or the new value).
+pub trait Storage: Send + Sync {
+    /// Store `value` under `namespace`/`key`, replacing any previous value
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<(), StorageError>;
+
+    /// Fetch the value stored under `namespace`/`key`
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Remove a record. Returns `true` if it existed.
+    fn delete(&self, namespace: &str, key: &str) -> Result<bool, StorageError>;
+
+    /// List the keys in a namespace in ascending order
+    fn keys(&self, namespace: &str) -> Result<Vec<String>, StorageError>;
+
+    /// Append bytes to a record, creating it if missing (for logs)
+    ///
+    /// The default reads the record and writes it back, so it is not
+    /// atomic: appends racing on one record can lose data. Backends that
+    /// are shared between tasks override it.
+    fn append(&self, namespace: &str, key: &str, data: &[u8]) -> Result<(), StorageError> {
+        let mut value = self.get(namespace, key)?.unwrap_or_default();
+        value.extend_from_slice(data);
+        self.put(namespace, key, &value)
+    }
+}
+
+/// Typed helpers on top of [`Storage`]
+pub trait StorageExt: Storage {
+    /// Serialize and store a value
+    fn save<T: Serialize>(&self, namespace: &str, key: &str, value: &T) -> Result<(), StorageError> {
+        let bytes = postcard::to_stdvec(value).map_err(|e| StorageError::Encoding(e.to_string()))?;
+        self.put(namespace, key, &bytes)
+    }
+
+    /// Load and deserialize a value
+    fn load<T: DeserializeOwned>(&self, namespace: &str, key: &str) -> Result<Option<T>, StorageError> {
+        match self.get(namespace, key)? {
+            Some(bytes) => postcard::from_bytes(&bytes)
+                .map(Some)
+                .map_err(|e| StorageError::Encoding(e.to_string())),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<S: Storage + ?Sized> StorageExt for S {}
+
+/// Namespaces and keys become path components, so keep them to a safe set
+fn validate(name: &str) -> Result<(), StorageError> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(name.to_string()))
+    }
+}
+
+// ============================================================================
+// In-memory backend
+// ============================================================================
+
+/// Volatile storage backed by a map
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    records: Mutex<BTreeMap<(String, String), Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<(), StorageError> {
+        validate(namespace)?;
+        validate(key)?;
+        self.records
+            .lock()
+            .unwrap()
+            .insert((namespace.to_string(), key.to_string()), value.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        validate(namespace)?;
+        validate(key)?;
+        Ok(self
+            .records
+            .lock()
+            .unwrap()
+            .get(&(namespace.to_string(), key.to_string()))
+            .cloned())
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<bool, StorageError> {
+        validate(namespace)?;
+        validate(key)?;
+        Ok(self
+            .records
+            .lock()
+            .unwrap()
+            .remove(&(namespace.to_string(), key.to_string()))
+            .is_some())
+    }
+
+    fn keys(&self, namespace: &str) -> Result<Vec<String>, StorageError> {
+        validate(namespace)?;
+        Ok(self
+            .records
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|(ns, _)| ns == namespace)
+            .map(|(_, key)| key.clone())
+            .collect())
+    }
+
+    /// Holds the map lock throughout, so racing appends all land
+    fn append(&self, namespace: &str, key: &str, data: &[u8]) -> Result<(), StorageError> {
+        validate(namespace)?;
+        validate(key)?;
+        self.records
+            .lock()
+            .unwrap()
+            .entry((namespace.to_string(), key.to_string()))
+            .or_default()
+            .extend_from_slice(data);
+        Ok(())
+    }
+}
+
+// ============================================================================
+// File backend
+// ============================================================================
+
+/// Stores each record as `<root>/<namespace>/<key>`
+///
+/// Writes go to a temporary file that is renamed into place, so a crash
+/// never leaves a half-written record behind.
+#[derive(Debug)]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+impl FileStorage {
+    /// Open (and create if needed) a storage directory
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    /// Root directory of this store
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, namespace: &str, key: &str) -> Result<PathBuf, StorageError> {
+        validate(namespace)?;
+        validate(key)?;
+        Ok(self.root.join(namespace).join(key))
+    }
+}
+
+impl Storage for FileStorage {
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<(), StorageError> {
+        let path = self.path(namespace, key)?;
+        fs::create_dir_all(self.root.join(namespace))?;
+
+        // Leading dot keeps temporaries out of keys(); the process ID and a
+        // counter keep racing writers out of each other's temporaries
+        static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+        let unique = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let tmp = self.root.join(namespace).join(format!(".{}.{}.{}.tmp", key, std::process::id(), unique));
+        fs::write(&tmp, value)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        match fs::read(self.path(namespace, key)?) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<bool, StorageError> {
+        match fs::remove_file(self.path(namespace, key)?) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn keys(&self, namespace: &str) -> Result<Vec<String>, StorageError> {
+        validate(namespace)?;
+        let entries = match fs::read_dir(self.root.join(namespace)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut keys = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                if !name.starts_with('.') {
+                    keys.push(name.to_string());
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// Appends in place instead of rewriting the whole record
+    fn append(&self, namespace: &str, key: &str, data: &[u8]) -> Result<(), StorageError> {
+        let path = self.path(namespace, key)?;
+        fs::create_dir_all(self.root.join(namespace))?;
+
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(data)?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// SQLite backend
+// ============================================================================
+
+/// Stores all records in one SQLite database file
+#[cfg(feature = "sqlite")]
+pub struct SqliteStorage {
+    conn: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStorage {
+    /// Open (and create if needed) a database file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        Self::init(rusqlite::Connection::open(path).map_err(backend)?)
+    }
+
+    /// Open a private in-memory database
+    pub fn open_in_memory() -> Result<Self, StorageError> {
+        Self::init(rusqlite::Connection::open_in_memory().map_err(backend)?)
+    }
+
+    fn init(conn: rusqlite::Connection) -> Result<Self, StorageError> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS records (
+                namespace TEXT NOT NULL,
+                key       TEXT NOT NULL,
+                value     BLOB NOT NULL,
+                PRIMARY KEY (namespace, key)
+            )",
+        )
+        .map_err(backend)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn backend(e: rusqlite::Error) -> StorageError {
+    StorageError::Backend(e.to_string())
+}
+
+#[cfg(feature = "sqlite")]
+impl Storage for SqliteStorage {
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<(), StorageError> {
+        validate(namespace)?;
+        validate(key)?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT OR REPLACE INTO records (namespace, key, value) VALUES (?1, ?2, ?3)",
+                rusqlite::params![namespace, key, value],
+            )
+            .map_err(backend)?;
+        Ok(())
+    }
+
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        use rusqlite::OptionalExtension;
+
+        validate(namespace)?;
+        validate(key)?;
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT value FROM records WHERE namespace = ?1 AND key = ?2",
+                rusqlite::params![namespace, key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(backend)
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<bool, StorageError> {
+        validate(namespace)?;
+        validate(key)?;
+        let removed = self
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "DELETE FROM records WHERE namespace = ?1 AND key = ?2",
+                rusqlite::params![namespace, key],
+            )
+            .map_err(backend)?;
+        Ok(removed > 0)
+    }
+
+    fn keys(&self, namespace: &str) -> Result<Vec<String>, StorageError> {
+        validate(namespace)?;
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT key FROM records WHERE namespace = ?1 ORDER BY key")
+            .map_err(backend)?;
+        let keys = stmt
+            .query_map([namespace], |row| row.get(0))
+            .map_err(backend)?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(backend)?;
+        Ok(keys)
+    }
+
+    fn append(&self, namespace: &str, key: &str, data: &[u8]) -> Result<(), StorageError> {
+        validate(namespace)?;
+        validate(key)?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO records (namespace, key, value) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (namespace, key) DO UPDATE SET value = CAST(value || excluded.value AS BLOB)",
+                rusqlite::params![namespace, key, data],
+            )
+            .map_err(backend)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+//! Winding thermal model and its identification
+//!
+//! The motor winding is modelled as a first-order thermal system: copper
+//! losses `P = I² · R_phase` heat it through a thermal resistance `R_th`
+//! (K/W) towards ambient with time constant `τ` (s):
+//!
+//! ```text
+//! dT/dt = (P · R_th - (T - T_ambient)) / τ
+//! ```
+//!
+//! The optional thermal calibration phase drives controlled current pulses
+//! and feeds the measured temperature into a [`ThermalIdentifier`], which
+//! fits `R_th` and `τ` by least squares. The result is reported in
+//! [`MotorParameters`] and used by the joint's derating through
+//! [`ThermalModel`].
+//!
+//! Everything here is `no_std` and allocation-free.
+
+use crate::protocol::MotorParameters;
+
+/// Identified first-order winding thermal model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThermalModel {
+    /// Winding-to-ambient thermal resistance (K/W)
+    pub thermal_resistance: f32,
+    /// Thermal time constant (s)
+    pub time_constant: f32,
+}
+
+impl ThermalModel {
+    /// Model reported by calibration, if the thermal phase ran
+    pub fn from_parameters(parameters: &MotorParameters) -> Option<Self> {
+        let model = Self {
+            thermal_resistance: parameters.thermal_resistance,
+            time_constant: parameters.thermal_time_constant,
+        };
+        model.is_valid().then_some(model)
+    }
+
+    /// Both parameters are finite and positive
+    pub fn is_valid(&self) -> bool {
+        self.thermal_resistance.is_finite()
+            && self.thermal_resistance > 0.0
+            && self.time_constant.is_finite()
+            && self.time_constant > 0.0
+    }
+
+    /// Temperature the winding settles at while dissipating `power` watts
+    pub fn steady_state(&self, ambient_c: f32, power: f32) -> f32 {
+        ambient_c + power * self.thermal_resistance
+    }
+
+    /// Highest dissipation that keeps the winding at or below `limit_c`
+    pub fn max_continuous_power(&self, ambient_c: f32, limit_c: f32) -> f32 {
+        ((limit_c - ambient_c) / self.thermal_resistance).max(0.0)
+    }
+
+    /// Advance a temperature estimate by `dt` seconds at `power` watts
+    ///
+    /// Forward Euler; accurate while `dt` is small against the time constant.
+    pub fn step(&self, temperature_c: f32, ambient_c: f32, power: f32, dt: f32) -> f32 {
+        let rise = self.steady_state(ambient_c, power) - temperature_c;
+        temperature_c + rise * (dt / self.time_constant).min(1.0)
+    }
+}
+
+/// Least-squares fit of a [`ThermalModel`] from pulse test samples
+///
+/// Each pair of consecutive samples gives one equation
+/// `ΔT/Δt = a · P - b · (T - T_ambient)` with `a = R_th / τ` and `b = 1 / τ`;
+/// the identifier accumulates the 2×2 normal equations, so memory use is
+/// constant however long the test runs. Samples should cover both heating
+/// (current on) and cooling (current off) for a well-conditioned fit.
+#[derive(Debug, Clone)]
+pub struct ThermalIdentifier {
+    ambient_c: f32,
+    phase_resistance: f32,
+    last: Option<(f32, f32, f32)>,
+    // Normal equation sums, in f64 to keep long runs accurate
+    spp: f64,
+    spd: f64,
+    sdd: f64,
+    syp: f64,
+    syd: f64,
+    equations: u32,
+}
+
+/// cbindgen:ignore
+impl ThermalIdentifier {
+    /// Fewest sample intervals accepted for an estimate
+    pub const MIN_EQUATIONS: u32 = 10;
+
+    /// Start a fit for a winding of `phase_resistance` ohms at `ambient_c`
+    pub fn new(ambient_c: f32, phase_resistance: f32) -> Self {
+        Self {
+            ambient_c,
+            phase_resistance,
+            last: None,
+            spp: 0.0,
+            spd: 0.0,
+            sdd: 0.0,
+            syp: 0.0,
+            syd: 0.0,
+            equations: 0,
+        }
+    }
+
+    /// Add a sample: time (s), RMS phase current (A) and winding temperature (°C)
+    ///
+    /// The current is the one applied since the previous sample. Samples
+    /// that do not advance time are ignored.
+    pub fn add_sample(&mut self, time_s: f32, current_a: f32, temperature_c: f32) {
+        if let Some((t0, i0, temp0)) = self.last {
+            let dt = time_s - t0;
+            if dt <= 0.0 {
+                return;
+            }
+            // Midpoint rule: losses and temperature over the interval
+            let current = (i0 + current_a) * 0.5;
+            let power = (current * current * self.phase_resistance) as f64;
+            let excess = (((temp0 + temperature_c) * 0.5) - self.ambient_c) as f64;
+            let slope = ((temperature_c - temp0) / dt) as f64;
+
+            // Unknowns x = [a, b]; row = [P, -(T - T_amb)]
+            let (p, d) = (power, -excess);
+            self.spp += p * p;
+            self.spd += p * d;
+            self.sdd += d * d;
+            self.syp += slope * p;
+            self.syd += slope * d;
+            self.equations += 1;
+        }
+        self.last = Some((time_s, current_a, temperature_c));
+    }
+
+    /// Number of sample intervals collected
+    pub fn equations(&self) -> u32 {
+        self.equations
+    }
+
+    /// The fitted model, or `None` if there is too little or degenerate data
+    pub fn estimate(&self) -> Option<ThermalModel> {
+        if self.equations < Self::MIN_EQUATIONS {
+            return None;
+        }
+        let det = self.spp * self.sdd - self.spd * self.spd;
+        if det.abs() <= f64::EPSILON * self.spp * self.sdd {
+            return None;
+        }
+        let a = (self.syp * self.sdd - self.syd * self.spd) / det;
+        let b = (self.spp * self.syd - self.spd * self.syp) / det;
+
+        let model = ThermalModel {
+            thermal_resistance: (a / b) as f32,
+            time_constant: (1.0 / b) as f32,
+        };
+        model.is_valid().then_some(model)
+    }
+}
@@ -0,0 +1,367 @@
+//! Waypoint trajectory files
+//!
+//! Loads motion authored in external tools (CAD, offline planners,
+//! spreadsheets) as timed per-joint waypoints, checks it against joint
+//! limits and resamples it to the streaming rate for
+//! [`ArmOrchestrator::stream_trajectory`](crate::ArmOrchestrator::stream_trajectory).
+//!
+//! # CSV format
+//!
+//! The first non-comment line is a header. The `time` column holds seconds;
+//! every other column is a joint ID (decimal or `0x` hex) holding its angle
+//! in degrees. Velocities in degrees/second may be given in `<id>.velocity`
+//! columns, either for every joint or for none. Lines starting with `#` and
+//! blank lines are ignored.
+//!
+//! ```text
+//! # pick approach
+//! time, 0x0010, 0x0010.velocity, 0x0011, 0x0011.velocity
+//! 0.0,  0.0,    0.0,             10.0,   0.0
+//! 0.5,  15.0,   30.0,            12.5,   5.0
+//! 1.0,  30.0,   0.0,             15.0,   0.0
+//! ```
+//!
+//! # JSON format (requires the `json` feature)
+//!
+//! ```text
+//! {
+//!   "joints": [16, 17],
+//!   "waypoints": [
+//!     { "time": 0.0, "angles": [0.0, 10.0] },
+//!     { "time": 1.0, "angles": [30.0, 15.0], "velocities": [0.0, 0.0] }
+//!   ]
+//! }
+//! ```
+//!
+//! Without explicit velocities, the velocity of each segment is derived
+//! from the angle difference between its waypoints.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use irpc::trajectory::Trajectory;
+//!
+//! let trajectory = Trajectory::load("pick.csv")?;
+//! let samples = trajectory.resample(100)?;
+//! println!("{} samples over {:.2} s", samples.waypoints().len(), samples.duration());
+//! # Ok::<(), irpc::trajectory::TrajectoryError>(())
+//! ```
+
+use crate::protocol::{DeviceId, JointLimits, ProtocolError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+
+/// Trajectory loading, validation and execution errors
+#[derive(Error, Debug)]
+pub enum TrajectoryError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("Unsupported trajectory file: {0}")]
+    UnsupportedFormat(String),
+    #[error("Trajectory has no joints or no waypoints")]
+    Empty,
+    #[error("Waypoint {index} does not have one value per joint")]
+    Shape { index: usize },
+    #[error("Waypoint {index} does not come after the previous one")]
+    TimeNotIncreasing { index: usize },
+    #[error("Joint {joint} at waypoint {index}: angle {angle} outside limits")]
+    PositionLimit { joint: DeviceId, index: usize, angle: f32 },
+    #[error("Joint {joint} at waypoint {index}: velocity {velocity} exceeds limit")]
+    VelocityLimit { joint: DeviceId, index: usize, velocity: f32 },
+    #[error("Joint {0} is not part of the arm")]
+    UnknownJoint(DeviceId),
+    #[error("Invalid resampling rate: {0} Hz")]
+    InvalidRate(u32),
+    #[error("Protocol error: {0}")]
+    Protocol(#[from] ProtocolError),
+}
+
+/// One point in time of a trajectory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Waypoint {
+    /// Seconds since the start of the trajectory
+    pub time: f64,
+    /// Angle of each joint in degrees, in [`Trajectory::joints`] order
+    pub angles: Vec<f32>,
+    /// Velocity of each joint in degrees/second, if given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub velocities: Option<Vec<f32>>,
+}
+
+/// Timed waypoints for a set of joints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawTrajectory")]
+pub struct Trajectory {
+    joints: Vec<DeviceId>,
+    waypoints: Vec<Waypoint>,
+}
+
+/// Unchecked form used for deserialization
+#[derive(Deserialize)]
+struct RawTrajectory {
+    joints: Vec<DeviceId>,
+    waypoints: Vec<Waypoint>,
+}
+
+impl TryFrom<RawTrajectory> for Trajectory {
+    type Error = TrajectoryError;
+
+    fn try_from(raw: RawTrajectory) -> Result<Self, Self::Error> {
+        Trajectory::new(raw.joints, raw.waypoints)
+    }
+}
+
+impl Trajectory {
+    /// Build a trajectory, checking that every waypoint covers every joint
+    /// and that time strictly increases
+    pub fn new(joints: Vec<DeviceId>, waypoints: Vec<Waypoint>) -> Result<Self, TrajectoryError> {
+        if joints.is_empty() || waypoints.is_empty() {
+            return Err(TrajectoryError::Empty);
+        }
+        for (index, waypoint) in waypoints.iter().enumerate() {
+            let velocities_ok = waypoint.velocities.as_ref().is_none_or(|v| v.len() == joints.len());
+            if waypoint.angles.len() != joints.len() || !velocities_ok {
+                return Err(TrajectoryError::Shape { index });
+            }
+            if !waypoint.time.is_finite() || (index > 0 && waypoint.time <= waypoints[index - 1].time) {
+                return Err(TrajectoryError::TimeNotIncreasing { index });
+            }
+        }
+        Ok(Self { joints, waypoints })
+    }
+
+    /// Load a `.csv` or `.json` file, chosen by extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TrajectoryError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Self::from_csv(&text),
+            #[cfg(feature = "json")]
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json(&text),
+            _ => Err(TrajectoryError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+
+    /// Parse the CSV format described in the [module docs](self)
+    pub fn from_csv(text: &str) -> Result<Self, TrajectoryError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (header_line, header) = lines.next().ok_or(TrajectoryError::Empty)?;
+        let columns = parse_header(header_line, header)?;
+        let joints: Vec<DeviceId> = columns
+            .iter()
+            .filter_map(|c| match c {
+                Column::Angle(joint) => Some(*joint),
+                _ => None,
+            })
+            .collect();
+        let has_velocities = columns.iter().any(|c| matches!(c, Column::Velocity(_)));
+        if has_velocities && columns.iter().filter(|c| matches!(c, Column::Velocity(_))).count() != joints.len() {
+            return Err(parse_error(header_line, "velocity columns must be given for all joints or none"));
+        }
+
+        let mut waypoints = Vec::new();
+        for (line_no, line) in lines {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != columns.len() {
+                return Err(parse_error(
+                    line_no,
+                    format!("expected {} fields, found {}", columns.len(), fields.len()),
+                ));
+            }
+
+            let mut time = 0.0;
+            let mut angles = vec![0.0; joints.len()];
+            let mut velocities = vec![0.0; joints.len()];
+            for (column, field) in columns.iter().zip(fields) {
+                let value: f64 = field
+                    .parse()
+                    .map_err(|_| parse_error(line_no, format!("invalid number {:?}", field)))?;
+                match column {
+                    Column::Time => time = value,
+                    Column::Angle(joint) => angles[joint_index(&joints, *joint)] = value as f32,
+                    Column::Velocity(joint) => velocities[joint_index(&joints, *joint)] = value as f32,
+                }
+            }
+            waypoints.push(Waypoint {
+                time,
+                angles,
+                velocities: has_velocities.then_some(velocities),
+            });
+        }
+
+        Self::new(joints, waypoints)
+    }
+
+    /// Parse the JSON format described in the [module docs](self)
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> Result<Self, TrajectoryError> {
+        serde_json::from_str(text).map_err(|e| parse_error(e.line(), e.to_string()))
+    }
+
+    /// Joints in the order their values appear in each waypoint
+    pub fn joints(&self) -> &[DeviceId] {
+        &self.joints
+    }
+
+    /// The waypoints, in time order
+    pub fn waypoints(&self) -> &[Waypoint] {
+        &self.waypoints
+    }
+
+    /// Time from the first to the last waypoint, in seconds
+    pub fn duration(&self) -> f64 {
+        self.waypoints[self.waypoints.len() - 1].time - self.waypoints[0].time
+    }
+
+    /// Velocity of `joint` (an index into [`joints`](Self::joints)) at waypoint `index`
+    ///
+    /// Uses the explicit velocity if the file gave one, otherwise the
+    /// average velocity of the segment leading up to the waypoint (the
+    /// following segment for the first waypoint).
+    pub fn velocity(&self, index: usize, joint: usize) -> f32 {
+        if let Some(velocities) = &self.waypoints[index].velocities {
+            return velocities[joint];
+        }
+        if self.waypoints.len() < 2 {
+            return 0.0;
+        }
+        let (a, b) = if index == 0 { (0, 1) } else { (index - 1, index) };
+        let (a, b) = (&self.waypoints[a], &self.waypoints[b]);
+        ((b.angles[joint] - a.angles[joint]) as f64 / (b.time - a.time)) as f32
+    }
+
+    /// Check every waypoint against the limits of the joints that have them
+    ///
+    /// Joints without an entry in `limits` are not checked.
+    pub fn validate(&self, limits: &HashMap<DeviceId, JointLimits>) -> Result<(), TrajectoryError> {
+        for (j, joint) in self.joints.iter().enumerate() {
+            let Some(limits) = limits.get(joint) else { continue };
+            for (index, waypoint) in self.waypoints.iter().enumerate() {
+                let angle = waypoint.angles[j];
+                if !(limits.min_position..=limits.max_position).contains(&angle) {
+                    return Err(TrajectoryError::PositionLimit { joint: *joint, index, angle });
+                }
+                let velocity = self.velocity(index, j);
+                if velocity.abs() > limits.max_velocity {
+                    return Err(TrajectoryError::VelocityLimit { joint: *joint, index, velocity });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Resample to a fixed rate by linear interpolation
+    ///
+    /// The result starts at the first waypoint, has one waypoint every
+    /// `1 / rate_hz` seconds, ends exactly at the last waypoint and always
+    /// carries velocities.
+    pub fn resample(&self, rate_hz: u32) -> Result<Self, TrajectoryError> {
+        if rate_hz == 0 {
+            return Err(TrajectoryError::InvalidRate(rate_hz));
+        }
+        let period = 1.0 / rate_hz as f64;
+        let start = self.waypoints[0].time;
+        let end = self.waypoints[self.waypoints.len() - 1].time;
+
+        let mut waypoints = Vec::new();
+        let mut segment = 0;
+        for step in 0usize.. {
+            let time = start + step as f64 * period;
+            // Stop once the remaining gap is below rounding noise
+            if time > end - period * 1e-6 {
+                break;
+            }
+            while self.waypoints[segment + 1].time < time {
+                segment += 1;
+            }
+            waypoints.push(self.interpolate(segment, time));
+        }
+        let last = self.waypoints.len() - 1;
+        waypoints.push(Waypoint {
+            time: end,
+            angles: self.waypoints[last].angles.clone(),
+            velocities: Some((0..self.joints.len()).map(|j| self.velocity(last, j)).collect()),
+        });
+
+        Self::new(self.joints.clone(), waypoints)
+    }
+
+    /// Linear interpolation between waypoints `segment` and `segment + 1`
+    fn interpolate(&self, segment: usize, time: f64) -> Waypoint {
+        let (a, b) = (&self.waypoints[segment], &self.waypoints[segment + 1]);
+        let t = ((time - a.time) / (b.time - a.time)) as f32;
+        let lerp = |x: f32, y: f32| x + (y - x) * t;
+
+        let angles = a.angles.iter().zip(&b.angles).map(|(x, y)| lerp(*x, *y)).collect();
+        let velocities = match (&a.velocities, &b.velocities) {
+            (Some(va), Some(vb)) => va.iter().zip(vb).map(|(x, y)| lerp(*x, *y)).collect(),
+            // Derived velocity is constant over the segment
+            _ => (0..self.joints.len()).map(|j| self.velocity(segment + 1, j)).collect(),
+        };
+        Waypoint { time, angles, velocities: Some(velocities) }
+    }
+}
+
+/// A CSV header column
+enum Column {
+    Time,
+    Angle(DeviceId),
+    Velocity(DeviceId),
+}
+
+fn parse_header(line: usize, header: &str) -> Result<Vec<Column>, TrajectoryError> {
+    let mut columns = Vec::new();
+    for name in header.split(',').map(str::trim) {
+        let column = if name.eq_ignore_ascii_case("time") {
+            Column::Time
+        } else if let Some(id) = name.strip_suffix(".velocity") {
+            Column::Velocity(parse_joint_id(line, id)?)
+        } else {
+            Column::Angle(parse_joint_id(line, name)?)
+        };
+        columns.push(column);
+    }
+
+    if columns.iter().filter(|c| matches!(c, Column::Time)).count() != 1 {
+        return Err(parse_error(line, "header needs exactly one time column"));
+    }
+    for (i, column) in columns.iter().enumerate() {
+        let duplicate = columns[..i].iter().any(|c| match (c, column) {
+            (Column::Angle(a), Column::Angle(b)) | (Column::Velocity(a), Column::Velocity(b)) => a == b,
+            _ => false,
+        });
+        let orphan = match column {
+            Column::Velocity(id) => !columns.iter().any(|c| matches!(c, Column::Angle(a) if a == id)),
+            _ => false,
+        };
+        if duplicate || orphan {
+            return Err(parse_error(line, format!("duplicate or orphaned column {}", i + 1)));
+        }
+    }
+    Ok(columns)
+}
+
+fn parse_joint_id(line: usize, text: &str) -> Result<DeviceId, TrajectoryError> {
+    let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => DeviceId::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    parsed.map_err(|_| parse_error(line, format!("invalid joint ID {:?}", text)))
+}
+
+fn joint_index(joints: &[DeviceId], joint: DeviceId) -> usize {
+    joints.iter().position(|j| *j == joint).expect("joint list built from the header")
+}
+
+fn parse_error(line: usize, message: impl Into<String>) -> TrajectoryError {
+    TrajectoryError::Parse { line, message: message.into() }
+}
@@ -0,0 +1,493 @@
+//! Classic CAN 2.0 transport for STM32 bxCAN peripherals
+//!
+//! Older joints (e.g. STM32F4) only have a bxCAN peripheral with 8-byte
+//! frames. They share the bus with CAN-FD nodes by using the same
+//! arbitration ID scheme ([`CanId`]) and splitting every message body that
+//! does not fit one frame into numbered fragments.
+//!
+//! # Fragment numbering
+//!
+//! The low 10 bits of the arbitration ID, reserved in the CAN-FD scheme,
+//! carry the fragment position:
+//!
+//! ```text
+//!  9        4   3     2      0
+//! +----------+------+--------+
+//! |  index   | more |transfer|
+//! +----------+------+--------+
+//! ```
+//!
+//! A message that fits one frame is sent with all three fields zero, so it
+//! is indistinguishable from a CAN-FD frame and any node can decode it with
+//! [`decode_frame`]. Longer messages are sent as fragments `0..n` with
+//! `more` set on all but the last; `transfer` is a rolling tag that keeps
+//! consecutive messages from the same source apart.
+//!
+//! # Example
+//!
+//! Needs the `stm32f4` feature and the chip's peripherals, so it is not
+//! compiled as a doctest.
+//!
+//! ```ignore
+//! use irpc::transport::{BxCanTransport, BxCanConfig};
+//! use irpc::Joint;
+//!
+//! let config = BxCanConfig::for_joint(0x0010);
+//! let mut transport = BxCanTransport::new(p.CAN1, p.PA11, p.PA12, Irqs, config).await;
+//! let mut joint = Joint::new(0x0010);
+//!
+//! loop {
+//!     if let Ok(msg) = transport.receive_message().await {
+//!         if let Some(resp) = joint.handle_message(&msg) {
+//!             transport.send_message(&resp).await.ok();
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::config::{CANFD_MAX_DATA_LEN, CLASSIC_CAN_MAX_DATA_LEN, FRAGMENT_REASSEMBLY_SLOTS};
+use crate::protocol::{DeviceId, Message};
+use crate::transport::canfd::{decode_frame, CanError, CanId};
+#[cfg(feature = "stm32f4")]
+use crate::protocol::{TransportErrorKind, TransportStats};
+#[cfg(feature = "stm32f4")]
+use crate::transport::canfd::encode_body;
+#[cfg(feature = "stm32f4")]
+use crate::bus::AsyncEmbeddedTransport;
+
+const INDEX_SHIFT: u32 = 4;
+const INDEX_MASK: u32 = 0x3F;
+const MORE_BIT: u32 = 1 << 3;
+const TRANSFER_MASK: u32 = 0x07;
+
+/// Largest number of fragments in one transfer
+pub const MAX_FRAGMENTS: usize = INDEX_MASK as usize + 1;
+
+// Reassembly buffer size; message bodies are never larger than a full message
+const MAX_BODY_LEN: usize = Message::max_size();
+
+// ============================================================================
+// Fragmentation
+// ============================================================================
+
+/// Fragment position carried in the low bits of the arbitration ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FragmentInfo {
+    /// Position of this fragment in the transfer
+    pub index: u8,
+    /// More fragments follow
+    pub more: bool,
+    /// Rolling transfer tag (0-7)
+    pub transfer: u8,
+}
+
+impl FragmentInfo {
+    /// Extract the fragment fields from a raw arbitration ID
+    pub const fn from_raw(raw: u32) -> Self {
+        Self {
+            index: ((raw >> INDEX_SHIFT) & INDEX_MASK) as u8,
+            more: raw & MORE_BIT != 0,
+            transfer: (raw & TRANSFER_MASK) as u8,
+        }
+    }
+
+    /// Pack into the low bits of an arbitration ID
+    pub const fn to_bits(&self) -> u32 {
+        ((self.index as u32 & INDEX_MASK) << INDEX_SHIFT)
+            | if self.more { MORE_BIT } else { 0 }
+            | (self.transfer as u32 & TRANSFER_MASK)
+    }
+
+    /// Whether the frame holds a complete message
+    pub const fn is_single(&self) -> bool {
+        self.index == 0 && !self.more
+    }
+}
+
+/// Iterator over the classic CAN frames of one message body
+///
+/// Yields `(raw_id, data)` pairs in transmission order.
+pub struct Fragments<'a> {
+    base_id: u32,
+    transfer: u8,
+    chunks: core::slice::Chunks<'a, u8>,
+    index: u8,
+    single: bool,
+}
+
+impl<'a> Iterator for Fragments<'a> {
+    type Item = (u32, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = self.chunks.next()?;
+        let info = if self.single {
+            FragmentInfo { index: 0, more: false, transfer: 0 }
+        } else {
+            FragmentInfo {
+                index: self.index,
+                more: self.chunks.len() > 0,
+                transfer: self.transfer,
+            }
+        };
+        self.index += 1;
+        Some((self.base_id | info.to_bits(), data))
+    }
+}
+
+/// Split an encoded message body into classic CAN frames
+///
+/// `raw_id` is the arbitration ID from [`encode_body`](crate::transport::canfd::encode_body);
+/// its fragment bits are overwritten. Bodies of up to 8 bytes produce a
+/// single unfragmented frame.
+pub fn fragment(raw_id: u32, transfer: u8, body: &[u8]) -> Result<Fragments<'_>, CanError> {
+    fragment_with_mtu(raw_id, transfer, body, CLASSIC_CAN_MAX_DATA_LEN)
+}
+
+/// Split an encoded message body into frames of at most `mtu` bytes
+///
+/// Same numbering as [`fragment`], for links whose frames are not 8 bytes
+/// (e.g. CAN-FD nodes sending payloads larger than one 64-byte frame).
+/// `mtu` must be between 1 and `CANFD_MAX_DATA_LEN`, the largest fragment
+/// a [`Reassembler`] accepts.
+pub fn fragment_with_mtu(
+    raw_id: u32,
+    transfer: u8,
+    body: &[u8],
+    mtu: usize,
+) -> Result<Fragments<'_>, CanError> {
+    if mtu == 0 || mtu > CANFD_MAX_DATA_LEN {
+        return Err(CanError::InvalidConfig);
+    }
+    let count = body.len().div_ceil(mtu);
+    if count > MAX_FRAGMENTS {
+        return Err(CanError::FrameTooLarge);
+    }
+
+    Ok(Fragments {
+        base_id: CanId::from_raw(raw_id).to_raw(),
+        transfer: transfer & TRANSFER_MASK as u8,
+        chunks: body.chunks(mtu),
+        index: 0,
+        single: count <= 1,
+    })
+}
+
+// ============================================================================
+// Reassembly
+// ============================================================================
+
+#[derive(Clone, Copy)]
+struct Slot {
+    active: bool,
+    source: DeviceId,
+    transfer: u8,
+    next_index: u8,
+    len: usize,
+    buf: [u8; MAX_BODY_LEN],
+}
+
+impl Slot {
+    const EMPTY: Self = Self {
+        active: false,
+        source: 0,
+        transfer: 0,
+        next_index: 0,
+        len: 0,
+        buf: [0u8; MAX_BODY_LEN],
+    };
+}
+
+/// Rebuilds messages from classic CAN fragments
+///
+/// Keeps one transfer in progress per source, for up to
+/// `FRAGMENT_REASSEMBLY_SLOTS` sources at once. A new transfer from a
+/// source replaces its unfinished one.
+pub struct Reassembler {
+    slots: [Slot; FRAGMENT_REASSEMBLY_SLOTS],
+    victim: usize,
+}
+
+impl Reassembler {
+    /// Create an empty reassembler
+    pub const fn new() -> Self {
+        Self {
+            slots: [Slot::EMPTY; FRAGMENT_REASSEMBLY_SLOTS],
+            victim: 0,
+        }
+    }
+
+    /// Feed one received frame
+    ///
+    /// Returns `Ok(Some(message))` once the last fragment of a transfer
+    /// arrives, `Ok(None)` while a transfer is still incomplete. Fragments
+    /// may be up to one CAN-FD frame long, so transfers split with
+    /// [`fragment_with_mtu`] by FD nodes are rebuilt as well.
+    pub fn push(&mut self, raw_id: u32, data: &[u8]) -> Result<Option<Message>, CanError> {
+        let info = FragmentInfo::from_raw(raw_id);
+        if info.is_single() {
+            return decode_frame(raw_id, data).map(Some);
+        }
+        if data.len() > CANFD_MAX_DATA_LEN {
+            return Err(CanError::FrameTooLarge);
+        }
+
+        let source = CanId::from_raw(raw_id).source_id;
+        let found = self.slots.iter().position(|s| s.active && s.source == source);
+
+        let index = if info.index == 0 {
+            let index = found
+                .or_else(|| self.slots.iter().position(|s| !s.active))
+                .unwrap_or_else(|| {
+                    // Every slot busy: evict transfers round-robin
+                    let index = self.victim;
+                    self.victim = (self.victim + 1) % FRAGMENT_REASSEMBLY_SLOTS;
+                    index
+                });
+            let slot = &mut self.slots[index];
+            slot.active = true;
+            slot.source = source;
+            slot.transfer = info.transfer;
+            slot.next_index = 0;
+            slot.len = 0;
+            index
+        } else {
+            match found {
+                Some(index)
+                    if self.slots[index].transfer == info.transfer
+                        && self.slots[index].next_index == info.index =>
+                {
+                    index
+                }
+                Some(index) => {
+                    self.slots[index].active = false;
+                    return Err(CanError::FragmentLost);
+                }
+                None => return Err(CanError::FragmentLost),
+            }
+        };
+
+        let slot = &mut self.slots[index];
+        if slot.len + data.len() > MAX_BODY_LEN {
+            slot.active = false;
+            return Err(CanError::FrameTooLarge);
+        }
+        slot.buf[slot.len..slot.len + data.len()].copy_from_slice(data);
+        slot.len += data.len();
+        slot.next_index += 1;
+
+        if info.more {
+            return Ok(None);
+        }
+
+        slot.active = false;
+        decode_frame(raw_id, &slot.buf[..slot.len]).map(Some)
+    }
+
+    /// Drop all unfinished transfers
+    pub fn clear(&mut self) {
+        for slot in &mut self.slots {
+            slot.active = false;
+        }
+    }
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Classic CAN configuration for a joint node
+#[derive(Debug, Clone)]
+pub struct BxCanConfig {
+    /// Node ID for this device (used in CAN identifiers)
+    pub node_id: DeviceId,
+
+    /// Bus bitrate (Hz)
+    /// Typical: 1_000_000 (1 Mbps), must match the nominal bitrate of FD nodes
+    pub bitrate: u32,
+}
+
+impl BxCanConfig {
+    /// Create configuration for a joint with the default bitrate
+    ///
+    /// Default: 1 Mbps
+    pub fn for_joint(node_id: DeviceId) -> Self {
+        Self {
+            node_id,
+            bitrate: 1_000_000,
+        }
+    }
+}
+
+// ============================================================================
+// STM32F4 Implementation
+// ============================================================================
+
+/// Classic CAN transport for STM32 bxCAN peripherals
+///
+/// Fragments outgoing messages and reassembles incoming ones transparently.
+#[cfg(feature = "stm32f4")]
+pub struct BxCanTransport<'d> {
+    can: embassy_stm32::can::Can<'d>,
+    node_id: DeviceId,
+    body_buffer: [u8; MAX_BODY_LEN],
+    reassembler: Reassembler,
+    transfer: u8,
+    stats: TransportStats,
+}
+
+#[cfg(feature = "stm32f4")]
+impl<'d> BxCanTransport<'d> {
+    /// Create, configure and enable a new bxCAN transport
+    ///
+    /// Accepts all extended-ID frames into FIFO0; addressing is handled by
+    /// the joint state machine.
+    pub async fn new<T, TX, RX, I>(
+        peri: embassy_stm32::Peri<'d, T>,
+        rx_pin: embassy_stm32::Peri<'d, RX>,
+        tx_pin: embassy_stm32::Peri<'d, TX>,
+        irqs: I,
+        config: BxCanConfig,
+    ) -> Self
+    where
+        T: embassy_stm32::can::Instance,
+        TX: embassy_stm32::can::TxPin<T>,
+        RX: embassy_stm32::can::RxPin<T>,
+        I: embassy_stm32::interrupt::typelevel::Binding<T::TXInterrupt, embassy_stm32::can::TxInterruptHandler<T>>
+            + embassy_stm32::interrupt::typelevel::Binding<T::RX0Interrupt, embassy_stm32::can::Rx0InterruptHandler<T>>
+            + embassy_stm32::interrupt::typelevel::Binding<T::RX1Interrupt, embassy_stm32::can::Rx1InterruptHandler<T>>
+            + embassy_stm32::interrupt::typelevel::Binding<T::SCEInterrupt, embassy_stm32::can::SceInterruptHandler<T>>
+            + 'd,
+    {
+        use embassy_stm32::can;
+
+        let mut can = can::Can::new(peri, rx_pin, tx_pin, irqs);
+
+        can.modify_filters()
+            .enable_bank(0, can::Fifo::Fifo0, can::filter::Mask32::accept_all());
+
+        can.modify_config()
+            .set_loopback(false)
+            .set_silent(false)
+            .set_bitrate(config.bitrate);
+
+        can.enable().await;
+
+        Self {
+            can,
+            node_id: config.node_id,
+            body_buffer: [0u8; MAX_BODY_LEN],
+            reassembler: Reassembler::new(),
+            transfer: 0,
+            stats: TransportStats::with_mtu(MAX_BODY_LEN),
+        }
+    }
+
+    /// Send a message, fragmenting it if it does not fit one frame
+    pub async fn send_message(&mut self, message: &Message) -> Result<(), CanError> {
+        use embassy_stm32::can::frame::Frame;
+
+        let (raw_id, len) = match encode_body(message, &mut self.body_buffer) {
+            Ok(body) => body,
+            Err(e) => {
+                self.stats.record_error(TransportErrorKind::Serialization);
+                return Err(e);
+            }
+        };
+
+        let transfer = self.transfer;
+        self.transfer = (self.transfer + 1) & TRANSFER_MASK as u8;
+
+        for (id, data) in fragment(raw_id, transfer, &self.body_buffer[..len])? {
+            let frame = Frame::new_extended(id, data).map_err(|_| CanError::InvalidConfig)?;
+            self.can.write(&frame).await;
+            self.stats.tx_frames = self.stats.tx_frames.wrapping_add(1);
+        }
+
+        Ok(())
+    }
+
+    /// Receive the next complete message
+    ///
+    /// Waits until all fragments of a message have arrived.
+    pub async fn receive_message(&mut self) -> Result<Message, CanError> {
+        use embassy_stm32::can::enums::BusError;
+        use embassy_stm32::can::Id;
+
+        loop {
+            let envelope = match self.can.read().await {
+                Ok(envelope) => envelope,
+                Err(e) => {
+                    self.stats.record_error(match e {
+                        BusError::Crc => TransportErrorKind::Crc,
+                        BusError::BusOff => TransportErrorKind::BusOff,
+                        _ => TransportErrorKind::Transport,
+                    });
+                    return Err(CanError::RxFailed);
+                }
+            };
+            self.stats.rx_frames = self.stats.rx_frames.wrapping_add(1);
+
+            let frame = envelope.frame;
+            let raw_id = match frame.header().id() {
+                Id::Extended(id) => id.as_raw(),
+                Id::Standard(_) => {
+                    self.stats.record_error(TransportErrorKind::Deserialization);
+                    return Err(CanError::DeserializationError);
+                }
+            };
+
+            match self.reassembler.push(raw_id, frame.data()) {
+                Ok(Some(message)) => return Ok(message),
+                Ok(None) => continue,
+                Err(e) => {
+                    self.stats.record_error(TransportErrorKind::Deserialization);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Get the transport statistics collected so far
+    pub fn stats(&self) -> &TransportStats {
+        &self.stats
+    }
+
+    /// Reset all statistics to zero
+    pub fn reset_stats(&mut self) {
+        self.stats = TransportStats::with_mtu(MAX_BODY_LEN);
+    }
+
+    /// Get node ID
+    pub fn node_id(&self) -> DeviceId {
+        self.node_id
+    }
+}
+
+/// Lets the transport be wrapped in an [`AsyncTransportLayer`](crate::AsyncTransportLayer)
+/// and driven by [`Joint::process_transport_async`](crate::Joint::process_transport_async)
+#[cfg(feature = "stm32f4")]
+impl<'d> AsyncEmbeddedTransport for BxCanTransport<'d> {
+    type Error = CanError;
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let message = Message::deserialize(data).map_err(|_| CanError::DeserializationError)?;
+        self.send_message(&message).await
+    }
+
+    async fn receive(&mut self) -> Result<&[u8], Self::Error> {
+        // The body buffer is only used while sending, so it can hold the
+        // re-encoded message until the next call
+        let message = self.receive_message().await?;
+        let len = message
+            .serialize_into(&mut self.body_buffer)
+            .map_err(|_| CanError::SerializationError)?;
+        Ok(&self.body_buffer[..len])
+    }
+}
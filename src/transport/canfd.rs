@@ -6,20 +6,27 @@
 //! # Features
 //!
 //! - Automatic FDCAN peripheral configuration
+//! - Routing header carried in the 29-bit arbitration ID (see [`CanId`])
+//! - Optional CANopen-style addressing for buses shared with CANopen devices
+//!   (see [`Addressing`])
 //! - Message serialization/deserialization
 //! - Buffer management
 //! - Error handling
 //!
 //! # Example
 //!
-//! ```no_run
-//! use irpc::transport::{CanFdTransport, CanFdConfig};
+//! Needs the `stm32g4` feature and the chip's FDCAN peripherals, so it is
+//! not compiled as a doctest.
+//!
+//! ```ignore
+//! use irpc::transport::{Addressing, CanFdTransport, CanFdConfig};
 //! use irpc::Joint;
 //!
 //! let config = CanFdConfig {
 //!     node_id: 0x0010,
 //!     nominal_bitrate: 1_000_000,  // 1 Mbps for arbitration
 //!     data_bitrate: 5_000_000,      // 5 Mbps for data phase
+//!     addressing: Addressing::Native,
 //! };
 //!
 //! let mut transport = CanFdTransport::new(
@@ -40,10 +47,13 @@
 //! }
 //! ```
 
-use crate::protocol::{Message, DeviceId};
+use crate::config::CANFD_MAX_DATA_LEN;
+use crate::protocol::{Header, Message, MessageId, Payload, DeviceId, TransportStats};
+#[cfg(feature = "stm32g4")]
+use crate::protocol::TransportErrorKind;
 
 // Maximum CAN-FD frame payload (64 bytes)
-const MAX_FDCAN_PAYLOAD: usize = 64;
+const MAX_FDCAN_PAYLOAD: usize = CANFD_MAX_DATA_LEN;
 
 // ============================================================================
 // Configuration
@@ -62,17 +72,21 @@ pub struct CanFdConfig {
     /// Data bitrate for FD data phase (Hz)
     /// Typical: 5_000_000 (5 Mbps)
     pub data_bitrate: u32,
+
+    /// How messages map onto CAN identifiers
+    pub addressing: Addressing,
 }
 
 impl CanFdConfig {
     /// Create configuration for a joint with default bitrates
     ///
-    /// Default: 1 Mbps nominal, 5 Mbps data
+    /// Default: 1 Mbps nominal, 5 Mbps data, native addressing
     pub fn for_joint(node_id: DeviceId) -> Self {
         Self {
             node_id,
             nominal_bitrate: 1_000_000,
             data_bitrate: 5_000_000,
+            addressing: Addressing::Native,
         }
     }
 }
@@ -118,15 +132,523 @@ pub enum CanError {
 
     /// Frame too large for CAN-FD
     FrameTooLarge,
+
+    /// Device ID does not fit the 8-bit address fields of the arbitration ID
+    AddressOutOfRange,
+
+    /// A fragment arrived out of order or a transfer was interrupted
+    FragmentLost,
+
+    /// Message has no CANopen COB-ID (a broadcast, or neither end is the host)
+    NotMapped,
+
+    /// A multi-frame transfer failed its CRC check
+    CrcMismatch,
+}
+
+// ============================================================================
+// Arbitration ID Scheme
+// ============================================================================
+
+const PRIORITY_SHIFT: u32 = 26;
+const TARGET_SHIFT: u32 = 18;
+const SOURCE_SHIFT: u32 = 10;
+const PRIORITY_MASK: u32 = 0x07;
+const ADDRESS_MASK: u32 = 0xFF;
+
+/// Routing information carried in a 29-bit extended CAN arbitration ID
+///
+/// ```text
+///  28     26 25       18 17       10 9          0
+/// +---------+-----------+-----------+------------+
+/// | priority|  target   |  source   |  reserved  |
+/// +---------+-----------+-----------+------------+
+/// ```
+///
+/// Source and target travel in the identifier, so the frame data only holds
+/// the `msg_id` and the payload. Lower priority values win bus arbitration.
+/// The reserved bits are zero for single-frame messages; classic CAN nodes
+/// use them to number fragments (see `transport::bxcan`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CanId {
+    /// Arbitration priority (0 = most urgent, 7 = least)
+    pub priority: u8,
+    /// Target device ID (0x00 for broadcast)
+    pub target_id: DeviceId,
+    /// Source device ID
+    pub source_id: DeviceId,
+}
+
+/// cbindgen:ignore
+impl CanId {
+    /// Emergency commands and safety signals (`Reset`, `InterlockState`, `SafetyEnable`, `FaultReport`),
+    /// and `Sync`, whose timing the joints' motion depends on
+    pub const PRIORITY_EMERGENCY: u8 = 0;
+    /// Lifecycle commands
+    pub const PRIORITY_LIFECYCLE: u8 = 1;
+    /// Motion setpoints
+    pub const PRIORITY_SETPOINT: u8 = 2;
+    /// Acknowledgements and status replies
+    pub const PRIORITY_RESPONSE: u8 = 3;
+    /// Configuration commands
+    pub const PRIORITY_CONFIG: u8 = 4;
+    /// Periodic telemetry
+    pub const PRIORITY_TELEMETRY: u8 = 5;
+    /// Discovery and diagnostics
+    pub const PRIORITY_DIAGNOSTIC: u8 = 7;
+
+    /// Largest device ID that can be addressed on the bus
+    pub const MAX_ADDRESS: DeviceId = ADDRESS_MASK as DeviceId;
+
+    /// Build the arbitration ID for a message
+    ///
+    /// Returns `CanError::AddressOutOfRange` if either device ID exceeds
+    /// [`CanId::MAX_ADDRESS`].
+    pub fn for_message(message: &Message) -> Result<Self, CanError> {
+        let header = &message.header;
+        if header.source_id > Self::MAX_ADDRESS || header.target_id > Self::MAX_ADDRESS {
+            return Err(CanError::AddressOutOfRange);
+        }
+
+        Ok(Self {
+            priority: Self::priority_of(&message.payload),
+            target_id: header.target_id,
+            source_id: header.source_id,
+        })
+    }
+
+    /// Arbitration priority class of a payload
+    pub const fn priority_of(payload: &Payload) -> u8 {
+        match payload {
+            Payload::Reset
+            | Payload::InterlockState(_)
+            | Payload::EngageBrake
+            | Payload::SafetyEnable { .. }
+            | Payload::FaultReport(_)
+            | Payload::Sync { .. } => Self::PRIORITY_EMERGENCY,
+            Payload::ArmReady
+            | Payload::Activate
+            | Payload::Deactivate
+            | Payload::StopCalibration
+            | Payload::EnterBootloader
+            | Payload::ReleaseBrake => Self::PRIORITY_LIFECYCLE,
+            Payload::SetTarget(_)
+            | Payload::SetTargetV2(_)
+            | Payload::SetTargetBatch(_)
+            | Payload::Home
+            | Payload::StartHoming(_)
+            | Payload::SetDigitalOutput(_)
+            | Payload::SetFeedforward { .. }
+            | Payload::SetVelocity { .. }
+            | Payload::SetTorque { .. } => Self::PRIORITY_SETPOINT,
+            Payload::Ack(_) | Payload::Nack { .. } | Payload::StreamAck { .. } | Payload::JointStatus { .. } => {
+                Self::PRIORITY_RESPONSE
+            }
+            Payload::Configure
+            | Payload::ConfigureTelemetry(_)
+            | Payload::ConfigureAdaptive(_)
+            | Payload::ConfigureThermal(_)
+            | Payload::ConfigurePower(_)
+            | Payload::StartCalibration(_)
+            | Payload::SetLimits(_)
+            | Payload::SaveConfig
+            | Payload::LoadConfig
+            | Payload::FactoryReset
+            | Payload::AssignId(_)
+            | Payload::SetEncoderOffset(_)
+            | Payload::ConfigureGains(_)
+            | Payload::SetGainScheduleEntry(_)
+            | Payload::ClearGainSchedule
+            | Payload::SetControlMode(_) => Self::PRIORITY_CONFIG,
+            Payload::Encoder(_)
+            | Payload::TelemetryStream(_)
+            | Payload::RequestTelemetry
+            | Payload::AdaptiveStatus(_)
+            | Payload::RequestAdaptiveStatus
+            | Payload::CalibrationStatus(_)
+            | Payload::CalibrationResult(_)
+            | Payload::ReadDigitalInput(_)
+            | Payload::DigitalInput(_)
+            | Payload::ReadAnalogInput(_)
+            | Payload::AnalogInput(_)
+            | Payload::RequestMultiTurnPosition
+            | Payload::MultiTurnPosition(_)
+            | Payload::RequestPowerStatus
+            | Payload::PowerStatus(_)
+            | Payload::SyncSample(_) => Self::PRIORITY_TELEMETRY,
+            Payload::Discover
+            | Payload::Hello(_)
+            | Payload::Boot(_)
+            | Payload::RequestBusStats
+            | Payload::BusStats(_)
+            | Payload::RequestStatus
+            | Payload::RequestBootInfo
+            | Payload::BootInfo(_)
+            | Payload::RequestFlightRecorder { .. }
+            | Payload::FlightRecorder(_)
+            | Payload::RequestStatistics
+            | Payload::Statistics(_)
+            | Payload::RunSelfTest(_)
+            | Payload::SelfTestResult(_) => Self::PRIORITY_DIAGNOSTIC,
+        }
+    }
+
+    /// Pack into a raw 29-bit extended identifier
+    pub const fn to_raw(&self) -> u32 {
+        ((self.priority as u32 & PRIORITY_MASK) << PRIORITY_SHIFT)
+            | ((self.target_id as u32 & ADDRESS_MASK) << TARGET_SHIFT)
+            | ((self.source_id as u32 & ADDRESS_MASK) << SOURCE_SHIFT)
+    }
+
+    /// Unpack a raw 29-bit extended identifier
+    pub const fn from_raw(raw: u32) -> Self {
+        Self {
+            priority: ((raw >> PRIORITY_SHIFT) & PRIORITY_MASK) as u8,
+            target_id: ((raw >> TARGET_SHIFT) & ADDRESS_MASK) as DeviceId,
+            source_id: ((raw >> SOURCE_SHIFT) & ADDRESS_MASK) as DeviceId,
+        }
+    }
+}
+
+/// Encode a message into a CAN-FD frame
+///
+/// Writes `msg_id` and the payload into `buf` (at most one frame's worth) and
+/// returns the raw arbitration ID together with the number of data bytes used.
+pub fn encode_frame(message: &Message, buf: &mut [u8]) -> Result<(u32, usize), CanError> {
+    let limit = buf.len().min(MAX_FDCAN_PAYLOAD);
+    encode_body(message, &mut buf[..limit])
+}
+
+/// Encode a message body without the single-frame size limit
+///
+/// Same layout as [`encode_frame`], for transports that split the body
+/// across several frames.
+pub fn encode_body(message: &Message, buf: &mut [u8]) -> Result<(u32, usize), CanError> {
+    let id = CanId::for_message(message)?;
+
+    let used = postcard::to_slice(&(message.header.msg_id, &message.payload), buf)
+        .map_err(|_| CanError::FrameTooLarge)?;
+
+    Ok((id.to_raw(), used.len()))
+}
+
+/// Decode a CAN-FD frame back into a message
+///
+/// The header is rebuilt from the arbitration ID; the frame data holds the
+/// `msg_id` and the payload.
+pub fn decode_frame(raw_id: u32, data: &[u8]) -> Result<Message, CanError> {
+    let id = CanId::from_raw(raw_id);
+    let (msg_id, payload): (MessageId, Payload) =
+        postcard::from_bytes(data).map_err(|_| CanError::DeserializationError)?;
+
+    Ok(Message {
+        header: Header {
+            source_id: id.source_id,
+            target_id: id.target_id,
+            msg_id,
+        },
+        payload,
+    })
+}
+
+// ============================================================================
+// CANopen Addressing
+// ============================================================================
+
+/// How messages are mapped onto CAN identifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Addressing {
+    /// iRPC's own 29-bit extended IDs (see [`CanId`])
+    #[default]
+    Native,
+
+    /// CANopen-style 11-bit COB-IDs (see [`CobId`])
+    ///
+    /// For buses shared with off-the-shelf CANopen devices: each joint only
+    /// uses the COB-IDs of its own node ID, so it coexists with CANopen nodes
+    /// numbered differently. The arm controller `host_id` plays the CANopen
+    /// master and has no COB-IDs of its own; broadcasts and joint-to-joint
+    /// messages cannot be sent.
+    CanOpen {
+        /// Device ID of the arm controller
+        host_id: DeviceId,
+    },
+}
+
+/// Identifier of a CAN frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameId {
+    /// 11-bit standard identifier
+    Standard(u16),
+    /// 29-bit extended identifier
+    Extended(u32),
+}
+
+impl Addressing {
+    /// Encode a message into a CAN-FD frame using this addressing
+    ///
+    /// Same as [`encode_frame`] or [`encode_canopen_frame`].
+    pub fn encode_frame(&self, message: &Message, buf: &mut [u8]) -> Result<(FrameId, usize), CanError> {
+        match *self {
+            Addressing::Native => {
+                encode_frame(message, buf).map(|(raw_id, len)| (FrameId::Extended(raw_id), len))
+            }
+            Addressing::CanOpen { host_id } => encode_canopen_frame(message, host_id, buf)
+                .map(|(cob_id, len)| (FrameId::Standard(cob_id), len)),
+        }
+    }
+
+    /// Decode a CAN-FD frame using this addressing
+    ///
+    /// Frames of the other identifier kind are not iRPC traffic and fail with
+    /// `CanError::DeserializationError`.
+    pub fn decode_frame(&self, id: FrameId, data: &[u8]) -> Result<Message, CanError> {
+        match (*self, id) {
+            (Addressing::Native, FrameId::Extended(raw_id)) => decode_frame(raw_id, data),
+            (Addressing::CanOpen { host_id }, FrameId::Standard(cob_id)) => {
+                decode_canopen_frame(cob_id, host_id, data)
+            }
+            _ => Err(CanError::DeserializationError),
+        }
+    }
+}
+
+/// CANopen function code, the upper four bits of a COB-ID
+///
+/// Only the node-specific codes are used. NMT (`0x000`), SYNC and TIME are
+/// broadcasts every CANopen device acts on, so lifecycle commands travel on
+/// the joint's first receive PDO instead, and heartbeats are left to real
+/// CANopen nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum CobFunction {
+    /// `0x080 + node`: emergency-class messages from the joint
+    Emergency = 0x1,
+    /// `0x180 + node`: telemetry from the joint
+    Tpdo1 = 0x3,
+    /// `0x200 + node`: lifecycle and emergency commands to the joint (the
+    /// NMT equivalent)
+    Rpdo1 = 0x4,
+    /// `0x300 + node`: motion setpoints to the joint
+    Rpdo2 = 0x6,
+    /// `0x580 + node`: replies and diagnostics from the joint (SDO response)
+    SdoTx = 0xB,
+    /// `0x600 + node`: configuration and queries to the joint (SDO request)
+    SdoRx = 0xC,
+}
+
+impl CobFunction {
+    /// Whether frames with this function code are sent by the host to the node
+    pub const fn is_to_node(self) -> bool {
+        matches!(self, CobFunction::Rpdo1 | CobFunction::Rpdo2 | CobFunction::SdoRx)
+    }
+
+    const fn from_code(code: u16) -> Option<Self> {
+        Some(match code {
+            0x1 => CobFunction::Emergency,
+            0x3 => CobFunction::Tpdo1,
+            0x4 => CobFunction::Rpdo1,
+            0x6 => CobFunction::Rpdo2,
+            0xB => CobFunction::SdoTx,
+            0xC => CobFunction::SdoRx,
+            _ => return None,
+        })
+    }
+}
+
+/// CANopen communication object ID (11-bit standard identifier)
+///
+/// ```text
+///  10      7 6          0
+/// +---------+------------+
+/// | function|  node ID   |
+/// +---------+------------+
+/// ```
+///
+/// The node is always the joint; which end sent the frame follows from the
+/// function code. Priorities follow CANopen: lower COB-IDs win arbitration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CobId {
+    /// Function code
+    pub function: CobFunction,
+    /// CANopen node ID of the joint (1-127)
+    pub node_id: DeviceId,
+}
+
+/// cbindgen:ignore
+impl CobId {
+    /// Largest CANopen node ID
+    pub const MAX_NODE_ID: DeviceId = 127;
+
+    /// Build the COB-ID for a message between `host_id` and a joint
+    ///
+    /// Returns `CanError::NotMapped` for broadcasts and messages the host is
+    /// not part of, and `CanError::AddressOutOfRange` if the joint's device ID
+    /// is not a valid node ID.
+    pub fn for_message(message: &Message, host_id: DeviceId) -> Result<Self, CanError> {
+        let header = &message.header;
+        let priority = CanId::priority_of(&message.payload);
+        let (function, node_id) = if header.source_id == host_id {
+            let function = match priority {
+                CanId::PRIORITY_EMERGENCY | CanId::PRIORITY_LIFECYCLE => CobFunction::Rpdo1,
+                CanId::PRIORITY_SETPOINT => CobFunction::Rpdo2,
+                _ => CobFunction::SdoRx,
+            };
+            (function, header.target_id)
+        } else if header.target_id == host_id {
+            let function = match priority {
+                CanId::PRIORITY_EMERGENCY => CobFunction::Emergency,
+                CanId::PRIORITY_TELEMETRY => CobFunction::Tpdo1,
+                _ => CobFunction::SdoTx,
+            };
+            (function, header.source_id)
+        } else {
+            return Err(CanError::NotMapped);
+        };
+
+        match node_id {
+            0 => Err(CanError::NotMapped),
+            1..=Self::MAX_NODE_ID => Ok(Self { function, node_id }),
+            _ => Err(CanError::AddressOutOfRange),
+        }
+    }
+
+    /// Pack into a raw 11-bit standard identifier
+    pub const fn to_raw(&self) -> u16 {
+        ((self.function as u16) << 7) | (self.node_id & Self::MAX_NODE_ID)
+    }
+
+    /// Unpack a raw 11-bit standard identifier
+    ///
+    /// `None` for COB-IDs iRPC does not use (NMT, SYNC, heartbeats, other
+    /// PDOs) and for node ID 0.
+    pub const fn from_raw(raw: u16) -> Option<Self> {
+        let node_id = raw & Self::MAX_NODE_ID;
+        match CobFunction::from_code((raw >> 7) & 0xF) {
+            Some(function) if node_id != 0 => Some(Self { function, node_id }),
+            _ => None,
+        }
+    }
+}
+
+/// Encode a message into a CAN-FD frame with a CANopen COB-ID
+///
+/// Frame data is the same as with [`encode_frame`]; only the identifier
+/// differs. Returns the raw COB-ID and the number of data bytes used.
+pub fn encode_canopen_frame(
+    message: &Message,
+    host_id: DeviceId,
+    buf: &mut [u8],
+) -> Result<(u16, usize), CanError> {
+    let id = CobId::for_message(message, host_id)?;
+    let limit = buf.len().min(MAX_FDCAN_PAYLOAD);
+
+    let used = postcard::to_slice(&(message.header.msg_id, &message.payload), &mut buf[..limit])
+        .map_err(|_| CanError::FrameTooLarge)?;
+
+    Ok((id.to_raw(), used.len()))
+}
+
+/// Decode a CAN-FD frame carrying a CANopen COB-ID back into a message
+///
+/// The header is rebuilt from the COB-ID and `host_id`. Frames on COB-IDs
+/// iRPC does not use fail with `CanError::DeserializationError`.
+pub fn decode_canopen_frame(cob_id: u16, host_id: DeviceId, data: &[u8]) -> Result<Message, CanError> {
+    let id = CobId::from_raw(cob_id).ok_or(CanError::DeserializationError)?;
+    let (msg_id, payload): (MessageId, Payload) =
+        postcard::from_bytes(data).map_err(|_| CanError::DeserializationError)?;
+
+    let (source_id, target_id) = if id.function.is_to_node() {
+        (host_id, id.node_id)
+    } else {
+        (id.node_id, host_id)
+    };
+    Ok(Message {
+        header: Header { source_id, target_id, msg_id },
+        payload,
+    })
 }
 
 // ============================================================================
 // STM32G4/F4 Implementation
 // ============================================================================
 
+// ============================================================================
+// TX mailbox
+// ============================================================================
+
+/// A CAN-FD TX mailbox that makes room for a frame by pushing out a queued
+/// frame of lower priority
+///
+/// Implemented for embassy-stm32's `Can` and `CanTx` with the `stm32g4`
+/// feature.
+#[allow(async_fn_in_trait)]
+pub trait TxMailbox {
+    /// Frame the mailbox holds
+    type Frame;
+
+    /// Queue `frame`, waiting for room unless a queued frame of lower
+    /// priority can be pushed out instead; returns the frame pushed out,
+    /// which the bus never sends
+    async fn write(&mut self, frame: &Self::Frame) -> Option<Self::Frame>;
+}
+
+/// Queue `frame`, then queue again every frame that pushes out, so none is
+/// lost
+///
+/// Each frame queued again counts in `stats.retransmissions`.
+pub async fn queue_frame<M: TxMailbox>(mailbox: &mut M, frame: M::Frame, stats: &mut TransportStats) {
+    let mut frame = frame;
+    while let Some(displaced) = mailbox.write(&frame).await {
+        stats.retransmissions = stats.retransmissions.wrapping_add(1);
+        frame = displaced;
+    }
+}
+
 #[cfg(feature = "stm32g4")]
 use embassy_stm32::can::{Can, Instance};
 
+#[cfg(feature = "stm32g4")]
+impl TxMailbox for Can<'_> {
+    type Frame = embassy_stm32::can::frame::FdFrame;
+
+    async fn write(&mut self, frame: &Self::Frame) -> Option<Self::Frame> {
+        self.write_fd(frame).await
+    }
+}
+
+#[cfg(feature = "stm32g4")]
+impl TxMailbox for embassy_stm32::can::CanTx<'_> {
+    type Frame = embassy_stm32::can::frame::FdFrame;
+
+    async fn write(&mut self, frame: &Self::Frame) -> Option<Self::Frame> {
+        self.write_fd(frame).await
+    }
+}
+
+#[cfg(feature = "stm32g4")]
+use crate::bus::AsyncEmbeddedTransport;
+
+#[cfg(feature = "stm32g4")]
+use crate::transport::rx_queue::RxQueue;
+
+/// Build an FDCAN frame with the given identifier
+#[cfg(feature = "stm32g4")]
+fn fd_frame(id: FrameId, data: &[u8]) -> Result<embassy_stm32::can::frame::FdFrame, CanError> {
+    use embassy_stm32::can::frame::FdFrame;
+
+    match id {
+        FrameId::Standard(raw_id) => FdFrame::new_standard(raw_id, data),
+        FrameId::Extended(raw_id) => FdFrame::new_extended(raw_id, data),
+    }
+    .map_err(|_| CanError::InvalidConfig)
+}
+
 /// CAN-FD transport for STM32G4 microcontrollers
 ///
 /// This transport handles all FDCAN hardware configuration and provides
@@ -135,8 +657,12 @@ use embassy_stm32::can::{Can, Instance};
 pub struct CanFdTransport<'d> {
     can: Can<'d>,
     node_id: DeviceId,
-    rx_buffer: [u8; MAX_FDCAN_PAYLOAD],
+    addressing: Addressing,
+    /// Holds received frame data, and the re-encoded message for
+    /// [`AsyncEmbeddedTransport::receive`]
+    rx_buffer: [u8; Message::max_size()],
     tx_buffer: [u8; MAX_FDCAN_PAYLOAD],
+    stats: TransportStats,
 }
 
 #[cfg(feature = "stm32g4")]
@@ -145,7 +671,7 @@ impl<'d> CanFdTransport<'d> {
     ///
     /// This function:
     /// - Configures FDCAN peripheral with specified bitrates
-    /// - Sets up standard ID filters for the node
+    /// - Sets up extended ID filters (see [`CanId`])
     /// - Initializes TX/RX FIFOs
     /// - Enables CAN-FD mode
     ///
@@ -187,10 +713,16 @@ impl<'d> CanFdTransport<'d> {
 
         // Configure filters to accept messages for this node
         // Accept all messages into FIFO0 for now (we'll filter by ID in software)
-        can_config.properties().set_extended_filter(
-            can::filter::ExtendedFilterSlot::_0,
-            can::filter::ExtendedFilter::accept_all_into_fifo0(),
-        );
+        match config.addressing {
+            Addressing::Native => can_config.properties().set_extended_filter(
+                can::filter::ExtendedFilterSlot::_0,
+                can::filter::ExtendedFilter::accept_all_into_fifo0(),
+            ),
+            Addressing::CanOpen { .. } => can_config.properties().set_standard_filter(
+                can::filter::StandardFilterSlot::_0,
+                can::filter::StandardFilter::accept_all_into_fifo0(),
+            ),
+        }
 
         // Start in normal operation mode
         let can = can_config.start(can::OperatingMode::NormalOperationMode);
@@ -198,34 +730,34 @@ impl<'d> CanFdTransport<'d> {
         Ok(Self {
             can,
             node_id: config.node_id,
-            rx_buffer: [0u8; MAX_FDCAN_PAYLOAD],
+            addressing: config.addressing,
+            rx_buffer: [0u8; Message::max_size()],
             tx_buffer: [0u8; MAX_FDCAN_PAYLOAD],
+            stats: TransportStats::with_mtu(MAX_FDCAN_PAYLOAD),
         })
     }
 
     /// Send a message over CAN-FD
     ///
-    /// Automatically serializes the message and transmits over CAN-FD.
+    /// Automatically serializes the message directly into the TX buffer
+    /// (no heap allocation) and transmits over CAN-FD.
     pub async fn send_message(&mut self, message: &Message) -> Result<(), CanError> {
-        // Serialize message
-        let data = message.serialize()
-            .map_err(|_| CanError::SerializationError)?;
-
-        if data.len() > MAX_FDCAN_PAYLOAD {
-            return Err(CanError::FrameTooLarge);
-        }
-
-        // Copy to TX buffer
-        self.tx_buffer[..data.len()].copy_from_slice(&data);
-
-        // Create CAN-FD frame with standard ID
-        use embassy_stm32::can::frame::FdFrame;
-
-        let frame = FdFrame::new_standard(self.node_id, &self.tx_buffer[..data.len()])
-            .map_err(|_| CanError::InvalidConfig)?;
-
-        // Transmit (async)
-        self.can.write_fd(&frame).await;
+        // Routing goes into the arbitration ID, msg_id and payload into the
+        // TX buffer; failure means a bad address or an oversized payload
+        let (id, len) = match self.addressing.encode_frame(message, &mut self.tx_buffer) {
+            Ok(frame) => frame,
+            Err(e) => {
+                self.stats.record_error(TransportErrorKind::Serialization);
+                return Err(e);
+            }
+        };
+
+        let frame = fd_frame(id, &self.tx_buffer[..len])?;
+
+        // Transmit (async). A lower-priority frame this one pushes out of the
+        // TX mailbox is never sent by the bus, so it is queued again.
+        queue_frame(&mut self.can, frame, &mut self.stats).await;
+        self.stats.tx_frames = self.stats.tx_frames.wrapping_add(1);
 
         Ok(())
     }
@@ -234,23 +766,55 @@ impl<'d> CanFdTransport<'d> {
     ///
     /// Waits for a message to be received.
     pub async fn receive_message(&mut self) -> Result<Message, CanError> {
+        use embassy_stm32::can::enums::BusError;
+        use embassy_stm32::can::Id;
+
         // Receive a frame (async)
-        let envelope = self.can.read_fd().await
-            .map_err(|_| CanError::RxFailed)?;
+        let envelope = match self.can.read_fd().await {
+            Ok(envelope) => envelope,
+            Err(e) => {
+                self.stats.record_error(match e {
+                    BusError::Crc => TransportErrorKind::Crc,
+                    BusError::BusOff => TransportErrorKind::BusOff,
+                    _ => TransportErrorKind::Transport,
+                });
+                return Err(CanError::RxFailed);
+            }
+        };
+        self.stats.rx_frames = self.stats.rx_frames.wrapping_add(1);
 
         let rx_frame = envelope.frame;
         let len = rx_frame.header().len() as usize;
 
         if len > MAX_FDCAN_PAYLOAD {
+            self.stats.record_error(TransportErrorKind::FrameTooLarge);
             return Err(CanError::FrameTooLarge);
         }
 
         // Copy data to RX buffer
         self.rx_buffer[..len].copy_from_slice(&rx_frame.data()[..len]);
 
-        // Deserialize
-        Message::deserialize(&self.rx_buffer[..len])
-            .map_err(|_| CanError::DeserializationError)
+        let id = match rx_frame.header().id() {
+            Id::Extended(id) => FrameId::Extended(id.as_raw()),
+            Id::Standard(id) => FrameId::Standard(id.as_raw()),
+        };
+
+        // Rebuild the header from the arbitration ID and deserialize the rest;
+        // frames of the ID kind the addressing does not use are rejected
+        self.addressing.decode_frame(id, &self.rx_buffer[..len]).map_err(|e| {
+            self.stats.record_error(TransportErrorKind::Deserialization);
+            e
+        })
+    }
+
+    /// Get the transport statistics collected so far
+    pub fn stats(&self) -> &TransportStats {
+        &self.stats
+    }
+
+    /// Reset all statistics to zero
+    pub fn reset_stats(&mut self) {
+        self.stats = TransportStats::with_mtu(MAX_FDCAN_PAYLOAD);
     }
 
     /// Check if transport is ready
@@ -266,6 +830,171 @@ impl<'d> CanFdTransport<'d> {
     }
 }
 
+/// Lets the transport be wrapped in an [`AsyncTransportLayer`](crate::AsyncTransportLayer)
+/// and driven by [`Joint::process_transport_async`](crate::Joint::process_transport_async)
+#[cfg(feature = "stm32g4")]
+impl<'d> AsyncEmbeddedTransport for CanFdTransport<'d> {
+    type Error = CanError;
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let message = Message::deserialize(data).map_err(|_| CanError::DeserializationError)?;
+        self.send_message(&message).await
+    }
+
+    async fn receive(&mut self) -> Result<&[u8], Self::Error> {
+        let message = self.receive_message().await?;
+        let len = message
+            .serialize_into(&mut self.rx_buffer)
+            .map_err(|_| CanError::SerializationError)?;
+        Ok(&self.rx_buffer[..len])
+    }
+
+    fn mtu(&self) -> usize {
+        MAX_FDCAN_PAYLOAD
+    }
+}
+
+#[cfg(feature = "stm32g4")]
+impl<'d> CanFdTransport<'d> {
+    /// Switch to queued reception
+    ///
+    /// Splits the transport into a sending half that drains `queue` without
+    /// waiting and a [`CanFdRxPump`] that fills it. Run the pump on an
+    /// interrupt-priority executor (e.g. embassy's `InterruptExecutor`) so
+    /// frames are moved out of the FDCAN FIFO as soon as its interrupt fires,
+    /// while the main loop picks up whole bursts per iteration.
+    pub fn into_queued<const N: usize>(
+        self,
+        queue: &'static RxQueue<N>,
+    ) -> (QueuedCanFdTransport<'d, N>, CanFdRxPump<'d, N>) {
+        let (tx, rx, _) = self.can.split();
+        (
+            QueuedCanFdTransport {
+                tx,
+                queue,
+                node_id: self.node_id,
+                addressing: self.addressing,
+                tx_buffer: self.tx_buffer,
+                stats: self.stats,
+            },
+            CanFdRxPump { rx, queue, addressing: self.addressing },
+        )
+    }
+}
+
+/// Receive half of a queued CAN-FD transport; see [`CanFdTransport::into_queued`]
+#[cfg(feature = "stm32g4")]
+pub struct CanFdRxPump<'d, const N: usize> {
+    rx: embassy_stm32::can::CanRx<'d>,
+    queue: &'static RxQueue<N>,
+    addressing: Addressing,
+}
+
+#[cfg(feature = "stm32g4")]
+impl<'d, const N: usize> CanFdRxPump<'d, N> {
+    /// Move received frames into the queue, forever
+    ///
+    /// Frames of the identifier kind the addressing does not use (standard
+    /// IDs with native addressing, extended IDs with CANopen addressing) are
+    /// discarded here.
+    pub async fn run(mut self) -> ! {
+        use embassy_stm32::can::enums::BusError;
+        use embassy_stm32::can::Id;
+
+        loop {
+            match self.rx.read_fd().await {
+                Ok(envelope) => {
+                    let frame = envelope.frame;
+                    let raw_id = match (self.addressing, frame.header().id()) {
+                        (Addressing::Native, Id::Extended(id)) => id.as_raw(),
+                        (Addressing::CanOpen { .. }, Id::Standard(id)) => id.as_raw() as u32,
+                        _ => continue,
+                    };
+                    let len = frame.header().len() as usize;
+                    self.queue.push(raw_id, &frame.data()[..len.min(MAX_FDCAN_PAYLOAD)]);
+                }
+                Err(e) => self.queue.record_error(match e {
+                    BusError::Crc => TransportErrorKind::Crc,
+                    BusError::BusOff => TransportErrorKind::BusOff,
+                    _ => TransportErrorKind::Transport,
+                }),
+            }
+        }
+    }
+}
+
+/// CAN-FD transport that receives from an [`RxQueue`]
+#[cfg(feature = "stm32g4")]
+pub struct QueuedCanFdTransport<'d, const N: usize> {
+    tx: embassy_stm32::can::CanTx<'d>,
+    queue: &'static RxQueue<N>,
+    node_id: DeviceId,
+    addressing: Addressing,
+    tx_buffer: [u8; MAX_FDCAN_PAYLOAD],
+    stats: TransportStats,
+}
+
+#[cfg(feature = "stm32g4")]
+impl<'d, const N: usize> QueuedCanFdTransport<'d, N> {
+    /// Send a message over CAN-FD
+    pub async fn send_message(&mut self, message: &Message) -> Result<(), CanError> {
+        let (id, len) = match self.addressing.encode_frame(message, &mut self.tx_buffer) {
+            Ok(frame) => frame,
+            Err(e) => {
+                self.stats.record_error(TransportErrorKind::Serialization);
+                return Err(e);
+            }
+        };
+        let frame = fd_frame(id, &self.tx_buffer[..len])?;
+
+        queue_frame(&mut self.tx, frame, &mut self.stats).await;
+        self.stats.tx_frames = self.stats.tx_frames.wrapping_add(1);
+        Ok(())
+    }
+
+    /// Take the next queued message without waiting
+    ///
+    /// Returns `Ok(None)` once the queue is empty; call in a loop to drain
+    /// a burst. A frame that fails to decode is consumed and reported.
+    pub fn receive_message(&mut self) -> Result<Option<Message>, CanError> {
+        let Some(frame) = self.queue.pop() else {
+            return Ok(None);
+        };
+        // The pump only queues IDs of the kind the addressing uses
+        let id = match self.addressing {
+            Addressing::Native => FrameId::Extended(frame.raw_id()),
+            Addressing::CanOpen { .. } => FrameId::Standard(frame.raw_id() as u16),
+        };
+        self.addressing.decode_frame(id, frame.data()).map(Some).map_err(|e| {
+            self.stats.record_error(TransportErrorKind::Deserialization);
+            e
+        })
+    }
+
+    /// Statistics of both halves, including RX queue overflows
+    pub fn stats(&self) -> TransportStats {
+        let mut stats = self.stats;
+        stats.merge(&self.queue.stats());
+        stats
+    }
+
+    /// Reset all statistics to zero
+    pub fn reset_stats(&mut self) {
+        self.stats = TransportStats::with_mtu(MAX_FDCAN_PAYLOAD);
+        self.queue.reset_stats();
+    }
+
+    /// The queue this transport drains
+    pub fn queue(&self) -> &'static RxQueue<N> {
+        self.queue
+    }
+
+    /// Get node ID
+    pub fn node_id(&self) -> DeviceId {
+        self.node_id
+    }
+}
+
 // ============================================================================
 // Compatibility layer for custom implementations
 // ============================================================================
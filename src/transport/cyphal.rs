@@ -0,0 +1,548 @@
+//! Cyphal/CAN transfer framing
+//!
+//! An alternative to the native frame format (see [`CanId`]) for buses shared
+//! with Cyphal nodes. iRPC messages travel as Cyphal/CAN v1 transfers, so
+//! Cyphal nodes ignore them cleanly and bus analyzers (Yukon, `yakut monitor`,
+//! the Cyphal dissector in Wireshark) can show them as regular transfers.
+//!
+//! # Mapping
+//!
+//! - Addressed messages become service transfers on
+//!   [`CyphalPorts::service_id`] from the source to the destination node.
+//!   Replies (`Ack`, `Nack`, `JointStatus`) are responses, everything else
+//!   a request; request and reply are still paired by `msg_id`, not by
+//!   transfer-ID.
+//! - Broadcasts (`target_id` 0) become message transfers on
+//!   [`CyphalPorts::subject_id`].
+//! - The iRPC priority class (see [`CanId::priority_of`]) is the Cyphal
+//!   priority; both put the most urgent at 0.
+//! - The transfer payload is `msg_id` and the payload, as in a native frame.
+//!   Device IDs must be valid Cyphal node IDs (up to 127).
+//!
+//! # Framing
+//!
+//! Every frame ends with a tail byte (start/end of transfer, toggle bit,
+//! 5-bit transfer-ID). Transfers that do not fit one frame are split and
+//! carry a CRC-16/CCITT-FALSE over the payload in their last bytes. CAN-FD
+//! frames are padded with zeros to the next valid data length.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use irpc::transport::cyphal::{CyphalEncoder, CyphalPorts, CyphalReassembler};
+//!
+//! let mut encoder = CyphalEncoder::new(CyphalPorts::default());
+//! for frame in encoder.encode(&reply, CANFD_MAX_DATA_LEN)? {
+//!     can.send_extended(frame.raw_id(), frame.data());
+//! }
+//!
+//! let mut reassembler = CyphalReassembler::new(CyphalPorts::default());
+//! if let Some(message) = reassembler.push(raw_id, data)? {
+//!     joint.handle_message(&message);
+//! }
+//! ```
+
+use crate::config::{
+    CANFD_MAX_DATA_LEN, CLASSIC_CAN_MAX_DATA_LEN, CYPHAL_SERVICE_ID, CYPHAL_SUBJECT_ID,
+    FRAGMENT_REASSEMBLY_SLOTS,
+};
+use crate::protocol::{DeviceId, Header, Message, MessageId, Payload};
+use crate::transport::canfd::{CanError, CanId};
+
+const PRIORITY_SHIFT: u32 = 26;
+const SERVICE_BIT: u32 = 1 << 25;
+const ANONYMOUS_BIT: u32 = 1 << 24;
+const REQUEST_BIT: u32 = 1 << 24;
+// Reserved bits 21-22 of message transfers are sent as ones
+const MESSAGE_RESERVED: u32 = 0b11 << 21;
+const SUBJECT_SHIFT: u32 = 8;
+const SERVICE_SHIFT: u32 = 14;
+const DESTINATION_SHIFT: u32 = 7;
+const NODE_MASK: u32 = 0x7F;
+
+const START_BIT: u8 = 1 << 7;
+const END_BIT: u8 = 1 << 6;
+const TOGGLE_BIT: u8 = 1 << 5;
+const TRANSFER_ID_MASK: u8 = 0x1F;
+
+const CRC_LEN: usize = 2;
+
+// Largest transfer payload: a message body plus CAN-FD padding and the CRC
+const MAX_TRANSFER_LEN: usize = Message::max_size() + CANFD_MAX_DATA_LEN + CRC_LEN;
+
+// ============================================================================
+// Identifiers
+// ============================================================================
+
+/// Port IDs the iRPC transfers use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CyphalPorts {
+    /// Subject-ID for broadcasts (0-8191)
+    pub subject_id: u16,
+    /// Service-ID for addressed messages (0-511)
+    pub service_id: u16,
+}
+
+impl Default for CyphalPorts {
+    fn default() -> Self {
+        Self {
+            subject_id: CYPHAL_SUBJECT_ID,
+            service_id: CYPHAL_SERVICE_ID,
+        }
+    }
+}
+
+/// Kind of a Cyphal transfer and its port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransferKind {
+    /// Message transfer (broadcast on a subject)
+    Message { subject_id: u16 },
+    /// Service request to `destination`
+    Request { service_id: u16, destination: u8 },
+    /// Service response to `destination`
+    Response { service_id: u16, destination: u8 },
+}
+
+/// Cyphal/CAN 29-bit extended identifier
+///
+/// ```text
+/// message:  28  26 25 24 23 22 21 20          8 7 6      0
+///          +------+--+--+--+-----+-------------+-+--------+
+///          | prio | 0|an| 0| 1 1 | subject-ID  |0| source |
+///          +------+--+--+--+-----+-------------+-+--------+
+/// service:  28  26 25 24 23 22       14 13      7 6      0
+///          +------+--+--+--+-----------+--------+--------+
+///          | prio | 1|rq| 0|service-ID | dest   | source |
+///          +------+--+--+--+-----------+--------+--------+
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CyphalId {
+    /// Transfer priority (0 = exceptional, 7 = optional)
+    pub priority: u8,
+    /// Transfer kind and port
+    pub kind: TransferKind,
+    /// Source node ID
+    pub source: u8,
+}
+
+/// cbindgen:ignore
+impl CyphalId {
+    /// Largest Cyphal node ID
+    pub const MAX_NODE_ID: DeviceId = NODE_MASK as DeviceId;
+    /// Largest subject-ID
+    pub const MAX_SUBJECT_ID: u16 = 8191;
+    /// Largest service-ID
+    pub const MAX_SERVICE_ID: u16 = 511;
+
+    /// Build the identifier for a message
+    ///
+    /// Returns `CanError::AddressOutOfRange` if a device ID is not a valid
+    /// node ID and `CanError::InvalidConfig` if a port ID is out of range.
+    pub fn for_message(message: &Message, ports: &CyphalPorts) -> Result<Self, CanError> {
+        let header = &message.header;
+        if header.source_id > Self::MAX_NODE_ID || header.target_id > Self::MAX_NODE_ID {
+            return Err(CanError::AddressOutOfRange);
+        }
+        if ports.subject_id > Self::MAX_SUBJECT_ID || ports.service_id > Self::MAX_SERVICE_ID {
+            return Err(CanError::InvalidConfig);
+        }
+
+        let priority = CanId::priority_of(&message.payload);
+        let destination = header.target_id as u8;
+        let kind = if header.target_id == crate::config::BROADCAST_ADDRESS {
+            TransferKind::Message { subject_id: ports.subject_id }
+        } else if priority == CanId::PRIORITY_RESPONSE {
+            TransferKind::Response { service_id: ports.service_id, destination }
+        } else {
+            TransferKind::Request { service_id: ports.service_id, destination }
+        };
+
+        Ok(Self { priority, kind, source: header.source_id as u8 })
+    }
+
+    /// Pack into a raw 29-bit extended identifier
+    pub const fn to_raw(&self) -> u32 {
+        let base = ((self.priority as u32 & 0x07) << PRIORITY_SHIFT) | (self.source as u32 & NODE_MASK);
+        match self.kind {
+            TransferKind::Message { subject_id } => {
+                base | MESSAGE_RESERVED | ((subject_id as u32 & Self::MAX_SUBJECT_ID as u32) << SUBJECT_SHIFT)
+            }
+            TransferKind::Request { service_id, destination } => {
+                base | SERVICE_BIT
+                    | REQUEST_BIT
+                    | ((service_id as u32 & Self::MAX_SERVICE_ID as u32) << SERVICE_SHIFT)
+                    | ((destination as u32 & NODE_MASK) << DESTINATION_SHIFT)
+            }
+            TransferKind::Response { service_id, destination } => {
+                base | SERVICE_BIT
+                    | ((service_id as u32 & Self::MAX_SERVICE_ID as u32) << SERVICE_SHIFT)
+                    | ((destination as u32 & NODE_MASK) << DESTINATION_SHIFT)
+            }
+        }
+    }
+
+    /// Unpack a raw 29-bit extended identifier
+    ///
+    /// `None` for anonymous transfers, which iRPC never sends.
+    pub const fn from_raw(raw: u32) -> Option<Self> {
+        let priority = ((raw >> PRIORITY_SHIFT) & 0x07) as u8;
+        let source = (raw & NODE_MASK) as u8;
+        let kind = if raw & SERVICE_BIT == 0 {
+            if raw & ANONYMOUS_BIT != 0 {
+                return None;
+            }
+            TransferKind::Message {
+                subject_id: ((raw >> SUBJECT_SHIFT) & Self::MAX_SUBJECT_ID as u32) as u16,
+            }
+        } else {
+            let service_id = ((raw >> SERVICE_SHIFT) & Self::MAX_SERVICE_ID as u32) as u16;
+            let destination = ((raw >> DESTINATION_SHIFT) & NODE_MASK) as u8;
+            if raw & REQUEST_BIT != 0 {
+                TransferKind::Request { service_id, destination }
+            } else {
+                TransferKind::Response { service_id, destination }
+            }
+        };
+        Some(Self { priority, kind, source })
+    }
+
+    /// Whether the transfer is on one of the iRPC ports
+    pub const fn is_irpc(&self, ports: &CyphalPorts) -> bool {
+        match self.kind {
+            TransferKind::Message { subject_id } => subject_id == ports.subject_id,
+            TransferKind::Request { service_id, .. } | TransferKind::Response { service_id, .. } => {
+                service_id == ports.service_id
+            }
+        }
+    }
+}
+
+/// Last byte of every Cyphal/CAN frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TailByte {
+    /// First frame of the transfer
+    pub start: bool,
+    /// Last frame of the transfer
+    pub end: bool,
+    /// Alternates between frames, starting at `true`
+    pub toggle: bool,
+    /// Transfer-ID (0-31)
+    pub transfer_id: u8,
+}
+
+impl TailByte {
+    /// Unpack a tail byte
+    pub const fn from_byte(byte: u8) -> Self {
+        Self {
+            start: byte & START_BIT != 0,
+            end: byte & END_BIT != 0,
+            toggle: byte & TOGGLE_BIT != 0,
+            transfer_id: byte & TRANSFER_ID_MASK,
+        }
+    }
+
+    /// Pack into a tail byte
+    pub const fn to_byte(&self) -> u8 {
+        (if self.start { START_BIT } else { 0 })
+            | (if self.end { END_BIT } else { 0 })
+            | (if self.toggle { TOGGLE_BIT } else { 0 })
+            | (self.transfer_id & TRANSFER_ID_MASK)
+    }
+}
+
+/// CRC-16/CCITT-FALSE of multi-frame transfers, continued from `crc`
+pub use crate::framing::crc16;
+
+/// Smallest valid CAN-FD data length of at least `len` bytes
+const fn padded_len(len: usize) -> usize {
+    match len {
+        0..=8 => len,
+        9..=12 => 12,
+        13..=16 => 16,
+        17..=20 => 20,
+        21..=24 => 24,
+        25..=32 => 32,
+        33..=48 => 48,
+        _ => 64,
+    }
+}
+
+// ============================================================================
+// Encoding
+// ============================================================================
+
+/// One Cyphal/CAN frame ready to send
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CyphalFrame {
+    raw_id: u32,
+    len: u8,
+    data: [u8; CANFD_MAX_DATA_LEN],
+}
+
+impl CyphalFrame {
+    /// 29-bit extended identifier
+    pub fn raw_id(&self) -> u32 {
+        self.raw_id
+    }
+
+    /// Frame data, tail byte included
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+}
+
+/// Splits messages into Cyphal/CAN transfers
+///
+/// Counts transfer-IDs for all transfers it sends.
+pub struct CyphalEncoder {
+    ports: CyphalPorts,
+    transfer_id: u8,
+    payload: [u8; MAX_TRANSFER_LEN],
+}
+
+impl CyphalEncoder {
+    /// Create an encoder using `ports`
+    pub const fn new(ports: CyphalPorts) -> Self {
+        Self {
+            ports,
+            transfer_id: 0,
+            payload: [0u8; MAX_TRANSFER_LEN],
+        }
+    }
+
+    /// Encode a message as one transfer of frames of at most `mtu` bytes
+    ///
+    /// `mtu` is 8 for classic CAN or a CAN-FD data length up to 64.
+    pub fn encode(&mut self, message: &Message, mtu: usize) -> Result<CyphalFrames<'_>, CanError> {
+        if !(CLASSIC_CAN_MAX_DATA_LEN..=CANFD_MAX_DATA_LEN).contains(&mtu) || padded_len(mtu) != mtu {
+            return Err(CanError::InvalidConfig);
+        }
+        let raw_id = CyphalId::for_message(message, &self.ports)?.to_raw();
+
+        let body = postcard::to_slice(&(message.header.msg_id, &message.payload), &mut self.payload)
+            .map_err(|_| CanError::FrameTooLarge)?;
+        let mut len = body.len();
+
+        let per_frame = mtu - 1;
+        if len > per_frame {
+            // Pad so the last frame has a valid length, then append the CRC
+            let last = (len + CRC_LEN - 1) % per_frame + 1;
+            if last + 1 > CLASSIC_CAN_MAX_DATA_LEN {
+                let padding = padded_len(last + 1) - (last + 1);
+                self.payload[len..len + padding].fill(0);
+                len += padding;
+            }
+            let crc = crc16(0xFFFF, &self.payload[..len]);
+            self.payload[len..len + CRC_LEN].copy_from_slice(&crc.to_be_bytes());
+            len += CRC_LEN;
+        }
+
+        let transfer_id = self.transfer_id;
+        self.transfer_id = (self.transfer_id + 1) & TRANSFER_ID_MASK;
+        Ok(CyphalFrames {
+            raw_id,
+            transfer_id,
+            toggle: true,
+            chunks: self.payload[..len].chunks(per_frame),
+            start: true,
+        })
+    }
+}
+
+/// Iterator over the frames of one transfer, in transmission order
+pub struct CyphalFrames<'a> {
+    raw_id: u32,
+    transfer_id: u8,
+    toggle: bool,
+    chunks: core::slice::Chunks<'a, u8>,
+    start: bool,
+}
+
+impl Iterator for CyphalFrames<'_> {
+    type Item = CyphalFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.chunks.next()?;
+        let end = self.chunks.len() == 0;
+        let tail = TailByte {
+            start: self.start,
+            end,
+            toggle: self.toggle,
+            transfer_id: self.transfer_id,
+        };
+        self.start = false;
+        self.toggle = !self.toggle;
+
+        // Single-frame transfers are padded here; multi-frame ones already
+        // carry their padding ahead of the CRC
+        let len = if end { padded_len(chunk.len() + 1) } else { chunk.len() + 1 };
+        let mut frame = CyphalFrame {
+            raw_id: self.raw_id,
+            len: len as u8,
+            data: [0u8; CANFD_MAX_DATA_LEN],
+        };
+        frame.data[..chunk.len()].copy_from_slice(chunk);
+        frame.data[len - 1] = tail.to_byte();
+        Some(frame)
+    }
+}
+
+// ============================================================================
+// Reassembly
+// ============================================================================
+
+#[derive(Clone, Copy)]
+struct Slot {
+    active: bool,
+    raw_id: u32,
+    transfer_id: u8,
+    toggle: bool,
+    len: usize,
+    buf: [u8; MAX_TRANSFER_LEN],
+}
+
+impl Slot {
+    const EMPTY: Self = Self {
+        active: false,
+        raw_id: 0,
+        transfer_id: 0,
+        toggle: false,
+        len: 0,
+        buf: [0u8; MAX_TRANSFER_LEN],
+    };
+}
+
+/// Rebuilds messages from Cyphal/CAN frames
+///
+/// Keeps one transfer in progress per session (identifier), for up to
+/// `FRAGMENT_REASSEMBLY_SLOTS` sessions at once. Frames of transfers on
+/// other ports belong to Cyphal nodes and are skipped.
+pub struct CyphalReassembler {
+    ports: CyphalPorts,
+    slots: [Slot; FRAGMENT_REASSEMBLY_SLOTS],
+    victim: usize,
+}
+
+impl CyphalReassembler {
+    /// Create an empty reassembler for transfers on `ports`
+    pub const fn new(ports: CyphalPorts) -> Self {
+        Self {
+            ports,
+            slots: [Slot::EMPTY; FRAGMENT_REASSEMBLY_SLOTS],
+            victim: 0,
+        }
+    }
+
+    /// Feed one received frame
+    ///
+    /// Returns `Ok(Some(message))` once the last frame of a transfer arrives,
+    /// `Ok(None)` while it is incomplete or if the frame is not iRPC traffic.
+    /// A frame with the wrong toggle bit or transfer-ID fails with
+    /// `CanError::FragmentLost`, a corrupted transfer with
+    /// `CanError::CrcMismatch`.
+    pub fn push(&mut self, raw_id: u32, data: &[u8]) -> Result<Option<Message>, CanError> {
+        let Some(id) = CyphalId::from_raw(raw_id) else {
+            return Ok(None);
+        };
+        if !id.is_irpc(&self.ports) {
+            return Ok(None);
+        }
+        let Some((&tail, chunk)) = data.split_last() else {
+            return Err(CanError::DeserializationError);
+        };
+        let tail = TailByte::from_byte(tail);
+
+        if tail.start && tail.end {
+            return decode_transfer(&id, chunk).map(Some);
+        }
+
+        let found = self.slots.iter().position(|s| s.active && s.raw_id == raw_id);
+        let index = if tail.start {
+            let index = found
+                .or_else(|| self.slots.iter().position(|s| !s.active))
+                .unwrap_or_else(|| {
+                    // Every slot busy: evict transfers round-robin
+                    let index = self.victim;
+                    self.victim = (self.victim + 1) % FRAGMENT_REASSEMBLY_SLOTS;
+                    index
+                });
+            let slot = &mut self.slots[index];
+            slot.active = true;
+            slot.raw_id = raw_id;
+            slot.transfer_id = tail.transfer_id;
+            slot.toggle = true;
+            slot.len = 0;
+            index
+        } else {
+            match found {
+                Some(index)
+                    if self.slots[index].transfer_id == tail.transfer_id
+                        && self.slots[index].toggle == tail.toggle =>
+                {
+                    index
+                }
+                Some(index) => {
+                    self.slots[index].active = false;
+                    return Err(CanError::FragmentLost);
+                }
+                None => return Err(CanError::FragmentLost),
+            }
+        };
+
+        let slot = &mut self.slots[index];
+        // Cyphal starts transfers with the toggle bit set (DroneCAN clears it)
+        if tail.toggle != slot.toggle {
+            slot.active = false;
+            return Err(CanError::FragmentLost);
+        }
+        if slot.len + chunk.len() > MAX_TRANSFER_LEN {
+            slot.active = false;
+            return Err(CanError::FrameTooLarge);
+        }
+        slot.buf[slot.len..slot.len + chunk.len()].copy_from_slice(chunk);
+        slot.len += chunk.len();
+        slot.toggle = !slot.toggle;
+
+        if !tail.end {
+            return Ok(None);
+        }
+
+        slot.active = false;
+        if slot.len < CRC_LEN || crc16(0xFFFF, &slot.buf[..slot.len]) != 0 {
+            return Err(CanError::CrcMismatch);
+        }
+        decode_transfer(&id, &slot.buf[..slot.len - CRC_LEN]).map(Some)
+    }
+
+    /// Drop all unfinished transfers
+    pub fn clear(&mut self) {
+        for slot in &mut self.slots {
+            slot.active = false;
+        }
+    }
+}
+
+/// Rebuild a message from a transfer payload; trailing padding is ignored
+fn decode_transfer(id: &CyphalId, payload: &[u8]) -> Result<Message, CanError> {
+    let (msg_id, payload): (MessageId, Payload) =
+        postcard::from_bytes(payload).map_err(|_| CanError::DeserializationError)?;
+    let target_id = match id.kind {
+        TransferKind::Message { .. } => crate::config::BROADCAST_ADDRESS,
+        TransferKind::Request { destination, .. } | TransferKind::Response { destination, .. } => {
+            destination as DeviceId
+        }
+    };
+
+    Ok(Message {
+        header: Header {
+            source_id: id.source as DeviceId,
+            target_id,
+            msg_id,
+        },
+        payload,
+    })
+}
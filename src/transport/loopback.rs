@@ -0,0 +1,204 @@
+//! In-memory transports for running firmware logic on the host
+//!
+//! - [`LoopbackTransport`] hands every sent frame back to its own receiver,
+//!   for exercising encode/decode paths and a single node's stack.
+//! - [`channel_pair`] returns two connected ends, so a [`Joint`] can sit on
+//!   one side and a test (or an arm-side driver) on the other. Not available
+//!   with `no_alloc`.
+//!
+//! Both queue whole frames in fixed-capacity `heapless` queues, so the
+//! behaviour under load (a full queue rejects the send) matches a real bus
+//! driver rather than growing without bound.
+//!
+//! # Example
+//!
+//! ```
+//! use irpc::transport::loopback::channel_pair;
+//! use irpc::{Header, Joint, Message, Payload, TransportLayer};
+//!
+//! let (arm_end, joint_end) = channel_pair();
+//! let mut arm = TransportLayer::new(arm_end);
+//! let mut bus = TransportLayer::new(joint_end);
+//! let mut joint = Joint::new(0x0010);
+//!
+//! arm.send_message(&Message {
+//!     header: Header { source_id: 0x0001, target_id: 0x0010, msg_id: 1 },
+//!     payload: Payload::Configure,
+//! }).unwrap();
+//! assert!(joint.process_transport(&mut bus).unwrap());
+//! assert!(matches!(arm.receive_message().unwrap().unwrap().payload, Payload::Ack(1)));
+//! ```
+//!
+//! [`Joint`]: crate::Joint
+
+use heapless::{Deque, Vec};
+
+use crate::bus::EmbeddedTransport;
+use crate::config::LOOPBACK_QUEUE_DEPTH;
+use crate::protocol::Message;
+
+#[cfg(not(feature = "no_alloc"))]
+use core::cell::RefCell;
+#[cfg(not(feature = "no_alloc"))]
+use critical_section::Mutex;
+
+#[cfg(all(not(feature = "std"), not(feature = "no_alloc")))]
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+/// One queued frame; large enough for any encoded message
+type Frame = Vec<u8, { Message::max_size() }>;
+
+/// In-memory transport errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopbackError {
+    /// Frame is larger than the MTU or any valid message
+    FrameTooLarge,
+    /// The receiving queue is full
+    QueueFull,
+}
+
+fn to_frame(data: &[u8]) -> Result<Frame, LoopbackError> {
+    Frame::from_slice(data).map_err(|_| LoopbackError::FrameTooLarge)
+}
+
+/// Transport whose receiver gets back every frame it sent
+///
+/// `N` is the queue depth in frames.
+pub struct LoopbackTransport<const N: usize = LOOPBACK_QUEUE_DEPTH> {
+    queue: Deque<Frame, N>,
+    current: Frame,
+    mtu: usize,
+}
+
+impl<const N: usize> LoopbackTransport<N> {
+    /// Create an empty loopback
+    pub const fn new() -> Self {
+        Self::with_mtu(Message::max_size())
+    }
+
+    /// Create an empty loopback that refuses frames above `mtu` bytes,
+    /// to stand in for a narrower link
+    pub const fn with_mtu(mtu: usize) -> Self {
+        Self {
+            queue: Deque::new(),
+            current: Vec::new(),
+            mtu,
+        }
+    }
+
+    /// Number of frames waiting to be received
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Check if no frames are waiting
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Drop all waiting frames
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+}
+
+impl<const N: usize> Default for LoopbackTransport<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> EmbeddedTransport for LoopbackTransport<N> {
+    type Error = LoopbackError;
+
+    fn send_blocking(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        if data.len() > self.mtu {
+            return Err(LoopbackError::FrameTooLarge);
+        }
+        let frame = to_frame(data)?;
+        self.queue.push_back(frame).map_err(|_| LoopbackError::QueueFull)
+    }
+
+    fn receive_blocking(&mut self) -> Result<Option<&[u8]>, Self::Error> {
+        match self.queue.pop_front() {
+            Some(frame) => {
+                self.current = frame;
+                Ok(Some(&self.current))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn mtu(&self) -> usize {
+        self.mtu.min(Message::max_size())
+    }
+}
+
+/// Queues of both directions, indexed by the receiving end
+#[cfg(not(feature = "no_alloc"))]
+type Link<const N: usize> = Mutex<RefCell<[Deque<Frame, N>; 2]>>;
+
+/// One end of a [`channel_pair`]
+///
+/// Ends can be moved to different threads; each frame sent on one end is
+/// received once, in order, on the other.
+#[cfg(not(feature = "no_alloc"))]
+pub struct ChannelEnd<const N: usize = LOOPBACK_QUEUE_DEPTH> {
+    link: Arc<Link<N>>,
+    side: usize,
+    current: Frame,
+}
+
+#[cfg(not(feature = "no_alloc"))]
+impl<const N: usize> ChannelEnd<N> {
+    /// Create two connected ends with a queue depth of `N` frames per direction
+    pub fn pair() -> (Self, Self) {
+        let link = Arc::new(Mutex::new(RefCell::new([Deque::new(), Deque::new()])));
+        let end = |side| Self {
+            link: Arc::clone(&link),
+            side,
+            current: Vec::new(),
+        };
+        (end(0), end(1))
+    }
+
+    /// Number of frames waiting to be received on this end
+    pub fn pending(&self) -> usize {
+        critical_section::with(|cs| self.link.borrow_ref(cs)[self.side].len())
+    }
+}
+
+#[cfg(not(feature = "no_alloc"))]
+impl<const N: usize> EmbeddedTransport for ChannelEnd<N> {
+    type Error = LoopbackError;
+
+    fn send_blocking(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let frame = to_frame(data)?;
+        critical_section::with(|cs| {
+            self.link.borrow_ref_mut(cs)[1 - self.side]
+                .push_back(frame)
+                .map_err(|_| LoopbackError::QueueFull)
+        })
+    }
+
+    fn receive_blocking(&mut self) -> Result<Option<&[u8]>, Self::Error> {
+        let frame = critical_section::with(|cs| self.link.borrow_ref_mut(cs)[self.side].pop_front());
+        match frame {
+            Some(frame) => {
+                self.current = frame;
+                Ok(Some(&self.current))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Two connected in-memory transports with the default queue depth
+///
+/// Use [`ChannelEnd::pair`] for a different depth.
+#[cfg(not(feature = "no_alloc"))]
+pub fn channel_pair() -> (ChannelEnd, ChannelEnd) {
+    ChannelEnd::pair()
+}
@@ -0,0 +1,497 @@
+//! CAN-FD transport for MCP2517FD / MCP2518FD SPI controllers
+//!
+//! Boards without an on-chip FDCAN peripheral (RP2040, ESP32, ...) can run
+//! the joint firmware with an external Microchip MCP2517FD/MCP2518FD
+//! controller. The driver only needs an `embedded-hal` 1.0 [`SpiDevice`], so
+//! it works with any HAL that implements it.
+//!
+//! Frames use the same arbitration ID scheme as [`CanFdTransport`](super::canfd)
+//! (see [`CanId`](super::CanId)), so MCP-based and STM32 joints share a bus.
+//! CANopen-style addressing is available through [`Mcp2518Config::addressing`].
+//!
+//! # Controller setup
+//!
+//! - TX queue: 8 objects of 64 bytes, unlimited retransmission
+//! - FIFO1: 16 receive objects of 64 bytes
+//! - Filter 0: accept every frame into FIFO1 (addressing is handled by the
+//!   joint state machine)
+//!
+//! # Example
+//!
+//! ```no_run
+//! use irpc::transport::{Mcp2518Config, Mcp2518Transport};
+//! use irpc::Joint;
+//!
+//! let config = Mcp2518Config::for_joint(0x0010);
+//! let mut transport = Mcp2518Transport::new(spi_device, config)?;
+//! let mut joint = Joint::new(0x0010);
+//!
+//! loop {
+//!     if let Some(msg) = transport.receive_message()? {
+//!         if let Some(resp) = joint.handle_message(&msg) {
+//!             transport.send_message(&resp)?;
+//!         }
+//!     }
+//! }
+//! ```
+
+use embedded_hal::spi::{Operation, SpiDevice};
+
+use crate::bus::EmbeddedTransport;
+use crate::config::CANFD_MAX_DATA_LEN;
+use crate::protocol::{DeviceId, Message, TransportErrorKind, TransportStats};
+use crate::transport::canfd::{Addressing, CanError, FrameId};
+
+// --- SPI instructions (upper nibble of the 16-bit command) ---
+const INSTR_RESET: u16 = 0x0;
+const INSTR_WRITE: u16 = 0x2;
+const INSTR_READ: u16 = 0x3;
+
+// --- Register addresses ---
+/// CAN control register
+pub const REG_C1CON: u16 = 0x000;
+/// Nominal bit time configuration
+pub const REG_C1NBTCFG: u16 = 0x004;
+/// Data bit time configuration
+pub const REG_C1DBTCFG: u16 = 0x008;
+/// Transmitter delay compensation
+pub const REG_C1TDC: u16 = 0x00C;
+/// Transmit/receive error counters (bus-off flag)
+pub const REG_C1TREC: u16 = 0x034;
+/// Transmit queue control
+pub const REG_C1TXQCON: u16 = 0x050;
+/// Transmit queue status
+pub const REG_C1TXQSTA: u16 = 0x054;
+/// Transmit queue user address
+pub const REG_C1TXQUA: u16 = 0x058;
+/// FIFO1 control
+pub const REG_C1FIFOCON1: u16 = 0x05C;
+/// FIFO1 status
+pub const REG_C1FIFOSTA1: u16 = 0x060;
+/// FIFO1 user address
+pub const REG_C1FIFOUA1: u16 = 0x064;
+/// Filter 0-3 control
+pub const REG_C1FLTCON0: u16 = 0x1D0;
+/// Filter 0 object
+pub const REG_C1FLTOBJ0: u16 = 0x1F0;
+/// Filter 0 mask
+pub const REG_C1MASK0: u16 = 0x1F4;
+/// Start of message RAM; user addresses are relative to it
+pub const RAM_START: u16 = 0x400;
+
+// --- C1CON fields ---
+const CON_REQOP_SHIFT: u32 = 24;
+const CON_OPMOD_SHIFT: u32 = 21;
+const CON_MODE_MASK: u32 = 0x7;
+const CON_TXQEN: u32 = 1 << 20;
+const CON_STEF: u32 = 1 << 19;
+
+/// Normal CAN-FD operating mode
+pub const MODE_NORMAL_FD: u32 = 0;
+/// Configuration mode (entered after reset)
+pub const MODE_CONFIGURATION: u32 = 4;
+
+// --- FIFO / queue fields ---
+const FIFO_PLSIZE_64: u32 = 7 << 29;
+const FIFO_FSIZE_SHIFT: u32 = 24;
+const TXQ_TXAT_UNLIMITED: u32 = 3 << 21;
+// Bits of the second control byte: UINC (bit 8) and TXREQ (bit 9)
+const CON_BYTE1_UINC: u8 = 0x01;
+const CON_BYTE1_TXREQ: u8 = 0x02;
+const STA_NOT_FULL_EMPTY: u32 = 1 << 0;
+const TREC_TXBO: u32 = 1 << 21;
+
+// --- Filter fields ---
+const FLTCON_FLTEN0: u32 = 1 << 7;
+
+// --- Message object fields (second word) ---
+const OBJ_IDE: u32 = 1 << 4;
+const OBJ_BRS: u32 = 1 << 6;
+const OBJ_FDF: u32 = 1 << 7;
+const OBJ_DLC_MASK: u32 = 0xF;
+
+// Polls of C1CON while waiting for a mode change
+const MODE_CHANGE_ATTEMPTS: u32 = 1_000;
+
+// Message object header (ID word + flags word)
+const OBJ_HEADER_LEN: usize = 8;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// MCP2517FD/MCP2518FD configuration for a joint node
+#[derive(Debug, Clone)]
+pub struct Mcp2518Config {
+    /// Node ID for this device (used in CAN identifiers)
+    pub node_id: DeviceId,
+
+    /// Nominal bitrate for arbitration phase (Hz)
+    /// Typical: 1_000_000 (1 Mbps)
+    pub nominal_bitrate: u32,
+
+    /// Data bitrate for FD data phase (Hz)
+    /// Typical: 5_000_000 (5 Mbps)
+    pub data_bitrate: u32,
+
+    /// Controller clock (Hz)
+    /// Typical: 40_000_000 (40 MHz crystal) or 20_000_000
+    pub oscillator_hz: u32,
+
+    /// How messages map onto CAN identifiers
+    pub addressing: Addressing,
+}
+
+impl Mcp2518Config {
+    /// Create configuration for a joint with default bitrates
+    ///
+    /// Default: 1 Mbps nominal, 5 Mbps data, 40 MHz oscillator, native addressing
+    pub fn for_joint(node_id: DeviceId) -> Self {
+        Self {
+            node_id,
+            nominal_bitrate: 1_000_000,
+            data_bitrate: 5_000_000,
+            oscillator_hz: 40_000_000,
+            addressing: Addressing::Native,
+        }
+    }
+}
+
+/// Bit timing segments, in time quanta (register values are one less)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitTiming {
+    /// Baud rate prescaler
+    pub prescaler: u32,
+    /// Propagation + phase segment 1
+    pub tseg1: u32,
+    /// Phase segment 2
+    pub tseg2: u32,
+    /// Synchronization jump width
+    pub sjw: u32,
+}
+
+impl BitTiming {
+    /// Compute timing for `bitrate` with an 80% sample point
+    ///
+    /// Uses the smallest prescaler that keeps the bit within `max_tq` time
+    /// quanta. Returns `None` if the bitrate cannot be reached exactly.
+    pub const fn compute(oscillator_hz: u32, bitrate: u32, max_tq: u32) -> Option<Self> {
+        if bitrate == 0 || !oscillator_hz.is_multiple_of(bitrate) {
+            return None;
+        }
+        let clocks_per_bit = oscillator_hz / bitrate;
+
+        let mut prescaler = 1;
+        while prescaler <= 256 {
+            if clocks_per_bit.is_multiple_of(prescaler) {
+                let tq = clocks_per_bit / prescaler;
+                if tq >= 8 && tq <= max_tq {
+                    // One quantum of sync segment, then 80% sample point
+                    let tseg1 = tq * 8 / 10 - 1;
+                    let tseg2 = tq - 1 - tseg1;
+                    return Some(Self { prescaler, tseg1, tseg2, sjw: tseg2 });
+                }
+            }
+            prescaler += 1;
+        }
+        None
+    }
+
+    /// `C1NBTCFG` / `C1DBTCFG` register value (both share one layout)
+    pub const fn register(&self) -> u32 {
+        ((self.prescaler - 1) << 24)
+            | ((self.tseg1 - 1) << 16)
+            | ((self.tseg2 - 1) << 8)
+            | (self.sjw - 1)
+    }
+}
+
+// ============================================================================
+// Error Handling
+// ============================================================================
+
+/// MCP2517FD/MCP2518FD transport errors
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum McpError<E> {
+    /// SPI bus error
+    Spi(E),
+    /// CAN-level error
+    Can(CanError),
+}
+
+impl<E> From<CanError> for McpError<E> {
+    fn from(e: CanError) -> Self {
+        McpError::Can(e)
+    }
+}
+
+/// Round a payload length up to the next valid CAN-FD length, returning the DLC
+const fn length_to_dlc(len: usize) -> (u32, usize) {
+    match len {
+        0..=8 => (len as u32, len),
+        9..=12 => (9, 12),
+        13..=16 => (10, 16),
+        17..=20 => (11, 20),
+        21..=24 => (12, 24),
+        25..=32 => (13, 32),
+        33..=48 => (14, 48),
+        _ => (15, 64),
+    }
+}
+
+/// Payload length for a DLC
+const fn dlc_to_length(dlc: u32) -> usize {
+    match dlc {
+        0..=8 => dlc as usize,
+        9 => 12,
+        10 => 16,
+        11 => 20,
+        12 => 24,
+        13 => 32,
+        14 => 48,
+        _ => 64,
+    }
+}
+
+/// Pack a frame ID into the first message object word
+const fn id_to_object(id: FrameId) -> u32 {
+    match id {
+        FrameId::Standard(raw_id) => raw_id as u32 & 0x7FF,
+        // SID holds the 11 most significant bits, EID the 18 least
+        FrameId::Extended(raw_id) => ((raw_id >> 18) & 0x7FF) | ((raw_id & 0x3_FFFF) << 11),
+    }
+}
+
+/// Unpack the first message object word, given the IDE flag
+const fn object_to_id(word: u32, extended: bool) -> FrameId {
+    if extended {
+        FrameId::Extended(((word & 0x7FF) << 18) | ((word >> 11) & 0x3_FFFF))
+    } else {
+        FrameId::Standard((word & 0x7FF) as u16)
+    }
+}
+
+// ============================================================================
+// Driver
+// ============================================================================
+
+/// CAN-FD transport over an MCP2517FD/MCP2518FD SPI controller
+pub struct Mcp2518Transport<SPI> {
+    spi: SPI,
+    node_id: DeviceId,
+    addressing: Addressing,
+    object: [u8; OBJ_HEADER_LEN + CANFD_MAX_DATA_LEN],
+    rx_buffer: [u8; Message::max_size()],
+    stats: TransportStats,
+}
+
+impl<SPI: SpiDevice> Mcp2518Transport<SPI> {
+    /// Reset and configure the controller, then enter normal CAN-FD mode
+    pub fn new(spi: SPI, config: Mcp2518Config) -> Result<Self, McpError<SPI::Error>> {
+        let nominal = BitTiming::compute(config.oscillator_hz, config.nominal_bitrate, 256)
+            .ok_or(CanError::InvalidConfig)?;
+        let data = BitTiming::compute(config.oscillator_hz, config.data_bitrate, 32)
+            .ok_or(CanError::InvalidConfig)?;
+
+        let mut transport = Self {
+            spi,
+            node_id: config.node_id,
+            addressing: config.addressing,
+            object: [0u8; OBJ_HEADER_LEN + CANFD_MAX_DATA_LEN],
+            rx_buffer: [0u8; Message::max_size()],
+            stats: TransportStats::with_mtu(CANFD_MAX_DATA_LEN),
+        };
+
+        transport.command(INSTR_RESET, 0)?;
+        transport.wait_for_mode(MODE_CONFIGURATION)?;
+
+        transport.write_register(REG_C1NBTCFG, nominal.register())?;
+        transport.write_register(REG_C1DBTCFG, data.register())?;
+        // Automatic transmitter delay compensation, offset at the data sample point
+        let tdco = data.prescaler * data.tseg1;
+        transport.write_register(REG_C1TDC, (2 << 16) | ((tdco & 0x7F) << 8))?;
+
+        // TX queue: 8 x 64 bytes, unlimited retransmission
+        transport.write_register(
+            REG_C1TXQCON,
+            FIFO_PLSIZE_64 | (7 << FIFO_FSIZE_SHIFT) | TXQ_TXAT_UNLIMITED,
+        )?;
+        // FIFO1: 16 x 64 bytes, receive
+        transport.write_register(REG_C1FIFOCON1, FIFO_PLSIZE_64 | (15 << FIFO_FSIZE_SHIFT))?;
+
+        // Filter 0: zero mask accepts everything into FIFO1
+        transport.write_register(REG_C1FLTOBJ0, 0)?;
+        transport.write_register(REG_C1MASK0, 0)?;
+        transport.write_register(REG_C1FLTCON0, FLTCON_FLTEN0 | 1)?;
+
+        let con = transport.read_register(REG_C1CON)?;
+        let con = (con & !(CON_MODE_MASK << CON_REQOP_SHIFT) & !CON_STEF)
+            | CON_TXQEN
+            | (MODE_NORMAL_FD << CON_REQOP_SHIFT);
+        transport.write_register(REG_C1CON, con)?;
+        transport.wait_for_mode(MODE_NORMAL_FD)?;
+
+        Ok(transport)
+    }
+
+    /// Send a message over CAN-FD
+    pub fn send_message(&mut self, message: &Message) -> Result<(), McpError<SPI::Error>> {
+        let status = self.read_register(REG_C1TXQSTA)?;
+        if status & STA_NOT_FULL_EMPTY == 0 {
+            if self.read_register(REG_C1TREC)? & TREC_TXBO != 0 {
+                self.stats.record_error(TransportErrorKind::BusOff);
+            }
+            return Err(CanError::TxBufferFull.into());
+        }
+
+        let (id, len) = match self.addressing.encode_frame(message, &mut self.object[OBJ_HEADER_LEN..]) {
+            Ok(frame) => frame,
+            Err(e) => {
+                self.stats.record_error(TransportErrorKind::Serialization);
+                return Err(e.into());
+            }
+        };
+        let (dlc, padded) = length_to_dlc(len);
+        self.object[OBJ_HEADER_LEN + len..OBJ_HEADER_LEN + padded].fill(0);
+        let ide = if matches!(id, FrameId::Extended(_)) { OBJ_IDE } else { 0 };
+        self.object[..4].copy_from_slice(&id_to_object(id).to_le_bytes());
+        self.object[4..8].copy_from_slice(&(dlc | ide | OBJ_BRS | OBJ_FDF).to_le_bytes());
+
+        let address = RAM_START + self.read_register(REG_C1TXQUA)? as u16;
+        let command = ((INSTR_WRITE << 12) | (address & 0x0FFF)).to_be_bytes();
+        let object_len = OBJ_HEADER_LEN + padded;
+        let result = self.spi.transaction(&mut [
+            Operation::Write(&command),
+            Operation::Write(&self.object[..object_len]),
+        ]);
+        result.map_err(|e| self.spi_error(e))?;
+
+        // Advance the queue and request transmission
+        self.write_byte(REG_C1TXQCON + 1, CON_BYTE1_UINC | CON_BYTE1_TXREQ)?;
+        self.stats.tx_frames = self.stats.tx_frames.wrapping_add(1);
+        Ok(())
+    }
+
+    /// Receive a message, if one is waiting
+    pub fn receive_message(&mut self) -> Result<Option<Message>, McpError<SPI::Error>> {
+        let status = self.read_register(REG_C1FIFOSTA1)?;
+        if status & STA_NOT_FULL_EMPTY == 0 {
+            return Ok(None);
+        }
+
+        let address = RAM_START + self.read_register(REG_C1FIFOUA1)? as u16;
+        let command = ((INSTR_READ << 12) | (address & 0x0FFF)).to_be_bytes();
+        let result = self
+            .spi
+            .transaction(&mut [Operation::Write(&command), Operation::Read(&mut self.object)]);
+        result.map_err(|e| self.spi_error(e))?;
+
+        // Release the object before decoding so a bad frame cannot stall the FIFO
+        self.write_byte(REG_C1FIFOCON1 + 1, CON_BYTE1_UINC)?;
+        self.stats.rx_frames = self.stats.rx_frames.wrapping_add(1);
+
+        let id_word = u32::from_le_bytes([self.object[0], self.object[1], self.object[2], self.object[3]]);
+        let flags = u32::from_le_bytes([self.object[4], self.object[5], self.object[6], self.object[7]]);
+        // Frames of the ID kind the addressing does not use are rejected
+        let id = object_to_id(id_word, flags & OBJ_IDE != 0);
+        let len = dlc_to_length(flags & OBJ_DLC_MASK);
+        self.addressing
+            .decode_frame(id, &self.object[OBJ_HEADER_LEN..OBJ_HEADER_LEN + len])
+            .map(Some)
+            .map_err(|e| {
+                self.stats.record_error(TransportErrorKind::Deserialization);
+                e.into()
+            })
+    }
+
+    /// Get the transport statistics collected so far
+    pub fn stats(&self) -> &TransportStats {
+        &self.stats
+    }
+
+    /// Reset all statistics to zero
+    pub fn reset_stats(&mut self) {
+        self.stats = TransportStats::with_mtu(CANFD_MAX_DATA_LEN);
+    }
+
+    /// Get node ID
+    pub fn node_id(&self) -> DeviceId {
+        self.node_id
+    }
+
+    /// Release the SPI device
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+
+    fn spi_error(&mut self, e: SPI::Error) -> McpError<SPI::Error> {
+        self.stats.record_error(TransportErrorKind::Transport);
+        McpError::Spi(e)
+    }
+
+    fn command(&mut self, instruction: u16, address: u16) -> Result<(), McpError<SPI::Error>> {
+        let command = ((instruction << 12) | (address & 0x0FFF)).to_be_bytes();
+        self.spi.write(&command).map_err(|e| self.spi_error(e))
+    }
+
+    fn read_register(&mut self, address: u16) -> Result<u32, McpError<SPI::Error>> {
+        let command = ((INSTR_READ << 12) | address).to_be_bytes();
+        let mut value = [0u8; 4];
+        self.spi
+            .transaction(&mut [Operation::Write(&command), Operation::Read(&mut value)])
+            .map_err(|e| self.spi_error(e))?;
+        Ok(u32::from_le_bytes(value))
+    }
+
+    fn write_register(&mut self, address: u16, value: u32) -> Result<(), McpError<SPI::Error>> {
+        let command = ((INSTR_WRITE << 12) | address).to_be_bytes();
+        self.spi
+            .transaction(&mut [Operation::Write(&command), Operation::Write(&value.to_le_bytes())])
+            .map_err(|e| self.spi_error(e))
+    }
+
+    fn write_byte(&mut self, address: u16, value: u8) -> Result<(), McpError<SPI::Error>> {
+        let command = ((INSTR_WRITE << 12) | address).to_be_bytes();
+        self.spi
+            .transaction(&mut [Operation::Write(&command), Operation::Write(&[value])])
+            .map_err(|e| self.spi_error(e))
+    }
+
+    fn wait_for_mode(&mut self, mode: u32) -> Result<(), McpError<SPI::Error>> {
+        for _ in 0..MODE_CHANGE_ATTEMPTS {
+            let con = self.read_register(REG_C1CON)?;
+            if (con >> CON_OPMOD_SHIFT) & CON_MODE_MASK == mode {
+                return Ok(());
+            }
+        }
+        Err(CanError::NotReady.into())
+    }
+}
+
+/// Byte-level access for use with `TransportLayer`
+///
+/// Outgoing buffers are decoded to recover the routing header for the
+/// arbitration ID; incoming frames are re-encoded as complete messages.
+impl<SPI: SpiDevice> EmbeddedTransport for Mcp2518Transport<SPI> {
+    type Error = McpError<SPI::Error>;
+
+    fn send_blocking(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let message = Message::deserialize(data).map_err(|_| CanError::DeserializationError)?;
+        self.send_message(&message)
+    }
+
+    fn receive_blocking(&mut self) -> Result<Option<&[u8]>, Self::Error> {
+        let Some(message) = self.receive_message()? else {
+            return Ok(None);
+        };
+        let len = message
+            .serialize_into(&mut self.rx_buffer)
+            .map_err(|_| CanError::SerializationError)?;
+        Ok(Some(&self.rx_buffer[..len]))
+    }
+
+    fn mtu(&self) -> usize {
+        CANFD_MAX_DATA_LEN
+    }
+}
@@ -6,14 +6,20 @@
 //!
 //! # Available Transports
 //!
-//! - **CAN-FD** - `CanFdTransport` (requires `stm32g4` or `stm32f4` feature)
+//! - **CAN-FD** - `CanFdTransport` (requires `stm32g4` feature), optionally
+//!   with interrupt-side reception into an `RxQueue` (`QueuedCanFdTransport`)
+//! - **Classic CAN** - `BxCanTransport` with fragmentation (requires `stm32f4` feature)
+//! - **CAN-FD over SPI** - `Mcp2518Transport` for MCP2517FD/MCP2518FD controllers
+//!   (requires `mcp2518fd` feature)
+//! - **Loopback / in-memory pair** - `LoopbackTransport` and `channel_pair()`
+//!   for running joint logic on the host
 //! - **SPI** - Coming soon
 //! - **UART** - Coming soon
 //!
 //! # Example
 //!
 //! ```no_run
-//! use irpc::transport::{CanFdTransport, CanFdConfig};
+//! use irpc::transport::{Addressing, CanFdTransport, CanFdConfig};
 //! use irpc::Joint;
 //!
 //! // iRPC handles all hardware configuration
@@ -21,6 +27,7 @@
 //!     node_id: 0x0010,
 //!     nominal_bitrate: 1_000_000,
 //!     data_bitrate: 5_000_000,
+//!     addressing: Addressing::Native,
 //! };
 //!
 //! let transport = CanFdTransport::new(peripherals.FDCAN1, pins, config)?;
@@ -35,12 +42,50 @@
 //! }
 //! ```
 
-// CAN-FD transport for STM32 microcontrollers
-#[cfg(any(feature = "stm32g4", feature = "stm32f4"))]
+// CAN-FD transport for STM32 microcontrollers. The arbitration ID scheme and
+// frame codec are hardware-independent and always available.
 pub mod canfd;
 
-#[cfg(any(feature = "stm32g4", feature = "stm32f4"))]
-pub use canfd::{CanFdTransport, CanFdConfig, CanFdPins, CanError};
+pub use canfd::{queue_frame, Addressing, CanFdConfig, CanError, CanId, CobId, FrameId, TxMailbox};
+
+#[cfg(feature = "stm32g4")]
+pub use canfd::{CanFdTransport, CanFdPins, CanFdRxPump, QueuedCanFdTransport};
+
+// Interrupt-safe frame queue between a receive interrupt and the main loop
+pub mod rx_queue;
+
+pub use rx_queue::{RxFrame, RxQueue};
+
+// In-memory transports for host-side tests and simulation
+pub mod loopback;
+
+pub use loopback::{LoopbackError, LoopbackTransport};
+
+#[cfg(not(feature = "no_alloc"))]
+pub use loopback::{channel_pair, ChannelEnd};
+
+// Classic CAN 2.0 transport for bxCAN peripherals. Fragmentation and
+// reassembly are hardware-independent.
+pub mod bxcan;
+
+pub use bxcan::{BxCanConfig, FragmentInfo, Reassembler};
+
+#[cfg(feature = "stm32f4")]
+pub use bxcan::BxCanTransport;
+
+// Cyphal/CAN transfer framing, for buses shared with Cyphal nodes
+#[cfg(feature = "cyphal")]
+pub mod cyphal;
+
+#[cfg(feature = "cyphal")]
+pub use cyphal::{CyphalEncoder, CyphalPorts, CyphalReassembler};
+
+// CAN-FD over SPI for boards without an on-chip FDCAN peripheral
+#[cfg(feature = "mcp2518fd")]
+pub mod mcp2518fd;
+
+#[cfg(feature = "mcp2518fd")]
+pub use mcp2518fd::{Mcp2518Config, Mcp2518Transport, McpError};
 
 // Future transports
 // #[cfg(feature = "spi")]
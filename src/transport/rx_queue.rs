@@ -0,0 +1,192 @@
+//! Interrupt-safe receive queue for CAN frames
+//!
+//! At 1 kHz telemetry rates, frames arrive in bursts faster than a main loop
+//! polling every millisecond can pick them up one at a time. An [`RxQueue`]
+//! decouples the two: the receive side (an interrupt handler, or a task on
+//! an interrupt-priority executor) pushes raw frames as they arrive, and the
+//! main loop drains everything queued on each iteration.
+//!
+//! The queue is a fixed-size ring guarded by a critical section, so it can
+//! live in a `static` and be shared between interrupt and thread context.
+//! When it is full, new frames are dropped and counted in
+//! [`TransportStats::rx_overflows`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use irpc::transport::RxQueue;
+//! use irpc::CANFD_RX_QUEUE_DEPTH;
+//!
+//! static RX_QUEUE: RxQueue<CANFD_RX_QUEUE_DEPTH> = RxQueue::new();
+//!
+//! # fn main() -> Result<(), irpc::transport::CanError> {
+//! # let (raw_id, data) = (0, &[0u8; 8][..]);
+//! // Interrupt side
+//! RX_QUEUE.push(raw_id, data);
+//!
+//! // Main loop
+//! while let Some(frame) = RX_QUEUE.pop() {
+//!     let message = irpc::transport::canfd::decode_frame(frame.raw_id(), frame.data())?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use core::cell::RefCell;
+use critical_section::Mutex;
+
+use crate::config::CANFD_MAX_DATA_LEN;
+use crate::protocol::{TransportErrorKind, TransportStats};
+
+/// A raw frame held in an [`RxQueue`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RxFrame {
+    raw_id: u32,
+    len: u8,
+    data: [u8; CANFD_MAX_DATA_LEN],
+}
+
+impl RxFrame {
+    const EMPTY: Self = Self {
+        raw_id: 0,
+        len: 0,
+        data: [0u8; CANFD_MAX_DATA_LEN],
+    };
+
+    /// Copy a frame, or `None` if `data` exceeds a CAN-FD frame
+    pub fn new(raw_id: u32, data: &[u8]) -> Option<Self> {
+        if data.len() > CANFD_MAX_DATA_LEN {
+            return None;
+        }
+        let mut frame = Self::EMPTY;
+        frame.raw_id = raw_id;
+        frame.len = data.len() as u8;
+        frame.data[..data.len()].copy_from_slice(data);
+        Some(frame)
+    }
+
+    /// 29-bit arbitration ID
+    pub fn raw_id(&self) -> u32 {
+        self.raw_id
+    }
+
+    /// Frame data
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+}
+
+struct Ring<const N: usize> {
+    slots: [RxFrame; N],
+    head: usize,
+    len: usize,
+    high_water: usize,
+    stats: TransportStats,
+}
+
+/// Fixed-depth frame queue shared between interrupt and thread context
+///
+/// `N` is the depth in frames (`CANFD_RX_QUEUE_DEPTH` by default).
+pub struct RxQueue<const N: usize> {
+    ring: Mutex<RefCell<Ring<N>>>,
+}
+
+impl<const N: usize> RxQueue<N> {
+    /// Create an empty queue (usable in a `static`)
+    pub const fn new() -> Self {
+        Self {
+            ring: Mutex::new(RefCell::new(Ring {
+                slots: [RxFrame::EMPTY; N],
+                head: 0,
+                len: 0,
+                high_water: 0,
+                stats: TransportStats::new(),
+            })),
+        }
+    }
+
+    /// Queue a received frame
+    ///
+    /// Returns `false` if the frame was dropped, because the queue is full
+    /// (counted as an overflow) or the data exceeds a CAN-FD frame.
+    pub fn push(&self, raw_id: u32, data: &[u8]) -> bool {
+        critical_section::with(|cs| {
+            let mut ring = self.ring.borrow_ref_mut(cs);
+            ring.stats.rx_frames = ring.stats.rx_frames.wrapping_add(1);
+
+            let Some(frame) = RxFrame::new(raw_id, data) else {
+                ring.stats.record_error(TransportErrorKind::FrameTooLarge);
+                return false;
+            };
+            if ring.len == N {
+                ring.stats.record_error(TransportErrorKind::RxOverflow);
+                return false;
+            }
+
+            let tail = (ring.head + ring.len) % N;
+            ring.slots[tail] = frame;
+            ring.len += 1;
+            ring.high_water = ring.high_water.max(ring.len);
+            true
+        })
+    }
+
+    /// Take the oldest queued frame
+    pub fn pop(&self) -> Option<RxFrame> {
+        critical_section::with(|cs| {
+            let mut ring = self.ring.borrow_ref_mut(cs);
+            if ring.len == 0 {
+                return None;
+            }
+            let frame = ring.slots[ring.head];
+            ring.head = (ring.head + 1) % N;
+            ring.len -= 1;
+            Some(frame)
+        })
+    }
+
+    /// Record a bus error seen by the receive side
+    pub fn record_error(&self, kind: TransportErrorKind) {
+        critical_section::with(|cs| self.ring.borrow_ref_mut(cs).stats.record_error(kind));
+    }
+
+    /// Number of frames waiting
+    pub fn len(&self) -> usize {
+        critical_section::with(|cs| self.ring.borrow_ref(cs).len)
+    }
+
+    /// Check if no frames are waiting
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Depth of the queue in frames
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Most frames that were ever waiting at once, for sizing the queue
+    pub fn high_water(&self) -> usize {
+        critical_section::with(|cs| self.ring.borrow_ref(cs).high_water)
+    }
+
+    /// Receive-side counters: frames seen, overflows and bus errors
+    pub fn stats(&self) -> TransportStats {
+        critical_section::with(|cs| self.ring.borrow_ref(cs).stats)
+    }
+
+    /// Reset the counters and the high-water mark (queued frames are kept)
+    pub fn reset_stats(&self) {
+        critical_section::with(|cs| {
+            let mut ring = self.ring.borrow_ref_mut(cs);
+            ring.stats = TransportStats::new();
+            ring.high_water = ring.len;
+        })
+    }
+}
+
+impl<const N: usize> Default for RxQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
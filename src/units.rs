@@ -0,0 +1,119 @@
+//! Unit-safe angles and angular velocities
+//!
+//! Motion payloads carry degrees and degrees/second, while the calibration
+//! payloads carry radians and radians/second, all as bare `f32`. The
+//! newtypes here make the unit part of the type; converting between them
+//! is explicit:
+//!
+//! ```
+//! use irpc::units::{DegPerSec, Degrees, Radians};
+//!
+//! let angle = Degrees::from(Radians(core::f32::consts::FRAC_PI_2));
+//! assert!((angle.0 - 90.0).abs() < 1e-4);
+//! let speed = DegPerSec(30.0);
+//! assert_eq!(speed.to_string(), "30 deg/s");
+//! ```
+//!
+//! Payloads keep their plain fields on the wire and offer typed accessors,
+//! e.g. [`TelemetryStream::position`](crate::TelemetryStream::position) and
+//! [`CalibrationStatus::position`](crate::CalibrationStatus::position).
+
+use core::fmt;
+use core::ops::{Add, Neg, Sub};
+
+use serde::{Deserialize, Serialize};
+
+macro_rules! unit {
+    ($(#[$doc:meta])* $name:ident, $symbol:literal) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(pub f32);
+
+        impl $name {
+            /// The bare value
+            pub fn value(self) -> f32 {
+                self.0
+            }
+
+            pub fn abs(self) -> Self {
+                Self(self.0.abs())
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self(self.0 + other.0)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self(self.0 - other.0)
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} {}", self.0, $symbol)
+            }
+        }
+    };
+}
+
+unit!(
+    /// An angle in degrees, the unit of motion payloads
+    Degrees,
+    "deg"
+);
+unit!(
+    /// An angle in radians, the unit of calibration payloads
+    Radians,
+    "rad"
+);
+unit!(
+    /// An angular velocity in degrees/second
+    DegPerSec,
+    "deg/s"
+);
+unit!(
+    /// An angular velocity in radians/second
+    RadPerSec,
+    "rad/s"
+);
+
+impl From<Radians> for Degrees {
+    fn from(angle: Radians) -> Self {
+        Self(angle.0.to_degrees())
+    }
+}
+
+impl From<Degrees> for Radians {
+    fn from(angle: Degrees) -> Self {
+        Self(angle.0.to_radians())
+    }
+}
+
+impl From<RadPerSec> for DegPerSec {
+    fn from(velocity: RadPerSec) -> Self {
+        Self(velocity.0.to_degrees())
+    }
+}
+
+impl From<DegPerSec> for RadPerSec {
+    fn from(velocity: DegPerSec) -> Self {
+        Self(velocity.0.to_radians())
+    }
+}
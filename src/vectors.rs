@@ -0,0 +1,209 @@
+//! Protocol conformance test vectors
+//!
+//! Firmware written in other languages checks its iRPC encoder and decoder
+//! against canonical encodings of every [`Payload`](crate::Payload) variant. [`write_vectors`]
+//! emits them into a directory:
+//!
+//! ```no_run
+//! # fn example() -> Result<(), irpc::vectors::VectorError> {
+//! let vectors = irpc::vectors::write_vectors("test_vectors")?;
+//! assert!(vectors.iter().all(|vector| irpc::Payload::tag_name(vector.tag).is_some()));
+//!
+//! // Later, e.g. in CI: vectors from any release still round-trip
+//! for vector in irpc::vectors::load_vectors("test_vectors")? {
+//!     vector.check()?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Directory layout
+//!
+//! One file per variant, `<tag>_<name>.bin` (e.g. `003_Deactivate.bin`),
+//! holds the encoded message: the body a transport carries, without framing.
+//! `index.txt` lists the same vectors, one per line, as
+//! `<tag> <name> <length> <hex bytes>`, after `#` comment lines giving the
+//! crate version and the [`SCHEMA_HASH`] they were generated with.
+//!
+//! Every vector goes from [`VECTOR_SOURCE_ID`] to [`VECTOR_TARGET_ID`] with
+//! message ID [`VECTOR_MSG_ID`]. The payload fields follow a fixed pattern
+//! rather than meaningful values: integers are 1, booleans true, optional
+//! fields present, enums their second variant and sequences one element long.
+//! Variants with a field that cannot take that pattern are all zeros
+//! instead.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::protocol::{DeviceId, Header, Message, MessageId, ProtocolError, SCHEMA_HASH};
+
+/// Sender of every vector
+pub const VECTOR_SOURCE_ID: DeviceId = 0x0001;
+/// Receiver of every vector
+pub const VECTOR_TARGET_ID: DeviceId = 0x0010;
+/// Message ID of every vector; two bytes on the wire
+pub const VECTOR_MSG_ID: MessageId = 0x0102;
+
+/// Name of the index file in a vector directory
+pub const INDEX_FILE: &str = "index.txt";
+
+/// Field patterns tried in turn after the tag
+const FILLS: [u8; 2] = [0x01, 0x00];
+
+/// Test vector errors
+#[derive(Debug, thiserror::Error)]
+pub enum VectorError {
+    /// Reading or writing the vector directory failed
+    #[error("Vector I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// A line of the index could not be parsed
+    #[error("Bad index line {line}: {reason}")]
+    Index { line: usize, reason: String },
+
+    /// A vector does not round-trip
+    #[error("Vector {name}: {reason}")]
+    Mismatch { name: String, reason: String },
+
+    /// A vector does not decode
+    #[error("Protocol error: {0}")]
+    Protocol(#[from] ProtocolError),
+}
+
+/// One canonical encoded message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestVector {
+    /// Wire tag of the payload variant
+    pub tag: u8,
+    /// Name of the payload variant (see [`Payload::name`](crate::Payload::name))
+    pub name: String,
+    /// The encoded message
+    pub bytes: Vec<u8>,
+}
+
+impl TestVector {
+    /// File the vector is stored in, within the vector directory
+    pub fn file_name(&self) -> String {
+        format!("{:03}_{}.bin", self.tag, self.name)
+    }
+
+    /// Decode the vector
+    pub fn decode(&self) -> Result<Message, VectorError> {
+        Ok(Message::deserialize(&self.bytes)?)
+    }
+
+    /// Check that the vector decodes to the variant it is named after and
+    /// encodes back to the same bytes
+    pub fn check(&self) -> Result<Message, VectorError> {
+        let mismatch = |reason: String| VectorError::Mismatch { name: self.name.clone(), reason };
+        let message = self.decode()?;
+        if message.payload.tag() != self.tag || message.payload.name() != self.name {
+            return Err(mismatch(format!(
+                "decodes as {} (tag {})",
+                message.payload.name(),
+                message.payload.tag()
+            )));
+        }
+        let mut buf = [0u8; Message::max_size()];
+        let len = message.serialize_into(&mut buf)?;
+        if buf[..len] != self.bytes[..] {
+            return Err(mismatch(format!("re-encodes as {}", hex(&buf[..len]))));
+        }
+        Ok(message)
+    }
+}
+
+/// The canonical vector of every payload variant, in tag order
+pub fn generate() -> Vec<TestVector> {
+    let header = Header { source_id: VECTOR_SOURCE_ID, target_id: VECTOR_TARGET_ID, msg_id: VECTOR_MSG_ID };
+    let mut raw = [0u8; Message::max_size() + 4];
+    let header_len = postcard::to_slice(&header, &mut raw).expect("a header fits").len();
+
+    let mut vectors = Vec::new();
+    // Tags are single-byte varints
+    for tag in 0..0x80 {
+        raw[header_len] = tag;
+        let decoded = FILLS.iter().find_map(|&fill| {
+            raw[header_len + 1..].fill(fill);
+            Message::deserialize(&raw).ok()
+        });
+        let Some(message) = decoded.filter(|message| message.payload.tag() == tag) else { continue };
+
+        let mut buf = [0u8; Message::max_size()];
+        let len = message.serialize_into(&mut buf).expect("decoded message fits its maximum size");
+        vectors.push(TestVector { tag, name: message.payload.name().to_string(), bytes: buf[..len].to_vec() });
+    }
+    vectors
+}
+
+/// Generate the vectors and write them to `dir`, creating it if needed
+pub fn write_vectors(dir: impl AsRef<Path>) -> Result<Vec<TestVector>, VectorError> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let vectors = generate();
+
+    let mut index = String::new();
+    writeln!(index, "# iRPC protocol test vectors").unwrap();
+    writeln!(index, "# version {}", env!("CARGO_PKG_VERSION")).unwrap();
+    writeln!(index, "# schema {:#010x}", SCHEMA_HASH).unwrap();
+    writeln!(index, "# tag name length bytes").unwrap();
+    for vector in &vectors {
+        fs::write(dir.join(vector.file_name()), &vector.bytes)?;
+        writeln!(index, "{} {} {} {}", vector.tag, vector.name, vector.bytes.len(), hex(&vector.bytes)).unwrap();
+    }
+    fs::write(dir.join(INDEX_FILE), index)?;
+    Ok(vectors)
+}
+
+/// Read the vectors listed in `dir`'s index
+///
+/// Each vector's file must hold the bytes the index lists for it. The
+/// vectors are not [checked](TestVector::check) here, so vectors of variants
+/// a newer release added can be told apart from ones that do not decode.
+pub fn load_vectors(dir: impl AsRef<Path>) -> Result<Vec<TestVector>, VectorError> {
+    let dir = dir.as_ref();
+    let index = fs::read_to_string(dir.join(INDEX_FILE))?;
+
+    let mut vectors = Vec::new();
+    for (number, line) in index.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let bad = |reason: &str| VectorError::Index { line: number + 1, reason: reason.to_string() };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [tag, name, len, bytes] = fields[..] else {
+            return Err(bad("expected tag, name, length and bytes"));
+        };
+        let tag = tag.parse().map_err(|_| bad("bad tag"))?;
+        let len: usize = len.parse().map_err(|_| bad("bad length"))?;
+        let bytes = unhex(bytes).ok_or_else(|| bad("bad hex bytes"))?;
+        if bytes.len() != len {
+            return Err(bad("length does not match the bytes"));
+        }
+
+        let vector = TestVector { tag, name: name.to_string(), bytes };
+        let file_name = vector.file_name();
+        if fs::read(dir.join(&file_name))? != vector.bytes {
+            return Err(VectorError::Mismatch { name: vector.name, reason: format!("{} differs from the index", file_name) });
+        }
+        vectors.push(vector);
+    }
+    Ok(vectors)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut out, byte| {
+        write!(out, "{:02x}", byte).unwrap();
+        out
+    })
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
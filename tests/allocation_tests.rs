@@ -0,0 +1,116 @@
+//! Tests for allocation-free message reception on the host
+#![cfg(feature = "arm_api")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use irpc::bus::{CommunicationAdapter, DeviceInfo};
+use irpc::config::RECEIVE_BATCH_LEN;
+use irpc::{Header, Message, Payload};
+
+/// Counts the allocations made on each thread
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[cfg(feature = "joint_api")]
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Adapter handing out a fixed queue of messages one `receive` at a time
+struct QueueAdapter(Mutex<VecDeque<Message>>);
+
+#[async_trait]
+impl CommunicationAdapter for QueueAdapter {
+    type Error = std::convert::Infallible;
+
+    async fn transmit(&self, _message: &Message) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<Option<Message>, Self::Error> {
+        Ok(self.0.lock().unwrap().pop_front())
+    }
+
+    async fn discover_devices(&self) -> Result<Vec<DeviceInfo>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_receive_batch_stops_at_the_batch_length() {
+    let message = |msg_id| Message {
+        header: Header { source_id: 0x0010, target_id: 0x0001, msg_id },
+        payload: Payload::ArmReady,
+    };
+    let adapter = QueueAdapter(Mutex::new((0..100).map(message).collect()));
+
+    let mut batch = Vec::with_capacity(RECEIVE_BATCH_LEN);
+    adapter.receive_batch(&mut batch).await.unwrap();
+    assert_eq!(batch.len(), RECEIVE_BATCH_LEN);
+    assert_eq!(batch[0].header.msg_id, 0);
+
+    batch.clear();
+    adapter.receive_batch(&mut batch).await.unwrap();
+    assert_eq!(batch.len(), 100 - RECEIVE_BATCH_LEN);
+    assert_eq!(batch.last().unwrap().header.msg_id, 99);
+}
+
+#[cfg(feature = "joint_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_telemetry_is_received_without_allocating_per_sample() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, TelemetryFilter};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    // Seven joints streaming at 1 kHz
+    let joints = [0x0010, 0x0011, 0x0012, 0x0013, 0x0014, 0x0015, 0x0016];
+    let bus = Arc::new(SimBus::with_joints(joints).with_telemetry(Duration::from_millis(1)));
+    let comm = CommunicationManager::with_adapter(bus);
+    for joint_id in joints {
+        comm.send_and_wait(joint_id, Payload::Configure).await.unwrap();
+        comm.send_and_wait(joint_id, Payload::Activate).await.unwrap();
+    }
+    let mut telemetry = comm.subscribe_telemetry(TelemetryFilter::default());
+    // Let queues and buffers reach their working size
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    while telemetry.try_recv().is_some() {}
+
+    let before = allocations();
+    let end = tokio::time::Instant::now() + Duration::from_secs(1);
+    let mut samples = 0;
+    while tokio::time::Instant::now() < end {
+        telemetry.recv().await.unwrap();
+        samples += 1;
+    }
+    let allocated = allocations() - before;
+
+    // What is left is per poll of the adapter, not per sample
+    assert!(samples >= 6_900, "{} samples", samples);
+    assert!(allocated < samples / 2, "{} allocations for {} samples", allocated, samples);
+}
@@ -0,0 +1,98 @@
+//! Tests for saved arm configurations
+
+#[cfg(feature = "json")]
+#[test]
+fn test_arm_config_json() {
+    use irpc::arm_config::{ArmConfig, ArmConfigError, JointEntry};
+    use irpc::{JointLimits, MultiTurnPosition};
+
+    let text = r#"{
+        "joints": [
+            { "joint_id": 16, "name": "shoulder",
+              "limits": { "min_position": -90.0, "max_position": 90.0, "max_velocity": 60.0 },
+              "encoder_offset": { "turns": 0, "angle": 12.5 } },
+            { "joint_id": 17, "name": "elbow" }
+        ]
+    }"#;
+    let config = ArmConfig::from_json(text).unwrap();
+    let shoulder = config.joint(0x0010).unwrap();
+    assert_eq!(shoulder.descriptor.name.as_deref(), Some("shoulder"));
+    assert_eq!(shoulder.limits, Some(JointLimits { min_position: -90.0, max_position: 90.0, max_velocity: 60.0 }));
+    assert_eq!(shoulder.encoder_offset, Some(MultiTurnPosition { turns: 0, angle: 12.5 }));
+    assert_eq!(config.joint(0x0011).unwrap().limits, None);
+    assert!(config.arm_model.is_none());
+    assert_eq!(ArmConfig::from_json(&config.to_json().unwrap()).unwrap(), config);
+
+    let twice = ArmConfig { joints: vec![JointEntry::new(0x0010), JointEntry::new(0x0010)], arm_model: None };
+    assert!(matches!(ArmConfig::from_json(&twice.to_json().unwrap()), Err(ArmConfigError::DuplicateJoint(0x0010))));
+    assert!(matches!(ArmConfig::from_json("{\"joints\": 3}"), Err(ArmConfigError::Parse(_))));
+    assert!(matches!(config.save("arm.toml"), Err(ArmConfigError::UnsupportedFormat(_))));
+}
+
+#[cfg(all(feature = "json", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_save_and_load_config() {
+    use irpc::bus::sim::SimBus;
+    use irpc::kinematics::{ArmModel, DhParameters, Link};
+    use irpc::{ArmOrchestrator, CommunicationManager, JointLimits, MultiTurnPosition};
+    use std::sync::Arc;
+
+    let sim_arm = |bus: Arc<SimBus>| async move {
+        let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus));
+        arm.add_joint(0x0010);
+        arm.add_joint(0x0011);
+        arm.configure_all().await.into_result().unwrap();
+        arm
+    };
+    let limits = JointLimits { min_position: -45.0, max_position: 45.0, max_velocity: 30.0 };
+    let offset = MultiTurnPosition { turns: 1, angle: 20.0 };
+    let path = std::env::temp_dir().join(format!("irpc-arm-config-{}.json", std::process::id()));
+
+    let mut arm = sim_arm(Arc::new(SimBus::with_joints([0x0010, 0x0011]))).await;
+    arm.set_joint_name(0x0010, "shoulder");
+    let shoulder = arm.get_joint(0x0010).unwrap();
+    shoulder.set_encoder_offset(offset).await.unwrap();
+    shoulder.set_limits(limits).await.unwrap();
+    let dh = DhParameters { a: 0.4, ..Default::default() };
+    arm.set_arm_model(ArmModel::new(vec![Link { joint_id: 0x0010, dh, mass: 1.0, center_of_mass: [0.0; 3] }]));
+    arm.save_config(&path).await.unwrap();
+
+    // Another arm picks up the names, model, offsets and limits
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0011]));
+    let mut restored = sim_arm(bus.clone()).await;
+    restored.load_config(&path).await.unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(restored.joint_name(0x0010), Some("shoulder"));
+    assert_eq!(restored.joint_name(0x0011), None);
+    assert_eq!(restored.arm_model(), arm.arm_model());
+    assert_eq!(restored.get_joint(0x0010).unwrap().limits().await, Some(limits));
+    assert_eq!(bus.with_joint(0x0010, |joint| joint.config().encoder_offset), Some(Some(offset)));
+    assert_eq!(restored.config().await, arm.config().await);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_apply_config_adds_joints_and_reports_refusals() {
+    use irpc::arm_config::{ArmConfig, ArmConfigError, JointEntry};
+    use irpc::bus::sim::SimBus;
+    use irpc::{ArmOrchestrator, CommunicationManager, JointDescriptor, MultiTurnPosition};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0011]));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    let elbow = JointEntry { descriptor: JointDescriptor::named(0x0011, "elbow"), ..JointEntry::new(0x0011) };
+    let config = ArmConfig { joints: vec![JointEntry::new(0x0010), elbow], arm_model: None };
+    arm.apply_config(&config).await.unwrap();
+    let mut ids = arm.get_joint_ids();
+    ids.sort();
+    assert_eq!(ids, vec![0x0010, 0x0011]);
+    assert_eq!(arm.joint_name(0x0011), Some("elbow"));
+
+    // An Active joint refuses a new encoder offset
+    arm.get_joint(0x0011).unwrap().configure().await.unwrap();
+    arm.get_joint(0x0011).unwrap().activate().await.unwrap();
+    let offset = MultiTurnPosition { turns: 0, angle: 5.0 };
+    let moved = JointEntry { encoder_offset: Some(offset), ..JointEntry::new(0x0011) };
+    let moved = ArmConfig { joints: vec![moved], arm_model: None };
+    assert!(matches!(arm.apply_config(&moved).await, Err(ArmConfigError::Joint { joint_id: 0x0011, .. })));
+}
@@ -0,0 +1,108 @@
+//! Tests for auxiliary I/O commands
+
+#[cfg(feature = "joint_api")]
+mod common;
+
+#[cfg(feature = "joint_api")]
+use common::command;
+#[cfg(feature = "joint_api")]
+use irpc::aux_io::{AuxIoError, AuxIoHandler};
+#[cfg(feature = "joint_api")]
+use irpc::{DigitalIoPayload, Joint, Payload};
+
+/// Two outputs looped back to the digital inputs, one analog input
+#[cfg(feature = "joint_api")]
+#[derive(Default)]
+struct LoopbackIo {
+    outputs: [bool; 2],
+    supply_voltage: f32,
+}
+
+#[cfg(feature = "joint_api")]
+impl AuxIoHandler for LoopbackIo {
+    fn set_digital_output(&mut self, channel: u8, value: bool) -> Result<(), AuxIoError> {
+        let output = self.outputs.get_mut(channel as usize).ok_or(AuxIoError::NoSuchChannel)?;
+        *output = value;
+        Ok(())
+    }
+
+    fn read_digital_input(&mut self, channel: u8) -> Result<bool, AuxIoError> {
+        self.outputs.get(channel as usize).copied().ok_or(AuxIoError::NoSuchChannel)
+    }
+
+    fn read_analog_input(&mut self, channel: u8) -> Result<f32, AuxIoError> {
+        match channel {
+            0 => Ok(self.supply_voltage),
+            _ => Err(AuxIoError::NoSuchChannel),
+        }
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_answers_aux_io_commands() {
+    let mut joint = Joint::new(0x0010);
+    let mut io = LoopbackIo { supply_voltage: 24.1, ..Default::default() };
+
+    let output = Payload::SetDigitalOutput(DigitalIoPayload { channel: 1, value: true });
+    let reply = joint.handle_aux_io(&command(1, output), &mut io).unwrap();
+    assert!(matches!(reply.payload, Payload::Ack(1)));
+    assert_eq!(io.outputs, [false, true]);
+
+    let reply = joint.handle_aux_io(&command(2, Payload::ReadDigitalInput(1)), &mut io).unwrap();
+    assert!(matches!(reply.payload, Payload::DigitalInput(DigitalIoPayload { channel: 1, value: true })));
+    let reply = joint.handle_aux_io(&command(3, Payload::ReadAnalogInput(0)), &mut io).unwrap();
+    assert!(matches!(reply.payload, Payload::AnalogInput(a) if a.channel == 0 && a.value == 24.1));
+
+    let reply = joint.handle_aux_io(&command(4, Payload::ReadAnalogInput(3)), &mut io).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { id: 4, error: irpc::ERROR_NO_SUCH_CHANNEL }));
+
+    // Everything else is left to the regular handling, which has no I/O
+    assert!(joint.handle_aux_io(&command(5, Payload::Configure), &mut io).is_none());
+    assert!(Joint::new(0x0020).handle_aux_io(&command(6, Payload::ReadDigitalInput(0)), &mut io).is_none());
+    let reply = joint.handle_message(&command(7, Payload::ReadDigitalInput(0))).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { id: 7, error: irpc::ERROR_UNKNOWN_COMMAND }));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_unimplemented_channel_kinds_are_refused() {
+    struct OutputsOnly;
+    impl AuxIoHandler for OutputsOnly {
+        fn set_digital_output(&mut self, _channel: u8, _value: bool) -> Result<(), AuxIoError> {
+            Err(AuxIoError::Failed)
+        }
+    }
+
+    let mut joint = Joint::new(0x0010);
+    let output = Payload::SetDigitalOutput(DigitalIoPayload { channel: 0, value: true });
+    let reply = joint.handle_aux_io(&command(1, output), &mut OutputsOnly).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { error: irpc::ERROR_AUX_IO, .. }));
+    let reply = joint.handle_aux_io(&command(2, Payload::ReadDigitalInput(0)), &mut OutputsOnly).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { error: irpc::ERROR_NO_SUCH_CHANNEL, .. }));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_proxy_aux_io_on_sim() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{Capabilities, CommunicationManager, JointProxy, ProtocolError};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0020]));
+    assert!(bus.set_aux_io(0x0010, LoopbackIo { supply_voltage: 48.0, ..Default::default() }));
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    let proxy = JointProxy::new(0x0010, comm.clone());
+
+    proxy.set_digital_output(0, true).await.unwrap();
+    assert!(proxy.read_digital_input(0).await.unwrap());
+    assert!(!proxy.read_digital_input(1).await.unwrap());
+    assert_eq!(proxy.read_analog_input(0).await.unwrap(), 48.0);
+    assert!(matches!(proxy.read_digital_input(5).await, Err(ProtocolError::IoError(_))));
+
+    // A joint without auxiliary I/O says so once discovered
+    comm.discover().await.unwrap();
+    assert!(proxy.capabilities().unwrap().contains(Capabilities::AUX_IO));
+    let bare = JointProxy::new(0x0020, comm);
+    assert!(matches!(bare.set_digital_output(0, true).await, Err(ProtocolError::Unsupported)));
+}
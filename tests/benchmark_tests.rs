@@ -0,0 +1,93 @@
+//! Tests for the bus latency and throughput benchmark
+
+#![cfg(feature = "arm_api")]
+
+use irpc::benchmark::{BenchmarkConfig, Percentiles};
+use std::time::Duration;
+
+#[test]
+fn test_percentiles_use_the_nearest_rank() {
+    assert_eq!(Percentiles::of(&mut []), None);
+
+    let mut samples: Vec<Duration> = (1..=100).rev().map(Duration::from_millis).collect();
+    let percentiles = Percentiles::of(&mut samples).unwrap();
+    assert_eq!(percentiles.count, 100);
+    assert_eq!((percentiles.min, percentiles.max), (Duration::from_millis(1), Duration::from_millis(100)));
+    assert_eq!(percentiles.p50, Duration::from_millis(50));
+    assert_eq!(percentiles.p90, Duration::from_millis(90));
+    assert_eq!(percentiles.p99, Duration::from_millis(99));
+    assert_eq!(percentiles.mean, Duration::from_micros(50_500));
+    assert!(percentiles.to_string().contains("p99 99.000"), "{}", percentiles);
+
+    let single = Percentiles::of(&mut [Duration::from_micros(250)]).unwrap();
+    assert_eq!((single.p50, single.p99), (Duration::from_micros(250), Duration::from_micros(250)));
+    assert_eq!(BenchmarkConfig::default().start_rate_hz, 100.0);
+}
+
+#[cfg(feature = "joint_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_benchmark_over_sim_bus() {
+    use irpc::benchmark::BusBenchmark;
+    use irpc::bus::sim::SimBus;
+    use irpc::{ArmOrchestrator, CommunicationManager};
+    use std::sync::Arc;
+
+    let ids = [0x0010, 0x0020];
+    let bus = Arc::new(SimBus::with_joints(ids).with_telemetry(Duration::from_millis(10)));
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    let mut arm = ArmOrchestrator::with_comm_manager(comm.clone());
+    for id in ids {
+        arm.add_joint(id);
+    }
+    arm.configure_all().await.into_result().unwrap();
+    arm.activate_all().await.into_result().unwrap();
+
+    let config = BenchmarkConfig {
+        round_trips: 50,
+        max_rate_hz: 1000.0,
+        step_window: Duration::from_millis(200),
+        telemetry_window: Duration::from_secs(1),
+        ..BenchmarkConfig::default()
+    };
+    let report = BusBenchmark::new(comm, ids, config).run().await.unwrap();
+
+    assert_eq!(report.round_trip.unwrap().count, 50);
+    // 100, 200, 400, 800 and 1000 Hz
+    let steps = &report.setpoint_rate.steps;
+    assert_eq!(steps.iter().map(|step| step.rate_hz).collect::<Vec<_>>(), [100.0, 200.0, 400.0, 800.0, 1000.0]);
+    let sustained = report.setpoint_rate.sustained().unwrap();
+    assert_eq!((sustained.rate_hz, sustained.sent, sustained.acked), (1000.0, 200, 200));
+    assert_eq!(sustained.latency.unwrap().count, 200);
+    // Holding still while streamed to
+    for id in ids {
+        assert!(bus.true_position(id).unwrap().abs() < 0.5);
+    }
+
+    // 100 Hz from each of two joints
+    let telemetry = report.telemetry;
+    assert!((telemetry.samples_per_sec - 200.0).abs() <= 4.0, "{:?}", telemetry);
+    assert!(telemetry.bytes_per_sec > telemetry.samples_per_sec * 50.0);
+    assert_eq!(telemetry.interval.unwrap().p50, Duration::from_millis(10));
+    let text = report.to_string();
+    assert!(text.contains("joints 0x0010, 0x0020") && text.contains("setpoints:"), "{}", text);
+}
+
+#[cfg(feature = "joint_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_refused_setpoints_count_as_lost() {
+    use irpc::benchmark::BusBenchmark;
+    use irpc::bus::sim::SimBus;
+    use irpc::CommunicationManager;
+    use std::sync::Arc;
+
+    // Never activated, so every SetTarget is refused
+    let bus = Arc::new(SimBus::with_joints([0x0010]));
+    let bench = BusBenchmark::new(CommunicationManager::with_adapter(bus), [0x0010], BenchmarkConfig::default());
+
+    let rate = bench.setpoint_rate().await.unwrap();
+    assert_eq!(rate.steps.len(), 1);
+    assert_eq!((rate.steps[0].sent, rate.steps[0].lost, rate.steps[0].latency), (50, 50, None));
+    assert!(rate.sustained().is_none());
+    let telemetry = bench.telemetry_throughput().await;
+    assert_eq!((telemetry.samples, telemetry.interval), (0, None));
+}
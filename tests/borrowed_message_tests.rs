@@ -0,0 +1,87 @@
+//! Tests for inspecting messages without decoding their payloads
+
+use irpc::{Header, LifecycleState, Message, MessageRef, Payload, ProtocolError, SetTargetPayload};
+
+fn message(payload: Payload) -> Message {
+    Message { header: Header { source_id: 0x0001, target_id: 0x0010, msg_id: 300 }, payload }
+}
+
+fn encode(message: &Message) -> ([u8; Message::max_size()], usize) {
+    let mut buf = [0u8; Message::max_size()];
+    let len = message.serialize_into(&mut buf).unwrap();
+    (buf, len)
+}
+
+#[test]
+fn test_borrowed_messages_match_owned_ones() {
+    let payloads = [
+        Payload::SetTarget(SetTargetPayload { target_angle: 45.0, velocity_limit: 30.0 }),
+        Payload::Configure,
+        Payload::JointStatus { state: LifecycleState::Active, error_code: 7 },
+        Payload::Nack { id: 8, error: 4 },
+        Payload::Sync { cycle_counter: 1_000_000 },
+    ];
+    for payload in payloads {
+        let owned = message(payload);
+        let (buf, len) = encode(&owned);
+
+        let borrowed = MessageRef::deserialize(&buf[..len]).unwrap();
+        assert_eq!(borrowed.header.target_id, 0x0010);
+        assert_eq!(borrowed.header.msg_id, 300);
+        assert_eq!(borrowed.payload.tag(), owned.payload.tag());
+        assert_eq!(borrowed.payload.name(), owned.payload.name());
+        // The payload is the rest of the frame, after the header
+        assert_eq!(borrowed.payload.bytes(), &buf[4..len]);
+
+        let decoded = borrowed.decode().unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", owned));
+        assert_eq!(format!("{:?}", borrowed.payload.decode().unwrap()), format!("{:?}", owned.payload));
+    }
+}
+
+#[test]
+fn test_fields_decode_on_demand() {
+    let (buf, len) = encode(&message(Payload::SetTarget(SetTargetPayload { target_angle: 45.0, velocity_limit: 30.0 })));
+    let target: SetTargetPayload = MessageRef::deserialize(&buf[..len]).unwrap().payload.fields().unwrap();
+    assert_eq!((target.target_angle, target.velocity_limit), (45.0, 30.0));
+
+    let (buf, len) = encode(&message(Payload::JointStatus { state: LifecycleState::Error, error_code: 0x0102 }));
+    let status = MessageRef::deserialize(&buf[..len]).unwrap().payload;
+    assert_eq!(status.fields::<(LifecycleState, u16)>().unwrap(), (LifecycleState::Error, 0x0102));
+
+    let (buf, len) = encode(&message(Payload::Activate));
+    let activate = MessageRef::deserialize(&buf[..len]).unwrap().payload;
+    assert!(activate.body().is_empty());
+    activate.fields::<()>().unwrap();
+
+    // Truncated fields pass the header and tag, and fail once decoded
+    let (buf, len) = encode(&message(Payload::SetTarget(SetTargetPayload { target_angle: 1.0, velocity_limit: 1.0 })));
+    let truncated = MessageRef::deserialize(&buf[..len - 2]).unwrap();
+    assert!(truncated.payload.fields::<SetTargetPayload>().is_err());
+    assert!(truncated.decode().is_err());
+
+    // Unknown and missing tags are refused up front
+    let unknown = [0x01, 0x10, 0x07, 0x7F];
+    assert!(matches!(MessageRef::deserialize(&unknown), Err(ProtocolError::DeserializationError(_))));
+    assert!(matches!(MessageRef::deserialize(&unknown[..3]), Err(ProtocolError::DeserializationError(_))));
+    assert!(Payload::deserialize_borrowed(&[]).is_err());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_transport_layer_receives_borrowed_messages() {
+    use irpc::transport::LoopbackTransport;
+    use irpc::{EmbeddedTransport, TransportLayer};
+
+    let mut transport = TransportLayer::new(LoopbackTransport::<4>::new());
+    transport.send_message(&message(Payload::SetTarget(SetTargetPayload { target_angle: 12.5, velocity_limit: 3.0 }))).unwrap();
+    transport.transport_mut().send_blocking(&[0x01, 0x10, 0x07, 0x7F]).unwrap();
+
+    let received = transport.receive_message_ref().unwrap().unwrap();
+    assert_eq!(received.payload.name(), "SetTarget");
+    assert_eq!(received.payload.fields::<SetTargetPayload>().unwrap().target_angle, 12.5);
+
+    assert!(transport.receive_message_ref().is_err());
+    assert!(transport.receive_message_ref().unwrap().is_none());
+    assert_eq!((transport.stats().rx_frames, transport.stats().deserialization_failures), (2, 1));
+}
@@ -0,0 +1,148 @@
+//! Tests for holding brake control
+
+#[cfg(feature = "joint_api")]
+mod common;
+
+#[cfg(feature = "joint_api")]
+use common::command;
+#[cfg(feature = "joint_api")]
+use irpc::{Capabilities, Joint, LifecycleState, Payload, SetTargetPayload, ERROR_BRAKE_ENGAGED, ERROR_UNKNOWN_COMMAND};
+
+#[cfg(feature = "joint_api")]
+fn reply(joint: &mut Joint, msg_id: u32, payload: Payload) -> Payload {
+    joint
+        .handle_message(&command(msg_id, payload))
+        .unwrap()
+        .payload
+}
+
+#[cfg(feature = "joint_api")]
+fn target() -> Payload {
+    Payload::SetTarget(SetTargetPayload {
+        target_angle: 10.0,
+        velocity_limit: 50.0,
+    })
+}
+
+#[cfg(feature = "joint_api")]
+fn braked_joint() -> Joint {
+    let mut joint = Joint::new(0x0010);
+    joint.set_capabilities(joint.capabilities() | Capabilities::BRAKE);
+    joint
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_without_brake_refuses_brake_commands() {
+    let mut joint = Joint::new(0x0010);
+    assert!(!joint.brake_engaged());
+    assert!(matches!(
+        reply(&mut joint, 1, Payload::EngageBrake),
+        Payload::Nack {
+            error: ERROR_UNKNOWN_COMMAND,
+            ..
+        }
+    ));
+    reply(&mut joint, 2, Payload::Configure);
+    reply(&mut joint, 3, Payload::Activate);
+    assert!(matches!(reply(&mut joint, 4, target()), Payload::Ack(4)));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_brake_interlocks() {
+    let mut joint = braked_joint();
+    assert!(joint.brake_engaged());
+
+    // Not before the joint is configured
+    assert!(matches!(
+        reply(&mut joint, 1, Payload::ReleaseBrake),
+        Payload::Nack { id: 1, error: 4 }
+    ));
+    reply(&mut joint, 2, Payload::Configure);
+    reply(&mut joint, 3, Payload::Activate);
+    assert!(matches!(
+        reply(&mut joint, 4, target()),
+        Payload::Nack {
+            error: ERROR_BRAKE_ENGAGED,
+            ..
+        }
+    ));
+    assert!(matches!(
+        reply(&mut joint, 5, Payload::Home),
+        Payload::Nack {
+            error: ERROR_BRAKE_ENGAGED,
+            ..
+        }
+    ));
+
+    assert!(matches!(
+        reply(&mut joint, 6, Payload::ReleaseBrake),
+        Payload::Ack(6)
+    ));
+    assert!(!joint.brake_engaged());
+    assert!(matches!(reply(&mut joint, 7, target()), Payload::Ack(7)));
+    assert!(matches!(
+        reply(&mut joint, 8, Payload::EngageBrake),
+        Payload::Ack(8)
+    ));
+    assert!(matches!(
+        reply(&mut joint, 9, target()),
+        Payload::Nack {
+            error: ERROR_BRAKE_ENGAGED,
+            ..
+        }
+    ));
+
+    // Emergency stop engages it
+    reply(&mut joint, 10, Payload::ReleaseBrake);
+    reply(&mut joint, 11, Payload::Reset);
+    assert!(joint.brake_engaged());
+
+    // So does a fault, and it stays engaged until reset and reconfigured
+    reply(&mut joint, 12, Payload::Configure);
+    reply(&mut joint, 13, Payload::ReleaseBrake);
+    joint.report_fault();
+    assert_eq!(joint.state(), LifecycleState::Error);
+    assert!(joint.brake_engaged());
+    assert!(matches!(
+        reply(&mut joint, 14, Payload::ReleaseBrake),
+        Payload::Nack { id: 14, error: 4 }
+    ));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_proxy_set_brake_on_sim() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, JointProxy, ProtocolError};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0020]));
+    bus.add_joint(braked_joint());
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    comm.discover().await.unwrap();
+    let proxy = JointProxy::new(0x0010, comm.clone());
+
+    assert!(matches!(
+        proxy.set_brake(false).await,
+        Err(ProtocolError::IoError(_))
+    ));
+    proxy.configure().await.unwrap();
+    proxy.set_brake(false).await.unwrap();
+    assert_eq!(
+        bus.with_joint(0x0010, |joint| joint.brake_engaged()),
+        Some(false)
+    );
+    proxy.emergency_stop().await.unwrap();
+    assert_eq!(
+        bus.with_joint(0x0010, |joint| joint.brake_engaged()),
+        Some(true)
+    );
+
+    let unbraked = JointProxy::new(0x0020, comm);
+    assert!(matches!(
+        unbraked.set_brake(true).await,
+        Err(ProtocolError::Unsupported)
+    ));
+}
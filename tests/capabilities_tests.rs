@@ -0,0 +1,104 @@
+//! Tests for capability advertisement and gating
+
+use irpc::{BootMode, Capabilities, Header, HelloPayload, Message, Payload};
+
+#[test]
+fn test_capability_flags() {
+    let caps = Capabilities::SET_TARGET_V2 | Capabilities::HOMING;
+    assert!(caps.contains(Capabilities::HOMING));
+    assert!(caps.contains(Capabilities::SET_TARGET_V2 | Capabilities::HOMING));
+    assert!(!caps.contains(Capabilities::HOMING | Capabilities::CALIBRATION));
+    assert!(caps.contains(Capabilities::NONE));
+    assert_eq!(caps.difference(Capabilities::HOMING), Capabilities::SET_TARGET_V2);
+    assert!(Capabilities::default().is_empty());
+
+    // Flags a newer joint defines survive decoding
+    let hello = Message {
+        header: Header { source_id: 0x0010, target_id: 0x0001, msg_id: 1 },
+        payload: Payload::Hello(HelloPayload {
+            entity_type: 0x1001,
+            mode: BootMode::Application,
+            capabilities: Capabilities(0x8000_0001),
+            schema: irpc::SCHEMA_HASH,
+        }),
+    };
+    let mut buf = [0u8; 64];
+    let len = hello.serialize_into(&mut buf).unwrap();
+    let decoded = Message::deserialize(&buf[..len]).unwrap();
+    let Payload::Hello(decoded) = decoded.payload else { panic!("not a Hello") };
+    assert_eq!(decoded.capabilities.bits(), 0x8000_0001);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_advertises_capabilities_in_hello() {
+    use irpc::{Joint, BROADCAST_ADDRESS};
+
+    let mut joint = Joint::new(0x0010);
+    assert!(joint.capabilities().contains(Capabilities::SET_TARGET_V2 | Capabilities::HOMING));
+    assert!(!joint.capabilities().contains(Capabilities::CALIBRATION));
+    joint.set_capabilities(joint.capabilities() | Capabilities::CALIBRATION);
+
+    let discover = Message {
+        header: Header { source_id: 0x0001, target_id: BROADCAST_ADDRESS, msg_id: 7 },
+        payload: Payload::Discover,
+    };
+    joint.handle_message(&discover);
+    let reply = joint.poll(0).or_else(|| joint.poll(u64::MAX / 2)).unwrap();
+    let Payload::Hello(hello) = reply.payload else { panic!("not a Hello") };
+    assert!(hello.capabilities.contains(Capabilities::CALIBRATION | Capabilities::HOMING));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_proxy_refuses_unadvertised_commands() {
+    use irpc::bus::sim::SimBus;
+    use irpc::compat::PayloadGeneration;
+    use irpc::{
+        CalibrationRequest, CommunicationManager, ConfigureTelemetryPayload, Joint, JointProxy, ProtocolError,
+        TelemetryMode,
+    };
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::new());
+    let mut joint = Joint::new(0x0010);
+    joint.set_capabilities(Capabilities::HOMING);
+    bus.add_joint(joint);
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    let proxy = JointProxy::new(0x0010, comm.clone());
+
+    // Unknown until discovered: commands go out and the joint refuses them
+    assert_eq!(proxy.capabilities(), None);
+    assert!(matches!(
+        proxy.start_calibration(CalibrationRequest::default()).await,
+        Err(ProtocolError::IoError(_))
+    ));
+
+    let devices = comm.discover().await.unwrap();
+    let expected = Capabilities::HOMING | Capabilities::CONFIG_STORE | Capabilities::FIRMWARE_UPDATE;
+    assert_eq!(devices[0].capabilities, expected);
+    assert_eq!(proxy.capabilities(), Some(expected));
+
+    let sent = bus.transmitted();
+    assert!(matches!(
+        proxy.start_calibration(CalibrationRequest::default()).await,
+        Err(ProtocolError::Unsupported)
+    ));
+    let telemetry = ConfigureTelemetryPayload {
+        mode: TelemetryMode::Periodic,
+        rate_hz: 100,
+        change_threshold: 0.0,
+        phase_offset_us: 0,
+    };
+    assert!(matches!(proxy.configure_telemetry(telemetry).await, Err(ProtocolError::Unsupported)));
+    assert_eq!(bus.transmitted(), sent);
+    proxy.save_config().await.unwrap();
+
+    // No SetTargetV2: motion goes out as v1 straight away
+    assert_eq!(proxy.payload_generation(), Some(PayloadGeneration::V1));
+    proxy.configure().await.unwrap();
+    proxy.activate().await.unwrap();
+    let sent = bus.transmitted();
+    proxy.set_target(10.0, 50.0).await.unwrap();
+    assert_eq!(bus.transmitted(), sent + 1);
+}
@@ -0,0 +1,161 @@
+//! Tests for the joint's collision and overload reaction
+
+#[cfg(feature = "joint_api")]
+fn adaptive(reaction: irpc::CollisionReaction, stallguard_enable: bool) -> irpc::ConfigureAdaptivePayload {
+    irpc::ConfigureAdaptivePayload {
+        coolstep_enable: false,
+        coolstep_min_current: 0.3,
+        coolstep_threshold: 50.0,
+        dcstep_enable: false,
+        dcstep_threshold: 80.0,
+        dcstep_max_derating: 0.5,
+        stallguard_enable,
+        stallguard_current_threshold: 1.5,
+        stallguard_velocity_threshold: 5.0,
+        collision_reaction: reaction,
+        collision_current_threshold: 2.0,
+        collision_time_ms: 20,
+        collision_backoff_deg: 5.0,
+    }
+}
+
+#[cfg(feature = "joint_api")]
+fn active_joint(reaction: irpc::CollisionReaction, stallguard_enable: bool) -> irpc::Joint {
+    use irpc::{Capabilities, Header, Joint, Message, MultiTurnPosition, Payload, SetTargetPayload};
+
+    let mut joint = Joint::new(0x0010);
+    joint.set_capabilities(joint.capabilities() | Capabilities::ADAPTIVE_CONTROL);
+    joint.update_encoder(MultiTurnPosition { turns: 0, angle: 10.0 });
+    let target = SetTargetPayload { target_angle: 90.0, velocity_limit: 30.0 };
+    let commands = [
+        Payload::ConfigureAdaptive(adaptive(reaction, stallguard_enable)),
+        Payload::Configure,
+        Payload::Activate,
+        Payload::SetTarget(target),
+    ];
+    for (msg_id, payload) in (1..).zip(commands) {
+        let header = Header { source_id: 0x0001, target_id: 0x0010, msg_id };
+        let reply = joint.handle_message(&Message { header, payload }).unwrap();
+        assert!(matches!(reply.payload, Payload::Ack(_)), "{:?}", reply.payload);
+    }
+    joint
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_stall_stops_the_joint_and_reports_it() {
+    use irpc::{
+        CollisionReaction, FaultCause, FaultReportPayload, Header, Joint, LifecycleState, Message, Payload,
+        StallStatus, ARM_DEVICE_ID, ERROR_LIMIT_VIOLATION, ERROR_UNKNOWN_COMMAND,
+    };
+
+    let configure = |joint: &mut Joint, config| {
+        let header = Header { source_id: 0x0001, target_id: 0x0010, msg_id: 100 };
+        joint.handle_message(&Message { header, payload: Payload::ConfigureAdaptive(config) }).unwrap().payload
+    };
+    // Only with adaptive control, and with sane settings
+    let mut config = adaptive(CollisionReaction::Stop, true);
+    assert!(matches!(configure(&mut Joint::new(0x0010), config), Payload::Nack { error: ERROR_UNKNOWN_COMMAND, .. }));
+    let mut joint = active_joint(CollisionReaction::Stop, true);
+    config.collision_backoff_deg = f32::NAN;
+    assert!(matches!(configure(&mut joint, config), Payload::Nack { error: ERROR_LIMIT_VIOLATION, .. }));
+    assert_eq!(joint.adaptive_config().unwrap().collision_backoff_deg, 5.0);
+
+    joint.update_load(StallStatus::Warning, 1.0, 1_000);
+    assert!(joint.poll(1_000).is_none());
+    joint.update_load(StallStatus::Stalled, 1.0, 2_000);
+    assert_eq!(joint.state(), LifecycleState::Active);
+    assert_eq!(joint.target().unwrap().target_angle, 10.0);
+    let report = joint.poll(2_000).unwrap();
+    assert_eq!(report.header.target_id, ARM_DEVICE_ID);
+    let expected = FaultReportPayload {
+        cause: FaultCause::Stall,
+        reaction: CollisionReaction::Stop,
+        position: 10.0,
+        current: 1.0,
+    };
+    assert!(matches!(report.payload, Payload::FaultReport(report) if report == expected));
+    assert!(joint.poll(3_000).is_none());
+
+    // Once per collision
+    joint.update_load(StallStatus::Stalled, 1.0, 4_000);
+    assert!(joint.poll(4_000).is_none());
+    joint.update_load(StallStatus::Normal, 0.2, 5_000);
+    joint.update_load(StallStatus::Stalled, 1.0, 6_000);
+    assert!(joint.poll(6_000).is_some());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_overcurrent_backs_off_or_goes_limp() {
+    use irpc::{CollisionReaction, FaultCause, LifecycleState, Payload, StallStatus, COLLISION_BACKOFF_VELOCITY_DPS};
+
+    // Without stallGuard a stall is ignored; the current must stay high
+    let mut joint = active_joint(CollisionReaction::BackOff, false);
+    joint.update_load(StallStatus::Stalled, 3.0, 0);
+    joint.update_load(StallStatus::Normal, 0.5, 10_000);
+    joint.update_load(StallStatus::Normal, 3.0, 20_000);
+    joint.update_load(StallStatus::Normal, 3.0, 39_000);
+    assert!(joint.poll(39_000).is_none());
+    joint.update_load(StallStatus::Normal, -3.0, 40_000);
+    let target = joint.target().unwrap();
+    // Heading for 90, so back towards 5
+    assert_eq!((target.target_angle, target.max_velocity), (5.0, COLLISION_BACKOFF_VELOCITY_DPS));
+    assert_eq!(joint.state(), LifecycleState::Active);
+    let Payload::FaultReport(report) = joint.poll(40_000).unwrap().payload else { panic!() };
+    assert_eq!((report.cause, report.reaction), (FaultCause::Overcurrent, CollisionReaction::BackOff));
+    assert_eq!(report.current, -3.0);
+
+    let mut joint = active_joint(CollisionReaction::GoLimp, true);
+    joint.update_load(StallStatus::Stalled, 0.5, 0);
+    assert_eq!(joint.state(), LifecycleState::Inactive);
+    let Payload::FaultReport(report) = joint.poll(0).unwrap().payload else { panic!() };
+    assert_eq!(report.reaction, CollisionReaction::GoLimp);
+
+    // Not while inactive, nor with no reaction configured
+    joint.update_load(StallStatus::Normal, 0.5, 1_000);
+    joint.update_load(StallStatus::Stalled, 0.5, 2_000);
+    assert!(joint.poll(2_000).is_none());
+    let mut joint = active_joint(CollisionReaction::None, true);
+    joint.update_load(StallStatus::Stalled, 3.0, 0);
+    joint.update_load(StallStatus::Stalled, 3.0, 100_000);
+    assert!(joint.poll(100_000).is_none());
+    assert_eq!(joint.target().unwrap().target_angle, 90.0);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_fault_reports_reach_the_host() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{
+        Capabilities, CollisionReaction, CommunicationManager, FaultCause, FaultReportPayload, Joint, JointProxy,
+        LifecycleState, ProtocolError, StallStatus,
+    };
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    let bus = Arc::new(SimBus::with_joints([0x0020]));
+    let mut joint = Joint::new(0x0010);
+    joint.set_capabilities(joint.capabilities() | Capabilities::ADAPTIVE_CONTROL);
+    bus.add_joint(joint);
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    comm.on_any({
+        let reports = reports.clone();
+        move |joint, report: FaultReportPayload| reports.lock().unwrap().push((joint, report.cause))
+    });
+
+    let joint = JointProxy::new(0x0010, comm.clone());
+    joint.configure_adaptive(adaptive(CollisionReaction::GoLimp, true)).await.unwrap();
+    joint.configure().await.unwrap();
+    joint.activate().await.unwrap();
+    bus.with_joint(0x0010, |joint| joint.update_load(StallStatus::Stalled, 1.2, 0));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(*reports.lock().unwrap(), [(0x0010, FaultCause::Stall)]);
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Inactive));
+
+    // The simulated joints have no adaptive control by default
+    let other = JointProxy::new(0x0020, comm.clone());
+    let refused = other.configure_adaptive(adaptive(CollisionReaction::Stop, true)).await;
+    assert!(matches!(refused, Err(ProtocolError::IoError(_))));
+}
@@ -0,0 +1,108 @@
+//! Tests for runtime node ID assignment
+
+#[cfg(feature = "joint_api")]
+use irpc::config_store::{ConfigStore, ConfigStoreError, JointConfig, MemoryConfigStore};
+#[cfg(feature = "joint_api")]
+use irpc::{AssignIdPayload, Header, Joint, Message, Payload, BROADCAST_ADDRESS, UNADDRESSED_DEVICE_ID};
+
+#[cfg(feature = "joint_api")]
+fn assign(msg_id: u32, serial_number: u64, new_id: u16) -> Message {
+    Message {
+        header: Header { source_id: 0x0001, target_id: BROADCAST_ADDRESS, msg_id },
+        payload: Payload::AssignId(AssignIdPayload { serial_number, new_id }),
+    }
+}
+
+#[cfg(feature = "joint_api")]
+fn command(target_id: u16, msg_id: u32, payload: Payload) -> Message {
+    Message { header: Header { source_id: 0x0001, target_id, msg_id }, payload }
+}
+
+/// Flash that has worn out
+#[cfg(feature = "joint_api")]
+struct BrokenStore;
+
+#[cfg(feature = "joint_api")]
+impl ConfigStore for BrokenStore {
+    fn read(&mut self, _buf: &mut [u8]) -> Result<(), ConfigStoreError> {
+        Err(ConfigStoreError::Io)
+    }
+
+    fn write(&mut self, _record: &[u8]) -> Result<(), ConfigStoreError> {
+        Err(ConfigStoreError::Io)
+    }
+
+    fn erase(&mut self) -> Result<(), ConfigStoreError> {
+        Err(ConfigStoreError::Io)
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_unaddressed_joint_only_answers_its_assignment() {
+    let mut store = MemoryConfigStore::new();
+    let mut joint = Joint::unaddressed(0xC0FF_EE00_0000_0042);
+    assert!(joint.is_unaddressed());
+    assert_eq!(joint.id(), UNADDRESSED_DEVICE_ID);
+
+    // Silent until addressed: no discovery reply, no direct commands
+    joint.handle_message(&command(BROADCAST_ADDRESS, 1, Payload::Discover));
+    assert!(joint.poll(u64::MAX / 2).is_none());
+    assert!(joint.handle_message(&command(UNADDRESSED_DEVICE_ID, 2, Payload::RequestStatus)).is_none());
+
+    // Someone else's serial number, then an ID no joint may take
+    assert!(joint.handle_with_store(&assign(3, 0x0042, 0x0020), &mut store).is_none());
+    assert!(joint.handle_with_store(&assign(4, 0xC0FF_EE00_0000_0042, BROADCAST_ADDRESS), &mut store).is_none());
+    assert!(joint.is_unaddressed());
+
+    let reply = joint.handle_with_store(&assign(5, 0xC0FF_EE00_0000_0042, 0x0020), &mut store).unwrap();
+    assert_eq!(reply.header.source_id, 0x0020);
+    assert!(matches!(reply.payload, Payload::Ack(5)));
+    assert_eq!(JointConfig::load(&mut store).unwrap().node_id, Some(0x0020));
+
+    // Addressed joints ignore further assignments
+    assert!(joint.handle_with_store(&assign(6, 0xC0FF_EE00_0000_0042, 0x0030), &mut store).is_none());
+    assert_eq!(joint.id(), 0x0020);
+
+    // After a power cycle the firmware starts unaddressed and restores the ID
+    let mut joint = Joint::unaddressed(0xC0FF_EE00_0000_0042);
+    joint.restore_config(&mut store).unwrap();
+    assert!(!joint.is_unaddressed());
+    assert!(joint.handle_message(&command(0x0020, 7, Payload::RequestStatus)).is_some());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_assignment_reports_store_failure() {
+    let mut joint = Joint::unaddressed(7);
+    let reply = joint.handle_with_store(&assign(1, 7, 0x0020), &mut BrokenStore).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { id: 1, error: irpc::ERROR_CONFIG_STORE }));
+    // The ID holds until reset
+    assert_eq!(reply.header.source_id, 0x0020);
+    assert_eq!(joint.id(), 0x0020);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_orchestrator_commissions_joints_on_sim() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{ArmOrchestrator, CommunicationManager, LifecycleState, ProtocolError};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::new());
+    bus.add_joint(Joint::unaddressed(1001));
+    bus.add_joint(Joint::unaddressed(1002));
+    let mut orchestrator = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+
+    assert!(matches!(
+        orchestrator.commission_joint(1001, UNADDRESSED_DEVICE_ID).await,
+        Err(ProtocolError::InvalidMessage)
+    ));
+    orchestrator.commission_joint(1001, 0x0010).await.unwrap();
+    orchestrator.commission_joint(1002, 0x0020).await.unwrap();
+    assert!(orchestrator.commission_joint(1003, 0x0030).await.is_err());
+
+    orchestrator.configure_all().await.into_result().unwrap();
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Inactive));
+    assert_eq!(bus.joint_state(0x0020), Some(LifecycleState::Inactive));
+}
@@ -0,0 +1,8 @@
+//! Fixtures shared by the joint tests
+
+use irpc::{Header, Message, Payload};
+
+/// A command from the arm controller to joint 0x0010
+pub fn command(msg_id: u32, payload: Payload) -> Message {
+    Message { header: Header { source_id: 0x0001, target_id: 0x0010, msg_id }, payload }
+}
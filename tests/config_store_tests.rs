@@ -0,0 +1,135 @@
+//! Tests for the persistent joint configuration
+
+#[cfg(feature = "joint_api")]
+mod common;
+
+#[cfg(feature = "joint_api")]
+use common::command;
+#[cfg(feature = "joint_api")]
+use irpc::config_store::{ConfigStore, ConfigStoreError, JointConfig, MemoryConfigStore};
+#[cfg(feature = "joint_api")]
+use irpc::{CalibrationConfidence, CalibrationResult, Joint, JointLimits, MotorParameters, Payload};
+
+#[cfg(feature = "joint_api")]
+fn limits() -> JointLimits {
+    JointLimits { min_position: -90.0, max_position: 90.0, max_velocity: 120.0 }
+}
+
+#[cfg(feature = "joint_api")]
+fn calibration() -> CalibrationResult {
+    CalibrationResult {
+        success: true,
+        parameters: MotorParameters {
+            inertia_J: 0.001,
+            torque_constant_kt: 0.15,
+            damping_b: 0.0005,
+            friction_coulomb: 0.02,
+            friction_stribeck: 0.01,
+            friction_vstribeck: 0.1,
+            friction_viscous: 0.001,
+            thermal_resistance: 2.5,
+            thermal_time_constant: 600.0,
+        },
+        confidence: CalibrationConfidence {
+            overall: 0.9,
+            inertia: 0.9,
+            friction: 0.9,
+            torque_constant: 0.9,
+            validation_rms: 0.01,
+        },
+        total_time: 30.0,
+        error_code: 0,
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_config_record_roundtrip_and_rejects_garbage() {
+    let mut store = MemoryConfigStore::new();
+    assert_eq!(JointConfig::load(&mut store).unwrap_err(), ConfigStoreError::NoConfig);
+
+    let config = JointConfig { node_id: Some(0x0023), limits: Some(limits()), ..Default::default() };
+    config.save(&mut store).unwrap();
+    let loaded = JointConfig::load(&mut store).unwrap();
+    assert_eq!(loaded.node_id, Some(0x0023));
+    assert_eq!(loaded.limits, Some(limits()));
+    assert!(loaded.motor_parameters.is_none());
+
+    // A flipped bit, as from a write torn by power loss
+    store.bytes_mut()[9] ^= 0x01;
+    assert_eq!(JointConfig::load(&mut store).unwrap_err(), ConfigStoreError::NoConfig);
+
+    store.erase().unwrap();
+    assert_eq!(JointConfig::load(&mut store).unwrap_err(), ConfigStoreError::NoConfig);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_saves_and_restores_config() {
+    let mut store = MemoryConfigStore::new();
+    let mut joint = Joint::new(0x0010);
+    joint.handle_message(&command(1, Payload::SetLimits(limits())));
+    joint.apply_calibration(&calibration());
+    assert!(joint.thermal_model().is_some());
+
+    // Plain handling has no store
+    let reply = joint.handle_message(&command(2, Payload::SaveConfig)).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { error: irpc::ERROR_UNKNOWN_COMMAND, .. }));
+    let reply = joint.handle_with_store(&command(3, Payload::SaveConfig), &mut store).unwrap();
+    assert!(matches!(reply.payload, Payload::Ack(3)));
+
+    // After a power cycle
+    let mut joint = Joint::new(0x0010);
+    assert!(joint.limits().is_none());
+    joint.restore_config(&mut store).unwrap();
+    assert_eq!(joint.limits(), Some(limits()));
+    assert_eq!(joint.motor_parameters().unwrap().torque_constant_kt, 0.15);
+    assert!(joint.thermal_model().is_some());
+
+    // Loading is refused in motion, factory reset drops everything
+    joint.handle_message(&command(4, Payload::Configure));
+    joint.handle_message(&command(5, Payload::Activate));
+    let reply = joint.handle_with_store(&command(6, Payload::FactoryReset), &mut store).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { id: 6, .. }));
+    joint.handle_message(&command(7, Payload::Deactivate));
+    let reply = joint.handle_with_store(&command(8, Payload::FactoryReset), &mut store).unwrap();
+    assert!(matches!(reply.payload, Payload::Ack(8)));
+    assert!(joint.limits().is_none() && joint.motor_parameters().is_none() && joint.thermal_model().is_none());
+    let reply = joint.handle_with_store(&command(9, Payload::LoadConfig), &mut store).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { id: 9, error: irpc::ERROR_CONFIG_STORE }));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_restored_node_id_replaces_default() {
+    let mut store = MemoryConfigStore::new();
+    JointConfig { node_id: Some(0x0030), ..Default::default() }.save(&mut store).unwrap();
+
+    let mut joint = Joint::new(0x0010);
+    joint.restore_config(&mut store).unwrap();
+    assert_eq!(joint.id(), 0x0030);
+    assert_eq!(joint.config().node_id, Some(0x0030));
+    assert!(joint.handle_with_store(&command(1, Payload::RequestStatus), &mut store).is_none());
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_proxy_config_commands_on_sim() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, JointProxy};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010]));
+    let proxy = JointProxy::new(0x0010, CommunicationManager::with_adapter(bus.clone()));
+    proxy.set_limits(limits()).await.unwrap();
+    proxy.save_config().await.unwrap();
+
+    let other = JointLimits { max_velocity: 10.0, ..limits() };
+    bus.with_joint(0x0010, |joint| joint.handle_message(&command(100, Payload::SetLimits(other))));
+    proxy.load_config().await.unwrap();
+    assert_eq!(bus.with_joint(0x0010, |joint| joint.limits()), Some(Some(limits())));
+
+    proxy.factory_reset().await.unwrap();
+    assert_eq!(bus.with_joint(0x0010, |joint| joint.limits()), Some(None));
+    assert!(proxy.load_config().await.is_err());
+}
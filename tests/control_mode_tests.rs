@@ -0,0 +1,174 @@
+//! Tests for the velocity and torque control modes
+
+#[cfg(feature = "joint_api")]
+mod joint {
+    use irpc::encoder::{Encoder, EncoderError};
+    use irpc::motor::{MotorDriver, MotorError};
+    use irpc::{Capabilities, Header, Joint, Message, MessageId, MultiTurnPosition, Payload};
+
+    /// Reads a fixed angle
+    pub struct At(pub f32);
+    impl Encoder for At {
+        fn read_position(&mut self) -> Result<MultiTurnPosition, EncoderError> {
+            Ok(MultiTurnPosition { turns: 0, angle: self.0 })
+        }
+        fn resolution(&self) -> u32 {
+            4096
+        }
+    }
+
+    /// Records the last commanded current
+    #[derive(Default)]
+    pub struct Driver(pub f32);
+    impl MotorDriver for Driver {
+        fn enable(&mut self) -> Result<(), MotorError> {
+            Ok(())
+        }
+        fn disable(&mut self) {}
+        fn set_voltage(&mut self, _volts: f32) -> Result<(), MotorError> {
+            Err(MotorError::Unsupported)
+        }
+        fn set_current(&mut self, amps: f32) -> Result<(), MotorError> {
+            self.0 = amps;
+            Ok(())
+        }
+    }
+
+    pub fn direct_joint() -> Joint {
+        let mut joint = Joint::new(0x0010);
+        joint.set_capabilities(joint.capabilities() | Capabilities::DIRECT_CONTROL);
+        joint
+    }
+
+    pub fn deliver(joint: &mut Joint, driver: &mut Driver, msg_id: MessageId, payload: Payload) -> Payload {
+        let msg = Message {
+            header: Header { source_id: 0x0001, target_id: 0x0010, msg_id },
+            payload,
+        };
+        let reply = joint.handle_motor(&msg, driver).or_else(|| joint.handle_message(&msg)).unwrap();
+        joint.sync_driver(driver);
+        reply.payload
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_velocity_mode_drives_the_velocity_loop() {
+    use irpc::{ControlGains, ControlMode, JointLimits, Payload, PidGains, ERROR_INVALID_STATE, ERROR_LIMIT_VIOLATION};
+    use joint::{deliver, direct_joint, At, Driver};
+
+    let mut driver = Driver::default();
+    let mut joint = direct_joint();
+    assert_eq!(joint.control_mode(), ControlMode::Position);
+    deliver(&mut joint, &mut driver, 1, Payload::Configure);
+    let mode = Payload::SetControlMode(ControlMode::Velocity);
+    assert!(matches!(deliver(&mut joint, &mut driver, 2, mode.clone()), Payload::Ack(2)));
+    let gains = ControlGains {
+        velocity: PidGains { kp: 0.02, ..Default::default() },
+        max_current: 5.0,
+        ..Default::default()
+    };
+    deliver(&mut joint, &mut driver, 3, Payload::ConfigureGains(gains));
+    let limits = JointLimits { min_position: -90.0, max_position: 90.0, max_velocity: 200.0 };
+    deliver(&mut joint, &mut driver, 4, Payload::SetLimits(limits));
+    deliver(&mut joint, &mut driver, 5, Payload::Activate);
+
+    // Switching is refused while Active, and so are position targets
+    assert!(matches!(deliver(&mut joint, &mut driver, 6, mode), Payload::Nack { id: 6, error: ERROR_INVALID_STATE }));
+    let target = Payload::SetTarget(irpc::SetTargetPayload { target_angle: 10.0, velocity_limit: 50.0 });
+    assert!(matches!(deliver(&mut joint, &mut driver, 7, target), Payload::Nack { id: 7, error: ERROR_INVALID_STATE }));
+    let too_fast = deliver(&mut joint, &mut driver, 8, Payload::SetVelocity { dps: 300.0 });
+    assert!(matches!(too_fast, Payload::Nack { error: ERROR_LIMIT_VIOLATION, .. }));
+    assert!(matches!(deliver(&mut joint, &mut driver, 9, Payload::SetVelocity { dps: 100.0 }), Payload::Ack(9)));
+    assert_eq!(joint.commanded_velocity(), 100.0);
+
+    joint.control_step(0.001, &mut At(0.0), &mut driver).unwrap();
+    assert!((driver.0 - 2.0).abs() < 1e-6, "{}", driver.0);
+    assert_eq!(joint.controller().unwrap().velocity_setpoint(), 100.0);
+
+    // At the upper limit the joint stops instead of pushing on (once the
+    // jump there has left the measured velocity)
+    joint.control_step(0.001, &mut At(90.0), &mut driver).unwrap();
+    joint.control_step(0.001, &mut At(90.0), &mut driver).unwrap();
+    assert!(driver.0.abs() < 1e-6, "{}", driver.0);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_torque_mode_commands_current_and_persists() {
+    use irpc::config_store::{JointConfig, MemoryConfigStore};
+    use irpc::{ControlGains, ControlMode, MotorParameters, Payload, ERROR_INVALID_STATE};
+    use joint::{deliver, direct_joint, At, Driver};
+
+    let mut driver = Driver::default();
+    let mut joint = direct_joint();
+    deliver(&mut joint, &mut driver, 1, Payload::Configure);
+    deliver(&mut joint, &mut driver, 2, Payload::SetControlMode(ControlMode::Torque));
+    let gains = ControlGains { max_current: 3.0, ..Default::default() };
+    deliver(&mut joint, &mut driver, 3, Payload::ConfigureGains(gains));
+    let early = deliver(&mut joint, &mut driver, 4, Payload::SetTorque { torque_nm: 0.3 });
+    assert!(matches!(early, Payload::Nack { id: 4, error: ERROR_INVALID_STATE }));
+
+    // Saved with the mode, restored with a torque constant
+    let mut store = MemoryConfigStore::new();
+    let parameters = MotorParameters {
+        inertia_J: 0.001,
+        torque_constant_kt: 0.15,
+        damping_b: 0.0,
+        friction_coulomb: 0.0,
+        friction_stribeck: 0.0,
+        friction_vstribeck: 0.0,
+        friction_viscous: 0.0,
+        thermal_resistance: 0.0,
+        thermal_time_constant: 0.0,
+    };
+    let mut restored = direct_joint();
+    deliver(&mut restored, &mut driver, 5, Payload::Configure);
+    deliver(&mut restored, &mut driver, 6, Payload::Activate);
+    // Still in position mode
+    let refused = deliver(&mut restored, &mut driver, 7, Payload::SetTorque { torque_nm: 0.3 });
+    assert!(matches!(refused, Payload::Nack { id: 7, error: ERROR_INVALID_STATE }));
+    JointConfig { motor_parameters: Some(parameters), ..joint.config() }.save(&mut store).unwrap();
+    restored.restore_config(&mut store).unwrap();
+    assert_eq!(restored.control_mode(), ControlMode::Torque);
+
+    let accepted = deliver(&mut restored, &mut driver, 8, Payload::SetTorque { torque_nm: 0.3 });
+    assert!(matches!(accepted, Payload::Ack(8)));
+    restored.control_step(0.001, &mut At(0.0), &mut driver).unwrap();
+    assert!((driver.0 - 2.0).abs() < 1e-6, "{}", driver.0);
+
+    // Bounded by the current limit
+    deliver(&mut restored, &mut driver, 9, Payload::SetTorque { torque_nm: -1.5 });
+    restored.control_step(0.001, &mut At(0.0), &mut driver).unwrap();
+    assert_eq!(driver.0, -3.0);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_proxy_direct_control() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, ControlMode, Joint, JointProxy, ProtocolError};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::new());
+    bus.add_joint(joint::direct_joint());
+    bus.add_joint(Joint::new(0x0011));
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    comm.discover().await.unwrap();
+    let proxy = JointProxy::new(0x0010, comm.clone());
+    let position_only = JointProxy::new(0x0011, comm.clone());
+
+    assert!(matches!(
+        position_only.set_control_mode(ControlMode::Velocity).await,
+        Err(ProtocolError::Unsupported)
+    ));
+    assert!(matches!(position_only.set_torque(0.1).await, Err(ProtocolError::Unsupported)));
+
+    proxy.configure().await.unwrap();
+    proxy.set_control_mode(ControlMode::Velocity).await.unwrap();
+    proxy.activate().await.unwrap();
+    proxy.set_velocity(-25.0).await.unwrap();
+    assert_eq!(bus.with_joint(0x0010, |joint| joint.commanded_velocity()), Some(-25.0));
+    assert!(matches!(proxy.set_torque(0.1).await, Err(ProtocolError::IoError(_))));
+    assert!(matches!(proxy.set_control_mode(ControlMode::Torque).await, Err(ProtocolError::IoError(_))));
+}
@@ -0,0 +1,233 @@
+//! Tests for the joint's position/velocity control loop
+
+#[cfg(feature = "joint_api")]
+mod common;
+
+#[cfg(feature = "joint_api")]
+use common::command;
+#[cfg(feature = "joint_api")]
+use irpc::control::{ControlError, Pid};
+#[cfg(feature = "joint_api")]
+use irpc::encoder::{Encoder, EncoderError};
+#[cfg(feature = "joint_api")]
+use irpc::motor::{MotorDriver, MotorError};
+#[cfg(feature = "joint_api")]
+use irpc::{
+    ControlGains, Joint, LifecycleState, Message, MultiTurnPosition, Payload, PidGains, SetTargetPayload,
+};
+#[cfg(feature = "joint_api")]
+use std::{cell::Cell, rc::Rc};
+
+/// A joint axis: current accelerates it
+#[cfg(feature = "joint_api")]
+#[derive(Default)]
+struct Plant {
+    position: Cell<f32>,
+    velocity: Cell<f32>,
+    current: Cell<f32>,
+}
+
+#[cfg(feature = "joint_api")]
+impl Plant {
+    /// Degrees/second² per ampere
+    const GAIN: f32 = 2000.0;
+
+    fn advance(&self, dt: f32) {
+        self.velocity.set(self.velocity.get() + Self::GAIN * self.current.get() * dt);
+        self.position.set(self.position.get() + self.velocity.get() * dt);
+    }
+}
+
+#[cfg(feature = "joint_api")]
+struct PlantEncoder(Rc<Plant>);
+
+#[cfg(feature = "joint_api")]
+impl Encoder for PlantEncoder {
+    fn read_position(&mut self) -> Result<MultiTurnPosition, EncoderError> {
+        Ok(MultiTurnPosition::from_degrees(self.0.position.get()))
+    }
+
+    fn read_velocity(&mut self) -> Option<f32> {
+        Some(self.0.velocity.get())
+    }
+
+    fn resolution(&self) -> u32 {
+        1 << 14
+    }
+}
+
+#[cfg(feature = "joint_api")]
+struct PlantDriver(Rc<Plant>);
+
+#[cfg(feature = "joint_api")]
+impl MotorDriver for PlantDriver {
+    fn enable(&mut self) -> Result<(), MotorError> {
+        Ok(())
+    }
+
+    fn disable(&mut self) {
+        self.0.current.set(0.0);
+    }
+
+    fn set_voltage(&mut self, _volts: f32) -> Result<(), MotorError> {
+        Err(MotorError::Unsupported)
+    }
+
+    fn set_current(&mut self, amps: f32) -> Result<(), MotorError> {
+        self.0.current.set(amps);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "joint_api")]
+fn gains() -> ControlGains {
+    ControlGains {
+        position: PidGains { kp: 5.0, ki: 0.0, kd: 0.0, integral_limit: 0.0 },
+        velocity: PidGains { kp: 0.05, ki: 0.5, kd: 0.0, integral_limit: 1.0 },
+        max_current: 2.0,
+    }
+}
+
+#[cfg(feature = "joint_api")]
+fn deliver(joint: &mut Joint, driver: &mut PlantDriver, msg: &Message) -> Payload {
+    let reply = joint.handle_motor(msg, driver).or_else(|| joint.handle_message(msg)).unwrap();
+    joint.sync_driver(driver);
+    reply.payload
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_pid_terms() {
+    let mut pid = Pid::new(PidGains { kp: 2.0, ki: 10.0, kd: 0.5, integral_limit: 1.5 });
+    // No derivative on the first update
+    assert_eq!(pid.update(1.0, 0.1), 2.0 + 1.0);
+    assert_eq!(pid.update(1.0, 0.1), 2.0 + 1.5);
+    // The integral stays clamped; the derivative follows the error
+    assert_eq!(pid.update(2.0, 0.1), 4.0 + 1.5 + 5.0);
+
+    pid.reset();
+    assert_eq!(pid.update(-1.0, 0.1), -2.0 - 1.0);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_configure_gains_is_validated() {
+    let mut joint = Joint::new(0x0010);
+    assert!(joint.controller().is_none());
+
+    let mut bad = gains();
+    bad.velocity.ki = -1.0;
+    assert!(matches!(
+        joint.handle_message(&command(1, Payload::ConfigureGains(bad))).unwrap().payload,
+        Payload::Nack { id: 1, error: irpc::ERROR_INVALID_GAINS }
+    ));
+    bad.velocity.ki = f32::NAN;
+    assert!(matches!(
+        joint.handle_message(&command(2, Payload::ConfigureGains(bad))).unwrap().payload,
+        Payload::Nack { id: 2, error: irpc::ERROR_INVALID_GAINS }
+    ));
+    assert!(joint.controller().is_none());
+
+    assert!(matches!(
+        joint.handle_message(&command(3, Payload::ConfigureGains(gains()))).unwrap().payload,
+        Payload::Ack(3)
+    ));
+    assert_eq!(joint.controller().unwrap().gains(), gains());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_control_step_moves_joint_to_target() {
+    const DT: f32 = 0.001;
+    let plant = Rc::new(Plant::default());
+    let mut encoder = PlantEncoder(plant.clone());
+    let mut driver = PlantDriver(plant.clone());
+    let mut joint = Joint::new(0x0010);
+
+    // Nothing is driven before the gains are set, nor while inactive
+    deliver(&mut joint, &mut driver, &command(1, Payload::Configure));
+    deliver(&mut joint, &mut driver, &command(2, Payload::Activate));
+    joint.control_step(DT, &mut encoder, &mut driver).unwrap();
+    assert!(joint.controller().is_none());
+    deliver(&mut joint, &mut driver, &command(3, Payload::Deactivate));
+    deliver(&mut joint, &mut driver, &command(4, Payload::ConfigureGains(gains())));
+    joint.control_step(DT, &mut encoder, &mut driver).unwrap();
+    assert_eq!(joint.controller().unwrap().current_setpoint(), 0.0);
+
+    deliver(&mut joint, &mut driver, &command(5, Payload::Activate));
+    let target = SetTargetPayload { target_angle: 30.0, velocity_limit: 50.0 };
+    assert!(matches!(deliver(&mut joint, &mut driver, &command(6, Payload::SetTarget(target))), Payload::Ack(6)));
+    assert_eq!(joint.state(), LifecycleState::Active);
+
+    let mut peak_velocity = 0.0f32;
+    for _ in 0..3000 {
+        joint.control_step(DT, &mut encoder, &mut driver).unwrap();
+        assert!(plant.current.get().abs() <= 2.0);
+        plant.advance(DT);
+        peak_velocity = peak_velocity.max(plant.velocity.get());
+    }
+    assert!((plant.position.get() - 30.0).abs() < 0.1, "position {}", plant.position.get());
+    assert!(peak_velocity < 55.0, "peak velocity {}", peak_velocity);
+
+    // Deactivating stops the drive
+    deliver(&mut joint, &mut driver, &command(7, Payload::Deactivate));
+    assert_eq!(
+        joint.command_motor(&mut driver, irpc::motor::MotorCommand::Current(1.0)),
+        Err(MotorError::Disabled)
+    );
+    joint.control_step(DT, &mut encoder, &mut driver).unwrap();
+    assert_eq!(plant.current.get(), 0.0);
+
+    // Activated without the driver being enabled
+    let mut joint = Joint::new(0x0010);
+    joint.handle_message(&command(8, Payload::Configure));
+    joint.handle_message(&command(9, Payload::Activate));
+    joint.handle_message(&command(10, Payload::ConfigureGains(gains())));
+    assert_eq!(
+        joint.control_step(DT, &mut encoder, &mut driver),
+        Err(ControlError::Motor(MotorError::Disabled))
+    );
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_reactivated_joint_holds_position() {
+    const DT: f32 = 0.001;
+    let plant = Rc::new(Plant::default());
+    let mut encoder = PlantEncoder(plant.clone());
+    let mut driver = PlantDriver(plant.clone());
+    let mut joint = Joint::new(0x0010);
+
+    deliver(&mut joint, &mut driver, &command(1, Payload::Configure));
+    deliver(&mut joint, &mut driver, &command(2, Payload::ConfigureGains(gains())));
+    deliver(&mut joint, &mut driver, &command(3, Payload::Activate));
+    let target = SetTargetPayload { target_angle: 90.0, velocity_limit: 50.0 };
+    deliver(&mut joint, &mut driver, &command(4, Payload::SetTarget(target)));
+    for _ in 0..200 {
+        joint.control_step(DT, &mut encoder, &mut driver).unwrap();
+        plant.advance(DT);
+    }
+
+    // Stopped on the way, then brought back up in both ways
+    let stops = [Payload::Reset, Payload::Deactivate];
+    for (i, stop) in stops.into_iter().enumerate() {
+        let msg_id = 10 * (i as u32 + 1);
+        deliver(&mut joint, &mut driver, &command(msg_id, stop));
+        assert!(joint.target().is_none());
+        plant.velocity.set(0.0);
+        let held = plant.position.get();
+        // The loop keeps running while the joint is stopped
+        joint.control_step(DT, &mut encoder, &mut driver).unwrap();
+        if joint.state() == LifecycleState::Unconfigured {
+            deliver(&mut joint, &mut driver, &command(msg_id + 1, Payload::Configure));
+        }
+        deliver(&mut joint, &mut driver, &command(msg_id + 2, Payload::Activate));
+        assert!(joint.target().is_none());
+
+        for _ in 0..1000 {
+            joint.control_step(DT, &mut encoder, &mut driver).unwrap();
+            plant.advance(DT);
+        }
+        assert!((plant.position.get() - held).abs() < 0.1, "position {}, held {}", plant.position.get(), held);
+    }
+}
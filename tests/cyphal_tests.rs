@@ -0,0 +1,150 @@
+//! Tests for the Cyphal/CAN framing
+
+#[cfg(feature = "cyphal")]
+use irpc::transport::cyphal::*;
+#[cfg(feature = "cyphal")]
+use irpc::transport::CanError;
+#[cfg(feature = "cyphal")]
+use irpc::{Header, Message, Payload, TelemetryStream, Warnings};
+
+#[cfg(feature = "cyphal")]
+fn message(source_id: u16, target_id: u16, payload: Payload) -> Message {
+    Message { header: Header { source_id, target_id, msg_id: 42 }, payload }
+}
+
+#[cfg(feature = "cyphal")]
+fn telemetry() -> Payload {
+    Payload::TelemetryStream(TelemetryStream {
+        timestamp_us: 123_456_789,
+        position: 1.5,
+        velocity: -0.25,
+        acceleration: 3.0,
+        current_d: 0.1,
+        current_q: 2.2,
+        voltage_d: 0.5,
+        voltage_q: 12.0,
+        torque_estimate: 0.8,
+        power: 26.4,
+        load_percent: 40.0,
+        foc_loop_time_us: 25,
+        temperature_c: 45.5,
+        warnings: Warnings::NONE,
+        trajectory_active: true,
+        brake_engaged: false,
+    })
+}
+
+#[cfg(feature = "cyphal")]
+#[test]
+fn test_cyphal_identifiers() {
+    let ports = CyphalPorts { subject_id: 4000, service_id: 200 };
+
+    // Configure: priority 4, service request 200 from node 1 to node 16
+    let id = CyphalId::for_message(&message(0x0001, 0x0010, Payload::Configure), &ports).unwrap();
+    assert_eq!(id.to_raw(), 0x1332_0801);
+    assert_eq!(CyphalId::from_raw(id.to_raw()), Some(id));
+
+    let ack = CyphalId::for_message(&message(0x0010, 0x0001, Payload::Ack(42)), &ports).unwrap();
+    assert_eq!(ack.kind, TransferKind::Response { service_id: 200, destination: 0x01 });
+
+    let discover = CyphalId::for_message(&message(0x0001, 0x0000, Payload::Discover), &ports).unwrap();
+    assert_eq!(discover.kind, TransferKind::Message { subject_id: 4000 });
+    assert_eq!(discover.to_raw() & (0b11 << 21), 0b11 << 21, "reserved bits set");
+    assert_eq!(CyphalId::from_raw(discover.to_raw()), Some(discover));
+
+    let wide = message(0x0080, 0x0001, Payload::Ack(1));
+    assert!(matches!(CyphalId::for_message(&wide, &ports), Err(CanError::AddressOutOfRange)));
+
+    // CRC-16/CCITT-FALSE check value
+    assert_eq!(crc16(0xFFFF, b"123456789"), 0x29B1);
+}
+
+#[cfg(feature = "cyphal")]
+#[test]
+fn test_cyphal_single_frame() {
+    let mut encoder = CyphalEncoder::new(CyphalPorts::default());
+    let mut reassembler = CyphalReassembler::new(CyphalPorts::default());
+
+    for transfer_id in 0..2u8 {
+        let frames: Vec<_> = encoder.encode(&message(0x0010, 0x0001, Payload::Ack(42)), 8).unwrap().collect();
+        assert_eq!(frames.len(), 1);
+        let tail = TailByte::from_byte(*frames[0].data().last().unwrap());
+        assert_eq!(tail, TailByte { start: true, end: true, toggle: true, transfer_id });
+
+        let decoded = reassembler.push(frames[0].raw_id(), frames[0].data()).unwrap().unwrap();
+        assert_eq!(decoded.header.source_id, 0x0010);
+        assert_eq!(decoded.header.target_id, 0x0001);
+        assert!(matches!(decoded.payload, Payload::Ack(42)));
+    }
+}
+
+#[cfg(feature = "cyphal")]
+#[test]
+fn test_cyphal_multi_frame_classic() {
+    let mut encoder = CyphalEncoder::new(CyphalPorts::default());
+    let mut reassembler = CyphalReassembler::new(CyphalPorts::default());
+
+    let frames: Vec<_> = encoder.encode(&message(0x0010, 0x0001, telemetry()), 8).unwrap().collect();
+    assert!(frames.len() > 2);
+    for (i, frame) in frames.iter().enumerate() {
+        let tail = TailByte::from_byte(*frame.data().last().unwrap());
+        assert_eq!(tail.start, i == 0);
+        assert_eq!(tail.end, i == frames.len() - 1);
+        assert_eq!(tail.toggle, i % 2 == 0, "toggle starts set and alternates");
+        assert!(frame.data().len() <= 8);
+    }
+
+    let (last, rest) = frames.split_last().unwrap();
+    for frame in rest {
+        assert!(reassembler.push(frame.raw_id(), frame.data()).unwrap().is_none());
+    }
+    let decoded = reassembler.push(last.raw_id(), last.data()).unwrap().unwrap();
+    assert_eq!(decoded.header.msg_id, 42);
+    assert!(matches!(decoded.payload, Payload::TelemetryStream(t) if t.timestamp_us == 123_456_789));
+}
+
+#[cfg(feature = "cyphal")]
+#[test]
+fn test_cyphal_canfd_padding() {
+    let mut encoder = CyphalEncoder::new(CyphalPorts::default());
+    let mut reassembler = CyphalReassembler::new(CyphalPorts::default());
+
+    // Smaller CAN-FD frames force a padded multi-frame transfer
+    for mtu in [12, 16, 20, 24, 32, 48, 64] {
+        let frames: Vec<_> = encoder.encode(&message(0x0010, 0x0001, telemetry()), mtu).unwrap().collect();
+        let mut decoded = None;
+        for frame in &frames {
+            assert!([1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64].contains(&frame.data().len()));
+            decoded = reassembler.push(frame.raw_id(), frame.data()).unwrap();
+        }
+        assert!(matches!(decoded.unwrap().payload, Payload::TelemetryStream(_)), "mtu {mtu}");
+    }
+    assert!(matches!(encoder.encode(&message(0x0010, 0x0001, Payload::Ack(1)), 10), Err(CanError::InvalidConfig)));
+}
+
+#[cfg(feature = "cyphal")]
+#[test]
+fn test_cyphal_rejects_broken_transfers() {
+    let mut encoder = CyphalEncoder::new(CyphalPorts::default());
+    let mut reassembler = CyphalReassembler::new(CyphalPorts::default());
+    let frames: Vec<_> = encoder.encode(&message(0x0010, 0x0001, telemetry()), 8).unwrap().collect();
+
+    // Corrupted payload byte
+    let mut corrupted = frames.clone();
+    let mut data = corrupted[1].data().to_vec();
+    data[0] ^= 0xFF;
+    let mut result = Ok(None);
+    for (i, frame) in corrupted.drain(..).enumerate() {
+        let bytes = if i == 1 { &data[..] } else { frame.data() };
+        result = reassembler.push(frame.raw_id(), bytes);
+    }
+    assert!(matches!(result, Err(CanError::CrcMismatch)));
+
+    // Lost frame: the toggle bit no longer matches
+    reassembler.push(frames[0].raw_id(), frames[0].data()).unwrap();
+    assert!(matches!(reassembler.push(frames[2].raw_id(), frames[2].data()), Err(CanError::FragmentLost)));
+
+    // Transfers on other ports belong to Cyphal nodes
+    let heartbeat = CyphalId { priority: 4, kind: TransferKind::Message { subject_id: 7509 }, source: 5 };
+    assert!(reassembler.push(heartbeat.to_raw(), &[0, 0, 0, 0, 0, 0, 0, 0xE0]).unwrap().is_none());
+}
@@ -0,0 +1,88 @@
+//! Tests for joint descriptors and names
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_add_joint_with_descriptor() {
+    use irpc::{AngleUnit, ArmOrchestrator, JointDescriptor};
+
+    let mut arm = ArmOrchestrator::new();
+    arm.add_joint_with(JointDescriptor {
+        entity_type: Some(0x1001),
+        gear_ratio: 50.0,
+        units: AngleUnit::Radians,
+        ..JointDescriptor::named(0x0030, "elbow")
+    });
+    arm.add_joint(0x0010);
+
+    let elbow = arm.descriptor(0x0030).unwrap();
+    assert_eq!(elbow.gear_ratio, 50.0);
+    assert!(elbow.accepts_entity_type(0x1001));
+    assert!(!elbow.accepts_entity_type(0x2001));
+    assert!((elbow.units.to_degrees(std::f32::consts::PI) - 180.0).abs() < 1e-4);
+    assert_eq!(AngleUnit::Degrees.from_degrees(90.0), 90.0);
+    assert_eq!(arm.get_joint(0x0030).unwrap().label(), "elbow (48)");
+
+    // Unnamed joints are labelled by ID; only the arm's joints take names
+    assert_eq!(arm.descriptor(0x0010), Some(&JointDescriptor::new(0x0010)));
+    assert_eq!(arm.get_joint(0x0010).unwrap().label(), "16");
+    arm.set_joint_name(0x0010, "base");
+    assert_eq!(arm.get_joint(0x0010).unwrap().label(), "base (16)");
+    arm.set_joint_name(0x0050, "gripper");
+    assert!(arm.joint_name(0x0050).is_none());
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test]
+async fn test_telemetry_samples_carry_joint_names() {
+    use irpc::{
+        ArmOrchestrator, CommunicationManager, Header, JointDescriptor, LifecycleState, Message, Payload,
+        TelemetryFilter,
+    };
+    use std::sync::Arc;
+
+    let comm = Arc::new(CommunicationManager::new());
+    let mut arm = ArmOrchestrator::with_comm_manager(comm.clone());
+    arm.add_joint_with(JointDescriptor::named(0x0030, "elbow"));
+    arm.add_joint(0x0010);
+    let mut telemetry = comm.subscribe_telemetry(TelemetryFilter::default());
+    let status = |joint| Message {
+        header: Header { source_id: joint, target_id: 0x0001, msg_id: 0 },
+        payload: Payload::JointStatus { state: LifecycleState::Active, error_code: 0 },
+    };
+
+    comm.process_incoming(status(0x0030)).await;
+    comm.process_incoming(status(0x0010)).await;
+    assert_eq!(telemetry.recv().await.unwrap().joint_name.as_deref(), Some("elbow"));
+    assert!(telemetry.recv().await.unwrap().joint_name.is_none());
+    assert_eq!(comm.joint_label(0x0030), "elbow (48)");
+}
+
+#[cfg(feature = "json")]
+#[tokio::test]
+async fn test_descriptor_in_arm_config() {
+    use irpc::arm_config::{ArmConfig, JointEntry};
+    use irpc::{AngleUnit, ArmOrchestrator, JointDescriptor};
+
+    let text = r#"{
+        "joints": [
+            { "joint_id": 48, "name": "elbow", "entity_type": 4097, "gear_ratio": 50.0, "units": "radians" },
+            { "joint_id": 16 }
+        ]
+    }"#;
+    let config = ArmConfig::from_json(text).unwrap();
+    let elbow = &config.joint(0x0030).unwrap().descriptor;
+    assert_eq!(elbow.entity_type, Some(0x1001));
+    assert_eq!(elbow.units, AngleUnit::Radians);
+    assert_eq!(config.joint(0x0010), Some(&JointEntry::new(0x0010)));
+
+    // Defaults are left out of the saved file
+    let saved = ArmConfig { joints: vec![JointEntry::new(0x0010)], arm_model: None }.to_json().unwrap();
+    assert!(!saved.contains("gear_ratio") && !saved.contains("units"));
+
+    let mut arm = ArmOrchestrator::new();
+    arm.add_joint_with(JointDescriptor::named(0x0030, "forearm"));
+    arm.apply_config(&config).await.unwrap();
+    assert_eq!(arm.descriptor(0x0030), Some(elbow));
+    assert_eq!(arm.joint_name(0x0030), Some("elbow"));
+    assert_eq!(arm.get_joint(0x0030).unwrap().label(), "elbow (48)");
+}
@@ -0,0 +1,180 @@
+//! Tests for unsolicited message handlers
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_handlers_by_payload_and_joint() {
+    use irpc::dispatch::StatusReport;
+    use irpc::{CommunicationManager, EncoderTelemetry, Header, LifecycleState, Message, Payload};
+    use std::sync::{Arc, Mutex};
+
+    let comm = CommunicationManager::deterministic(21);
+    type Seen = Arc<Mutex<Vec<(&'static str, u16)>>>;
+    fn record<T>(seen: &Seen, tag: &'static str) -> impl Fn(u16, T) + Send + Sync + 'static {
+        let seen = seen.clone();
+        move |joint, _| seen.lock().unwrap().push((tag, joint))
+    }
+    let seen = Seen::default();
+    comm.on::<EncoderTelemetry>(0x0010, record(&seen, "encoder"));
+    let faults = comm.on_any::<StatusReport>({
+        let seen = seen.clone();
+        move |joint, status: StatusReport| {
+            if status.error_code != 0 {
+                seen.lock().unwrap().push(("fault", joint));
+            }
+        }
+    });
+    comm.on_any::<Payload>(record(&seen, "any"));
+
+    let from = |source_id, msg_id, payload| Message {
+        header: Header { source_id, target_id: 0x0001, msg_id },
+        payload,
+    };
+    let encoder = Payload::Encoder(EncoderTelemetry { position: 1.0, velocity: 0.0 });
+    let fault = Payload::JointStatus { state: LifecycleState::Error, error_code: 0x0102 };
+    comm.process_incoming(from(0x0010, 7000, encoder.clone())).await;
+    comm.process_incoming(from(0x0020, 7001, encoder)).await;
+    comm.process_incoming(from(0x0020, 7002, fault.clone())).await;
+    assert!(comm.remove_handler(faults));
+    assert!(!comm.remove_handler(faults));
+    comm.process_incoming(from(0x0010, 7003, fault)).await;
+
+    // A reply goes to its request only
+    let request = comm.send_request(0x0010, Payload::RequestStatus).await.unwrap();
+    let msg_id = request.msg_id();
+    comm.process_incoming(from(0x0010, msg_id, Payload::Ack(msg_id))).await;
+    assert!(request.response(std::time::Duration::from_secs(1)).await.is_ok());
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            ("encoder", 0x0010),
+            ("any", 0x0010),
+            ("any", 0x0020),
+            ("fault", 0x0020),
+            ("any", 0x0020),
+            ("any", 0x0010),
+        ]
+    );
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_messages_sharing_a_request_id_are_not_its_reply() {
+    use irpc::{CommunicationManager, Header, LifecycleState, Message, Payload, TelemetryStream, Warnings};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    let comm = CommunicationManager::deterministic(23);
+    let samples = Arc::new(Mutex::new(Vec::new()));
+    comm.on(0x0010, {
+        let samples = samples.clone();
+        move |joint, stream: TelemetryStream| samples.lock().unwrap().push((joint, stream.position))
+    });
+    let request = comm.send_request(0x0010, Payload::RequestStatus).await.unwrap();
+    let msg_id = request.msg_id();
+    let from = |source_id, payload| Message { header: Header { source_id, target_id: 0x0001, msg_id }, payload };
+
+    // The joint's own telemetry counter caught up with the request ID
+    let stream = TelemetryStream {
+        timestamp_us: 1_000,
+        position: 12.5,
+        velocity: 0.0,
+        acceleration: 0.0,
+        current_d: 0.0,
+        current_q: 0.0,
+        voltage_d: 0.0,
+        voltage_q: 0.0,
+        torque_estimate: 0.0,
+        power: 0.0,
+        load_percent: 0.0,
+        foc_loop_time_us: 25,
+        temperature_c: 30.0,
+        warnings: Warnings::NONE,
+        trajectory_active: false,
+        brake_engaged: false,
+    };
+    comm.process_incoming(from(0x0010, Payload::TelemetryStream(stream))).await;
+    // Another joint answering a request of its own
+    let other = Payload::JointStatus { state: LifecycleState::Inactive, error_code: 0 };
+    comm.process_incoming(from(0x0020, other)).await;
+    assert_eq!(comm.pending_requests().await, 1);
+
+    let status = Payload::JointStatus { state: LifecycleState::Active, error_code: 0 };
+    comm.process_incoming(from(0x0010, status)).await;
+    let reply = request.response(Duration::from_secs(1)).await.unwrap();
+    assert!(matches!(reply.payload, Payload::JointStatus { state: LifecycleState::Active, .. }));
+    assert_eq!(*samples.lock().unwrap(), [(0x0010, 12.5)]);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_telemetry_reaches_joint_handler() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, JointProxy, TelemetryStream};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0020]).with_telemetry(Duration::from_millis(10)));
+    let comm = CommunicationManager::with_adapter(bus);
+    let samples = Arc::new(Mutex::new(Vec::new()));
+    comm.on(0x0010, {
+        let samples = samples.clone();
+        move |joint, stream: TelemetryStream| samples.lock().unwrap().push((joint, stream.position))
+    });
+    for id in [0x0010, 0x0020] {
+        let joint = JointProxy::new(id, comm.clone());
+        joint.configure().await.unwrap();
+        joint.activate().await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let samples = samples.lock().unwrap();
+    assert!(samples.len() >= 5, "only {} samples", samples.len());
+    assert!(samples.iter().all(|(joint, _)| *joint == 0x0010));
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_calibration_and_boot_reports_reach_handlers() {
+    use irpc::{
+        BootPayload, CalibrationEvent, CalibrationPhase, CalibrationStatus, CommunicationManager, Header, Message,
+        Payload,
+    };
+    use std::sync::{Arc, Mutex};
+
+    let comm = Arc::new(CommunicationManager::deterministic(22));
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    comm.on(0x0010, {
+        let progress = progress.clone();
+        move |_, status: CalibrationStatus| progress.lock().unwrap().push(status.progress)
+    });
+    // Handlers may register others while running
+    let boots = Arc::new(Mutex::new(Vec::new()));
+    comm.on_any({
+        let boots = boots.clone();
+        let registrar = Arc::downgrade(&comm);
+        move |joint, boot: BootPayload| {
+            boots.lock().unwrap().push((joint, boot.entity_type));
+            registrar.upgrade().unwrap().on::<BootPayload>(joint, |_, _| {});
+        }
+    });
+    let mut calibration = comm.subscribe_calibration();
+
+    let status = CalibrationStatus {
+        phase: CalibrationPhase::FrictionTest,
+        progress: 0.25,
+        time_remaining: 3.0,
+        current_position: 0.0,
+        current_velocity: 0.0,
+        current_iq: 0.0,
+    };
+    let from = |source_id, payload| Message { header: Header { source_id, target_id: 0x0001, msg_id: 9000 }, payload };
+    comm.process_incoming(from(0x0010, Payload::CalibrationStatus(status))).await;
+    comm.process_incoming(from(0x0020, Payload::CalibrationStatus(status))).await;
+    comm.process_incoming(from(0x0020, Payload::Boot(BootPayload { entity_type: 0x1001, crash: None }))).await;
+
+    assert_eq!(*progress.lock().unwrap(), [0.25]);
+    assert_eq!(*boots.lock().unwrap(), [(0x0020, 0x1001)]);
+    // Subscribers still get the report
+    assert!(matches!(calibration.try_recv(), Ok(CalibrationEvent::Progress { joint_id: 0x0010, .. })));
+}
@@ -0,0 +1,96 @@
+//! Tests for hosting the arm and joints in one binary (the `sim` feature)
+#![cfg(all(feature = "arm_api", feature = "joint_api"))]
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use irpc::bus::sim::SimBus;
+use irpc::transport::{channel_pair, ChannelEnd};
+use irpc::{
+    ArmOrchestrator, CommunicationAdapter, CommunicationManager, DeviceInfo, Joint, JointProxy, LifecycleState,
+    Message, ProtocolError, TransportLayer,
+};
+
+/// Adapter reaching a firmware `Joint` through the joint-side transport layer,
+/// the way it would run on a microcontroller at the other end of the bus
+struct FirmwareLink {
+    arm: Mutex<TransportLayer<ChannelEnd>>,
+    joint: Mutex<(Joint, TransportLayer<ChannelEnd>)>,
+}
+
+impl FirmwareLink {
+    fn new(joint_id: u16) -> Self {
+        let (arm, joint) = channel_pair();
+        Self {
+            arm: Mutex::new(TransportLayer::new(arm)),
+            joint: Mutex::new((Joint::new(joint_id), TransportLayer::new(joint))),
+        }
+    }
+
+    fn joint_state(&self) -> LifecycleState {
+        self.joint.lock().unwrap().0.state()
+    }
+}
+
+#[async_trait]
+impl CommunicationAdapter for FirmwareLink {
+    type Error = ProtocolError;
+
+    async fn transmit(&self, message: &Message) -> Result<(), Self::Error> {
+        self.arm.lock().unwrap().send_message(message)?;
+        // Run the firmware's receive loop until the frame is handled
+        let (joint, transport) = &mut *self.joint.lock().unwrap();
+        while joint.process_transport(transport)? {}
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<Option<Message>, Self::Error> {
+        Ok(self.arm.lock().unwrap().receive_message()?)
+    }
+
+    async fn discover_devices(&self) -> Result<Vec<DeviceInfo>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+}
+
+#[tokio::test]
+async fn test_arm_drives_firmware_joint_in_process() {
+    let link = Arc::new(FirmwareLink::new(0x0010));
+    let joint = JointProxy::new(0x0010, CommunicationManager::with_adapter(link.clone()));
+
+    joint.configure().await.unwrap();
+    assert_eq!(link.joint_state(), LifecycleState::Inactive);
+    joint.activate().await.unwrap();
+    assert_eq!(link.joint_state(), LifecycleState::Active);
+    joint.set_target(45.0, 30.0).await.unwrap();
+
+    // Rejected transitions come back from the firmware as errors
+    joint.deactivate().await.unwrap();
+    assert!(joint.activate().await.is_ok());
+    assert!(joint.configure().await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_orchestrator_runs_simulated_joints() {
+    let ids = [0x0010, 0x0020, 0x0030];
+    let bus = Arc::new(SimBus::with_joints(ids));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    for id in ids {
+        arm.add_joint(id);
+    }
+
+    assert!(arm.configure_all().await.is_success());
+    assert!(arm.activate_all().await.is_success());
+    for id in ids {
+        assert_eq!(bus.joint_state(id), Some(LifecycleState::Active));
+        // The simulated joints are the firmware's own state machine
+        assert_eq!(bus.with_joint(id, |joint| joint.id()), Some(id));
+    }
+
+    assert!(arm.emergency_stop().await.is_success());
+    assert!(ids.iter().all(|&id| bus.joint_state(id) != Some(LifecycleState::Active)));
+}
@@ -0,0 +1,83 @@
+//! Tests for encoder offset and multi-turn position
+
+use irpc::MultiTurnPosition;
+
+#[cfg(feature = "joint_api")]
+mod common;
+
+#[test]
+fn test_multi_turn_position_arithmetic() {
+    let p = MultiTurnPosition::from_degrees(725.0);
+    assert_eq!(p, MultiTurnPosition { turns: 2, angle: 5.0 });
+    assert_eq!(p.degrees(), 725.0);
+    assert_eq!(MultiTurnPosition::from_degrees(-10.0), MultiTurnPosition { turns: -1, angle: 350.0 });
+    assert_eq!(MultiTurnPosition::from_degrees(-360.0), MultiTurnPosition { turns: -1, angle: 0.0 });
+
+    // Exact far from zero, where an f32 of degrees has lost the fraction
+    let raw = MultiTurnPosition { turns: 100_000, angle: 12.5 };
+    let zero = MultiTurnPosition { turns: 99_999, angle: 350.25 };
+    assert_eq!(raw.relative_to(zero), MultiTurnPosition { turns: 0, angle: 22.25 });
+    assert_eq!(zero.relative_to(raw), MultiTurnPosition { turns: -1, angle: 337.75 });
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_applies_and_persists_offset() {
+    use irpc::config_store::MemoryConfigStore;
+    use irpc::{Joint, JointLimits, Payload, ERROR_POSITION_UNKNOWN};
+    use common::command;
+
+    let mut store = MemoryConfigStore::new();
+    let mut joint = Joint::new(0x0010);
+    let reply = joint.handle_message(&command(1, Payload::RequestMultiTurnPosition)).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { id: 1, error: ERROR_POSITION_UNKNOWN }));
+
+    joint.update_encoder(MultiTurnPosition { turns: 3, angle: 90.0 });
+    let limits = JointLimits { min_position: -90.0, max_position: 90.0, max_velocity: 50.0 };
+    joint.handle_message(&command(2, Payload::SetLimits(limits)));
+    let offset = MultiTurnPosition { turns: 3, angle: 0.0 };
+    let reply = joint.handle_message(&command(3, Payload::SetEncoderOffset(offset))).unwrap();
+    assert!(matches!(reply.payload, Payload::Ack(3)));
+    assert!(joint.limits().is_none());
+    let reply = joint.handle_message(&command(4, Payload::RequestMultiTurnPosition)).unwrap();
+    assert!(matches!(reply.payload, Payload::MultiTurnPosition(MultiTurnPosition { turns: 0, angle: 90.0 })));
+
+    // Not while the joint may be moving
+    joint.handle_message(&command(5, Payload::Configure));
+    joint.handle_message(&command(6, Payload::Activate));
+    let reply = joint.handle_message(&command(7, Payload::SetEncoderOffset(Default::default()))).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { id: 7, error: 4 }));
+
+    // Survives a power cycle once saved
+    joint.handle_with_store(&command(8, Payload::SaveConfig), &mut store);
+    let mut joint = Joint::new(0x0010);
+    joint.restore_config(&mut store).unwrap();
+    assert_eq!(joint.encoder_offset(), offset);
+    joint.update_encoder(MultiTurnPosition { turns: 2, angle: 270.0 });
+    assert_eq!(joint.position(), Some(MultiTurnPosition { turns: -1, angle: 270.0 }));
+
+    joint.report_encoder_fault();
+    assert_eq!(joint.position(), None);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_proxy_offset_on_sim() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, JointProxy};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010]));
+    let proxy = JointProxy::new(0x0010, CommunicationManager::with_adapter(bus.clone()));
+    proxy.set_encoder_offset(MultiTurnPosition { turns: 1, angle: 0.0 }).await.unwrap();
+    assert_eq!(proxy.read_multi_turn_position().await.unwrap(), MultiTurnPosition { turns: -1, angle: 0.0 });
+
+    // Targets are in the offset frame
+    proxy.configure().await.unwrap();
+    proxy.activate().await.unwrap();
+    proxy.set_target(10.0, 1000.0).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    assert_eq!(bus.true_position(0x0010), Some(370.0));
+    let position = proxy.read_multi_turn_position().await.unwrap();
+    assert_eq!(position, MultiTurnPosition { turns: 0, angle: 10.0 });
+}
@@ -0,0 +1,156 @@
+//! Tests for the encoder abstraction
+
+#[cfg(feature = "joint_api")]
+use irpc::encoder::{Encoder, EncoderError, EncoderFlags, QuadratureCounter, QuadratureEncoder};
+#[cfg(feature = "joint_api")]
+use irpc::{Joint, MultiTurnPosition};
+#[cfg(feature = "joint_api")]
+use std::{cell::Cell, rc::Rc};
+
+/// A timer counter the test winds by hand
+#[cfg(feature = "joint_api")]
+struct Counter(Rc<Cell<u16>>);
+
+#[cfg(feature = "joint_api")]
+impl QuadratureCounter for Counter {
+    fn count(&mut self) -> u16 {
+        self.0.get()
+    }
+}
+
+/// An encoder the test moves and breaks by hand
+#[cfg(feature = "joint_api")]
+struct Scripted(Rc<Cell<Option<MultiTurnPosition>>>);
+
+#[cfg(feature = "joint_api")]
+impl Encoder for Scripted {
+    fn read_position(&mut self) -> Result<MultiTurnPosition, EncoderError> {
+        self.0.get().ok_or(EncoderError::Communication)
+    }
+
+    fn resolution(&self) -> u32 {
+        4096
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_quadrature_counts_turns_across_wraps() {
+    let count = Rc::new(Cell::new(0u16));
+    let mut encoder = QuadratureEncoder::new(Counter(count.clone()), 4000);
+    assert_eq!(encoder.resolution(), 4000);
+    assert_eq!(encoder.read_position().unwrap(), MultiTurnPosition { turns: 0, angle: 0.0 });
+
+    // Backwards through the counter's wrap
+    count.set(count.get().wrapping_sub(1000));
+    assert_eq!(encoder.read_position().unwrap(), MultiTurnPosition { turns: -1, angle: 270.0 });
+
+    // Forwards over many turns, read often enough
+    for _ in 0..20 {
+        count.set(count.get().wrapping_add(10_000));
+        encoder.read_position().unwrap();
+    }
+    assert_eq!(encoder.read_position().unwrap(), MultiTurnPosition { turns: 49, angle: 270.0 });
+    assert!(encoder.error_flags().is_empty());
+
+    // Half the counter range at once cannot be told from backwards
+    count.set(count.get().wrapping_add(32_768));
+    encoder.read_position().unwrap();
+    assert!(encoder.error_flags().contains(EncoderFlags::OVERSPEED));
+    assert!(encoder.error_flags().is_empty());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_samples_encoder() {
+    let reading = Rc::new(Cell::new(Some(MultiTurnPosition { turns: 2, angle: 350.0 })));
+    let mut encoder = Scripted(reading.clone());
+    let mut joint = Joint::new(0x0010);
+    assert!(joint.encoder_telemetry().is_none());
+
+    joint.sample_encoder(&mut encoder, 1_000).unwrap();
+    assert_eq!(joint.velocity(), 0.0);
+    reading.set(Some(MultiTurnPosition { turns: 3, angle: 10.0 }));
+    joint.sample_encoder(&mut encoder, 11_000).unwrap();
+    // 20 degrees in 10 ms, across the turn
+    assert!((joint.velocity() - 2000.0).abs() < 0.1);
+    let telemetry = joint.encoder_telemetry().unwrap();
+    assert_eq!(telemetry.position, 1090.0);
+
+    // A failed read leaves the position unknown until re-homed
+    reading.set(None);
+    assert_eq!(joint.sample_encoder(&mut encoder, 12_000), Err(EncoderError::Communication));
+    assert!(!joint.position_known());
+    assert!(joint.encoder_telemetry().is_none());
+    reading.set(Some(MultiTurnPosition { turns: 3, angle: 10.0 }));
+    joint.sample_encoder(&mut encoder, 13_000).unwrap();
+    assert!(joint.position().is_none());
+}
+
+/// AS5047P on a mock SPI bus: answers each frame with the previous command's
+/// register, with even parity
+#[cfg(feature = "mcp2518fd")]
+struct MockAs5047 {
+    angle: u16,
+    diagnostics: u16,
+    pending: u16,
+    corrupt: bool,
+}
+
+#[cfg(feature = "mcp2518fd")]
+impl embedded_hal::spi::ErrorType for MockAs5047 {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "mcp2518fd")]
+impl embedded_hal::spi::SpiDevice for MockAs5047 {
+    fn transaction(&mut self, operations: &mut [embedded_hal::spi::Operation<'_, u8>]) -> Result<(), Self::Error> {
+        let [embedded_hal::spi::Operation::TransferInPlace(frame)] = operations else {
+            panic!("one 16-bit frame per transaction");
+        };
+        let command = u16::from_be_bytes([frame[0], frame[1]]);
+        assert_eq!(command.count_ones() % 2, 0, "command parity");
+        assert_ne!(command & 0x4000, 0, "reads only");
+        let data = match self.pending {
+            0x3FFF => self.angle,
+            0x3FFC => self.diagnostics,
+            _ => 0,
+        };
+        self.pending = command & 0x3FFF;
+        let mut word = data | ((data.count_ones() as u16 & 1) << 15);
+        if self.corrupt {
+            word ^= 1;
+        }
+        frame.copy_from_slice(&word.to_be_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "mcp2518fd")]
+#[test]
+fn test_as5047_reads_angle_and_diagnostics() {
+    use irpc::encoder::As5047;
+
+    let spi = MockAs5047 { angle: 4096, diagnostics: 0x0100, pending: 0, corrupt: false };
+    let mut encoder = As5047::new(spi);
+    assert_eq!(encoder.read_position().unwrap(), MultiTurnPosition { turns: 0, angle: 90.0 });
+
+    let mut spi = encoder.release();
+    spi.angle = 16_000;
+    spi.diagnostics = 0x0100 | (1 << 11);
+    let mut encoder = As5047::new(spi);
+    let position = encoder.read_position().unwrap();
+    assert_eq!(position.turns, 0);
+    assert!(encoder.error_flags().contains(EncoderFlags::FIELD_LOW));
+
+    // CORDIC overflow: no valid angle
+    let mut spi = encoder.release();
+    spi.diagnostics = 1 << 9;
+    let mut encoder = As5047::new(spi);
+    assert_eq!(encoder.read_position(), Err(EncoderError::SignalLost));
+
+    let mut spi = encoder.release();
+    spi.corrupt = true;
+    let mut encoder = As5047::new(spi);
+    assert_eq!(encoder.read_position(), Err(EncoderError::Communication));
+}
@@ -0,0 +1,158 @@
+//! Tests for gravity compensation feedforward
+
+#[cfg(feature = "arm_api")]
+use irpc::kinematics::{ArmModel, DhParameters, Link};
+
+/// Two links moving in a vertical plane: gravity along the base's -y axis
+#[cfg(feature = "arm_api")]
+fn planar_arm() -> ArmModel {
+    let link = |joint_id, length: f64, mass| Link {
+        joint_id,
+        dh: DhParameters { a: length, ..Default::default() },
+        mass,
+        center_of_mass: [-length / 2.0, 0.0, 0.0],
+    };
+    ArmModel::new(vec![link(0x0010, 0.4, 2.0), link(0x0011, 0.3, 1.0)]).with_gravity([0.0, -9.81, 0.0])
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_gravity_torques_of_planar_arm() {
+    use irpc::kinematics::KinematicsError;
+
+    let arm = planar_arm();
+    assert_eq!(arm.joint_ids(), vec![0x0010, 0x0011]);
+    let close = |angles: [f32; 2], expected: [f32; 2]| {
+        let torques = arm.gravity_torques(&angles).unwrap();
+        assert!(torques.iter().zip(expected).all(|(t, e)| (t - e).abs() < 1e-3), "{:?} at {:?}", torques, angles);
+    };
+
+    // Stretched out horizontally: both links lift their own weight
+    close([0.0, 0.0], [9.81 * (2.0 * 0.2 + 1.0 * 0.55), 9.81 * 0.15]);
+    // Straight up: nothing to hold
+    close([90.0, 0.0], [0.0, 0.0]);
+    // Forearm folded back to horizontal, pulling the other way
+    close([90.0, 90.0], [-9.81 * 0.15, -9.81 * 0.15]);
+
+    assert!(matches!(
+        arm.gravity_torques(&[0.0]),
+        Err(KinematicsError::AngleCount { expected: 2, got: 1 })
+    ));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_applies_feedforward_as_current() {
+    use irpc::config_store::{JointConfig, MemoryConfigStore};
+    use irpc::encoder::{Encoder, EncoderError};
+    use irpc::motor::{MotorDriver, MotorError};
+    use irpc::{ControlGains, Header, Joint, Message, MotorParameters, MultiTurnPosition, Payload};
+
+    struct Still;
+    impl Encoder for Still {
+        fn read_position(&mut self) -> Result<MultiTurnPosition, EncoderError> {
+            Ok(MultiTurnPosition::default())
+        }
+        fn resolution(&self) -> u32 {
+            4096
+        }
+    }
+
+    #[derive(Default)]
+    struct Driver(f32);
+    impl MotorDriver for Driver {
+        fn enable(&mut self) -> Result<(), MotorError> {
+            Ok(())
+        }
+        fn disable(&mut self) {}
+        fn set_voltage(&mut self, _volts: f32) -> Result<(), MotorError> {
+            Err(MotorError::Unsupported)
+        }
+        fn set_current(&mut self, amps: f32) -> Result<(), MotorError> {
+            self.0 = amps;
+            Ok(())
+        }
+    }
+
+    let command = |msg_id, payload| Message {
+        header: Header { source_id: 0x0001, target_id: 0x0010, msg_id },
+        payload,
+    };
+    let mut driver = Driver::default();
+    let mut joint = Joint::new(0x0010);
+    let deliver = |joint: &mut Joint, driver: &mut Driver, msg_id, payload| {
+        let msg = command(msg_id, payload);
+        let reply = joint.handle_motor(&msg, driver).or_else(|| joint.handle_message(&msg)).unwrap();
+        joint.sync_driver(driver);
+        reply.payload
+    };
+
+    let early = deliver(&mut joint, &mut driver, 1, Payload::SetFeedforward { torque: 1.0 });
+    assert!(matches!(early, Payload::Nack { id: 1, error: irpc::ERROR_INVALID_STATE }));
+    deliver(&mut joint, &mut driver, 2, Payload::Configure);
+    deliver(&mut joint, &mut driver, 3, Payload::Activate);
+    let nan = Payload::SetFeedforward { torque: f32::NAN };
+    let refused = deliver(&mut joint, &mut driver, 4, nan);
+    assert!(matches!(refused, Payload::Nack { error: irpc::ERROR_LIMIT_VIOLATION, .. }));
+    assert!(matches!(deliver(&mut joint, &mut driver, 5, Payload::SetFeedforward { torque: 0.3 }), Payload::Ack(5)));
+    assert_eq!(joint.feedforward(), 0.3);
+    let gains = ControlGains { max_current: 5.0, ..Default::default() };
+    deliver(&mut joint, &mut driver, 6, Payload::ConfigureGains(gains));
+
+    // Zero gains: the output is the feedforward alone, once the torque
+    // constant is known
+    joint.control_step(0.001, &mut Still, &mut driver).unwrap();
+    assert_eq!(driver.0, 0.0);
+
+    let mut store = MemoryConfigStore::new();
+    let parameters = MotorParameters {
+        inertia_J: 0.001,
+        torque_constant_kt: 0.15,
+        damping_b: 0.0,
+        friction_coulomb: 0.0,
+        friction_stribeck: 0.0,
+        friction_vstribeck: 0.0,
+        friction_viscous: 0.0,
+        thermal_resistance: 0.0,
+        thermal_time_constant: 0.0,
+    };
+    let config = JointConfig { motor_parameters: Some(parameters), ..joint.config() };
+    config.save(&mut store).unwrap();
+    joint.restore_config(&mut store).unwrap();
+    joint.control_step(0.001, &mut Still, &mut driver).unwrap();
+    assert!((driver.0 - 2.0).abs() < 1e-6, "{}", driver.0);
+
+    // Re-activating starts without feedforward
+    deliver(&mut joint, &mut driver, 7, Payload::Deactivate);
+    deliver(&mut joint, &mut driver, 8, Payload::Activate);
+    assert_eq!(joint.feedforward(), 0.0);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_orchestrator_sends_gravity_feedforward() {
+    use irpc::bus::sim::SimBus;
+    use irpc::kinematics::KinematicsError;
+    use irpc::{ArmOrchestrator, CommunicationManager};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0011]));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    arm.add_joint(0x0010);
+    arm.add_joint(0x0011);
+    arm.configure_all().await.into_result().unwrap();
+    arm.activate_all().await.into_result().unwrap();
+
+    assert!(matches!(arm.send_gravity_feedforward(&[0.0, 0.0]).await, Err(KinematicsError::NoModel)));
+    arm.set_arm_model(planar_arm());
+    arm.send_gravity_feedforward(&[0.0, 0.0]).await.unwrap();
+    let feedforward = |id| bus.with_joint(id, |joint| joint.feedforward()).unwrap();
+    assert!((feedforward(0x0010) - 9.81 * 0.95).abs() < 1e-3);
+    assert!((feedforward(0x0011) - 9.81 * 0.15).abs() < 1e-3);
+
+    // Streamed with the trajectory: the last sample points straight up
+    let trajectory = irpc::trajectory::Trajectory::from_csv("time,16,17\n0,0,0\n0.1,90,0\n").unwrap();
+    arm.stream_trajectory(&trajectory, 100).await.unwrap();
+    assert!(feedforward(0x0010).abs() < 1e-3);
+    assert!(feedforward(0x0011).abs() < 1e-3);
+}
@@ -0,0 +1,90 @@
+//! Tests for the C API, called the way C code would
+
+#[cfg(feature = "ffi")]
+use irpc::ffi::*;
+#[cfg(feature = "ffi")]
+use irpc::{CrashKind, CrashRecord, Header, LifecycleState, Message, Payload};
+#[cfg(feature = "ffi")]
+use std::mem::MaybeUninit;
+
+#[cfg(feature = "ffi")]
+fn request(payload: Payload) -> IrpcMessage {
+    Message { header: Header { source_id: 0x0001, target_id: 0x0010, msg_id: 7 }, payload }.into()
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn test_ffi_encode_decode() {
+    let message = request(Payload::Nack { id: 3, error: 5 });
+    let mut buf = [0u8; 128];
+    let mut written = 0;
+    let status = unsafe { irpc_message_encode(&message, buf.as_mut_ptr(), buf.len(), &mut written) };
+    assert_eq!(status, IrpcStatus::Ok);
+    assert!(written > 0 && written <= irpc_max_message_size());
+
+    let mut out = MaybeUninit::<IrpcMessage>::uninit();
+    let status = unsafe { irpc_message_decode(buf.as_ptr(), written, out.as_mut_ptr()) };
+    assert_eq!(status, IrpcStatus::Ok);
+    let out = unsafe { out.assume_init() };
+    assert_eq!(out.header.msg_id, 7);
+    assert!(matches!(out.payload, IrpcPayload::Nack(IrpcNack { id: 3, error: 5 })));
+
+    let status = unsafe { irpc_message_encode(&message, buf.as_mut_ptr(), 2, &mut written) };
+    assert_eq!(status, IrpcStatus::BufferTooSmall);
+    let mut scratch = MaybeUninit::<IrpcMessage>::uninit();
+    let status = unsafe { irpc_message_decode([0xFF; 4].as_ptr(), 4, scratch.as_mut_ptr()) };
+    assert_eq!(status, IrpcStatus::Decode);
+    let status = unsafe { irpc_message_decode(std::ptr::null(), 4, scratch.as_mut_ptr()) };
+    assert_eq!(status, IrpcStatus::NullPointer);
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn test_ffi_joint_state_machine() {
+    let joint = irpc_joint_new(0x0010);
+    assert_eq!(unsafe { irpc_joint_state(joint) }, LifecycleState::Unconfigured);
+
+    let mut reply = MaybeUninit::<IrpcMessage>::uninit();
+    let answered = unsafe { irpc_joint_handle(joint, &request(Payload::Configure), reply.as_mut_ptr()) };
+    assert_eq!(answered, 1);
+    let reply = unsafe { reply.assume_init() };
+    assert_eq!(reply.header.target_id, 0x0001);
+    assert!(matches!(reply.payload, IrpcPayload::Ack(7)));
+    assert_eq!(unsafe { irpc_joint_state(joint) }, LifecycleState::Inactive);
+
+    // Frame in, frame out
+    let mut rx = [0u8; 128];
+    let mut rx_len = 0;
+    unsafe { irpc_message_encode(&request(Payload::Activate), rx.as_mut_ptr(), rx.len(), &mut rx_len) };
+    let mut tx = [0u8; 128];
+    let tx_len = unsafe { irpc_joint_handle_frame(joint, rx.as_ptr(), rx_len, tx.as_mut_ptr(), tx.len()) };
+    assert!(tx_len > 0);
+    let reply = Message::deserialize(&tx[..tx_len as usize]).unwrap();
+    assert!(matches!(reply.payload, Payload::Ack(7)));
+    assert_eq!(unsafe { irpc_joint_state(joint) }, LifecycleState::Active);
+
+    // Not addressed to this joint: no reply
+    let mut other = request(Payload::Reset);
+    other.header.target_id = 0x0011;
+    unsafe { irpc_message_encode(&other, rx.as_mut_ptr(), rx.len(), &mut rx_len) };
+    assert_eq!(unsafe { irpc_joint_handle_frame(joint, rx.as_ptr(), rx_len, tx.as_mut_ptr(), tx.len()) }, 0);
+
+    assert_eq!(
+        unsafe { irpc_joint_handle_frame(joint, rx.as_ptr(), 1, tx.as_mut_ptr(), tx.len()) },
+        IrpcStatus::Decode as isize
+    );
+    unsafe { irpc_joint_free(joint) };
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn test_ffi_boot_crash_record() {
+    let crash = CrashRecord::new(CrashKind::HardFault, 12, 3, "ctrl", "stalled");
+    let boot = IrpcPayload::from(Payload::Boot(irpc::BootPayload { entity_type: 0x1001, crash: Some(crash) }));
+    let IrpcPayload::Boot(flat) = boot else { panic!("expected Boot") };
+    assert!(flat.has_crash);
+    assert!(matches!(Payload::from(boot), Payload::Boot(p) if p.crash == Some(crash)));
+
+    let boot = IrpcPayload::from(Payload::Boot(irpc::BootPayload { entity_type: 0x1001, crash: None }));
+    assert!(matches!(Payload::from(boot), Payload::Boot(p) if p.crash.is_none()));
+}
@@ -0,0 +1,140 @@
+//! Tests for the joint and host flight recorders
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_records_commands_replies_and_transitions() {
+    use irpc::{
+        Capabilities, FlightEvent, Header, Joint, LifecycleState, Message, Payload, ERROR_UNKNOWN_COMMAND,
+        FLIGHT_RECORDER_LEN, FLIGHT_RECORDS_PER_CHUNK,
+    };
+
+    let command = |msg_id, payload| Message {
+        header: Header { source_id: 0x0001, target_id: 0x0010, msg_id },
+        payload,
+    };
+    let mut joint = Joint::new(0x0010);
+    assert!(joint.handle_message(&command(1, Payload::Configure)).is_some());
+    assert!(joint.flight_recorder().is_none());
+    let refused = joint.handle_message(&command(2, Payload::RequestFlightRecorder { from: 0 })).unwrap();
+    assert!(matches!(refused.payload, Payload::Nack { error: ERROR_UNKNOWN_COMMAND, .. }));
+
+    joint.set_flight_recorder(true);
+    assert!(joint.capabilities().contains(Capabilities::FLIGHT_RECORDER));
+    joint.poll(1_500);
+    joint.handle_message(&command(3, Payload::Activate));
+    joint.handle_message(&command(4, Payload::Configure));
+    joint.poll(2_000);
+    joint.report_fault();
+    // Not for this joint
+    let mut other = command(5, Payload::Reset);
+    other.header.target_id = 0x0020;
+    joint.handle_message(&other);
+
+    let recorder = joint.flight_recorder().unwrap();
+    let events: Vec<_> = recorder.iter().map(|record| (record.timestamp_us, record.event)).collect();
+    let tag = |payload: Payload| payload.tag();
+    use LifecycleState::*;
+    assert_eq!(
+        events,
+        [
+            (1_500, FlightEvent::Received { msg_id: 3, payload: tag(Payload::Activate) }),
+            (1_500, FlightEvent::StateChanged { from: Inactive, to: Active }),
+            (1_500, FlightEvent::Replied { msg_id: 3, payload: tag(Payload::Ack(3)) }),
+            (1_500, FlightEvent::Received { msg_id: 4, payload: tag(Payload::Configure) }),
+            (1_500, FlightEvent::Nacked { msg_id: 4, error: 1 }),
+            (2_000, FlightEvent::StateChanged { from: Active, to: Error }),
+        ]
+    );
+
+    // Read back in chunks; reading records nothing
+    let reply = joint.handle_message(&command(6, Payload::RequestFlightRecorder { from: 0 })).unwrap();
+    let Payload::FlightRecorder(chunk) = reply.payload else { panic!("{:?}", reply.payload) };
+    assert_eq!((chunk.first, chunk.records().len()), (0, FLIGHT_RECORDS_PER_CHUNK));
+    let reply = joint.handle_message(&command(7, Payload::RequestFlightRecorder { from: 3 })).unwrap();
+    let Payload::FlightRecorder(chunk) = reply.payload else { panic!("{:?}", reply.payload) };
+    assert_eq!(chunk.records()[2].event, FlightEvent::StateChanged { from: Active, to: Error });
+    assert_eq!(joint.flight_recorder().unwrap().len(), 6);
+
+    // Once full, the oldest entries go and requests for them start at the oldest held
+    for msg_id in 100..100 + FLIGHT_RECORDER_LEN as u32 {
+        joint.handle_message(&command(msg_id, Payload::RequestStatus));
+    }
+    let recorder = joint.flight_recorder().unwrap();
+    assert_eq!(recorder.recorded(), 6 + 2 * FLIGHT_RECORDER_LEN as u32);
+    assert_eq!((recorder.len(), recorder.oldest()), (FLIGHT_RECORDER_LEN, 6 + FLIGHT_RECORDER_LEN as u32));
+    let chunk = recorder.chunk(0);
+    assert_eq!(chunk.first, recorder.oldest());
+    assert_eq!(recorder.chunk(recorder.recorded()).records().len(), 0);
+
+    joint.set_flight_recorder(false);
+    assert!(joint.flight_recorder().is_none());
+    assert!(!joint.capabilities().contains(Capabilities::FLIGHT_RECORDER));
+}
+
+#[test]
+fn test_payload_tags_name_every_variant() {
+    use irpc::{Message, Payload};
+
+    // Every variant decodes from its tag followed by zeros (tags above 127
+    // would start a longer varint)
+    let mut bytes = [0u8; Message::max_size()];
+    let mut variants = 0;
+    for tag in 0..0x80 {
+        bytes[0] = tag;
+        match postcard::from_bytes::<Payload>(&bytes) {
+            Ok(payload) => {
+                assert_eq!(payload.tag(), tag);
+                assert_eq!(Payload::tag_name(tag), Some(payload.name()));
+                variants += 1;
+            }
+            Err(_) => assert_eq!(Payload::tag_name(tag), None, "tag {}", tag),
+        }
+    }
+    assert_eq!(variants, 71);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_flight_recorders_over_sim_bus() {
+    use irpc::bus::record::Direction;
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, FlightEvent, Joint, JointProxy, Payload, ProtocolError};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0020]));
+    let mut joint = Joint::new(0x0010);
+    joint.set_flight_recorder(true);
+    bus.add_joint(joint);
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    comm.set_flight_recorder(Some(64));
+
+    let joint = JointProxy::new(0x0010, comm.clone());
+    joint.configure().await.unwrap();
+    joint.activate().await.unwrap();
+    for angle in 0..20 {
+        joint.set_target(angle as f32, 90.0).await.unwrap();
+    }
+    bus.with_joint(0x0010, |joint| joint.report_fault());
+
+    let records = joint.flight_recorder().await.unwrap();
+    // Configure and Activate change state, each target is received and acked
+    assert_eq!(records.len(), 3 + 3 + 2 * 20 + 1);
+    assert!(matches!(records[0].event, FlightEvent::Received { payload, .. } if payload == Payload::Configure.tag()));
+    assert!(matches!(records.last().unwrap().event, FlightEvent::StateChanged { .. }));
+
+    // The host kept the latest messages, the flight recorder reads included
+    let host = comm.flight_recorder();
+    assert_eq!(host.len(), 64);
+    let last = host.last().unwrap();
+    assert_eq!(last.direction, Direction::Received);
+    assert!(matches!(last.message.payload, Payload::FlightRecorder(_)));
+    assert!(host.windows(2).all(|pair| pair[0].timestamp_us <= pair[1].timestamp_us));
+    comm.set_flight_recorder(Some(8));
+    assert_eq!(comm.flight_recorder().len(), 8);
+    comm.set_flight_recorder(None);
+    assert!(comm.flight_recorder().is_empty());
+
+    // A joint without one refuses
+    let other = JointProxy::new(0x0020, comm.clone());
+    assert!(matches!(other.flight_recorder().await, Err(ProtocolError::IoError(_))));
+}
@@ -0,0 +1,65 @@
+//! Tests for the COBS byte-stream framing
+
+use irpc::framing::{crc16, decode_frame, encode_frame, max_encoded_len, FrameDecoder, FramingError};
+
+#[test]
+fn test_frames_round_trip_any_payload() {
+    // Zeros everywhere, and runs longer than one COBS block
+    let payloads: [&[u8]; 5] = [&[], &[0], &[0, 0, 7, 0], &[0x55; 254], &[0xAA; 600]];
+    let mut decoder = FrameDecoder::<1024>::new();
+    for payload in payloads {
+        let mut frame = [0xEEu8; 1024];
+        let len = encode_frame(payload, &mut frame).unwrap();
+        assert!(len <= max_encoded_len(payload.len()));
+        assert_eq!(frame[..len].iter().position(|&b| b == 0), Some(len - 1));
+
+        let (delimiter, body) = frame[..len].split_last().unwrap();
+        assert!(body.iter().all(|&b| decoder.push(b).is_none()));
+        assert_eq!(decoder.pending(), len - 1);
+        assert_eq!(decoder.push(*delimiter).unwrap().unwrap(), payload);
+        assert_eq!(decode_frame(&mut frame[..len - 1]).unwrap(), payload);
+    }
+
+    let mut small = [0u8; 8];
+    assert!(matches!(encode_frame(&[1; 8], &mut small), Err(FramingError::BufferTooSmall)));
+    assert_eq!(crc16(0xFFFF, b"123456789"), 0x29B1);
+}
+
+#[test]
+fn test_damaged_frames_are_rejected() {
+    let mut frame = [0u8; 32];
+    let len = encode_frame(&[1, 2, 3, 4], &mut frame).unwrap() - 1;
+
+    let mut flipped = frame;
+    flipped[4] ^= 0x10;
+    assert!(matches!(decode_frame(&mut flipped[..len]), Err(FramingError::Crc)));
+    // Cut short, the last COBS block runs past the end
+    let mut truncated = frame;
+    assert!(matches!(decode_frame(&mut truncated[..len - 1]), Err(FramingError::Encoding)));
+    // Too short to hold a length and CRC
+    assert!(matches!(decode_frame(&mut [0x03, 0x07, 0x07]), Err(FramingError::Length)));
+}
+
+#[test]
+fn test_decoder_resynchronises_at_delimiters() {
+    use irpc::framing::{encode_message, MAX_MESSAGE_FRAME_LEN};
+    use irpc::{Header, Message, Payload};
+
+    let message = Message { header: Header { source_id: 0x0010, target_id: 0x0001, msg_id: 9 }, payload: Payload::Ack(9) };
+    let mut frame = [0u8; MAX_MESSAGE_FRAME_LEN];
+    let len = encode_message(&message, &mut frame).unwrap();
+
+    // Joined mid-frame, then an overlong run of noise
+    let mut stream = frame[3..len].to_vec();
+    stream.extend_from_slice(&[0x7F; 200]);
+    stream.push(0);
+    stream.extend_from_slice(&frame[..len]);
+
+    let mut decoder = FrameDecoder::<MAX_MESSAGE_FRAME_LEN>::new();
+    let results: Vec<_> = stream.iter().filter_map(|&b| decoder.push_message(b)).collect();
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_err());
+    assert!(matches!(results[1], Err(FramingError::Overflow)));
+    assert_eq!(results[2].as_ref().unwrap().header.msg_id, 9);
+    assert_eq!(decoder.pending(), 0);
+}
@@ -0,0 +1,109 @@
+//! Tests for controller gain scheduling
+
+#[cfg(feature = "joint_api")]
+use irpc::control::{CascadedController, GainSchedule};
+#[cfg(feature = "joint_api")]
+use irpc::{ControlGains, GainRegion, GainScheduleEntry, MotionProfile, PidGains};
+
+#[cfg(feature = "joint_api")]
+fn gains(kp: f32) -> ControlGains {
+    ControlGains {
+        position: PidGains { kp, ..Default::default() },
+        velocity: PidGains { kp: 0.05, ki: 0.5, kd: 0.0, integral_limit: 1.0 },
+        max_current: 2.0,
+    }
+}
+
+#[cfg(feature = "joint_api")]
+fn entry(slot: u8, profiles: u8, min_velocity: f32, min_load: f32, kp: f32) -> GainScheduleEntry {
+    GainScheduleEntry { slot, region: GainRegion { profiles, min_velocity, min_load }, gains: gains(kp) }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_schedule_picks_highest_matching_slot() {
+    let scurve = GainRegion::profile_mask(MotionProfile::SCurve);
+    let mut schedule = GainSchedule::default();
+    assert!(schedule.is_empty());
+    assert!(schedule.set(&entry(0, 0, 20.0, 0.0, 2.0)));
+    assert!(schedule.set(&entry(1, scurve, 0.0, 0.0, 3.0)));
+    assert!(schedule.set(&entry(2, 0, 0.0, 1.0, 4.0)));
+    assert!(!schedule.set(&entry(irpc::GAIN_SCHEDULE_SLOTS as u8, 0, 0.0, 0.0, 5.0)));
+
+    let kp = |profile, speed, load| schedule.select(profile, speed, load).map(|g| g.position.kp);
+    assert_eq!(kp(None, 5.0, 0.1), None);
+    assert_eq!(kp(None, 30.0, 0.1), Some(2.0));
+    assert_eq!(kp(Some(MotionProfile::Trapezoidal), 5.0, 0.1), None);
+    assert_eq!(kp(Some(MotionProfile::SCurve), 30.0, 0.1), Some(3.0));
+    // Heavy load overrides everything below it
+    assert_eq!(kp(Some(MotionProfile::SCurve), 30.0, 1.5), Some(4.0));
+    assert_eq!(schedule.get(1).unwrap().0.profiles, scurve);
+
+    schedule.clear();
+    assert!(schedule.is_empty());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_controller_switches_gains_under_load() {
+    let mut schedule = GainSchedule::default();
+    schedule.set(&entry(0, 0, 0.0, 1.0, 8.0));
+    let mut controller = CascadedController::new(gains(5.0));
+    controller.set_schedule(schedule);
+
+    // Small error, little current: base gains
+    controller.update(None, 0.0, 0.0, 0.001);
+    assert_eq!(controller.active_gains(), gains(5.0));
+
+    // A stalled joint far from its target saturates the current
+    let target = irpc::SetTargetPayloadV2 {
+        target_angle: 90.0,
+        max_velocity: 0.0,
+        target_velocity: 0.0,
+        max_acceleration: 0.0,
+        max_deceleration: 0.0,
+        max_jerk: 0.0,
+        profile: MotionProfile::Trapezoidal,
+        max_current: 0.0,
+        max_temperature: 0.0,
+    };
+    controller.update(Some(&target), 0.0, 0.0, 0.001);
+    assert_eq!(controller.current_setpoint(), 2.0);
+    controller.update(Some(&target), 0.0, 0.0, 0.001);
+    assert_eq!(controller.active_gains(), gains(8.0));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_schedule_entries_validated_and_persisted() {
+    use irpc::config_store::MemoryConfigStore;
+    use irpc::{Header, Joint, Message, Payload};
+
+    let command = |msg_id, payload| Message { header: Header { source_id: 0x0001, target_id: 0x0010, msg_id }, payload };
+    let mut store = MemoryConfigStore::new();
+    let mut joint = Joint::new(0x0010);
+
+    // Kept before the base gains arrive
+    let heavy = entry(3, 0, 0.0, 1.0, 8.0);
+    let reply = joint.handle_with_store(&command(1, Payload::SetGainScheduleEntry(heavy)), &mut store);
+    assert!(matches!(reply.unwrap().payload, Payload::Ack(1)));
+    // No such slot, negative threshold, negative gain
+    let bad = [entry(4, 0, 0.0, 0.0, 1.0), entry(0, 0, -1.0, 0.0, 1.0), entry(0, 0, 0.0, 0.0, -1.0)];
+    for (msg_id, bad) in (2..).zip(bad) {
+        let reply = joint.handle_with_store(&command(msg_id, Payload::SetGainScheduleEntry(bad)), &mut store);
+        assert!(matches!(reply.unwrap().payload, Payload::Nack { error: irpc::ERROR_INVALID_GAINS, .. }));
+    }
+    joint.handle_with_store(&command(5, Payload::ConfigureGains(gains(5.0))), &mut store);
+    assert_eq!(joint.controller().unwrap().schedule().get(3).unwrap().1, gains(8.0));
+    joint.handle_with_store(&command(6, Payload::SaveConfig), &mut store);
+
+    let mut restored = Joint::new(0x0010);
+    restored.restore_config(&mut store).unwrap();
+    assert_eq!(restored.controller().unwrap().gains(), gains(5.0));
+    assert_eq!(restored.gain_schedule(), joint.gain_schedule());
+    assert_eq!(restored.controller().unwrap().schedule(), joint.gain_schedule());
+
+    restored.handle_with_store(&command(7, Payload::ClearGainSchedule), &mut store);
+    assert!(restored.gain_schedule().is_empty());
+    assert!(restored.controller().unwrap().schedule().is_empty());
+}
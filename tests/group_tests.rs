@@ -0,0 +1,91 @@
+//! Tests for joint groups
+
+#[cfg(feature = "arm_api")]
+#[tokio::test]
+async fn test_create_group() {
+    use irpc::group::GroupError;
+    use irpc::ArmOrchestrator;
+
+    let mut arm = ArmOrchestrator::new();
+    for id in [0x0010, 0x0020, 0x0030, 0x0040] {
+        arm.add_joint(id);
+    }
+
+    let wrist = arm.create_group("wrist", [0x0040, 0x0030]).unwrap();
+    assert_eq!(wrist.name(), "wrist");
+    assert_eq!(wrist.joint_ids(), vec![0x0040, 0x0030]);
+    assert!(matches!(arm.create_group("gripper", [0x0050]), Err(GroupError::UnknownJoint(0x0050))));
+    assert!(matches!(arm.create_group("gripper", [0x0010, 0x0010]), Err(GroupError::DuplicateJoint(0x0010))));
+    assert!(arm.group("gripper").is_none());
+
+    // Joints may be shared; a name is replaced
+    arm.create_group("arm", [0x0010, 0x0020, 0x0030]).unwrap();
+    arm.create_group("wrist", [0x0030]).unwrap();
+    let names: Vec<&str> = arm.groups().map(|group| group.name()).collect();
+    assert_eq!(names, vec!["arm", "wrist"]);
+    assert!(arm.group("wrist").unwrap().contains(0x0030));
+    assert!(!arm.group("wrist").unwrap().contains(0x0040));
+    assert!(arm.remove_group("arm").is_some());
+    assert!(arm.group("arm").is_none());
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_group_lifecycle_and_targets() {
+    use irpc::bus::sim::SimBus;
+    use irpc::group::GroupError;
+    use irpc::{ArmOrchestrator, CommunicationManager, LifecycleState};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0020, 0x0030]));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    for id in [0x0010, 0x0020, 0x0030] {
+        arm.add_joint(id);
+    }
+    let wrist = arm.create_group("wrist", [0x0020, 0x0030]).unwrap().clone();
+
+    wrist.configure().await.unwrap();
+    wrist.activate().await.unwrap();
+    let status = wrist.status().await;
+    assert_eq!(status.len(), 2);
+    assert!(status.values().all(|state| *state == LifecycleState::Active));
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Unconfigured));
+
+    assert!(matches!(
+        wrist.set_targets(&[10.0], 45.0).await,
+        Err(GroupError::TargetCount { expected: 2, actual: 1 })
+    ));
+    wrist.set_targets(&[10.0, -20.0], 45.0).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!((bus.true_position(0x0020).unwrap() - 10.0).abs() < 0.5);
+    assert!((bus.true_position(0x0030).unwrap() + 20.0).abs() < 0.5);
+
+    wrist.deactivate().await.unwrap();
+    assert_eq!(bus.joint_state(0x0020), Some(LifecycleState::Inactive));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_group_commands_run_concurrently() {
+    use irpc::bus::sim::SimBus;
+    use irpc::group::GroupError;
+    use irpc::{ArmOrchestrator, CommunicationManager, LifecycleState, ProtocolError};
+    use std::sync::Arc;
+    use tokio::time::Instant;
+
+    // 0x0020 and 0x0030 never answer
+    let bus = Arc::new(SimBus::with_joints([0x0010]));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    for id in [0x0030, 0x0010, 0x0020] {
+        arm.add_joint(id);
+    }
+    let group = arm.create_group("all", [0x0030, 0x0010, 0x0020]).unwrap().clone();
+
+    // Both time out side by side; the failure is the first in group order
+    let start = Instant::now();
+    let result = group.configure().await;
+    assert!(start.elapsed().as_secs_f32() < 6.0, "{:?}", start.elapsed());
+    assert!(matches!(result, Err(GroupError::Joint { joint_id: 0x0030, error: ProtocolError::Timeout })));
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Inactive));
+}
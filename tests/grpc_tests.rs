@@ -0,0 +1,63 @@
+//! Tests for the gRPC bridge, over a loopback connection to a simulated arm
+
+#[cfg(all(feature = "grpc", feature = "joint_api"))]
+#[tokio::test]
+async fn test_grpc_drives_simulated_arm() {
+    use irpc::bus::sim::SimBus;
+    use irpc::grpc::proto::{self, arm_client::ArmClient};
+    use irpc::grpc::ArmService;
+    use irpc::{ArmOrchestrator, CommunicationManager, LifecycleState};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0020]).with_telemetry(Duration::from_millis(10)));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    arm.add_joint(0x0010);
+    arm.add_joint(0x0020);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(ArmService::new(arm).into_server())
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    let mut client = ArmClient::connect(format!("http://{}", addr)).await.unwrap();
+
+    let status = client.get_status(proto::StatusRequest {}).await.unwrap().into_inner();
+    assert_eq!(status.joints.len(), 2);
+    assert!(status.joints.iter().all(|joint| joint.state() == proto::LifecycleState::Unconfigured));
+
+    // Whole arm, then a single joint
+    client.configure(proto::JointRequest { joint_id: None }).await.unwrap();
+    client.activate(proto::JointRequest { joint_id: Some(0x0010) }).await.unwrap();
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Active));
+    assert_eq!(bus.joint_state(0x0020), Some(LifecycleState::Inactive));
+
+    let mut telemetry = client
+        .stream_telemetry(proto::TelemetryRequest {
+            joint_ids: vec![0x0010],
+            topics: vec![proto::TelemetryTopic::Motion.into()],
+            snapshot: false,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let target = |joint_id| proto::SetTargetRequest {
+        joint_id,
+        target: Some(proto::SetTargetPayload { target_angle: 10.0, velocity_limit: 100.0 }),
+    };
+    client.set_target(target(0x0010)).await.unwrap();
+
+    let sample = telemetry.message().await.unwrap().unwrap();
+    assert_eq!(sample.joint_id, 0x0010);
+    assert!(matches!(sample.payload, Some(proto::telemetry_sample::Payload::Motion(_))));
+
+    // Errors come back as gRPC statuses
+    let error = client.set_target(target(0x0030)).await.unwrap_err();
+    assert_eq!(error.code(), tonic::Code::NotFound);
+    let error = client.set_target(target(0x0020)).await.unwrap_err();
+    assert_eq!(error.code(), tonic::Code::Internal);
+    server.abort();
+}
@@ -0,0 +1,70 @@
+//! Tests for the hardware-in-the-loop conformance suite
+
+#![cfg(feature = "hil")]
+
+use irpc::hil::{CheckOutcome, CheckResult, HilConfig, HilReport};
+use std::time::Duration;
+
+#[test]
+fn test_report_lists_every_check() {
+    let check = |name, outcome| CheckResult { name, outcome, elapsed: Duration::from_millis(12) };
+    let mut report = HilReport {
+        joint_id: 0x0010,
+        checks: vec![check("lifecycle", CheckOutcome::Passed), check("timeouts", CheckOutcome::Passed)],
+        max_response_time: Duration::from_micros(1500),
+        telemetry_rate_hz: Some(99.5),
+    };
+    assert!(report.passed());
+    assert!(report.to_string().ends_with("PASSED: 2 checks"));
+
+    report.checks[1].outcome = CheckOutcome::Failed("no reply to Reset within 100ms".to_string());
+    assert!(!report.passed());
+    assert_eq!(report.failures().map(|check| check.name).collect::<Vec<_>>(), ["timeouts"]);
+    let text = report.to_string();
+    assert!(text.contains("FAIL  timeouts") && text.contains("no reply to Reset"), "{}", text);
+    assert!(text.contains("slowest reply: 1.5 ms") && text.contains("telemetry rate: 99.5 Hz"), "{}", text);
+    assert!(text.ends_with("FAILED: 1 of 2 checks"));
+    assert_eq!(HilConfig::new(0x0010).telemetry_rate_hz, 100);
+}
+
+#[cfg(feature = "joint_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_simulated_joint_conforms() {
+    use irpc::bus::sim::SimBus;
+    use irpc::hil::HilSuite;
+    use irpc::{CommunicationManager, LifecycleState};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0020]).with_telemetry(Duration::from_millis(10)));
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    let report = HilSuite::new(comm, HilConfig::new(0x0010)).run().await;
+
+    assert!(report.passed(), "{}", report);
+    assert_eq!(report.checks.len(), 5);
+    assert!(report.max_response_time <= Duration::from_millis(100));
+    assert!((report.telemetry_rate_hz.unwrap() - 100.0).abs() < 5.0);
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Unconfigured));
+}
+
+#[cfg(feature = "joint_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_nonconforming_joints_fail() {
+    use irpc::bus::sim::SimBus;
+    use irpc::hil::HilSuite;
+    use irpc::CommunicationManager;
+    use std::sync::Arc;
+
+    // Streams at half the requested rate
+    let bus = Arc::new(SimBus::with_joints([0x0010]).with_telemetry(Duration::from_millis(20)));
+    let report = HilSuite::new(CommunicationManager::with_adapter(bus), HilConfig::new(0x0010)).run().await;
+    assert_eq!(report.failures().map(|check| check.name).collect::<Vec<_>>(), ["telemetry rate"], "{}", report);
+    let CheckOutcome::Failed(reason) = &report.check("telemetry rate").unwrap().outcome else { unreachable!() };
+    assert!(reason.contains("100 Hz requested"), "{}", reason);
+
+    // Not on the bus at all
+    let bus = Arc::new(SimBus::with_joints([0x0020]));
+    let config = HilConfig { response_timeout: Duration::from_millis(20), ..HilConfig::new(0x0010) };
+    let report = HilSuite::new(CommunicationManager::with_adapter(bus), config).run().await;
+    assert!(report.checks.iter().all(|check| !check.passed()), "{}", report);
+    assert!(report.to_string().contains("joint did not answer Discover"));
+}
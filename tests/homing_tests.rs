@@ -0,0 +1,109 @@
+//! Tests for the homing procedure
+
+#[cfg(feature = "joint_api")]
+mod common;
+
+#[cfg(feature = "joint_api")]
+use common::command;
+#[cfg(feature = "joint_api")]
+use irpc::{
+    HomingConfig, HomingDirection, HomingMethod, Joint, LifecycleState, Payload, SetTargetPayload, ERROR_POSITION_UNKNOWN,
+};
+
+#[cfg(feature = "joint_api")]
+fn reply(joint: &mut Joint, msg_id: u32, payload: Payload) -> Payload {
+    joint.handle_message(&command(msg_id, payload)).unwrap().payload
+}
+
+#[cfg(feature = "joint_api")]
+fn target() -> Payload {
+    Payload::SetTarget(SetTargetPayload { target_angle: 10.0, velocity_limit: 50.0 })
+}
+
+#[cfg(feature = "joint_api")]
+fn hard_stop() -> HomingConfig {
+    HomingConfig {
+        method: HomingMethod::HardStop,
+        direction: HomingDirection::Negative,
+        speed: 5.0,
+        offset: -120.0,
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_homing_runs_as_calibrating_substate() {
+    let mut joint = Joint::new(0x0010);
+    assert!(matches!(reply(&mut joint, 1, Payload::StartHoming(hard_stop())), Payload::Nack { id: 1, error: 4 }));
+    reply(&mut joint, 2, Payload::Configure);
+    reply(&mut joint, 3, Payload::Activate);
+
+    assert!(matches!(reply(&mut joint, 4, Payload::StartHoming(hard_stop())), Payload::Ack(4)));
+    assert_eq!(joint.state(), LifecycleState::Calibrating);
+    assert_eq!(joint.homing(), Some(hard_stop()));
+    assert!(!joint.position_known());
+    assert!(matches!(
+        reply(&mut joint, 5, Payload::RequestStatus),
+        Payload::JointStatus { state: LifecycleState::Calibrating, error_code: ERROR_POSITION_UNKNOWN }
+    ));
+    // No motion or second homing while the routine runs
+    assert!(matches!(reply(&mut joint, 6, target()), Payload::Nack { id: 6, error: 4 }));
+    assert!(matches!(reply(&mut joint, 7, Payload::Home), Payload::Nack { id: 7, error: 4 }));
+
+    joint.complete_homing();
+    assert_eq!(joint.state(), LifecycleState::Active);
+    assert!(joint.homing().is_none() && joint.position_known());
+    assert!(matches!(reply(&mut joint, 8, target()), Payload::Ack(8)));
+
+    // Plain Home runs the default procedure
+    reply(&mut joint, 9, Payload::Home);
+    assert_eq!(joint.homing(), Some(HomingConfig::default()));
+    assert!(joint.target().is_none());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_homing_abort_leaves_position_unknown() {
+    let mut joint = Joint::new(0x0010);
+    reply(&mut joint, 1, Payload::Configure);
+    reply(&mut joint, 2, Payload::Activate);
+
+    reply(&mut joint, 3, Payload::StartHoming(hard_stop()));
+    assert!(matches!(reply(&mut joint, 4, Payload::StopCalibration), Payload::Ack(4)));
+    assert_eq!(joint.state(), LifecycleState::Active);
+    assert!(matches!(reply(&mut joint, 5, target()), Payload::Nack { error: ERROR_POSITION_UNKNOWN, .. }));
+
+    // Firmware giving up behaves the same
+    reply(&mut joint, 6, Payload::StartHoming(hard_stop()));
+    joint.abort_homing();
+    assert_eq!(joint.state(), LifecycleState::Active);
+    assert!(!joint.position_known());
+
+    // Reset drops a running procedure
+    reply(&mut joint, 7, Payload::StartHoming(hard_stop()));
+    reply(&mut joint, 8, Payload::Reset);
+    assert!(joint.homing().is_none());
+    assert_eq!(joint.state(), LifecycleState::Unconfigured);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_proxy_homing_on_sim() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, JointProxy};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010]));
+    let proxy = JointProxy::new(0x0010, CommunicationManager::with_adapter(bus.clone()));
+    assert!(proxy.start_homing(hard_stop()).await.is_err());
+    proxy.configure().await.unwrap();
+    proxy.activate().await.unwrap();
+
+    proxy.start_homing(hard_stop()).await.unwrap();
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Active));
+    assert_eq!(bus.true_position(0x0010), Some(-120.0));
+
+    proxy.home().await.unwrap();
+    assert_eq!(bus.true_position(0x0010), Some(0.0));
+    proxy.set_target(10.0, 50.0).await.unwrap();
+}
@@ -73,6 +73,166 @@ fn test_payload_variants() {
     }
 }
 
+#[cfg(not(feature = "no_alloc"))]
+#[test]
+fn test_serialize_into_matches_serialize() {
+    let msg = Message {
+        header: Header {
+            source_id: 0x0001,
+            target_id: 0x0010,
+            msg_id: 7,
+        },
+        payload: Payload::SetTarget(SetTargetPayload {
+            target_angle: 45.0,
+            velocity_limit: 20.0,
+        }),
+    };
+
+    let mut buf = [0u8; Message::max_size()];
+    let len = msg.serialize_into(&mut buf).expect("serialize_into failed");
+
+    assert_eq!(&buf[..len], msg.serialize().unwrap().as_slice());
+
+    let decoded = Message::deserialize(&buf[..len]).unwrap();
+    assert_eq!(decoded.header.msg_id, 7);
+}
+
+#[cfg(not(feature = "no_alloc"))]
+#[test]
+fn test_encoded_size_matches_serialization() {
+    let msg = Message {
+        header: Header {
+            source_id: 0x0001,
+            target_id: 0x0010,
+            msg_id: 300,
+        },
+        payload: Payload::SetTarget(SetTargetPayload {
+            target_angle: 45.0,
+            velocity_limit: 20.0,
+        }),
+    };
+
+    assert_eq!(msg.encoded_size(), msg.serialize().unwrap().len());
+    assert_eq!(msg.payload.encoded_size(), 1 + 8); // tag + two f32
+}
+
+#[test]
+fn test_serialize_into_buffer_too_small() {
+    let msg = Message {
+        header: Header {
+            source_id: 0x0001,
+            target_id: 0x0010,
+            msg_id: 7,
+        },
+        payload: Payload::SetTarget(SetTargetPayload {
+            target_angle: 45.0,
+            velocity_limit: 20.0,
+        }),
+    };
+
+    let mut buf = [0u8; 4];
+    assert!(msg.serialize_into(&mut buf).is_err());
+}
+
+#[test]
+fn test_max_size_bounds_worst_case_telemetry() {
+    use irpc::{TelemetryStream, Warnings, CANFD_MAX_DATA_LEN};
+
+    // Values chosen so every varint takes its maximum width
+    let msg = Message {
+        header: Header {
+            source_id: u16::MAX,
+            target_id: u16::MAX,
+            msg_id: u32::MAX,
+        },
+        payload: Payload::TelemetryStream(TelemetryStream {
+            timestamp_us: u64::MAX,
+            position: 1.0,
+            velocity: 1.0,
+            acceleration: 1.0,
+            current_d: 1.0,
+            current_q: 1.0,
+            voltage_d: 1.0,
+            voltage_q: 1.0,
+            torque_estimate: 1.0,
+            power: 1.0,
+            load_percent: 1.0,
+            foc_loop_time_us: u16::MAX,
+            temperature_c: 1.0,
+            warnings: Warnings(u16::MAX),
+            trajectory_active: true,
+            brake_engaged: false,
+        }),
+    };
+
+    let mut buf = [0u8; Message::max_size()];
+    let len = msg.serialize_into(&mut buf).expect("max_size() must bound every message");
+    assert!(len <= Message::max_size());
+
+    assert!(len > CANFD_MAX_DATA_LEN);
+    assert!(msg.payload.requires_fragmentation());
+    assert!(!Payload::Configure.requires_fragmentation());
+
+    // The calibration result (with thermal parameters) is the largest payload
+    use irpc::{CalibrationConfidence, CalibrationResult, MotorParameters};
+    let calibration = Message {
+        header: msg.header.clone(),
+        payload: Payload::CalibrationResult(CalibrationResult {
+            success: true,
+            parameters: MotorParameters {
+                inertia_J: 1.0,
+                torque_constant_kt: 1.0,
+                damping_b: 1.0,
+                friction_coulomb: 1.0,
+                friction_stribeck: 1.0,
+                friction_vstribeck: 1.0,
+                friction_viscous: 1.0,
+                thermal_resistance: 1.0,
+                thermal_time_constant: 1.0,
+            },
+            confidence: CalibrationConfidence {
+                overall: 1.0,
+                inertia: 1.0,
+                friction: 1.0,
+                torque_constant: 1.0,
+                validation_rms: 1.0,
+            },
+            total_time: 1.0,
+            error_code: u16::MAX,
+        }),
+    };
+    let len = calibration.serialize_into(&mut buf).expect("max_size() must bound every message");
+    assert_eq!(len, Message::max_size());
+}
+
+#[cfg(feature = "no_alloc")]
+#[test]
+fn test_no_alloc_roundtrip() {
+    let msg = Message {
+        header: Header {
+            source_id: 0x0010,
+            target_id: 0x0001,
+            msg_id: 9,
+        },
+        payload: Payload::Encoder(EncoderTelemetry {
+            position: 12.5,
+            velocity: -3.0,
+        }),
+    };
+
+    let mut buf = [0u8; Message::max_size()];
+    let len = msg.serialize_into(&mut buf).unwrap();
+    let decoded = Message::deserialize(&buf[..len]).unwrap();
+
+    match decoded.payload {
+        Payload::Encoder(e) => assert_eq!(e.position, 12.5),
+        _ => panic!("Wrong payload"),
+    }
+
+    // Decode errors still work without an allocator
+    assert!(Message::deserialize(&[0xFF]).is_err());
+}
+
 #[cfg(feature = "joint_api")]
 #[test]
 fn test_joint_state_machine() {
@@ -215,6 +375,172 @@ fn test_joint_message_targeting() {
     assert_eq!(joint.state(), LifecycleState::Unconfigured);
 }
 
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_reports_status() {
+    use irpc::Joint;
+
+    let mut joint = Joint::new(0x0010);
+    let configure = Message {
+        header: Header {
+            source_id: 0x0001,
+            target_id: 0x0010,
+            msg_id: 1,
+        },
+        payload: Payload::Configure,
+    };
+    joint.handle_message(&configure);
+
+    let request = Message {
+        header: Header {
+            source_id: 0x0001,
+            target_id: 0x0010,
+            msg_id: 2,
+        },
+        payload: Payload::RequestStatus,
+    };
+    let response = joint.handle_message(&request).expect("Expected status response");
+
+    assert_eq!(response.header.msg_id, 2);
+    match response.payload {
+        Payload::JointStatus { state, error_code } => {
+            assert_eq!(state, LifecycleState::Inactive);
+            assert_eq!(error_code, 0);
+        }
+        _ => panic!("Expected JointStatus"),
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_discovery_backoff() {
+    use irpc::{Joint, BROADCAST_ADDRESS, DISCOVERY_JITTER_US, DISCOVERY_SLOTS, DISCOVERY_SLOT_US};
+
+    let max_delay = (DISCOVERY_SLOTS * DISCOVERY_SLOT_US + DISCOVERY_JITTER_US) as u64;
+
+    let mut joint = Joint::new(0x0010);
+    joint.set_discovery_seed(0xDEAD_BEEF);
+
+    let discover = Message {
+        header: Header {
+            source_id: 0x0001,
+            target_id: BROADCAST_ADDRESS,
+            msg_id: 77,
+        },
+        payload: Payload::Discover,
+    };
+
+    // The reply is not sent immediately
+    assert!(joint.handle_message(&discover).is_none());
+    assert!(joint.poll(1_000).is_none());
+
+    // ...but is released once the backoff has elapsed
+    let hello = joint.poll(1_000 + max_delay).expect("Expected Hello after backoff");
+    assert_eq!(hello.header.source_id, 0x0010);
+    assert_eq!(hello.header.target_id, 0x0001);
+    assert_eq!(hello.header.msg_id, 77);
+    assert!(matches!(hello.payload, Payload::Hello(_)));
+
+    // Only one reply per request
+    assert!(joint.poll(10 * max_delay).is_none());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_discovery_backoff_spreads_joints() {
+    use irpc::{Joint, DISCOVERY_SLOT_US};
+
+    // Neighbouring joints land in distinct ID-derived slots
+    let mut slots: Vec<u32> = [0x0010, 0x0020, 0x0030, 0x0040]
+        .iter()
+        .map(|&id| Joint::new(id).discovery_backoff_us() / DISCOVERY_SLOT_US)
+        .collect();
+    slots.sort();
+    slots.dedup();
+    assert_eq!(slots.len(), 4);
+
+    // The same ID with different seeds gets different jitter
+    let mut a = Joint::new(0x0010);
+    let mut b = Joint::new(0x0010);
+    a.set_discovery_seed(1);
+    b.set_discovery_seed(2);
+    assert_ne!(a.discovery_backoff_us(), b.discovery_backoff_us());
+}
+
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_position_loss_recovery() {
+    use irpc::{Joint, JointLimits, ERROR_LIMIT_VIOLATION, ERROR_POSITION_UNKNOWN};
+
+    let mut joint = Joint::new(0x0010);
+    let mut msg_id = 0;
+    let mut send = |joint: &mut Joint, payload: Payload| {
+        msg_id += 1;
+        joint
+            .handle_message(&Message {
+                header: Header {
+                    source_id: 0x0001,
+                    target_id: 0x0010,
+                    msg_id,
+                },
+                payload,
+            })
+            .expect("Expected response")
+            .payload
+    };
+    let target = |angle| Payload::SetTarget(SetTargetPayload {
+        target_angle: angle,
+        velocity_limit: 10.0,
+    });
+    let limits = JointLimits {
+        min_position: -90.0,
+        max_position: 90.0,
+        max_velocity: 50.0,
+    };
+
+    send(&mut joint, Payload::Configure);
+    send(&mut joint, Payload::Activate);
+    assert!(matches!(send(&mut joint, Payload::SetLimits(limits)), Payload::Ack(_)));
+    assert!(matches!(
+        send(&mut joint, target(120.0)),
+        Payload::Nack { error: ERROR_LIMIT_VIOLATION, .. }
+    ));
+
+    // Encoder glitch: motion and limits are refused, status reports it
+    joint.report_encoder_fault();
+    assert!(!joint.position_known());
+    assert_eq!(joint.limits(), None);
+    assert!(matches!(
+        send(&mut joint, target(10.0)),
+        Payload::Nack { error: ERROR_POSITION_UNKNOWN, .. }
+    ));
+    assert!(matches!(
+        send(&mut joint, Payload::SetLimits(limits)),
+        Payload::Nack { error: ERROR_POSITION_UNKNOWN, .. }
+    ));
+    assert!(matches!(
+        send(&mut joint, Payload::RequestStatus),
+        Payload::JointStatus { state: LifecycleState::Active, error_code: ERROR_POSITION_UNKNOWN }
+    ));
+
+    // Homing request is acknowledged; firmware finishes it
+    assert!(matches!(send(&mut joint, Payload::Home), Payload::Ack(_)));
+    assert!(joint.homing_requested());
+    joint.complete_homing();
+    assert!(!joint.homing_requested());
+
+    assert!(matches!(
+        send(&mut joint, Payload::RequestStatus),
+        Payload::JointStatus { error_code: 0, .. }
+    ));
+    assert!(matches!(send(&mut joint, target(10.0)), Payload::Ack(_)));
+
+    // Homing needs an active joint
+    send(&mut joint, Payload::Deactivate);
+    assert!(matches!(send(&mut joint, Payload::Home), Payload::Nack { .. }));
+}
+
 /*
 #[cfg(feature = "arm_api")]
 #[tokio::test]
@@ -260,4 +586,195 @@ fn test_joint_client() {
     client.disconnect();
     assert!(!client.is_connected());
 }
-*/
\ No newline at end of file
+*/
+#[test]
+fn test_crash_record_truncates_text() {
+    use irpc::{CrashKind, CrashRecord, CRASH_MESSAGE_LEN, CRASH_TASK_NAME_LEN};
+
+    let record = CrashRecord::new(CrashKind::HardFault, 0x0800_1234, 0x0800_0abc, "joint", "bus fault");
+    assert_eq!(record.task(), "joint");
+    assert_eq!(record.message(), "bus fault");
+
+    // Cut at a character boundary, never in the middle of one
+    let long = "control_loop_task";
+    let message = "é".repeat(CRASH_MESSAGE_LEN);
+    let record = CrashRecord::new(CrashKind::Panic, 0, 0, long, &message);
+    assert_eq!(record.task(), &long[..CRASH_TASK_NAME_LEN]);
+    assert_eq!(record.message(), "é".repeat(CRASH_MESSAGE_LEN / 2));
+
+    // A boot announcement with a full crash record fits one CAN-FD frame
+    let boot = Message {
+        header: Header { source_id: 0x0010, target_id: 0x0001, msg_id: u32::MAX },
+        payload: Payload::Boot(irpc::BootPayload { entity_type: 0x1001, crash: Some(record) }),
+    };
+    assert!(boot.encoded_size() <= irpc::CANFD_MAX_DATA_LEN);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_crash_slot_survives_and_rejects_garbage() {
+    use irpc::crash::CrashSlot;
+    use irpc::{CrashKind, CrashRecord, Joint};
+    use std::mem::MaybeUninit;
+
+    let record = CrashRecord::new(CrashKind::Panic, 0, 0, "joint", "main.rs:42: oops");
+
+    // Leftover RAM contents are not mistaken for a record
+    let mut ram = MaybeUninit::<CrashSlot>::uninit();
+    unsafe { ram.as_mut_ptr().write_bytes(0xA5, 1) };
+    let slot = unsafe { CrashSlot::from_noinit(&mut ram) };
+    assert_eq!(slot.peek(), None);
+
+    // Stored before the reset, taken once after it
+    slot.store(&record);
+    assert_eq!(slot.peek(), Some(record));
+    assert_eq!(slot.take(), Some(record));
+    assert_eq!(slot.take(), None);
+
+    // A corrupted record is dropped
+    let mut slot = CrashSlot::new();
+    slot.store(&record);
+    let bytes = unsafe {
+        std::slice::from_raw_parts_mut(&mut slot as *mut CrashSlot as *mut u8, std::mem::size_of::<CrashSlot>())
+    };
+    bytes[12] ^= 0x01;
+    assert_eq!(slot.take(), None);
+
+    let boot = Joint::new(0x0010).boot_announcement(Some(record));
+    assert_eq!(boot.header.source_id, 0x0010);
+    assert!(matches!(boot.payload, Payload::Boot(b) if b.crash == Some(record)));
+}
+
+#[test]
+fn test_compat_translation() {
+    use irpc::compat::{translate, PayloadGeneration};
+    use irpc::{MotionProfile, SetTargetPayloadV2};
+
+    let v1 = Payload::SetTarget(SetTargetPayload {
+        target_angle: 45.0,
+        velocity_limit: 30.0,
+    });
+    assert_eq!(PayloadGeneration::of(&v1), Some(PayloadGeneration::V1));
+    let Payload::SetTargetV2(v2) = translate(v1, PayloadGeneration::V2) else {
+        panic!("SetTarget should upgrade to SetTargetV2");
+    };
+    assert_eq!(v2.target_angle, 45.0);
+    assert_eq!(v2.max_velocity, 30.0);
+    assert_eq!(v2.target_velocity, 0.0);
+    assert_eq!(v2.max_acceleration, irpc::V1_TARGET_ACCELERATION_DPS2);
+    assert!(matches!(v2.profile, MotionProfile::Trapezoidal));
+
+    // Downgrading keeps what v1 can express
+    let v2 = SetTargetPayloadV2 { max_jerk: 500.0, profile: MotionProfile::SCurve, ..v2 };
+    let Payload::SetTarget(v1) = translate(Payload::SetTargetV2(v2), PayloadGeneration::V1) else {
+        panic!("SetTargetV2 should downgrade to SetTarget");
+    };
+    assert_eq!((v1.target_angle, v1.velocity_limit), (45.0, 30.0));
+
+    let encoder = Payload::Encoder(EncoderTelemetry { position: 12.5, velocity: -3.0 });
+    let Payload::TelemetryStream(stream) = translate(encoder, PayloadGeneration::V2) else {
+        panic!("Encoder should upgrade to TelemetryStream");
+    };
+    assert_eq!((stream.position, stream.velocity, stream.timestamp_us), (12.5, -3.0, 0));
+    let Payload::Encoder(encoder) = translate(Payload::TelemetryStream(stream), PayloadGeneration::V1) else {
+        panic!("TelemetryStream should downgrade to Encoder");
+    };
+    assert_eq!((encoder.position, encoder.velocity), (12.5, -3.0));
+
+    // Payloads without a counterpart pass through
+    assert_eq!(PayloadGeneration::of(&Payload::Home), None);
+    assert!(matches!(translate(Payload::Home, PayloadGeneration::V1), Payload::Home));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_accepts_both_target_generations() {
+    use irpc::{Joint, MotionProfile, SetTargetPayloadV2, ERROR_UNKNOWN_COMMAND};
+
+    let mut joint = Joint::new(0x0010);
+    let send = |joint: &mut Joint, msg_id, payload| {
+        let message = Message {
+            header: Header { source_id: 0x0001, target_id: 0x0010, msg_id },
+            payload,
+        };
+        joint.handle_message(&message).map(|reply| reply.payload)
+    };
+    send(&mut joint, 1, Payload::Configure);
+    send(&mut joint, 2, Payload::Activate);
+
+    // v1 targets are stored in their v2 form
+    let v1 = Payload::SetTarget(SetTargetPayload { target_angle: 20.0, velocity_limit: 10.0 });
+    assert!(matches!(send(&mut joint, 3, v1), Some(Payload::Ack(3))));
+    let target = joint.target().unwrap();
+    assert_eq!((target.target_angle, target.max_velocity), (20.0, 10.0));
+
+    let v2 = SetTargetPayloadV2 {
+        target_angle: -15.0,
+        max_velocity: 20.0,
+        target_velocity: 0.0,
+        max_acceleration: 100.0,
+        max_deceleration: 100.0,
+        max_jerk: 0.0,
+        profile: MotionProfile::Trapezoidal,
+        max_current: 0.0,
+        max_temperature: 0.0,
+    };
+    assert!(matches!(send(&mut joint, 4, Payload::SetTargetV2(v2)), Some(Payload::Ack(4))));
+    assert_eq!(joint.target().unwrap().target_angle, -15.0);
+
+    // Firmware that has dropped v1 rejects it as unknown
+    joint.set_accept_v1(false);
+    let v1 = Payload::SetTarget(SetTargetPayload { target_angle: 20.0, velocity_limit: 10.0 });
+    assert!(matches!(
+        send(&mut joint, 5, v1),
+        Some(Payload::Nack { id: 5, error: ERROR_UNKNOWN_COMMAND })
+    ));
+    assert_eq!(joint.target().unwrap().target_angle, -15.0);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_bootloader_handoff() {
+    use irpc::{BootInfoPayload, BootMode, FirmwareVersion, Joint, ERROR_IN_BOOTLOADER};
+
+    let command = |msg_id, payload| Message {
+        header: Header { source_id: 0x0001, target_id: 0x0010, msg_id },
+        payload,
+    };
+
+    let mut joint = Joint::new(0x0010);
+    joint.handle_message(&command(1, Payload::Configure));
+    joint.handle_message(&command(2, Payload::Activate));
+    let reply = joint.handle_message(&command(3, Payload::EnterBootloader)).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { id: 3, .. }));
+    assert!(!joint.bootloader_requested());
+
+    joint.handle_message(&command(4, Payload::Deactivate));
+    let reply = joint.handle_message(&command(5, Payload::EnterBootloader)).unwrap();
+    assert!(matches!(reply.payload, Payload::Ack(5)));
+    assert!(joint.bootloader_requested());
+
+    // The bootloader, with no valid application left
+    let mut bootloader = Joint::new(0x0010);
+    let info = BootInfoPayload {
+        mode: BootMode::Bootloader,
+        app_version: FirmwareVersion::NONE,
+        bootloader_version: FirmwareVersion::new(1, 2, 0),
+        boot_count: 17,
+    };
+    bootloader.set_boot_info(info);
+    let reply = bootloader.handle_message(&command(6, Payload::RequestBootInfo)).unwrap();
+    assert!(matches!(reply.payload, Payload::BootInfo(i) if i == info));
+    let reply = bootloader.handle_message(&command(7, Payload::Configure)).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { id: 7, error: ERROR_IN_BOOTLOADER }));
+    let reply = bootloader.handle_message(&command(8, Payload::RequestStatus)).unwrap();
+    assert!(matches!(reply.payload, Payload::JointStatus { error_code: ERROR_IN_BOOTLOADER, .. }));
+
+    let discover = Message {
+        header: Header { source_id: 0x0001, target_id: 0x0000, msg_id: 9 },
+        payload: Payload::Discover,
+    };
+    assert!(bootloader.handle_message(&discover).is_none());
+    let hello = bootloader.poll(0).or_else(|| bootloader.poll(u64::MAX / 2)).unwrap();
+    assert!(matches!(hello.payload, Payload::Hello(h) if h.mode == BootMode::Bootloader));
+}
@@ -0,0 +1,116 @@
+//! Tests for Cartesian jogging
+
+#[cfg(feature = "arm_api")]
+use irpc::kinematics::{ArmModel, DhParameters, Link};
+
+/// Two links of 0.4 m and 0.3 m moving in the base's xy plane
+#[cfg(feature = "arm_api")]
+fn planar_arm() -> ArmModel {
+    let link = |joint_id, length: f64| Link {
+        joint_id,
+        dh: DhParameters { a: length, ..Default::default() },
+        mass: 1.0,
+        center_of_mass: [-length / 2.0, 0.0, 0.0],
+    };
+    ArmModel::new(vec![link(0x0010, 0.4), link(0x0011, 0.3)])
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_joint_rates_move_the_flange_along_the_axis() {
+    use irpc::kinematics::CartesianAxis;
+
+    let arm = planar_arm();
+    let angles = [0.0, 90.0];
+    let flange = arm.flange_position(&angles).unwrap();
+    assert!((flange[0] - 0.4).abs() < 1e-9 && (flange[1] - 0.3).abs() < 1e-9, "{:?}", flange);
+
+    // A millisecond at the rates moves the flange 0.1 mm along -x only
+    let rates = arm.joint_rates(&angles, CartesianAxis::X, -0.1).unwrap();
+    let moved: Vec<f32> = angles.iter().zip(&rates).map(|(a, r)| a + r * 0.001).collect();
+    let after = arm.flange_position(&moved).unwrap();
+    assert!((after[0] - flange[0] + 1e-4).abs() < 2e-6, "{:?}", after);
+    assert!((after[1] - flange[1]).abs() < 2e-6, "{:?}", after);
+
+    // Both joints turn the flange about z; the rotation is shared evenly
+    let rates = arm.joint_rates(&angles, CartesianAxis::Rz, 10.0).unwrap();
+    assert!((rates[0] - 5.0).abs() < 0.01 && (rates[1] - 5.0).abs() < 0.01, "{:?}", rates);
+    assert!(arm.joint_rates(&[0.0], CartesianAxis::Z, 0.1).is_err());
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+async fn jogging_arm() -> (std::sync::Arc<irpc::bus::sim::SimBus>, irpc::ArmOrchestrator) {
+    use irpc::bus::sim::SimBus;
+    use irpc::{ArmOrchestrator, CommunicationManager};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0011]));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    arm.add_joint(0x0010);
+    arm.add_joint(0x0011);
+    arm.configure_all().await.into_result().unwrap();
+    arm.activate_all().await.into_result().unwrap();
+    arm.set_arm_model(planar_arm());
+
+    // Elbow bent: the flange at (0.4, 0.3)
+    arm.get_joint(0x0011).unwrap().set_target(90.0, 90.0).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    (bus, arm)
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_jog_streams_a_straight_line_until_stopped() {
+    use irpc::kinematics::{CartesianAxis, KinematicsError};
+    use std::time::Duration;
+
+    let (bus, mut arm) = jogging_arm().await;
+    let flange = |bus: &irpc::bus::sim::SimBus| {
+        let angles = [bus.true_position(0x0010).unwrap(), bus.true_position(0x0011).unwrap()];
+        planar_arm().flange_position(&angles).unwrap()
+    };
+
+    arm.jog(CartesianAxis::X, -0.05).await.unwrap();
+    assert!(arm.is_jogging());
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    arm.stop_jog().await;
+    assert!(!arm.is_jogging());
+    let stopped = flange(&bus);
+    assert!((stopped[0] - 0.35).abs() < 0.005, "{:?}", stopped);
+    assert!((stopped[1] - 0.3).abs() < 0.005, "{:?}", stopped);
+
+    // Nothing moves once stopped
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!((flange(&bus)[0] - stopped[0]).abs() < 0.002);
+
+    let mut unmodelled = irpc::ArmOrchestrator::with_comm_manager(irpc::CommunicationManager::with_adapter(bus));
+    unmodelled.add_joint(0x0010);
+    assert!(matches!(unmodelled.jog(CartesianAxis::Y, 0.05).await, Err(KinematicsError::NoModel)));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_jog_scales_joint_speeds_and_stops_at_limits() {
+    use irpc::kinematics::CartesianAxis;
+    use irpc::JointLimits;
+    use std::time::Duration;
+
+    let (bus, mut arm) = jogging_arm().await;
+    let limits = |max_velocity| JointLimits { min_position: -10.0, max_position: 10.0, max_velocity };
+    arm.get_joint(0x0010).unwrap().set_limits(limits(2.0)).await.unwrap();
+
+    // Rotating about z turns both joints alike; the limited shoulder holds
+    // the elbow back to its speed
+    arm.jog(CartesianAxis::Rz, 20.0).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let shoulder = bus.true_position(0x0010).unwrap();
+    let elbow = bus.true_position(0x0011).unwrap() - 90.0;
+    assert!((shoulder - 2.0).abs() < 0.1, "{}", shoulder);
+    assert!((elbow - shoulder).abs() < 0.1, "{} {}", elbow, shoulder);
+
+    // The jog ends on its own before the shoulder passes 10°
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    assert!(!arm.is_jogging());
+    let shoulder = bus.true_position(0x0010).unwrap();
+    assert!(shoulder <= 10.0 && shoulder > 9.9, "{}", shoulder);
+}
@@ -0,0 +1,97 @@
+//! Tests for joint-side diagnostics
+
+#[cfg(feature = "joint_api")]
+static EVENTS: std::sync::Mutex<Vec<(u16, irpc::joint_log::JointEvent)>> = std::sync::Mutex::new(Vec::new());
+
+/// Events logged by one joint; tests use distinct IDs as they share the log
+#[cfg(feature = "joint_api")]
+fn logged(joint: u16) -> Vec<irpc::joint_log::JointEvent> {
+    EVENTS.lock().unwrap().iter().filter(|(id, _)| *id == joint).map(|(_, event)| *event).collect()
+}
+
+#[cfg(feature = "joint_api")]
+fn record(joint: u16, event: &irpc::joint_log::JointEvent) {
+    EVENTS.lock().unwrap().push((joint, *event));
+}
+
+#[cfg(feature = "joint_api")]
+fn command(joint: u16, msg_id: u32, payload: irpc::Payload) -> irpc::Message {
+    irpc::Message { header: irpc::Header { source_id: 0x0001, target_id: joint, msg_id }, payload }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_transitions_and_refusals_are_logged() {
+    use irpc::joint_log::JointEvent;
+    use irpc::{Joint, LifecycleState, Payload, ERROR_UNKNOWN_COMMAND};
+
+    let mut joint = Joint::new(0x0011);
+    joint.set_logger(Some(record));
+    joint.handle_message(&command(0x0011, 1, Payload::Configure));
+    joint.handle_message(&command(0x0011, 2, Payload::Configure));
+    joint.handle_message(&command(0x0011, 3, Payload::EnterBootloader));
+    joint.handle_message(&command(0x0011, 4, Payload::ArmReady));
+    joint.handle_message(&command(0x0011, 5, Payload::RequestStatus));
+
+    assert_eq!(
+        logged(0x0011),
+        [
+            JointEvent::StateChanged { from: LifecycleState::Unconfigured, to: LifecycleState::Inactive },
+            JointEvent::Nacked { command: "Configure", msg_id: 2, error: 1 },
+            JointEvent::Nacked { command: "ArmReady", msg_id: 4, error: ERROR_UNKNOWN_COMMAND },
+        ]
+    );
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_faults_and_homing_are_logged() {
+    use irpc::joint_log::JointEvent;
+    use irpc::{Capabilities, Joint, LifecycleState, Payload, ERROR_BRAKE_ENGAGED};
+
+    let mut joint = Joint::new(0x0012);
+    joint.set_capabilities(joint.capabilities() | Capabilities::BRAKE);
+    joint.handle_message(&command(0x0012, 1, Payload::Configure));
+    joint.handle_message(&command(0x0012, 2, Payload::Activate));
+    joint.set_logger(Some(record));
+    joint.handle_message(&command(0x0012, 3, Payload::Home));
+    joint.handle_message(&command(0x0012, 4, Payload::ReleaseBrake));
+    joint.handle_message(&command(0x0012, 5, Payload::Home));
+    joint.complete_homing();
+    joint.report_fault();
+    // Already faulted: nothing changes
+    joint.report_fault();
+
+    use LifecycleState::*;
+    assert_eq!(
+        logged(0x0012),
+        [
+            JointEvent::Nacked { command: "Home", msg_id: 3, error: ERROR_BRAKE_ENGAGED },
+            JointEvent::StateChanged { from: Active, to: Calibrating },
+            JointEvent::StateChanged { from: Calibrating, to: Active },
+            JointEvent::StateChanged { from: Active, to: Error },
+        ]
+    );
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_restart_is_logged_at_boot() {
+    use irpc::crash::watchdog_record;
+    use irpc::joint_log::JointEvent;
+    use irpc::{CrashKind, Joint, Payload};
+
+    let mut joint = Joint::new(0x0013);
+    joint.boot_announcement(Some(watchdog_record()));
+    assert!(logged(0x0013).is_empty());
+
+    joint.set_logger(Some(record));
+    joint.boot_announcement(None);
+    let boot = joint.boot_announcement(Some(watchdog_record()));
+    assert!(matches!(boot.payload, Payload::Boot(boot) if boot.crash.unwrap().kind == CrashKind::Watchdog));
+    assert_eq!(logged(0x0013), [JointEvent::Restarted(watchdog_record())]);
+
+    joint.set_logger(None);
+    joint.handle_message(&command(0x0013, 1, Payload::Activate));
+    assert_eq!(logged(0x0013).len(), 1);
+}
@@ -0,0 +1,90 @@
+//! Tests for request tracing spans and latency histograms
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_latency_histogram() {
+    use irpc::latency::LatencyHistogram;
+    use std::time::Duration;
+
+    let mut histogram = LatencyHistogram::new();
+    assert_eq!((histogram.count(), histogram.quantile(0.5), histogram.mean()), (0, None, None));
+    for micros in [100, 120, 300, 900, 5_000] {
+        histogram.record(Duration::from_micros(micros));
+    }
+    assert_eq!(histogram.count(), 5);
+    assert_eq!(histogram.min(), Some(Duration::from_micros(100)));
+    assert_eq!(histogram.max(), Some(Duration::from_micros(5_000)));
+    assert_eq!(histogram.mean(), Some(Duration::from_micros(1_284)));
+    // 100 and 120 µs share the 128 µs bucket
+    assert_eq!(histogram.buckets().next(), Some((Duration::from_micros(128), 2)));
+    assert_eq!(histogram.quantile(0.4), Some(Duration::from_micros(128)));
+    assert_eq!(histogram.quantile(0.6), Some(Duration::from_micros(512)));
+    assert_eq!(histogram.quantile(1.0), Some(Duration::from_micros(5_000)));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_latency_tracking_per_payload() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, JointProxy};
+    use std::sync::Arc;
+
+    let comm = CommunicationManager::with_adapter(Arc::new(SimBus::with_joints([0x0010])));
+    let joint = JointProxy::new(0x0010, comm.clone());
+    joint.configure().await.unwrap();
+    assert!(comm.latency_histograms().is_empty());
+
+    comm.set_latency_tracking(true);
+    joint.activate().await.unwrap();
+    joint.set_target(10.0, 90.0).await.unwrap();
+    joint.set_target(20.0, 90.0).await.unwrap();
+    let histograms = comm.latency_histograms();
+    assert_eq!(histograms.keys().copied().collect::<Vec<_>>(), ["Activate", "SetTarget"]);
+    assert_eq!(comm.latency_histogram("SetTarget").unwrap().count(), 2);
+    assert!(comm.latency_histogram("Configure").is_none());
+
+    comm.set_latency_tracking(false);
+    assert!(comm.latency_histograms().is_empty());
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_timeout_logged_in_request_span() {
+    use irpc::{CommunicationManager, JointProxy, ProtocolError};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Log(Arc<Mutex<Vec<u8>>>);
+    impl Write for Log {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(bytes)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let log = Log::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer({
+            let log = log.clone();
+            move || log.clone()
+        })
+        .with_ansi(false)
+        .with_max_level(tracing::Level::DEBUG)
+        .finish();
+    let _default = tracing::subscriber::set_default(subscriber);
+
+    let comm = Arc::new(CommunicationManager::deterministic(31));
+    comm.set_joint_name(0x0030, Some("wrist".into()));
+    let joint = JointProxy::new(0x0030, comm.clone());
+    assert!(matches!(joint.configure().await, Err(ProtocolError::Timeout)));
+
+    let log = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+    let timeout = log.lines().find(|line| line.contains("No reply")).expect("timeout not logged");
+    let msg_id = comm.poll_outbound().unwrap().header.msg_id;
+    let span = format!("request{{msg_id={} device_id=48 payload=\"Configure\"}}", msg_id);
+    assert!(timeout.contains(&span), "{}", timeout);
+    assert!(timeout.contains("joint{joint=wrist (48) command=\"configure\"}"), "{}", timeout);
+}
@@ -0,0 +1,198 @@
+//! Property tests for the joint lifecycle state machine
+//!
+//! Arbitrary sequences of commands and firmware events are run against a
+//! `Joint`, checking its replies and state against the lifecycle rules
+//! after every step.
+
+#![cfg(feature = "joint_api")]
+
+use irpc::{
+    Capabilities, ControlMode, Header, Joint, LifecycleState, Message, MultiTurnPosition, Payload,
+    SetTargetPayload,
+};
+use proptest::prelude::*;
+
+const JOINT_ID: u16 = 0x0010;
+const OTHER_ID: u16 = 0x0020;
+
+/// One thing that happens to the joint
+#[derive(Debug, Clone)]
+enum Step {
+    /// A command from the arm, to this joint or to another one
+    Command { payload: Payload, to_self: bool },
+    /// Firmware reports an unrecoverable fault
+    Fault,
+    /// Firmware's homing routine finished or gave up
+    CompleteHoming,
+    AbortHoming,
+    /// A new encoder reading, then time passes
+    Tick { degrees: f32, elapsed_us: u64 },
+}
+
+fn command() -> impl Strategy<Value = Payload> {
+    let angle = -360.0f32..360.0;
+    prop_oneof![
+        3 => Just(Payload::Configure),
+        3 => Just(Payload::Activate),
+        2 => Just(Payload::Deactivate),
+        1 => Just(Payload::Reset),
+        2 => (angle.clone(), 1.0f32..90.0).prop_map(|(target_angle, velocity_limit)| {
+            Payload::SetTarget(SetTargetPayload { target_angle, velocity_limit })
+        }),
+        1 => Just(Payload::Home),
+        1 => Just(Payload::StopCalibration),
+        1 => Just(Payload::RequestStatus),
+        1 => angle.clone().prop_map(|degrees| Payload::SetEncoderOffset(MultiTurnPosition::from_degrees(degrees))),
+        1 => Just(Payload::EngageBrake),
+        1 => Just(Payload::ReleaseBrake),
+        1 => prop_oneof![Just(ControlMode::Position), Just(ControlMode::Velocity)].prop_map(Payload::SetControlMode),
+        1 => angle.prop_map(|dps| Payload::SetVelocity { dps }),
+    ]
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    prop_oneof![
+        12 => (command(), prop::bool::weighted(0.9)).prop_map(|(payload, to_self)| Step::Command { payload, to_self }),
+        1 => Just(Step::Fault),
+        1 => Just(Step::CompleteHoming),
+        1 => Just(Step::AbortHoming),
+        2 => (-360.0f32..360.0, 0u64..50_000).prop_map(|(degrees, elapsed_us)| Step::Tick { degrees, elapsed_us }),
+    ]
+}
+
+/// What the lifecycle allows since the last reset
+#[derive(Debug, Default)]
+struct Model {
+    configured: bool,
+    activated: bool,
+}
+
+/// Run `steps` against a fresh joint, checking every invariant on the way
+fn check(capabilities: Capabilities, steps: &[Step]) -> Result<(), TestCaseError> {
+    let mut joint = Joint::new(JOINT_ID);
+    joint.set_capabilities(capabilities);
+    let mut model = Model::default();
+    let mut now_us = 0;
+
+    for (msg_id, step) in (1..).zip(steps) {
+        let before = joint.state();
+        let mut reset = false;
+        match step {
+            Step::Command { payload, to_self } => {
+                let target_id = if *to_self { JOINT_ID } else { OTHER_ID };
+                let header = Header { source_id: 0x0001, target_id, msg_id };
+                let reply = joint.handle_message(&Message { header, payload: payload.clone() });
+                if !to_self {
+                    prop_assert!(reply.is_none(), "answered a command for another joint: {:?}", reply);
+                    continue;
+                }
+
+                // Every command to the joint gets exactly one reply, to the sender
+                let Some(reply) = reply else { return Err(TestCaseError::fail(format!("no reply to {:?}", payload))) };
+                prop_assert_eq!((reply.header.source_id, reply.header.target_id), (JOINT_ID, 0x0001));
+                prop_assert_eq!(reply.header.msg_id, msg_id);
+                let acked = match reply.payload {
+                    Payload::Ack(id) | Payload::Nack { id, .. } => {
+                        prop_assert_eq!(id, msg_id);
+                        matches!(reply.payload, Payload::Ack(_))
+                    }
+                    Payload::JointStatus { state, .. } => {
+                        prop_assert_eq!(state, before);
+                        false
+                    }
+                    ref other => return Err(TestCaseError::fail(format!("{:?} answered with {:?}", payload, other))),
+                };
+
+                match payload {
+                    Payload::Reset => {
+                        prop_assert!(acked);
+                        prop_assert_eq!(joint.state(), LifecycleState::Unconfigured);
+                        model = Model::default();
+                        reset = true;
+                    }
+                    Payload::Configure if acked => {
+                        prop_assert_eq!(before, LifecycleState::Unconfigured);
+                        model = Model { configured: true, activated: false };
+                    }
+                    Payload::Activate if acked => {
+                        prop_assert_eq!(before, LifecycleState::Inactive);
+                        prop_assert!(model.configured);
+                        model.activated = true;
+                    }
+                    Payload::Configure | Payload::Activate => prop_assert_eq!(joint.state(), before),
+                    _ => {}
+                }
+            }
+            Step::Fault => {
+                joint.report_fault();
+                prop_assert_eq!(joint.state(), LifecycleState::Error);
+            }
+            Step::CompleteHoming => joint.complete_homing(),
+            Step::AbortHoming => joint.abort_homing(),
+            Step::Tick { degrees, elapsed_us } => {
+                joint.update_encoder(MultiTurnPosition::from_degrees(*degrees));
+                now_us += elapsed_us;
+                // Replies are never held back for a later poll
+                while let Some(message) = joint.poll(now_us) {
+                    prop_assert!(!matches!(message.payload, Payload::Ack(_) | Payload::Nack { .. }), "{:?}", message);
+                }
+            }
+        }
+
+        let state = joint.state();
+        if matches!(state, LifecycleState::Active | LifecycleState::Calibrating) {
+            prop_assert!(model.configured && model.activated, "{:?} without Configure and Activate", state);
+        }
+        if before == LifecycleState::Error && !reset {
+            prop_assert_eq!(state, LifecycleState::Error, "left Error without a Reset");
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn lifecycle_invariants_hold(steps in prop::collection::vec(step(), 1..64)) {
+        check(Capabilities::NONE, &steps)?;
+    }
+
+    #[test]
+    fn lifecycle_invariants_hold_with_brake_and_direct_control(steps in prop::collection::vec(step(), 1..64)) {
+        check(Capabilities::BRAKE | Capabilities::DIRECT_CONTROL, &steps)?;
+    }
+}
+
+#[test]
+fn test_reset_from_any_state_reaches_unconfigured() {
+    let send = |joint: &mut Joint, msg_id, payload| {
+        joint.handle_message(&Message { header: Header { source_id: 0x0001, target_id: JOINT_ID, msg_id }, payload })
+    };
+    let mut joint = Joint::new(JOINT_ID);
+    joint.update_encoder(MultiTurnPosition::from_degrees(0.0));
+    let paths: [&[Payload]; 4] = [
+        &[],
+        &[Payload::Configure],
+        &[Payload::Configure, Payload::Activate],
+        &[Payload::Configure, Payload::Activate, Payload::Home],
+    ];
+    for (path, expected) in paths.iter().zip([
+        LifecycleState::Unconfigured,
+        LifecycleState::Inactive,
+        LifecycleState::Active,
+        LifecycleState::Calibrating,
+    ]) {
+        for payload in path.iter() {
+            send(&mut joint, 1, payload.clone());
+        }
+        assert_eq!(joint.state(), expected);
+        assert!(matches!(send(&mut joint, 2, Payload::Reset).unwrap().payload, Payload::Ack(2)));
+        assert_eq!(joint.state(), LifecycleState::Unconfigured);
+    }
+
+    joint.report_fault();
+    assert!(matches!(send(&mut joint, 3, Payload::Configure).unwrap().payload, Payload::Nack { id: 3, .. }));
+    send(&mut joint, 4, Payload::Reset);
+    assert_eq!(joint.state(), LifecycleState::Unconfigured);
+}
@@ -0,0 +1,118 @@
+//! Property tests for message decoding
+//!
+//! Quick, deterministic counterparts of the cargo-fuzz targets in `fuzz/`:
+//! whatever arrives off the wire must decode or fail, never panic.
+
+use irpc::framing::{encode_message, FrameDecoder, MAX_MESSAGE_FRAME_LEN};
+use irpc::Message;
+use proptest::prelude::*;
+
+/// Payload tags in use, and a few past the last one
+const TAGS: core::ops::Range<u8> = 0..80;
+
+/// A decodable message body, or `None` if the bytes do not form one
+fn decode(bytes: &[u8]) -> Option<Message> {
+    Message::deserialize(bytes).ok()
+}
+
+/// Header bytes followed by a tag and tail, mostly decodable
+fn message_bytes() -> impl Strategy<Value = Vec<u8>> {
+    (any::<[u8; 3]>(), TAGS, prop::collection::vec(any::<u8>(), 0..Message::max_size())).prop_map(
+        |(header, tag, tail)| {
+            // Short varints keep the header a single byte per field
+            let mut bytes: Vec<u8> = header.iter().map(|b| b & 0x7F).collect();
+            bytes.push(tag);
+            bytes.extend(tail);
+            bytes
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn decoded_messages_round_trip(bytes in message_bytes()) {
+        if let Some(message) = decode(&bytes) {
+            let mut buf = [0u8; Message::max_size()];
+            let len = message.serialize_into(&mut buf).unwrap();
+            // Overlong varints decode too, so the encoding may be shorter
+            // than the input rather than a copy of it
+            prop_assert!(len <= bytes.len());
+            let again = decode(&buf[..len]).unwrap();
+            prop_assert_eq!(again.payload.tag(), message.payload.tag());
+            let mut again_buf = [0u8; Message::max_size()];
+            let again_len = again.serialize_into(&mut again_buf).unwrap();
+            prop_assert_eq!(&again_buf[..again_len], &buf[..len]);
+        }
+    }
+
+    #[test]
+    fn damaged_input_never_panics(
+        bytes in message_bytes(),
+        cut in any::<prop::sample::Index>(),
+        flip in any::<prop::sample::Index>(),
+        bit in 0u8..8,
+        padding in prop::collection::vec(any::<u8>(), 0..4 * Message::max_size()),
+    ) {
+        let truncated = &bytes[..cut.index(bytes.len() + 1)];
+        let mut flipped = bytes.clone();
+        flipped[flip.index(bytes.len())] ^= 1 << bit;
+        let oversize = [bytes.as_slice(), &padding].concat();
+
+        for input in [truncated, &flipped, &oversize] {
+            let _ = decode(input);
+            if let Some((id, data)) = input.split_first_chunk::<4>() {
+                let _ = decode_can(u32::from_le_bytes(*id), data);
+            }
+            let mut decoder = FrameDecoder::<MAX_MESSAGE_FRAME_LEN>::new();
+            for &byte in input {
+                let _ = decoder.push_message(byte);
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted_frames_are_rejected(bytes in message_bytes(), flip in any::<prop::sample::Index>(), bit in 0u8..8) {
+        let Some(message) = decode(&bytes) else { return Ok(()) };
+        let mut frame = [0u8; MAX_MESSAGE_FRAME_LEN];
+        let len = encode_message(&message, &mut frame).unwrap();
+
+        // One flipped bit anywhere before the delimiter
+        frame[flip.index(len - 1)] ^= 1 << bit;
+        let mut decoder = FrameDecoder::<MAX_MESSAGE_FRAME_LEN>::new();
+        let results: Vec<_> = frame[..len].iter().filter_map(|&b| decoder.push_message(b)).collect();
+        prop_assert!(results.iter().all(Result::is_err), "{:?}", results);
+    }
+}
+
+/// A CAN-FD frame, where the transport is built
+#[cfg(feature = "joint_api")]
+fn decode_can(raw_id: u32, data: &[u8]) -> Option<Message> {
+    irpc::transport::canfd::decode_frame(raw_id, data).ok()
+}
+
+#[cfg(not(feature = "joint_api"))]
+fn decode_can(_raw_id: u32, _data: &[u8]) -> Option<Message> {
+    None
+}
+
+#[cfg(feature = "joint_api")]
+proptest! {
+    #[test]
+    fn joints_survive_any_decoded_message(messages in prop::collection::vec(message_bytes(), 1..16)) {
+        use irpc::{Header, Joint, MultiTurnPosition, Payload};
+
+        let mut joint = Joint::new(0x0010);
+        joint.update_encoder(MultiTurnPosition::from_degrees(0.0));
+        for (msg_id, payload) in [(1, Payload::Configure), (2, Payload::Activate)] {
+            let header = Header { source_id: 0x0001, target_id: 0x0010, msg_id };
+            joint.handle_message(&Message { header, payload });
+        }
+        for (i, bytes) in messages.iter().enumerate() {
+            if let Some(mut message) = decode(bytes) {
+                message.header.target_id = 0x0010;
+                joint.handle_message(&message);
+            }
+            joint.poll(i as u64 * 1_000);
+        }
+    }
+}
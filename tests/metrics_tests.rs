@@ -0,0 +1,60 @@
+//! Tests for the host metrics (one test: the recorder is process-global)
+
+#[cfg(all(feature = "metrics", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_joint_metrics() {
+    use irpc::bus::sim::SimBus;
+    use irpc::metrics::*;
+    use irpc::{CommunicationManager, JointProxy, ProtocolError};
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use std::time::Duration;
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    recorder.install().unwrap();
+    describe();
+
+    let bus = std::sync::Arc::new(SimBus::with_joints([0x0010]).with_telemetry(Duration::from_millis(10)));
+    bus.with_joint(0x0010, |joint| joint.set_accept_v1(false));
+    let comm = CommunicationManager::with_adapter(bus);
+    let proxy = JointProxy::new(0x0010, comm.clone());
+    proxy.configure().await.unwrap();
+    proxy.activate().await.unwrap();
+    // Falls back to SetTargetV2 after one refused attempt
+    proxy.set_target(10.0, 90.0).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    proxy.deactivate().await.unwrap();
+    assert!(proxy.set_target(0.0, 90.0).await.is_err());
+    let missing = JointProxy::new(0x0030, comm);
+    assert!(matches!(missing.configure().await, Err(ProtocolError::Timeout)));
+
+    let metrics: Vec<_> = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| {
+            let (_, key) = key.into_parts();
+            let joint = key.labels().find(|l| l.key() == "joint").map(|l| l.value().to_string());
+            (key.name().to_string(), joint.unwrap_or_default(), value)
+        })
+        .collect();
+    let value = |name: &str, joint: &str| {
+        metrics
+            .iter()
+            .find(|(n, j, _)| n == name && j == joint)
+            .map(|(_, _, value)| value)
+    };
+
+    // configure, activate, two set_target attempts, deactivate, refused set_target
+    assert!(matches!(value(COMMAND_LATENCY, "16"), Some(DebugValue::Histogram(h)) if h.len() == 6));
+    assert_eq!(value(COMMAND_NACKS, "16"), Some(&DebugValue::Counter(2)));
+    assert_eq!(value(COMMAND_RETRIES, "16"), Some(&DebugValue::Counter(1)));
+    assert_eq!(value(COMMAND_TIMEOUTS, "16"), None);
+    assert_eq!(value(COMMAND_TIMEOUTS, "48"), Some(&DebugValue::Counter(1)));
+    assert!(matches!(value(TELEMETRY_SAMPLES, "16"), Some(DebugValue::Counter(n)) if *n >= 5));
+    assert!(matches!(value(JOINT_TEMPERATURE, "16"), Some(DebugValue::Gauge(t)) if t.0 == 25.0));
+    assert!(matches!(value(JOINT_LOAD, "16"), Some(DebugValue::Gauge(_))));
+    assert!(matches!(value(JOINT_LIFECYCLE_STATE, "16"), Some(DebugValue::Gauge(s)) if s.0 == 1.0));
+    // Not per joint; the queue has drained
+    assert_eq!(value(OUTBOUND_QUEUE_DEPTH, ""), Some(&DebugValue::Gauge(0.0.into())));
+}
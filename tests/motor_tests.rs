@@ -0,0 +1,120 @@
+//! Tests for the motor driver interface
+
+#[cfg(feature = "joint_api")]
+mod common;
+
+#[cfg(feature = "joint_api")]
+use common::command;
+#[cfg(feature = "joint_api")]
+use irpc::motor::{DriverFaults, MotorCommand, MotorDriver, MotorError};
+#[cfg(feature = "joint_api")]
+use irpc::{Capabilities, Joint, LifecycleState, Message, Payload};
+
+/// A power stage that records what the joint asked of it
+#[cfg(feature = "joint_api")]
+#[derive(Default)]
+struct MockDriver {
+    enabled: bool,
+    refuse_enable: bool,
+    faults: DriverFaults,
+    volts: Option<f32>,
+}
+
+#[cfg(feature = "joint_api")]
+impl MotorDriver for MockDriver {
+    fn enable(&mut self) -> Result<(), MotorError> {
+        if self.refuse_enable {
+            return Err(MotorError::Fault);
+        }
+        self.enabled = true;
+        Ok(())
+    }
+
+    fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    fn set_voltage(&mut self, volts: f32) -> Result<(), MotorError> {
+        if !self.faults.is_empty() {
+            return Err(MotorError::Fault);
+        }
+        self.volts = Some(volts);
+        Ok(())
+    }
+
+    fn faults(&mut self) -> DriverFaults {
+        self.faults
+    }
+}
+
+/// What `run_embassy` does with every message
+#[cfg(feature = "joint_api")]
+fn deliver(joint: &mut Joint, driver: &mut MockDriver, msg: &Message) -> Option<Message> {
+    let reply = joint.handle_motor(msg, driver).or_else(|| joint.handle_message(msg));
+    joint.sync_driver(driver);
+    reply
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_driver_follows_lifecycle() {
+    let mut joint = Joint::new(0x0010);
+    let mut driver = MockDriver::default();
+
+    deliver(&mut joint, &mut driver, &command(1, Payload::Configure));
+    assert!(!driver.enabled);
+    assert_eq!(joint.command_motor(&mut driver, MotorCommand::Voltage(1.0)), Err(MotorError::Disabled));
+
+    let reply = deliver(&mut joint, &mut driver, &command(2, Payload::Activate)).unwrap();
+    assert!(matches!(reply.payload, Payload::Ack(2)));
+    assert_eq!(joint.state(), LifecycleState::Active);
+    assert!(driver.enabled);
+    joint.command_motor(&mut driver, MotorCommand::Voltage(2.5)).unwrap();
+    assert_eq!(driver.volts, Some(2.5));
+    assert_eq!(joint.command_motor(&mut driver, MotorCommand::Current(1.0)), Err(MotorError::Unsupported));
+
+    deliver(&mut joint, &mut driver, &command(3, Payload::Deactivate));
+    assert!(!driver.enabled);
+    assert_eq!(joint.command_motor(&mut driver, MotorCommand::Voltage(1.0)), Err(MotorError::Disabled));
+
+    deliver(&mut joint, &mut driver, &command(4, Payload::Activate));
+    assert!(driver.enabled);
+    deliver(&mut joint, &mut driver, &command(5, Payload::Reset));
+    assert!(!driver.enabled);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_activate_refused_when_driver_will_not_enable() {
+    let mut joint = Joint::new(0x0010);
+    let mut driver = MockDriver { refuse_enable: true, ..Default::default() };
+    deliver(&mut joint, &mut driver, &command(1, Payload::Configure));
+
+    let reply = deliver(&mut joint, &mut driver, &command(2, Payload::Activate)).unwrap();
+    assert!(matches!(reply.payload, Payload::Nack { id: 2, error: irpc::ERROR_MOTOR_FAULT }));
+    assert!(!driver.enabled);
+    assert_eq!(joint.state(), LifecycleState::Inactive);
+    assert_eq!(joint.command_motor(&mut driver, MotorCommand::Voltage(1.0)), Err(MotorError::Disabled));
+
+    // A fault latched while idle faults the joint too
+    driver.faults = DriverFaults::UNDERVOLTAGE;
+    joint.sync_driver(&mut driver);
+    assert_eq!(joint.state(), LifecycleState::Error);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_driver_fault_puts_joint_in_error() {
+    let mut joint = Joint::new(0x0010);
+    joint.set_capabilities(joint.capabilities() | Capabilities::BRAKE);
+    let mut driver = MockDriver::default();
+    deliver(&mut joint, &mut driver, &command(1, Payload::Configure));
+    deliver(&mut joint, &mut driver, &command(2, Payload::Activate));
+
+    driver.faults = DriverFaults::OVERCURRENT | DriverFaults::GATE_DRIVER;
+    assert_eq!(joint.command_motor(&mut driver, MotorCommand::Voltage(3.0)), Err(MotorError::Fault));
+    assert_eq!(joint.state(), LifecycleState::Error);
+    assert!(!driver.enabled);
+    assert!(joint.brake_engaged());
+    assert_eq!(joint.driver_faults(), DriverFaults::OVERCURRENT | DriverFaults::GATE_DRIVER);
+}
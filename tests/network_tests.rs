@@ -0,0 +1,160 @@
+//! Tests for the UDP/TCP network adapter, peer discovery and the gateway
+//!
+//! These use real loopback sockets, so they run on real time.
+
+#[cfg(feature = "arm_api")]
+use irpc::bus::net::{NetworkAdapter, NetworkError};
+#[cfg(feature = "arm_api")]
+use irpc::{CommunicationAdapter, Header, Message, Payload};
+#[cfg(feature = "arm_api")]
+use std::time::Duration;
+
+#[cfg(feature = "arm_api")]
+fn ack(id: u32) -> Message {
+    Message {
+        header: Header { source_id: 0x0010, target_id: 0x0001, msg_id: id },
+        payload: Payload::Ack(id),
+    }
+}
+
+/// Poll until `f` returns something, or give up after a few seconds
+#[cfg(feature = "arm_api")]
+async fn eventually<T>(mut f: impl AsyncFnMut() -> Option<T>) -> T {
+    for _ in 0..500 {
+        if let Some(value) = f().await {
+            return value;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("condition not met in time");
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test]
+async fn test_udp_adapter_request_response() {
+    use irpc::CommunicationManager;
+    use std::sync::Arc;
+
+    // Remote side learns the host's address from its first datagram
+    let remote = Arc::new(NetworkAdapter::udp("127.0.0.1:0".parse().unwrap(), None).await.unwrap());
+    assert!(!remote.is_connected());
+    assert!(matches!(remote.transmit(&ack(1)).await, Err(NetworkError::NotConnected)));
+
+    let host = NetworkAdapter::udp("127.0.0.1:0".parse().unwrap(), remote.local_addr()).await.unwrap();
+    let comm = CommunicationManager::with_adapter(Arc::new(host));
+    assert_eq!(comm.max_message_size(), Message::max_size());
+
+    // Answer every request with an Ack
+    let responder = tokio::spawn({
+        let remote = remote.clone();
+        async move {
+            loop {
+                while let Some(request) = remote.receive().await.unwrap() {
+                    remote.transmit(&ack(request.header.msg_id)).await.unwrap();
+                }
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        }
+    });
+
+    let response = comm.send_and_wait(0x0010, Payload::Configure).await.unwrap();
+    assert!(matches!(response.payload, Payload::Ack(_)));
+    assert!(remote.is_connected());
+    responder.abort();
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test]
+async fn test_tcp_adapter_reconnects() {
+    let server = NetworkAdapter::tcp_listen("127.0.0.1:0".parse().unwrap()).await.unwrap();
+    let addr = server.local_addr().unwrap();
+    let client = NetworkAdapter::tcp_connect(addr);
+
+    eventually(async || client.is_connected().then_some(())).await;
+    client.transmit(&ack(1)).await.unwrap();
+    let received = eventually(async || server.receive().await.unwrap()).await;
+    assert_eq!(received.header.msg_id, 1);
+    server.transmit(&ack(2)).await.unwrap();
+    let received = eventually(async || client.receive().await.unwrap()).await;
+    assert_eq!(received.header.msg_id, 2);
+
+    // The server goes away: the client notices and refuses to send
+    drop(server);
+    eventually(async || (!client.is_connected()).then_some(())).await;
+    assert!(matches!(client.transmit(&ack(3)).await, Err(NetworkError::NotConnected)));
+
+    // ...and comes back on its own once the server is up again
+    let server = NetworkAdapter::tcp_listen(addr).await.unwrap();
+    eventually(async || client.is_connected().then_some(())).await;
+    client.transmit(&ack(4)).await.unwrap();
+    let received = eventually(async || server.receive().await.unwrap()).await;
+    assert_eq!(received.header.msg_id, 4);
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test]
+async fn test_beacon_discovery() {
+    use irpc::bus::net::{discover_peers, spawn_beacon, Beacon, NetTransport, Peer};
+
+    let beacon = Beacon { transport: NetTransport::Tcp, port: 47_200 };
+    assert_eq!(Beacon::decode(&beacon.encode()), Some(beacon));
+    assert_eq!(Beacon::decode(b"iRPC"), None);
+    assert_eq!(Beacon::decode(b"nope\x01\x01\x00\x01"), None);
+
+    // Find a free port to listen for beacons on
+    let port = std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let announcer = spawn_beacon(([127, 0, 0, 1], port).into(), beacon, Duration::from_millis(20));
+
+    let peers = discover_peers(([127, 0, 0, 1], port).into(), Duration::from_millis(300)).await.unwrap();
+    assert_eq!(peers, [Peer { addr: ([127, 0, 0, 1], 47_200).into(), transport: NetTransport::Tcp }]);
+    announcer.abort();
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test]
+async fn test_gateway_bridges_sim_bus_to_udp() {
+    use irpc::bus::gateway::{DeviceFilter, Gateway};
+    use irpc::bus::sim::SimBus;
+    use irpc::{ArmClient, CommunicationManager, LifecycleState};
+    use std::sync::Arc;
+
+    // Robot side: a simulated bus; network side: UDP towards the workstation
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0020, 0x0030]));
+    let uplink = Arc::new(NetworkAdapter::udp("127.0.0.1:0".parse().unwrap(), None).await.unwrap());
+    let gateway = Gateway::spawn(bus.clone(), uplink.clone(), DeviceFilter::only([0x0010, 0x0020]));
+
+    let workstation = NetworkAdapter::udp("127.0.0.1:0".parse().unwrap(), uplink.local_addr()).await.unwrap();
+    let comm = CommunicationManager::with_adapter(Arc::new(workstation));
+    let mut client = ArmClient::with_comm_manager(comm.clone());
+    client.add_joint(0x0010);
+    client.add_joint(0x0020);
+    client.initialize().await.unwrap();
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Active));
+    assert_eq!(bus.joint_state(0x0020), Some(LifecycleState::Active));
+
+    // Joint 0x0030 is not exposed through this gateway
+    comm.send_fire_and_forget(0x0030, Payload::Configure).await.unwrap();
+    eventually(async || (gateway.stats().filtered == 1).then_some(())).await;
+    assert_eq!(bus.joint_state(0x0030), Some(LifecycleState::Unconfigured));
+
+    // configure and activate for each joint, both ways
+    let stats = gateway.stats();
+    assert_eq!((stats.forwarded_b_to_a, stats.forwarded_a_to_b, stats.errors), (4, 4, 0));
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_device_filter() {
+    use irpc::bus::gateway::DeviceFilter;
+
+    let message = |source_id, target_id| Message {
+        header: Header { source_id, target_id, msg_id: 1 },
+        payload: Payload::Discover,
+    };
+    let filter = DeviceFilter::only([0x0010]);
+    assert!(filter.allows(&message(0x0001, 0x0010)));
+    assert!(filter.allows(&message(0x0010, 0x0001)));
+    assert!(filter.allows(&message(0x0001, irpc::BROADCAST_ADDRESS)));
+    assert!(!filter.allows(&message(0x0001, 0x0020)));
+    assert!(DeviceFilter::All.allows(&message(0x0001, 0x0020)));
+}
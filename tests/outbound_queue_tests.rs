@@ -0,0 +1,108 @@
+//! Tests for the bounded outbound queue
+
+/// A transport that sends one message per permit and blocks otherwise
+#[cfg(feature = "arm_api")]
+struct GatedBus {
+    permits: tokio::sync::Semaphore,
+    sent: std::sync::atomic::AtomicUsize,
+}
+
+#[cfg(feature = "arm_api")]
+#[async_trait::async_trait]
+impl irpc::CommunicationAdapter for GatedBus {
+    type Error = ();
+
+    async fn transmit(&self, _message: &irpc::Message) -> Result<(), ()> {
+        self.permits.acquire().await.unwrap().forget();
+        self.sent.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<Option<irpc::Message>, ()> {
+        Ok(None)
+    }
+
+    async fn discover_devices(&self) -> Result<Vec<irpc::DeviceInfo>, ()> {
+        Ok(Vec::new())
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_full_queue_refuses_messages() {
+    use irpc::{CommunicationManager, Payload, ProtocolError, TraceEvent, OUTBOUND_QUEUE_DEPTH};
+
+    let comm = CommunicationManager::deterministic(5);
+    assert_eq!(comm.outbound_capacity(), OUTBOUND_QUEUE_DEPTH);
+    for _ in 0..OUTBOUND_QUEUE_DEPTH {
+        comm.send_fire_and_forget(0x0010, Payload::RequestStatus).await.unwrap();
+    }
+    assert_eq!(comm.outbound_depth(), OUTBOUND_QUEUE_DEPTH);
+    let refused = comm.send_fire_and_forget(0x0010, Payload::Configure).await;
+    assert!(matches!(refused, Err(ProtocolError::QueueFull)));
+
+    // Refused messages are not traced; draining one makes room again
+    assert!(comm.poll_outbound().is_some());
+    assert_eq!(comm.outbound_depth(), OUTBOUND_QUEUE_DEPTH - 1);
+    comm.send_fire_and_forget(0x0010, Payload::Configure).await.unwrap();
+    let sent = comm.trace().iter().filter(|event| matches!(event, TraceEvent::Sent(_))).count();
+    assert_eq!(sent, OUTBOUND_QUEUE_DEPTH + 1);
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_stuck_transport_fails_fast() {
+    use irpc::{CommunicationManager, Payload, ProtocolError};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    let bus = Arc::new(GatedBus { permits: tokio::sync::Semaphore::new(0), sent: Default::default() });
+    let comm = CommunicationManager::with_adapter_and_queue_depth(bus, 4);
+    assert_eq!(comm.outbound_capacity(), 4);
+
+    // The adapter holds one message in `transmit`, the queue four more
+    comm.send_fire_and_forget(0x0010, Payload::RequestStatus).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    for _ in 0..4 {
+        comm.send_fire_and_forget(0x0010, Payload::RequestStatus).await.unwrap();
+    }
+    assert_eq!(comm.outbound_depth(), 4);
+
+    let start = Instant::now();
+    let reply = comm.send_and_wait(0x0010, Payload::Activate).await;
+    assert!(matches!(reply, Err(ProtocolError::QueueFull)));
+    assert_eq!(start.elapsed(), Duration::ZERO);
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_queue_drains_when_transport_recovers() {
+    use irpc::{CommunicationManager, Payload, ProtocolError};
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let bus = Arc::new(GatedBus { permits: tokio::sync::Semaphore::new(0), sent: Default::default() });
+    // A depth of zero still leaves room for one message
+    let comm = CommunicationManager::with_adapter_and_queue_depth(bus.clone(), 0);
+    assert_eq!(comm.outbound_capacity(), 1);
+    for _ in 0..2 {
+        comm.send_fire_and_forget(0x0010, Payload::RequestStatus).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    let refused = comm.send_fire_and_forget(0x0010, Payload::RequestStatus).await;
+    assert!(matches!(refused, Err(ProtocolError::QueueFull)));
+
+    bus.permits.add_permits(10);
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert_eq!(bus.sent.load(Ordering::SeqCst), 2);
+    assert_eq!(comm.outbound_depth(), 0);
+    comm.send_fire_and_forget(0x0010, Payload::RequestStatus).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert_eq!(bus.sent.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,121 @@
+//! Tests for supply monitoring and the brown-out reaction
+
+#[cfg(feature = "joint_api")]
+fn limits() -> irpc::PowerLimits {
+    irpc::PowerLimits { undervoltage_v: 21.0, brownout_v: 18.0 }
+}
+
+#[cfg(feature = "joint_api")]
+fn send(joint: &mut irpc::Joint, msg_id: u32, payload: irpc::Payload) -> irpc::Payload {
+    let header = irpc::Header { source_id: 0x0001, target_id: 0x0010, msg_id };
+    joint.handle_message(&irpc::Message { header, payload }).unwrap().payload
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_reports_power_and_warns_on_undervoltage() {
+    use irpc::{Joint, Payload, PowerLimits, PowerStatusPayload, Warnings, ERROR_LIMIT_VIOLATION, ERROR_UNKNOWN_COMMAND};
+
+    let mut joint = Joint::new(0x0010);
+    // Firmware that does not measure the supply
+    let reply = send(&mut joint, 1, Payload::RequestPowerStatus);
+    assert!(matches!(reply, Payload::Nack { error: ERROR_UNKNOWN_COMMAND, .. }));
+
+    for invalid in [
+        PowerLimits { brownout_v: 22.0, ..limits() },
+        PowerLimits { brownout_v: 0.0, ..limits() },
+        PowerLimits { undervoltage_v: f32::INFINITY, ..limits() },
+    ] {
+        let reply = send(&mut joint, 2, Payload::ConfigurePower(invalid));
+        assert!(matches!(reply, Payload::Nack { error: ERROR_LIMIT_VIOLATION, .. }));
+    }
+    assert!(joint.power_limits().is_none());
+    assert!(matches!(send(&mut joint, 3, Payload::ConfigurePower(limits())), Payload::Ack(3)));
+
+    joint.update_power(24.0, 1.5);
+    let expected = PowerStatusPayload { bus_voltage: 24.0, bus_current: 1.5 };
+    assert!(matches!(send(&mut joint, 4, Payload::RequestPowerStatus), Payload::PowerStatus(p) if p == expected));
+    assert_eq!(expected.power(), 36.0);
+    assert_eq!(joint.warnings(), Warnings::NONE);
+    joint.update_power(20.0, 1.5);
+    assert_eq!(joint.warnings(), Warnings::UNDERVOLTAGE);
+    joint.update_power(21.0, 1.5);
+    assert_eq!(joint.warnings(), Warnings::NONE);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_brownout_stops_and_brakes_the_joint() {
+    use irpc::encoder::{Encoder, EncoderError};
+    use irpc::{
+        Capabilities, Joint, LifecycleState, MultiTurnPosition, Payload, ERROR_UNDERVOLTAGE, SAFETY_STOP_TIME_US,
+    };
+
+    /// Reads a fixed angle and speed
+    struct Moving(f32);
+    impl Encoder for Moving {
+        fn read_position(&mut self) -> Result<MultiTurnPosition, EncoderError> {
+            Ok(MultiTurnPosition { turns: 0, angle: 30.0 })
+        }
+        fn read_velocity(&mut self) -> Option<f32> {
+            Some(self.0)
+        }
+        fn resolution(&self) -> u32 {
+            4096
+        }
+    }
+
+    let mut joint = Joint::new(0x0010);
+    joint.set_capabilities(joint.capabilities() | Capabilities::BRAKE);
+    send(&mut joint, 1, Payload::ConfigurePower(limits()));
+    send(&mut joint, 2, Payload::Configure);
+    send(&mut joint, 3, Payload::ReleaseBrake);
+    assert!(matches!(send(&mut joint, 4, Payload::Activate), Payload::Ack(4)));
+    joint.sample_encoder(&mut Moving(90.0), 0).unwrap();
+    joint.poll(1_000);
+
+    // Held where it is while it slows down, then braked
+    joint.update_power(17.0, 0.5);
+    assert_eq!(joint.state(), LifecycleState::Active);
+    assert_eq!(joint.target().unwrap().target_angle, 30.0);
+    assert!(!joint.brake_engaged());
+    joint.poll(1_000 + SAFETY_STOP_TIME_US - 1);
+    assert_eq!(joint.state(), LifecycleState::Active);
+    joint.poll(1_000 + SAFETY_STOP_TIME_US);
+    assert_eq!(joint.state(), LifecycleState::Inactive);
+    assert!(joint.brake_engaged());
+
+    let status = send(&mut joint, 5, Payload::RequestStatus);
+    assert!(matches!(status, Payload::JointStatus { error_code: ERROR_UNDERVOLTAGE, .. }));
+    assert!(matches!(send(&mut joint, 6, Payload::Activate), Payload::Nack { error: ERROR_UNDERVOLTAGE, .. }));
+    joint.update_power(19.0, 0.1);
+    send(&mut joint, 7, Payload::ReleaseBrake);
+    assert!(matches!(send(&mut joint, 8, Payload::Activate), Payload::Ack(8)));
+
+    // Standing still, it deactivates at once
+    joint.sample_encoder(&mut Moving(0.0), 2_000_000).unwrap();
+    joint.update_power(12.0, 0.1);
+    assert_eq!(joint.state(), LifecycleState::Inactive);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_sim_joint_browns_out() {
+    use irpc::bus::sim::{SensorModel, SimBus};
+    use irpc::{CommunicationManager, JointProxy, LifecycleState, SIM_SUPPLY_VOLTAGE_V};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010]));
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    let joint = JointProxy::new(0x0010, comm.clone());
+    joint.configure_power(limits()).await.unwrap();
+    joint.configure().await.unwrap();
+    joint.activate().await.unwrap();
+    assert_eq!(joint.power_status().await.unwrap().bus_voltage, SIM_SUPPLY_VOLTAGE_V);
+
+    bus.set_sensor_model(0x0010, SensorModel { supply_v: 15.0, ..Default::default() });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Inactive));
+    assert!(joint.activate().await.is_err());
+}
@@ -0,0 +1,29 @@
+//! Tests for using the protocol types on std without the host or joint APIs
+#![cfg(feature = "std")]
+
+use irpc::{Header, Message, Payload, ProtocolError, SetTargetPayload};
+
+#[test]
+fn test_protocol_errors_are_std_errors() {
+    fn decode(bytes: &[u8]) -> Result<Message, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Message::deserialize(bytes)?)
+    }
+
+    let error = decode(&[0x01, 0x10]).unwrap_err();
+    assert!(error.downcast_ref::<ProtocolError>().is_some());
+    assert!(error.to_string().starts_with("Deserialization failed"), "{}", error);
+}
+
+#[test]
+fn test_tooling_decodes_and_prints_frames() {
+    let message = Message {
+        header: Header { source_id: 0x0001, target_id: 0x0010, msg_id: 7 },
+        payload: Payload::SetTarget(SetTargetPayload { target_angle: 45.0, velocity_limit: 30.0 }),
+    };
+    let frame = message.serialize().unwrap();
+    assert_eq!(frame.len(), message.encoded_size());
+
+    let decoded = Message::deserialize(&frame).unwrap();
+    assert_eq!(decoded.to_string(), message.to_string());
+    assert!(decoded.to_string().starts_with("0x0001 -> 0x0010 #7 SetTarget"));
+}
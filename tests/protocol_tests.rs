@@ -1,4 +1,5 @@
-#[cfg(test)]
+// These tests use the Vec-returning `Message::serialize()`
+#[cfg(all(test, not(feature = "no_alloc")))]
 mod calibration_tests {
     use irpc::protocol::*;
 
@@ -83,6 +84,8 @@ mod calibration_tests {
                 friction_stribeck: 0.01,
                 friction_vstribeck: 0.1,
                 friction_viscous: 0.001,
+                thermal_resistance: 0.0,
+                thermal_time_constant: 0.0,
             },
             confidence: CalibrationConfidence {
                 overall: 0.92,
@@ -161,3 +164,53 @@ mod calibration_tests {
         }
     }
 }
+
+mod text_encoding_tests {
+    use irpc::protocol::*;
+
+    fn set_target() -> Message {
+        Message {
+            header: Header {
+                source_id: 0x0001,
+                target_id: 0x0010,
+                msg_id: 42,
+            },
+            payload: Payload::SetTarget(SetTargetPayload {
+                target_angle: 1.5708,
+                velocity_limit: 2.0,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_message_display() {
+        assert_eq!(
+            format!("{}", set_target()),
+            "0x0001 -> 0x0010 #42 SetTarget angle=1.571 vel_limit=2.000"
+        );
+        assert_eq!(format!("{}", Payload::Nack { id: 7, error: 5 }), "Nack #7 error=5");
+        assert_eq!(format!("{}", Payload::Activate), "Activate");
+        assert_eq!(Payload::RequestBusStats.name(), "RequestBusStats");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_message_json_roundtrip() {
+        let json = set_target().to_json().unwrap();
+        assert!(json.contains("\"SetTarget\":{\"target_angle\":1.5708"), "{}", json);
+
+        let decoded = Message::from_json(&json).unwrap();
+        assert_eq!(decoded.header.msg_id, 42);
+        assert!(matches!(decoded.payload, Payload::SetTarget(t) if t.velocity_limit == 2.0));
+
+        let activate = Message::from_json(
+            r#"{"header":{"source_id":1,"target_id":16,"msg_id":3},"payload":"Activate"}"#,
+        )
+        .unwrap();
+        assert!(matches!(activate.payload, Payload::Activate));
+        assert!(matches!(
+            Message::from_json(r#"{"payload":"Activate"}"#),
+            Err(ProtocolError::DeserializationError(_))
+        ));
+    }
+}
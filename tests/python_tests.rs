@@ -0,0 +1,54 @@
+//! Tests for the Python bindings, driven from an embedded interpreter
+
+#[cfg(all(feature = "python", feature = "joint_api"))]
+#[test]
+fn test_python_sim_session() {
+    use irpc::python::irpc as module;
+    use pyo3::ffi::c_str;
+    use pyo3::prelude::*;
+
+    pyo3::append_to_inittab!(module);
+    pyo3::prepare_freethreaded_python();
+
+    let script = c_str!(
+        r#"
+import asyncio
+import irpc
+
+async def main():
+    arm = irpc.ArmClient.simulated([0x10], telemetry_period_ms=10)
+    await arm.initialize()
+    assert await arm.status() == {0x10: "Active"}
+
+    joint = arm.joint(0x10)
+    assert joint.id == 0x10
+    await joint.set_target(5.0, 50.0)
+    assert await joint.query_status() == ("Active", 0)
+
+    telemetry = arm.subscribe_telemetry(joints=[0x10], topics=["motion"], snapshot=False)
+    sample = await asyncio.wait_for(telemetry.__anext__(), 5)
+    assert sample["joint_id"] == 0x10 and sample["topic"] == "motion"
+    assert isinstance(sample["data"]["position"], float)
+
+    # The simulated joint has no calibration routine
+    try:
+        await joint.calibrate(phases=irpc.PHASE_INERTIA, timeout=1.0)
+        raise AssertionError("calibration should be refused")
+    except irpc.IrpcError:
+        pass
+
+    for bad in (lambda: arm.joint(0x11), lambda: arm.subscribe_telemetry(topics=["bogus"])):
+        try:
+            bad()
+            raise AssertionError("expected an error")
+        except (KeyError, ValueError):
+            pass
+
+    await arm.shutdown()
+    assert await joint.state() == "Inactive"
+
+asyncio.run(main())
+"#
+    );
+    Python::with_gil(|py| py.run(script, None, None)).unwrap();
+}
@@ -0,0 +1,110 @@
+//! Tests for host-side rate limiting
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_payload_classes() {
+    use irpc::rate_limit::{ClassLimit, PayloadClass, RateLimits};
+    use irpc::{Payload, SetTargetPayload};
+    use std::time::Duration;
+
+    let target = SetTargetPayload { target_angle: 10.0, velocity_limit: 5.0 };
+    assert_eq!(PayloadClass::of(&Payload::SetTarget(target)), PayloadClass::Setpoint);
+    assert_eq!(PayloadClass::of(&Payload::SetVelocity { dps: 1.0 }), PayloadClass::Setpoint);
+    assert_eq!(PayloadClass::of(&Payload::RequestStatus), PayloadClass::Query);
+    assert_eq!(PayloadClass::of(&Payload::ReadAnalogInput(2)), PayloadClass::Query);
+    assert_eq!(PayloadClass::of(&Payload::Configure), PayloadClass::Command);
+
+    let capped = ClassLimit { max_bytes_per_sec: Some(100), ..Default::default() };
+    let limits = RateLimits::new().class(PayloadClass::Query, capped);
+    assert_eq!(limits.get(PayloadClass::Setpoint), ClassLimit::default());
+    assert!(!limits.coalesces_setpoints());
+    let spaced = ClassLimit { min_interval: Duration::from_millis(10), ..Default::default() };
+    assert!(limits.class(PayloadClass::Setpoint, spaced).coalesces_setpoints());
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_setpoints_coalesce_and_queries_share_bandwidth() {
+    use irpc::rate_limit::{ClassLimit, PayloadClass, RateLimits};
+    use irpc::{CommunicationManager, Message, Payload, ProtocolError, SetTargetPayload};
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    let comm = CommunicationManager::deterministic(3);
+    comm.send_fire_and_forget(0x0010, Payload::RequestStatus).await.unwrap();
+    let status_size = comm.poll_outbound().unwrap().encoded_size() as u32;
+    let spaced = ClassLimit { min_interval: Duration::from_millis(100), ..Default::default() };
+    let capped = ClassLimit { max_bytes_per_sec: Some(status_size * 10), ..Default::default() };
+    comm.set_rate_limits(Some(
+        RateLimits::new().class(PayloadClass::Setpoint, spaced).class(PayloadClass::Query, capped),
+    ));
+    let target = |target_angle| Payload::SetTarget(SetTargetPayload { target_angle, velocity_limit: 30.0 });
+    let sent_angle = |message: Message| match message.payload {
+        Payload::SetTarget(target) => (message.header.target_id, target.target_angle),
+        other => panic!("unexpected {:?}", other),
+    };
+
+    // The first goes at once; of the two waiting behind it only the newest is sent
+    let start = Instant::now();
+    let (first, second, third, other_joint) = tokio::join!(
+        comm.send_fire_and_forget(0x0010, target(1.0)),
+        comm.send_fire_and_forget(0x0010, target(2.0)),
+        comm.send_fire_and_forget(0x0010, target(3.0)),
+        comm.send_fire_and_forget(0x0020, target(4.0)),
+    );
+    assert!(first.is_ok() && third.is_ok() && other_joint.is_ok());
+    assert!(matches!(second, Err(ProtocolError::Superseded)));
+    assert_eq!(start.elapsed(), Duration::from_millis(100));
+    assert_eq!(sent_angle(comm.poll_outbound().unwrap()), (0x0010, 1.0));
+    assert_eq!(sent_angle(comm.poll_outbound().unwrap()), (0x0020, 4.0));
+    assert_eq!(sent_angle(comm.poll_outbound().unwrap()), (0x0010, 3.0));
+    assert!(comm.poll_outbound().is_none());
+
+    // Ten status requests a second, across joints
+    let start = Instant::now();
+    for joint in [0x0010, 0x0020, 0x0010, 0x0020, 0x0030] {
+        comm.send_fire_and_forget(joint, Payload::RequestStatus).await.unwrap();
+    }
+    assert_eq!(start.elapsed(), Duration::from_millis(400));
+    comm.set_rate_limits(None);
+    let start = Instant::now();
+    comm.send_fire_and_forget(0x0010, Payload::RequestStatus).await.unwrap();
+    assert_eq!(start.elapsed(), Duration::ZERO);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_proxy_setpoints_keep_the_freshest() {
+    use irpc::bus::sim::SimBus;
+    use irpc::rate_limit::{ClassLimit, PayloadClass, RateLimits};
+    use irpc::{CommunicationManager, JointProxy, ProtocolError};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010]));
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    let joint = JointProxy::new(0x0010, comm.clone());
+    joint.configure().await.unwrap();
+    joint.activate().await.unwrap();
+    let spaced = ClassLimit { min_interval: Duration::from_millis(50), ..Default::default() };
+    comm.set_rate_limits(Some(RateLimits::new().class(PayloadClass::Setpoint, spaced)));
+
+    // A slider dragged through 20 positions at once
+    let mut slider = tokio::task::JoinSet::new();
+    for step in 1..=20 {
+        let joint = joint.clone();
+        slider.spawn(async move { joint.set_target(step as f32, 90.0).await });
+        tokio::task::yield_now().await;
+    }
+    let mut superseded = 0;
+    while let Some(outcome) = slider.join_next().await {
+        match outcome.unwrap() {
+            Ok(()) => {}
+            Err(ProtocolError::Superseded) => superseded += 1,
+            Err(e) => panic!("unexpected {:?}", e),
+        }
+    }
+    assert!(superseded >= 17, "only {} superseded", superseded);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!((bus.true_position(0x0010).unwrap() - 20.0).abs() < 0.5);
+}
@@ -0,0 +1,204 @@
+//! Tests for recording bus traffic and replaying it
+
+#[cfg(feature = "arm_api")]
+use irpc::bus::record::{open_log, read_log, Direction, LogRecord, RecordScope, RecordingError, TelemetryPlayer, TelemetryRecorder};
+#[cfg(feature = "arm_api")]
+use irpc::{Header, Message, Payload};
+#[cfg(feature = "arm_api")]
+use std::sync::Arc;
+
+#[cfg(feature = "arm_api")]
+fn record(timestamp_us: u64, direction: Direction, payload: Payload) -> LogRecord {
+    let (source_id, target_id) = match direction {
+        Direction::Transmitted => (0x0001, 0x0010),
+        Direction::Received => (0x0010, 0x0001),
+    };
+    LogRecord {
+        timestamp_us,
+        direction,
+        message: Message {
+            header: Header { source_id, target_id, msg_id: timestamp_us as u32 },
+            payload,
+        },
+    }
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_record_sim_session() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, JointProxy};
+    use std::time::Duration;
+
+    let dir = tempfile::tempdir().unwrap();
+    let all = dir.path().join("all.irpclog");
+    let telemetry = dir.path().join("telemetry.irpclog");
+
+    let bus = Arc::new(SimBus::with_joints([0x0010]).with_telemetry(Duration::from_millis(10)));
+    let inner = Arc::new(TelemetryRecorder::create(&telemetry, bus, RecordScope::TelemetryStream).unwrap());
+    let recorder = Arc::new(TelemetryRecorder::create(&all, inner.clone(), RecordScope::All).unwrap());
+    let proxy = JointProxy::new(0x0010, CommunicationManager::with_adapter(recorder.clone()));
+    proxy.configure().await.unwrap();
+    proxy.activate().await.unwrap();
+    proxy.set_target(5.0, 50.0).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    recorder.flush().unwrap();
+    inner.flush().unwrap();
+
+    let records = open_log(&all).unwrap();
+    assert_eq!(records.len() as u64, recorder.records());
+    let commands: Vec<_> = records
+        .iter()
+        .filter(|r| r.direction == Direction::Transmitted)
+        .map(|r| &r.message.payload)
+        .collect();
+    assert!(matches!(commands[..], [Payload::Configure, Payload::Activate, Payload::SetTarget(_)]));
+    assert!(records.windows(2).all(|w| w[0].timestamp_us <= w[1].timestamp_us));
+
+    // The narrower recording holds exactly the telemetry samples
+    let samples = open_log(&telemetry).unwrap();
+    let streams = records
+        .iter()
+        .filter(|r| matches!(r.message.payload, Payload::TelemetryStream(_)))
+        .count();
+    assert!(streams >= 5);
+    assert_eq!(samples.len(), streams);
+    assert!(samples.iter().all(|r| r.direction == Direction::Received));
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_player_replays_at_recorded_pace() {
+    use irpc::{CommunicationManager, EncoderTelemetry, TelemetryFilter};
+    use std::time::Duration;
+
+    let sample = |t| Payload::Encoder(EncoderTelemetry { position: t as f32, velocity: 0.0 });
+    let records = vec![
+        record(1_000, Direction::Received, sample(1)),
+        record(1_500, Direction::Transmitted, Payload::Home),
+        record(51_000, Direction::Received, sample(2)),
+        record(101_000, Direction::Received, sample(3)),
+    ];
+    let player = Arc::new(TelemetryPlayer::new(records.clone()));
+    assert_eq!(player.remaining(), 3);
+    let comm = CommunicationManager::with_adapter(player.clone());
+    let mut telemetry = comm.subscribe_telemetry(TelemetryFilter::default());
+
+    let start = tokio::time::Instant::now();
+    let mut arrivals = Vec::new();
+    for _ in 0..3 {
+        let sample = telemetry.recv().await.unwrap();
+        arrivals.push((start.elapsed(), sample.message.payload));
+    }
+    assert!(player.is_finished());
+    // Spacing is kept within one adapter poll interval
+    let gaps: Vec<_> = arrivals.windows(2).map(|w| w[1].0 - w[0].0).collect();
+    assert!(gaps.iter().all(|gap| gap.abs_diff(Duration::from_millis(50)) <= Duration::from_millis(2)));
+    assert!(matches!(&arrivals[2].1, Payload::TelemetryStream(s) if s.position == 3.0));
+
+    // Host commands during replay are captured rather than sent anywhere
+    comm.send_fire_and_forget(0x0010, Payload::Home).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(matches!(player.transmitted()[..], [Message { payload: Payload::Home, .. }]));
+
+    // Unpaced replay onto another adapter
+    let target = Arc::new(TelemetryPlayer::new(Vec::new()));
+    let start = tokio::time::Instant::now();
+    let sent = TelemetryPlayer::new(records).unpaced().replay_into(&*target).await.unwrap();
+    assert_eq!(sent, 3);
+    assert_eq!(start.elapsed(), Duration::ZERO);
+    assert_eq!(target.transmitted().len(), 3);
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_log_format_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.irpclog");
+    {
+        let recorder = TelemetryRecorder::<TelemetryPlayer>::create(
+            &path,
+            Arc::new(TelemetryPlayer::new(Vec::new())),
+            RecordScope::All,
+        )
+        .unwrap();
+        recorder.flush().unwrap();
+    }
+    assert!(open_log(&path).unwrap().is_empty());
+
+    assert!(matches!(read_log(&b"not a log"[..]), Err(RecordingError::NotALog)));
+
+    // A record cut short by a crash ends the log
+    let mut bytes = b"iRPClog\x01".to_vec();
+    let full = postcard::to_stdvec(&record(10, Direction::Received, Payload::Home)).unwrap();
+    for _ in 0..2 {
+        bytes.extend_from_slice(&(full.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&full);
+    }
+    bytes.truncate(bytes.len() - 1);
+    let records = read_log(&bytes[..]).unwrap();
+    assert_eq!(records.len(), 1);
+    assert!(matches!(records[0].message.payload, Payload::Home));
+
+    // A complete record that does not decode is an error
+    let mut bytes = b"iRPClog\x01".to_vec();
+    bytes.extend_from_slice(&[0, 2, 0xFF, 0xFF]);
+    assert!(matches!(read_log(&bytes[..]), Err(RecordingError::Corrupt { index: 0, .. })));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_export_csv_and_mcap() {
+    use irpc::bus::export::{export_csv, export_mcap};
+    use irpc::{EncoderTelemetry, SetTargetPayload};
+
+    let records = vec![
+        record(0, Direction::Transmitted, Payload::SetTarget(SetTargetPayload { target_angle: 10.0, velocity_limit: 5.0 })),
+        record(100, Direction::Received, Payload::Ack(0)),
+        record(200, Direction::Received, Payload::Encoder(EncoderTelemetry { position: 1.5, velocity: 2.0 })),
+        record(300, Direction::Received, Payload::Encoder(EncoderTelemetry { position: 2.5, velocity: 2.0 })),
+    ];
+
+    let mut csv = Vec::new();
+    assert_eq!(export_csv(&records, "Encoder", &mut csv).unwrap(), 2);
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "timestamp_us,direction,joint_id,msg_id,position,velocity\n\
+         200,received,16,200,1.5,2.0\n\
+         300,received,16,300,2.5,2.0\n"
+    );
+
+    let mut mcap = Vec::new();
+    export_mcap(&records, &mut mcap).unwrap();
+    assert_eq!(&mcap[..8], b"\x89MCAP0\r\n");
+    assert_eq!(&mcap[mcap.len() - 8..], b"\x89MCAP0\r\n");
+
+    // Walk the records: opcode, u64 length, body
+    let mut records = Vec::new();
+    let mut at = 8;
+    while at < mcap.len() - 8 {
+        let opcode = mcap[at];
+        let len = u64::from_le_bytes(mcap[at + 1..at + 9].try_into().unwrap()) as usize;
+        records.push((opcode, mcap[at + 9..at + 9 + len].to_vec()));
+        at += 9 + len;
+    }
+    assert_eq!(at, mcap.len() - 8);
+    let opcodes: Vec<u8> = records.iter().map(|(op, _)| *op).collect();
+    // header, then schema + channel before the first message of each kind
+    assert_eq!(opcodes, [0x01, 0x03, 0x04, 0x05, 0x03, 0x04, 0x05, 0x03, 0x04, 0x05, 0x05, 0x0F, 0x02]);
+
+    let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+    let (_, schema) = &records[7];
+    assert!(text(schema).contains("irpc.Encoder"));
+    assert!(text(schema).contains(r#""position":{"type":"number"}"#));
+    let (_, channel) = &records[8];
+    assert!(text(channel).contains("/joint/16/Encoder"));
+    let (_, command) = &records[2];
+    assert!(text(command).contains("/joint/16/command/SetTarget"));
+    // channel id, sequence, log time in ns, publish time, JSON body
+    let (_, message) = &records[10];
+    assert_eq!(u16::from_le_bytes([message[0], message[1]]), 3);
+    assert_eq!(u32::from_le_bytes(message[2..6].try_into().unwrap()), 2);
+    assert_eq!(u64::from_le_bytes(message[6..14].try_into().unwrap()), 300_000);
+    assert_eq!(text(&message[22..]), r#"{"position":2.5,"velocity":2.0}"#);
+}
@@ -0,0 +1,91 @@
+//! Tests for cancellation-safe request handles
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_dropped_request_forgets_correlation() {
+    use irpc::{CommunicationManager, Header, Message, Payload};
+    use std::time::Duration;
+
+    let comm = CommunicationManager::deterministic(11);
+    let abandoned = tokio::time::timeout(Duration::from_millis(10), comm.send_and_wait(0x0010, Payload::Configure));
+    assert!(abandoned.await.is_err());
+    assert_eq!(comm.pending_requests().await, 0);
+
+    // The late reply finds nobody waiting
+    let sent = comm.poll_outbound().unwrap();
+    comm.process_incoming(Message {
+        header: Header { source_id: 0x0010, target_id: 0x0001, msg_id: sent.header.msg_id },
+        payload: Payload::Ack(sent.header.msg_id),
+    })
+    .await;
+    assert_eq!(comm.pending_requests().await, 0);
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_request_handle_response_and_cancel() {
+    use irpc::{CommunicationManager, Header, Message, Payload, ProtocolError};
+    use std::time::Duration;
+
+    let comm = CommunicationManager::deterministic(12);
+    let request = comm.send_request(0x0010, Payload::RequestStatus).await.unwrap();
+    let msg_id = request.msg_id();
+    assert_eq!(request.target_id(), 0x0010);
+    comm.process_incoming(Message {
+        header: Header { source_id: 0x0010, target_id: 0x0001, msg_id },
+        payload: Payload::Ack(msg_id),
+    })
+    .await;
+    let reply = request.response(Duration::from_secs(1)).await.unwrap();
+    assert!(matches!(reply.payload, Payload::Ack(id) if id == msg_id));
+
+    let request = comm.send_request(0x0010, Payload::RequestStatus).await.unwrap();
+    assert_eq!(comm.pending_requests().await, 1);
+    request.cancel().await;
+    assert_eq!(comm.pending_requests().await, 0);
+
+    let request = comm.send_request(0x0010, Payload::RequestStatus).await.unwrap();
+    let timed_out = request.response(Duration::from_millis(50)).await;
+    assert!(matches!(timed_out, Err(ProtocolError::Timeout)));
+    assert_eq!(comm.pending_requests().await, 0);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_aborted_requests_leave_no_orphans() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, Payload, PENDING_SWEEP_INTERVAL_MS};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    // Nobody answers, so every request stays pending until its caller gives up
+    let comm = CommunicationManager::with_adapter(Arc::new(SimBus::new()));
+    let mut callers = tokio::task::JoinSet::new();
+    for _ in 0..64 {
+        let comm = comm.clone();
+        callers.spawn(async move { comm.send_and_wait(0x0030, Payload::RequestStatus).await });
+    }
+    while comm.pending_requests().await < 64 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    // Handles dropped while the table is locked are left to the sweeper
+    let contention = tokio::spawn({
+        let comm = comm.clone();
+        async move {
+            loop {
+                comm.sweep_pending().await;
+                tokio::task::yield_now().await;
+            }
+        }
+    });
+    callers.abort_all();
+    while callers.join_next().await.is_some() {}
+    contention.abort();
+
+    let deadline = Instant::now() + Duration::from_millis(PENDING_SWEEP_INTERVAL_MS * 3);
+    while comm.pending_requests().await > 0 {
+        assert!(Instant::now() < deadline, "orphaned requests were never swept");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
@@ -0,0 +1,144 @@
+//! Tests for the SafetyEnable heartbeat
+
+#[cfg(feature = "joint_api")]
+fn command(msg_id: u32, payload: irpc::Payload) -> irpc::Message {
+    irpc::Message { header: irpc::Header { source_id: 0x0001, target_id: 0x0010, msg_id }, payload }
+}
+
+#[cfg(feature = "joint_api")]
+fn error_of(reply: Option<irpc::Message>) -> Option<u16> {
+    match reply?.payload {
+        irpc::Payload::Nack { error, .. } => Some(error),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_stays_active_only_while_enabled() {
+    use irpc::{
+        Capabilities, Joint, LifecycleState, Payload, SetTargetPayload, ERROR_SAFETY_ENABLE, ERROR_UNKNOWN_COMMAND,
+    };
+
+    let enable = |token| Payload::SafetyEnable { token, ttl_ms: 100 };
+    let mut joint = Joint::new(0x0010);
+    assert_eq!(error_of(joint.handle_message(&command(1, enable(1)))), Some(ERROR_UNKNOWN_COMMAND));
+
+    joint.require_safety_enable(true);
+    assert!(joint.capabilities().contains(Capabilities::SAFETY_ENABLE));
+    joint.handle_message(&command(2, Payload::Configure));
+    assert_eq!(error_of(joint.handle_message(&command(3, Payload::Activate))), Some(ERROR_SAFETY_ENABLE));
+    joint.poll(10_000);
+    assert_eq!(error_of(joint.handle_message(&command(4, enable(7)))), None);
+    assert_eq!(error_of(joint.handle_message(&command(5, Payload::Activate))), None);
+    // The same token again does not count
+    assert_eq!(error_of(joint.handle_message(&command(6, enable(7)))), Some(ERROR_SAFETY_ENABLE));
+
+    joint.poll(100_000);
+    assert!(joint.safety_enabled());
+    let target = Payload::SetTarget(SetTargetPayload { target_angle: 10.0, velocity_limit: 30.0 });
+    assert_eq!(error_of(joint.handle_message(&command(7, target.clone()))), None);
+
+    // Ran out at 110 ms; standing still, the joint deactivates at once
+    joint.poll(110_000);
+    assert!(!joint.safety_enabled());
+    assert_eq!(joint.state(), LifecycleState::Inactive);
+    assert_eq!(error_of(joint.handle_message(&command(8, target))), Some(ERROR_SAFETY_ENABLE));
+    assert_eq!(error_of(joint.handle_message(&command(9, Payload::Activate))), Some(ERROR_SAFETY_ENABLE));
+    joint.handle_message(&command(10, enable(8)));
+    assert_eq!(error_of(joint.handle_message(&command(11, Payload::Activate))), None);
+
+    joint.require_safety_enable(false);
+    joint.poll(1_000_000);
+    assert_eq!(joint.state(), LifecycleState::Active);
+    assert!(!joint.capabilities().contains(Capabilities::SAFETY_ENABLE));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_moving_joint_decelerates_before_deactivating() {
+    use irpc::encoder::{Encoder, EncoderError};
+    use irpc::{Joint, LifecycleState, MultiTurnPosition, Payload, SAFETY_STOP_TIME_US};
+
+    /// Reads a fixed angle and speed
+    struct Moving(f32, f32);
+    impl Encoder for Moving {
+        fn read_position(&mut self) -> Result<MultiTurnPosition, EncoderError> {
+            Ok(MultiTurnPosition { turns: 0, angle: self.0 })
+        }
+        fn read_velocity(&mut self) -> Option<f32> {
+            Some(self.1)
+        }
+        fn resolution(&self) -> u32 {
+            4096
+        }
+    }
+
+    let mut joint = Joint::new(0x0010);
+    joint.require_safety_enable(true);
+    joint.handle_message(&command(1, Payload::Configure));
+    joint.handle_message(&command(2, Payload::SafetyEnable { token: 1, ttl_ms: 50 }));
+    joint.handle_message(&command(3, Payload::Activate));
+    joint.sample_encoder(&mut Moving(30.0, 90.0), 0).unwrap();
+
+    // Still turning: held where it is while it slows down
+    joint.poll(60_000);
+    assert_eq!(joint.state(), LifecycleState::Active);
+    let hold = joint.target().unwrap();
+    assert_eq!(hold.target_angle, 30.0);
+    assert!(hold.max_acceleration > 0.0);
+    joint.sample_encoder(&mut Moving(31.0, 0.5), 80_000).unwrap();
+    joint.poll(80_000);
+    assert_eq!(joint.state(), LifecycleState::Inactive);
+
+    // One that does not slow down is deactivated after the stop time
+    joint.handle_message(&command(4, Payload::SafetyEnable { token: 2, ttl_ms: 50 }));
+    joint.handle_message(&command(5, Payload::Activate));
+    joint.sample_encoder(&mut Moving(31.0, 90.0), 90_000).unwrap();
+    joint.poll(130_000);
+    assert_eq!(joint.state(), LifecycleState::Active);
+    joint.poll(130_000 + SAFETY_STOP_TIME_US - 1);
+    assert_eq!(joint.state(), LifecycleState::Active);
+    joint.poll(130_000 + SAFETY_STOP_TIME_US);
+    assert_eq!(joint.state(), LifecycleState::Inactive);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_heartbeat_keeps_joints_enabled_until_stopped() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{ArmOrchestrator, CommunicationManager, Joint, LifecycleState, ProtocolError};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let bus = Arc::new(SimBus::with_joints([0x0020]));
+    let mut joint = Joint::new(0x0010);
+    joint.require_safety_enable(true);
+    bus.add_joint(joint);
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    arm.add_joint(0x0010);
+    arm.add_joint(0x0020);
+    arm.configure_all().await.into_result().unwrap();
+    assert_eq!(arm.activate_all().await.succeeded, [0x0020]);
+
+    let ttl = Duration::from_millis(200);
+    arm.start_safety_heartbeat(ttl);
+    // The first heartbeat goes out once the task runs
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    let joint = arm.get_joint(0x0010).unwrap().clone();
+    joint.activate().await.unwrap();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Active));
+
+    // A joint that does not require it refuses a manual one
+    let other = arm.get_joint(0x0020).unwrap().clone();
+    assert!(matches!(other.safety_enable(1, ttl).await, Err(ProtocolError::IoError(_))));
+
+    // Silence: the joint stops within the TTL, the other one carries on
+    arm.stop_safety_heartbeat();
+    tokio::time::sleep(ttl).await;
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Inactive));
+    assert_eq!(bus.joint_state(0x0020), Some(LifecycleState::Active));
+    joint.safety_enable(1, ttl).await.unwrap();
+    joint.activate().await.unwrap();
+}
@@ -0,0 +1,129 @@
+//! Tests for the host-side safety limits
+
+#[cfg(feature = "arm_api")]
+fn target(target_angle: f32, velocity_limit: f32) -> irpc::Payload {
+    irpc::Payload::SetTarget(irpc::SetTargetPayload { target_angle, velocity_limit })
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_targets_outside_the_limits_are_refused() {
+    use irpc::safety::{SafetyLimits, Violation};
+    use irpc::{CommunicationManager, JointLimits, MotionProfile, Payload, ProtocolError, SetTargetPayloadV2};
+
+    let comm = CommunicationManager::deterministic(5);
+    let limits = JointLimits { min_position: -90.0, max_position: 90.0, max_velocity: 60.0 };
+    comm.set_safety_limits(Some(SafetyLimits::new().joint(0x0010, limits).max_velocity(45.0)));
+
+    let refused = comm.send_fire_and_forget(0x0010, target(120.0, 30.0)).await;
+    assert!(matches!(refused, Err(ProtocolError::SafetyViolation)));
+    let refused = comm.send_fire_and_forget(0x0020, target(0.0, 50.0)).await;
+    assert!(matches!(refused, Err(ProtocolError::SafetyViolation)));
+    let mut v2 = SetTargetPayloadV2 {
+        target_angle: -95.0,
+        max_velocity: 90.0,
+        target_velocity: 0.0,
+        max_acceleration: 500.0,
+        max_deceleration: 500.0,
+        max_jerk: 0.0,
+        profile: MotionProfile::Trapezoidal,
+        max_current: 0.0,
+        max_temperature: 0.0,
+    };
+    assert!(comm.send_fire_and_forget(0x0010, Payload::SetTargetV2(v2)).await.is_err());
+    assert!(comm.poll_outbound().is_none());
+
+    // Within the limits, and other payloads, pass unchanged
+    v2.target_angle = -85.0;
+    v2.max_velocity = 45.0;
+    comm.send_fire_and_forget(0x0010, Payload::SetTargetV2(v2)).await.unwrap();
+    comm.send_fire_and_forget(0x0020, Payload::SetVelocity { dps: 500.0 }).await.unwrap();
+    assert!(matches!(comm.poll_outbound().unwrap().payload, Payload::SetTargetV2(sent) if sent.target_angle == -85.0));
+    assert!(comm.poll_outbound().is_some());
+
+    let interventions = comm.safety_interventions();
+    assert_eq!(interventions.len(), 3);
+    assert_eq!(interventions[0].joint_id, 0x0010);
+    assert_eq!(interventions[0].violations, [Violation::Position { min: -90.0, max: 90.0 }]);
+    assert_eq!((interventions[0].requested, interventions[0].sent), ((120.0, 30.0), None));
+    assert_eq!(interventions[1].violations, [Violation::Velocity { max: 45.0 }]);
+    assert_eq!(interventions[2].payload, "SetTargetV2");
+    assert_eq!(interventions[2].violations.len(), 2);
+
+    // Off, targets go through; the audit log stays
+    comm.set_safety_limits(None);
+    comm.send_fire_and_forget(0x0010, target(120.0, 30.0)).await.unwrap();
+    assert!(comm.safety_limits().is_none());
+    assert_eq!(comm.safety_interventions().len(), 3);
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_clamping_sends_the_closest_target_within_the_limits() {
+    use irpc::safety::{SafetyAction, SafetyLimits, Violation};
+    use irpc::{CommunicationManager, JointLimits, Payload, ProtocolError};
+
+    let comm = CommunicationManager::deterministic(6);
+    let limits = JointLimits { min_position: -90.0, max_position: 90.0, max_velocity: 60.0 };
+    comm.set_safety_limits(Some(SafetyLimits::new().joint(0x0010, limits).action(SafetyAction::Clamp)));
+
+    comm.send_fire_and_forget(0x0010, target(120.0, 75.0)).await.unwrap();
+    let Payload::SetTarget(sent) = comm.poll_outbound().unwrap().payload else { panic!() };
+    assert_eq!((sent.target_angle, sent.velocity_limit), (90.0, 60.0));
+    let intervention = &comm.safety_interventions()[0];
+    let violations = [Violation::Velocity { max: 60.0 }, Violation::Position { min: -90.0, max: 90.0 }];
+    assert_eq!(intervention.violations, violations);
+    assert_eq!(intervention.sent, Some((90.0, 60.0)));
+
+    // Nothing to clamp a number that is not one to
+    let refused = comm.send_fire_and_forget(0x0010, target(f32::NAN, 30.0)).await;
+    assert!(matches!(refused, Err(ProtocolError::SafetyViolation)));
+    assert_eq!(comm.safety_interventions()[1].violations, [Violation::NotFinite]);
+    assert!(comm.poll_outbound().is_none());
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_workspace_is_checked_with_the_arm_model() {
+    use irpc::bus::sim::SimBus;
+    use irpc::kinematics::{ArmModel, DhParameters, Link};
+    use irpc::safety::{SafetyAction, SafetyLimits, Violation};
+    use irpc::{ArmOrchestrator, CommunicationManager, ProtocolError};
+    use std::sync::Arc;
+
+    // Two links of 0.4 m and 0.3 m in the xy plane, the flange kept to x <= 0.6
+    let link = |joint_id, length: f64| Link {
+        joint_id,
+        dh: DhParameters { a: length, ..Default::default() },
+        mass: 1.0,
+        center_of_mass: [-length / 2.0, 0.0, 0.0],
+    };
+    let model = ArmModel::new(vec![link(0x0010, 0.4), link(0x0011, 0.3)]);
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0011]));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    arm.add_joint(0x0010);
+    arm.add_joint(0x0011);
+    arm.configure_all().await.into_result().unwrap();
+    arm.activate_all().await.into_result().unwrap();
+    let limits = SafetyLimits::new().workspace(model, [-1.0, -1.0, -1.0], [0.6, 1.0, 1.0]);
+    arm.set_safety_limits(Some(limits.clone()));
+
+    // The shoulder's position is unknown at first, so the elbow is not checked
+    let (shoulder, elbow) = (arm.get_joint(0x0010).unwrap(), arm.get_joint(0x0011).unwrap());
+    elbow.set_target(90.0, 30.0).await.unwrap();
+    shoulder.set_target(0.0, 30.0).await.unwrap();
+    // Stretched out, the flange would be at x = 0.7
+    assert!(matches!(elbow.set_target(0.0, 30.0).await, Err(ProtocolError::SafetyViolation)));
+    let intervention = &arm.safety_interventions()[0];
+    assert!(matches!(intervention.violations[..], [Violation::Workspace { flange }] if (flange[0] - 0.7).abs() < 1e-6));
+
+    // Clamped, the elbow stops where the flange reaches the boundary
+    arm.set_safety_limits(Some(limits.action(SafetyAction::Clamp)));
+    shoulder.set_target(0.0, 30.0).await.unwrap();
+    elbow.set_target(90.0, 30.0).await.unwrap();
+    elbow.set_target(0.0, 30.0).await.unwrap();
+    let sent = bus.with_joint(0x0011, |joint| joint.target().unwrap().target_angle).unwrap();
+    let boundary = (2.0f32 / 3.0).acos().to_degrees();
+    assert!(sent >= boundary && sent - boundary < 0.01, "{} vs {}", sent, boundary);
+    assert_eq!(arm.safety_interventions().len(), 2);
+}
@@ -0,0 +1,124 @@
+//! Tests for the bus scheduling planner
+
+#[cfg(feature = "arm_api")]
+use irpc::schedule::{plan, BusTiming, JointTraffic, ScheduleError, Topology};
+#[cfg(feature = "arm_api")]
+use std::time::Duration;
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_frame_time() {
+    let timing = BusTiming::default();
+    let full = timing.frame_time(64);
+    // 58 arbitration-rate bits at 1 Mbit/s plus 679 data-rate bits at 5 Mbit/s
+    assert_eq!(full, Duration::from_nanos(58_000 + 135_800));
+    // Padded up to the next CAN-FD length
+    assert_eq!(timing.frame_time(9), timing.frame_time(12));
+    assert!(timing.frame_time(8) < timing.frame_time(9));
+    // Without bit rate switching everything runs at the nominal rate
+    let classic = BusTiming { nominal_bitrate: 1_000_000, data_bitrate: 1_000_000 };
+    assert!(classic.frame_time(64) > full * 3);
+    // Longer messages are split into full frames
+    assert_eq!(timing.message_time(73), full + timing.frame_time(9));
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_plan_spreads_streams_over_cycles() {
+    let cycle = Duration::from_millis(1);
+    let mut topology = Topology::new(BusTiming::default(), cycle)
+        .with_joint(JointTraffic::telemetry(0x0001, 0, 0).with_cyclic_slot(Duration::from_micros(100)))
+        .with_joint(JointTraffic::telemetry(0x0002, 0, 0).with_cyclic_slot(Duration::from_micros(50)));
+    for joint_id in 0x0010..0x0014 {
+        topology = topology.with_joint(JointTraffic::telemetry(joint_id, 500, 64));
+    }
+    topology = topology.with_joint(JointTraffic::telemetry(0x0020, 100, 16));
+
+    let schedule = plan(&topology).unwrap();
+    assert_eq!(schedule.hyperperiod, Duration::from_millis(10));
+    assert_eq!(schedule.joint(0x0001).unwrap().slot_offset, Some(Duration::ZERO));
+    assert_eq!(schedule.joint(0x0002).unwrap().slot_offset, Some(Duration::from_micros(100)));
+
+    // Busy intervals of every cycle in the hyperperiod must not overlap
+    let mut busy: Vec<Vec<(Duration, Duration)>> = vec![vec![(Duration::ZERO, Duration::from_micros(150))]; 10];
+    for joint in schedule.joints.iter().filter(|joint| joint.telemetry_rate_hz > 0) {
+        let period = Duration::from_secs(1) / joint.telemetry_rate_hz;
+        let mut start = joint.telemetry_offset;
+        while start < schedule.hyperperiod {
+            let index = (start.as_nanos() / cycle.as_nanos()) as usize;
+            let within = start - cycle * index as u32;
+            busy[index].push((within, within + joint.telemetry_time));
+            start += period;
+        }
+    }
+    for intervals in &mut busy {
+        intervals.sort();
+        assert!(intervals.windows(2).all(|pair| pair[0].1 <= pair[1].0), "{:?}", intervals);
+        assert!(intervals.last().unwrap().1 <= cycle);
+    }
+
+    // Four 500 Hz streams split two and two over alternate cycles
+    let phase = |id| schedule.joint(id).unwrap().telemetry_offset >= cycle;
+    assert_eq!([phase(0x0010), phase(0x0011), phase(0x0012), phase(0x0013)], [false, true, false, true]);
+    assert!(schedule.peak_load < 0.7);
+    assert!(schedule.utilization < schedule.peak_load);
+
+    let config = schedule.joint(0x0010).unwrap().telemetry_config();
+    assert_eq!(config.rate_hz, 500);
+    assert_eq!(config.phase_offset_us, schedule.joint(0x0010).unwrap().telemetry_offset.as_micros() as u32);
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_plan_rejects_infeasible_topologies() {
+    let topology = |rate_hz| {
+        (0x0010..0x001A).fold(Topology::new(BusTiming::default(), Duration::from_millis(1)), |topology, id| {
+            topology.with_joint(JointTraffic::telemetry(id, rate_hz, 64))
+        })
+    };
+
+    // Ten full frames every millisecond do not fit
+    assert!(matches!(plan(&topology(1000)), Err(ScheduleError::OverSubscribed { cycle: 0, .. })));
+    // ...but at 250 Hz they spread over four cycles
+    assert!(plan(&topology(250)).is_ok());
+
+    assert_eq!(
+        plan(&topology(300)),
+        Err(ScheduleError::RateNotAligned { joint: 0x0010, rate_hz: 300 })
+    );
+    assert_eq!(
+        plan(&topology(250).with_joint(JointTraffic::telemetry(0x0010, 100, 8))),
+        Err(ScheduleError::DuplicateJoint(0x0010))
+    );
+    assert_eq!(
+        plan(&Topology::new(BusTiming::default(), Duration::ZERO)),
+        Err(ScheduleError::InvalidTiming)
+    );
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_apply_schedule_configures_joints() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{ArmOrchestrator, CommunicationManager};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0011]));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    arm.add_joint(0x0010);
+    arm.add_joint(0x0011);
+
+    let schedule = plan(
+        &Topology::new(BusTiming::default(), Duration::from_millis(1))
+            .with_joint(JointTraffic::telemetry(0x0010, 1000, 64))
+            .with_joint(JointTraffic::telemetry(0x0011, 1000, 64)),
+    )
+    .unwrap();
+    arm.apply_schedule(&schedule).await.unwrap();
+
+    let first = bus.with_joint(0x0010, |joint| joint.telemetry_config()).unwrap().unwrap();
+    let second = bus.with_joint(0x0011, |joint| joint.telemetry_config()).unwrap().unwrap();
+    assert_eq!((first.rate_hz, first.phase_offset_us), (1000, 0));
+    // Right after the first joint's frame
+    assert_eq!(second.phase_offset_us, schedule.joints[0].telemetry_time.as_micros() as u32);
+}
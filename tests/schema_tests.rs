@@ -0,0 +1,80 @@
+//! Tests for the `Payload` schema fingerprint exchanged in `Hello`
+
+use irpc::{BootMode, Capabilities, DeviceInfo, ProtocolError, SCHEMA_HASH};
+
+#[test]
+fn test_device_schema_is_checked() {
+    let device = |schema| DeviceInfo {
+        id: 0x0010,
+        entity_type: 0x1001,
+        mode: BootMode::Application,
+        capabilities: Capabilities::NONE,
+        schema,
+    };
+    assert_ne!(SCHEMA_HASH, 0);
+    assert!(device(SCHEMA_HASH).check_schema().is_ok());
+
+    let error = device(SCHEMA_HASH ^ 1).check_schema().unwrap_err();
+    assert!(matches!(error, ProtocolError::SchemaMismatch { local, remote } if local == SCHEMA_HASH && remote == SCHEMA_HASH ^ 1));
+    assert!(error.to_string().contains(&format!("{:#010x}", SCHEMA_HASH ^ 1)));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_sends_its_schema_in_hello() {
+    use irpc::{Header, Joint, Message, Payload, BROADCAST_ADDRESS};
+
+    let mut joint = Joint::new(0x0010);
+    let discover = Message {
+        header: Header { source_id: 0x0001, target_id: BROADCAST_ADDRESS, msg_id: 3 },
+        payload: Payload::Discover,
+    };
+    joint.handle_message(&discover);
+    let reply = joint.poll(0).or_else(|| joint.poll(u64::MAX / 2)).unwrap();
+    let Payload::Hello(hello) = reply.payload else { panic!("not a Hello") };
+    assert_eq!(hello.schema, SCHEMA_HASH);
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_host_refuses_joints_with_another_schema() {
+    use irpc::{CommunicationManager, Header, HelloPayload, Message, Payload};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let comm = Arc::new(CommunicationManager::deterministic(5));
+    // Two joints answer discovery, one built from another Payload layout
+    let bus = tokio::spawn({
+        let comm = comm.clone();
+        async move {
+            let discover = loop {
+                match comm.poll_outbound() {
+                    Some(message) => break message,
+                    None => tokio::time::sleep(Duration::from_millis(1)).await,
+                }
+            };
+            for (source_id, schema) in [(0x0010, SCHEMA_HASH ^ 0x5A5A), (0x0020, SCHEMA_HASH)] {
+                let hello = HelloPayload {
+                    entity_type: 0x1001,
+                    mode: BootMode::Application,
+                    capabilities: Capabilities::HOMING,
+                    schema,
+                };
+                let header = Header { source_id, target_id: 0x0001, msg_id: discover.header.msg_id };
+                comm.process_incoming(Message { header, payload: Payload::Hello(hello) }).await;
+            }
+        }
+    });
+
+    let devices = comm.discover().await.unwrap();
+    bus.await.unwrap();
+    assert_eq!(devices.len(), 2);
+    assert!(devices[0].check_schema().is_err() && devices[1].check_schema().is_ok());
+    assert_eq!(comm.capabilities(0x0010), None);
+    assert_eq!(comm.capabilities(0x0020), Some(Capabilities::HOMING));
+
+    let refused = comm.send_fire_and_forget(0x0010, Payload::Configure).await;
+    assert!(matches!(refused, Err(ProtocolError::SchemaMismatch { remote, .. }) if remote == SCHEMA_HASH ^ 0x5A5A));
+    assert!(comm.send_fire_and_forget(0x0020, Payload::Configure).await.is_ok());
+    assert!(comm.poll_outbound().is_some_and(|message| message.header.target_id == 0x0020));
+}
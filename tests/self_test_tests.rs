@@ -0,0 +1,125 @@
+//! Tests for the joint self-test
+
+#[cfg(feature = "joint_api")]
+fn run_self_test(joint: &mut irpc::Joint, msg_id: u32, motion_deg: f32) -> Option<irpc::Payload> {
+    let header = irpc::Header { source_id: 0x0001, target_id: 0x0010, msg_id };
+    let payload = irpc::Payload::RunSelfTest(irpc::SelfTestRequest { nonce: 0xC0FFEE, motion_deg });
+    joint.handle_message(&irpc::Message { header, payload }).map(|reply| reply.payload)
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_self_test_checks_encoder_and_driver() {
+    use irpc::motor::{DriverFaults, MotorDriver, MotorError};
+    use irpc::{Joint, MultiTurnPosition, Payload, SelfTestOutcome, ERROR_INVALID_STATE, ERROR_LIMIT_VIOLATION};
+
+    struct Overheated;
+    impl MotorDriver for Overheated {
+        fn enable(&mut self) -> Result<(), MotorError> {
+            Ok(())
+        }
+
+        fn disable(&mut self) {}
+
+        fn set_voltage(&mut self, _volts: f32) -> Result<(), MotorError> {
+            Ok(())
+        }
+
+        fn set_current(&mut self, _amps: f32) -> Result<(), MotorError> {
+            Ok(())
+        }
+
+        fn faults(&mut self) -> DriverFaults {
+            DriverFaults::OVERTEMPERATURE
+        }
+    }
+
+    let mut joint = Joint::new(0x0010);
+    // Never read its encoder
+    let Some(Payload::SelfTestResult(result)) = run_self_test(&mut joint, 1, 0.0) else { panic!() };
+    assert_eq!(result.nonce, 0xC0FFEE);
+    assert_eq!(
+        (result.encoder, result.driver, result.motion),
+        (SelfTestOutcome::Failed, SelfTestOutcome::Passed, SelfTestOutcome::Skipped)
+    );
+    assert!(!result.passed());
+
+    joint.update_encoder(MultiTurnPosition::from_degrees(10.0));
+    let Some(Payload::SelfTestResult(result)) = run_self_test(&mut joint, 2, 0.0) else { panic!() };
+    assert!(result.passed());
+    joint.sync_driver(&mut Overheated);
+    let Some(Payload::SelfTestResult(result)) = run_self_test(&mut joint, 3, 0.0) else { panic!() };
+    assert_eq!((result.encoder, result.driver), (SelfTestOutcome::Passed, SelfTestOutcome::Failed));
+
+    for motion_deg in [6.0, f32::NAN] {
+        let reply = run_self_test(&mut joint, 4, motion_deg);
+        assert!(matches!(reply, Some(Payload::Nack { error: ERROR_LIMIT_VIOLATION, .. })));
+    }
+    // Moving needs an Active joint
+    assert!(matches!(run_self_test(&mut joint, 5, 2.0), Some(Payload::Nack { error: ERROR_INVALID_STATE, .. })));
+    assert!(!joint.self_test_running());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_motion_test_moves_and_returns() {
+    use irpc::{Header, Joint, Message, MultiTurnPosition, Payload, SelfTestOutcome, ERROR_INVALID_STATE, SELF_TEST_TIME_US};
+
+    let mut joint = Joint::new(0x0010);
+    joint.update_encoder(MultiTurnPosition::from_degrees(30.0));
+    for (msg_id, payload) in [(1, Payload::Configure), (2, Payload::Activate)] {
+        let header = Header { source_id: 0x0001, target_id: 0x0010, msg_id };
+        joint.handle_message(&Message { header, payload });
+    }
+    joint.poll(1_000);
+
+    // Answered once the joint covered half the distance
+    assert!(run_self_test(&mut joint, 3, -2.0).is_none());
+    assert!(joint.self_test_running());
+    assert_eq!(joint.target().unwrap().target_angle, 28.0);
+    assert!(matches!(run_self_test(&mut joint, 4, 2.0), Some(Payload::Nack { error: ERROR_INVALID_STATE, .. })));
+    joint.update_encoder(MultiTurnPosition::from_degrees(29.5));
+    assert!(joint.poll(200_000).is_none());
+    joint.update_encoder(MultiTurnPosition::from_degrees(28.75));
+    let reply = joint.poll(300_000).unwrap();
+    assert_eq!((reply.header.target_id, reply.header.msg_id), (0x0001, 3));
+    let Payload::SelfTestResult(result) = reply.payload else { panic!() };
+    assert_eq!((result.motion, result.moved_deg), (SelfTestOutcome::Passed, 1.25));
+    assert!(result.passed());
+    assert_eq!(joint.target().unwrap().target_angle, 30.0);
+    assert!(!joint.self_test_running());
+
+    // A joint that does not move fails in time
+    joint.update_encoder(MultiTurnPosition::from_degrees(30.0));
+    assert!(run_self_test(&mut joint, 5, 2.0).is_none());
+    assert!(joint.poll(300_000 + SELF_TEST_TIME_US - 1).is_none());
+    let Payload::SelfTestResult(result) = joint.poll(300_000 + SELF_TEST_TIME_US).unwrap().payload else { panic!() };
+    assert_eq!((result.motion, result.moved_deg), (SelfTestOutcome::Failed, 0.0));
+    assert!(!result.passed());
+    assert_eq!(joint.target().unwrap().target_angle, 30.0);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_self_test_all_over_sim_bus() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{ArmOrchestrator, CommunicationManager, ProtocolError, SelfTestOutcome};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0011]));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus));
+    arm.add_joint(0x0010);
+    arm.add_joint(0x0011);
+    arm.configure_all().await.into_result().unwrap();
+    arm.get_joint(0x0010).unwrap().activate().await.unwrap();
+
+    let results = arm.self_test_all(0.0).await;
+    assert_eq!(results.len(), 2);
+    assert!(results.values().all(|result| result.as_ref().is_ok_and(|r| r.passed())));
+
+    let results = arm.self_test_all(2.0).await;
+    let moved = results[&0x0010].as_ref().unwrap();
+    assert_eq!(moved.motion, SelfTestOutcome::Passed);
+    assert!(moved.moved_deg >= 1.0);
+    assert!(matches!(results[&0x0011], Err(ProtocolError::IoError(_))));
+}
@@ -0,0 +1,110 @@
+//! Tests for scripted motion sequences
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_sequence_builder() {
+    use irpc::sequence::{MotionSequence, MotionStep};
+    use std::time::Duration;
+
+    let pick = MotionSequence::new()
+        .move_to([(0x0011, 10.0), (0x0010, 20.0)], 45.0)
+        .set_gripper(0x0012, 1, true)
+        .parallel([
+            MotionSequence::new().move_to([(0x0010, 0.0)], 45.0),
+            MotionSequence::new().wait(Duration::from_millis(100)).move_to([(0x0011, 0.0)], 45.0),
+        ]);
+    let job = MotionSequence::new().wait(Duration::from_secs(1)).repeat(3, pick.clone());
+
+    assert_eq!(pick.step_count(), 5);
+    assert_eq!(job.step_count(), 16);
+    assert_eq!(job.joints(), vec![0x0010, 0x0011, 0x0012]);
+    assert_eq!(job.steps()[0], MotionStep::Wait(Duration::from_secs(1)));
+    assert!(matches!(&job.steps()[1], MotionStep::Repeat { count: 3, body } if *body == pick));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+async fn sim_arm() -> (std::sync::Arc<irpc::bus::sim::SimBus>, irpc::ArmOrchestrator) {
+    use irpc::bus::sim::SimBus;
+    use irpc::{ArmOrchestrator, CommunicationManager};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0011]));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    arm.add_joint(0x0010);
+    arm.add_joint(0x0011);
+    arm.configure_all().await.into_result().unwrap();
+    arm.activate_all().await.into_result().unwrap();
+    (bus, arm)
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_sequence_runs_with_progress_events() {
+    use irpc::aux_io::{AuxIoError, AuxIoHandler};
+    use irpc::sequence::{MotionSequence, SequenceError, SequenceEvent};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct Gripper(Arc<Mutex<Vec<bool>>>);
+    impl AuxIoHandler for Gripper {
+        fn set_digital_output(&mut self, _channel: u8, value: bool) -> Result<(), AuxIoError> {
+            self.0.lock().unwrap().push(value);
+            Ok(())
+        }
+    }
+
+    let (bus, arm) = sim_arm().await;
+    let gripper = Arc::new(Mutex::new(Vec::new()));
+    bus.set_aux_io(0x0011, Gripper(gripper.clone()));
+
+    let cycle = MotionSequence::new()
+        .parallel([
+            MotionSequence::new().move_to([(0x0010, 30.0)], 60.0),
+            MotionSequence::new().wait(Duration::from_millis(200)).move_to([(0x0011, -20.0)], 40.0),
+        ])
+        .set_gripper(0x0011, 0, true)
+        .move_to([(0x0010, 0.0), (0x0011, 0.0)], 60.0)
+        .set_gripper(0x0011, 0, false);
+    let job = MotionSequence::new().repeat(2, cycle);
+    let total = job.step_count();
+
+    let mut run = arm.start_sequence(job).unwrap();
+    let mut events = Vec::new();
+    while let Some(event) = run.next_event().await {
+        events.push(event);
+    }
+    run.wait().await.unwrap();
+
+    assert_eq!(events.len(), 2 * total);
+    assert_eq!(events.iter().filter(|e| matches!(e, SequenceEvent::StepFinished { .. })).count(), total);
+    assert!(matches!(events.last(), Some(SequenceEvent::StepFinished { completed, .. }) if *completed == total));
+    assert_eq!(*gripper.lock().unwrap(), vec![true, false, true, false]);
+    // Done within the tolerance, still settling
+    for id in [0x0010, 0x0011] {
+        assert!(bus.true_position(id).unwrap().abs() <= irpc::SEQUENCE_POSITION_TOLERANCE_DEG);
+    }
+
+    let stray = MotionSequence::new().move_to([(0x0020, 0.0)], 10.0);
+    assert!(matches!(arm.run_sequence(stray).await, Err(SequenceError::UnknownJoint(0x0020))));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_cancelled_sequence_holds_the_joints() {
+    use irpc::sequence::{MotionSequence, SequenceError};
+    use std::time::Duration;
+
+    let (bus, arm) = sim_arm().await;
+    let slow = MotionSequence::new().move_to([(0x0010, 90.0)], 10.0).move_to([(0x0011, 90.0)], 10.0);
+    let run = arm.start_sequence(slow).unwrap();
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    run.cancel();
+    assert!(matches!(run.wait().await, Err(SequenceError::Cancelled)));
+    let held = bus.true_position(0x0010).unwrap();
+    assert!((held - 20.0).abs() < 1.0, "{}", held);
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!((bus.true_position(0x0010).unwrap() - held).abs() < 0.5);
+    assert_eq!(bus.true_position(0x0011), Some(0.0));
+}
@@ -0,0 +1,100 @@
+//! Tests for shutting the arm down and for dropping it without doing so
+#![cfg(all(feature = "arm_api", feature = "joint_api"))]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use irpc::bus::sim::SimBus;
+use irpc::{ArmOrchestrator, CommunicationManager, LifecycleState, Payload, ProtocolError};
+
+async fn arm_with(bus: &Arc<SimBus>, active: &[u16]) -> (ArmOrchestrator, Arc<CommunicationManager>) {
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    let mut arm = ArmOrchestrator::with_comm_manager(comm.clone());
+    for id in bus.joint_ids() {
+        arm.add_joint(id);
+    }
+    arm.configure_all().await.into_result().unwrap();
+    for &id in active {
+        arm.get_joint(id).unwrap().activate().await.unwrap();
+    }
+    (arm, comm)
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_shutdown_deactivates_joints_and_closes_the_bus() {
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0020]));
+    let (mut arm, comm) = arm_with(&bus, &[0x0010]).await;
+    arm.start_safety_heartbeat(Duration::from_millis(200));
+    arm.start_reconciliation(Duration::from_millis(50));
+
+    // A request in flight is answered before the joints are deactivated
+    let joint = arm.get_joint(0x0010).unwrap().clone();
+    let in_flight = tokio::spawn(async move { joint.set_target(10.0, 20.0).await });
+    tokio::task::yield_now().await;
+
+    let result = arm.shutdown().await;
+    assert!(result.is_success());
+    assert_eq!(result.succeeded, vec![0x0010, 0x0020]);
+    in_flight.await.unwrap().unwrap();
+    assert!(!arm.is_ready());
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Inactive));
+    assert_eq!(bus.joint_state(0x0020), Some(LifecycleState::Inactive));
+
+    // The bus is closed: nothing more goes out, not even on drop
+    let sent = bus.transmitted();
+    assert!(matches!(comm.send_fire_and_forget(0x0010, Payload::Activate).await, Err(ProtocolError::IoError(_))));
+    drop(arm);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(bus.transmitted(), sent);
+    assert_eq!(comm.pending_requests().await, 0);
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_shutdown_flushes_recordings() {
+    use irpc::bus::record::{open_log, Direction, RecordScope, TelemetryRecorder};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.irpclog");
+    let bus = Arc::new(SimBus::with_joints([0x0010]));
+    let recorder = Arc::new(TelemetryRecorder::create(&path, bus, RecordScope::All).unwrap());
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(recorder.clone()));
+    arm.add_joint(0x0010);
+    arm.configure_all().await.into_result().unwrap();
+    arm.activate_all().await.into_result().unwrap();
+
+    // Buffered until the shutdown flushes it
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+    arm.shutdown().await.into_result().unwrap();
+
+    let records = open_log(&path).unwrap();
+    assert_eq!(records.len() as u64, recorder.records());
+    let commands: Vec<_> = records
+        .into_iter()
+        .filter(|record| record.direction == Direction::Transmitted)
+        .map(|record| record.message.payload)
+        .collect();
+    assert!(matches!(commands[..], [Payload::Configure, Payload::Activate, Payload::Deactivate]));
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_drop_resets_active_joints() {
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0020, 0x0030]));
+    let (arm, _comm) = arm_with(&bus, &[0x0010, 0x0030]).await;
+    let sent = bus.transmitted();
+
+    drop(arm);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    // Only the joints that could be moving are stopped
+    assert_eq!(bus.transmitted(), sent + 2);
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Unconfigured));
+    assert_eq!(bus.joint_state(0x0020), Some(LifecycleState::Inactive));
+    assert_eq!(bus.joint_state(0x0030), Some(LifecycleState::Unconfigured));
+
+    // Even when the manager goes with it, what was queued still goes out
+    let bus = Arc::new(SimBus::with_joints([0x0010]));
+    let (arm, comm) = arm_with(&bus, &[0x0010]).await;
+    drop((arm, comm));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Unconfigured));
+}
@@ -0,0 +1,110 @@
+//! Tests for passive bus monitoring
+
+#[cfg(feature = "arm_api")]
+use irpc::bus::record::{Direction, LogRecord, TelemetryPlayer};
+#[cfg(feature = "arm_api")]
+use irpc::bus::sniffer::{Sniffer, SnifferFilter};
+#[cfg(feature = "arm_api")]
+use irpc::{Header, Message, Payload};
+#[cfg(feature = "arm_api")]
+use std::sync::Arc;
+
+#[cfg(feature = "arm_api")]
+fn message(source_id: u16, target_id: u16, msg_id: u32, payload: Payload) -> Message {
+    Message { header: Header { source_id, target_id, msg_id }, payload }
+}
+
+/// A bus as a listen-only node sees it: commands from the host and replies
+#[cfg(feature = "arm_api")]
+fn bus_traffic() -> TelemetryPlayer {
+    let messages = [
+        message(0x0001, 0x0010, 1, Payload::Configure),
+        message(0x0010, 0x0001, 2, Payload::Ack(1)),
+        message(0x0001, 0x0020, 3, Payload::Activate),
+        message(0x0020, 0x0001, 4, Payload::Nack { id: 3, error: 2 }),
+        message(0x0010, 0x0001, 5, Payload::Ack(9)),
+    ];
+    TelemetryPlayer::new(messages.into_iter().map(|message| LogRecord {
+        timestamp_us: 0,
+        direction: Direction::Received,
+        message,
+    }))
+    .unpaced()
+}
+
+#[cfg(feature = "arm_api")]
+async fn drain<A: irpc::CommunicationAdapter>(sniffer: &Sniffer<A>) -> Vec<LogRecord> {
+    let mut records = Vec::new();
+    while let Some(record) = sniffer.try_next().await.unwrap() {
+        records.push(record);
+    }
+    records
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test]
+async fn test_sniffer_reports_everything_by_default() {
+    let sniffer = Sniffer::new(Arc::new(bus_traffic()));
+    let records = drain(&sniffer).await;
+    let directions: Vec<_> = records.iter().map(|r| r.direction).collect();
+    assert_eq!(
+        directions,
+        [
+            Direction::Transmitted,
+            Direction::Received,
+            Direction::Transmitted,
+            Direction::Received,
+            Direction::Received
+        ]
+    );
+    assert_eq!(sniffer.seen(), 5);
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test]
+async fn test_sniffer_filters() {
+    let by_device = Sniffer::new(Arc::new(bus_traffic())).with_filter(SnifferFilter::new().device(0x0010));
+    let ids: Vec<_> = drain(&by_device).await.iter().map(|r| r.message.header.msg_id).collect();
+    assert_eq!(ids, [1, 2, 5]);
+    assert_eq!(by_device.seen(), 5);
+
+    let by_kind = Sniffer::new(Arc::new(bus_traffic())).with_filter(SnifferFilter::new().kind("Ack").kind("Nack"));
+    let ids: Vec<_> = drain(&by_kind).await.iter().map(|r| r.message.header.msg_id).collect();
+    assert_eq!(ids, [2, 4, 5]);
+
+    // Criteria of different sorts must all match
+    let combined = Sniffer::new(Arc::new(bus_traffic()))
+        .with_filter(SnifferFilter::new().device(0x0010).kind("Ack").direction(Direction::Received));
+    let ids: Vec<_> = drain(&combined).await.iter().map(|r| r.message.header.msg_id).collect();
+    assert_eq!(ids, [2, 5]);
+
+    let other_host = Sniffer::new(Arc::new(bus_traffic()))
+        .with_host(0x0010)
+        .with_filter(SnifferFilter::new().direction(Direction::Transmitted));
+    let ids: Vec<_> = drain(&other_host).await.iter().map(|r| r.message.header.msg_id).collect();
+    assert_eq!(ids, [2, 5]);
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_sniffer_waits_and_timestamps() {
+    use irpc::clock::VirtualClock;
+    use std::time::Duration;
+
+    let clock = Arc::new(VirtualClock::new());
+    clock.advance(Duration::from_micros(1_500_250));
+    let sniffer = Sniffer::new(Arc::new(bus_traffic()))
+        .with_clock(clock.clone())
+        .with_filter(SnifferFilter::new().kind("Nack"));
+
+    let record = sniffer.next().await.unwrap();
+    assert_eq!(record.timestamp_us, 1_500_250);
+    assert_eq!(record.to_string(), "     1.500250 RX 0x0020 -> 0x0001 #4 Nack #3 error=2");
+
+    // Nothing else matches: the sniffer keeps polling
+    let waiting = tokio::spawn(async move { sniffer.next().await.map(|r| r.message.header.msg_id) });
+    tokio::task::yield_now().await;
+    assert!(!waiting.is_finished());
+    assert_eq!(clock.pending_sleepers(), 1);
+    waiting.abort();
+}
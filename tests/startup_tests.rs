@@ -0,0 +1,90 @@
+//! Tests for dependency-ordered startup
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_startup_plan_stages() {
+    use irpc::startup::{StartupError, StartupPlan};
+
+    let plan = StartupPlan::new()
+        .braked_joint(0x0040, [0x0020])
+        .joint(0x0020, [0x0010])
+        .joint(0x0010, [])
+        .braked_joint(0x0030, [0x0020, 0x0010]);
+    assert_eq!(plan.stages().unwrap(), vec![vec![0x0010], vec![0x0020], vec![0x0040, 0x0030]]);
+    assert_eq!(plan.braked_joints(), vec![0x0040, 0x0030]);
+    assert_eq!(StartupPlan::flat([0x0010, 0x0020]).stages().unwrap(), vec![vec![0x0010, 0x0020]]);
+
+    let cycle = StartupPlan::new().joint(0x0010, []).joint(0x0020, [0x0030]).joint(0x0030, [0x0020]);
+    assert!(matches!(cycle.stages(), Err(StartupError::Cycle(stuck)) if stuck == [0x0020, 0x0030]));
+    let dangling = StartupPlan::new().joint(0x0020, [0x0010]);
+    assert!(matches!(
+        dangling.stages(),
+        Err(StartupError::UnknownDependency { joint_id: 0x0020, after: 0x0010 })
+    ));
+    let twice = StartupPlan::new().joint(0x0010, []).joint(0x0010, []);
+    assert!(matches!(twice.stages(), Err(StartupError::DuplicateJoint(0x0010))));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_start_up_releases_brakes_last() {
+    use irpc::bus::sim::SimBus;
+    use irpc::startup::{StartupError, StartupPlan};
+    use irpc::{ArmOrchestrator, Capabilities, CommunicationManager, Joint, LifecycleState};
+    use std::sync::Arc;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0020]));
+    let mut wrist = Joint::new(0x0030);
+    wrist.set_capabilities(wrist.capabilities() | Capabilities::BRAKE);
+    bus.add_joint(wrist);
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    comm.discover().await.unwrap();
+    let mut arm = ArmOrchestrator::with_comm_manager(comm);
+    for id in [0x0010, 0x0020, 0x0030] {
+        arm.add_joint(id);
+    }
+
+    let stray = StartupPlan::new().joint(0x0050, []);
+    assert!(matches!(arm.start_up(&stray).await, Err(StartupError::UnknownJoint(0x0050))));
+
+    // The base is already up and is left alone
+    arm.get_joint(0x0010).unwrap().configure().await.unwrap();
+    arm.get_joint(0x0010).unwrap().activate().await.unwrap();
+    let partial = StartupPlan::new().joint(0x0010, []).joint(0x0020, [0x0010]);
+    arm.start_up(&partial).await.unwrap();
+    assert!(!arm.is_ready());
+
+    assert_eq!(bus.with_joint(0x0030, |joint| joint.brake_engaged()), Some(true));
+    let plan = partial.braked_joint(0x0030, [0x0020]);
+    arm.start_up(&plan).await.unwrap();
+    assert!(arm.is_ready());
+    assert_eq!(bus.joint_state(0x0030), Some(LifecycleState::Active));
+    assert_eq!(bus.with_joint(0x0030, |joint| joint.brake_engaged()), Some(false));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_start_up_stops_at_a_failed_stage() {
+    use irpc::bus::sim::SimBus;
+    use irpc::startup::{StartupError, StartupPlan, StartupStep};
+    use irpc::{ArmOrchestrator, CommunicationManager, LifecycleState, ProtocolError};
+    use std::sync::Arc;
+
+    // The base never answers, so nothing after it starts
+    let bus = Arc::new(SimBus::with_joints([0x0020, 0x0030]));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    for id in [0x0010, 0x0020, 0x0030] {
+        arm.add_joint(id);
+    }
+    let plan = StartupPlan::new().joint(0x0030, []).joint(0x0010, []).joint(0x0020, [0x0010]);
+
+    let error = arm.start_up(&plan).await.unwrap_err();
+    assert!(matches!(
+        error,
+        StartupError::Failed { joint_id: 0x0010, step: StartupStep::Configure, error: ProtocolError::Timeout }
+    ));
+    assert_eq!(error.to_string(), "Joint 16 failed to configure: Communication timeout");
+    assert_eq!(bus.joint_state(0x0030), Some(LifecycleState::Active));
+    assert_eq!(bus.joint_state(0x0020), Some(LifecycleState::Unconfigured));
+    assert!(!arm.is_ready());
+}
@@ -0,0 +1,100 @@
+//! Tests for the joint's usage counters
+
+#[cfg(feature = "joint_api")]
+fn send(joint: &mut irpc::Joint, msg_id: u32, payload: irpc::Payload) -> irpc::Payload {
+    let header = irpc::Header { source_id: 0x0001, target_id: 0x0010, msg_id };
+    joint.handle_message(&irpc::Message { header, payload }).unwrap().payload
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_counts_energy_time_and_moves() {
+    use irpc::{Joint, JointStatistics, MultiTurnPosition, Payload, SetTargetPayload};
+
+    let mut joint = Joint::new(0x0010);
+    joint.update_encoder(MultiTurnPosition::from_degrees(0.0));
+    assert_eq!(joint.statistics(), JointStatistics::default());
+
+    // 100 W for 18 s is half a watt-hour; only driven time counts as on
+    joint.update_power(24.0, 100.0 / 24.0);
+    joint.poll(18_000_000);
+    joint.update_power(24.0, 0.0);
+    assert!((joint.statistics().energy_wh - 0.5).abs() < 1e-6, "{:?}", joint.statistics());
+    assert_eq!(joint.statistics().motor_on_s, 0);
+    // Regeneration does not give energy back
+    joint.update_power(24.0, -10.0);
+    joint.poll(20_000_000);
+    joint.update_power(24.0, 0.0);
+    assert!((joint.statistics().energy_wh - 0.5).abs() < 1e-6);
+
+    send(&mut joint, 1, Payload::Configure);
+    send(&mut joint, 2, Payload::Activate);
+    joint.poll(22_500_000);
+    for (msg_id, angle) in [(3, 10.0), (4, 20.0)] {
+        let target = SetTargetPayload { target_angle: angle, velocity_limit: 30.0 };
+        assert!(matches!(send(&mut joint, msg_id, Payload::SetTarget(target)), Payload::Ack(_)));
+    }
+    send(&mut joint, 5, Payload::Deactivate);
+    joint.poll(30_000_000);
+
+    let Payload::Statistics(statistics) = send(&mut joint, 6, Payload::RequestStatistics) else { panic!() };
+    assert_eq!((statistics.motor_on_s, statistics.move_count), (2, 2));
+    assert_eq!(statistics, joint.statistics());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_counters_survive_a_power_cycle() {
+    use irpc::config_store::{JointConfig, MemoryConfigStore};
+    use irpc::{Header, Joint, JointStatistics, Message, MultiTurnPosition, Payload, SetTargetPayload};
+
+    let mut joint = Joint::new(0x0010);
+    joint.update_encoder(MultiTurnPosition::from_degrees(0.0));
+    send(&mut joint, 1, Payload::Configure);
+    send(&mut joint, 2, Payload::Activate);
+    send(&mut joint, 3, Payload::SetTarget(SetTargetPayload { target_angle: 5.0, velocity_limit: 10.0 }));
+    joint.poll(3_000_000);
+    let expected = JointStatistics { energy_wh: 0.0, motor_on_s: 3, move_count: 1 };
+    assert_eq!(joint.statistics(), expected);
+
+    // Saved into a blank store without disturbing the settings
+    let mut store = MemoryConfigStore::new();
+    joint.save_statistics(&mut store).unwrap();
+    let stored = JointConfig::load(&mut store).unwrap();
+    assert_eq!((stored.statistics, stored.limits), (expected, None));
+    let mut rebooted = Joint::new(0x0010);
+    rebooted.restore_config(&mut store).unwrap();
+    assert_eq!(rebooted.statistics(), expected);
+
+    // Loading or wiping the settings keeps the counters
+    let store_command = |joint: &mut Joint, store: &mut MemoryConfigStore, msg_id, payload| {
+        let header = Header { source_id: 0x0001, target_id: 0x0010, msg_id };
+        joint.handle_with_store(&Message { header, payload }, store).unwrap().payload
+    };
+    let mut blank = MemoryConfigStore::new();
+    assert!(matches!(store_command(&mut rebooted, &mut blank, 4, Payload::FactoryReset), Payload::Ack(4)));
+    assert!(matches!(store_command(&mut rebooted, &mut store, 5, Payload::LoadConfig), Payload::Ack(5)));
+    assert_eq!(rebooted.statistics(), expected);
+    assert!(matches!(store_command(&mut rebooted, &mut blank, 6, Payload::SaveConfig), Payload::Ack(6)));
+    assert_eq!(JointConfig::load(&mut blank).unwrap().statistics, expected);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_host_reads_statistics() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, JointProxy};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010]).with_telemetry(Duration::from_millis(10)));
+    let joint = JointProxy::new(0x0010, CommunicationManager::with_adapter(bus));
+    joint.configure().await.unwrap();
+    joint.activate().await.unwrap();
+    joint.set_target(10.0, 100.0).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+
+    let statistics = joint.statistics().await.unwrap();
+    assert_eq!(statistics.move_count, 1);
+    assert_eq!(statistics.motor_on_s, 1);
+}
@@ -0,0 +1,125 @@
+//! Tests for the host-side persistence backends
+
+#[cfg(feature = "arm_api")]
+use irpc::storage::{namespace, FileStorage, MemoryStorage, Storage, StorageError, StorageExt};
+
+/// Behaviour every backend must share
+#[cfg(feature = "arm_api")]
+fn exercise_backend(store: &dyn Storage) {
+    assert_eq!(store.get(namespace::COUNTERS, "joint-0010").unwrap(), None);
+
+    store.put(namespace::COUNTERS, "joint-0010", &[1, 2, 3]).unwrap();
+    store.put(namespace::COUNTERS, "joint-0010", &[4, 5]).unwrap();
+    assert_eq!(store.get(namespace::COUNTERS, "joint-0010").unwrap(), Some(vec![4, 5]));
+
+    store.put(namespace::COUNTERS, "joint-0020", &[]).unwrap();
+    store.put(namespace::CALIBRATION, "joint-0010", &[9]).unwrap();
+    assert_eq!(
+        store.keys(namespace::COUNTERS).unwrap(),
+        vec!["joint-0010".to_string(), "joint-0020".to_string()]
+    );
+    assert!(store.keys(namespace::INCIDENTS).unwrap().is_empty());
+
+    store.append(namespace::AUDIT, "2026-10-16.log", b"activate\n").unwrap();
+    store.append(namespace::AUDIT, "2026-10-16.log", b"deactivate\n").unwrap();
+    assert_eq!(
+        store.get(namespace::AUDIT, "2026-10-16.log").unwrap().unwrap(),
+        b"activate\ndeactivate\n"
+    );
+
+    assert!(store.delete(namespace::COUNTERS, "joint-0020").unwrap());
+    assert!(!store.delete(namespace::COUNTERS, "joint-0020").unwrap());
+
+    assert!(matches!(
+        store.put("../etc", "passwd", &[]),
+        Err(StorageError::InvalidKey(_))
+    ));
+    assert!(matches!(
+        store.put(namespace::COUNTERS, "a/b", &[]),
+        Err(StorageError::InvalidKey(_))
+    ));
+    // Every operation refuses them, not only writes
+    assert!(matches!(store.get("../etc", "passwd"), Err(StorageError::InvalidKey(_))));
+    assert!(matches!(store.delete(namespace::COUNTERS, "a/b"), Err(StorageError::InvalidKey(_))));
+    assert!(matches!(store.keys(".hidden"), Err(StorageError::InvalidKey(_))));
+
+    // Typed helpers
+    store.save(namespace::COUNTERS, "cycles", &(42u64, 3.5f32)).unwrap();
+    let loaded: Option<(u64, f32)> = store.load(namespace::COUNTERS, "cycles").unwrap();
+    assert_eq!(loaded, Some((42, 3.5)));
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_memory_storage() {
+    exercise_backend(&MemoryStorage::new());
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_file_storage() {
+    let dir = tempfile::tempdir().unwrap();
+    exercise_backend(&FileStorage::open(dir.path()).unwrap());
+
+    // Records survive reopening
+    let reopened = FileStorage::open(dir.path()).unwrap();
+    assert_eq!(reopened.get(namespace::CALIBRATION, "joint-0010").unwrap(), Some(vec![9]));
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_concurrent_writes() {
+    use std::thread;
+
+    // Appends racing on one record all land
+    let memory = MemoryStorage::new();
+    thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| {
+                for _ in 0..100 {
+                    memory.append(namespace::AUDIT, "shared.log", b"x").unwrap();
+                }
+            });
+        }
+    });
+    assert_eq!(memory.get(namespace::AUDIT, "shared.log").unwrap().unwrap().len(), 800);
+
+    // Puts racing on one key leave one writer's value, whole
+    let dir = tempfile::tempdir().unwrap();
+    let files = FileStorage::open(dir.path()).unwrap();
+    thread::scope(|scope| {
+        for writer in 0..8u8 {
+            let files = &files;
+            scope.spawn(move || {
+                for _ in 0..50 {
+                    files.put(namespace::COUNTERS, "shared", &[writer; 4096]).unwrap();
+                }
+            });
+        }
+    });
+    let value = files.get(namespace::COUNTERS, "shared").unwrap().unwrap();
+    assert_eq!(value.len(), 4096);
+    assert!(value.iter().all(|&byte| byte == value[0]));
+    assert_eq!(std::fs::read_dir(dir.path().join(namespace::COUNTERS)).unwrap().count(), 1);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_sqlite_storage() {
+    use irpc::storage::SqliteStorage;
+
+    exercise_backend(&SqliteStorage::open_in_memory().unwrap());
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("irpc.db");
+    SqliteStorage::open(&path)
+        .unwrap()
+        .put(namespace::INCIDENTS, "fault-1", b"overcurrent")
+        .unwrap();
+
+    let reopened = SqliteStorage::open(&path).unwrap();
+    assert_eq!(
+        reopened.get(namespace::INCIDENTS, "fault-1").unwrap(),
+        Some(b"overcurrent".to_vec())
+    );
+}
@@ -0,0 +1,97 @@
+//! Tests for streamed commands and their consolidated `StreamAck`
+
+#[cfg(feature = "joint_api")]
+fn active_joint() -> irpc::Joint {
+    use irpc::{Header, Joint, JointLimits, Message, MultiTurnPosition, Payload};
+
+    let mut joint = Joint::new(0x0010);
+    joint.update_encoder(MultiTurnPosition::from_degrees(0.0));
+    let limits = JointLimits { min_position: -90.0, max_position: 90.0, max_velocity: 60.0 };
+    for (msg_id, payload) in [(1, Payload::Configure), (2, Payload::SetLimits(limits)), (3, Payload::Activate)] {
+        let header = Header { source_id: 0x0001, target_id: 0x0010, msg_id };
+        joint.handle_message(&Message { header, payload });
+    }
+    joint
+}
+
+#[cfg(feature = "joint_api")]
+fn stream(joint: &mut irpc::Joint, msg_id: u32, angle: f32) -> Option<irpc::Payload> {
+    let header = irpc::Header { source_id: 0x0001, target_id: 0x0010, msg_id };
+    let payload = irpc::Payload::SetTarget(irpc::SetTargetPayload { target_angle: angle, velocity_limit: 30.0 });
+    joint.handle_message(&irpc::Message { header, payload }).map(|reply| reply.payload)
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_streamed_commands_are_acknowledged_together() {
+    use irpc::{Payload, ERROR_LIMIT_VIOLATION, STREAM_ACK_IDLE_US, STREAM_ACK_INTERVAL, STREAM_MSG_ID_FLAG};
+
+    let mut joint = active_joint();
+    // One missing from the sequence
+    let ids: Vec<u32> = (1..=STREAM_ACK_INTERVAL + 1).filter(|&n| n != 7).map(|n| STREAM_MSG_ID_FLAG | n).collect();
+    for &msg_id in &ids[..ids.len() - 1] {
+        assert!(stream(&mut joint, msg_id, 1.0).is_none());
+    }
+    let last = *ids.last().unwrap();
+    let Some(Payload::StreamAck { last_msg_id, dropped }) = stream(&mut joint, last, 2.0) else { panic!() };
+    assert_eq!((last_msg_id, dropped), (last, 1));
+    assert_eq!(joint.target().unwrap().target_angle, 2.0);
+
+    // Refusals are not held back
+    let reply = stream(&mut joint, last + 1, 120.0);
+    assert!(matches!(reply, Some(Payload::Nack { id, error: ERROR_LIMIT_VIOLATION }) if id == last + 1));
+    assert!(stream(&mut joint, last + 2, 3.0).is_none());
+    joint.poll(1_000);
+    assert!(joint.poll(STREAM_ACK_IDLE_US - 1).is_none());
+    let reply = joint.poll(1_000 + STREAM_ACK_IDLE_US).unwrap();
+    assert_eq!(reply.header.target_id, 0x0001);
+    let Payload::StreamAck { last_msg_id, dropped } = reply.payload else { panic!() };
+    assert_eq!((last_msg_id, dropped), (last + 2, 0));
+    assert!(joint.poll(10 * STREAM_ACK_IDLE_US).is_none());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_restarted_stream_is_not_a_loss() {
+    use irpc::{Payload, STREAM_ACK_IDLE_US, STREAM_MSG_ID_FLAG};
+
+    let mut joint = active_joint();
+    for n in [10, 11, 11, 3, 4] {
+        assert!(stream(&mut joint, STREAM_MSG_ID_FLAG | n, n as f32).is_none());
+    }
+    let reply = joint.poll(STREAM_ACK_IDLE_US).unwrap();
+    let Payload::StreamAck { last_msg_id, dropped } = reply.payload else { panic!() };
+    assert_eq!((last_msg_id, dropped), (STREAM_MSG_ID_FLAG | 4, 0));
+
+    // Ordinary commands are still acknowledged one by one
+    assert!(matches!(stream(&mut joint, 12, 5.0), Some(Payload::Ack(12))));
+    assert!(joint.poll(2 * STREAM_ACK_IDLE_US).is_none());
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_proxy_streams_targets_over_sim_bus() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{CommunicationManager, JointProxy, SetTargetPayload, StreamStatus, STREAM_MSG_ID_FLAG};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010]));
+    let joint = JointProxy::new(0x0010, CommunicationManager::with_adapter(bus));
+    joint.configure().await.unwrap();
+    joint.activate().await.unwrap();
+    assert_eq!(joint.stream_status(), StreamStatus::default());
+
+    for step in 1..=120 {
+        let target = SetTargetPayload { target_angle: step as f32 * 0.01, velocity_limit: 30.0 };
+        joint.stream_target(target.into()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let status = joint.stream_status();
+    assert_eq!((status.sent, status.dropped, status.refused), (120, 0, 0));
+    assert_eq!(status.last_acked, Some(STREAM_MSG_ID_FLAG | 120));
+    let position = joint.read_multi_turn_position().await.unwrap();
+    assert!((position.degrees() - 1.2).abs() < 0.05);
+}
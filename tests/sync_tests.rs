@@ -0,0 +1,99 @@
+//! Tests for cyclic synchronous position mode and `Sync` frames
+
+#[test]
+fn test_control_modes_need_their_capability() {
+    use irpc::{Capabilities, ControlMode, Payload, SyncSample};
+
+    assert_eq!(ControlMode::Position.capability(), Capabilities::NONE);
+    assert_eq!(ControlMode::Torque.capability(), Capabilities::DIRECT_CONTROL);
+    assert_eq!(ControlMode::CyclicPosition.capability(), Capabilities::CYCLIC_SYNC);
+
+    let sample = SyncSample { cycle_counter: 7, position: 12.5, velocity: -3.0 };
+    assert!(!Payload::SyncSample(sample).requires_fragmentation());
+    assert_eq!(Payload::Sync { cycle_counter: 7 }.name(), "Sync");
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_targets_wait_for_the_next_sync() {
+    use irpc::{
+        Capabilities, ControlMode, Header, Joint, LifecycleState, Message, MultiTurnPosition, Payload,
+        SetTargetPayload, BROADCAST_ADDRESS,
+    };
+
+    let mut joint = Joint::new(0x0010);
+    joint.update_encoder(MultiTurnPosition::from_degrees(4.0));
+    let send = |joint: &mut Joint, target_id, msg_id, payload| {
+        joint.handle_message(&Message { header: Header { source_id: 0x0001, target_id, msg_id }, payload })
+    };
+
+    // Only joints that support it switch to cyclic mode
+    let reply = send(&mut joint, 0x0010, 1, Payload::SetControlMode(ControlMode::CyclicPosition));
+    assert!(matches!(reply, Some(Message { payload: Payload::Nack { id: 1, .. }, .. })));
+    joint.set_capabilities(joint.capabilities() | Capabilities::CYCLIC_SYNC);
+    send(&mut joint, 0x0010, 2, Payload::Configure);
+    let reply = send(&mut joint, 0x0010, 3, Payload::SetControlMode(ControlMode::CyclicPosition));
+    assert!(matches!(reply, Some(Message { payload: Payload::Ack(3), .. })));
+
+    // Inactive joints ignore Sync
+    assert!(send(&mut joint, BROADCAST_ADDRESS, 4, Payload::Sync { cycle_counter: 1 }).is_none());
+    send(&mut joint, 0x0010, 5, Payload::Activate);
+    assert_eq!(joint.state(), LifecycleState::Active);
+
+    let target = SetTargetPayload { target_angle: 30.0, velocity_limit: 20.0 };
+    let reply = send(&mut joint, 0x0010, 6, Payload::SetTarget(target));
+    assert!(matches!(reply, Some(Message { payload: Payload::Ack(6), .. })));
+    assert!(joint.target().is_none());
+
+    let Some(reply) = send(&mut joint, BROADCAST_ADDRESS, 7, Payload::Sync { cycle_counter: 2 }) else { panic!() };
+    assert_eq!((reply.header.source_id, reply.header.target_id), (0x0010, 0x0001));
+    let Payload::SyncSample(sample) = reply.payload else { panic!("{:?}", reply.payload) };
+    assert_eq!(sample.cycle_counter, 2);
+    assert_eq!(sample.position, 4.0);
+    assert_eq!(joint.target().unwrap().target_angle, 30.0);
+
+    // Nothing new buffered: the latched target stays
+    send(&mut joint, BROADCAST_ADDRESS, 8, Payload::Sync { cycle_counter: 3 });
+    assert_eq!(joint.target().unwrap().target_angle, 30.0);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_orchestrator_syncs_joints_over_sim_bus() {
+    use irpc::bus::sim::SimBus;
+    use irpc::{ArmOrchestrator, Capabilities, CommunicationManager, ControlMode};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let ids = [0x0010, 0x0020];
+    let bus = Arc::new(SimBus::with_joints(ids));
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    let mut arm = ArmOrchestrator::with_comm_manager(comm.clone());
+    for id in ids {
+        bus.with_joint(id, |joint| joint.set_capabilities(joint.capabilities() | Capabilities::CYCLIC_SYNC));
+        arm.add_joint(id);
+    }
+    arm.configure_all().await.into_result().unwrap();
+    for id in ids {
+        arm.get_joint(id).unwrap().set_control_mode(ControlMode::CyclicPosition).await.unwrap();
+    }
+    arm.activate_all().await.into_result().unwrap();
+
+    // Buffered targets wait for the first Sync
+    arm.get_joint(0x0010).unwrap().set_target(20.0, 45.0).await.unwrap();
+    arm.get_joint(0x0020).unwrap().set_target(-20.0, 45.0).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(bus.true_position(0x0010).unwrap().abs() < 0.5);
+
+    arm.start_sync(Duration::from_millis(10));
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    arm.stop_sync();
+    for (id, angle) in ids.into_iter().zip([20.0, -20.0]) {
+        assert!((bus.true_position(id).unwrap() - angle).abs() < 0.5, "joint {:#06x}", id);
+    }
+    let cycle = comm.send_sync().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let samples = comm.sync_samples(cycle);
+    assert_eq!(samples.keys().copied().collect::<Vec<_>>(), ids);
+    assert!((samples[&0x0020].position().0 + 20.0).abs() < 0.5);
+}
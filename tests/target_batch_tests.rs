@@ -0,0 +1,115 @@
+//! Tests for `SetTargetBatch` and batched group targets
+
+#[test]
+fn test_target_batch_holds_what_fits() {
+    use irpc::{BatchTarget, Payload, SetTargetPayload, TargetBatch, TARGET_BATCH_MAX_JOINTS};
+
+    let entry = |joint_id, target_angle| BatchTarget {
+        joint_id,
+        target: SetTargetPayload { target_angle, velocity_limit: 30.0 },
+    };
+    let targets: Vec<BatchTarget> = (0..=TARGET_BATCH_MAX_JOINTS as u16).map(|n| entry(0x0010 + n, n as f32)).collect();
+    assert!(TargetBatch::new(&targets).is_none());
+
+    let batch = TargetBatch::new(&[entry(0x0010, 5.0), entry(0x0020, -5.0), entry(0x0010, 7.0)]).unwrap();
+    assert_eq!(batch.targets().len(), 3);
+    assert_eq!(batch.target_for(0x0010).unwrap().target_angle, 5.0);
+    assert!(batch.target_for(0x0030).is_none());
+    let full = TargetBatch::new(&targets[..TARGET_BATCH_MAX_JOINTS]).unwrap();
+    assert!(!Payload::SetTargetBatch(full).requires_fragmentation());
+    assert_eq!(
+        Payload::SetTargetBatch(batch).to_string(),
+        "SetTargetBatch 0x0010:5.000@30.000 0x0020:-5.000@30.000 0x0010:7.000@30.000"
+    );
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_listed_joints_take_their_targets() {
+    use irpc::{
+        BatchTarget, Header, Joint, JointLimits, LifecycleState, Message, MultiTurnPosition, Payload,
+        SetTargetPayload, TargetBatch, ERROR_LIMIT_VIOLATION,
+    };
+
+    let mut joints: Vec<Joint> = [0x0010, 0x0020, 0x0030].into_iter().map(Joint::new).collect();
+    for joint in &mut joints {
+        joint.update_encoder(MultiTurnPosition::from_degrees(0.0));
+        let limits = JointLimits { min_position: -90.0, max_position: 90.0, max_velocity: 60.0 };
+        for (msg_id, payload) in [(1, Payload::Configure), (2, Payload::SetLimits(limits)), (3, Payload::Activate)] {
+            let header = Header { source_id: 0x0001, target_id: joint.id(), msg_id };
+            joint.handle_message(&Message { header, payload });
+        }
+        assert_eq!(joint.state(), LifecycleState::Active);
+    }
+    let entry = |joint_id, target_angle| BatchTarget {
+        joint_id,
+        target: SetTargetPayload { target_angle, velocity_limit: 30.0 },
+    };
+    let send = |joints: &mut Vec<Joint>, master, msg_id, targets: &[BatchTarget]| -> Vec<Option<Message>> {
+        let header = Header { source_id: 0x0001, target_id: master, msg_id };
+        let message = Message { header, payload: Payload::SetTargetBatch(TargetBatch::new(targets).unwrap()) };
+        joints.iter_mut().map(|joint| joint.handle_message(&message)).collect()
+    };
+
+    // Only the master answers; a joint left out keeps its target
+    let replies = send(&mut joints, 0x0010, 10, &[entry(0x0010, 10.0), entry(0x0020, -20.0)]);
+    assert!(matches!(&replies[0], Some(Message { payload: Payload::Ack(10), .. })));
+    assert!(replies[1].is_none() && replies[2].is_none());
+    assert_eq!(joints[0].target().unwrap().target_angle, 10.0);
+    assert_eq!(joints[1].target().unwrap().target_angle, -20.0);
+    assert!(joints[2].target().is_none());
+
+    // Refusals come from whichever joint refuses
+    let replies = send(&mut joints, 0x0010, 11, &[entry(0x0010, 15.0), entry(0x0030, 120.0)]);
+    assert!(matches!(&replies[0], Some(Message { payload: Payload::Ack(11), .. })));
+    let Some(refusal) = &replies[2] else { panic!() };
+    assert_eq!((refusal.header.source_id, refusal.header.target_id), (0x0030, 0x0001));
+    assert!(matches!(refusal.payload, Payload::Nack { id: 11, error: ERROR_LIMIT_VIOLATION }));
+
+    // A master with no target of its own still acknowledges
+    let replies = send(&mut joints, 0x0010, 12, &[entry(0x0020, 25.0)]);
+    assert!(matches!(&replies[0], Some(Message { payload: Payload::Ack(12), .. })));
+    assert_eq!(joints[0].target().unwrap().target_angle, 15.0);
+    assert_eq!(joints[1].target().unwrap().target_angle, 25.0);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_group_sends_batched_targets_over_sim_bus() {
+    use irpc::bus::sim::SimBus;
+    use irpc::group::GroupError;
+    use irpc::{ArmOrchestrator, CommunicationManager};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let ids = [0x0010, 0x0020, 0x0030, 0x0040, 0x0050];
+    let bus = Arc::new(SimBus::with_joints(ids));
+    let mut arm = ArmOrchestrator::with_comm_manager(CommunicationManager::with_adapter(bus.clone()));
+    for id in ids {
+        arm.add_joint(id);
+    }
+    // Led by 0x0050 and 0x0010; the bus hands frames to joints in ID order
+    let group = arm.create_group("arm", ids.into_iter().rev()).unwrap().clone();
+    group.configure().await.unwrap();
+    group.activate().await.unwrap();
+
+    // Five joints take two frames
+    let before = bus.transmitted();
+    group.set_targets_batched(&[30.0, -20.0, 20.0, -10.0, 10.0], 45.0).await.unwrap();
+    assert_eq!(bus.transmitted() - before, 2);
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    for (id, angle) in ids.into_iter().zip([10.0, -10.0, 20.0, -20.0, 30.0]) {
+        assert!((bus.true_position(id).unwrap() - angle).abs() < 0.5, "joint {:#06x}", id);
+    }
+
+    // A refusal that comes in before the master's Ack names the joint
+    bus.with_joint(0x0030, |joint| joint.report_fault());
+    assert!(matches!(
+        group.set_targets_batched(&[0.0; 5], 45.0).await,
+        Err(GroupError::Joint { joint_id: 0x0030, .. })
+    ));
+    assert!(matches!(
+        group.set_targets_batched(&[0.0; 4], 45.0).await,
+        Err(GroupError::TargetCount { expected: 5, actual: 4 })
+    ));
+}
@@ -0,0 +1,129 @@
+//! Tests for the joint's thermal derating and overtemperature protection
+
+#[cfg(feature = "joint_api")]
+fn limits() -> irpc::ThermalLimits {
+    irpc::ThermalLimits { warning_c: 70.0, critical_c: 90.0, min_scale: 0.2 }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_derates_and_faults_when_hot() {
+    use irpc::{
+        Header, Joint, LifecycleState, Message, Payload, ThermalLimits, Warnings, ERROR_LIMIT_VIOLATION,
+        ERROR_OVERTEMPERATURE,
+    };
+
+    let mut joint = Joint::new(0x0010);
+    let send = |joint: &mut Joint, payload| {
+        let header = Header { source_id: 0x0001, target_id: 0x0010, msg_id: 1 };
+        joint.handle_message(&Message { header, payload }).unwrap().payload
+    };
+    // Warning below critical, scale within 0..=1
+    for invalid in [
+        ThermalLimits { warning_c: 90.0, ..limits() },
+        ThermalLimits { min_scale: 1.5, ..limits() },
+        ThermalLimits { critical_c: f32::NAN, ..limits() },
+    ] {
+        let reply = send(&mut joint, Payload::ConfigureThermal(invalid));
+        assert!(matches!(reply, Payload::Nack { error: ERROR_LIMIT_VIOLATION, .. }));
+    }
+    assert!(joint.thermal_limits().is_none());
+    // No limits, no derating
+    joint.update_temperature(120.0);
+    assert_eq!((joint.derating(), joint.warnings()), (1.0, Warnings::NONE));
+    assert!(matches!(send(&mut joint, Payload::ConfigureThermal(limits())), Payload::Ack(1)));
+    assert_eq!(joint.thermal_limits(), Some(limits()));
+    assert!(matches!(send(&mut joint, Payload::Configure), Payload::Ack(_)));
+    assert!(matches!(send(&mut joint, Payload::Activate), Payload::Nack { error: ERROR_OVERTEMPERATURE, .. }));
+
+    joint.update_temperature(60.0);
+    assert_eq!((joint.derating(), joint.warnings()), (1.0, Warnings::NONE));
+    assert!(matches!(send(&mut joint, Payload::Activate), Payload::Ack(_)));
+    // Halfway between warning and critical
+    joint.update_temperature(80.0);
+    assert!((joint.derating() - 0.6).abs() < 1e-6, "derating {}", joint.derating());
+    assert_eq!(joint.warnings(), Warnings::OVERTEMPERATURE);
+    assert_eq!(joint.state(), LifecycleState::Active);
+
+    joint.update_temperature(90.0);
+    assert_eq!(joint.state(), LifecycleState::Error);
+    assert_eq!(joint.derating(), 0.0);
+    let status = send(&mut joint, Payload::RequestStatus);
+    assert!(matches!(status, Payload::JointStatus { error_code: ERROR_OVERTEMPERATURE, .. }));
+    assert!(matches!(send(&mut joint, Payload::Reset), Payload::Ack(_)));
+    assert!(matches!(send(&mut joint, Payload::Configure), Payload::Ack(_)));
+    assert!(matches!(send(&mut joint, Payload::Activate), Payload::Nack { error: ERROR_OVERTEMPERATURE, .. }));
+
+    // Cooled down
+    joint.update_temperature(75.0);
+    assert!(matches!(send(&mut joint, Payload::Activate), Payload::Ack(_)));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_derating_scales_the_controller_limits() {
+    use irpc::control::CascadedController;
+    use irpc::{ControlGains, PidGains, SetTargetPayload, SetTargetPayloadV2};
+
+    let gains = ControlGains {
+        position: PidGains { kp: 100.0, ki: 0.0, kd: 0.0, integral_limit: 0.0 },
+        velocity: PidGains { kp: 1.0, ki: 0.0, kd: 0.0, integral_limit: 0.0 },
+        max_current: 2.0,
+    };
+    let target = SetTargetPayload { target_angle: 90.0, velocity_limit: 50.0 };
+    let target = SetTargetPayloadV2 { max_acceleration: 0.0, ..target.into() };
+    let mut controller = CascadedController::new(gains);
+    assert_eq!(controller.update(Some(&target), 0.0, 0.0, 0.001), 2.0);
+    assert_eq!(controller.velocity_setpoint(), 50.0);
+
+    controller.set_derating(0.25);
+    assert_eq!(controller.update(Some(&target), 0.0, 0.0, 0.001), 0.5);
+    assert_eq!(controller.velocity_setpoint(), 12.5);
+    assert_eq!(controller.update_velocity(-100.0, 0.0, 0.001), -0.5);
+    assert_eq!(controller.velocity_setpoint(), -25.0);
+    assert_eq!(controller.update_current(1.0), 0.5);
+
+    // Kept across resets, and clamped
+    controller.reset();
+    assert_eq!(controller.derating(), 0.25);
+    controller.set_derating(-1.0);
+    assert_eq!(controller.update_current(1.0), 0.0);
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_sim_joint_overheats() {
+    use irpc::bus::sim::{SensorModel, SimBus};
+    use irpc::{
+        CommunicationManager, JointProxy, LifecycleState, ProtocolError, TelemetryStream, ThermalLimits,
+    };
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    let warm = SensorModel { ambient_c: 80.0, ..Default::default() };
+    let bus = Arc::new(
+        SimBus::with_joints([0x0010])
+            .with_telemetry(Duration::from_millis(10))
+            .with_sensor_model(warm),
+    );
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    comm.on(0x0010, {
+        let warnings = warnings.clone();
+        move |_, stream: TelemetryStream| warnings.lock().unwrap().push(stream.warnings)
+    });
+
+    let joint = JointProxy::new(0x0010, comm.clone());
+    let refused = joint.configure_thermal(ThermalLimits { min_scale: -0.5, ..limits() }).await;
+    assert!(matches!(refused, Err(ProtocolError::IoError(_))));
+    joint.configure_thermal(limits()).await.unwrap();
+    joint.configure().await.unwrap();
+    joint.activate().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(warnings.lock().unwrap().iter().any(|w| w.is_overtemperature()));
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Active));
+
+    bus.set_sensor_model(0x0010, SensorModel { ambient_c: 95.0, ..warm });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(bus.joint_state(0x0010), Some(LifecycleState::Error));
+}
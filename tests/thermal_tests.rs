@@ -0,0 +1,133 @@
+//! Tests for winding thermal model identification
+
+use irpc::thermal::{ThermalIdentifier, ThermalModel};
+
+const AMBIENT: f32 = 25.0;
+const PHASE_RESISTANCE: f32 = 0.5;
+
+/// Run current pulses through an exact first-order plant, sampling at 10 Hz
+fn pulse_test(plant: ThermalModel, identifier: &mut ThermalIdentifier) {
+    let dt = 0.1f32;
+    let mut temperature = AMBIENT;
+    let mut time = 0.0f32;
+    identifier.add_sample(time, 0.0, temperature);
+
+    for cycle in 0..2 {
+        // 60 s on at a different current each cycle, then 60 s off
+        for (current, seconds) in [(6.0 + 2.0 * cycle as f32, 60.0), (0.0, 60.0)] {
+            let power = current * current * PHASE_RESISTANCE;
+            let target = plant.steady_state(AMBIENT, power);
+            for _ in 0..(seconds / dt) as usize {
+                temperature = target + (temperature - target) * (-dt / plant.time_constant).exp();
+                time += dt;
+                identifier.add_sample(time, current, temperature);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_thermal_identification_recovers_plant() {
+    let plant = ThermalModel { thermal_resistance: 2.0, time_constant: 30.0 };
+    let mut identifier = ThermalIdentifier::new(AMBIENT, PHASE_RESISTANCE);
+    pulse_test(plant, &mut identifier);
+
+    let model = identifier.estimate().expect("pulse test should identify the model");
+    assert!((model.thermal_resistance - 2.0).abs() < 0.05, "{:?}", model);
+    assert!((model.time_constant - 30.0).abs() < 1.0, "{:?}", model);
+}
+
+#[test]
+fn test_thermal_identification_rejects_poor_data() {
+    // Too few samples
+    let mut identifier = ThermalIdentifier::new(AMBIENT, PHASE_RESISTANCE);
+    for i in 0..5 {
+        identifier.add_sample(i as f32, 4.0, AMBIENT + i as f32);
+    }
+    assert_eq!(identifier.equations(), 4);
+    assert!(identifier.estimate().is_none());
+
+    // No current: losses never excite the model
+    let mut identifier = ThermalIdentifier::new(AMBIENT, PHASE_RESISTANCE);
+    for i in 0..50 {
+        identifier.add_sample(i as f32, 0.0, AMBIENT);
+    }
+    assert!(identifier.estimate().is_none());
+
+    // Time going backwards is ignored
+    let mut identifier = ThermalIdentifier::new(AMBIENT, PHASE_RESISTANCE);
+    identifier.add_sample(1.0, 4.0, AMBIENT);
+    identifier.add_sample(1.0, 4.0, AMBIENT);
+    identifier.add_sample(0.5, 4.0, AMBIENT);
+    assert_eq!(identifier.equations(), 0);
+}
+
+#[test]
+fn test_thermal_model_predictions() {
+    let model = ThermalModel { thermal_resistance: 2.0, time_constant: 30.0 };
+    assert_eq!(model.steady_state(25.0, 10.0), 45.0);
+    assert_eq!(model.max_continuous_power(25.0, 105.0), 40.0);
+    assert_eq!(model.max_continuous_power(110.0, 105.0), 0.0);
+
+    // Stepping converges on the steady state without overshoot
+    let mut temperature = 25.0;
+    for _ in 0..10_000 {
+        temperature = model.step(temperature, 25.0, 10.0, 0.1);
+        assert!(temperature <= 45.0);
+    }
+    assert!((temperature - 45.0).abs() < 0.01);
+    assert_eq!(model.step(25.0, 25.0, 10.0, 1_000.0), 45.0);
+
+    assert!(!ThermalModel { thermal_resistance: 0.0, time_constant: 30.0 }.is_valid());
+    assert!(!ThermalModel { thermal_resistance: 2.0, time_constant: f32::NAN }.is_valid());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_applies_thermal_calibration() {
+    use irpc::{CalibrationConfidence, CalibrationRequest, CalibrationResult, Joint, MotorParameters};
+
+    let request = CalibrationRequest {
+        phases: CalibrationRequest::default().phases | CalibrationRequest::PHASE_THERMAL,
+        ..Default::default()
+    };
+    assert!(request.runs(CalibrationRequest::PHASE_THERMAL));
+    assert!(!CalibrationRequest::default().runs(CalibrationRequest::PHASE_THERMAL));
+
+    let result = |thermal_resistance, success| CalibrationResult {
+        success,
+        parameters: MotorParameters {
+            inertia_J: 0.001,
+            torque_constant_kt: 0.15,
+            damping_b: 0.0005,
+            friction_coulomb: 0.02,
+            friction_stribeck: 0.01,
+            friction_vstribeck: 0.1,
+            friction_viscous: 0.001,
+            thermal_resistance,
+            thermal_time_constant: 30.0,
+        },
+        confidence: CalibrationConfidence {
+            overall: 0.9,
+            inertia: 0.9,
+            friction: 0.9,
+            torque_constant: 0.9,
+            validation_rms: 0.01,
+        },
+        total_time: 400.0,
+        error_code: 0,
+    };
+
+    let mut joint = Joint::new(0x0010);
+    assert!(joint.thermal_model().is_none());
+
+    joint.apply_calibration(&result(2.0, false));
+    assert!(joint.thermal_model().is_none());
+
+    joint.apply_calibration(&result(2.0, true));
+    assert_eq!(joint.thermal_model().unwrap().thermal_resistance, 2.0);
+
+    // A run without the thermal phase keeps the earlier model
+    joint.apply_calibration(&result(0.0, true));
+    assert_eq!(joint.thermal_model().unwrap().thermal_resistance, 2.0);
+}
@@ -0,0 +1,314 @@
+//! Tests for trajectory file loading and streaming
+
+#[cfg(feature = "arm_api")]
+use irpc::trajectory::{Trajectory, TrajectoryError, Waypoint};
+#[cfg(feature = "arm_api")]
+use irpc::JointLimits;
+#[cfg(feature = "arm_api")]
+use std::collections::HashMap;
+
+#[cfg(feature = "arm_api")]
+const PICK_CSV: &str = "\
+# pick approach
+time, 0x0010, 0x0010.velocity, 17,   17.velocity
+
+0.0,  0.0,    0.0,             10.0, 0.0
+0.5,  15.0,   30.0,            12.5, 5.0
+1.0,  30.0,   0.0,             15.0, 0.0
+";
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_trajectory_csv_parsing() {
+    let trajectory = Trajectory::from_csv(PICK_CSV).unwrap();
+    assert_eq!(trajectory.joints(), [0x0010, 0x0011]);
+    assert_eq!(trajectory.waypoints().len(), 3);
+    assert_eq!(trajectory.duration(), 1.0);
+    assert_eq!(trajectory.waypoints()[1].angles, [15.0, 12.5]);
+    assert_eq!(trajectory.waypoints()[1].velocities, Some(vec![30.0, 5.0]));
+
+    // Without velocity columns, segment velocities are derived
+    let derived = Trajectory::from_csv("time,16\n0,0\n2,10\n3,40\n").unwrap();
+    assert_eq!(derived.waypoints()[0].velocities, None);
+    assert_eq!(derived.velocity(0, 0), 5.0);
+    assert_eq!(derived.velocity(1, 0), 5.0);
+    assert_eq!(derived.velocity(2, 0), 30.0);
+
+    let err = |text: &str| Trajectory::from_csv(text).unwrap_err();
+    assert!(matches!(err("time,16\n0,0\n1,abc\n"), TrajectoryError::Parse { line: 3, .. }));
+    assert!(matches!(err("time,16\n0,0,1\n"), TrajectoryError::Parse { line: 2, .. }));
+    assert!(matches!(err("16,17\n0,0\n"), TrajectoryError::Parse { line: 1, .. }));
+    assert!(matches!(err("time,16,16\n0,0,0\n"), TrajectoryError::Parse { line: 1, .. }));
+    assert!(matches!(err("time,16,17.velocity\n0,0,0\n"), TrajectoryError::Parse { line: 1, .. }));
+    assert!(matches!(err("time,16,17,16.velocity\n0,0,0,0\n"), TrajectoryError::Parse { line: 1, .. }));
+    assert!(matches!(err("time,16\n0,0\n0,1\n"), TrajectoryError::TimeNotIncreasing { index: 1 }));
+    assert!(matches!(err("time,16\n"), TrajectoryError::Empty));
+    assert!(matches!(err("# nothing\n"), TrajectoryError::Empty));
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_trajectory_validation_against_limits() {
+    let trajectory = Trajectory::from_csv(PICK_CSV).unwrap();
+    let limits = |max_position, max_velocity| JointLimits {
+        min_position: -90.0,
+        max_position,
+        max_velocity,
+    };
+
+    // Joints without limits are not checked
+    assert!(trajectory.validate(&HashMap::new()).is_ok());
+
+    let ok = HashMap::from([(0x0010, limits(90.0, 50.0)), (0x0011, limits(90.0, 50.0))]);
+    assert!(trajectory.validate(&ok).is_ok());
+
+    let narrow = HashMap::from([(0x0011, limits(14.0, 50.0))]);
+    assert!(matches!(
+        trajectory.validate(&narrow),
+        Err(TrajectoryError::PositionLimit { joint: 0x0011, index: 2, .. })
+    ));
+
+    let slow = HashMap::from([(0x0010, limits(90.0, 20.0))]);
+    assert!(matches!(
+        trajectory.validate(&slow),
+        Err(TrajectoryError::VelocityLimit { joint: 0x0010, index: 1, .. })
+    ));
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_trajectory_resampling() {
+    let trajectory = Trajectory::from_csv("time,16\n0,0\n0.25,10\n1.0,40\n").unwrap();
+    let samples = trajectory.resample(10).unwrap();
+    let points = samples.waypoints();
+
+    assert_eq!(points.len(), 11);
+    assert_eq!(points[0].time, 0.0);
+    assert_eq!(points[10].time, 1.0);
+    assert_eq!(points[10].angles, [40.0]);
+    for (point, expected) in points.iter().zip([0.0, 4.0, 8.0, 12.0, 16.0, 20.0]) {
+        assert!((point.angles[0] - expected).abs() < 1e-4, "{:?}", point);
+    }
+    // Derived velocities follow the segment each sample falls in
+    assert_eq!(points[1].velocities, Some(vec![40.0]));
+    assert_eq!(points[5].velocities, Some(vec![40.0]));
+
+    // The last waypoint is kept even if it falls between samples
+    let uneven = Trajectory::from_csv("time,16\n0,0\n0.25,10\n").unwrap().resample(10).unwrap();
+    let times: Vec<f64> = uneven.waypoints().iter().map(|w| w.time).collect();
+    assert_eq!(times.len(), 4);
+    assert_eq!(times[3], 0.25);
+
+    // Explicit velocities are interpolated
+    let explicit = Trajectory::new(
+        vec![16],
+        vec![
+            Waypoint { time: 0.0, angles: vec![0.0], velocities: Some(vec![0.0]) },
+            Waypoint { time: 1.0, angles: vec![10.0], velocities: Some(vec![20.0]) },
+        ],
+    )
+    .unwrap();
+    let samples = explicit.resample(2).unwrap();
+    assert_eq!(samples.waypoints()[1].velocities, Some(vec![10.0]));
+
+    assert!(matches!(trajectory.resample(0), Err(TrajectoryError::InvalidRate(0))));
+}
+
+#[cfg(feature = "arm_api")]
+#[test]
+fn test_trajectory_load_by_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let csv = dir.path().join("pick.CSV");
+    std::fs::write(&csv, PICK_CSV).unwrap();
+    assert_eq!(Trajectory::load(&csv).unwrap(), Trajectory::from_csv(PICK_CSV).unwrap());
+
+    let other = dir.path().join("pick.txt");
+    std::fs::write(&other, PICK_CSV).unwrap();
+    assert!(matches!(Trajectory::load(&other), Err(TrajectoryError::UnsupportedFormat(_))));
+    assert!(matches!(Trajectory::load(dir.path().join("missing.csv")), Err(TrajectoryError::Io(_))));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_trajectory_json_parsing() {
+    let json = r#"{
+        "joints": [16, 17],
+        "waypoints": [
+            { "time": 0.0, "angles": [0.0, 10.0] },
+            { "time": 1.0, "angles": [30.0, 15.0], "velocities": [0.0, 0.0] }
+        ]
+    }"#;
+    let trajectory = Trajectory::from_json(json).unwrap();
+    assert_eq!(trajectory.joints(), [16, 17]);
+    assert_eq!(trajectory.waypoints()[1].velocities, Some(vec![0.0, 0.0]));
+
+    // Shape is checked while deserializing
+    let bad = r#"{ "joints": [16, 17], "waypoints": [ { "time": 0.0, "angles": [0.0] } ] }"#;
+    assert!(matches!(Trajectory::from_json(bad), Err(TrajectoryError::Parse { .. })));
+}
+
+#[cfg(feature = "arm_api")]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_stream_trajectory() {
+    use irpc::{ArmOrchestrator, CommunicationManager, Header, Message, Payload};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let comm = Arc::new(CommunicationManager::deterministic(9));
+    let mut arm = ArmOrchestrator::with_comm_manager(comm.clone());
+    arm.add_joint(0x0010);
+    arm.add_joint(0x0011);
+
+    // Joint that acks everything, recording setpoints and when they arrived
+    let joint = tokio::spawn({
+        let comm = comm.clone();
+        async move {
+            let start = tokio::time::Instant::now();
+            let mut targets = Vec::new();
+            loop {
+                let Some(request) = comm.poll_outbound() else {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                    continue;
+                };
+                let id = request.header.msg_id;
+                if let Payload::SetTarget(t) = request.payload {
+                    targets.push((request.header.target_id, t.target_angle, t.velocity_limit, start.elapsed()));
+                }
+                comm.process_incoming(Message {
+                    header: Header { source_id: request.header.target_id, target_id: 0x0001, msg_id: id },
+                    payload: Payload::Ack(id),
+                })
+                .await;
+                if targets.len() == 6 {
+                    return targets;
+                }
+            }
+        }
+    });
+
+    let trajectory = Trajectory::from_csv("time,16,17\n0,0,10\n0.2,20,10\n").unwrap();
+    arm.stream_trajectory(&trajectory, 10).await.unwrap();
+
+    let targets = joint.await.unwrap();
+    let joint_16: Vec<(f32, f32)> = targets.iter().filter(|t| t.0 == 0x0010).map(|t| (t.1, t.2)).collect();
+    assert_eq!(joint_16, [(0.0, 100.0), (10.0, 100.0), (20.0, 100.0)]);
+    // Samples go out one period apart
+    assert!(targets[2].3 >= Duration::from_millis(100));
+    assert!(targets[4].3 >= Duration::from_millis(200));
+    assert!(targets[4].3 < Duration::from_millis(250));
+
+    // Joints outside the arm and limit violations are refused before sending
+    let unknown = Trajectory::from_csv("time,18\n0,0\n").unwrap();
+    assert!(matches!(
+        arm.stream_trajectory(&unknown, 10).await,
+        Err(TrajectoryError::UnknownJoint(0x0012))
+    ));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread")]
+async fn test_stream_trajectory_on_virtual_clock() {
+    use irpc::bus::sim::SimBus;
+    use irpc::clock::{Clock, VirtualClock};
+    use irpc::{ArmOrchestrator, CommunicationManager};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    // Both sides of the bus share one clock that only the test moves
+    let clock = Arc::new(VirtualClock::new());
+    let bus = Arc::new(SimBus::with_joints([0x0010, 0x0011]).with_clock(clock.clone()));
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    comm.set_clock(clock.clone());
+
+    let task = tokio::spawn(async move {
+        let mut arm = ArmOrchestrator::with_comm_manager(comm);
+        arm.add_joint(0x0010);
+        arm.add_joint(0x0011);
+        arm.configure_all().await.into_result().unwrap();
+        arm.activate_all().await.into_result().unwrap();
+
+        // Ten seconds of motion, 1001 samples at 100 Hz
+        let trajectory = Trajectory::from_csv("time,16,17\n0,0,10\n10,90,-10\n").unwrap();
+        // Kept alive, so no reset goes out on drop
+        arm.stream_trajectory(&trajectory, 100).await.map(|()| arm)
+    });
+
+    // Let every task reach its next sleep, then jump to the earliest deadline
+    while !task.is_finished() {
+        for _ in 0..8 {
+            tokio::task::yield_now().await;
+        }
+        clock.advance_to_next();
+    }
+    let _arm = task.await.unwrap().unwrap();
+
+    // configure and activate for each joint, then one SetTarget per joint per sample
+    assert_eq!(bus.transmitted(), 4 + 2 * 1001);
+    assert!(clock.now() >= Duration::from_secs(10));
+    assert!(clock.now() < Duration::from_millis(10_010));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test]
+async fn test_stream_trajectory_holds_while_paused() {
+    use irpc::bus::sim::SimBus;
+    use irpc::clock::{Clock, VirtualClock};
+    use irpc::{ArmOrchestrator, CommunicationManager, Header, InterlockAction, InterlockConfig, InterlockStatePayload, Message, Payload};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let clock = Arc::new(VirtualClock::new());
+    let bus = Arc::new(SimBus::with_joints([0x0010]).with_clock(clock.clone()));
+    let comm = CommunicationManager::with_adapter(bus.clone());
+    comm.set_clock(clock.clone());
+    comm.configure_interlock(InterlockConfig {
+        name: "cell door".into(),
+        source: 0x0050,
+        channel: 0,
+        action: InterlockAction::Pause,
+    });
+    let report = |tripped| Message {
+        header: Header { source_id: 0x0050, target_id: 0x0001, msg_id: 0 },
+        payload: Payload::InterlockState(InterlockStatePayload { wired: 1, tripped }),
+    };
+    comm.process_incoming(report(0)).await;
+
+    let task = tokio::spawn({
+        let comm = comm.clone();
+        async move {
+            let mut arm = ArmOrchestrator::with_comm_manager(comm);
+            arm.add_joint(0x0010);
+            arm.configure_all().await.into_result().unwrap();
+            arm.activate_all().await.into_result().unwrap();
+            let trajectory = Trajectory::from_csv("time,16\n0,0\n1,90\n").unwrap();
+            arm.stream_trajectory(&trajectory, 100).await.map(|()| arm)
+        }
+    });
+    let step = async || {
+        for _ in 0..8 {
+            tokio::task::yield_now().await;
+        }
+        clock.advance_to_next();
+    };
+
+    // Open the door half way through
+    while clock.now() < Duration::from_millis(500) {
+        step().await;
+    }
+    comm.process_incoming(report(1)).await;
+    step().await;
+    let sent = bus.transmitted();
+    clock.advance(Duration::from_secs(5));
+    step().await;
+    assert_eq!(bus.transmitted(), sent);
+
+    comm.process_incoming(report(0)).await;
+    while !task.is_finished() {
+        step().await;
+    }
+    let _arm = task.await.unwrap().unwrap();
+
+    // Every sample was still sent, and the pause added to the run time
+    assert_eq!(bus.transmitted(), 2 + 101);
+    assert!(clock.now() >= Duration::from_secs(6));
+}
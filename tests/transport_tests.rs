@@ -0,0 +1,1144 @@
+//! Tests for the joint-side transport layer
+
+#[cfg(feature = "joint_api")]
+use irpc::{EmbeddedTransport, Header, Message, Payload, TransportErrorKind, TransportLayer};
+
+/// Minimal transport that replays one queued frame and records the last sent one
+#[cfg(feature = "joint_api")]
+struct ScriptedTransport {
+    rx: [u8; 128],
+    rx_len: Option<usize>,
+    sent: [u8; 128],
+    sent_len: usize,
+}
+
+#[cfg(feature = "joint_api")]
+impl ScriptedTransport {
+    fn new() -> Self {
+        Self {
+            rx: [0u8; 128],
+            rx_len: None,
+            sent: [0u8; 128],
+            sent_len: 0,
+        }
+    }
+
+    fn queue(&mut self, data: &[u8]) {
+        self.rx[..data.len()].copy_from_slice(data);
+        self.rx_len = Some(data.len());
+    }
+
+    fn sent(&self) -> &[u8] {
+        &self.sent[..self.sent_len]
+    }
+}
+
+#[cfg(feature = "joint_api")]
+impl EmbeddedTransport for ScriptedTransport {
+    type Error = ();
+
+    fn send_blocking(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.sent[..data.len()].copy_from_slice(data);
+        self.sent_len = data.len();
+        Ok(())
+    }
+
+    fn receive_blocking(&mut self) -> Result<Option<&[u8]>, Self::Error> {
+        match self.rx_len.take() {
+            Some(len) => Ok(Some(&self.rx[..len])),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Async side of the same mock; receiving with nothing queued is an error
+/// rather than a hang
+#[cfg(feature = "joint_api")]
+impl irpc::AsyncEmbeddedTransport for ScriptedTransport {
+    type Error = ();
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.send_blocking(data)
+    }
+
+    async fn receive(&mut self) -> Result<&[u8], Self::Error> {
+        self.receive_blocking()?.ok_or(())
+    }
+}
+
+#[cfg(feature = "joint_api")]
+fn encode(msg: &Message) -> ([u8; Message::max_size()], usize) {
+    let mut buf = [0u8; Message::max_size()];
+    let len = msg.serialize_into(&mut buf).unwrap();
+    (buf, len)
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_transport_stats_counters() {
+    let mut layer = TransportLayer::new(ScriptedTransport::new());
+
+    let msg = Message {
+        header: Header {
+            source_id: 0x0010,
+            target_id: 0x0001,
+            msg_id: 1,
+        },
+        payload: Payload::Ack(1),
+    };
+    layer.send_message(&msg).unwrap();
+    assert_eq!(layer.stats().tx_frames, 1);
+
+    // Valid frame
+    let (buf, len) = encode(&msg);
+    layer.transport_mut().queue(&buf[..len]);
+    assert!(layer.receive_message().unwrap().is_some());
+
+    // Garbage frame
+    layer.transport_mut().queue(&[0xFF, 0xFF, 0xFF]);
+    assert!(layer.receive_message().is_err());
+
+    // Oversized frame
+    layer.transport_mut().queue(&[0u8; Message::max_size() + 1]);
+    assert!(layer.receive_message().is_err());
+
+    let stats = *layer.stats();
+    assert_eq!(stats.rx_frames, 3);
+    assert_eq!(stats.deserialization_failures, 2);
+    assert_eq!(stats.last_error, TransportErrorKind::FrameTooLarge);
+    assert_eq!(stats.total_errors(), 2);
+
+    // Driver-level events can be recorded from outside the layer
+    layer.stats_mut().record_error(TransportErrorKind::BusOff);
+    assert_eq!(layer.stats().bus_off_events, 1);
+
+    layer.reset_stats();
+    assert_eq!(layer.stats().total_errors(), 0);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_answers_bus_stats_request() {
+    use irpc::Joint;
+
+    let mut joint = Joint::new(0x0010);
+    let mut layer = TransportLayer::new(ScriptedTransport::new());
+
+    let request = Message {
+        header: Header {
+            source_id: 0x0001,
+            target_id: 0x0010,
+            msg_id: 42,
+        },
+        payload: Payload::RequestBusStats,
+    };
+    let (buf, len) = encode(&request);
+    layer.transport_mut().queue(&buf[..len]);
+
+    assert!(joint.process_transport(&mut layer).unwrap());
+
+    let response = Message::deserialize(layer.transport().sent()).unwrap();
+    assert_eq!(response.header.msg_id, 42);
+    match response.payload {
+        Payload::BusStats(stats) => {
+            assert_eq!(stats.rx_frames, 1);
+            // The link carries any message unless the transport says otherwise
+            assert_eq!(stats.mtu as usize, Message::max_size());
+        }
+        _ => panic!("Expected BusStats response"),
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[tokio::test]
+async fn test_joint_process_transport_async() {
+    use irpc::{AsyncTransportLayer, Joint, LifecycleState};
+
+    let mut joint = Joint::new(0x0010);
+    let mut layer = AsyncTransportLayer::new(ScriptedTransport::new());
+
+    // State machine commands are answered like on the blocking path
+    let configure = Message {
+        header: Header {
+            source_id: 0x0001,
+            target_id: 0x0010,
+            msg_id: 7,
+        },
+        payload: Payload::Configure,
+    };
+    let (buf, len) = encode(&configure);
+    layer.transport_mut().queue(&buf[..len]);
+
+    assert!(joint.process_transport_async(&mut layer).await.unwrap());
+    assert_eq!(joint.state(), LifecycleState::Inactive);
+    let response = Message::deserialize(layer.transport().sent()).unwrap();
+    assert!(matches!(response.payload, Payload::Ack(7)));
+
+    // Bus statistics are intercepted before the state machine
+    let request = Message {
+        header: Header {
+            source_id: 0x0001,
+            target_id: 0x0010,
+            msg_id: 8,
+        },
+        payload: Payload::RequestBusStats,
+    };
+    let (buf, len) = encode(&request);
+    layer.transport_mut().queue(&buf[..len]);
+
+    assert!(joint.process_transport_async(&mut layer).await.unwrap());
+    let response = Message::deserialize(layer.transport().sent()).unwrap();
+    match response.payload {
+        Payload::BusStats(stats) => {
+            assert_eq!(stats.rx_frames, 2);
+            assert_eq!(stats.tx_frames, 1);
+        }
+        _ => panic!("Expected BusStats response"),
+    }
+
+    // Driver errors surface and are counted
+    assert!(joint.process_transport_async(&mut layer).await.is_err());
+    assert_eq!(layer.stats().transport_errors, 1);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_can_id_roundtrip() {
+    use irpc::transport::canfd::{decode_frame, encode_frame};
+    use irpc::transport::CanId;
+
+    let msg = Message {
+        header: Header {
+            source_id: 0x0001,
+            target_id: 0x0010,
+            msg_id: 300,
+        },
+        payload: Payload::Reset,
+    };
+
+    let mut buf = [0u8; 64];
+    let (raw_id, len) = encode_frame(&msg, &mut buf).unwrap();
+    assert!(raw_id < (1 << 29), "must fit an extended identifier");
+
+    let id = CanId::from_raw(raw_id);
+    assert_eq!(id.priority, CanId::PRIORITY_EMERGENCY);
+    assert_eq!(id.target_id, 0x0010);
+    assert_eq!(id.source_id, 0x0001);
+
+    let decoded = decode_frame(raw_id, &buf[..len]).unwrap();
+    assert_eq!(decoded.header.source_id, 0x0001);
+    assert_eq!(decoded.header.target_id, 0x0010);
+    assert_eq!(decoded.header.msg_id, 300);
+    assert!(matches!(decoded.payload, Payload::Reset));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_can_frame_omits_routing_header() {
+    use irpc::transport::canfd::encode_frame;
+
+    let msg = Message {
+        header: Header {
+            source_id: 0x00F0,
+            target_id: 0x00F1,
+            msg_id: 7,
+        },
+        payload: Payload::Ack(7),
+    };
+
+    let mut buf = [0u8; 64];
+    let (_, len) = encode_frame(&msg, &mut buf).unwrap();
+
+    // Both 8-bit addresses above 0x7F take two varint bytes each in the header
+    assert_eq!(len + 4, msg.encoded_size());
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_can_id_priority_ordering() {
+    use irpc::transport::CanId;
+    use irpc::{LifecycleState, SetTargetPayload};
+
+    let reset = CanId::priority_of(&Payload::Reset);
+    let setpoint = CanId::priority_of(&Payload::SetTarget(SetTargetPayload {
+        target_angle: 0.0,
+        velocity_limit: 0.0,
+    }));
+    let status = CanId::priority_of(&Payload::JointStatus {
+        state: LifecycleState::Active,
+        error_code: 0,
+    });
+    let discover = CanId::priority_of(&Payload::Discover);
+
+    // Lower value wins arbitration
+    assert!(reset < setpoint);
+    assert!(setpoint < status);
+    assert!(status < discover);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_can_id_rejects_wide_addresses() {
+    use irpc::transport::canfd::encode_frame;
+    use irpc::transport::CanError;
+
+    let msg = Message {
+        header: Header {
+            source_id: 0x0100,
+            target_id: 0x0010,
+            msg_id: 1,
+        },
+        payload: Payload::Ack(1),
+    };
+
+    let mut buf = [0u8; 64];
+    assert!(matches!(encode_frame(&msg, &mut buf), Err(CanError::AddressOutOfRange)));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_canopen_cob_ids() {
+    use irpc::transport::canfd::{decode_canopen_frame, encode_canopen_frame};
+    use irpc::transport::CanError;
+    use irpc::SetTargetPayload;
+
+    let host = 0x0001;
+    let to_joint = |payload| Message {
+        header: Header { source_id: host, target_id: 0x0010, msg_id: 5 },
+        payload,
+    };
+    let from_joint = |payload| Message {
+        header: Header { source_id: 0x0010, target_id: host, msg_id: 5 },
+        payload,
+    };
+    let target = SetTargetPayload { target_angle: 1.0, velocity_limit: 2.0 };
+
+    // Node-specific COB-IDs only: RPDO1, RPDO2, SDO request / TPDO1, SDO response
+    let mut buf = [0u8; 64];
+    let cases = [
+        (to_joint(Payload::Activate), 0x210),
+        (to_joint(Payload::SetTarget(target)), 0x310),
+        (to_joint(Payload::Configure), 0x610),
+        (from_joint(telemetry_message().payload), 0x190),
+        (from_joint(Payload::Ack(5)), 0x590),
+    ];
+    for (message, cob_id) in cases {
+        let (raw, len) = encode_canopen_frame(&message, host, &mut buf).unwrap();
+        assert_eq!(raw, cob_id, "{:?}", message.payload);
+
+        let decoded = decode_canopen_frame(raw, host, &buf[..len]).unwrap();
+        assert_eq!(decoded.header.source_id, message.header.source_id);
+        assert_eq!(decoded.header.target_id, message.header.target_id);
+        assert_eq!(decoded.header.msg_id, 5);
+    }
+
+    // No broadcasts, no joint-to-joint traffic, node IDs up to 127
+    let mut discover = to_joint(Payload::Discover);
+    discover.header.target_id = 0x0000;
+    assert!(matches!(encode_canopen_frame(&discover, host, &mut buf), Err(CanError::NotMapped)));
+    let mut peer = from_joint(Payload::Ack(5));
+    peer.header.target_id = 0x0011;
+    assert!(matches!(encode_canopen_frame(&peer, host, &mut buf), Err(CanError::NotMapped)));
+    let mut wide = to_joint(Payload::Reset);
+    wide.header.target_id = 0x0080;
+    assert!(matches!(encode_canopen_frame(&wide, host, &mut buf), Err(CanError::AddressOutOfRange)));
+
+    // NMT, SYNC and heartbeats belong to the CANopen devices
+    for foreign in [0x000, 0x080, 0x710] {
+        assert!(matches!(
+            decode_canopen_frame(foreign, host, &[0x01, 0x00]),
+            Err(CanError::DeserializationError)
+        ));
+    }
+}
+
+#[cfg(feature = "joint_api")]
+fn telemetry_message() -> Message {
+    use irpc::{TelemetryStream, Warnings};
+
+    Message {
+        header: Header {
+            source_id: 0x0010,
+            target_id: 0x0001,
+            msg_id: u32::MAX,
+        },
+        payload: Payload::TelemetryStream(TelemetryStream {
+            timestamp_us: u64::MAX,
+            position: 1.0,
+            velocity: 1.0,
+            acceleration: 1.0,
+            current_d: 1.0,
+            current_q: 1.0,
+            voltage_d: 1.0,
+            voltage_q: 1.0,
+            torque_estimate: 1.0,
+            power: 1.0,
+            load_percent: 1.0,
+            foc_loop_time_us: u16::MAX,
+            temperature_c: 1.0,
+            warnings: Warnings(u16::MAX),
+            trajectory_active: true,
+            brake_engaged: false,
+        }),
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_classic_can_fragmentation_roundtrip() {
+    use irpc::transport::bxcan::fragment;
+    use irpc::transport::canfd::encode_body;
+    use irpc::transport::{FragmentInfo, Reassembler};
+
+    let msg = telemetry_message();
+    let mut body = [0u8; Message::max_size()];
+    let (raw_id, len) = encode_body(&msg, &mut body).unwrap();
+    assert!(len > 64, "larger than even a CAN-FD frame");
+
+    let frames: Vec<(u32, Vec<u8>)> = fragment(raw_id, 5, &body[..len])
+        .unwrap()
+        .map(|(id, data)| (id, data.to_vec()))
+        .collect();
+    assert_eq!(frames.len(), len.div_ceil(8));
+
+    for (i, (id, data)) in frames.iter().enumerate() {
+        let info = FragmentInfo::from_raw(*id);
+        assert_eq!(info.index as usize, i);
+        assert_eq!(info.transfer, 5);
+        assert_eq!(info.more, i + 1 < frames.len());
+        assert!(data.len() <= 8);
+    }
+
+    let mut reassembler = Reassembler::new();
+    let (last, rest) = frames.split_last().unwrap();
+    for (id, data) in rest {
+        assert!(reassembler.push(*id, data).unwrap().is_none());
+    }
+    let decoded = reassembler.push(last.0, &last.1).unwrap().unwrap();
+    assert_eq!(decoded.header.source_id, 0x0010);
+    assert_eq!(decoded.header.msg_id, u32::MAX);
+    assert!(matches!(decoded.payload, Payload::TelemetryStream(_)));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_fragmentation_sized_from_mtu() {
+    use irpc::transport::bxcan::fragment_with_mtu;
+    use irpc::transport::canfd::encode_body;
+    use irpc::transport::{CanError, Reassembler};
+    use irpc::CANFD_MAX_DATA_LEN;
+
+    // A CAN-FD node splits a payload larger than one frame into 64-byte fragments
+    let msg = telemetry_message();
+    let mut body = [0u8; Message::max_size()];
+    let (raw_id, len) = encode_body(&msg, &mut body).unwrap();
+    let frames: Vec<_> = fragment_with_mtu(raw_id, 2, &body[..len], CANFD_MAX_DATA_LEN)
+        .unwrap()
+        .collect();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].1.len(), CANFD_MAX_DATA_LEN);
+
+    let mut reassembler = Reassembler::new();
+    assert!(reassembler.push(frames[0].0, frames[0].1).unwrap().is_none());
+    let decoded = reassembler.push(frames[1].0, frames[1].1).unwrap().unwrap();
+    assert!(matches!(decoded.payload, Payload::TelemetryStream(_)));
+
+    for mtu in [0, CANFD_MAX_DATA_LEN + 1] {
+        assert!(matches!(
+            fragment_with_mtu(raw_id, 2, &body[..len], mtu),
+            Err(CanError::InvalidConfig)
+        ));
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_classic_can_single_frame_is_fd_compatible() {
+    use irpc::transport::bxcan::fragment;
+    use irpc::transport::canfd::{decode_frame, encode_body};
+
+    let msg = Message {
+        header: Header {
+            source_id: 0x0001,
+            target_id: 0x0010,
+            msg_id: 3,
+        },
+        payload: Payload::Activate,
+    };
+    let mut body = [0u8; Message::max_size()];
+    let (raw_id, len) = encode_body(&msg, &mut body).unwrap();
+
+    let frames: Vec<_> = fragment(raw_id, 6, &body[..len]).unwrap().collect();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].0, raw_id, "no fragment bits on a single frame");
+    assert!(matches!(decode_frame(frames[0].0, frames[0].1).unwrap().payload, Payload::Activate));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_classic_can_reassembly_errors_and_interleaving() {
+    use irpc::transport::bxcan::fragment;
+    use irpc::transport::canfd::encode_body;
+    use irpc::transport::{CanError, Reassembler};
+
+    let mut a = telemetry_message();
+    let mut b = telemetry_message();
+    a.header.source_id = 0x0010;
+    b.header.source_id = 0x0020;
+
+    let mut body_a = [0u8; Message::max_size()];
+    let mut body_b = [0u8; Message::max_size()];
+    let (id_a, len_a) = encode_body(&a, &mut body_a).unwrap();
+    let (id_b, len_b) = encode_body(&b, &mut body_b).unwrap();
+    let frames_a: Vec<_> = fragment(id_a, 1, &body_a[..len_a]).unwrap().collect();
+    let frames_b: Vec<_> = fragment(id_b, 1, &body_b[..len_b]).unwrap().collect();
+
+    // Two sources interleaved frame by frame
+    let mut reassembler = Reassembler::new();
+    let mut done = Vec::new();
+    for (fa, fb) in frames_a.iter().zip(frames_b.iter()) {
+        done.extend(reassembler.push(fa.0, fa.1).unwrap());
+        done.extend(reassembler.push(fb.0, fb.1).unwrap());
+    }
+    let sources: Vec<_> = done.iter().map(|m| m.header.source_id).collect();
+    assert_eq!(sources, vec![0x0010, 0x0020]);
+
+    // A skipped fragment aborts the transfer
+    reassembler.push(frames_a[0].0, frames_a[0].1).unwrap();
+    assert!(matches!(
+        reassembler.push(frames_a[2].0, frames_a[2].1),
+        Err(CanError::FragmentLost)
+    ));
+    assert!(matches!(
+        reassembler.push(frames_a[3].0, frames_a[3].1),
+        Err(CanError::FragmentLost)
+    ));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_rx_queue_burst_and_overflow() {
+    use irpc::transport::canfd::{decode_frame, encode_frame};
+    use irpc::transport::RxQueue;
+
+    static QUEUE: RxQueue<4> = RxQueue::new();
+    assert!(QUEUE.is_empty());
+    assert_eq!(QUEUE.capacity(), 4);
+
+    // A burst larger than the queue: the excess is dropped and counted
+    for msg_id in 0..6 {
+        let msg = Message {
+            header: Header { source_id: 0x0010, target_id: 0x0001, msg_id },
+            payload: Payload::Ack(msg_id),
+        };
+        let mut buf = [0u8; 64];
+        let (raw_id, len) = encode_frame(&msg, &mut buf).unwrap();
+        assert_eq!(QUEUE.push(raw_id, &buf[..len]), msg_id < 4);
+    }
+    let stats = QUEUE.stats();
+    assert_eq!(stats.rx_frames, 6);
+    assert_eq!(stats.rx_overflows, 2);
+    assert_eq!(stats.last_error, TransportErrorKind::RxOverflow);
+    assert_eq!(QUEUE.high_water(), 4);
+
+    // The main loop drains the whole burst in order
+    let mut ids = Vec::new();
+    while let Some(frame) = QUEUE.pop() {
+        let msg = decode_frame(frame.raw_id(), frame.data()).unwrap();
+        assert_eq!(msg.header.source_id, 0x0010);
+        ids.push(msg.header.msg_id);
+    }
+    assert_eq!(ids, [0, 1, 2, 3]);
+
+    // Wrap around the ring
+    for id in 10..13 {
+        assert!(QUEUE.push(id, &[id as u8]));
+    }
+    assert_eq!(QUEUE.pop().unwrap().raw_id(), 10);
+    assert!(QUEUE.push(13, &[13]));
+    assert!(QUEUE.push(14, &[14]));
+    let order: Vec<u32> = core::iter::from_fn(|| QUEUE.pop()).map(|f| f.raw_id()).collect();
+    assert_eq!(order, [11, 12, 13, 14]);
+
+    // Oversized data is rejected rather than truncated
+    assert!(!QUEUE.push(1, &[0u8; 65]));
+    assert_eq!(QUEUE.stats().deserialization_failures, 1);
+
+    QUEUE.reset_stats();
+    assert_eq!(QUEUE.stats().total_errors(), 0);
+    assert_eq!(QUEUE.high_water(), 0);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_rx_queue_concurrent_producer() {
+    use irpc::transport::RxQueue;
+    use std::sync::Arc;
+
+    // Every frame is either delivered once, in order, or counted as overflow
+    let queue = Arc::new(RxQueue::<8>::new());
+    let producer = std::thread::spawn({
+        let queue = queue.clone();
+        move || {
+            for id in 0..10_000u32 {
+                queue.push(id, &id.to_le_bytes());
+            }
+        }
+    });
+
+    let mut received = Vec::new();
+    while !producer.is_finished() || !queue.is_empty() {
+        if let Some(frame) = queue.pop() {
+            assert_eq!(frame.data(), frame.raw_id().to_le_bytes());
+            received.push(frame.raw_id());
+        }
+    }
+    producer.join().unwrap();
+
+    assert!(received.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(received.len() as u32 + queue.stats().rx_overflows, 10_000);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_loopback_roundtrip_and_backpressure() {
+    use irpc::transport::{LoopbackError, LoopbackTransport};
+
+    let mut transport = TransportLayer::new(LoopbackTransport::<2>::new());
+    let msg = |msg_id| Message {
+        header: Header { source_id: 0x0001, target_id: 0x0010, msg_id },
+        payload: Payload::Activate,
+    };
+
+    transport.send_message(&msg(1)).unwrap();
+    transport.send_message(&msg(2)).unwrap();
+    // A full queue rejects the frame instead of growing
+    assert!(transport.send_message(&msg(3)).is_err());
+    assert_eq!(transport.transport().len(), 2);
+
+    assert_eq!(transport.receive_message().unwrap().unwrap().header.msg_id, 1);
+    assert_eq!(transport.receive_message().unwrap().unwrap().header.msg_id, 2);
+    assert!(transport.receive_message().unwrap().is_none());
+
+    let mut raw = LoopbackTransport::<2>::new();
+    assert_eq!(
+        raw.send_blocking(&[0u8; Message::max_size() + 1]),
+        Err(LoopbackError::FrameTooLarge)
+    );
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_transport_layer_enforces_mtu() {
+    use irpc::transport::LoopbackTransport;
+    use irpc::TransportError;
+
+    let mut transport = TransportLayer::new(LoopbackTransport::<4>::with_mtu(16));
+    assert_eq!(transport.mtu(), 16);
+    assert_eq!(transport.stats().mtu, 16);
+
+    let small = Message {
+        header: Header { source_id: 0x0001, target_id: 0x0010, msg_id: 1 },
+        payload: Payload::Activate,
+    };
+    transport.send_message(&small).unwrap();
+
+    // Refused before it reaches the link, and counted
+    let large = telemetry_message();
+    assert!(large.encoded_size() > 16);
+    assert!(matches!(transport.send_message(&large), Err(TransportError::FrameTooLarge)));
+    assert_eq!(transport.stats().serialization_failures, 1);
+    assert_eq!(transport.transport().len(), 1);
+
+    // Resetting the counters keeps the MTU
+    transport.reset_stats();
+    assert_eq!(transport.stats().mtu, 16);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_transport_layer_buffer_size() {
+    use irpc::transport::LoopbackTransport;
+    use irpc::{TransportError, CANFD_MAX_DATA_LEN, DEFAULT_TRANSPORT_BUFFER_LEN};
+
+    // Buffers sized for one CAN-FD frame instead of any message
+    let mut transport = TransportLayer::<_, CANFD_MAX_DATA_LEN>::with_buffers(LoopbackTransport::<4>::new());
+    assert!(
+        core::mem::size_of_val(&transport)
+            < core::mem::size_of::<TransportLayer<LoopbackTransport<4>>>()
+    );
+    assert_eq!(transport.mtu(), CANFD_MAX_DATA_LEN);
+    assert_eq!(transport.stats().mtu as usize, CANFD_MAX_DATA_LEN);
+    assert_eq!(DEFAULT_TRANSPORT_BUFFER_LEN, Message::max_size());
+
+    let small = Message {
+        header: Header { source_id: 0x0001, target_id: 0x0010, msg_id: 1 },
+        payload: Payload::Activate,
+    };
+    transport.send_message(&small).unwrap();
+    assert_eq!(transport.receive_message().unwrap().unwrap().header.msg_id, 1);
+
+    // Larger messages fit neither way
+    let large = telemetry_message();
+    assert!(large.encoded_size() > CANFD_MAX_DATA_LEN);
+    assert!(matches!(transport.send_message(&large), Err(TransportError::FrameTooLarge)));
+    let mut frame = [0u8; Message::max_size()];
+    let len = large.serialize_into(&mut frame).unwrap();
+    transport.transport_mut().send_blocking(&frame[..len]).unwrap();
+    assert!(matches!(transport.receive_message(), Err(TransportError::FrameTooLarge)));
+    assert_eq!(transport.stats().serialization_failures, 1);
+}
+
+#[cfg(all(feature = "joint_api", not(feature = "no_alloc")))]
+#[test]
+fn test_channel_pair_drives_joint_across_threads() {
+    use irpc::transport::channel_pair;
+    use irpc::{Joint, LifecycleState};
+
+    let (arm_end, joint_end) = channel_pair();
+    let joint = std::thread::spawn(move || {
+        let mut bus = TransportLayer::new(joint_end);
+        let mut joint = Joint::new(0x0010);
+        let mut handled = 0;
+        while handled < 2 {
+            if joint.process_transport(&mut bus).unwrap() {
+                handled += 1;
+            } else {
+                std::thread::yield_now();
+            }
+        }
+        joint.state()
+    });
+
+    let mut arm = TransportLayer::new(arm_end);
+    for (msg_id, payload) in [(1, Payload::Configure), (2, Payload::Activate)] {
+        arm.send_message(&Message {
+            header: Header { source_id: 0x0001, target_id: 0x0010, msg_id },
+            payload,
+        })
+        .unwrap();
+    }
+
+    let mut acks = Vec::new();
+    while acks.len() < 2 {
+        match arm.receive_message().unwrap() {
+            Some(reply) => match reply.payload {
+                Payload::Ack(id) => acks.push(id),
+                other => panic!("unexpected reply {other:?}"),
+            },
+            None => std::thread::yield_now(),
+        }
+    }
+
+    assert_eq!(acks, [1, 2]);
+    assert_eq!(joint.join().unwrap(), LifecycleState::Active);
+    assert_eq!(arm.transport().pending(), 0);
+}
+
+/// Async wrapper polling a channel end, for driving `run_embassy` on the host
+/// Three-slot TX mailbox that behaves like the FDCAN's: when full, a frame
+/// pushes out the queued frame of lowest priority (highest ID), and
+/// otherwise waits while the bus sends the frame of highest priority
+#[cfg(feature = "joint_api")]
+#[derive(Default)]
+struct Mailbox {
+    queued: Vec<u32>,
+    sent: Vec<u32>,
+}
+
+#[cfg(feature = "joint_api")]
+impl irpc::transport::TxMailbox for Mailbox {
+    type Frame = u32;
+
+    async fn write(&mut self, frame: &u32) -> Option<u32> {
+        if self.queued.len() == 3 {
+            let lowest = *self.queued.iter().max().unwrap();
+            if lowest > *frame {
+                self.queued.retain(|&id| id != lowest);
+                self.queued.push(*frame);
+                return Some(lowest);
+            }
+            let highest = *self.queued.iter().min().unwrap();
+            self.queued.retain(|&id| id != highest);
+            self.sent.push(highest);
+        }
+        self.queued.push(*frame);
+        None
+    }
+}
+
+#[cfg(feature = "joint_api")]
+#[tokio::test]
+async fn test_frames_pushed_out_of_a_full_mailbox_are_queued_again() {
+    use irpc::transport::queue_frame;
+    use irpc::TransportStats;
+
+    let mut mailbox = Mailbox::default();
+    let mut stats = TransportStats::default();
+    for id in [0x300, 0x301, 0x302] {
+        queue_frame(&mut mailbox, id, &mut stats).await;
+    }
+    assert_eq!(stats.retransmissions, 0);
+
+    // 0x100 takes 0x302's slot; 0x302 then waits for 0x100 to go out
+    queue_frame(&mut mailbox, 0x100, &mut stats).await;
+    assert_eq!(stats.retransmissions, 1);
+    assert_eq!(mailbox.sent, vec![0x100]);
+    let mut queued = mailbox.queued.clone();
+    queued.sort();
+    assert_eq!(queued, vec![0x300, 0x301, 0x302]);
+}
+
+#[cfg(feature = "embassy")]
+struct PolledChannel(irpc::transport::ChannelEnd);
+
+#[cfg(feature = "embassy")]
+impl irpc::AsyncEmbeddedTransport for PolledChannel {
+    type Error = irpc::transport::LoopbackError;
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.0.send_blocking(data)
+    }
+
+    async fn receive(&mut self) -> Result<&[u8], Self::Error> {
+        while self.0.pending() == 0 {
+            embassy_time::Timer::after_micros(100).await;
+        }
+        Ok(self.0.receive_blocking()?.expect("frame pending"))
+    }
+}
+
+#[cfg(feature = "embassy")]
+#[test]
+fn test_run_embassy_answers_and_ticks() {
+    use embassy_futures::select::{select, Either};
+    use irpc::transport::channel_pair;
+    use irpc::{run_embassy, AsyncTransportLayer, CrashKind, CrashRecord, Joint, JointHooks, LifecycleState};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Counters {
+        updates: AtomicU32,
+        feeds: AtomicU32,
+    }
+
+    struct Hooks(Arc<Counters>);
+
+    impl JointHooks for Hooks {
+        const TELEMETRY_PERIOD_US: u64 = 5_000;
+
+        fn update(&mut self, _joint: &mut Joint, _now_us: u64) {
+            self.0.updates.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn telemetry(&mut self, joint: &Joint) -> Option<Payload> {
+            (joint.state() == LifecycleState::Inactive).then_some(Payload::JointStatus {
+                state: joint.state(),
+                error_code: 0,
+            })
+        }
+
+        fn feed_watchdog(&mut self) {
+            self.0.feeds.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn crash_record(&mut self) -> Option<CrashRecord> {
+            Some(CrashRecord::new(CrashKind::Panic, 0, 0, "joint", "main.rs:7: boom"))
+        }
+    }
+
+    let (arm_end, joint_end) = channel_pair();
+    let counters = Arc::new(Counters::default());
+    let runner = run_embassy(
+        Joint::new(0x0010),
+        AsyncTransportLayer::new(PolledChannel(joint_end)),
+        Hooks(counters.clone()),
+    );
+
+    let arm = async {
+        let mut arm = AsyncTransportLayer::new(PolledChannel(arm_end));
+        arm.send_message(&Message {
+            header: Header { source_id: 0x0001, target_id: 0x0010, msg_id: 7 },
+            payload: Payload::Configure,
+        })
+        .await
+        .unwrap();
+
+        // The boot announcement with the previous run's crash comes first
+        let boot = arm.receive_message().await.unwrap();
+        match boot.payload {
+            Payload::Boot(boot) => assert_eq!(boot.crash.unwrap().message(), "main.rs:7: boom"),
+            other => panic!("expected Boot, got {:?}", other),
+        }
+
+        // The Ack, then telemetry (sent only once configured) with its own IDs
+        let ack = arm.receive_message().await.unwrap();
+        assert!(matches!(ack.payload, Payload::Ack(7)));
+        let mut telemetry_ids = Vec::new();
+        while telemetry_ids.len() < 3 {
+            let msg = arm.receive_message().await.unwrap();
+            assert!(matches!(msg.payload, Payload::JointStatus { state: LifecycleState::Inactive, .. }));
+            assert_eq!(msg.header.target_id, 0x0001);
+            telemetry_ids.push(msg.header.msg_id);
+        }
+        telemetry_ids
+    };
+
+    match embassy_futures::block_on(select(runner, arm)) {
+        Either::Second(telemetry_ids) => assert_eq!(telemetry_ids, [1, 2, 3]),
+    }
+    // Three telemetry periods cover several 1 ms control ticks and a watchdog feed
+    assert!(counters.updates.load(Ordering::Relaxed) >= 5);
+    assert!(counters.feeds.load(Ordering::Relaxed) >= 1);
+}
+
+/// Register-level model of an MCP2518FD: mode changes, one TX queue and one RX FIFO
+#[cfg(feature = "mcp2518fd")]
+struct MockMcp2518 {
+    mem: Vec<u8>,
+    sent: Vec<(irpc::transport::FrameId, Vec<u8>)>,
+    pending_rx: std::collections::VecDeque<(irpc::transport::FrameId, Vec<u8>)>,
+}
+
+#[cfg(feature = "mcp2518fd")]
+mod mcp {
+    use irpc::transport::mcp2518fd::*;
+    use irpc::transport::FrameId;
+
+    pub const TXQ_UA: u32 = 0x000;
+    pub const FIFO1_UA: u32 = 0x200;
+
+    impl super::MockMcp2518 {
+        pub fn new() -> Self {
+            let mut chip = Self {
+                mem: vec![0u8; 0x1000],
+                sent: Vec::new(),
+                pending_rx: Default::default(),
+            };
+            chip.set(REG_C1TXQSTA, 1);
+            chip.set(REG_C1TXQUA, TXQ_UA);
+            chip.set(REG_C1FIFOUA1, FIFO1_UA);
+            chip
+        }
+
+        pub fn get(&self, reg: u16) -> u32 {
+            let a = reg as usize;
+            u32::from_le_bytes(self.mem[a..a + 4].try_into().unwrap())
+        }
+
+        pub fn set(&mut self, reg: u16, value: u32) {
+            let a = reg as usize;
+            self.mem[a..a + 4].copy_from_slice(&value.to_le_bytes());
+        }
+
+        pub fn inject(&mut self, raw_id: u32, data: &[u8]) {
+            self.inject_id(FrameId::Extended(raw_id), data);
+        }
+
+        pub fn inject_id(&mut self, id: FrameId, data: &[u8]) {
+            self.pending_rx.push_back((id, data.to_vec()));
+            if self.get(REG_C1FIFOSTA1) & 1 == 0 {
+                self.load_next_rx();
+            }
+        }
+
+        fn load_next_rx(&mut self) {
+            let Some((id, data)) = self.pending_rx.pop_front() else {
+                self.set(REG_C1FIFOSTA1, 0);
+                return;
+            };
+            let base = RAM_START + FIFO1_UA as u16;
+            let (id_word, ide) = match id {
+                FrameId::Standard(raw_id) => (raw_id as u32, 0),
+                FrameId::Extended(raw_id) => (((raw_id >> 18) & 0x7FF) | ((raw_id & 0x3_FFFF) << 11), 1 << 4),
+            };
+            let dlc = [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64]
+                .iter()
+                .position(|&l| l >= data.len())
+                .unwrap() as u32;
+            self.set(base, id_word);
+            self.set(base + 4, dlc | ide | (1 << 7));
+            let a = base as usize + 8;
+            self.mem[a..a + 64].fill(0);
+            self.mem[a..a + data.len()].copy_from_slice(&data);
+            self.set(REG_C1FIFOSTA1, 1);
+        }
+
+        pub fn write(&mut self, addr: u16, data: &[u8]) {
+            let a = addr as usize;
+            self.mem[a..a + data.len()].copy_from_slice(data);
+
+            if addr == REG_C1CON {
+                // Mode requests complete immediately
+                let con = self.get(REG_C1CON);
+                let reqop = (con >> 24) & 7;
+                self.set(REG_C1CON, (con & !(7 << 21)) | (reqop << 21));
+            } else if addr == REG_C1TXQCON + 1 && data[0] & 0x02 != 0 {
+                let base = RAM_START + TXQ_UA as u16;
+                let id_word = self.get(base);
+                let flags = self.get(base + 4);
+                let len = [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64][(flags & 0xF) as usize];
+                let id = if flags & (1 << 4) != 0 {
+                    FrameId::Extended(((id_word & 0x7FF) << 18) | ((id_word >> 11) & 0x3_FFFF))
+                } else {
+                    FrameId::Standard((id_word & 0x7FF) as u16)
+                };
+                let a = base as usize + 8;
+                self.sent.push((id, self.mem[a..a + len].to_vec()));
+            } else if addr == REG_C1FIFOCON1 + 1 && data[0] & 0x01 != 0 {
+                self.load_next_rx();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "mcp2518fd")]
+impl embedded_hal::spi::ErrorType for MockMcp2518 {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "mcp2518fd")]
+impl embedded_hal::spi::SpiDevice for MockMcp2518 {
+    fn transaction(
+        &mut self,
+        operations: &mut [embedded_hal::spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        use embedded_hal::spi::Operation;
+
+        let Some((Operation::Write(command), rest)) = operations.split_first_mut() else {
+            panic!("transaction must start with a command");
+        };
+        let command = u16::from_be_bytes([command[0], command[1]]);
+        let (instruction, addr) = (command >> 12, command & 0x0FFF);
+
+        if instruction == 0x0 {
+            // RESET: back to configuration mode
+            self.set(irpc::transport::mcp2518fd::REG_C1CON, 4 << 21);
+            return Ok(());
+        }
+        for op in rest {
+            match op {
+                Operation::Write(data) => {
+                    assert_eq!(instruction, 0x2);
+                    self.write(addr, data);
+                }
+                Operation::Read(buf) => {
+                    assert_eq!(instruction, 0x3);
+                    let a = addr as usize;
+                    buf.copy_from_slice(&self.mem[a..a + buf.len()]);
+                }
+                _ => panic!("unsupported SPI operation"),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "mcp2518fd")]
+#[test]
+fn test_mcp2518_bit_timing() {
+    use irpc::transport::mcp2518fd::BitTiming;
+
+    // 40 MHz, 1 Mbps: 40 quanta, 80% sample point
+    let nominal = BitTiming::compute(40_000_000, 1_000_000, 256).unwrap();
+    assert_eq!((nominal.prescaler, nominal.tseg1, nominal.tseg2), (1, 31, 8));
+    assert_eq!(nominal.register(), 0x001E_0707);
+
+    // 40 MHz, 5 Mbps: 8 quanta
+    let data = BitTiming::compute(40_000_000, 5_000_000, 32).unwrap();
+    assert_eq!(1 + data.tseg1 + data.tseg2, 8);
+
+    // Unreachable rates are rejected
+    assert!(BitTiming::compute(40_000_000, 3_000_000, 32).is_none());
+}
+
+#[cfg(feature = "mcp2518fd")]
+#[test]
+fn test_mcp2518_transport_roundtrip() {
+    use irpc::transport::canfd::encode_frame;
+    use irpc::transport::mcp2518fd::{REG_C1CON, REG_C1NBTCFG};
+    use irpc::transport::{Addressing, Mcp2518Config, Mcp2518Transport};
+    use irpc::{Joint, LifecycleState};
+
+    let mut transport =
+        Mcp2518Transport::new(MockMcp2518::new(), Mcp2518Config::for_joint(0x0010)).unwrap();
+
+    // Outgoing: routing header lands in the arbitration ID
+    let ack = Message {
+        header: Header { source_id: 0x0010, target_id: 0x0001, msg_id: 9 },
+        payload: Payload::Ack(9),
+    };
+    transport.send_message(&ack).unwrap();
+
+    // Incoming: a Configure for the joint, handled through TransportLayer
+    let configure = Message {
+        header: Header { source_id: 0x0001, target_id: 0x0010, msg_id: 10 },
+        payload: Payload::Configure,
+    };
+    let mut frame = [0u8; 64];
+    let (raw_id, len) = encode_frame(&configure, &mut frame).unwrap();
+
+    let mut chip = transport.release();
+    assert_eq!(chip.get(REG_C1NBTCFG), 0x001E_0707);
+    assert_eq!((chip.get(REG_C1CON) >> 21) & 7, 0, "normal FD mode");
+    chip.inject(raw_id, &frame[..len]);
+
+    let mut layer = TransportLayer::new(
+        Mcp2518Transport::new(chip, Mcp2518Config::for_joint(0x0010)).unwrap(),
+    );
+    let mut joint = Joint::new(0x0010);
+    assert!(joint.process_transport(&mut layer).unwrap());
+    assert!(!joint.process_transport(&mut layer).unwrap(), "FIFO drained");
+    assert_eq!(joint.state(), LifecycleState::Inactive);
+
+    let chip = layer.into_inner().release();
+    assert_eq!(chip.sent.len(), 2);
+    let first = Addressing::Native.decode_frame(chip.sent[0].0, &chip.sent[0].1).unwrap();
+    assert!(matches!(first.payload, Payload::Ack(9)));
+    let reply = Addressing::Native.decode_frame(chip.sent[1].0, &chip.sent[1].1).unwrap();
+    assert_eq!(reply.header.target_id, 0x0001);
+    assert!(matches!(reply.payload, Payload::Ack(10)));
+}
+
+#[cfg(feature = "mcp2518fd")]
+#[test]
+fn test_mcp2518_canopen_addressing() {
+    use irpc::transport::{Addressing, FrameId, Mcp2518Config, Mcp2518Transport};
+    use irpc::{Joint, LifecycleState};
+
+    let addressing = Addressing::CanOpen { host_id: 0x0001 };
+    let config = Mcp2518Config { addressing, ..Mcp2518Config::for_joint(0x0010) };
+    let mut chip = MockMcp2518::new();
+
+    // Extended-ID traffic is not ours in CANopen mode
+    let activate = Message {
+        header: Header { source_id: 0x0001, target_id: 0x0010, msg_id: 1 },
+        payload: Payload::Activate,
+    };
+    let mut frame = [0u8; 64];
+    let (raw_id, len) = irpc::transport::canfd::encode_frame(&activate, &mut frame).unwrap();
+    chip.inject(raw_id, &frame[..len]);
+
+    let configure = Message {
+        header: Header { source_id: 0x0001, target_id: 0x0010, msg_id: 2 },
+        payload: Payload::Configure,
+    };
+    let (id, len) = addressing.encode_frame(&configure, &mut frame).unwrap();
+    assert_eq!(id, FrameId::Standard(0x610));
+    chip.inject_id(id, &frame[..len]);
+
+    let mut layer = TransportLayer::new(Mcp2518Transport::new(chip, config).unwrap());
+    let mut joint = Joint::new(0x0010);
+    assert!(joint.process_transport(&mut layer).is_err());
+    assert!(joint.process_transport(&mut layer).unwrap());
+    assert_eq!(joint.state(), LifecycleState::Inactive);
+
+    // The Ack goes out as an SDO response with a standard ID
+    let chip = layer.into_inner().release();
+    assert_eq!(chip.sent.len(), 1);
+    assert_eq!(chip.sent[0].0, FrameId::Standard(0x590));
+    let reply = addressing.decode_frame(chip.sent[0].0, &chip.sent[0].1).unwrap();
+    assert_eq!(reply.header.target_id, 0x0001);
+    assert!(matches!(reply.payload, Payload::Ack(2)));
+}
@@ -0,0 +1,69 @@
+//! Tests for unit-safe angles and velocities
+
+#[test]
+fn test_unit_conversions() {
+    use irpc::units::{DegPerSec, Degrees, RadPerSec, Radians};
+    use std::f32::consts::PI;
+
+    assert!((Degrees::from(Radians(PI)).value() - 180.0).abs() < 1e-4);
+    assert!((Radians::from(Degrees(-90.0)).value() + PI / 2.0).abs() < 1e-6);
+    assert!((DegPerSec::from(RadPerSec(2.0 * PI)).value() - 360.0).abs() < 1e-3);
+    assert!((RadPerSec::from(DegPerSec(180.0)).value() - PI).abs() < 1e-6);
+
+    assert_eq!(Degrees(30.0) + Degrees(15.0) - Degrees(50.0), Degrees(-5.0));
+    assert_eq!((-Degrees(5.0)).abs(), Degrees(5.0));
+    assert!(DegPerSec(10.0) < DegPerSec(20.0));
+    assert_eq!(Degrees(12.5).to_string(), "12.5 deg");
+    assert_eq!(RadPerSec(1.0).to_string(), "1 rad/s");
+}
+
+#[test]
+fn test_payload_unit_accessors() {
+    use irpc::units::{DegPerSec, Degrees, RadPerSec, Radians};
+    use irpc::{CalibrationPhase, CalibrationRequest, CalibrationStatus, EncoderTelemetry, SetTargetPayload};
+    use std::f32::consts::PI;
+
+    let target = SetTargetPayload::new(Radians(PI / 2.0), DegPerSec(45.0));
+    assert!((target.target_angle - 90.0).abs() < 1e-4);
+    assert_eq!(target.velocity_limit(), DegPerSec(45.0));
+    assert_eq!(SetTargetPayload::new(Degrees(10.0), RadPerSec(0.0)).target(), Degrees(10.0));
+
+    // Calibration limits are radians on the wire
+    let request = CalibrationRequest::default().with_motion_limits(DegPerSec(180.0), Degrees(90.0));
+    assert!((request.max_velocity - PI).abs() < 1e-6);
+    assert!((request.max_position_range().value() - PI / 2.0).abs() < 1e-6);
+
+    let status = CalibrationStatus {
+        phase: CalibrationPhase::FrictionTest,
+        progress: 0.5,
+        time_remaining: 3.0,
+        current_position: PI,
+        current_velocity: 1.0,
+        current_iq: 2.0,
+    };
+    assert!((Degrees::from(status.position()).value() - 180.0).abs() < 1e-4);
+    assert_eq!(status.velocity(), RadPerSec(1.0));
+
+    let encoder = EncoderTelemetry { position: 12.0, velocity: -3.0 };
+    assert_eq!((encoder.position(), encoder.velocity()), (Degrees(12.0), DegPerSec(-3.0)));
+}
+
+#[cfg(all(feature = "arm_api", feature = "joint_api"))]
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn test_move_to_converts_radians() {
+    use irpc::bus::sim::SimBus;
+    use irpc::units::{RadPerSec, Radians};
+    use irpc::{CommunicationManager, JointProxy};
+    use std::f32::consts::FRAC_PI_4;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let bus = Arc::new(SimBus::with_joints([0x0010]));
+    let joint = JointProxy::new(0x0010, CommunicationManager::with_adapter(bus.clone()));
+    joint.configure().await.unwrap();
+    joint.activate().await.unwrap();
+
+    joint.move_to(Radians(FRAC_PI_4), RadPerSec(1.0)).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!((bus.true_position(0x0010).unwrap() - 45.0).abs() < 0.5);
+}
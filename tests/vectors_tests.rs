@@ -0,0 +1,57 @@
+//! Tests for the protocol conformance test vectors
+
+#![cfg(feature = "arm_api")]
+
+use irpc::vectors::{generate, load_vectors, write_vectors, VectorError, INDEX_FILE, VECTOR_MSG_ID};
+use irpc::Payload;
+
+#[test]
+fn test_every_variant_has_a_canonical_vector() {
+    let vectors = generate();
+    let tags: Vec<u8> = vectors.iter().map(|vector| vector.tag).collect();
+    let known: Vec<u8> = (0..=u8::MAX).filter(|&tag| Payload::tag_name(tag).is_some()).collect();
+    assert_eq!(tags, known);
+
+    for vector in &vectors {
+        let message = vector.check().unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!(message.header.msg_id, VECTOR_MSG_ID);
+    }
+    // Fields take the pattern where they can
+    let Payload::SetTarget(target) = vectors[0].decode().unwrap().payload else { unreachable!() };
+    assert_eq!(target.target_angle.to_le_bytes(), [1; 4]);
+    assert_eq!(generate(), vectors);
+}
+
+#[test]
+fn test_vectors_round_trip_through_a_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let written = write_vectors(dir.path()).unwrap();
+    assert_eq!(load_vectors(dir.path()).unwrap(), written);
+    let index = std::fs::read_to_string(dir.path().join(INDEX_FILE)).unwrap();
+    assert!(index.contains(&format!("# schema {:#010x}", irpc::SCHEMA_HASH)));
+
+    // A vector file that disagrees with the index
+    let ack = written.iter().find(|vector| vector.name == "Ack").unwrap();
+    std::fs::write(dir.path().join(ack.file_name()), [0x01, 0x10]).unwrap();
+    assert!(matches!(load_vectors(dir.path()), Err(VectorError::Mismatch { name, .. }) if name == "Ack"));
+
+    // One that decodes as another variant
+    let mut swapped = ack.clone();
+    swapped.tag = ack.tag + 1;
+    assert!(matches!(swapped.check(), Err(VectorError::Mismatch { .. })));
+
+    std::fs::write(dir.path().join(INDEX_FILE), "# header\n3 Deactivate 5 zz\n").unwrap();
+    assert!(matches!(load_vectors(dir.path()), Err(VectorError::Index { line: 2, .. })));
+}
+
+#[test]
+fn test_released_vectors_still_round_trip() {
+    // Generated by `examples/gen_test_vectors.rs`; regenerate only when a
+    // release deliberately changes the wire format
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/vectors");
+    let vectors = load_vectors(dir).unwrap();
+    assert!(!vectors.is_empty());
+    for vector in vectors {
+        vector.check().unwrap_or_else(|e| panic!("{}", e));
+    }
+}
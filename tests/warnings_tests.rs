@@ -0,0 +1,129 @@
+//! Tests for the telemetry warning flags
+
+#[test]
+fn test_warnings_decode_and_display() {
+    use irpc::Warnings;
+
+    let warnings = Warnings::OVERTEMPERATURE | Warnings::STALL_WARNING;
+    assert!(warnings.is_overtemperature() && warnings.is_stall_warning());
+    assert!(!warnings.is_undervoltage() && !warnings.is_overcurrent());
+    assert!(!warnings.is_encoder_error() && !warnings.is_comm_degraded());
+    assert!(warnings.intersects(Warnings::STALL_WARNING | Warnings::UNDERVOLTAGE));
+    assert!(!warnings.contains(Warnings::STALL_WARNING | Warnings::UNDERVOLTAGE));
+    assert_eq!(warnings.names().collect::<Vec<_>>(), ["OVERTEMPERATURE", "STALL_WARNING"]);
+    assert_eq!(warnings.to_string(), "OVERTEMPERATURE|STALL_WARNING");
+    assert_eq!(Warnings::NONE.to_string(), "NONE");
+    // Bits from a newer joint are kept
+    assert_eq!(Warnings(0x8010).to_string(), "COMM_DEGRADED|0x8000");
+    assert_eq!(Warnings(0x8000).difference(Warnings::COMM_DEGRADED).bits(), 0x8000);
+
+    // Encoded like the bare `u16` it replaces
+    let mut flags = [0u8; 4];
+    let mut bits = [0u8; 4];
+    assert_eq!(
+        postcard::to_slice(&Warnings(0x1234), &mut flags).unwrap(),
+        postcard::to_slice(&0x1234u16, &mut bits).unwrap()
+    );
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_joint_raises_warnings() {
+    use irpc::encoder::{Encoder, EncoderError, EncoderFlags};
+    use irpc::motor::{DriverFaults, MotorDriver, MotorError};
+    use irpc::{
+        Capabilities, CollisionReaction, ConfigureAdaptivePayload, Header, Joint, LifecycleState, Message,
+        MultiTurnPosition, Payload, StallStatus, Warnings,
+    };
+
+    struct WeakMagnet;
+    impl Encoder for WeakMagnet {
+        fn read_position(&mut self) -> Result<MultiTurnPosition, EncoderError> {
+            Ok(MultiTurnPosition::from_degrees(10.0))
+        }
+
+        fn resolution(&self) -> u32 {
+            1 << 14
+        }
+
+        fn error_flags(&mut self) -> EncoderFlags {
+            EncoderFlags::FIELD_LOW
+        }
+    }
+
+    struct BrownOut;
+    impl MotorDriver for BrownOut {
+        fn enable(&mut self) -> Result<(), MotorError> {
+            Ok(())
+        }
+
+        fn disable(&mut self) {}
+
+        fn set_voltage(&mut self, _volts: f32) -> Result<(), MotorError> {
+            Ok(())
+        }
+
+        fn set_current(&mut self, _amps: f32) -> Result<(), MotorError> {
+            Ok(())
+        }
+
+        fn faults(&mut self) -> DriverFaults {
+            DriverFaults::UNDERVOLTAGE
+        }
+    }
+
+    let mut joint = Joint::new(0x0010);
+    joint.set_capabilities(joint.capabilities() | Capabilities::ADAPTIVE_CONTROL);
+    let config = ConfigureAdaptivePayload {
+        coolstep_enable: false,
+        coolstep_min_current: 0.3,
+        coolstep_threshold: 50.0,
+        dcstep_enable: false,
+        dcstep_threshold: 80.0,
+        dcstep_max_derating: 0.5,
+        stallguard_enable: true,
+        stallguard_current_threshold: 1.5,
+        stallguard_velocity_threshold: 5.0,
+        collision_reaction: CollisionReaction::None,
+        collision_current_threshold: 2.0,
+        collision_time_ms: 20,
+        collision_backoff_deg: 5.0,
+    };
+    let header = Header { source_id: 0x0001, target_id: 0x0010, msg_id: 1 };
+    let reply = joint.handle_message(&Message { header, payload: Payload::ConfigureAdaptive(config) }).unwrap();
+    assert!(matches!(reply.payload, Payload::Ack(1)));
+    assert_eq!(joint.warnings(), Warnings::NONE);
+
+    // Without a collision reaction the load is only flagged
+    joint.update_load(StallStatus::Warning, 2.5, 0);
+    assert_eq!(joint.warnings(), Warnings::STALL_WARNING | Warnings::OVERCURRENT);
+    joint.update_load(StallStatus::Normal, 0.5, 1_000);
+    assert_eq!(joint.warnings(), Warnings::NONE);
+
+    joint.sample_encoder(&mut WeakMagnet, 2_000).unwrap();
+    assert_eq!(joint.warnings(), Warnings::ENCODER_ERROR);
+
+    joint.sync_driver(&mut BrownOut);
+    assert_eq!(joint.state(), LifecycleState::Error);
+    assert_eq!(joint.warnings(), Warnings::ENCODER_ERROR | Warnings::UNDERVOLTAGE);
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_lost_heartbeat_degrades_comm() {
+    use irpc::{Header, Joint, Message, Payload, Warnings};
+
+    let mut joint = Joint::new(0x0010);
+    joint.require_safety_enable(true);
+    // Not before the first heartbeat
+    joint.poll(1_000_000);
+    assert_eq!(joint.warnings(), Warnings::NONE);
+
+    let header = Header { source_id: 0x0001, target_id: 0x0010, msg_id: 1 };
+    let enable = Payload::SafetyEnable { token: 7, ttl_ms: 100 };
+    assert!(matches!(joint.handle_message(&Message { header, payload: enable }).unwrap().payload, Payload::Ack(1)));
+    joint.poll(1_099_000);
+    assert_eq!(joint.warnings(), Warnings::NONE);
+    joint.poll(1_100_000);
+    assert!(joint.warnings().is_comm_degraded());
+}
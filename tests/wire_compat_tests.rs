@@ -0,0 +1,72 @@
+//! Tests for wire compatibility across protocol versions
+
+use irpc::{LifecycleState, Message, Payload};
+
+/// Frames as v1.0 firmware and hosts put them on the bus
+const V1_0_FIXTURES: [(&str, &[u8]); 8] = [
+    // Arm → joint 0x0010, msg 7: SetTarget 45° at 30°/s
+    ("SetTarget", &[0x01, 0x10, 0x07, 0x00, 0x00, 0x00, 0x34, 0x42, 0x00, 0x00, 0xF0, 0x41]),
+    ("Configure", &[0x01, 0x10, 0x08, 0x01]),
+    ("Activate", &[0x01, 0x10, 0x09, 0x02]),
+    // Joint 0x0010 → arm, msg 300: Encoder at 12.5° and -3°/s
+    ("Encoder", &[0x10, 0x01, 0xAC, 0x02, 0x06, 0x00, 0x00, 0x48, 0x41, 0x00, 0x00, 0x40, 0xC0]),
+    ("JointStatus", &[0x10, 0x01, 0x0A, 0x07, 0x02, 0x00]),
+    ("Ack", &[0x10, 0x01, 0x07, 0x12, 0x07]),
+    ("Nack", &[0x10, 0x01, 0x08, 0x13, 0x08, 0x04]),
+    // Arm → broadcast
+    ("ArmReady", &[0x01, 0x00, 0x01, 0x14]),
+];
+
+#[test]
+fn test_every_variant_encodes_its_discriminant() {
+    // Every variant decodes from its tag followed by zeros
+    let mut bytes = [0u8; Message::max_size()];
+    let mut encoded = [0u8; Message::max_size()];
+    for tag in 0..0x80 {
+        bytes[0] = tag;
+        let Ok(payload) = postcard::from_bytes::<Payload>(&bytes) else { continue };
+        let len = postcard::to_slice(&payload, &mut encoded).unwrap().len();
+        assert!(len > 0);
+        assert_eq!(encoded[0], payload.tag(), "{} is declared out of order", payload.name());
+        assert_eq!(payload.tag(), tag);
+    }
+}
+
+#[test]
+fn test_v1_0_frames_decode() {
+    let mut encoded = [0u8; Message::max_size()];
+    for (name, frame) in V1_0_FIXTURES {
+        let message = Message::deserialize(frame).unwrap_or_else(|e| panic!("{}: {:?}", name, e));
+        assert_eq!(message.payload.name(), name);
+        // A newer host answers in kind
+        let len = message.serialize_into(&mut encoded).unwrap();
+        assert_eq!(&encoded[..len], frame, "{}", name);
+    }
+
+    let decode = |frame: &[u8]| Message::deserialize(frame).unwrap();
+    let Payload::SetTarget(target) = decode(V1_0_FIXTURES[0].1).payload else { unreachable!() };
+    assert_eq!((target.target_angle, target.velocity_limit), (45.0, 30.0));
+    let Message { header, payload: Payload::Encoder(encoder) } = decode(V1_0_FIXTURES[3].1) else { unreachable!() };
+    assert_eq!((header.source_id, header.target_id, header.msg_id), (0x0010, 0x0001, 300));
+    assert_eq!((encoder.position, encoder.velocity), (12.5, -3.0));
+    assert!(matches!(
+        decode(V1_0_FIXTURES[4].1).payload,
+        Payload::JointStatus { state: LifecycleState::Active, error_code: 0 }
+    ));
+    assert!(matches!(decode(V1_0_FIXTURES[6].1).payload, Payload::Nack { id: 8, error: 4 }));
+}
+
+#[cfg(feature = "joint_api")]
+#[test]
+fn test_current_joint_obeys_v1_0_commands() {
+    use irpc::{Joint, MultiTurnPosition};
+
+    let mut joint = Joint::new(0x0010);
+    joint.update_encoder(MultiTurnPosition::from_degrees(0.0));
+    for (name, frame) in [V1_0_FIXTURES[1], V1_0_FIXTURES[2], V1_0_FIXTURES[0]] {
+        let reply = joint.handle_message(&Message::deserialize(frame).unwrap()).unwrap();
+        assert!(matches!(reply.payload, Payload::Ack(_)), "{}: {:?}", name, reply.payload);
+    }
+    assert_eq!(joint.state(), LifecycleState::Active);
+    assert_eq!(joint.target().unwrap().target_angle, 45.0);
+}
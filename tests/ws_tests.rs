@@ -0,0 +1,68 @@
+//! Tests for the WebSocket framing (the browser socket itself needs a JS host)
+
+#[cfg(feature = "wasm")]
+use irpc::bus::ws::{encode_frame, FrameDecoder};
+#[cfg(feature = "wasm")]
+use irpc::protocol::{Header, Message, Payload, ProtocolError};
+
+#[cfg(feature = "wasm")]
+fn message(msg_id: u32, payload: Payload) -> Message {
+    Message { header: Header { source_id: 0x0010, target_id: 0x0001, msg_id }, payload }
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn test_ws_frames_split_and_merged() {
+    let first = encode_frame(&message(1, Payload::Ack(1))).unwrap();
+    let second = encode_frame(&message(2, Payload::Nack { id: 2, error: 4 })).unwrap();
+    assert_eq!(first.iter().position(|&b| b == 0), Some(first.len() - 1));
+
+    // One frame arriving in two WebSocket messages
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&first[..3]);
+    assert!(decoder.next_message().is_none());
+    assert_eq!(decoder.pending(), 3);
+    decoder.push(&first[3..]);
+    let decoded = decoder.next_message().unwrap().unwrap();
+    assert!(matches!(decoded.payload, Payload::Ack(1)));
+    assert_eq!(decoder.pending(), 0);
+
+    // Two frames sharing one WebSocket message
+    let mut both = first.clone();
+    both.extend_from_slice(&second);
+    decoder.push(&both);
+    assert_eq!(decoder.next_message().unwrap().unwrap().header.msg_id, 1);
+    let decoded = decoder.next_message().unwrap().unwrap();
+    assert!(matches!(decoded.payload, Payload::Nack { id: 2, error: 4 }));
+    assert!(decoder.next_message().is_none());
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn test_ws_corrupt_frame_is_skipped() {
+    let mut garbage = [0u8; 16];
+    let len = irpc::framing::encode_frame(&[0xFF, 0xFF], &mut garbage).unwrap();
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&garbage[..len]);
+    decoder.push(&encode_frame(&message(3, Payload::Reset)).unwrap());
+
+    let error = decoder.next_message().unwrap().unwrap_err();
+    assert!(matches!(error, ProtocolError::DeserializationError(_)));
+    assert!(!error.to_string().is_empty());
+    assert!(matches!(decoder.next_message().unwrap().unwrap().payload, Payload::Reset));
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn test_ws_oversize_frame_is_skipped() {
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&[0xFF; 512]);
+    assert!(matches!(decoder.next_message(), Some(Err(ProtocolError::InvalidMessage))));
+    assert_eq!(decoder.pending(), 0);
+    assert!(decoder.next_message().is_none());
+
+    // Decoding picks up again after the oversize frame's delimiter
+    decoder.push(&[0xFF, 0x00]);
+    decoder.push(&encode_frame(&message(4, Payload::Reset)).unwrap());
+    assert_eq!(decoder.next_message().unwrap().unwrap().header.msg_id, 4);
+}